@@ -447,6 +447,9 @@ impl fmt::Display for Enum<'_> {
                     pdb::Variant::I16(v) => format!("{}", v),
                     pdb::Variant::I32(v) => format!("{}", v),
                     pdb::Variant::I64(v) => format!("{}", v),
+                    pdb::Variant::U128(v) => format!("0x{:032x}", v),
+                    pdb::Variant::I128(v) => format!("{}", v),
+                    other => other.to_string(),
                 }
             )?;
         }
@@ -587,7 +590,7 @@ impl<'p> Data<'p> {
 
 fn write_class(filename: &str, class_name: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = pdb::PDB::open(file)?;
+    let pdb = pdb::PDB::open(file)?;
 
     let type_information = pdb.type_information()?;
     let mut type_finder = type_information.finder();
@@ -10,7 +10,7 @@ fn print_usage(program: &str, opts: Options) {
 
 fn dump_framedata(filename: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = pdb::PDB::open(file)?;
+    let pdb = pdb::PDB::open(file)?;
 
     let string_table = pdb.string_table()?;
 
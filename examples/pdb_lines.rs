@@ -7,7 +7,7 @@ use pdb::{FallibleIterator, SymbolData, PDB};
 
 fn dump_pdb(filename: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = PDB::open(file)?;
+    let pdb = PDB::open(file)?;
 
     let address_map = pdb.address_map()?;
     let string_table = pdb.string_table()?;
@@ -39,7 +39,7 @@ fn dump_pdb(filename: &str) -> pdb::Result<()> {
                 while let Some(line_info) = lines.next()? {
                     let rva = line_info.offset.to_rva(&address_map).expect("invalid rva");
                     let file_info = program.get_file_info(line_info.file_index)?;
-                    let file_name = file_info.name.to_string_lossy(&string_table)?;
+                    let file_name = file_info.resolve_name(&string_table)?.to_string();
                     println!("  {} {}:{}", rva, file_name, line_info.line_start);
                 }
             }
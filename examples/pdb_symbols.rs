@@ -49,7 +49,7 @@ fn walk_symbols(mut symbols: pdb::SymbolIter<'_>) -> pdb::Result<()> {
 
 fn dump_pdb(filename: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = pdb::PDB::open(file)?;
+    let pdb = pdb::PDB::open(file)?;
     let symbol_table = pdb.global_symbols()?;
     println!("Global symbols:");
     walk_symbols(symbol_table.iter())?;
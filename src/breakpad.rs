@@ -0,0 +1,509 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Conversion of frame data and unwind information into Breakpad `STACK` records.
+//!
+//! Symbol files with only functions and lines do not allow minidump processors to walk the stack
+//! reliably. `STACK WIN` records describe x86 frames from the frame table, and `STACK CFI` records
+//! describe x64 frames from the unwind information of the image.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::common::*;
+use crate::framedata::{FrameData, FrameType};
+use crate::omap::AddressMap;
+use crate::strings::StringTable;
+use crate::unwind::*;
+use crate::FallibleIterator;
+
+/// The maximum depth of chained unwind information followed for a single function.
+const MAX_CHAIN_DEPTH: usize = 32;
+
+/// Names of the x64 integer registers in the order of their encoding.
+const REGISTERS: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+/// A Breakpad `STACK WIN` record describing the frame of an x86 function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackWinRecord {
+    /// The kind of frame data, which is either [`FrameType::FPO`] or [`FrameType::FrameData`] for
+    /// most functions.
+    pub ty: FrameType,
+    /// The address of the code covered by this record.
+    pub rva: Rva,
+    /// Size of the code covered by this record in bytes.
+    pub code_size: u32,
+    /// Size of the prologue in bytes.
+    pub prolog_size: u16,
+    /// Size of parameters pushed on the stack in bytes.
+    pub params_size: u32,
+    /// Size of saved registers pushed on the stack in bytes.
+    pub saved_regs_size: u16,
+    /// Size of local variables pushed on the stack in bytes.
+    pub locals_size: u32,
+    /// The maximum number of bytes pushed on the stack, if known.
+    pub max_stack_size: Option<u32>,
+    /// Whether the function uses the EBP register.
+    pub uses_base_pointer: bool,
+    /// The program string reconstructing register values for this frame.
+    pub program: Option<String>,
+}
+
+impl StackWinRecord {
+    /// Creates a record from an entry of the [`FrameTable`](crate::FrameTable).
+    ///
+    /// Program strings are resolved in `strings`. If it is `None`, records are emitted without
+    /// their program, which makes minidump processors fall back to the frame sizes.
+    ///
+    /// Returns `None` for frames of unknown type and frames whose code is not present in the
+    /// image.
+    pub fn new(
+        frame: &FrameData,
+        address_map: &AddressMap<'_>,
+        strings: Option<&StringTable<'_>>,
+    ) -> Result<Option<Self>> {
+        if frame.ty == FrameType::Unknown {
+            return Ok(None);
+        }
+
+        let rva = match frame.code_start.to_rva(address_map) {
+            Some(rva) => rva,
+            None => return Ok(None),
+        };
+
+        let program = match (frame.program, strings) {
+            (Some(program), Some(strings)) => Some(program.to_string_lossy(strings)?.into_owned()),
+            _ => None,
+        };
+
+        Ok(Some(Self {
+            ty: frame.ty,
+            rva,
+            code_size: frame.code_size,
+            prolog_size: frame.prolog_size,
+            params_size: frame.params_size,
+            saved_regs_size: frame.saved_regs_size,
+            locals_size: frame.locals_size,
+            max_stack_size: frame.max_stack_size,
+            uses_base_pointer: frame.uses_base_pointer,
+            program,
+        }))
+    }
+}
+
+impl fmt::Display for StackWinRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The epilogue size is always zero, since it is not recorded in frame data.
+        write!(
+            f,
+            "STACK WIN {:x} {:x} {:x} {:x} 0 {:x} {:x} {:x} {:x} ",
+            self.ty as u8,
+            self.rva.0,
+            self.code_size,
+            self.prolog_size,
+            self.params_size,
+            self.saved_regs_size,
+            self.locals_size,
+            self.max_stack_size.unwrap_or(0),
+        )?;
+
+        match self.program {
+            Some(ref program) => write!(f, "1 {}", program),
+            None => write!(f, "0 {}", u8::from(self.uses_base_pointer)),
+        }
+    }
+}
+
+/// A row of a [`StackCfiRecord`], updating the rules from its address onwards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackCfiRow {
+    /// The address of the first instruction where the rules apply.
+    pub rva: Rva,
+    /// The rules that change at this address, such as `.cfa: $rsp 16 + $rbx: .cfa 16 - ^`.
+    pub rules: String,
+}
+
+/// A Breakpad `STACK CFI` record describing the frame of an x64 function.
+///
+/// The record consists of the initial rules at the start of the function, followed by a row for
+/// every instruction of the prologue that changes the frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackCfiRecord {
+    /// The address of the function.
+    pub rva: Rva,
+    /// The size of the function in bytes.
+    pub size: u32,
+    /// The rules at the start of the function.
+    pub init: String,
+    /// Rows changing the rules within the prologue, in ascending order of address.
+    pub rows: Vec<StackCfiRow>,
+}
+
+/// The state of the frame while executing the prologue of an x64 function.
+#[derive(Debug)]
+struct FrameState {
+    /// Offset of the CFA from `rsp`.
+    stack_size: u32,
+    /// The frame register and offset of the CFA from it, once established.
+    frame: Option<(u8, u32)>,
+    /// Saved registers and their offsets below the CFA.
+    ///
+    /// Offsets are negative for registers saved in the home space of the caller's frame.
+    saved: Vec<(u8, i64)>,
+}
+
+impl FrameState {
+    fn cfa_rule(&self) -> String {
+        match self.frame {
+            Some((register, offset)) => {
+                format!(".cfa: ${} {} +", REGISTERS[usize::from(register)], offset)
+            }
+            None => format!(".cfa: $rsp {} +", self.stack_size),
+        }
+    }
+
+    /// Returns the complete rules for the current state.
+    fn init_rules(&self) -> String {
+        let mut rules = self.cfa_rule();
+        rules.push_str(" .ra: .cfa 8 - ^");
+        for &(register, offset) in &self.saved {
+            let _ = write!(rules, " {}", register_rule(register, offset));
+        }
+        rules
+    }
+
+    /// Applies an unwind operation and returns the rules it changes, if any.
+    ///
+    /// Returns `None` if the operation cannot be expressed as CFI rules.
+    fn apply(&mut self, info: &UnwindInfo<'_>, operation: UnwindOperation) -> Option<String> {
+        let mut rules = String::new();
+
+        match operation {
+            UnwindOperation::PushNonVolatile(register) => {
+                self.stack_size = self.stack_size.checked_add(8)?;
+                if self.frame.is_none() {
+                    rules.push_str(&self.cfa_rule());
+                    rules.push(' ');
+                }
+                let offset = i64::from(self.stack_size);
+                self.saved.push((register, offset));
+                rules.push_str(&register_rule(register, offset));
+            }
+            UnwindOperation::Alloc(size) => {
+                self.stack_size = self.stack_size.checked_add(size)?;
+                if self.frame.is_none() {
+                    rules.push_str(&self.cfa_rule());
+                }
+            }
+            UnwindOperation::SetFramePointer => {
+                let offset = self.stack_size.checked_sub(info.frame_offset)?;
+                self.frame = Some((info.frame_register, offset));
+                rules.push_str(&self.cfa_rule());
+            }
+            UnwindOperation::SaveNonVolatile { register, offset } => {
+                let offset = i64::from(self.stack_size) - i64::from(offset);
+                self.saved.push((register, offset));
+                rules.push_str(&register_rule(register, offset));
+            }
+            // XMM registers are not unwound by minidump processors.
+            UnwindOperation::SaveXmm128 { .. } | UnwindOperation::Epilog => (),
+            UnwindOperation::PushMachineFrame { .. } => return None,
+        }
+
+        Some(rules)
+    }
+}
+
+impl StackCfiRecord {
+    /// Creates a record for an entry of the function table of an x64 image.
+    ///
+    /// If the function is a fragment of a parent function, the prologue of the parent is folded
+    /// into the initial rules. Returns `None` for functions whose prologue cannot be expressed as
+    /// CFI rules, such as interrupt handlers pushing a machine frame.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidUnwindInfo` if the unwind information of the function or one of its
+    ///   parents is missing or malformed
+    pub fn new(table: &UnwindTable<'_>, function: &RuntimeFunction) -> Result<Option<Self>> {
+        let info = table.unwind_info(function.unwind_info)?;
+
+        // Collect the chain of parent functions, whose prologues have completed.
+        let mut parents = Vec::new();
+        let mut chained = info.chained;
+        while let Some(parent) = chained {
+            if parents.len() >= MAX_CHAIN_DEPTH {
+                return Err(Error::InvalidUnwindInfo(function.unwind_info.0));
+            }
+            let parent_info = table.unwind_info(parent.unwind_info)?;
+            chained = parent_info.chained;
+            parents.push(parent_info);
+        }
+
+        let mut state = FrameState {
+            stack_size: 8,
+            frame: None,
+            saved: Vec::new(),
+        };
+
+        for parent in parents.iter().rev() {
+            for code in prolog_codes(parent)? {
+                if state.apply(parent, code.operation).is_none() {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let init = state.init_rules();
+
+        let mut rows: Vec<StackCfiRow> = Vec::new();
+        for code in prolog_codes(&info)? {
+            let rules = match state.apply(&info, code.operation) {
+                Some(rules) if rules.is_empty() => continue,
+                Some(rules) => rules,
+                None => return Ok(None),
+            };
+
+            let rva = Rva(function.begin.0 + u32::from(code.code_offset));
+            match rows.last_mut() {
+                Some(row) if row.rva == rva => {
+                    row.rules.push(' ');
+                    row.rules.push_str(&rules);
+                }
+                _ => rows.push(StackCfiRow { rva, rules }),
+            }
+        }
+
+        Ok(Some(Self {
+            rva: function.begin,
+            size: function.end.0.saturating_sub(function.begin.0),
+            init,
+            rows,
+        }))
+    }
+}
+
+/// Returns the unwind codes of a function in the order of the prologue's instructions.
+fn prolog_codes(info: &UnwindInfo<'_>) -> Result<Vec<UnwindCode>> {
+    let mut codes = Vec::new();
+    let mut iter = info.codes();
+    while let Some(code) = iter.next()? {
+        codes.push(code);
+    }
+
+    codes.reverse();
+    Ok(codes)
+}
+
+/// Returns the rule recovering a register saved at the given offset below the CFA.
+fn register_rule(register: u8, offset: i64) -> String {
+    let name = REGISTERS[usize::from(register & 0xf)];
+    if offset < 0 {
+        format!("${}: .cfa {} + ^", name, -offset)
+    } else {
+        format!("${}: .cfa {} - ^", name, offset)
+    }
+}
+
+impl fmt::Display for StackCfiRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "STACK CFI INIT {:x} {:x} {}",
+            self.rva.0, self.size, self.init
+        )?;
+
+        for row in &self.rows {
+            write!(f, "\nSTACK CFI {:x} {}", row.rva.0, row.rules)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A Breakpad `STACK` record, returned by [`PDB::stack_records`](crate::PDB::stack_records).
+///
+/// The `Display` implementation writes the record in the format of Breakpad symbol files, without
+/// a trailing newline. `STACK CFI` records span multiple lines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StackRecord {
+    /// A `STACK WIN` record from the frame table.
+    Win(StackWinRecord),
+    /// A `STACK CFI` record from x64 unwind information.
+    Cfi(StackCfiRecord),
+}
+
+impl StackRecord {
+    /// The address of the code covered by this record.
+    pub fn rva(&self) -> Rva {
+        match self {
+            Self::Win(record) => record.rva,
+            Self::Cfi(record) => record.rva,
+        }
+    }
+}
+
+impl fmt::Display for StackRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Win(record) => record.fmt(f),
+            Self::Cfi(record) => record.fmt(f),
+        }
+    }
+}
+
+impl From<StackWinRecord> for StackRecord {
+    fn from(record: StackWinRecord) -> Self {
+        Self::Win(record)
+    }
+}
+
+impl From<StackCfiRecord> for StackRecord {
+    fn from(record: StackCfiRecord) -> Self {
+        Self::Cfi(record)
+    }
+}
+
+/// Converts all entries of an x64 function table into `STACK CFI` records.
+///
+/// Functions whose prologue cannot be expressed as CFI rules are skipped.
+pub fn stack_cfi_records(table: &UnwindTable<'_>) -> Result<Vec<StackCfiRecord>> {
+    let mut records = Vec::new();
+    let mut functions = table.functions();
+
+    while let Some(function) = functions.next()? {
+        // Tables may be padded with empty entries.
+        if function.begin == function.end {
+            continue;
+        }
+
+        if let Some(record) = StackCfiRecord::new(table, &function)? {
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(pdata: &'static [u8], xdata: &'static [u8]) -> UnwindTable<'static> {
+        let mut table = UnwindTable::new(pdata);
+        table.add_image_data(Rva(0x2000), xdata);
+        table
+    }
+
+    #[test]
+    fn test_cfi_prologue() {
+        let pdata = &[
+            0x00, 0x10, 0x00, 0x00, // begin
+            0x80, 0x10, 0x00, 0x00, // end
+            0x00, 0x20, 0x00, 0x00, // unwind info
+        ];
+        let xdata = &[
+            0x01, 0x0a, 0x04, 0x35, // version 1, 4 codes, rbp at rsp+48
+            0x0a, 0x53, // 0a: UWOP_SET_FPREG
+            0x06, 0x72, // 06: UWOP_ALLOC_SMALL 64 bytes
+            0x02, 0x30, // 02: UWOP_PUSH_NONVOL rbx
+            0x01, 0x50, // 01: UWOP_PUSH_NONVOL rbp
+        ];
+
+        let table = table(pdata, xdata);
+        let records = stack_cfi_records(&table).expect("records");
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].to_string(),
+            "STACK CFI INIT 1000 80 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n\
+             STACK CFI 1001 .cfa: $rsp 16 + $rbp: .cfa 16 - ^\n\
+             STACK CFI 1002 .cfa: $rsp 24 + $rbx: .cfa 24 - ^\n\
+             STACK CFI 1006 .cfa: $rsp 88 +\n\
+             STACK CFI 100a .cfa: $rbp 40 +"
+        );
+    }
+
+    #[test]
+    fn test_cfi_chained() {
+        let pdata = &[
+            0x00, 0x10, 0x00, 0x00, // parent begin
+            0x40, 0x10, 0x00, 0x00, // parent end
+            0x00, 0x20, 0x00, 0x00, // parent unwind info
+            0x40, 0x10, 0x00, 0x00, // fragment begin
+            0x60, 0x10, 0x00, 0x00, // fragment end
+            0x10, 0x20, 0x00, 0x00, // fragment unwind info
+        ];
+        let xdata = &[
+            0x01, 0x05, 0x02, 0x00, // version 1, 2 codes
+            0x05, 0x32, // 05: UWOP_ALLOC_SMALL 32 bytes
+            0x01, 0x30, // 01: UWOP_PUSH_NONVOL rbx
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // padding
+            0x21, 0x00, 0x00, 0x00, // version 1, UNW_FLAG_CHAININFO
+            0x00, 0x10, 0x00, 0x00, // parent begin
+            0x40, 0x10, 0x00, 0x00, // parent end
+            0x00, 0x20, 0x00, 0x00, // parent unwind info
+        ];
+
+        let table = table(pdata, xdata);
+        let records = stack_cfi_records(&table).expect("records");
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1].to_string(),
+            "STACK CFI INIT 1040 20 .cfa: $rsp 48 + .ra: .cfa 8 - ^ $rbx: .cfa 16 - ^"
+        );
+    }
+
+    #[test]
+    fn test_cfi_machine_frame() {
+        let pdata = &[
+            0x00, 0x10, 0x00, 0x00, // begin
+            0x10, 0x10, 0x00, 0x00, // end
+            0x00, 0x20, 0x00, 0x00, // unwind info
+        ];
+        let xdata = &[
+            0x01, 0x00, 0x01, 0x00, // version 1, 1 code
+            0x00, 0x1a, // 00: UWOP_PUSH_MACHFRAME with error code
+        ];
+
+        let table = table(pdata, xdata);
+        assert_eq!(stack_cfi_records(&table).expect("records"), []);
+    }
+
+    #[test]
+    fn test_stack_win_display() {
+        let record = StackWinRecord {
+            ty: FrameType::FrameData,
+            rva: Rva(0x1010),
+            code_size: 0x2c,
+            prolog_size: 6,
+            params_size: 8,
+            saved_regs_size: 4,
+            locals_size: 0x10,
+            max_stack_size: Some(0),
+            uses_base_pointer: true,
+            program: Some("$T0 $ebp = $eip $T0 4 + ^ =".into()),
+        };
+
+        assert_eq!(
+            record.to_string(),
+            "STACK WIN 4 1010 2c 6 0 8 4 10 0 1 $T0 $ebp = $eip $T0 4 + ^ ="
+        );
+
+        let record = StackWinRecord {
+            ty: FrameType::FPO,
+            program: None,
+            ..record
+        };
+
+        assert_eq!(record.to_string(), "STACK WIN 0 1010 2c 6 0 8 4 10 0 0 1");
+    }
+}
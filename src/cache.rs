@@ -0,0 +1,148 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+// Serialized indexes start with a fixed header, followed by the index-specific payload. All
+// integers are stored in little-endian byte order:
+//
+//   magic    [u8; 4]   b"PDBX"
+//   version  u32       INDEX_FORMAT_VERSION
+//   kind     u32       IndexKind
+//   guid     [u8; 16]  PDB GUID in canonical byte order
+//   age      u32       PDB age
+//   length   u32       size of the payload in bytes
+//   payload  [u8; length]
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use uuid::Uuid;
+
+use crate::common::*;
+use crate::pdbi::PdbIdentity;
+
+const MAGIC: &[u8; 4] = b"PDBX";
+
+/// The version of the binary format produced when serializing indexes.
+///
+/// Serialized indexes of a different version are rejected when loading them.
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// The kind of a serialized index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub(crate) enum IndexKind {
+    /// An [`ItemFinder`](crate::ItemFinder) of the type stream.
+    TypeFinder = 1,
+    /// An [`ItemFinder`](crate::ItemFinder) of the id stream.
+    IdFinder = 2,
+    /// A [`SymbolCursor`](crate::SymbolCursor).
+    SymbolCursor = 3,
+}
+
+/// Serializes an index with the given payload.
+pub(crate) fn write_index(kind: IndexKind, identity: PdbIdentity, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(36 + payload.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&INDEX_FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&(kind as u32).to_le_bytes());
+    data.extend_from_slice(identity.guid.as_bytes());
+    data.extend_from_slice(&identity.age.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Validates the header of a serialized index and returns a buffer over its payload.
+///
+/// # Errors
+///
+/// * `Error::InvalidIndex` if the data is not a serialized index of the given kind and version.
+/// * `Error::IndexMismatch` if the index was built from a different PDB.
+pub(crate) fn read_index(
+    data: &[u8],
+    kind: IndexKind,
+    identity: PdbIdentity,
+) -> Result<ParseBuffer<'_>> {
+    let mut buf = ParseBuffer::from(data);
+
+    let magic = buf.take(MAGIC.len()).map_err(|_| Error::InvalidIndex)?;
+    if magic != MAGIC || buf.parse_u32()? != INDEX_FORMAT_VERSION {
+        return Err(Error::InvalidIndex);
+    }
+
+    if buf.parse_u32()? != kind as u32 {
+        return Err(Error::InvalidIndex);
+    }
+
+    let guid = Uuid::from_slice(buf.take(16)?).map_err(|_| Error::InvalidIndex)?;
+    let age = buf.parse_u32()?;
+    if (PdbIdentity { guid, age }) != identity {
+        return Err(Error::IndexMismatch);
+    }
+
+    let length = usize::try_from(buf.parse_u32()?).map_err(|_| Error::InvalidIndex)?;
+    if buf.len() != length {
+        return Err(Error::InvalidIndex);
+    }
+
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(age: u32) -> PdbIdentity {
+        PdbIdentity {
+            guid: Uuid::from_u128(0x2b3c3fa5_5a2e_44b8_8bba_c3300ff69f62),
+            age,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let data = write_index(IndexKind::TypeFinder, identity(2), b"payload");
+        assert_eq!(&data[..4], b"PDBX");
+
+        let mut buf = read_index(&data, IndexKind::TypeFinder, identity(2)).expect("read index");
+        assert_eq!(buf.take(7).expect("payload"), b"payload");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_identity_mismatch() {
+        let data = write_index(IndexKind::TypeFinder, identity(2), b"payload");
+        assert!(matches!(
+            read_index(&data, IndexKind::TypeFinder, identity(3)),
+            Err(Error::IndexMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        let mut data = write_index(IndexKind::TypeFinder, identity(2), b"payload");
+        data[4] = 0xff; // version
+
+        assert!(matches!(
+            read_index(&data, IndexKind::TypeFinder, identity(2)),
+            Err(Error::InvalidIndex)
+        ));
+
+        let data = write_index(IndexKind::TypeFinder, identity(2), b"payload");
+        assert!(matches!(
+            read_index(&data[..data.len() - 1], IndexKind::TypeFinder, identity(2)),
+            Err(Error::InvalidIndex)
+        ));
+
+        // a finder of the id stream cannot be loaded for the type stream
+        let data = write_index(IndexKind::IdFinder, identity(2), b"payload");
+        assert!(matches!(
+            read_index(&data, IndexKind::TypeFinder, identity(2)),
+            Err(Error::InvalidIndex)
+        ));
+    }
+}
@@ -5,20 +5,72 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::borrow::Cow;
-use std::fmt;
-use std::io;
-use std::mem;
-use std::ops::{Add, AddAssign, Sub};
-use std::result;
-use std::slice;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::cmp;
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Add, AddAssign, Sub};
+use core::result;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use scroll::ctx::TryFromCtx;
 use scroll::{self, Endian, Pread, LE};
 
+use crate::source::SourceError;
 use crate::tpi::constants;
+use crate::FallibleIterator;
+
+/// The category of an [`Error`], returned by [`Error::kind`].
+///
+/// Variants of [`Error`] are added and refined between releases, while their kinds are stable.
+/// Match on the kind to decide whether to retry, skip a record or abort, and on the error itself
+/// only where the details matter.
+///
+/// Kinds only categorize the existing variants of [`Error`], which are unchanged. Some variants can
+/// be caused both by corrupt data and by invalid arguments, and are categorized by their more
+/// common cause.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// Reading from the data source failed.
+    ///
+    /// The PDB itself may be intact, so the operation can be retried once the source recovers.
+    Io,
+    /// The PDB or a record in it is corrupt or truncated.
+    Malformed,
+    /// The data is valid, but uses a record kind or format that this crate does not support.
+    ///
+    /// Such errors are usually confined to a single record, which can be skipped.
+    Unsupported,
+    /// The PDB does not contain a stream or table required for the operation.
+    Missing,
+    /// An argument does not satisfy the requirements of the operation, such as an index beyond the
+    /// range of a finder or a serialized index or continuation from a different PDB.
+    ConstraintViolation,
+    /// The operation was cancelled through a [`CancellationToken`].
+    Cancelled,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Io => "I/O error",
+            Self::Malformed => "malformed data",
+            Self::Unsupported => "unsupported feature",
+            Self::Missing => "missing data",
+            Self::ConstraintViolation => "constraint violation",
+            Self::Cancelled => "cancelled",
+        })
+    }
+}
 
 /// An error that occurred while reading or parsing the PDB.
+///
+/// Use [`kind`](Self::kind) to handle errors by category.
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum Error {
@@ -39,11 +91,17 @@ pub enum Error {
     /// A stream requested by name was not found.
     StreamNameNotFound,
 
+    /// The requested stream index marks a deleted stream.
+    StreamDeleted,
+
+    /// A stream number does not fit into a [`StreamIndex`].
+    StreamIndexOutOfRange(u32),
+
     /// Invalid length or alignment of a stream.
     InvalidStreamLength(&'static str),
 
     /// An IO error occurred while reading from the data source.
-    IoError(io::Error),
+    IoError(SourceError),
 
     /// Unexpectedly reached end of input.
     UnexpectedEof,
@@ -67,8 +125,14 @@ pub enum Error {
     TypeTooShort,
 
     /// Type or Id not found.
+    ///
+    /// This is usually caused by a reference from another record, and categorized as
+    /// [`ErrorKind::Malformed`].
     TypeNotFound(u32),
 
+    /// There is no symbol record at the given symbol index.
+    SymbolNotFound(u32),
+
     /// Type or Id not indexed -- the requested type (`.0`) is larger than the maximum index covered
     /// by the `ItemFinder` (`.1`).
     TypeNotIndexed(u32, u32),
@@ -82,12 +146,43 @@ pub enum Error {
     /// Cross module reference not found in imports.
     CrossModuleRefNotFound(u32),
 
+    /// The type is not a class, struct or union.
+    NotAClassType(u32),
+
+    /// The type is a forward reference without definition.
+    ///
+    /// The definition is missing from the stream, so this is categorized as
+    /// [`ErrorKind::Malformed`].
+    IncompleteType(u32),
+
     /// Variable-length numeric parsing encountered an unexpected prefix.
     UnexpectedNumericPrefix(u16),
 
     /// Required mapping for virtual addresses (OMAP) was not found.
     AddressMapNotFound,
 
+    /// A section number does not refer to a section of the executable.
+    InvalidSection(u16),
+
+    /// A section offset (`.1`) does not describe a valid address in its section (`.0`).
+    InvalidSectionOffset(u16, u32),
+
+    /// An address is not covered by any section of the executable.
+    AddressOutsideSections(u32),
+
+    /// The code or data at an address was eliminated when rearranging the executable (OMAP).
+    AddressEliminated(u32),
+
+    /// The operation was cancelled through a [`CancellationToken`].
+    Cancelled,
+
+    /// The data is not a serialized index of the expected kind and format version.
+    InvalidIndex,
+
+    /// The serialized index was built from a different PDB, or a search continuation was created for
+    /// a different stream.
+    IndexMismatch,
+
     /// A parse error from scroll.
     ScrollError(scroll::Error),
 
@@ -100,16 +195,48 @@ pub enum Error {
     /// There is no source file checksum at the given offset.
     InvalidFileChecksumOffset(u32),
 
+    /// There is no source file record at the given offset.
+    InvalidSourceFileOffset(u32),
+
     /// The lines table is missing.
     LinesNotFound,
 
-    /// A binary annotation was compressed incorrectly.
+    /// A binary annotation was compressed incorrectly, or an operand is too large to be
+    /// compressed.
     InvalidCompressedAnnotation,
 
     /// An unknown binary annotation was encountered.
     UnknownBinaryAnnotation(u32),
+
+    /// A string id refers to itself through its list of substrings.
+    CyclicStringId(u32),
+
+    /// A degenerate record was found at the given offset, and its policy is
+    /// [`DegenerateRecordPolicy::Report`].
+    DegenerateRecord(DegenerateRecordKind, PdbInternalSectionOffset),
+
+    /// Unwind information at the given RVA is missing or malformed.
+    InvalidUnwindInfo(u32),
+
+    /// A CodeView record does not describe a PDB 7.0 file.
+    InvalidCodeViewRecord,
+
+    /// The data is not a PE image.
+    InvalidImage,
+
+    /// A type refers to itself through the types it contains.
+    ///
+    /// This likely indicates file corruption.
+    CyclicTypeReference(u32),
+
+    /// Resolving a type exceeded the limit of nested types.
+    ///
+    /// Such deeply nested types do not occur in valid PDBs, so this is categorized as
+    /// [`ErrorKind::Malformed`].
+    TypeDepthExceeded(u32),
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -120,7 +247,7 @@ impl std::error::Error for Error {
 }
 
 impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> ::std::result::Result<(), fmt::Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> ::core::result::Result<(), fmt::Error> {
         match self {
             Self::PageReferenceOutOfRange(p) => {
                 write!(f, "MSF referred to page number ({}) out of range", p)
@@ -133,6 +260,10 @@ impl fmt::Display for Error {
             Self::StreamNotFound(s) => {
                 write!(f, "The requested stream ({}) is not stored in this file", s)
             }
+            Self::StreamDeleted => write!(f, "The requested stream has been deleted"),
+            Self::StreamIndexOutOfRange(n) => {
+                write!(f, "Stream number {} exceeds the range of stream indexes", n)
+            }
             Self::InvalidStreamLength(s) => write!(
                 f,
                 "{} stream has an invalid length or alignment for its records",
@@ -151,6 +282,7 @@ impl fmt::Display for Error {
                 write!(f, "The type information header was invalid: {}", reason)
             }
             Self::TypeNotFound(type_index) => write!(f, "Type {} not found", type_index),
+            Self::SymbolNotFound(index) => write!(f, "Symbol {:#x} not found", index),
             Self::TypeNotIndexed(type_index, indexed_count) => write!(
                 f,
                 "Type {} not indexed (index covers {})",
@@ -169,6 +301,14 @@ impl fmt::Display for Error {
                 "Cross module reference {:#06x} not found in imports",
                 index
             ),
+            Self::NotAClassType(index) => {
+                write!(f, "Type {:#06x} is not a class, struct or union", index)
+            }
+            Self::IncompleteType(index) => write!(
+                f,
+                "Type {:#06x} is a forward reference without definition",
+                index
+            ),
             Self::UnexpectedNumericPrefix(prefix) => write!(
                 f,
                 "Variable-length numeric parsing encountered an unexpected prefix ({:#06x}",
@@ -185,14 +325,144 @@ impl fmt::Display for Error {
             Self::InvalidFileChecksumOffset(offset) => {
                 write!(f, "Invalid source file checksum offset {:#x}", offset)
             }
+            Self::InvalidSourceFileOffset(offset) => {
+                write!(f, "Invalid source file record offset {:#x}", offset)
+            }
+            Self::InvalidSection(section) => {
+                write!(f, "Section {} does not exist in the executable", section)
+            }
+            Self::InvalidSectionOffset(section, offset) => write!(
+                f,
+                "Offset {:#x} is not a valid address in section {}",
+                offset, section
+            ),
+            Self::AddressOutsideSections(address) => {
+                write!(f, "Address {:#x} is outside any section", address)
+            }
+            Self::AddressEliminated(address) => write!(
+                f,
+                "Address {:#x} was eliminated from the executable",
+                address
+            ),
+            Self::Cancelled => write!(f, "The operation was cancelled"),
+            Self::InvalidIndex => write!(f, "Invalid or unsupported serialized index"),
+            Self::IndexMismatch => write!(f, "Serialized index was built from a different PDB"),
             Self::UnknownBinaryAnnotation(num) => write!(f, "Unknown binary annotation {}", num),
+            Self::DegenerateRecord(kind, offset) => write!(
+                f,
+                "Degenerate record ({:?}) at section {} offset {:#x}",
+                kind, offset.section, offset.offset
+            ),
+            Self::CyclicStringId(index) => {
+                write!(
+                    f,
+                    "String id {:#x} refers to itself through its substrings",
+                    index
+                )
+            }
+            Self::InvalidCodeViewRecord => write!(f, "Invalid or unsupported CodeView record"),
+            Self::InvalidImage => write!(f, "The data is not a valid PE image"),
+            Self::CyclicTypeReference(index) => {
+                write!(f, "Type {:#06x} refers to itself", index)
+            }
+            Self::TypeDepthExceeded(index) => write!(
+                f,
+                "Resolving type {:#06x} exceeds the limit of nested types",
+                index
+            ),
+            Self::InvalidUnwindInfo(rva) => write!(f, "Invalid unwind info at RVA {:#x}", rva),
             _ => fmt::Debug::fmt(self, f),
         }
     }
 }
 
-impl From<io::Error> for Error {
-    fn from(e: io::Error) -> Self {
+impl Error {
+    /// Returns the category of this error.
+    ///
+    /// The kind of every error is stable across releases.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     match typ.parse() {
+    ///         Ok(data) => println!("{:?}", data),
+    ///         Err(error) if error.kind() == pdb::ErrorKind::Unsupported => continue,
+    ///         Err(error) => return Err(error),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::IoError(_) => ErrorKind::Io,
+
+            Self::UnrecognizedFileFormat
+            | Self::InvalidPageSize(_)
+            | Self::PageReferenceOutOfRange(_)
+            | Self::StreamIndexOutOfRange(_)
+            | Self::InvalidStreamLength(_)
+            | Self::UnexpectedEof
+            | Self::SymbolTooShort
+            | Self::InvalidTypeInformationHeader(_)
+            | Self::TypeTooShort
+            | Self::UnexpectedNumericPrefix(_)
+            | Self::ScrollError(_)
+            | Self::InvalidFileChecksumOffset(_)
+            | Self::InvalidSourceFileOffset(_)
+            | Self::InvalidCompressedAnnotation
+            | Self::CyclicStringId(_)
+            | Self::DegenerateRecord(_, _)
+            | Self::InvalidUnwindInfo(_)
+            | Self::InvalidCodeViewRecord
+            | Self::InvalidImage
+            | Self::CyclicTypeReference(_)
+            | Self::TypeNotFound(_)
+            | Self::IncompleteType(_)
+            | Self::TypeDepthExceeded(_) => ErrorKind::Malformed,
+
+            Self::UnimplementedFeature(_)
+            | Self::UnimplementedSymbolKind(_)
+            | Self::UnimplementedTypeKind(_)
+            | Self::UnimplementedDebugSubsection(_)
+            | Self::UnimplementedFileChecksumKind(_)
+            | Self::UnknownBinaryAnnotation(_) => ErrorKind::Unsupported,
+
+            Self::StreamNotFound(_)
+            | Self::StreamNameNotFound
+            | Self::StreamDeleted
+            | Self::GlobalSymbolsNotFound
+            | Self::AddressMapNotFound
+            | Self::LinesNotFound => ErrorKind::Missing,
+
+            Self::SymbolNotFound(_)
+            | Self::TypeNotIndexed(_, _)
+            | Self::NotACrossModuleRef(_)
+            | Self::CrossModuleRefNotFound(_)
+            | Self::NotAClassType(_)
+            | Self::InvalidSection(_)
+            | Self::InvalidSectionOffset(_, _)
+            | Self::AddressOutsideSections(_)
+            | Self::AddressEliminated(_)
+            | Self::InvalidIndex
+            | Self::IndexMismatch => ErrorKind::ConstraintViolation,
+
+            Self::Cancelled => ErrorKind::Cancelled,
+        }
+    }
+}
+
+impl From<SourceError> for Error {
+    fn from(e: SourceError) -> Self {
         Self::IoError(e)
     }
 }
@@ -210,6 +480,52 @@ impl From<scroll::Error> for Error {
 /// The result type returned by this crate.
 pub type Result<T> = result::Result<T, Error>;
 
+/// The kind of a degenerate record, see [`DegenerateRecordPolicy`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DegenerateRecordKind {
+    /// A procedure whose code has a length of zero.
+    ZeroLengthFunction,
+    /// A section contribution with a size of zero.
+    EmptyContribution,
+    /// A public symbol that does not point into any section, and thus has no address other than
+    /// RVA 0.
+    ZeroAddressPublic,
+    /// A line record whose code has a length of zero.
+    ZeroLengthLine,
+}
+
+/// How to handle degenerate records.
+///
+/// Degenerate records, such as functions without code or contributions without bytes, are valid
+/// according to the format but usually stem from discarded or folded code. When they end up in
+/// address indexes, they cause spurious lookup hits. The default keeps them, like any other record.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DegenerateRecordPolicy {
+    /// Keep degenerate records.
+    #[default]
+    Keep,
+    /// Silently skip degenerate records.
+    Skip,
+    /// Fail with [`Error::DegenerateRecord`] on the first degenerate record.
+    Report,
+}
+
+impl DegenerateRecordPolicy {
+    /// Returns whether a degenerate record at the given offset should be kept.
+    pub(crate) fn keep(
+        self,
+        kind: DegenerateRecordKind,
+        offset: PdbInternalSectionOffset,
+    ) -> Result<bool> {
+        match self {
+            Self::Keep => Ok(true),
+            Self::Skip => Ok(false),
+            Self::Report => Err(Error::DegenerateRecord(kind, offset)),
+        }
+    }
+}
+
 /// Implements `Pread` using the inner type.
 macro_rules! impl_pread {
     ($type:ty) => {
@@ -453,7 +769,7 @@ macro_rules! impl_section_offset {
         impl PartialOrd for $type {
             /// Compares offsets if they reside in the same section.
             #[inline]
-            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
                 if self.section == other.section {
                     Some(self.offset.cmp(&other.offset))
                 } else {
@@ -530,17 +846,58 @@ impl_section_offset!(PdbInternalSectionOffset);
 /// This index can either refer to a stream, or indicate the absence of a stream. Check
 /// [`is_none`](Self::is_none) to see whether a stream should exist.
 ///
+/// Two values are reserved as sentinels and never refer to a stream: `0xffff` indicates the absence
+/// of a stream, and `0xfffe` marks a stream that has been deleted. Thus, the largest index of a
+/// stream is [`StreamIndex::MAX`]. Loading a deleted stream fails with `Error::StreamDeleted`.
+///
 /// Use [`get`](Self::get) to load data for this stream.
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct StreamIndex(pub u16);
 
 impl StreamIndex {
+    /// The largest index that refers to a stream.
+    pub const MAX: Self = Self(0xfffd);
+
+    /// Returns an index that marks a deleted stream.
+    #[inline]
+    pub const fn deleted() -> Self {
+        Self(0xfffe)
+    }
+
+    /// Returns `true` if the index marks a deleted stream.
+    #[inline]
+    #[must_use]
+    pub fn is_deleted(self) -> bool {
+        self == Self::deleted()
+    }
+
     /// Returns the MSF stream number, if this stream is not a NULL stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamDeleted` if the index marks a deleted stream
     #[inline]
-    pub(crate) fn msf_number(self) -> Option<u32> {
+    pub(crate) fn msf_number(self) -> Result<Option<u32>> {
         match self.0 {
-            0xffff => None,
-            index => Some(u32::from(index)),
+            0xffff => Ok(None),
+            0xfffe => Err(Error::StreamDeleted),
+            index => Ok(Some(u32::from(index))),
+        }
+    }
+}
+
+impl TryFrom<u32> for StreamIndex {
+    type Error = Error;
+
+    /// Converts an MSF stream number into a stream index.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamIndexOutOfRange` if the number is larger than [`StreamIndex::MAX`]
+    fn try_from(number: u32) -> Result<Self> {
+        match u16::try_from(number) {
+            Ok(index) if index <= Self::MAX.0 => Ok(Self(index)),
+            _ => Err(Error::StreamIndexOutOfRange(number)),
         }
     }
 }
@@ -548,8 +905,9 @@ impl StreamIndex {
 impl fmt::Display for StreamIndex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.msf_number() {
-            Some(number) => write!(f, "{}", number),
-            None => write!(f, "None"),
+            Ok(Some(number)) => write!(f, "{}", number),
+            Ok(None) => write!(f, "None"),
+            Err(_) => write!(f, "Deleted"),
         }
     }
 }
@@ -680,6 +1038,180 @@ pub struct Register(pub u16);
 impl_convert!(Register, u16);
 impl_pread!(Register);
 
+/// The state of a long-running scan over a stream, reported to progress callbacks.
+///
+/// The following operations periodically invoke a callback with the current progress, which allows
+/// tools to display progress bars while processing large PDBs:
+///
+///  - Building an [`ItemFinder`](crate::ItemFinder) with
+///    [`ItemInformation::finder_with_progress`](crate::ItemInformation::finder_with_progress) or
+///    [`ItemInformation::finder_cancellable`](crate::ItemInformation::finder_cancellable).
+///  - Verifying a PDB with [`PDB::verify_with_progress`](crate::PDB::verify_with_progress).
+///  - Exporting line information with
+///    [`PDB::export_lines_with_progress`](crate::PDB::export_lines_with_progress).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+    /// The number of records processed so far.
+    pub records: usize,
+    /// The number of bytes consumed so far.
+    pub bytes: usize,
+    /// The total number of bytes that will be consumed by the operation.
+    pub total_bytes: usize,
+}
+
+impl Progress {
+    /// Returns the fraction of work completed, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            self.bytes as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// A handle to cooperatively cancel long-running operations.
+///
+/// Tokens are cheap to clone, and all clones share the same state. Cancelling any clone, possibly
+/// from another thread, causes operations observing the token to stop before processing their next
+/// record and return [`Error::Cancelled`].
+///
+/// Tokens are observed by:
+///
+///  - Iterators wrapped with [`wrap`](Self::wrap), which check the token before every record.
+///  - [`ItemInformation::finder_cancellable`](crate::ItemInformation::finder_cancellable) and
+///    [`ItemInformation::search_cancellable`](crate::ItemInformation::search_cancellable), which
+///    check the token while scanning the type or id stream.
+///  - [`TypeInformation::search_names_cancellable`](crate::TypeInformation::search_names_cancellable)
+///    and [`TypeInformation::find_by_name_cancellable`](crate::TypeInformation::find_by_name_cancellable).
+///  - [`SymbolTable::search_cancellable`](crate::SymbolTable::search_cancellable).
+///  - [`PDB::verify_cancellable`](crate::PDB::verify_cancellable).
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// #
+/// # fn test() -> pdb::Result<()> {
+/// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// # let pdb = pdb::PDB::open(file)?;
+/// let token = pdb::CancellationToken::new();
+/// let type_information = pdb.type_information()?;
+/// let mut types = token.wrap(type_information.iter());
+///
+/// // typically called from a different thread, e.g. when the user changes the query
+/// token.cancel();
+/// assert!(matches!(types.next(), Err(pdb::Error::Cancelled)));
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of all operations observing this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns `Err(Error::Cancelled)` if cancellation has been requested.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wraps an iterator so that it fails with `Error::Cancelled` once this token is cancelled.
+    pub fn wrap<I>(&self, iter: I) -> Cancellable<I> {
+        Cancellable {
+            iter,
+            token: self.clone(),
+        }
+    }
+}
+
+/// An iterator that stops with [`Error::Cancelled`] when its [`CancellationToken`] is cancelled.
+///
+/// Created by [`CancellationToken::wrap`].
+#[derive(Clone, Debug)]
+pub struct Cancellable<I> {
+    iter: I,
+    token: CancellationToken,
+}
+
+impl<I> Cancellable<I> {
+    /// Returns the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I> FallibleIterator for Cancellable<I>
+where
+    I: FallibleIterator<Error = Error>,
+{
+    type Item = I::Item;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        self.token.check()?;
+        self.iter.next()
+    }
+}
+
+/// Invokes a progress callback at most once every `INTERVAL` records.
+pub(crate) struct ProgressReporter<F> {
+    callback: F,
+    progress: Progress,
+}
+
+impl<F> ProgressReporter<F>
+where
+    F: FnMut(Progress),
+{
+    /// The number of records between two invocations of the callback.
+    const INTERVAL: usize = 4096;
+
+    pub fn new(callback: F, total_bytes: usize) -> Self {
+        Self {
+            callback,
+            progress: Progress {
+                records: 0,
+                bytes: 0,
+                total_bytes,
+            },
+        }
+    }
+
+    /// Records that another record was processed, with `bytes` consumed in total.
+    pub fn record(&mut self, bytes: usize) {
+        self.progress.records += 1;
+        self.progress.bytes = bytes;
+        if self.progress.records % Self::INTERVAL == 0 {
+            (self.callback)(self.progress);
+        }
+    }
+
+    /// Reports completion of the operation.
+    pub fn finish(mut self) {
+        self.progress.bytes = self.progress.total_bytes;
+        (self.callback)(self.progress);
+    }
+}
+
 /// Provides little-endian access to a &[u8].
 #[derive(Debug, Default, Clone)]
 pub(crate) struct ParseBuffer<'b>(&'b [u8], usize);
@@ -727,7 +1259,7 @@ impl<'b> ParseBuffer<'b> {
     /// Seek to the given absolute position.
     #[inline]
     pub fn seek(&mut self, pos: usize) {
-        self.1 = std::cmp::min(pos, self.0.len());
+        self.1 = core::cmp::min(pos, self.0.len());
     }
 
     /// Truncates the buffer at the given absolute position.
@@ -838,6 +1370,7 @@ impl<'b> fmt::LowerHex for ParseBuffer<'b> {
 }
 
 /// Value of an enumerate type.
+#[non_exhaustive]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[allow(missing_docs)]
 pub enum Variant {
@@ -849,6 +1382,8 @@ pub enum Variant {
     I16(i16),
     I32(i32),
     I64(i64),
+    U128(u128),
+    I128(i128),
 }
 
 impl fmt::Display for Variant {
@@ -862,6 +1397,42 @@ impl fmt::Display for Variant {
             Self::I16(value) => write!(f, "{}", value),
             Self::I32(value) => write!(f, "{}", value),
             Self::I64(value) => write!(f, "{}", value),
+            Self::U128(value) => write!(f, "{}", value),
+            Self::I128(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Variant {
+    /// Returns the value as a signed integer, if it can be represented without loss.
+    pub fn to_i64(self) -> Option<i64> {
+        match self {
+            Self::U8(value) => Some(value.into()),
+            Self::U16(value) => Some(value.into()),
+            Self::U32(value) => Some(value.into()),
+            Self::U64(value) => i64::try_from(value).ok(),
+            Self::I8(value) => Some(value.into()),
+            Self::I16(value) => Some(value.into()),
+            Self::I32(value) => Some(value.into()),
+            Self::I64(value) => Some(value),
+            Self::U128(value) => i64::try_from(value).ok(),
+            Self::I128(value) => i64::try_from(value).ok(),
+        }
+    }
+
+    /// Returns the value as an unsigned integer, if it can be represented without loss.
+    pub fn to_u64(self) -> Option<u64> {
+        match self {
+            Self::U8(value) => Some(value.into()),
+            Self::U16(value) => Some(value.into()),
+            Self::U32(value) => Some(value.into()),
+            Self::U64(value) => Some(value),
+            Self::I8(value) => u64::try_from(value).ok(),
+            Self::I16(value) => u64::try_from(value).ok(),
+            Self::I32(value) => u64::try_from(value).ok(),
+            Self::I64(value) => u64::try_from(value).ok(),
+            Self::U128(value) => u64::try_from(value).ok(),
+            Self::I128(value) => u64::try_from(value).ok(),
         }
     }
 }
@@ -881,7 +1452,8 @@ impl<'a> TryFromCtx<'a, Endian> for Variant {
             constants::LF_USHORT => Self::U16(this.gread_with(&mut offset, le)?),
             constants::LF_ULONG => Self::U32(this.gread_with(&mut offset, le)?),
             constants::LF_UQUADWORD => Self::U64(this.gread_with(&mut offset, le)?),
-            _ if cfg!(debug_assertions) => unreachable!(),
+            constants::LF_OCTWORD => Self::I128(this.gread_with(&mut offset, le)?),
+            constants::LF_UOCTWORD => Self::U128(this.gread_with(&mut offset, le)?),
             other => return Err(Error::UnexpectedNumericPrefix(other)),
         };
 
@@ -955,31 +1527,197 @@ impl<'b> From<&'b [u8]> for RawString<'b> {
     }
 }
 
-/// Cast a binary slice to a slice of types.
-///
-/// This function performs a cast of a binary slice to a slice of some type, returning `Some` if the
-/// following two conditions are met:
+/// A table of fixed-size records stored in little-endian byte order.
 ///
-///  1. The size of the slize must be a multiple of the type's size.
-///  2. The slice must be aligned to the alignment of the type.
-///
-/// Note that this function will not convert any endianness. The types must be capable of reading
-/// endianness correclty in case data from other hosts is read.
-pub(crate) fn cast_aligned<T>(data: &[u8]) -> Option<&[T]> {
-    let alignment = mem::align_of::<T>();
-    let size = mem::size_of::<T>();
+/// Records are decoded on access rather than cast from the underlying bytes, so the table yields
+/// the same values on hosts of any byte order and does not require the data to be aligned. The
+/// size of a record is the in-memory size of `T`, which must therefore not contain padding.
+pub(crate) struct RecordTable<'t, T> {
+    data: &'t [u8],
+    _ph: PhantomData<T>,
+}
+
+impl<'t, T> RecordTable<'t, T>
+where
+    T: TryFromCtx<'t, Endian, Error = scroll::Error>,
+{
+    const SIZE: usize = mem::size_of::<T>();
+
+    /// Creates a table over the given data, which must hold a whole number of records.
+    pub fn new(data: &'t [u8]) -> Option<Self> {
+        if data.len() % Self::SIZE != 0 {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            _ph: PhantomData,
+        })
+    }
+
+    /// Creates a table without records.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of records in this table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len() / Self::SIZE
+    }
+
+    /// Returns `true` if this table contains no records.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Decodes the record at the given index.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<T> {
+        let offset = index.checked_mul(Self::SIZE)?;
+        self.data.pread_with(offset, LE).ok()
+    }
+
+    /// Returns a table of the records starting at the given index.
+    pub fn skip(&self, index: usize) -> Self {
+        let offset = index.saturating_mul(Self::SIZE);
+        Self {
+            data: self.data.get(offset..).unwrap_or_default(),
+            _ph: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over all records in this table.
+    pub fn iter(&self) -> RecordIter<'t, T> {
+        RecordIter { table: *self }
+    }
+
+    /// Binary searches this table for a record with the given key.
+    ///
+    /// The table must be sorted by the key. See [`slice::binary_search_by_key`] for a description of
+    /// the return value.
+    pub fn binary_search_by_key<K, F>(&self, key: &K, mut f: F) -> result::Result<usize, usize>
+    where
+        K: Ord,
+        F: FnMut(T) -> K,
+    {
+        let mut low = 0;
+        let mut high = self.len();
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            // mid is always in bounds, so the record exists
+            let record = match self.get(mid) {
+                Some(record) => record,
+                None => return Err(mid),
+            };
+
+            match f(record).cmp(key) {
+                cmp::Ordering::Less => low = mid + 1,
+                cmp::Ordering::Greater => high = mid,
+                cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+
+        Err(low)
+    }
+}
 
-    let ptr = data.as_ptr();
-    let bytes = data.len();
+impl<T> Clone for RecordTable<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RecordTable<'_, T> {}
 
-    match (bytes % size, ptr.align_offset(alignment)) {
-        (0, 0) => Some(unsafe { slice::from_raw_parts(ptr as *const T, bytes / size) }),
-        (_, _) => None,
+impl<T> Default for RecordTable<'_, T> {
+    fn default() -> Self {
+        Self {
+            data: &[],
+            _ph: PhantomData,
+        }
+    }
+}
+
+impl<'t, T> fmt::Debug for RecordTable<'t, T>
+where
+    T: TryFromCtx<'t, Endian, Error = scroll::Error> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the records of a [`RecordTable`].
+#[derive(Clone, Copy)]
+pub(crate) struct RecordIter<'t, T> {
+    table: RecordTable<'t, T>,
+}
+
+impl<'t, T> Iterator for RecordIter<'t, T>
+where
+    T: TryFromCtx<'t, Endian, Error = scroll::Error>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.table.get(0)?;
+        self.table = self.table.skip(1);
+        Some(record)
+    }
+}
+
+impl<'t, T> Default for RecordIter<'t, T>
+where
+    T: TryFromCtx<'t, Endian, Error = scroll::Error>,
+{
+    fn default() -> Self {
+        RecordTable::empty().iter()
+    }
+}
+
+impl<'t, T> fmt::Debug for RecordIter<'t, T>
+where
+    T: TryFromCtx<'t, Endian, Error = scroll::Error> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RecordIter").field(&self.table).finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    mod error {
+        use crate::common::*;
+
+        #[test]
+        fn test_kind() {
+            assert_eq!(Error::UnexpectedEof.kind(), ErrorKind::Malformed);
+            assert_eq!(
+                Error::from(scroll::Error::BadOffset(4)).kind(),
+                ErrorKind::Malformed
+            );
+            assert_eq!(
+                Error::UnimplementedTypeKind(0x1234).kind(),
+                ErrorKind::Unsupported
+            );
+            assert_eq!(Error::StreamNotFound(5).kind(), ErrorKind::Missing);
+            assert_eq!(Error::TypeNotFound(0x1100).kind(), ErrorKind::Malformed);
+            assert_eq!(Error::IncompleteType(0x1100).kind(), ErrorKind::Malformed);
+            assert_eq!(
+                Error::TypeDepthExceeded(0x1100).kind(),
+                ErrorKind::Malformed
+            );
+            assert_eq!(
+                Error::TypeNotIndexed(0x1100, 0x1000).kind(),
+                ErrorKind::ConstraintViolation
+            );
+            assert_eq!(Error::Cancelled.kind(), ErrorKind::Cancelled);
+        }
+    }
+
     mod parse_buffer {
         use crate::common::*;
 
@@ -1244,48 +1982,197 @@ mod tests {
         }
     }
 
-    mod cast_aligned {
-        use crate::common::cast_aligned;
-        use std::slice;
+    mod stream_index {
+        use crate::common::*;
+
+        #[test]
+        fn test_sentinels() {
+            assert!(StreamIndex::none().is_none());
+            assert!(!StreamIndex::none().is_deleted());
+            assert!(StreamIndex::deleted().is_deleted());
+
+            assert_eq!(StreamIndex(7).msf_number().unwrap(), Some(7));
+            assert_eq!(StreamIndex::MAX.msf_number().unwrap(), Some(0xfffd));
+            assert_eq!(StreamIndex::none().msf_number().unwrap(), None);
+            assert!(matches!(
+                StreamIndex::deleted().msf_number(),
+                Err(Error::StreamDeleted)
+            ));
+        }
+
+        #[test]
+        fn test_format_sentinels() {
+            assert_eq!(format!("{}", StreamIndex(7)), "7");
+            assert_eq!(format!("{}", StreamIndex::none()), "None");
+            assert_eq!(
+                format!("{:?}", StreamIndex::deleted()),
+                "StreamIndex(Deleted)"
+            );
+        }
 
         #[test]
-        fn test_cast_aligned() {
-            let data: &[u32] = &[1, 2, 3];
+        fn test_try_from_u32() {
+            assert_eq!(StreamIndex::try_from(0xfffd).unwrap(), StreamIndex::MAX);
+            assert!(matches!(
+                StreamIndex::try_from(0xfffe),
+                Err(Error::StreamIndexOutOfRange(0xfffe))
+            ));
+            assert!(matches!(
+                StreamIndex::try_from(0x1_0005),
+                Err(Error::StreamIndexOutOfRange(0x1_0005))
+            ));
+        }
+    }
+
+    mod variant {
+        use crate::common::*;
 
-            let ptr = data.as_ptr() as *const u8;
-            let bin: &[u8] = unsafe { slice::from_raw_parts(ptr, 12) };
+        #[test]
+        fn test_parse_variant() {
+            let data = &[0x34, 0x12];
+            assert_eq!(
+                ParseBuffer::from(&data[..]).parse::<Variant>().unwrap(),
+                Variant::U16(0x1234)
+            );
+
+            let data = &[0x01, 0x80, 0xfe, 0xff];
+            assert_eq!(
+                ParseBuffer::from(&data[..]).parse::<Variant>().unwrap(),
+                Variant::I16(-2)
+            );
+
+            let mut data = vec![0x17, 0x80];
+            data.extend_from_slice(&(-3i128).to_le_bytes());
+            assert_eq!(
+                ParseBuffer::from(&data[..]).parse::<Variant>().unwrap(),
+                Variant::I128(-3)
+            );
+
+            let mut data = vec![0x18, 0x80];
+            data.extend_from_slice(&u128::MAX.to_le_bytes());
+            assert_eq!(
+                ParseBuffer::from(&data[..]).parse::<Variant>().unwrap(),
+                Variant::U128(u128::MAX)
+            );
+        }
 
-            assert_eq!(cast_aligned(bin), Some(data));
+        #[test]
+        fn test_parse_unsupported_variant() {
+            // LF_REAL32
+            let data = &[0x05, 0x80, 0x00, 0x00, 0x80, 0x3f];
+            assert!(matches!(
+                ParseBuffer::from(&data[..]).parse::<Variant>(),
+                Err(Error::UnexpectedNumericPrefix(0x8005))
+            ));
         }
 
         #[test]
-        fn test_cast_empty() {
-            let data: &[u32] = &[];
+        fn test_variant_conversion() {
+            assert_eq!(Variant::I16(-2).to_i64(), Some(-2));
+            assert_eq!(Variant::I16(-2).to_u64(), None);
+            assert_eq!(Variant::U64(u64::MAX).to_i64(), None);
+            assert_eq!(Variant::U64(u64::MAX).to_u64(), Some(u64::MAX));
+            assert_eq!(Variant::U128(7).to_u64(), Some(7));
+            assert_eq!(Variant::I128(i128::MIN).to_i64(), None);
+        }
+    }
 
-            let ptr = data.as_ptr() as *const u8;
-            let bin: &[u8] = unsafe { slice::from_raw_parts(ptr, 0) };
+    mod record_table {
+        use crate::common::*;
 
-            assert_eq!(cast_aligned(bin), Some(data));
+        #[test]
+        fn test_record_table() {
+            let data = &[1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 1];
+            let table = RecordTable::<u32>::new(data).expect("record table");
+
+            assert_eq!(table.len(), 3);
+            assert_eq!(table.get(2), Some(0x0100_0000));
+            assert_eq!(table.get(3), None);
+            assert_eq!(table.iter().collect::<Vec<_>>(), vec![1, 2, 0x0100_0000]);
+            assert_eq!(
+                table.skip(1).iter().collect::<Vec<_>>(),
+                vec![2, 0x0100_0000]
+            );
         }
 
         #[test]
-        fn test_cast_unaligned() {
-            let data: &[u32] = &[1, 2, 3];
+        fn test_record_table_unaligned() {
+            let data = &[0, 1, 0, 0, 0, 2, 0, 0, 0];
+            let table = RecordTable::<u32>::new(&data[1..]).expect("record table");
+            assert_eq!(table.iter().collect::<Vec<_>>(), vec![1, 2]);
+        }
 
-            let ptr = data.as_ptr() as *const u8;
-            let bin: &[u8] = unsafe { slice::from_raw_parts(ptr.offset(2), 8) };
+        #[test]
+        fn test_record_table_wrong_size() {
+            let data = &[1, 0, 0, 0, 2, 0, 0];
+            assert!(RecordTable::<u32>::new(data).is_none());
+        }
 
-            assert_eq!(cast_aligned::<u32>(bin), None);
+        #[test]
+        fn test_record_table_binary_search() {
+            let data = &[1, 0, 0, 0, 3, 0, 0, 0, 5, 0, 0, 0];
+            let table = RecordTable::<u32>::new(data).expect("record table");
+
+            assert_eq!(table.binary_search_by_key(&3, |r| r), Ok(1));
+            assert_eq!(table.binary_search_by_key(&0, |r| r), Err(0));
+            assert_eq!(table.binary_search_by_key(&4, |r| r), Err(2));
+            assert_eq!(table.binary_search_by_key(&6, |r| r), Err(3));
         }
+    }
+
+    mod progress {
+        use crate::common::*;
 
         #[test]
-        fn test_cast_wrong_size() {
-            let data: &[u32] = &[1, 2, 3];
+        fn test_progress_reporter() {
+            let mut reports = Vec::new();
+            let mut reporter = ProgressReporter::new(|progress| reports.push(progress), 10_000);
+            for record in 1..=5000 {
+                reporter.record(record * 2);
+            }
+            reporter.finish();
+
+            assert_eq!(
+                reports,
+                [
+                    Progress {
+                        records: 4096,
+                        bytes: 8192,
+                        total_bytes: 10_000,
+                    },
+                    Progress {
+                        records: 5000,
+                        bytes: 10_000,
+                        total_bytes: 10_000,
+                    },
+                ]
+            );
+        }
 
-            let ptr = data.as_ptr() as *const u8;
-            let bin: &[u8] = unsafe { slice::from_raw_parts(ptr, 11) };
+        #[test]
+        fn test_cancellation_token() {
+            let token = CancellationToken::new();
+            let data = [1u8, 2, 3];
+            let mut iter = token.wrap(fallible_iterator::convert(data.iter().map(Ok::<_, Error>)));
+
+            assert_eq!(iter.next().expect("first"), Some(&1));
+
+            // clones share the same state
+            token.clone().cancel();
+            assert!(token.is_cancelled());
+            assert!(matches!(iter.next(), Err(Error::Cancelled)));
+            assert!(matches!(token.check(), Err(Error::Cancelled)));
+        }
 
-            assert_eq!(cast_aligned::<u32>(bin), None);
+        #[test]
+        fn test_progress_fraction() {
+            let progress = Progress {
+                records: 1,
+                bytes: 25,
+                total_bytes: 100,
+            };
+            assert_eq!(progress.fraction(), 0.25);
+            assert_eq!(Progress::default().fraction(), 1.0);
         }
     }
 }
@@ -0,0 +1,559 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// Magic number of a zstd frame.
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: u32 = 0xfd2f_b528;
+
+/// Magic number of zstd skippable frames, ignoring the lowest four bits.
+#[cfg(feature = "zstd")]
+const ZSTD_SKIPPABLE_MAGIC: u32 = 0x184d_2a50;
+
+/// Magic number of the skippable frame holding the seek table of the zstd seekable format.
+#[cfg(feature = "zstd")]
+const ZSTD_SEEK_TABLE_MAGIC: u32 = 0x184d_2a5e;
+
+/// Magic number at the end of the seek table footer of the zstd seekable format.
+#[cfg(feature = "zstd")]
+const ZSTD_SEEKABLE_MAGIC: u32 = 0x8f92_eab1;
+
+/// Magic number of a gzip member.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// The number of decompressed frames kept in memory by default.
+const DEFAULT_CACHE_FRAMES: usize = 8;
+
+/// The compression format of a [`CompressedSource`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompressionFormat {
+    /// Zstandard, optionally in the seekable format.
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// Gzip, optionally split into blocks in the BGZF format.
+    #[cfg(feature = "gzip")]
+    Gzip,
+}
+
+/// A range of the decompressed file that can be decompressed independently.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Frame {
+    /// Offset of the compressed frame in the underlying reader.
+    compressed_offset: u64,
+    /// Size of the compressed frame in bytes.
+    compressed_size: u64,
+    /// Offset of the decompressed data in the decompressed file.
+    offset: u64,
+    /// Size of the decompressed data in bytes.
+    size: u64,
+}
+
+/// A [`Source`] for a PDB file stored compressed at rest.
+///
+/// The file is split into frames that are decompressed independently when the PDB reads from
+/// them, so that the decompressed file never needs to be written to disk. Recently used frames are
+/// kept in memory, see [`cache_frames`](Self::cache_frames).
+///
+/// Random access is only efficient if the file consists of many small frames:
+///
+///  - zstd files are indexed by the seek table of the [seekable format], if present. Otherwise, the
+///    frames are located by their headers, which is cheap if the frames declare their content
+///    size.
+///  - gzip files are indexed by the block sizes of the BGZF format, as written by `bgzip`. Other
+///    gzip files are treated as a single frame, which is decompressed in full.
+///
+/// Requires the `zstd` or `gzip` feature.
+///
+/// [seekable format]: https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md
+///
+/// # Example
+///
+/// ```no_run
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("foo.pdb.zst")?;
+/// let source = pdb::CompressedSource::new(file)?;
+/// let pdb = pdb::PDB::open(source)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CompressedSource<R> {
+    reader: R,
+    format: CompressionFormat,
+    frames: Vec<Frame>,
+    /// Decompressed frames by position in `frames`, least recently used first.
+    cache: Vec<(usize, Vec<u8>)>,
+    cache_frames: usize,
+}
+
+impl<R> CompressedSource<R>
+where
+    R: Read + Seek,
+{
+    /// Creates a source decompressing the contents of `reader`.
+    ///
+    /// The compression format is detected from the first bytes of the reader.
+    ///
+    /// # Errors
+    ///
+    /// * `io::ErrorKind::InvalidData` if the format is not supported, or if the frames cannot be
+    ///   located
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+
+        let mut magic = [0; 4];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+
+        let (format, frames) = match magic {
+            #[cfg(feature = "zstd")]
+            magic if is_zstd_magic(u32::from_le_bytes(magic)) => {
+                (CompressionFormat::Zstd, zstd_frames(&mut reader, len)?)
+            }
+            #[cfg(feature = "gzip")]
+            [0x1f, 0x8b, _, _] => (CompressionFormat::Gzip, gzip_frames(&mut reader, len)?),
+            _ => return Err(invalid_data("unsupported compression format")),
+        };
+
+        Ok(Self {
+            reader,
+            format,
+            frames,
+            cache: Vec::new(),
+            cache_frames: DEFAULT_CACHE_FRAMES,
+        })
+    }
+
+    /// Sets the number of decompressed frames kept in memory.
+    ///
+    /// At least one frame is always kept. Defaults to 8.
+    pub fn cache_frames(mut self, frames: usize) -> Self {
+        self.cache_frames = frames.max(1);
+        self
+    }
+
+    /// Returns the compression format of the file.
+    pub fn format(&self) -> CompressionFormat {
+        self.format
+    }
+
+    /// Returns the number of independently decompressed frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the size of the decompressed file in bytes.
+    pub fn decompressed_len(&self) -> u64 {
+        self.frames
+            .last()
+            .map_or(0, |frame| frame.offset + frame.size)
+    }
+
+    /// Returns the decompressed data of the frame at `index`.
+    fn frame(&mut self, index: usize) -> io::Result<&[u8]> {
+        match self.cache.iter().position(|&(cached, _)| cached == index) {
+            Some(position) => {
+                let entry = self.cache.remove(position);
+                self.cache.push(entry);
+            }
+            None => {
+                let frame = self.frames[index];
+                let mut compressed = vec![0; to_usize(frame.compressed_size)?];
+                self.reader.seek(SeekFrom::Start(frame.compressed_offset))?;
+                self.reader.read_exact(&mut compressed)?;
+
+                let data = decompress(self.format, &compressed)?;
+                if data.len() as u64 != frame.size {
+                    return Err(invalid_data("frame size does not match its index"));
+                }
+
+                if self.cache.len() >= self.cache_frames {
+                    self.cache.remove(0);
+                }
+                self.cache.push((index, data));
+            }
+        }
+
+        Ok(&self.cache[self.cache.len() - 1].1)
+    }
+
+    /// Appends the decompressed data of `slice` to `bytes`.
+    fn read_slice(&mut self, slice: &SourceSlice, bytes: &mut Vec<u8>) -> io::Result<()> {
+        let mut offset = slice.offset;
+        let mut remaining = slice.size;
+
+        while remaining > 0 {
+            let index = self
+                .frames
+                .partition_point(|frame| frame.offset + frame.size <= offset);
+            let frame = match self.frames.get(index) {
+                Some(frame) if frame.offset <= offset => *frame,
+                _ => return Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+
+            let start = to_usize(offset - frame.offset)?;
+            let data = self.frame(index)?;
+            let len = remaining.min(data.len() - start);
+            bytes.extend_from_slice(&data[start..start + len]);
+
+            offset += len as u64;
+            remaining -= len;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> fmt::Debug for CompressedSource<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CompressedSource({:?}, {} frames)",
+            self.format,
+            self.frames.len()
+        )
+    }
+}
+
+#[derive(Clone)]
+struct CompressedView {
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for CompressedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CompressedView({} bytes)", self.bytes.len())
+    }
+}
+
+impl SourceView<'_> for CompressedView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+impl<'s, R> Source<'s> for CompressedSource<R>
+where
+    R: Read + Seek + 's,
+{
+    fn view(&mut self, slices: &[SourceSlice]) -> io::Result<Box<dyn SourceView<'s>>> {
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+
+        for slice in slices {
+            self.read_slice(slice, &mut bytes)?;
+        }
+
+        Ok(Box::new(CompressedView { bytes }))
+    }
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn to_usize(value: u64) -> io::Result<usize> {
+    usize::try_from(value).map_err(|_| invalid_data("frame too large"))
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+/// Decompresses a single frame.
+fn decompress(format: CompressionFormat, compressed: &[u8]) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+
+    match format {
+        #[cfg(feature = "zstd")]
+        CompressionFormat::Zstd => {
+            let mut input = compressed;
+            while input.len() >= 8 {
+                let magic = u32::from_le_bytes([input[0], input[1], input[2], input[3]]);
+                if magic & 0xffff_fff0 == ZSTD_SKIPPABLE_MAGIC {
+                    let size = u32::from_le_bytes([input[4], input[5], input[6], input[7]]);
+                    input = input.get(8 + size as usize..).unwrap_or_default();
+                    continue;
+                }
+
+                let mut decoder = ruzstd::decoding::StreamingDecoder::new(&mut input)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                decoder.read_to_end(&mut data)?;
+            }
+        }
+        #[cfg(feature = "gzip")]
+        CompressionFormat::Gzip => {
+            flate2::read::MultiGzDecoder::new(compressed).read_to_end(&mut data)?;
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(feature = "zstd")]
+fn is_zstd_magic(magic: u32) -> bool {
+    magic == ZSTD_MAGIC || magic & 0xffff_fff0 == ZSTD_SKIPPABLE_MAGIC
+}
+
+/// Locates the frames of a zstd file.
+#[cfg(feature = "zstd")]
+fn zstd_frames<R: Read + Seek>(reader: &mut R, len: u64) -> io::Result<Vec<Frame>> {
+    if let Some(frames) = zstd_seek_table(reader, len)? {
+        return Ok(frames);
+    }
+
+    let mut frames = Vec::new();
+    let mut compressed_offset = 0;
+    let mut offset = 0;
+
+    while compressed_offset < len {
+        reader.seek(SeekFrom::Start(compressed_offset))?;
+        let magic = read_u32(reader)?;
+
+        if magic & 0xffff_fff0 == ZSTD_SKIPPABLE_MAGIC {
+            compressed_offset += 8 + u64::from(read_u32(reader)?);
+            continue;
+        } else if magic != ZSTD_MAGIC {
+            return Err(invalid_data("invalid zstd frame"));
+        }
+
+        let (compressed_size, content_size) = zstd_frame_size(reader)?;
+        let mut frame = Frame {
+            compressed_offset,
+            compressed_size,
+            offset,
+            size: 0,
+        };
+
+        frame.size = match content_size {
+            Some(size) => size,
+            None => {
+                let mut compressed = vec![0; to_usize(compressed_size)?];
+                reader.seek(SeekFrom::Start(compressed_offset))?;
+                reader.read_exact(&mut compressed)?;
+                decompress(CompressionFormat::Zstd, &compressed)?.len() as u64
+            }
+        };
+
+        if frame.size > 0 {
+            frames.push(frame);
+        }
+
+        compressed_offset += compressed_size;
+        offset += frame.size;
+    }
+
+    Ok(frames)
+}
+
+/// Determines the compressed size and content size of the zstd frame following its magic number.
+#[cfg(feature = "zstd")]
+fn zstd_frame_size<R: Read + Seek>(reader: &mut R) -> io::Result<(u64, Option<u64>)> {
+    let [descriptor] = read_array(reader)?;
+    let single_segment = descriptor & 0x20 != 0;
+    let has_checksum = descriptor & 0x04 != 0;
+
+    let window_size = if single_segment { 0 } else { 1 };
+    let dictionary_size = [0, 1, 2, 4][usize::from(descriptor & 0x03)];
+    let content_size_size = match descriptor >> 6 {
+        0 if single_segment => 1,
+        0 => 0,
+        1 => 2,
+        2 => 4,
+        _ => 8,
+    };
+
+    reader.seek(SeekFrom::Current(window_size + dictionary_size))?;
+    let content_size = match content_size_size {
+        0 => None,
+        size => {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes[..size])?;
+            let value = u64::from_le_bytes(bytes);
+            Some(if size == 2 { value + 256 } else { value })
+        }
+    };
+
+    let mut size = 5 + window_size as u64 + dictionary_size as u64 + content_size_size as u64;
+    loop {
+        let [a, b, c] = read_array(reader)?;
+        let header = u32::from_le_bytes([a, b, c, 0]);
+        let block_size = match (header >> 1) & 0x03 {
+            1 => 1,
+            3 => return Err(invalid_data("reserved zstd block type")),
+            _ => u64::from(header >> 3),
+        };
+
+        reader.seek(SeekFrom::Current(block_size as i64))?;
+        size += 3 + block_size;
+
+        if header & 1 != 0 {
+            break;
+        }
+    }
+
+    if has_checksum {
+        size += 4;
+    }
+
+    Ok((size, content_size))
+}
+
+/// Reads the seek table of a zstd file in the seekable format.
+///
+/// Returns `None` if the file does not end with a seek table.
+#[cfg(feature = "zstd")]
+fn zstd_seek_table<R: Read + Seek>(reader: &mut R, len: u64) -> io::Result<Option<Vec<Frame>>> {
+    // The seek table is a skippable frame ending with a footer of 9 bytes.
+    if len < 17 {
+        return Ok(None);
+    }
+
+    reader.seek(SeekFrom::Start(len - 9))?;
+    let count = u64::from(read_u32(reader)?);
+    let [descriptor] = read_array(reader)?;
+    if read_u32(reader)? != ZSTD_SEEKABLE_MAGIC {
+        return Ok(None);
+    }
+
+    let entry_size = if descriptor & 0x80 != 0 { 12 } else { 8 };
+    let table_size = count * entry_size + 9;
+    if table_size + 8 > len {
+        return Err(invalid_data("invalid zstd seek table"));
+    }
+
+    let table_offset = len - table_size - 8;
+    reader.seek(SeekFrom::Start(table_offset))?;
+    if read_u32(reader)? != ZSTD_SEEK_TABLE_MAGIC || u64::from(read_u32(reader)?) != table_size {
+        return Err(invalid_data("invalid zstd seek table"));
+    }
+
+    let mut frames = Vec::with_capacity(to_usize(count)?);
+    let mut compressed_offset = 0;
+    let mut offset = 0;
+
+    for _ in 0..count {
+        let compressed_size = u64::from(read_u32(reader)?);
+        let size = u64::from(read_u32(reader)?);
+        if entry_size == 12 {
+            read_u32(reader)?;
+        }
+
+        if size > 0 {
+            frames.push(Frame {
+                compressed_offset,
+                compressed_size,
+                offset,
+                size,
+            });
+        }
+
+        compressed_offset += compressed_size;
+        offset += size;
+    }
+
+    if compressed_offset > table_offset {
+        return Err(invalid_data("invalid zstd seek table"));
+    }
+
+    Ok(Some(frames))
+}
+
+/// Locates the blocks of a gzip file in the BGZF format.
+///
+/// Files not in the BGZF format consist of a single frame.
+#[cfg(feature = "gzip")]
+fn gzip_frames<R: Read + Seek>(reader: &mut R, len: u64) -> io::Result<Vec<Frame>> {
+    if let Some(frames) = bgzf_blocks(reader, len)? {
+        return Ok(frames);
+    }
+
+    let mut compressed = vec![0; to_usize(len)?];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut compressed)?;
+    let size = decompress(CompressionFormat::Gzip, &compressed)?.len() as u64;
+
+    Ok(vec![Frame {
+        compressed_offset: 0,
+        compressed_size: len,
+        offset: 0,
+        size,
+    }])
+}
+
+/// Reads the block sizes of a gzip file in the BGZF format.
+///
+/// Returns `None` if a member does not declare its size.
+#[cfg(feature = "gzip")]
+fn bgzf_blocks<R: Read + Seek>(reader: &mut R, len: u64) -> io::Result<Option<Vec<Frame>>> {
+    let mut frames = Vec::new();
+    let mut compressed_offset = 0;
+    let mut offset = 0;
+
+    while compressed_offset < len {
+        reader.seek(SeekFrom::Start(compressed_offset))?;
+        let header: [u8; 12] = read_array(reader)?;
+        if header[..2] != GZIP_MAGIC || header[3] & 0x04 == 0 {
+            return Ok(None);
+        }
+
+        // Find the `BC` subfield of the extra field, which holds the block size minus one.
+        let extra_len = u16::from_le_bytes([header[10], header[11]]);
+        let mut extra = vec![0; usize::from(extra_len)];
+        reader.read_exact(&mut extra)?;
+
+        let mut block_size = None;
+        let mut subfields = &extra[..];
+        while subfields.len() >= 4 {
+            let subfield_len = usize::from(u16::from_le_bytes([subfields[2], subfields[3]]));
+            let data = match subfields.get(4..4 + subfield_len) {
+                Some(data) => data,
+                None => break,
+            };
+
+            if subfields[..2] == *b"BC" && subfield_len == 2 {
+                block_size = Some(u64::from(u16::from_le_bytes([data[0], data[1]])) + 1);
+            }
+            subfields = &subfields[4 + subfield_len..];
+        }
+
+        let compressed_size = match block_size {
+            Some(size) if compressed_offset + size <= len => size,
+            _ => return Ok(None),
+        };
+
+        // The decompressed size is stored at the end of the member.
+        reader.seek(SeekFrom::Start(compressed_offset + compressed_size - 4))?;
+        let size = u64::from(read_u32(reader)?);
+
+        if size > 0 {
+            frames.push(Frame {
+                compressed_offset,
+                compressed_size,
+                offset,
+                size,
+            });
+        }
+
+        compressed_offset += compressed_size;
+        offset += size;
+    }
+
+    Ok(Some(frames))
+}
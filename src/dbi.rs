@@ -7,13 +7,19 @@
 
 // DBI = "Debug Information"
 
-use std::borrow::Cow;
-use std::fmt;
-use std::result;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use core::result;
 
 use crate::common::*;
+use crate::modi::FileChecksum;
 use crate::msf::*;
-use crate::{FallibleIterator, SectionCharacteristics};
+use crate::pdb::PDB;
+use crate::source::Source;
+use crate::symbol::{SourceLanguage, SymbolData, SymbolKind};
+use crate::{FallibleIterator, SectionCharacteristics, SectionKind};
 
 /// Provides access to the "DBI" stream inside the PDB.
 ///
@@ -26,10 +32,9 @@ use crate::{FallibleIterator, SectionCharacteristics};
 /// #
 /// # fn test() -> pdb::Result<usize> {
 /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(file)?;
+/// let pdb = pdb::PDB::open(file)?;
 ///
 /// let dbi = pdb.debug_information()?;
-
 ///
 /// # let mut count: usize = 0;
 /// let mut modules = dbi.modules()?;
@@ -71,6 +76,11 @@ impl<'s> DebugInformation<'s> {
         Ok(self.header.machine_type.into())
     }
 
+    /// Returns the version of the DBI stream format.
+    pub fn version(&self) -> HeaderVersion {
+        self.header.version
+    }
+
     /// Returns this PDB's original `age`.
     ///
     /// This number is written by the linker and should be equal to the image's `age` value. In
@@ -88,39 +98,447 @@ impl<'s> DebugInformation<'s> {
         }
     }
 
+    /// Returns the range of bytes in the DBI stream occupied by a substream.
+    ///
+    /// The range is computed from the sizes declared in the stream header, without validating them.
+    /// It may extend beyond the end of the stream if the PDB is corrupt.
+    pub fn substream_range(&self, substream: DBISubstream) -> Range<usize> {
+        let start = DBISubstream::ALL
+            .iter()
+            .take_while(|&&kind| kind != substream)
+            .fold(self.header_len, |start, &kind| {
+                start.saturating_add(self.substream_size(kind))
+            });
+
+        start..start.saturating_add(self.substream_size(substream))
+    }
+
+    /// Returns the raw bytes of a substream.
+    ///
+    /// This gives access to substreams even if this crate fails to parse them, for example to dump
+    /// or compare them.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the substream extends beyond the end of the DBI stream
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let dbi = pdb.debug_information()?;
+    /// for &substream in pdb::DBISubstream::ALL {
+    ///     let range = dbi.substream_range(substream);
+    ///     let bytes = dbi.substream(substream)?;
+    ///     println!("{:?} at {:#x}: {} bytes", substream, range.start, bytes.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn substream(&self, substream: DBISubstream) -> Result<&[u8]> {
+        let range = self.substream_range(substream);
+        self.stream
+            .as_slice()
+            .get(range)
+            .ok_or(Error::UnexpectedEof)
+    }
+
+    /// Returns the size of a substream declared in the stream header.
+    fn substream_size(&self, substream: DBISubstream) -> usize {
+        let header = &self.header;
+        let size = match substream {
+            DBISubstream::ModuleInfo => header.module_list_size,
+            DBISubstream::SectionContributions => header.section_contribution_size,
+            DBISubstream::SectionMap => header.section_map_size,
+            DBISubstream::FileInfo => header.file_info_size,
+            DBISubstream::TypeServerMap => header.type_server_map_size,
+            DBISubstream::EditAndContinue => header.ec_substream_size,
+            DBISubstream::OptionalDebugHeader => header.debug_header_size,
+        };
+        size as usize
+    }
+
+    /// Returns the indexes of additional streams declared in the optional debug header.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the optional debug header extends beyond the end of the stream
+    /// * `Error::InvalidStreamLength` if the optional debug header has an odd length
+    pub fn optional_debug_header(&self) -> Result<OptionalDebugHeader> {
+        let bytes = self.substream(DBISubstream::OptionalDebugHeader)?;
+        OptionalDebugHeader::parse(&mut ParseBuffer::from(bytes))
+    }
+
     /// Returns an iterator that can traverse the modules list in sequential order.
     pub fn modules(&self) -> Result<ModuleIter<'_>> {
-        let mut buf = self.stream.parse_buffer();
-        // drop the header
-        buf.take(self.header_len)?;
-        let modules_buf = buf.take(self.header.module_list_size as usize)?;
+        let modules_buf = self.substream(DBISubstream::ModuleInfo)?;
         Ok(ModuleIter {
             buf: modules_buf.into(),
         })
     }
 
+    /// Counts the modules of each source language.
+    ///
+    /// The language of a module is read from its compile flags record, which requires loading the
+    /// module information of every module from the given PDB. Modules without module information or
+    /// compile flags are counted as [`unknown`](LanguageSummary::unknown). Modules that declare a
+    /// language this crate does not know are counted separately for each value, as
+    /// [`SourceLanguage::Unknown`]. Modules built by rustc are counted as [`SourceLanguage::Rust`],
+    /// even if the compiler declared a different language.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let dbi = pdb.debug_information()?;
+    /// let summary = dbi.language_summary(&pdb)?;
+    /// for (language, count) in &summary.languages {
+    ///     println!("{}: {} modules", language, count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn language_summary<S>(&self, pdb: &PDB<'s, S>) -> Result<LanguageSummary>
+    where
+        S: Source<'s> + 's,
+    {
+        let mut summary = LanguageSummary::default();
+
+        let mut modules = self.modules()?;
+        while let Some(module) = modules.next()? {
+            let language = match pdb.module_info(&module)? {
+                Some(info) => info.compile_flags()?.map(|flags| {
+                    if flags.is_rust() {
+                        SourceLanguage::Rust
+                    } else {
+                        flags.language
+                    }
+                }),
+                None => None,
+            };
+
+            match language {
+                Some(language) => summary.add(language),
+                None => summary.unknown += 1,
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Checks the debug information of every module for common gaps.
+    ///
+    /// This loads the module information of every module from the given PDB, and reports missing
+    /// C13 line information, source files without checksums, source files that are only recorded
+    /// for edit and continue, and symbol records this crate cannot parse. Use
+    /// [`ModuleHealth::is_healthy`] to gate symbol pipelines on the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let dbi = pdb.debug_information()?;
+    /// for health in dbi.module_health(&pdb)? {
+    ///     if !health.is_healthy() {
+    ///         println!("module {}: {:?}", health.module, health);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn module_health<S>(&self, pdb: &PDB<'s, S>) -> Result<Vec<ModuleHealth>>
+    where
+        S: Source<'s> + 's,
+    {
+        let strings = match pdb.string_table() {
+            Ok(strings) => Some(strings),
+            Err(Error::StreamNameNotFound) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut reports = Vec::new();
+
+        let mut modules = self.modules()?;
+        while let Some(module) = modules.next()? {
+            let mut health = ModuleHealth {
+                module: reports.len(),
+                ..Default::default()
+            };
+
+            let ec_source = self.ec_name(module.info().source)?;
+
+            let info = match pdb.module_info(&module)? {
+                Some(info) => info,
+                None => {
+                    health.ec_only_source = ec_source.is_some();
+                    reports.push(health);
+                    continue;
+                }
+            };
+
+            health.has_module_info = true;
+            health.has_c13_lines = info.has_c13_lines();
+
+            let mut ec_source_found = false;
+            let program = info.line_program()?;
+            let mut files = program.files();
+            while let Some(file) = files.next()? {
+                if matches!(file.checksum, FileChecksum::None) {
+                    health.files_without_checksum += 1;
+                }
+
+                // Without a string table, file names cannot be compared and the check is skipped.
+                let name = match strings {
+                    Some(ref strings) => file.resolve_name(strings)?,
+                    None => {
+                        ec_source_found = true;
+                        continue;
+                    }
+                };
+
+                if let Some(source) = ec_source {
+                    ec_source_found |= file_name(name.as_bytes())
+                        .eq_ignore_ascii_case(file_name(source.as_bytes()));
+                }
+            }
+
+            health.ec_only_source = ec_source.is_some() && !ec_source_found;
+
+            let mut symbols = info.symbols()?;
+            while let Some(symbol) = symbols.next()? {
+                if let Ok(SymbolData::Unknown { kind, .. }) = symbol.parse() {
+                    health.unknown_symbol_kinds.push(kind);
+                }
+            }
+
+            health.unknown_symbol_kinds.sort_unstable();
+            health.unknown_symbol_kinds.dedup();
+            reports.push(health);
+        }
+
+        Ok(reports)
+    }
+
+    /// Returns the name at `offset` in the edit and continue substream.
+    ///
+    /// The substream is a name table listing the source files and compiler PDBs of the modules.
+    /// Returns `None` for offset zero, which refers to the empty name.
+    pub(crate) fn ec_name(&self, offset: u32) -> Result<Option<RawString<'_>>> {
+        if offset == 0 || self.header.ec_substream_size == 0 {
+            return Ok(None);
+        }
+
+        let mut ec = ParseBuffer::from(self.substream(DBISubstream::EditAndContinue)?);
+        let _magic = ec.parse_u32()?;
+        let _hash_version = ec.parse_u32()?;
+        let names_size = ec.parse_u32()?;
+        if offset >= names_size {
+            return Ok(None);
+        }
+
+        let mut names = ParseBuffer::from(ec.take(names_size as usize)?);
+        names.take(offset as usize)?;
+        Ok(Some(names.parse_cstring()?).filter(|name| !name.is_empty()))
+    }
+
     /// Returns an iterator that can traverse the section contributions list in sequential order.
     pub fn section_contributions(&self) -> Result<DBISectionContributionIter<'_>> {
-        let mut buf = self.stream.parse_buffer();
-        // drop the header and modules list
-        buf.take(self.header_len + self.header.module_list_size as usize)?;
-        let contributions_buf = buf.take(self.header.section_contribution_size as usize)?;
+        let contributions_buf = self.substream(DBISubstream::SectionContributions)?;
         DBISectionContributionIter::parse(contributions_buf.into())
     }
+
+    /// Returns an iterator over the section contributions, joined with the names of the modules
+    /// they belong to.
+    ///
+    /// The module names are collected once when the iterator is created, so that each contribution
+    /// can be resolved without scanning the modules list again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let dbi = pdb.debug_information()?;
+    /// let mut contributions = dbi.module_contributions()?;
+    /// while let Some(contribution) = contributions.next()? {
+    ///     if let Some(name) = contribution.module_name {
+    ///         let size = contribution.range.end.offset - contribution.range.start.offset;
+    ///         println!("{}: {} bytes", name, size);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn module_contributions(&self) -> Result<ModuleContributionIter<'_>> {
+        let mut module_names = Vec::new();
+        let mut modules = self.modules()?;
+        while let Some(module) = modules.next()? {
+            module_names.push(module.module_name);
+        }
+
+        Ok(ModuleContributionIter {
+            contributions: self.section_contributions()?,
+            module_names,
+        })
+    }
 }
 
-/// The version of the PDB format.
+/// A substream of the DBI stream.
 ///
-/// This version type is used in multiple locations: the DBI header, and the PDBI header.
+/// The substreams follow the stream header in the order listed here. Use
+/// [`DebugInformation::substream`] to access their raw bytes.
 #[non_exhaustive]
-#[derive(Debug, Copy, Clone)]
-#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DBISubstream {
+    /// The module info substream, listing the modules and their streams.
+    ModuleInfo,
+    /// The section contribution substream.
+    SectionContributions,
+    /// The section map substream, describing the logical segments of the image.
+    SectionMap,
+    /// The file info substream, listing the source files of each module.
+    FileInfo,
+    /// The type server map substream.
+    TypeServerMap,
+    /// The edit and continue substream, a name table of source files and compiler PDBs.
+    EditAndContinue,
+    /// The optional debug header substream, listing the indexes of additional streams.
+    OptionalDebugHeader,
+}
+
+impl DBISubstream {
+    /// All substreams, in the order they are stored in the DBI stream.
+    pub const ALL: &'static [Self] = &[
+        Self::ModuleInfo,
+        Self::SectionContributions,
+        Self::SectionMap,
+        Self::FileInfo,
+        Self::TypeServerMap,
+        Self::EditAndContinue,
+        Self::OptionalDebugHeader,
+    ];
+}
+
+/// The number of modules per source language.
+///
+/// Returned by [`DebugInformation::language_summary`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LanguageSummary {
+    /// The number of modules of each language, in the order the languages first occur.
+    pub languages: Vec<(SourceLanguage, usize)>,
+    /// The number of modules that do not declare a language.
+    ///
+    /// Modules declaring a language that is not known to this crate are listed in `languages` as
+    /// [`SourceLanguage::Unknown`] instead.
+    pub unknown: usize,
+}
+
+impl LanguageSummary {
+    fn add(&mut self, language: SourceLanguage) {
+        match self.languages.iter_mut().find(|(l, _)| *l == language) {
+            Some((_, count)) => *count += 1,
+            None => self.languages.push((language, 1)),
+        }
+    }
+
+    /// Returns the number of modules of the given language.
+    pub fn count(&self, language: SourceLanguage) -> usize {
+        self.languages
+            .iter()
+            .find(|(l, _)| *l == language)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Returns the total number of modules, including those that do not declare a language.
+    pub fn total(&self) -> usize {
+        self.languages.iter().map(|(_, count)| count).sum::<usize>() + self.unknown
+    }
+}
+
+/// Returns the last component of a Windows or Unix path.
+fn file_name(path: &[u8]) -> &[u8] {
+    match path.iter().rposition(|&b| b == b'\\' || b == b'/') {
+        Some(index) => &path[index + 1..],
+        None => path,
+    }
+}
+
+/// A summary of the debug information quality of a module.
+///
+/// Returned by [`DebugInformation::module_health`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModuleHealth {
+    /// Index of the module in [`DebugInformation::modules`].
+    pub module: usize,
+    /// Whether the module has a module info stream.
+    ///
+    /// Modules without one contribute no symbols or line information at all. All other checks
+    /// are skipped for these modules.
+    pub has_module_info: bool,
+    /// Whether the module contains line information in the C13 format.
+    ///
+    /// This is `false` for modules without line information, and for modules using the legacy
+    /// C11 format, which lacks file checksums and inlinee lines.
+    pub has_c13_lines: bool,
+    /// The number of source files without a checksum.
+    ///
+    /// Debuggers and source servers cannot verify that the source files they load match the build.
+    pub files_without_checksum: usize,
+    /// Whether the source file of the module is only recorded in the edit and continue substream,
+    /// but not in the files of its line program.
+    ///
+    /// The edit and continue substream often stores paths relative to the build directory, so
+    /// files are compared by their file names.
+    pub ec_only_source: bool,
+    /// Kinds of symbol records that this crate cannot parse, sorted and deduplicated.
+    pub unknown_symbol_kinds: Vec<SymbolKind>,
+}
+
+impl ModuleHealth {
+    /// Returns whether none of the checks found a problem.
+    pub fn is_healthy(&self) -> bool {
+        self.has_module_info
+            && self.has_c13_lines
+            && self.files_without_checksum == 0
+            && !self.ec_only_source
+            && self.unknown_symbol_kinds.is_empty()
+    }
+}
+
+/// The version of the DBI stream format.
+///
+/// Returned by [`DebugInformation::version`]. Each version corresponds to the Visual C++ release
+/// that introduced it. Use [`u32::from`] to compare versions numerically.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum HeaderVersion {
+    /// Visual C++ 4.1.
     V41,
+    /// Visual C++ 5.0.
     V50,
+    /// Visual C++ 6.0.
     V60,
+    /// Visual C++ 7.0 (Visual Studio .NET) and later.
     V70,
+    /// Visual C++ 11.0 (Visual Studio 2012) and later.
     V110,
+    /// An unknown version.
     OtherValue(u32),
 }
 
@@ -138,6 +556,33 @@ impl From<u32> for HeaderVersion {
     }
 }
 
+impl From<HeaderVersion> for u32 {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(version: HeaderVersion) -> Self {
+        match version {
+            HeaderVersion::V41 => 93_08_03,
+            HeaderVersion::V50 => 1996_03_07,
+            HeaderVersion::V60 => 1997_06_06,
+            HeaderVersion::V70 => 1999_09_03,
+            HeaderVersion::V110 => 2009_12_01,
+            HeaderVersion::OtherValue(v) => v,
+        }
+    }
+}
+
+impl fmt::Display for HeaderVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V41 => f.write_str("V41"),
+            Self::V50 => f.write_str("V50"),
+            Self::V60 => f.write_str("V60"),
+            Self::V70 => f.write_str("V70"),
+            Self::V110 => f.write_str("V110"),
+            Self::OtherValue(v) => write!(f, "unknown ({})", v),
+        }
+    }
+}
+
 /// A DBI header -- `NewDBIHdr`, really -- parsed from a stream.
 ///
 /// Reference:
@@ -234,7 +679,7 @@ impl DBIHeader {
             reserved: buf.parse_u32()?,
         };
 
-        if header.signature != u32::max_value() {
+        if header.signature != u32::MAX {
             // this is likely a DBIHdr, not a NewDBIHdr
             // it could be promoted:
             //   https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.cpp#L291-L313
@@ -262,6 +707,12 @@ pub enum MachineType {
     Arm = 0x1C0,
     /// ARM64 little endian
     Arm64 = 0xAA64,
+    /// ARM64 code interoperable with x64 emulation (ARM64EC)
+    Arm64EC = 0xA641,
+    /// Hybrid image containing both ARM64 and ARM64EC code (ARM64X)
+    Arm64X = 0xA64E,
+    /// Hybrid image containing x86 and ARM64 code (CHPE)
+    ChpeX86 = 0x3A64,
     /// ARM Thumb-2 little endian
     ArmNT = 0x1C4,
     /// EFI byte code
@@ -315,6 +766,9 @@ impl fmt::Display for MachineType {
             Self::Amd64 => write!(f, "Amd64"),
             Self::Arm => write!(f, "Arm"),
             Self::Arm64 => write!(f, "Arm64"),
+            Self::Arm64EC => write!(f, "Arm64EC"),
+            Self::Arm64X => write!(f, "Arm64X"),
+            Self::ChpeX86 => write!(f, "ChpeX86"),
             Self::ArmNT => write!(f, "ArmNT"),
             Self::Ebc => write!(f, "Ebc"),
             Self::X86 => write!(f, "X86"),
@@ -339,6 +793,16 @@ impl fmt::Display for MachineType {
     }
 }
 
+impl MachineType {
+    /// Returns whether images of this machine type mix code of multiple architectures.
+    ///
+    /// The architecture of a code range in such images can be determined with
+    /// [`PDB::architecture_map`](crate::PDB::architecture_map).
+    pub fn is_hybrid(self) -> bool {
+        matches!(self, Self::Arm64EC | Self::Arm64X | Self::ChpeX86)
+    }
+}
+
 impl From<u16> for MachineType {
     fn from(value: u16) -> Self {
         match value {
@@ -348,6 +812,9 @@ impl From<u16> for MachineType {
             0x8664 => Self::Amd64,
             0x1C0 => Self::Arm,
             0xAA64 => Self::Arm64,
+            0xA641 => Self::Arm64EC,
+            0xA64E => Self::Arm64X,
+            0x3A64 => Self::ChpeX86,
             0x1C4 => Self::ArmNT,
             0xEBC => Self::Ebc,
             0x14C => Self::X86,
@@ -376,6 +843,7 @@ impl From<u16> for MachineType {
 /// Information about a module's contribution to a section.
 /// `struct SC` in Microsoft's code:
 /// <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/include/dbicommon.h#L42>
+#[non_exhaustive]
 #[derive(Debug, Copy, Clone)]
 pub struct DBISectionContribution {
     /// Start offset of the section.
@@ -392,9 +860,21 @@ pub struct DBISectionContribution {
     pub data_crc: u32,
     /// CRC of relocations(?)
     pub reloc_crc: u32,
+    /// Index of the section in the object file's COFF section table.
+    ///
+    /// Only version 2 of the section contribution substream, `struct SC2` in Microsoft's code,
+    /// records this index.
+    pub coff_section: Option<u32>,
 }
 
 impl DBISectionContribution {
+    /// Classifies the contributed data into code, data, read-only data or uninitialized data.
+    ///
+    /// See [`SectionCharacteristics::kind`].
+    pub fn kind(&self) -> SectionKind {
+        self.characteristics.kind()
+    }
+
     fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
         let section = buf.parse_u16()?;
         let _padding = buf.parse_u16()?;
@@ -411,6 +891,7 @@ impl DBISectionContribution {
             module,
             data_crc: buf.parse_u32()?,
             reloc_crc: buf.parse_u32()?,
+            coff_section: None,
         })
     }
 }
@@ -501,6 +982,140 @@ impl<'m> Module<'m> {
     pub fn object_file_name(&self) -> Cow<'m, str> {
         self.object_file_name.to_string()
     }
+
+    /// Determines where this module originates from, based on its module and object file names.
+    ///
+    /// The linker encodes library members, import stubs and linker-generated modules in the two
+    /// name strings in different ways. This splits them into their components and strips quotes
+    /// that may remain from response files.
+    pub fn kind(&self) -> ModuleKind<'m> {
+        ModuleKind::parse(self.module_name, self.object_file_name)
+    }
+}
+
+/// The origin of a [`Module`], as determined by [`Module::kind`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModuleKind<'m> {
+    /// An object file passed directly to the linker.
+    Object(RawString<'m>),
+    /// An object file that was linked from a static library.
+    ArchiveMember {
+        /// Path to the library archive.
+        archive: RawString<'m>,
+        /// Path of the object file within the archive, usually the path at which it was compiled.
+        member: RawString<'m>,
+    },
+    /// Import stubs for a DLL, linked from an import library.
+    Import {
+        /// Name of the imported DLL.
+        dll: RawString<'m>,
+        /// Path to the import library, if known.
+        library: Option<RawString<'m>>,
+    },
+    /// A module synthesized by the linker, such as `* Linker *`.
+    Linker(RawString<'m>),
+}
+
+impl<'m> ModuleKind<'m> {
+    fn parse(module_name: RawString<'m>, object_file_name: RawString<'m>) -> Self {
+        let name = normalize_module_path(module_name.as_bytes());
+        let object = normalize_module_path(object_file_name.as_bytes());
+
+        if name.starts_with(b"* ") && name.ends_with(b" *") {
+            return Self::Linker(name.into());
+        }
+
+        if let Some(dll) = name.strip_prefix(b"Import:") {
+            return Self::Import {
+                dll: dll.into(),
+                library: Some(object)
+                    .filter(|object| !object.is_empty())
+                    .map(RawString::from),
+            };
+        }
+
+        // lld and thin archives encode both paths in the module name as `archive(member)`. The
+        // archive path may contain parentheses itself, so split at the last opening parenthesis.
+        if let Some(close) = name
+            .len()
+            .checked_sub(1)
+            .filter(|&close| name[close] == b')')
+        {
+            let open = name[..close].iter().rposition(|&b| b == b'(');
+            if let Some(open) = open.filter(|&open| open > 0 && is_archive(&name[..open])) {
+                return Self::ArchiveMember {
+                    archive: name[..open].into(),
+                    member: name[open + 1..close].into(),
+                };
+            }
+        }
+
+        if object.is_empty() || object.eq_ignore_ascii_case(name) || !is_archive(object) {
+            return Self::Object(name.into());
+        }
+
+        if !name.contains(&b'\\') && !name.contains(&b'/') && ends_with_ignore_case(name, b".dll") {
+            Self::Import {
+                dll: name.into(),
+                library: Some(object.into()),
+            }
+        } else {
+            Self::ArchiveMember {
+                archive: object.into(),
+                member: name.into(),
+            }
+        }
+    }
+
+    /// Returns the file name of the object file or DLL, without its directory.
+    pub fn file_name(&self) -> RawString<'m> {
+        let path = match *self {
+            Self::Object(path) => path,
+            Self::ArchiveMember { member, .. } => member,
+            Self::Import { dll, .. } => dll,
+            Self::Linker(name) => return name,
+        };
+
+        let bytes = path.as_bytes();
+        match bytes.iter().rposition(|&b| b == b'\\' || b == b'/') {
+            Some(index) => bytes[index + 1..].into(),
+            None => path,
+        }
+    }
+}
+
+/// Strips whitespace and a pair of enclosing quotes, which response files may leave in names.
+fn normalize_module_path(path: &[u8]) -> &[u8] {
+    let path = trim_whitespace(path);
+    match path {
+        [b'"', inner @ .., b'"'] => trim_whitespace(inner),
+        _ => path,
+    }
+}
+
+fn trim_whitespace(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+fn ends_with_ignore_case(path: &[u8], suffix: &[u8]) -> bool {
+    path.len() >= suffix.len() && path[path.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+}
+
+fn is_archive(path: &[u8]) -> bool {
+    ends_with_ignore_case(path, b".lib") || ends_with_ignore_case(path, b".a")
 }
 
 /// A `ModuleIter` iterates over the modules in the DBI section, producing `Module`s.
@@ -557,12 +1172,25 @@ impl From<u32> for DBISectionContributionStreamVersion {
 pub struct DBISectionContributionIter<'c> {
     buf: ParseBuffer<'c>,
     version: DBISectionContributionStreamVersion,
+    empty: DegenerateRecordPolicy,
 }
 
 impl<'c> DBISectionContributionIter<'c> {
     fn parse(mut buf: ParseBuffer<'c>) -> Result<Self> {
         let version = buf.parse_u32()?.into();
-        Ok(Self { buf, version })
+        Ok(Self {
+            buf,
+            version,
+            empty: DegenerateRecordPolicy::Keep,
+        })
+    }
+
+    /// Sets how contributions with a size of zero are handled.
+    ///
+    /// By default, empty contributions are returned like any other contribution.
+    pub fn empty_contributions(mut self, policy: DegenerateRecordPolicy) -> Self {
+        self.empty = policy;
+        self
     }
 }
 
@@ -571,23 +1199,97 @@ impl<'c> FallibleIterator for DBISectionContributionIter<'c> {
     type Error = Error;
 
     fn next(&mut self) -> result::Result<Option<Self::Item>, Self::Error> {
-        // see if we're at EOF
-        if self.buf.is_empty() {
-            return Ok(None);
-        }
+        while !self.buf.is_empty() {
+            let mut contribution = DBISectionContribution::parse(&mut self.buf)?;
+            if self.version == DBISectionContributionStreamVersion::V2 {
+                contribution.coff_section = Some(self.buf.parse_u32()?);
+            }
 
-        let contribution = DBISectionContribution::parse(&mut self.buf)?;
-        if self.version == DBISectionContributionStreamVersion::V2 {
-            self.buf.parse_u32()?;
+            if contribution.size == 0
+                && !self
+                    .empty
+                    .keep(DegenerateRecordKind::EmptyContribution, contribution.offset)?
+            {
+                continue;
+            }
+
+            return Ok(Some(contribution));
         }
-        Ok(Some(contribution))
+
+        Ok(None)
     }
 }
 
-/// A `DbgDataHdr`, which contains a series of (optional) MSF stream numbers.
-#[derive(Debug, Copy, Clone)]
-#[allow(dead_code)] // reason = "unused fields added for completeness"
-pub(crate) struct DBIExtraStreams {
+/// A section contribution joined with the name of the module it belongs to.
+///
+/// Returned by [`DebugInformation::module_contributions`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleContribution<'m> {
+    /// The range of contributed bytes, which lies within a single section.
+    pub range: Range<PdbInternalSectionOffset>,
+    /// The characteristics of the contributed data.
+    pub characteristics: SectionCharacteristics,
+    /// Index of the module in [`DebugInformation::modules`].
+    pub module: usize,
+    /// Name of the module, or `None` if the module index is out of range.
+    pub module_name: Option<RawString<'m>>,
+}
+
+/// An iterator over section contributions joined with module names.
+///
+/// Created by [`DebugInformation::module_contributions`].
+#[derive(Debug)]
+pub struct ModuleContributionIter<'m> {
+    contributions: DBISectionContributionIter<'m>,
+    module_names: Vec<RawString<'m>>,
+}
+
+impl ModuleContributionIter<'_> {
+    /// Sets how contributions with a size of zero are handled.
+    ///
+    /// By default, empty contributions are returned like any other contribution.
+    pub fn empty_contributions(mut self, policy: DegenerateRecordPolicy) -> Self {
+        self.contributions = self.contributions.empty_contributions(policy);
+        self
+    }
+}
+
+impl<'m> FallibleIterator for ModuleContributionIter<'m> {
+    type Item = ModuleContribution<'m>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let contribution = match self.contributions.next()? {
+            Some(contribution) => contribution,
+            None => return Ok(None),
+        };
+
+        let start = contribution.offset;
+        let end = PdbInternalSectionOffset {
+            offset: start.offset.saturating_add(contribution.size),
+            ..start
+        };
+
+        Ok(Some(ModuleContribution {
+            range: start..end,
+            characteristics: contribution.characteristics,
+            module: contribution.module,
+            module_name: self.module_names.get(contribution.module).copied(),
+        }))
+    }
+}
+
+/// The indexes of additional streams, stored in the optional debug header of the DBI stream.
+///
+/// This is the `DbgDataHdr` of the reference implementation. Each field is `None` if the PDB does
+/// not contain the stream. Use [`PDB::raw_stream`](crate::PDB::raw_stream) to read streams that
+/// this crate does not parse.
+///
+/// Returned by [`DebugInformation::optional_debug_header`] and
+/// [`PDB::optional_debug_header`](crate::PDB::optional_debug_header).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OptionalDebugHeader {
     // The struct itself is defined at:
     //    https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.h#L250-L274
     // It's just an array of stream numbers; `u16`s where 0xffff means "no stream".
@@ -596,45 +1298,33 @@ pub(crate) struct DBIExtraStreams {
     //    https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/langapi/include/pdb.h#L439-L449
     // We'll map those to fields.
     //
-    // The struct itself can be truncated. This is an internal struct; we'll treat missing fields as
-    // StreamIndex::none() even if it's a short read, so long as the short read stops on a u16 boundary.
-    pub fpo: StreamIndex,
-    pub exception: StreamIndex,
-    pub fixup: StreamIndex,
-    pub omap_to_src: StreamIndex,
-    pub omap_from_src: StreamIndex,
-    pub section_headers: StreamIndex,
-    pub token_rid_map: StreamIndex,
-    pub xdata: StreamIndex,
-    pub pdata: StreamIndex,
-    pub framedata: StreamIndex,
-    pub original_section_headers: StreamIndex,
-}
-
-impl DBIExtraStreams {
-    pub(crate) fn new(debug_info: &DebugInformation<'_>) -> Result<Self> {
-        // calculate the location of the extra stream information
-        let header = debug_info.header;
-        let offset = debug_info.header_len
-            + (header.module_list_size
-                + header.section_contribution_size
-                + header.section_map_size
-                + header.file_info_size
-                + header.type_server_map_size
-                + header.ec_substream_size) as usize;
-
-        // seek
-        let mut buf = debug_info.stream.parse_buffer();
-        buf.take(offset)?;
-
-        // grab that section as bytes
-        let bytes = buf.take(header.debug_header_size as _)?;
-
-        // parse those bytes
-        let mut extra_streams_buf = ParseBuffer::from(bytes);
-        Self::parse(&mut extra_streams_buf)
-    }
+    // The struct itself can be truncated. We'll treat missing fields as `None` even if it's a
+    // short read, so long as the short read stops on a u16 boundary.
+    /// The `FPO_DATA` records of 32-bit images.
+    pub fpo: Option<StreamIndex>,
+    /// The exception data of the image.
+    pub exception: Option<StreamIndex>,
+    /// The fixup records of the image.
+    pub fixup: Option<StreamIndex>,
+    /// The OMAP table translating addresses of the image to the original image.
+    pub omap_to_src: Option<StreamIndex>,
+    /// The OMAP table translating addresses of the original image to the image.
+    pub omap_from_src: Option<StreamIndex>,
+    /// The section headers of the image.
+    pub section_headers: Option<StreamIndex>,
+    /// The map from CLR metadata tokens to record ids.
+    pub token_rid_map: Option<StreamIndex>,
+    /// A copy of the `.xdata` section of the image.
+    pub xdata: Option<StreamIndex>,
+    /// A copy of the `.pdata` section of the image.
+    pub pdata: Option<StreamIndex>,
+    /// The new FPO records (`FRAMEDATA`) of 32-bit images.
+    pub framedata: Option<StreamIndex>,
+    /// The section headers of the original image, if the image was reordered by a post-link tool.
+    pub original_section_headers: Option<StreamIndex>,
+}
 
+impl OptionalDebugHeader {
     pub(crate) fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
         // short reads are okay, as are long reads -- this struct is actually an array
         // what's _not_ okay are
@@ -642,11 +1332,12 @@ impl DBIExtraStreams {
             return Err(Error::InvalidStreamLength("DbgDataHdr"));
         }
 
-        fn next_index(buf: &mut ParseBuffer<'_>) -> Result<StreamIndex> {
+        fn next_index(buf: &mut ParseBuffer<'_>) -> Result<Option<StreamIndex>> {
             if buf.is_empty() {
-                Ok(StreamIndex::none())
+                Ok(None)
             } else {
-                buf.parse()
+                let index: StreamIndex = buf.parse()?;
+                Ok(Some(index).filter(|index| index.is_some()))
             }
         }
 
@@ -671,22 +1362,177 @@ mod tests {
     use crate::dbi::*;
 
     #[test]
-    fn test_dbi_extra_streams() {
+    fn test_language_summary_unknown() {
+        let mut summary = LanguageSummary::default();
+        for value in [0x03, 0x13, 0x16, 0x13] {
+            summary.add(SourceLanguage::from(value));
+        }
+        summary.unknown += 1;
+
+        assert_eq!(summary.count(SourceLanguage::Masm), 1);
+        assert_eq!(summary.count(SourceLanguage::Unknown(0x13)), 2);
+        assert_eq!(summary.count(SourceLanguage::Unknown(0x16)), 1);
+        assert_eq!(summary.total(), 5);
+        assert_eq!(SourceLanguage::Unknown(0x13).to_string(), "Unknown(0x13)");
+    }
+
+    #[test]
+    fn test_optional_debug_header() {
         let bytes = vec![0xff, 0xff, 0x01, 0x02, 0x03, 0x04, 0xff, 0xff, 0x05, 0x06];
 
         let mut buf = ParseBuffer::from(bytes.as_slice());
-        let extra_streams = DBIExtraStreams::parse(&mut buf).expect("parse");
+        let header = OptionalDebugHeader::parse(&mut buf).expect("parse");
 
         // check readback
-        assert_eq!(extra_streams.fpo, StreamIndex::none());
-        assert_eq!(extra_streams.exception, StreamIndex(0x0201));
-        assert_eq!(extra_streams.fixup, StreamIndex(0x0403));
-        assert_eq!(extra_streams.omap_to_src, StreamIndex::none());
-        assert_eq!(extra_streams.omap_from_src, StreamIndex(0x0605));
-
-        // check that short reads => StreamIndex::none()
-        assert_eq!(extra_streams.section_headers, StreamIndex::none());
-        assert_eq!(extra_streams.token_rid_map, StreamIndex::none());
-        assert_eq!(extra_streams.original_section_headers, StreamIndex::none());
+        assert_eq!(header.fpo, None);
+        assert_eq!(header.exception, Some(StreamIndex(0x0201)));
+        assert_eq!(header.fixup, Some(StreamIndex(0x0403)));
+        assert_eq!(header.omap_to_src, None);
+        assert_eq!(header.omap_from_src, Some(StreamIndex(0x0605)));
+
+        // check that short reads => None
+        assert_eq!(header.section_headers, None);
+        assert_eq!(header.token_rid_map, None);
+        assert_eq!(header.original_section_headers, None);
+    }
+
+    fn contribution(section: u16, offset: u32, size: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&section.to_le_bytes());
+        data.extend_from_slice(&[0; 2]); // padding
+        data.extend_from_slice(&offset.to_le_bytes());
+        data.extend_from_slice(&size.to_le_bytes());
+        data.extend_from_slice(&[0; 16]); // characteristics, module, padding, crcs
+        data
+    }
+
+    #[test]
+    fn test_empty_contributions() {
+        let mut data = (0xeffe_0000u32 + 19_970_605).to_le_bytes().to_vec();
+        data.extend(contribution(1, 0x10, 0x20));
+        data.extend(contribution(1, 0x30, 0));
+        data.extend(contribution(2, 0x00, 0x08));
+
+        let iter = || DBISectionContributionIter::parse(data.as_slice().into()).expect("parse");
+
+        let sizes: Vec<_> = iter().map(|c| Ok(c.size)).collect().expect("keep");
+        assert_eq!(sizes, [0x20, 0, 0x08]);
+
+        let sizes: Vec<_> = iter()
+            .empty_contributions(DegenerateRecordPolicy::Skip)
+            .map(|c| Ok(c.size))
+            .collect()
+            .expect("skip");
+        assert_eq!(sizes, [0x20, 0x08]);
+
+        let sections: Vec<_> = iter().map(|c| Ok(c.coff_section)).collect().expect("v60");
+        assert_eq!(sections, [None, None, None]);
+
+        let mut contributions = iter().empty_contributions(DegenerateRecordPolicy::Report);
+        assert!(contributions.next().expect("first").is_some());
+        assert!(matches!(
+            contributions.next(),
+            Err(Error::DegenerateRecord(
+                DegenerateRecordKind::EmptyContribution,
+                PdbInternalSectionOffset {
+                    section: 1,
+                    offset: 0x30
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_contributions_v2() {
+        let mut data = (0xeffe_0000u32 + 20_140_516).to_le_bytes().to_vec();
+        data.extend(contribution(1, 0x10, 0x20));
+        data.extend(&3u32.to_le_bytes());
+        data.extend(contribution(2, 0x00, 0x08));
+        data.extend(&1u32.to_le_bytes());
+
+        let contributions: Vec<_> = DBISectionContributionIter::parse(data.as_slice().into())
+            .expect("parse")
+            .map(|c| Ok((c.offset, c.size, c.coff_section)))
+            .collect()
+            .expect("collect");
+
+        assert_eq!(
+            contributions,
+            [
+                (PdbInternalSectionOffset::new(1, 0x10), 0x20, Some(3)),
+                (PdbInternalSectionOffset::new(2, 0x00), 0x08, Some(1)),
+            ]
+        );
+    }
+
+    fn kind<'a>(module_name: &'a str, object_file_name: &'a str) -> ModuleKind<'a> {
+        ModuleKind::parse(module_name.into(), object_file_name.into())
+    }
+
+    #[test]
+    fn test_module_kind() {
+        assert_eq!(
+            kind("c:\\self\\foo.obj", "c:\\self\\foo.obj"),
+            ModuleKind::Object("c:\\self\\foo.obj".into())
+        );
+        assert_eq!(
+            kind("f:\\objr\\fltused.obj", "C:\\LIB\\LIBCMT.lib"),
+            ModuleKind::ArchiveMember {
+                archive: "C:\\LIB\\LIBCMT.lib".into(),
+                member: "f:\\objr\\fltused.obj".into(),
+            }
+        );
+        assert_eq!(
+            kind("KERNEL32.dll", "C:\\x64\\kernel32.lib"),
+            ModuleKind::Import {
+                dll: "KERNEL32.dll".into(),
+                library: Some("C:\\x64\\kernel32.lib".into()),
+            }
+        );
+        assert_eq!(
+            kind("Import:USER32.dll", ""),
+            ModuleKind::Import {
+                dll: "USER32.dll".into(),
+                library: None,
+            }
+        );
+        assert_eq!(
+            kind("* Linker *", ""),
+            ModuleKind::Linker("* Linker *".into())
+        );
+    }
+
+    #[test]
+    fn test_module_kind_combined() {
+        let module = kind("/build/libfoo.a(bar.o)", "/build/libfoo.a(bar.o)");
+        assert_eq!(
+            module,
+            ModuleKind::ArchiveMember {
+                archive: "/build/libfoo.a".into(),
+                member: "bar.o".into(),
+            }
+        );
+        assert_eq!(module.file_name(), "bar.o".into());
+
+        // parentheses in directory names do not indicate archives
+        let module = kind("C:\\Program Files (x86)\\foo.obj", "");
+        assert_eq!(module.file_name(), "foo.obj".into());
+
+        let module = kind("C:\\Program Files (x86)\\lib\\foo.lib(bar.obj)", "");
+        assert_eq!(
+            module,
+            ModuleKind::ArchiveMember {
+                archive: "C:\\Program Files (x86)\\lib\\foo.lib".into(),
+                member: "bar.obj".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_module_kind_quoted() {
+        assert_eq!(
+            kind(" \"c:\\my dir\\foo.obj\" ", "\"c:\\my dir\\foo.obj\""),
+            ModuleKind::Object("c:\\my dir\\foo.obj".into())
+        );
     }
 }
@@ -0,0 +1,254 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Bulk export of line information as flat rows.
+
+use core::fmt;
+use core::result;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::common::*;
+use crate::pdb::PDB;
+use crate::source::{Source, SourceError};
+use crate::FallibleIterator;
+
+/// A line record of a PDB, flattened into a single row.
+///
+/// Rows are produced by [`PDB::export_lines`] and passed to a [`LineSink`]. All fields are scalars
+/// or strings, so rows map directly onto columnar formats such as CSV or Parquet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LineRow<'a> {
+    /// The address of the code covered by the line.
+    pub rva: Rva,
+    /// The size of the code covered by the line in bytes.
+    pub length: u32,
+    /// The path of the source file as recorded in the PDB.
+    pub file: RawString<'a>,
+    /// The line number in the source file.
+    pub line: u32,
+    /// The column number, if the PDB records columns.
+    pub column: Option<u32>,
+    /// The name of the function containing the code, if known.
+    pub function: Option<RawString<'a>>,
+    /// Index of the module in [`DebugInformation::modules`](crate::DebugInformation::modules)
+    /// declaring the line.
+    pub module: usize,
+}
+
+/// A destination for rows exported by [`PDB::export_lines`].
+///
+/// Implement this to write rows to a database, a columnar file format or any other storage.
+/// [`CsvLineSink`] writes rows as CSV.
+pub trait LineSink: fmt::Debug {
+    /// Writes a single row.
+    fn write_row(&mut self, row: &LineRow<'_>) -> result::Result<(), SourceError>;
+
+    /// Flushes any buffered rows after the last row has been written.
+    ///
+    /// The default implementation does nothing.
+    fn finish(&mut self) -> result::Result<(), SourceError> {
+        Ok(())
+    }
+}
+
+/// A [`LineSink`] writing rows as comma-separated values.
+///
+/// The first line is a header naming the columns `rva,length,file,line,column,function,module`.
+/// Addresses are written in decimal. Missing columns and functions are left empty, and strings are
+/// quoted if necessary. Invalid UTF-8 sequences in strings are replaced with `U+FFFD REPLACEMENT
+/// CHARACTER`.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct CsvLineSink<W> {
+    writer: W,
+    header: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> CsvLineSink<W> {
+    /// Creates a sink writing to `writer`.
+    ///
+    /// Rows are written one at a time, so wrap unbuffered writers in an [`io::BufWriter`].
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header: false,
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Writes a string as CSV field, quoting it if it contains separators, quotes or line breaks.
+#[cfg(feature = "std")]
+fn write_csv_field(writer: &mut impl io::Write, value: RawString<'_>) -> io::Result<()> {
+    let value = value.to_string();
+    if !value.contains(&[',', '"', '\n', '\r'][..]) {
+        return writer.write_all(value.as_bytes());
+    }
+
+    write!(writer, "\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write + fmt::Debug> LineSink for CsvLineSink<W> {
+    fn write_row(&mut self, row: &LineRow<'_>) -> io::Result<()> {
+        let writer = &mut self.writer;
+        if !self.header {
+            writer.write_all(b"rva,length,file,line,column,function,module\n")?;
+            self.header = true;
+        }
+
+        write!(writer, "{},{},", row.rva.0, row.length)?;
+        write_csv_field(writer, row.file)?;
+        write!(writer, ",{},", row.line)?;
+        if let Some(column) = row.column {
+            write!(writer, "{}", column)?;
+        }
+        writer.write_all(b",")?;
+        if let Some(function) = row.function {
+            write_csv_field(writer, function)?;
+        }
+        writeln!(writer, ",{}", row.module)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+    /// Writes every line record of this PDB to `sink` as a flat row.
+    ///
+    /// Rows are written module by module, and ordered by address within each module. Each row is
+    /// joined with the path of its source file and the function containing its code, as returned
+    /// by [`Functions::lookup`](crate::Functions::lookup). Lines whose code was eliminated from the
+    /// image are skipped, and lines split by the address map are written as one row per
+    /// contiguous range. [`LineSink::finish`] is called after the last row.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source` or the sink
+    /// * Errors reading the debug information, address map or line programs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let mut sink = pdb::CsvLineSink::new(Vec::new());
+    /// pdb.export_lines(&mut sink)?;
+    /// let csv = String::from_utf8(sink.into_inner()).expect("utf-8");
+    /// assert!(csv.starts_with("rva,length,file,line,column,function,module\n"));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn export_lines<K>(&self, sink: &mut K) -> Result<()>
+    where
+        K: LineSink + ?Sized,
+    {
+        self.export_lines_with_progress(sink, |_| ())
+    }
+
+    /// Writes every line record of this PDB to `sink` like [`export_lines`](Self::export_lines),
+    /// reporting progress along the way.
+    ///
+    /// `progress` is invoked periodically with the number of lines read and the number of bytes
+    /// of line information processed, as well as once after the sink has finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`export_lines`](Self::export_lines).
+    pub fn export_lines_with_progress<K, P>(&self, sink: &mut K, progress: P) -> Result<()>
+    where
+        K: LineSink + ?Sized,
+        P: FnMut(Progress),
+    {
+        let address_map = self.address_map()?;
+        let functions = self.functions()?;
+        let strings = match self.string_table() {
+            Ok(strings) => Some(strings),
+            Err(Error::StreamNameNotFound) => None,
+            Err(error) => return Err(error),
+        };
+
+        let total = functions
+            .modules()
+            .iter()
+            .map(|(_, info)| info.lines_len())
+            .sum();
+        let mut reporter = ProgressReporter::new(progress, total);
+        let mut offset = 0;
+
+        for (module, info) in functions.modules() {
+            let program = info.line_program()?;
+            let mut lines = program.lines_sorted(&address_map)?;
+            while let Some(line) = lines.next()? {
+                reporter.record(offset);
+                let file = program.get_file_info(line.file_index)?;
+                let file = file.name(strings.as_ref())?.unwrap_or_default();
+
+                let start = match line.offset.to_internal_rva(&address_map) {
+                    Some(start) => start,
+                    None => continue,
+                };
+
+                let length = line.length.unwrap_or(0);
+                let mut ranges = address_map.rva_ranges(start..start.saturating_add(length));
+                let mut first = ranges.next();
+                if first.is_none() {
+                    // Empty lines have no range, but still have an address.
+                    first = line.offset.to_rva(&address_map).map(|rva| rva..rva);
+                }
+
+                for range in first.into_iter().chain(ranges) {
+                    let function = functions.lookup(range.start)?;
+                    sink.write_row(&LineRow {
+                        rva: range.start,
+                        length: range.end - range.start,
+                        file,
+                        line: line.line_start,
+                        column: line.column_start,
+                        function: function.map(|function| function.name),
+                        module: *module,
+                    })?;
+                }
+            }
+
+            offset += info.lines_len();
+        }
+
+        sink.finish()?;
+        reporter.finish();
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv_field() {
+        let field = |value: &str| {
+            let mut output = Vec::new();
+            write_csv_field(&mut output, RawString::from(value)).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(field(r"c:\src\main.cpp"), r"c:\src\main.cpp");
+        assert_eq!(field("std::map<int,int>"), "\"std::map<int,int>\"");
+        assert_eq!(field("operator\"\" _x"), "\"operator\"\"\"\" _x\"");
+    }
+}
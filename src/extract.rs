@@ -0,0 +1,292 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Extraction of all strings of a PDB for indexing.
+
+use crate::common::*;
+use crate::pdb::PDB;
+use crate::source::Source;
+use crate::strings::StringTable;
+use crate::tpi::{IdData, TypeData};
+use crate::FallibleIterator;
+
+/// The record a string extracted by [`PDB::extract_strings`] was found in.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StringOrigin {
+    /// An entry of the global string table at the given offset.
+    StringTable(StringRef),
+    /// The name of a named stream.
+    StreamName(StreamIndex),
+    /// The name of the module at the given index.
+    ModuleName(usize),
+    /// The object file or library of the module at the given index.
+    ObjectFileName(usize),
+    /// A source file referenced by the line program of the module at the given index.
+    SourceFile(usize),
+    /// A symbol in the global symbol table.
+    GlobalSymbol(SymbolIndex),
+    /// A symbol in the symbol stream of a module.
+    ModuleSymbol {
+        /// The index of the module.
+        module: usize,
+        /// The symbol within the module.
+        symbol: SymbolIndex,
+    },
+    /// A type record, including the names of members in field lists and unique names.
+    Type(TypeIndex),
+    /// An id record, such as a function id or string id.
+    Id(IdIndex),
+}
+
+/// A string found by [`PDB::extract_strings`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExtractedString<'a> {
+    /// The raw value of the string.
+    pub value: RawString<'a>,
+    /// The record the string was found in.
+    pub origin: StringOrigin,
+}
+
+impl ExtractedString<'_> {
+    /// Returns a 64-bit FNV-1a hash of the string value.
+    ///
+    /// The hash only depends on the bytes of the string, so it is stable across PDBs, platforms
+    /// and versions of this crate. Indexes can use it to store every distinct string once.
+    pub fn content_hash(&self) -> u64 {
+        self.value
+            .as_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+}
+
+/// Returns whether an error indicates a record of a kind this crate does not parse.
+fn is_unsupported(error: &Error) -> bool {
+    error.kind() == ErrorKind::Unsupported
+}
+
+/// Passes a string to the visitor, unless it is empty.
+fn emit<F>(visitor: &mut F, value: RawString<'_>, origin: StringOrigin)
+where
+    F: FnMut(ExtractedString<'_>),
+{
+    if !value.is_empty() {
+        visitor(ExtractedString { value, origin });
+    }
+}
+
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+    /// Passes every string in this PDB to `visitor`, tagged with the record it was found in.
+    ///
+    /// This covers the global string table, the names of streams and modules, the source files of
+    /// all modules, the names of global and module symbols, and the names in type and id records.
+    /// Strings are reported in the order of the streams listed above, and in stream order within
+    /// each stream. The same string is reported once for every record containing it. Empty strings
+    /// are skipped.
+    ///
+    /// Records of kinds this crate cannot parse are skipped, as are the string table and id stream
+    /// if the PDB does not contain them.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * Errors reading or parsing the streams, for instance if the PDB is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// pdb.extract_strings(|string| {
+    ///     if string.value.as_bytes().starts_with(b"std::bad_alloc") {
+    ///         println!("{:016x} {:?}", string.content_hash(), string.origin);
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn extract_strings<F>(&self, mut visitor: F) -> Result<()>
+    where
+        F: FnMut(ExtractedString<'_>),
+    {
+        let strings = match self.string_table() {
+            Ok(strings) => Some(strings),
+            Err(Error::StreamNameNotFound) => None,
+            Err(error) => return Err(error),
+        };
+
+        if let Some(ref strings) = strings {
+            let mut iter = strings.iter();
+            while let Some((offset, value)) = iter.next()? {
+                emit(&mut visitor, value, StringOrigin::StringTable(offset));
+            }
+        }
+
+        let info = self.pdb_information()?;
+        for name in &info.stream_names()? {
+            emit(
+                &mut visitor,
+                name.name,
+                StringOrigin::StreamName(name.stream_id),
+            );
+        }
+
+        self.extract_module_strings(strings.as_ref(), &mut visitor)?;
+
+        let table = self.global_symbols()?;
+        let mut symbols = table.iter();
+        while let Some(symbol) = symbols.next()? {
+            match symbol.parse() {
+                Ok(data) => {
+                    if let Some(name) = data.name() {
+                        emit(
+                            &mut visitor,
+                            name,
+                            StringOrigin::GlobalSymbol(symbol.index()),
+                        );
+                    }
+                }
+                Err(ref error) if is_unsupported(error) => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        self.extract_type_strings(&mut visitor)?;
+        self.extract_id_strings(&mut visitor)
+    }
+
+    /// Extracts the names, source files and symbols of all modules.
+    fn extract_module_strings<F>(
+        &self,
+        strings: Option<&StringTable<'_>>,
+        visitor: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(ExtractedString<'_>),
+    {
+        let dbi = self.debug_information()?;
+        let mut modules = dbi.modules()?;
+        let mut index = 0;
+        while let Some(module) = modules.next()? {
+            emit(
+                visitor,
+                RawString::from(module.module_name().as_bytes()),
+                StringOrigin::ModuleName(index),
+            );
+            emit(
+                visitor,
+                RawString::from(module.object_file_name().as_bytes()),
+                StringOrigin::ObjectFileName(index),
+            );
+
+            if let Some(info) = self.module_info(&module)? {
+                let program = info.line_program()?;
+                let mut files = program.files();
+                while let Some(file) = files.next()? {
+                    if let Some(name) = file.name(strings)? {
+                        emit(visitor, name, StringOrigin::SourceFile(index));
+                    }
+                }
+
+                let mut symbols = info.symbols()?;
+                while let Some(symbol) = symbols.next()? {
+                    let origin = StringOrigin::ModuleSymbol {
+                        module: index,
+                        symbol: symbol.index(),
+                    };
+                    match symbol.parse() {
+                        Ok(data) => {
+                            if let Some(name) = data.name() {
+                                emit(visitor, name, origin);
+                            }
+                        }
+                        Err(ref error) if is_unsupported(error) => (),
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+
+            index += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the names of all type records, including the members of field lists.
+    fn extract_type_strings<F>(&self, visitor: &mut F) -> Result<()>
+    where
+        F: FnMut(ExtractedString<'_>),
+    {
+        let types = self.type_information()?;
+        let mut iter = types.iter();
+        while let Some(item) = iter.next()? {
+            let origin = StringOrigin::Type(item.index());
+            let data = match item.parse() {
+                Ok(data) => data,
+                Err(ref error) if is_unsupported(error) => continue,
+                Err(error) => return Err(error),
+            };
+
+            let unique_name = match data {
+                TypeData::Class(ref class) => class.unique_name,
+                TypeData::Union(ref union) => union.unique_name,
+                TypeData::Enumeration(ref enumeration) => enumeration.unique_name,
+                TypeData::FieldList(ref list) => {
+                    for field in &list.fields {
+                        if let Some(name) = field.name() {
+                            emit(visitor, name, origin);
+                        }
+                    }
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(name) = data.name() {
+                emit(visitor, name, origin);
+            }
+            if let Some(name) = unique_name {
+                emit(visitor, name, origin);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the names of all id records.
+    fn extract_id_strings<F>(&self, visitor: &mut F) -> Result<()>
+    where
+        F: FnMut(ExtractedString<'_>),
+    {
+        let ids = match self.id_information() {
+            Ok(ids) => ids,
+            Err(Error::StreamNotFound(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        let mut iter = ids.iter();
+        while let Some(item) = iter.next()? {
+            let name = match item.parse() {
+                Ok(IdData::Function(data)) => data.name,
+                Ok(IdData::MemberFunction(data)) => data.name,
+                Ok(IdData::String(data)) => data.name,
+                Ok(_) => continue,
+                Err(ref error) if is_unsupported(error) => continue,
+                Err(error) => return Err(error),
+            };
+            emit(visitor, name, StringOrigin::Id(item.index()));
+        }
+
+        Ok(())
+    }
+}
@@ -7,8 +7,11 @@
 
 //! Facilities for parsing legacy FPO and FrameData streams.
 
-use std::cmp::Ordering;
-use std::fmt;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+
+use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
 use crate::msf::Stream;
@@ -83,6 +86,7 @@ impl fmt::Display for FrameType {
 ///
 /// [`struct tagFRAMEDATA`]: https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L4635
 #[repr(C)]
+#[derive(Clone, Copy)]
 struct NewFrameData {
     code_start: u32,
     code_size: u32,
@@ -97,35 +101,35 @@ struct NewFrameData {
 
 impl NewFrameData {
     pub fn code_start(&self) -> PdbInternalRva {
-        PdbInternalRva(u32::from_le(self.code_start))
+        PdbInternalRva(self.code_start)
     }
 
     pub fn code_size(&self) -> u32 {
-        u32::from_le(self.code_size)
+        self.code_size
     }
 
     pub fn locals_size(&self) -> u32 {
-        u32::from_le(self.locals_size)
+        self.locals_size
     }
 
     pub fn params_size(&self) -> u32 {
-        u32::from_le(self.params_size)
+        self.params_size
     }
 
     pub fn max_stack_size(&self) -> u32 {
-        u32::from_le(self.max_stack_size)
+        self.max_stack_size
     }
 
     pub fn frame_func(&self) -> StringRef {
-        StringRef(u32::from_le(self.frame_func))
+        StringRef(self.frame_func)
     }
 
     pub fn prolog_size(&self) -> u16 {
-        u16::from_le(self.prolog_size)
+        self.prolog_size
     }
 
     pub fn saved_regs_size(&self) -> u16 {
-        u16::from_le(self.saved_regs_size)
+        self.saved_regs_size
     }
 
     pub fn has_seh(&self) -> bool {
@@ -141,7 +145,27 @@ impl NewFrameData {
     }
 
     fn flags(&self) -> u32 {
-        u32::from_le(self.flags)
+        self.flags
+    }
+}
+
+impl<'t> TryFromCtx<'t, Endian> for NewFrameData {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let mut offset = 0;
+        let data = Self {
+            code_start: this.gread_with(&mut offset, le)?,
+            code_size: this.gread_with(&mut offset, le)?,
+            locals_size: this.gread_with(&mut offset, le)?,
+            params_size: this.gread_with(&mut offset, le)?,
+            max_stack_size: this.gread_with(&mut offset, le)?,
+            frame_func: this.gread_with(&mut offset, le)?,
+            prolog_size: this.gread_with(&mut offset, le)?,
+            saved_regs_size: this.gread_with(&mut offset, le)?,
+            flags: this.gread_with(&mut offset, le)?,
+        };
+        Ok((data, offset))
     }
 }
 
@@ -191,6 +215,7 @@ impl fmt::Debug for NewFrameData {
 /// } FPO_DATA;
 /// ```
 #[repr(C)]
+#[derive(Clone, Copy)]
 struct OldFrameData {
     code_start: u32,
     code_size: u32,
@@ -201,19 +226,19 @@ struct OldFrameData {
 
 impl OldFrameData {
     pub fn code_start(&self) -> PdbInternalRva {
-        PdbInternalRva(u32::from_le(self.code_start))
+        PdbInternalRva(self.code_start)
     }
 
     pub fn code_size(&self) -> u32 {
-        u32::from_le(self.code_size)
+        self.code_size
     }
 
     pub fn locals_size(&self) -> u32 {
-        u32::from_le(self.locals_size)
+        self.locals_size
     }
 
     pub fn params_size(&self) -> u16 {
-        u16::from_le(self.params_size)
+        self.params_size
     }
 
     pub fn prolog_size(&self) -> u16 {
@@ -244,7 +269,23 @@ impl OldFrameData {
     }
 
     fn attributes(&self) -> u16 {
-        u16::from_le(self.attributes)
+        self.attributes
+    }
+}
+
+impl<'t> TryFromCtx<'t, Endian> for OldFrameData {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let mut offset = 0;
+        let data = Self {
+            code_start: this.gread_with(&mut offset, le)?,
+            code_size: this.gread_with(&mut offset, le)?,
+            locals_size: this.gread_with(&mut offset, le)?,
+            params_size: this.gread_with(&mut offset, le)?,
+            attributes: this.gread_with(&mut offset, le)?,
+        };
+        Ok((data, offset))
     }
 }
 
@@ -318,8 +359,8 @@ pub struct FrameData {
     pub program: Option<StringRef>,
 }
 
-impl From<&'_ OldFrameData> for FrameData {
-    fn from(data: &OldFrameData) -> Self {
+impl From<OldFrameData> for FrameData {
+    fn from(data: OldFrameData) -> Self {
         Self {
             ty: data.frame_type(),
             code_start: data.code_start(),
@@ -338,8 +379,8 @@ impl From<&'_ OldFrameData> for FrameData {
     }
 }
 
-impl From<&'_ NewFrameData> for FrameData {
-    fn from(data: &NewFrameData) -> Self {
+impl From<NewFrameData> for FrameData {
+    fn from(data: NewFrameData) -> Self {
         Self {
             ty: FrameType::FrameData,
             code_start: data.code_start(),
@@ -361,12 +402,32 @@ impl From<&'_ NewFrameData> for FrameData {
 /// Iterator over entries in a [`FrameTable`].
 #[derive(Debug, Default)]
 pub struct FrameDataIter<'t> {
-    old_frames: &'t [OldFrameData],
-    new_frames: &'t [NewFrameData],
+    old_frames: RecordTable<'t, OldFrameData>,
+    new_frames: RecordTable<'t, NewFrameData>,
     old_index: usize,
     new_index: usize,
 }
 
+impl<'t> FrameDataIter<'t> {
+    /// Creates an iterator over the records of a `DEBUG_S_FRAMEDATA` module subsection.
+    pub(crate) fn from_subsection(data: &'t [u8]) -> Result<Self> {
+        // Object files prefix the records with a relocation pointer. Linkers usually retain it when
+        // copying the subsection into the module stream.
+        let mut buf = ParseBuffer::from(data);
+        if buf.len() % mem::size_of::<NewFrameData>() != 0 {
+            buf.parse_u32()?; // relocation pointer
+        }
+
+        let new_frames = RecordTable::new(buf.take(buf.len())?)
+            .ok_or(Error::InvalidStreamLength("DEBUG_S_FRAMEDATA"))?;
+
+        Ok(Self {
+            new_frames,
+            ..Default::default()
+        })
+    }
+}
+
 impl FallibleIterator for FrameDataIter<'_> {
     type Item = FrameData;
     type Error = Error;
@@ -448,11 +509,14 @@ impl AddrRange for NewFrameData {
 }
 
 /// Searches for a frame data entry covering the given `PdbInternalRva`.
-fn binary_search_by_rva<R: AddrRange>(frames: &[R], rva: PdbInternalRva) -> usize {
+fn binary_search_by_rva<'t, R>(frames: RecordTable<'t, R>, rva: PdbInternalRva) -> usize
+where
+    R: AddrRange + TryFromCtx<'t, Endian, Error = scroll::Error>,
+{
     match frames.binary_search_by_key(&rva, |f| f.start()) {
         Ok(index) => index,
         Err(index) => {
-            if index > 0 && frames[index - 1].contains(rva) {
+            if index > 0 && frames.get(index - 1).is_some_and(|f| f.contains(rva)) {
                 index - 1
             } else {
                 index
@@ -480,7 +544,7 @@ fn binary_search_by_rva<R: AddrRange>(frames: &[R], rva: PdbInternalRva) -> usiz
 /// #
 /// # fn test() -> pdb::Result<()> {
 /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = PDB::open(source)?;
+/// let pdb = PDB::open(source)?;
 ///
 /// // Read the frame table once and reuse it
 /// let frame_table = pdb.frame_table()?;
@@ -506,13 +570,13 @@ impl<'s> FrameTable<'s> {
         new_stream: Option<Stream<'s>>,
     ) -> Result<Self> {
         if let Some(ref stream) = old_stream {
-            if cast_aligned::<OldFrameData>(stream.as_slice()).is_none() {
+            if RecordTable::<OldFrameData>::new(stream.as_slice()).is_none() {
                 return Err(Error::InvalidStreamLength("FrameData"));
             }
         }
 
         if let Some(ref stream) = new_stream {
-            if cast_aligned::<NewFrameData>(stream.as_slice()).is_none() {
+            if RecordTable::<NewFrameData>::new(stream.as_slice()).is_none() {
                 return Err(Error::InvalidStreamLength("FPO"));
             }
         }
@@ -562,19 +626,19 @@ impl<'s> FrameTable<'s> {
         self.new_frames().is_empty() && self.old_frames().is_empty()
     }
 
-    fn old_frames(&self) -> &[OldFrameData] {
+    fn old_frames(&self) -> RecordTable<'_, OldFrameData> {
         match self.old_stream {
-            // alignment checked during parsing
-            Some(ref stream) => cast_aligned(stream.as_slice()).unwrap(),
-            None => &[],
+            // size checked during parsing
+            Some(ref stream) => RecordTable::new(stream.as_slice()).unwrap(),
+            None => RecordTable::empty(),
         }
     }
 
-    fn new_frames(&self) -> &[NewFrameData] {
+    fn new_frames(&self) -> RecordTable<'_, NewFrameData> {
         match self.new_stream {
-            // alignment checked during parsing
-            Some(ref stream) => cast_aligned(stream.as_slice()).unwrap(),
-            None => &[],
+            // size checked during parsing
+            Some(ref stream) => RecordTable::new(stream.as_slice()).unwrap(),
+            None => RecordTable::empty(),
         }
     }
 }
@@ -583,7 +647,7 @@ impl<'s> FrameTable<'s> {
 mod tests {
     use super::*;
 
-    use std::mem;
+    use core::mem;
 
     #[test]
     fn test_new_frame_data() {
@@ -20,7 +20,7 @@
 //! #
 //! # fn test() -> pdb::Result<usize> {
 //! let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-//! let mut pdb = pdb::PDB::open(file)?;
+//! let pdb = pdb::PDB::open(file)?;
 //!
 //! let symbol_table = pdb.global_symbols()?;
 //! let address_map = pdb.address_map()?;
@@ -44,11 +44,20 @@
 //! # assert!(test().expect("test") > 2000);
 //! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 // modules
+mod breakpad;
+mod cache;
 mod common;
+#[cfg(any(feature = "zstd", feature = "gzip"))]
+mod compressed;
 mod dbi;
+mod export;
+mod extract;
 mod framedata;
 mod modi;
 mod msf;
@@ -56,24 +65,42 @@ mod omap;
 mod pdb;
 mod pdbi;
 mod pe;
+mod process;
+mod sample;
+mod search;
 mod source;
 mod strings;
 mod symbol;
+mod sync;
 mod tpi;
+mod unwind;
+mod verify;
 
 // exports
+pub use crate::breakpad::*;
+pub use crate::cache::*;
 pub use crate::common::*;
+#[cfg(any(feature = "zstd", feature = "gzip"))]
+pub use crate::compressed::*;
 pub use crate::dbi::*;
+pub use crate::export::*;
+pub use crate::extract::*;
 pub use crate::framedata::*;
 pub use crate::modi::*;
 pub use crate::omap::*;
 pub use crate::pdb::*;
 pub use crate::pdbi::*;
 pub use crate::pe::*;
+pub use crate::process::*;
+pub use crate::sample::*;
+pub use crate::search::*;
 pub use crate::source::*;
 pub use crate::strings::*;
 pub use crate::symbol::*;
+pub use crate::sync::{MaybeSend, MaybeSendSync};
 pub use crate::tpi::*;
+pub use crate::unwind::*;
+pub use crate::verify::*;
 
 // re-export FallibleIterator for convenience
 #[doc(no_inline)]
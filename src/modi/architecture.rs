@@ -0,0 +1,208 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::common::*;
+use crate::dbi::{DBISectionContribution, MachineType};
+use crate::omap::AddressMap;
+use crate::symbol::CPUType;
+
+/// A range of code compiled for a single architecture.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchitectureRange {
+    /// The code in the executable.
+    pub range: Range<Rva>,
+    /// The architecture the code was compiled for, as declared by its module.
+    pub cpu_type: CPUType,
+}
+
+/// The architectures of the code ranges of an executable.
+///
+/// Hybrid images, such as ARM64X or ARM64EC binaries, contain code for multiple architectures.
+/// The architecture of each range is taken from the compile flags of the module contributing it.
+///
+/// Created by [`PDB::architecture_map`](crate::PDB::architecture_map).
+#[derive(Clone, Debug)]
+pub struct ArchitectureMap {
+    machine_type: MachineType,
+    ranges: Vec<ArchitectureRange>,
+}
+
+impl ArchitectureMap {
+    /// Creates the map from the code contributions of the modules, where `cpu_types` is indexed by
+    /// module index.
+    pub(crate) fn new(
+        machine_type: MachineType,
+        cpu_types: &[Option<CPUType>],
+        contributions: &[DBISectionContribution],
+        address_map: &AddressMap<'_>,
+    ) -> Self {
+        let mut ranges = Vec::new();
+
+        for contribution in contributions {
+            if !contribution.characteristics.execute() || contribution.size == 0 {
+                continue;
+            }
+
+            let cpu_type = match cpu_types.get(contribution.module) {
+                Some(&Some(cpu_type)) => cpu_type,
+                _ => continue,
+            };
+
+            let start = match contribution.offset.to_internal_rva(address_map) {
+                Some(start) => start,
+                None => continue,
+            };
+
+            let end = match start.checked_add(contribution.size) {
+                Some(end) => end,
+                None => continue,
+            };
+
+            for range in address_map.rva_ranges(start..end) {
+                ranges.push(ArchitectureRange { range, cpu_type });
+            }
+        }
+
+        ranges.sort_by_key(|range| range.range.start);
+
+        // Merge adjacent ranges of the same architecture.
+        let mut merged: Vec<ArchitectureRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last)
+                    if last.cpu_type == range.cpu_type && last.range.end >= range.range.start =>
+                {
+                    last.range.end = last.range.end.max(range.range.end);
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        Self {
+            machine_type,
+            ranges: merged,
+        }
+    }
+
+    /// Returns the machine type of the executable.
+    pub fn machine_type(&self) -> MachineType {
+        self.machine_type
+    }
+
+    /// Returns whether the executable contains code of multiple architectures.
+    ///
+    /// This is the case if the machine type is hybrid, or if modules declare different
+    /// architectures.
+    pub fn is_hybrid(&self) -> bool {
+        self.machine_type.is_hybrid()
+            || self
+                .ranges
+                .windows(2)
+                .any(|pair| pair[0].cpu_type != pair[1].cpu_type)
+    }
+
+    /// Returns the code ranges with their architecture, sorted by address.
+    pub fn ranges(&self) -> &[ArchitectureRange] {
+        &self.ranges
+    }
+
+    /// Returns the architecture of the code at the given address.
+    pub fn lookup(&self, rva: Rva) -> Option<CPUType> {
+        let index = self
+            .ranges
+            .partition_point(|range| range.range.start <= rva);
+        let range = self.ranges[..index].last()?;
+        if range.range.contains(&rva) {
+            Some(range.cpu_type)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pe::{ImageSectionHeader, SectionCharacteristics};
+
+    const CODE: SectionCharacteristics = SectionCharacteristics(0x6000_0020);
+    const DATA: SectionCharacteristics = SectionCharacteristics(0x4000_0040);
+
+    fn contribution(
+        offset: u32,
+        size: u32,
+        module: usize,
+        characteristics: SectionCharacteristics,
+    ) -> DBISectionContribution {
+        DBISectionContribution {
+            offset: PdbInternalSectionOffset::new(1, offset),
+            size,
+            characteristics,
+            module,
+            data_crc: 0,
+            reloc_crc: 0,
+            coff_section: None,
+        }
+    }
+
+    #[test]
+    fn test_cpu_type_from_raw() {
+        assert_eq!(CPUType::from(0x69), CPUType::ARM64);
+        assert_eq!(CPUType::from(0xf6), CPUType::ARM64);
+        assert_eq!(CPUType::from(0xf7), CPUType::HybridX86ARM64);
+        assert_eq!(CPUType::from(0xf8), CPUType::ARM64EC);
+        assert_eq!(CPUType::from(0xf9), CPUType::ARM64X);
+        assert_eq!(CPUType::ARM64EC as u16, 0xf8);
+        assert_eq!(CPUType::ARM64X as u16, 0xf9);
+    }
+
+    #[test]
+    fn test_hybrid_ranges() {
+        let address_map = AddressMap {
+            original_sections: vec![ImageSectionHeader {
+                virtual_address: 0x1000,
+                virtual_size: 0x1000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // The CPU types as recorded in the compile flags of ARM64X modules.
+        let cpu_types = [Some(CPUType::from(0xf6)), Some(CPUType::from(0xf8)), None];
+        let contributions = [
+            contribution(0x00, 0x10, 0, CODE),
+            contribution(0x10, 0x08, 0, CODE),
+            contribution(0x18, 0x18, 1, CODE),
+            contribution(0x30, 0x10, 2, CODE),
+            contribution(0x40, 0x10, 1, DATA),
+        ];
+
+        let map = ArchitectureMap::new(
+            MachineType::Arm64X,
+            &cpu_types,
+            &contributions,
+            &address_map,
+        );
+
+        assert!(map.is_hybrid());
+        assert_eq!(
+            map.ranges(),
+            [
+                ArchitectureRange {
+                    range: Rva(0x1000)..Rva(0x1018),
+                    cpu_type: CPUType::ARM64,
+                },
+                ArchitectureRange {
+                    range: Rva(0x1018)..Rva(0x1030),
+                    cpu_type: CPUType::ARM64EC,
+                },
+            ]
+        );
+
+        assert_eq!(map.lookup(Rva(0xfff)), None);
+        assert_eq!(map.lookup(Rva(0x1010)), Some(CPUType::ARM64));
+        assert_eq!(map.lookup(Rva(0x1018)), Some(CPUType::ARM64EC));
+        assert_eq!(map.lookup(Rva(0x1030)), None);
+    }
+}
@@ -0,0 +1,429 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::slice;
+
+use scroll::{Pread, LE};
+
+use crate::common::*;
+use crate::modi::{FileChecksum, FileIndex, FileInfo, LineInfo, LineInfoKind};
+use crate::FallibleIterator;
+
+/// A source file record of the C11 line information.
+///
+/// The source file is identified by the byte offset of its record in the lines data, which is also
+/// used as its [`FileIndex`].
+#[derive(Clone, Copy, Debug)]
+struct SourceFile<'a> {
+    index: FileIndex,
+    /// Offsets of the line tables of each segment in the lines data.
+    tables: &'a [u8],
+    /// Start and end offsets of the code covered by each line table.
+    ranges: &'a [u8],
+    name: RawString<'a>,
+}
+
+impl<'a> SourceFile<'a> {
+    fn parse(data: &'a [u8], index: FileIndex) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        buf.seek(index.0 as usize);
+
+        let segment_count = buf.parse_u16()? as usize;
+        buf.parse_u16()?; // padding
+
+        Ok(Self {
+            index,
+            tables: buf.take(segment_count * 4)?,
+            ranges: buf.take(segment_count * 8)?,
+            name: buf.parse_u8_pascal_string()?,
+        })
+    }
+
+    fn segment_count(&self) -> usize {
+        self.tables.len() / 4
+    }
+
+    fn line_table(&self, data: &'a [u8], segment: usize) -> Result<LineTable> {
+        let offset: u32 = self.tables.pread_with(segment * 4, LE)?;
+        let start = self.ranges.pread_with(segment * 8, LE)?;
+        let end = self.ranges.pread_with(segment * 8 + 4, LE)?;
+
+        let mut buf = ParseBuffer::from(data);
+        buf.seek(offset as usize);
+
+        let section = buf.parse_u16()?;
+        let count = buf.parse_u16()? as usize;
+
+        let offsets = buf.pos();
+        buf.take(count * 6)?; // offsets and line numbers
+
+        Ok(LineTable {
+            file_index: self.index,
+            section,
+            start,
+            end,
+            offsets,
+            count,
+        })
+    }
+
+    fn info(&self) -> FileInfo<'a> {
+        FileInfo {
+            // C11 stores file names inline rather than in the string table.
+            name: StringRef(0),
+            checksum: FileChecksum::None,
+            legacy_name: Some(self.name),
+        }
+    }
+}
+
+/// The line records of a single source file in a single section.
+#[derive(Clone, Copy, Debug)]
+struct LineTable {
+    file_index: FileIndex,
+    section: u16,
+    /// Offset of the first byte of code covered by this table.
+    start: u32,
+    /// Offset of the last byte of code covered by this table.
+    end: u32,
+    /// Offset of the line records in the lines data, consisting of all code offsets followed by
+    /// all line numbers.
+    offsets: usize,
+    /// The number of line records.
+    count: usize,
+}
+
+impl LineTable {
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns whether the table covers any code in `range`.
+    fn overlaps(&self, range: &LineRange) -> bool {
+        range.section == self.section
+            && u64::from(range.start) <= u64::from(self.end)
+            && range.end.map_or(true, |end| end > self.start)
+    }
+
+    fn line_info(&self, data: &[u8], index: usize) -> Result<LineInfo> {
+        let offset: u32 = data.pread_with(self.offsets + index * 4, LE)?;
+        let line: u16 = data.pread_with(self.offsets + self.count * 4 + index * 2, LE)?;
+
+        // Line records are sorted by offset, so each record extends up to the next one. The last
+        // record extends to the end of the code covered by the table.
+        let end = if index + 1 < self.len() {
+            Some(data.pread_with::<u32>(self.offsets + (index + 1) * 4, LE)?)
+        } else {
+            self.end.checked_add(1)
+        };
+
+        Ok(LineInfo {
+            offset: PdbInternalSectionOffset::new(self.section, offset),
+            length: end.and_then(|end| end.checked_sub(offset)),
+            file_index: self.file_index,
+            line_start: line.into(),
+            line_end: line.into(),
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        })
+    }
+}
+
+/// A range of code in a section, used to filter line records.
+#[derive(Clone, Copy, Debug)]
+struct LineRange {
+    section: u16,
+    /// Offset of the first byte of code.
+    start: u32,
+    /// Offset past the last byte of code, or `None` if the range extends to the end of the section.
+    end: Option<u32>,
+}
+
+impl LineRange {
+    fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        offset.section == self.section
+            && offset.offset >= self.start
+            && self.end.map_or(true, |end| offset.offset < end)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LineIterator<'a> {
+    /// The lines data of the module.
+    data: &'a [u8],
+    /// Iterator over the remaining line tables.
+    tables: slice::Iter<'a, LineTable>,
+    /// Only visit line records within this range.
+    filter: Option<LineRange>,
+    /// The current line table and the index of the next line record in it.
+    current: Option<(LineTable, usize)>,
+}
+
+impl FallibleIterator for LineIterator<'_> {
+    type Item = LineInfo;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some((ref table, ref mut index)) = self.current {
+                while *index < table.len() {
+                    let line_info = table.line_info(self.data, *index)?;
+                    *index += 1;
+                    if self
+                        .filter
+                        .map_or(true, |range| range.contains(line_info.offset))
+                    {
+                        return Ok(Some(line_info));
+                    }
+                }
+            }
+
+            match self.tables.next() {
+                Some(table) if self.filter.map_or(true, |range| table.overlaps(&range)) => {
+                    self.current = Some((*table, 0));
+                }
+                Some(_) => self.current = None,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileIterator<'a> {
+    data: &'a [u8],
+    files: ParseBuffer<'a>,
+}
+
+impl<'a> FallibleIterator for FileIterator<'a> {
+    type Item = FileInfo<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.files.is_empty() {
+            return Ok(None);
+        }
+
+        let index = FileIndex(self.files.parse_u32()?);
+        Ok(Some(SourceFile::parse(self.data, index)?.info()))
+    }
+}
+
+/// Line information in the C11 format, used by compilers before Visual C++ 7.0.
+///
+/// The data starts with a header listing the offsets of all source file records. Each source file
+/// record in turn lists the offsets of its line tables, one per section containing code of that
+/// file.
+///
+/// The line program only stores the locations of the records it refers to. All accessors take the
+/// lines data of the module that the program was parsed from.
+#[derive(Debug, Default)]
+pub struct LineProgram {
+    files: Range<usize>,
+    tables: Vec<LineTable>,
+}
+
+impl LineProgram {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        let file_count = buf.parse_u16()? as usize;
+        buf.parse_u16()?; // segment count
+        let files_start = buf.pos();
+        let files = buf.take(file_count * 4)?;
+
+        let mut tables = Vec::new();
+        let mut file_offsets = ParseBuffer::from(files);
+        while !file_offsets.is_empty() {
+            let file = SourceFile::parse(data, FileIndex(file_offsets.parse_u32()?))?;
+            for segment in 0..file.segment_count() {
+                tables.push(file.line_table(data, segment)?);
+            }
+        }
+
+        Ok(Self {
+            files: files_start..buf.pos(),
+            tables,
+        })
+    }
+
+    pub(crate) fn lines<'a>(&'a self, data: &'a [u8]) -> LineIterator<'a> {
+        LineIterator {
+            data,
+            tables: self.tables.iter(),
+            filter: None,
+            current: None,
+        }
+    }
+
+    /// Returns the line records of the code starting at `offset` with length `len`.
+    ///
+    /// Line tables are only split by source file and section, not by procedure, so records of
+    /// other procedures are skipped by their offset. Without a length, the records of all code
+    /// following `offset` in the section are returned.
+    pub(crate) fn lines_for_symbol<'a>(
+        &'a self,
+        data: &'a [u8],
+        offset: PdbInternalSectionOffset,
+        len: Option<u32>,
+    ) -> LineIterator<'a> {
+        LineIterator {
+            data,
+            tables: self.tables.iter(),
+            filter: Some(LineRange {
+                section: offset.section,
+                start: offset.offset,
+                end: len.and_then(|len| offset.offset.checked_add(len)),
+            }),
+            current: None,
+        }
+    }
+
+    pub(crate) fn files<'a>(&self, data: &'a [u8]) -> FileIterator<'a> {
+        FileIterator {
+            data,
+            files: ParseBuffer::from(&data[self.files.clone()]),
+        }
+    }
+
+    pub(crate) fn get_file_info<'a>(
+        &self,
+        data: &'a [u8],
+        index: FileIndex,
+    ) -> Result<FileInfo<'a>> {
+        // The file index is the offset of the source file record. Only accept offsets listed in the
+        // header, so that an invalid index cannot be misinterpreted as a source file record.
+        let mut files = self.files(data);
+        while !files.files.is_empty() {
+            if files.files.parse_u32()? == index.0 {
+                return Ok(SourceFile::parse(data, index)?.info());
+            }
+        }
+
+        Err(Error::InvalidSourceFileOffset(index.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two source files, the first of which has code in two sections.
+    const LINES: &[u8] = &[
+        0x02, 0x00, 0x03, 0x00, // 2 files, 3 segments
+        0x0c, 0x00, 0x00, 0x00, // file a.c at 0x0c
+        0x2c, 0x00, 0x00, 0x00, // file b.c at 0x2c
+        // a.c
+        0x02, 0x00, 0x00, 0x00, // 2 segments, padding
+        0x40, 0x00, 0x00, 0x00, // line table at 0x40
+        0x56, 0x00, 0x00, 0x00, // line table at 0x56
+        0x10, 0x00, 0x00, 0x00, 0x2f, 0x00, 0x00, 0x00, // range 0x10..=0x2f
+        0x00, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, // range 0x00..=0x07
+        0x03, b'a', b'.', b'c', // name
+        // b.c
+        0x01, 0x00, 0x00, 0x00, // 1 segment, padding
+        0x60, 0x00, 0x00, 0x00, // line table at 0x60
+        0x40, 0x00, 0x00, 0x00, 0x4f, 0x00, 0x00, 0x00, // range 0x40..=0x4f
+        0x03, b'b', b'.', b'c', // name
+        // line table of a.c in section 1
+        0x01, 0x00, 0x03, 0x00, // section 1, 3 lines
+        0x10, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, // offsets
+        0x0a, 0x00, 0x0b, 0x00, 0x0d, 0x00, // line numbers
+        // line table of a.c in section 2
+        0x02, 0x00, 0x01, 0x00, // section 2, 1 line
+        0x00, 0x00, 0x00, 0x00, // offset
+        0x14, 0x00, // line number
+        // line table of b.c in section 1
+        0x01, 0x00, 0x01, 0x00, // section 1, 1 line
+        0x40, 0x00, 0x00, 0x00, // offset
+        0x05, 0x00, // line number
+    ];
+
+    fn line(section: u16, offset: u32, length: u32, file: u32, line: u32) -> LineInfo {
+        LineInfo {
+            offset: PdbInternalSectionOffset::new(section, offset),
+            length: Some(length),
+            file_index: FileIndex(file),
+            line_start: line,
+            line_end: line,
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        }
+    }
+
+    #[test]
+    fn test_lines() {
+        let program = LineProgram::parse(LINES).expect("parse line program");
+        let lines: Vec<_> = program.lines(LINES).collect().expect("collect lines");
+
+        assert_eq!(
+            lines,
+            vec![
+                line(1, 0x10, 0x08, 0x0c, 10),
+                line(1, 0x18, 0x08, 0x0c, 11),
+                line(1, 0x20, 0x10, 0x0c, 13),
+                line(2, 0x00, 0x08, 0x0c, 20),
+                line(1, 0x40, 0x10, 0x2c, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lines_for_symbol() {
+        let program = LineProgram::parse(LINES).expect("parse line program");
+
+        let offset = PdbInternalSectionOffset::new(1, 0x40);
+        let lines: Vec<_> = program
+            .lines_for_symbol(LINES, offset, Some(0x10))
+            .collect()
+            .expect("collect lines");
+        assert_eq!(lines, vec![line(1, 0x40, 0x10, 0x2c, 5)]);
+
+        let offset = PdbInternalSectionOffset::new(2, 0x30);
+        let mut lines = program.lines_for_symbol(LINES, offset, None);
+        assert_eq!(lines.next().expect("next line"), None);
+
+        // records of other procedures in the same table are skipped
+        let offset = PdbInternalSectionOffset::new(1, 0x18);
+        let lines: Vec<_> = program
+            .lines_for_symbol(LINES, offset, Some(0x08))
+            .collect()
+            .expect("collect lines");
+        assert_eq!(lines, vec![line(1, 0x18, 0x08, 0x0c, 11)]);
+
+        // without a length, all following records of the section are returned
+        let lines: Vec<_> = program
+            .lines_for_symbol(LINES, offset, None)
+            .collect()
+            .expect("collect lines");
+        assert_eq!(
+            lines,
+            vec![
+                line(1, 0x18, 0x08, 0x0c, 11),
+                line(1, 0x20, 0x10, 0x0c, 13),
+                line(1, 0x40, 0x10, 0x2c, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files() {
+        let program = LineProgram::parse(LINES).expect("parse line program");
+        let files: Vec<_> = program.files(LINES).collect().expect("collect files");
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].legacy_name, Some(RawString::from("a.c")));
+        assert_eq!(files[1].legacy_name, Some(RawString::from("b.c")));
+
+        let file = program
+            .get_file_info(LINES, FileIndex(0x2c))
+            .expect("get file info");
+        assert_eq!(file.legacy_name, Some(RawString::from("b.c")));
+        assert_eq!(file.name(None).expect("name"), Some(RawString::from("b.c")));
+
+        assert!(matches!(
+            program.get_file_info(LINES, FileIndex(0x30)),
+            Err(Error::InvalidSourceFileOffset(0x30))
+        ));
+    }
+}
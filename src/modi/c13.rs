@@ -1,13 +1,17 @@
-use std::fmt;
-use std::mem;
-use std::slice;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+use core::ops::Range;
+use core::slice;
 
 use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
+use crate::framedata::{FrameData, FrameDataIter};
 use crate::modi::{
-    constants, CrossModuleExport, CrossModuleRef, FileChecksum, FileIndex, FileInfo, LineInfo,
-    LineInfoKind, ModuleRef,
+    constants, CodeRange, CoffSymbolRva, CrossModuleExport, CrossModuleRef, FileChecksum,
+    FileIndex, FileInfo, ILLineInfo, IgnoredSubsection, LineInfo, LineInfoKind, ModuleRef,
+    TokenMap,
 };
 use crate::symbol::{BinaryAnnotation, BinaryAnnotationsIter, InlineSiteSymbol};
 use crate::FallibleIterator;
@@ -37,10 +41,11 @@ enum DebugSubsectionKind {
 
 impl DebugSubsectionKind {
     fn parse(value: u32) -> Result<Option<Self>> {
-        if (0xf1..=0xfd).contains(&value) {
-            Ok(Some(unsafe { std::mem::transmute(value) }))
-        } else if value == constants::DEBUG_S_IGNORE {
+        if value & constants::DEBUG_S_IGNORE != 0 {
+            // Linkers set the ignore bit to disable subsections in place, e.g. for padding.
             Ok(None)
+        } else if (0xf1..=0xfd).contains(&value) {
+            Ok(Some(unsafe { core::mem::transmute::<u32, Self>(value) }))
         } else {
             Err(Error::UnimplementedDebugSubsection(value))
         }
@@ -82,6 +87,8 @@ impl DebugSubsectionHeader {
 struct DebugSubsection<'a> {
     pub kind: DebugSubsectionKind,
     pub data: &'a [u8],
+    /// Offset of the data within the subsections.
+    pub offset: usize,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -104,13 +111,14 @@ impl<'a> FallibleIterator for DebugSubsectionIterator<'a> {
     fn next(&mut self) -> Result<Option<Self::Item>> {
         while !self.buf.is_empty() {
             let header = self.buf.parse::<DebugSubsectionHeader>()?;
+            let offset = self.buf.pos();
             let data = self.buf.take(header.len())?;
             let kind = match header.kind()? {
                 Some(kind) => kind,
                 None => continue,
             };
 
-            return Ok(Some(DebugSubsection { kind, data }));
+            return Ok(Some(DebugSubsection { kind, data, offset }));
         }
 
         Ok(None)
@@ -150,7 +158,36 @@ pub struct InlineeSourceLine<'a> {
 }
 
 impl<'a> InlineeSourceLine<'a> {
-    // TODO: Implement extra files iterator when needed.
+    /// Returns an iterator over additional files contributing to this inlinee.
+    ///
+    /// Extra files are only present if the subsection uses the extended signature, otherwise the
+    /// iterator is empty.
+    pub fn extra_files(&self) -> InlineeFileIterator<'a> {
+        InlineeFileIterator {
+            buf: ParseBuffer::from(self.extra_files),
+        }
+    }
+}
+
+/// An iterator over the additional files of an inlinee.
+///
+/// Created by [`Inlinee::extra_files`].
+#[derive(Clone, Debug, Default)]
+pub struct InlineeFileIterator<'a> {
+    buf: ParseBuffer<'a>,
+}
+
+impl FallibleIterator for InlineeFileIterator<'_> {
+    type Item = FileIndex;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.buf.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(self.buf.parse()?))
+        }
+    }
 }
 
 impl<'a> TryFromCtx<'a, DebugInlineeLinesHeader> for InlineeSourceLine<'a> {
@@ -164,7 +201,7 @@ impl<'a> TryFromCtx<'a, DebugInlineeLinesHeader> for InlineeSourceLine<'a> {
 
         let extra_files = if header.has_extra_files() {
             let file_count = buf.parse::<u32>()? as usize;
-            buf.take(file_count * std::mem::size_of::<u32>())?
+            buf.take(file_count * core::mem::size_of::<u32>())?
         } else {
             &[]
         };
@@ -277,6 +314,34 @@ impl<'a> DebugLinesSubsection<'a> {
     }
 }
 
+/// The location of a [`DebugLinesSubsection`] within the line data of a module.
+///
+/// This allows a parsed [`LineProgram`] to be stored independently of the line data.
+#[derive(Clone, Debug)]
+struct DebugLinesSubsectionRange {
+    header: DebugLinesHeader,
+    range: Range<usize>,
+}
+
+impl DebugLinesSubsectionRange {
+    fn parse(subsection: DebugSubsection<'_>) -> Result<Self> {
+        let lines = DebugLinesSubsection::parse(subsection.data)?;
+        let end = subsection.offset + subsection.data.len();
+
+        Ok(Self {
+            header: lines.header,
+            range: end - lines.data.len()..end,
+        })
+    }
+
+    fn resolve<'a>(&self, data: &'a [u8]) -> DebugLinesSubsection<'a> {
+        DebugLinesSubsection {
+            header: self.header,
+            data: &data[self.range.clone()],
+        }
+    }
+}
+
 /// Marker instructions for a line offset.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum LineMarkerKind {
@@ -345,6 +410,7 @@ enum LineEntry {
     /// Declares a source line number.
     Number(LineNumberEntry),
     /// Declares a debugging marker.
+    #[allow(dead_code)] // reason = "markers are skipped by LineIterator"
     Marker(LineMarkerEntry),
 }
 
@@ -490,18 +556,18 @@ impl<'t> TryFromCtx<'t, Endian> for DebugLinesBlockHeader {
 impl DebugLinesBlockHeader {
     /// The byte size of all line and column records combined.
     fn data_size(&self) -> usize {
-        self.block_size as usize - std::mem::size_of::<Self>()
+        self.block_size as usize - core::mem::size_of::<Self>()
     }
 
     /// The byte size of all line number entries combined.
     fn line_size(&self) -> usize {
-        self.num_lines as usize * std::mem::size_of::<LineNumberHeader>()
+        self.num_lines as usize * core::mem::size_of::<LineNumberHeader>()
     }
 
     /// The byte size of all column number entries combined.
     fn column_size(&self, subsection: DebugLinesHeader) -> usize {
         if subsection.has_columns() {
-            self.num_lines as usize * std::mem::size_of::<ColumnNumberEntry>()
+            self.num_lines as usize * core::mem::size_of::<ColumnNumberEntry>()
         } else {
             0
         }
@@ -587,7 +653,7 @@ impl FileChecksumKind {
     /// Parses the checksum kind from its raw value.
     fn parse(value: u8) -> Result<Self> {
         if value <= 3 {
-            Ok(unsafe { std::mem::transmute(value) })
+            Ok(unsafe { core::mem::transmute::<u8, Self>(value) })
         } else {
             Err(Error::UnimplementedFileChecksumKind(value))
         }
@@ -686,8 +752,7 @@ impl<'a> DebugFileChecksumsSubsection<'a> {
 #[derive(Clone, Copy, Debug)]
 struct CrossScopeImportModule<'a> {
     name: ModuleRef,
-    /// unparsed in LE byteorder
-    imports: &'a [u32],
+    imports: RecordTable<'a, u32>,
 }
 
 impl CrossScopeImportModule<'_> {
@@ -700,8 +765,7 @@ impl CrossScopeImportModule<'_> {
     where
         I: ItemIndex,
     {
-        let value = self.imports.get(import)?;
-        let index = u32::from_le(*value).into();
+        let index = self.imports.get(import)?.into();
         Some(Local(index))
     }
 }
@@ -724,7 +788,8 @@ impl<'a> FallibleIterator for CrossScopeImportModuleIter<'a> {
         let count = self.buf.parse::<u32>()? as usize;
 
         let data = self.buf.take(count * mem::size_of::<u32>())?;
-        let imports = cast_aligned(data).ok_or(Error::InvalidStreamLength("CrossScopeImports"))?;
+        let imports =
+            RecordTable::new(data).ok_or(Error::InvalidStreamLength("CrossScopeImports"))?;
 
         Ok(Some(CrossScopeImportModule { name, imports }))
     }
@@ -758,7 +823,7 @@ pub struct CrossModuleImports<'a> {
 impl<'a> CrossModuleImports<'a> {
     /// Creates `CrossModuleImports` from the imports debug subsection.
     fn from_section(section: DebugCrossScopeImportsSubsection<'a>) -> Result<Self> {
-        let modules = section.modules().collect()?;
+        let modules = section.modules().iterator().collect::<Result<_>>()?;
         Ok(Self { modules })
     }
 
@@ -880,7 +945,7 @@ struct DebugCrossScopeExportsSubsection<'a> {
 impl<'a> DebugCrossScopeExportsSubsection<'a> {
     /// Creates a new cross scope exports subsection.
     fn parse(data: &'a [u8]) -> Result<Self> {
-        if cast_aligned::<RawCrossScopeExport>(data).is_none() {
+        if RecordTable::<RawCrossScopeExport>::new(data).is_none() {
             return Err(Error::InvalidStreamLength(
                 "DebugCrossScopeExportsSubsection",
             ));
@@ -927,7 +992,7 @@ pub struct CrossModuleExports {
 
 impl CrossModuleExports {
     fn from_section(section: DebugCrossScopeExportsSubsection<'_>) -> Result<Self> {
-        let raw_exports = section.exports().collect()?;
+        let raw_exports = section.exports().iterator().collect::<Result<_>>()?;
         Ok(Self { raw_exports })
     }
 
@@ -983,8 +1048,10 @@ impl CrossModuleExports {
 
 #[derive(Clone)]
 pub struct LineIterator<'a> {
+    /// The line data of the current module.
+    data: &'a [u8],
     /// Iterator over all subsections in the current module.
-    sections: std::slice::Iter<'a, DebugLinesSubsection<'a>>,
+    sections: core::slice::Iter<'a, DebugLinesSubsectionRange>,
     /// Iterator over all blocks in the current lines subsection.
     blocks: DebugLinesBlockIterator<'a>,
     /// Iterator over lines in the current block.
@@ -1032,7 +1099,7 @@ impl<'a> FallibleIterator for LineIterator<'a> {
                     kind: line_entry.kind,
                 };
 
-                let mut last_info = match std::mem::replace(&mut self.last_info, Some(line_info)) {
+                let mut last_info = match self.last_info.replace(line_info) {
                     Some(last_info) => last_info,
                     None => continue,
                 };
@@ -1056,7 +1123,7 @@ impl<'a> FallibleIterator for LineIterator<'a> {
             }
 
             if let Some(lines_section) = self.sections.next() {
-                self.blocks = lines_section.blocks();
+                self.blocks = lines_section.resolve(self.data).blocks();
                 continue;
             }
 
@@ -1068,6 +1135,7 @@ impl<'a> FallibleIterator for LineIterator<'a> {
 impl Default for LineIterator<'_> {
     fn default() -> Self {
         Self {
+            data: &[],
             sections: [].iter(),
             blocks: DebugLinesBlockIterator::default(),
             lines: DebugLinesIterator::default(),
@@ -1103,10 +1171,13 @@ pub struct InlineeLineIterator<'a> {
     col_end: Option<u32>,
     line_kind: LineInfoKind,
     last_info: Option<LineInfo>,
+    zero_length: DegenerateRecordPolicy,
+    infer_lengths: bool,
+    clamp: Option<CodeRange>,
 }
 
 impl<'a> InlineeLineIterator<'a> {
-    fn new(
+    pub(crate) fn new(
         parent_offset: PdbInternalSectionOffset,
         inline_site: &InlineSiteSymbol<'a>,
         inlinee_line: InlineeSourceLine<'a>,
@@ -1123,15 +1194,64 @@ impl<'a> InlineeLineIterator<'a> {
             col_end: None,
             line_kind: LineInfoKind::Statement,
             last_info: None,
+            zero_length: DegenerateRecordPolicy::Keep,
+            infer_lengths: true,
+            clamp: None,
         }
     }
-}
 
-impl<'a> FallibleIterator for InlineeLineIterator<'a> {
-    type Item = LineInfo;
-    type Error = Error;
+    /// Sets how line records covering no code are handled.
+    ///
+    /// Such records occur when the compiler emits several records for the same code offset. By
+    /// default, they are returned like any other record.
+    pub fn zero_length(mut self, policy: DegenerateRecordPolicy) -> Self {
+        self.zero_length = policy;
+        self
+    }
 
-    fn next(&mut self) -> Result<Option<Self::Item>> {
+    /// Sets whether the lengths of line records are inferred from the offset of the next record.
+    ///
+    /// Binary annotations only store the length of some records explicitly. By default, the length
+    /// of the remaining records is inferred from the record following them. When disabled, these
+    /// records have a length of `None`.
+    pub fn infer_lengths(mut self, infer: bool) -> Self {
+        self.infer_lengths = infer;
+        self
+    }
+
+    /// Restricts the line records to the given range, usually the code of the parent function.
+    ///
+    /// Records starting outside of the range are skipped, and records extending past its end are
+    /// shortened. By default, records are returned as encoded.
+    pub fn clamp_to(mut self, range: CodeRange) -> Self {
+        self.clamp = Some(range);
+        self
+    }
+
+    /// Applies the clamping range and zero length policy to a line record.
+    fn finish(&self, mut line: LineInfo) -> Result<Option<LineInfo>> {
+        if let Some(range) = self.clamp {
+            if !range.contains(line.offset) {
+                return Ok(None);
+            }
+
+            let available = range.end_offset() - line.offset.offset;
+            line.length = line.length.map(|length| length.min(available));
+        }
+
+        if line.length == Some(0)
+            && !self
+                .zero_length
+                .keep(DegenerateRecordKind::ZeroLengthLine, line.offset)?
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(line))
+    }
+
+    /// Returns the next line record as encoded in the binary annotations.
+    fn next_line(&mut self) -> Result<Option<LineInfo>> {
         while let Some(op) = self.annotations.next()? {
             match op {
                 BinaryAnnotation::CodeOffset(code_offset) => {
@@ -1201,7 +1321,10 @@ impl<'a> FallibleIterator for InlineeLineIterator<'a> {
 
             let line_offset = self.code_offset + self.code_offset_base;
             if let Some(ref mut last_info) = self.last_info {
-                if last_info.length.is_none() && last_info.kind == self.line_kind {
+                if self.infer_lengths
+                    && last_info.length.is_none()
+                    && last_info.kind == self.line_kind
+                {
                     last_info.length = Some(line_offset.offset - last_info.offset.offset);
                 }
             }
@@ -1222,7 +1345,7 @@ impl<'a> FallibleIterator for InlineeLineIterator<'a> {
 
             // Finish the previous record and emit it. The current record is stored so that the
             // length can be inferred from subsequent operators or the next line info.
-            if let Some(last_info) = std::mem::replace(&mut self.last_info, Some(line_info)) {
+            if let Some(last_info) = self.last_info.replace(line_info) {
                 return Ok(Some(last_info));
             }
         }
@@ -1231,6 +1354,21 @@ impl<'a> FallibleIterator for InlineeLineIterator<'a> {
     }
 }
 
+impl<'a> FallibleIterator for InlineeLineIterator<'a> {
+    type Item = LineInfo;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(line) = self.next_line()? {
+            if let Some(line) = self.finish(line)? {
+                return Ok(Some(line));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
 /// An inlined function that can evaluate to line information.
 #[derive(Clone, Debug, Default)]
 pub struct Inlinee<'a>(InlineeSourceLine<'a>);
@@ -1241,6 +1379,15 @@ impl<'a> Inlinee<'a> {
         self.0.inlinee
     }
 
+    /// Returns an iterator over additional files that this inlinee's code was taken from.
+    ///
+    /// The primary file is referenced by the line records. If the inlinee's lines span further
+    /// files, for instance through `#include` directives within the function body, those files are
+    /// listed here.
+    pub fn extra_files(&self) -> InlineeFileIterator<'a> {
+        self.0.extra_files()
+    }
+
     /// Returns an iterator over line records for an inline site.
     ///
     /// Note that line records are not guaranteed to be ordered by source code offset. If a
@@ -1291,6 +1438,221 @@ impl<'a> FallibleIterator for InlineeIterator<'a> {
     }
 }
 
+/// An iterator over the IL line records of a managed module.
+///
+/// Created by [`ModuleInfo::il_lines`](crate::ModuleInfo::il_lines).
+#[derive(Clone, Debug, Default)]
+pub struct ILLineIterator<'a> {
+    subsections: DebugSubsectionIterator<'a>,
+    blocks: DebugLinesBlockIterator<'a>,
+    lines: DebugLinesIterator<'a>,
+    columns: DebugColumnsIterator<'a>,
+}
+
+impl<'a> ILLineIterator<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Self {
+        Self {
+            subsections: DebugSubsectionIterator::new(data),
+            ..Default::default()
+        }
+    }
+}
+
+impl FallibleIterator for ILLineIterator<'_> {
+    type Item = ILLineInfo;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some(entry) = self.lines.next()? {
+                let column_entry = self.columns.next()?;
+                let line_entry = match entry {
+                    LineEntry::Number(line_entry) => line_entry,
+                    LineEntry::Marker(_) => continue,
+                };
+
+                return Ok(Some(ILLineInfo {
+                    method: self.blocks.header.offset,
+                    il_offset: line_entry.offset,
+                    file_index: FileIndex(self.lines.block.file_index),
+                    line_start: line_entry.start_line,
+                    line_end: line_entry.end_line,
+                    column_start: column_entry.map(|e| e.start_column.into()),
+                    column_end: column_entry.map(|e| e.end_column.into()),
+                    kind: line_entry.kind,
+                }));
+            }
+
+            if let Some(block) = self.blocks.next()? {
+                self.lines = block.lines();
+                self.columns = block.columns();
+                continue;
+            }
+
+            // IL lines use the same layout as native lines, but map offsets into the IL code.
+            match self.subsections.next()? {
+                Some(sec) if sec.kind == DebugSubsectionKind::ILLines => {
+                    self.blocks = DebugLinesSubsection::parse(sec.data)?.blocks();
+                }
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// An iterator over the frame data of a module.
+///
+/// Created by [`ModuleInfo::frame_data`](crate::ModuleInfo::frame_data).
+#[derive(Debug, Default)]
+pub struct ModuleFrameDataIter<'a> {
+    subsections: DebugSubsectionIterator<'a>,
+    frames: FrameDataIter<'a>,
+}
+
+impl<'a> ModuleFrameDataIter<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Self {
+        Self {
+            subsections: DebugSubsectionIterator::new(data),
+            frames: FrameDataIter::default(),
+        }
+    }
+}
+
+impl FallibleIterator for ModuleFrameDataIter<'_> {
+    type Item = FrameData;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some(frame) = self.frames.next()? {
+                return Ok(Some(frame));
+            }
+
+            match self.subsections.next()? {
+                Some(sec) if sec.kind == DebugSubsectionKind::FrameData => {
+                    self.frames = FrameDataIter::from_subsection(sec.data)?;
+                }
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Adds the entries of the function and type token map subsections to `map`.
+///
+/// Both subsections consist of pairs of a metadata token and its target. In function token maps,
+/// the target is the offset of a symbol in the module's symbol stream. In type token maps, it is an
+/// index into the type stream.
+pub(crate) fn read_token_maps(data: &[u8], map: &mut TokenMap) -> Result<()> {
+    let mut subsections = DebugSubsectionIterator::new(data);
+    while let Some(sec) = subsections.next()? {
+        if !matches!(
+            sec.kind,
+            DebugSubsectionKind::FuncMDTokenMap | DebugSubsectionKind::TypeMDTokenMap
+        ) {
+            continue;
+        }
+
+        let mut buf = ParseBuffer::from(sec.data);
+        while !buf.is_empty() {
+            let token = buf.parse_u32()?;
+            let target = buf.parse_u32()?;
+            if sec.kind == DebugSubsectionKind::FuncMDTokenMap {
+                map.insert_symbol(token, SymbolIndex(target));
+            } else {
+                map.insert_type(token, TypeIndex(target));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An iterator over the COFF symbol RVAs of a module.
+///
+/// Created by [`ModuleInfo::coff_symbol_rvas`](crate::ModuleInfo::coff_symbol_rvas).
+#[derive(Clone, Debug, Default)]
+pub struct CoffSymbolRvaIter<'a> {
+    subsections: DebugSubsectionIterator<'a>,
+    rvas: ParseBuffer<'a>,
+    index: u32,
+}
+
+impl<'a> CoffSymbolRvaIter<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Self {
+        Self {
+            subsections: DebugSubsectionIterator::new(data),
+            rvas: ParseBuffer::default(),
+            index: 0,
+        }
+    }
+}
+
+impl FallibleIterator for CoffSymbolRvaIter<'_> {
+    type Item = CoffSymbolRva;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if !self.rvas.is_empty() {
+                let symbol = CoffSymbolRva {
+                    ordinal: self.index,
+                    rva: PdbInternalRva(self.rvas.parse_u32()?),
+                };
+                self.index += 1;
+                return Ok(Some(symbol));
+            }
+
+            match self.subsections.next()? {
+                Some(sec) if sec.kind == DebugSubsectionKind::CoffSymbolRva => {
+                    self.rvas = ParseBuffer::from(sec.data);
+                }
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// An iterator over the subsections of a module that are marked with `DEBUG_S_IGNORE`.
+///
+/// Created by [`ModuleInfo::ignored_subsections`](crate::ModuleInfo::ignored_subsections).
+#[derive(Clone, Debug, Default)]
+pub struct IgnoredSubsectionIter<'a> {
+    buf: ParseBuffer<'a>,
+}
+
+impl<'a> IgnoredSubsectionIter<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Self {
+        Self {
+            buf: ParseBuffer::from(data),
+        }
+    }
+}
+
+impl<'a> FallibleIterator for IgnoredSubsectionIter<'a> {
+    type Item = IgnoredSubsection<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while !self.buf.is_empty() {
+            let header = self.buf.parse::<DebugSubsectionHeader>()?;
+            let data = self.buf.take(header.len())?;
+
+            if header.kind & constants::DEBUG_S_IGNORE != 0 {
+                return Ok(Some(IgnoredSubsection {
+                    kind: header.kind & !constants::DEBUG_S_IGNORE,
+                    data,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct FileIterator<'a> {
     checksums: DebugFileChecksumsIterator<'a>,
@@ -1305,6 +1667,7 @@ impl<'a> FallibleIterator for FileIterator<'a> {
             Ok(Some(entry)) => Ok(Some(FileInfo {
                 name: entry.name,
                 checksum: entry.checksum,
+                legacy_name: None,
             })),
             Ok(None) => Ok(None),
             Err(error) => Err(error),
@@ -1312,24 +1675,29 @@ impl<'a> FallibleIterator for FileIterator<'a> {
     }
 }
 
-pub struct LineProgram<'a> {
-    file_checksums: DebugFileChecksumsSubsection<'a>,
-    line_sections: Vec<DebugLinesSubsection<'a>>,
+/// Line information in the C13 format.
+///
+/// The line program only stores the locations of the subsections it refers to. All accessors take
+/// the line data of the module that the program was parsed from.
+#[derive(Debug, Default)]
+pub struct LineProgram {
+    file_checksums: Range<usize>,
+    line_sections: Vec<DebugLinesSubsectionRange>,
 }
 
-impl<'a> LineProgram<'a> {
-    pub(crate) fn parse(data: &'a [u8]) -> Result<Self> {
-        let mut file_checksums = DebugFileChecksumsSubsection::default();
+impl LineProgram {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        let mut file_checksums = 0..0;
         let mut line_sections = Vec::new();
 
         let mut section_iter = DebugSubsectionIterator::new(data);
         while let Some(sec) = section_iter.next()? {
             match sec.kind {
                 DebugSubsectionKind::FileChecksums => {
-                    file_checksums = DebugFileChecksumsSubsection::new(sec.data);
+                    file_checksums = sec.offset..sec.offset + sec.data.len();
                 }
                 DebugSubsectionKind::Lines => {
-                    line_sections.push(DebugLinesSubsection::parse(sec.data)?);
+                    line_sections.push(DebugLinesSubsectionRange::parse(sec)?);
                 }
                 _ => {}
             }
@@ -1343,8 +1711,9 @@ impl<'a> LineProgram<'a> {
         })
     }
 
-    pub(crate) fn lines(&self) -> LineIterator<'_> {
+    pub(crate) fn lines<'a>(&'a self, data: &'a [u8]) -> LineIterator<'a> {
         LineIterator {
+            data,
             sections: self.line_sections.iter(),
             blocks: DebugLinesBlockIterator::default(),
             lines: DebugLinesIterator::default(),
@@ -1353,7 +1722,11 @@ impl<'a> LineProgram<'a> {
         }
     }
 
-    pub(crate) fn lines_for_symbol(&self, offset: PdbInternalSectionOffset) -> LineIterator<'_> {
+    pub(crate) fn lines_for_symbol<'a>(
+        &'a self,
+        data: &'a [u8],
+        offset: PdbInternalSectionOffset,
+    ) -> LineIterator<'a> {
         // Search for the lines subsection that covers the given offset. They are non-overlapping
         // and not empty, so there will be at most one match. In most cases, there will be an exact
         // match for each symbol. However, ASM sometimes yields line records outside of the stated
@@ -1366,8 +1739,8 @@ impl<'a> LineProgram<'a> {
 
         let section = match index_result {
             Err(0) => return LineIterator::default(),
-            Err(i) => self.line_sections[i - 1],
-            Ok(i) => self.line_sections[i],
+            Err(i) => &self.line_sections[i - 1],
+            Ok(i) => &self.line_sections[i],
         };
 
         // In the `Err(i)` case, we might have chosen a lines subsection pointing into a different
@@ -1377,24 +1750,33 @@ impl<'a> LineProgram<'a> {
         }
 
         LineIterator {
+            data,
             sections: [].iter(),
-            blocks: section.blocks(),
+            blocks: section.resolve(data).blocks(),
             lines: DebugLinesIterator::default(),
             columns: DebugColumnsIterator::default(),
             last_info: None,
         }
     }
 
-    pub(crate) fn files(&self) -> FileIterator<'a> {
+    fn file_checksums<'a>(&self, data: &'a [u8]) -> DebugFileChecksumsSubsection<'a> {
+        DebugFileChecksumsSubsection::new(&data[self.file_checksums.clone()])
+    }
+
+    pub(crate) fn files<'a>(&self, data: &'a [u8]) -> FileIterator<'a> {
         FileIterator {
-            checksums: self.file_checksums.entries().unwrap_or_default(),
+            checksums: self.file_checksums(data).entries().unwrap_or_default(),
         }
     }
 
-    pub(crate) fn get_file_info(&self, index: FileIndex) -> Result<FileInfo<'a>> {
+    pub(crate) fn get_file_info<'a>(
+        &self,
+        data: &'a [u8],
+        index: FileIndex,
+    ) -> Result<FileInfo<'a>> {
         // The file index actually contains the byte offset value into the file_checksums
         // subsection. Therefore, treat it as the offset.
-        let mut entries = self.file_checksums.entries_at_offset(index)?;
+        let mut entries = self.file_checksums(data).entries_at_offset(index)?;
         let entry = entries
             .next()?
             .ok_or(Error::InvalidFileChecksumOffset(index.0))?;
@@ -1402,6 +1784,7 @@ impl<'a> LineProgram<'a> {
         Ok(FileInfo {
             name: entry.name,
             checksum: entry.checksum,
+            legacy_name: None,
         })
     }
 
@@ -1409,7 +1792,7 @@ impl<'a> LineProgram<'a> {
         (offset.section, offset.offset)
     }
 
-    fn lines_key(lines: &DebugLinesSubsection<'_>) -> (u16, u32) {
+    fn lines_key(lines: &DebugLinesSubsectionRange) -> (u16, u32) {
         Self::lines_offset_key(lines.header.offset)
     }
 }
@@ -1418,9 +1801,135 @@ impl<'a> LineProgram<'a> {
 mod tests {
     use super::*;
 
-    use std::mem;
+    use core::mem;
+
+    use crate::symbol::{BinaryAnnotations, BinaryAnnotationsEncoder};
+
+    #[test]
+    fn test_skip_ignored_subsections() {
+        let data = &[
+            0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, // DEBUG_S_IGNORE, empty
+            0xf2, 0x00, 0x00, 0x80, 0x04, 0x00, 0x00, 0x00, // ignored lines
+            0xff, 0xff, 0xff, 0xff, // garbage
+            0xf4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file checksums, empty
+        ];
+
+        let subsections: Vec<_> = DebugSubsectionIterator::new(data)
+            .collect()
+            .expect("collect subsections");
+        assert_eq!(subsections.len(), 1);
+        assert_eq!(subsections[0].kind, DebugSubsectionKind::FileChecksums);
+    }
+
+    #[test]
+    fn test_module_frame_data() {
+        let data = &[
+            0xf5, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, // frame data, 36 bytes
+            0x00, 0x00, 0x00, 0x00, // relocation pointer
+            0x10, 0x10, 0x00, 0x00, // code start
+            0x20, 0x00, 0x00, 0x00, // code size
+            0x08, 0x00, 0x00, 0x00, // locals size
+            0x04, 0x00, 0x00, 0x00, // params size
+            0x00, 0x00, 0x00, 0x00, // max stack size
+            0x2a, 0x00, 0x00, 0x00, // program string
+            0x03, 0x00, 0x04, 0x00, // prolog size, saved regs size
+            0x04, 0x00, 0x00, 0x00, // function start
+            0xf4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file checksums, empty
+        ];
+
+        let frames: Vec<_> = ModuleFrameDataIter::parse(data)
+            .collect()
+            .expect("collect frame data");
+        assert_eq!(frames.len(), 1);
+
+        let frame = &frames[0];
+        assert_eq!(frame.code_start, PdbInternalRva(0x1010));
+        assert_eq!(frame.code_size, 0x20);
+        assert_eq!(frame.locals_size, 8);
+        assert_eq!(frame.params_size, 4);
+        assert_eq!(frame.prolog_size, 3);
+        assert_eq!(frame.saved_regs_size, 4);
+        assert_eq!(frame.program, Some(StringRef(0x2a)));
+        assert!(frame.is_function_start);
+    }
+
+    #[test]
+    fn test_ignored_subsections() {
+        let data = &[
+            0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, // DEBUG_S_IGNORE, empty
+            0xf4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file checksums, empty
+            0x34, 0x12, 0x00, 0x80, 0x04, 0x00, 0x00, 0x00, // ignored vendor data
+            0xff, 0xff, 0xff, 0xff, // payload
+        ];
 
-    use crate::symbol::BinaryAnnotations;
+        let subsections: Vec<_> = IgnoredSubsectionIter::parse(data)
+            .collect()
+            .expect("collect ignored subsections");
+
+        assert_eq!(
+            subsections,
+            vec![
+                IgnoredSubsection { kind: 0, data: &[] },
+                IgnoredSubsection {
+                    kind: 0x1234,
+                    data: &[0xff, 0xff, 0xff, 0xff],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coff_symbol_rvas() {
+        let data = &[
+            0xfd, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, // coff symbol rva, 8 bytes
+            0x00, 0x10, 0x00, 0x00, // rva
+            0x40, 0x12, 0x00, 0x00, // rva
+            0xf4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file checksums, empty
+            0xfd, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, // coff symbol rva, 4 bytes
+            0x80, 0x20, 0x00, 0x00, // rva
+        ];
+
+        let symbols: Vec<_> = CoffSymbolRvaIter::parse(data)
+            .collect()
+            .expect("collect coff symbol rvas");
+
+        assert_eq!(
+            symbols,
+            vec![
+                CoffSymbolRva {
+                    ordinal: 0,
+                    rva: PdbInternalRva(0x1000),
+                },
+                CoffSymbolRva {
+                    ordinal: 1,
+                    rva: PdbInternalRva(0x1240),
+                },
+                CoffSymbolRva {
+                    ordinal: 2,
+                    rva: PdbInternalRva(0x2080),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_token_maps() {
+        let data = &[
+            0xfa, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, // function token map, 8 bytes
+            0x01, 0x00, 0x00, 0x06, // token
+            0x40, 0x00, 0x00, 0x00, // symbol offset
+            0xfb, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, // type token map, 8 bytes
+            0x02, 0x00, 0x00, 0x02, // token
+            0x03, 0x10, 0x00, 0x00, // type index
+        ];
+
+        let mut map = TokenMap::default();
+        read_token_maps(data, &mut map).expect("read token maps");
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.symbol_token(SymbolIndex(0x40)), Some(0x0600_0001));
+        assert_eq!(map.type_token(TypeIndex(0x1003)), Some(0x0200_0002));
+    }
 
     #[test]
     fn test_line_number_header() {
@@ -1456,7 +1965,7 @@ mod tests {
         ];
 
         let line_program = LineProgram::parse(data).expect("parse line program");
-        let lines: Vec<_> = line_program.lines().collect().expect("collect lines");
+        let lines: Vec<_> = line_program.lines(data).collect().expect("collect lines");
 
         let expected = [
             LineInfo {
@@ -1503,6 +2012,77 @@ mod tests {
         assert_eq!(lines, expected);
     }
 
+    #[test]
+    fn test_iter_il_lines() {
+        let data = &[
+            249, 0, 0, 0, 48, 0, 0, 0, 0, 16, 0, 0, 1, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0,
+            0, 36, 0, 0, 0, 0, 0, 0, 0, 22, 0, 0, 128, 0, 0, 0, 0, 23, 0, 0, 128, 11, 0, 0, 0, 24,
+            0, 0, 128, // IL lines
+            242, 0, 0, 0, 12, 0, 0, 0, 0, 32, 0, 0, 1, 0, 0, 0, 4, 0, 0,
+            0, // empty native lines
+        ];
+
+        let lines: Vec<_> = ILLineIterator::parse(data)
+            .collect()
+            .expect("collect IL lines");
+
+        let line = |il_offset, line| ILLineInfo {
+            method: PdbInternalSectionOffset::new(1, 0x1000),
+            il_offset,
+            file_index: FileIndex(0),
+            line_start: line,
+            line_end: line,
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        };
+
+        assert_eq!(lines, [line(0, 22), line(0, 23), line(11, 24)]);
+    }
+
+    #[test]
+    fn test_iter_lines_with_columns() {
+        let data = &[
+            242, 0, 0, 0, 48, 0, 0, 0, 32, 0, 0, 0, 1, 0, 1, 0, 16, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0,
+            0, 36, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 128, 6, 0, 0, 0, 4, 0, 0, 128, 5, 0, 12, 0, 13, 0,
+            20, 0,
+        ];
+
+        let line_program = LineProgram::parse(data).expect("parse line program");
+        let lines: Vec<_> = line_program.lines(data).collect().expect("collect lines");
+
+        let expected = [
+            LineInfo {
+                offset: PdbInternalSectionOffset {
+                    section: 0x1,
+                    offset: 0x20,
+                },
+                length: Some(6),
+                file_index: FileIndex(0x0),
+                line_start: 3,
+                line_end: 3,
+                column_start: Some(5),
+                column_end: Some(12),
+                kind: LineInfoKind::Statement,
+            },
+            LineInfo {
+                offset: PdbInternalSectionOffset {
+                    section: 0x1,
+                    offset: 0x26,
+                },
+                length: Some(10),
+                file_index: FileIndex(0x0),
+                line_start: 4,
+                line_end: 4,
+                column_start: Some(13),
+                column_end: Some(20),
+                kind: LineInfoKind::Statement,
+            },
+        ];
+
+        assert_eq!(lines, expected);
+    }
+
     #[test]
     fn test_lines_for_symbol() {
         let data = &[
@@ -1519,7 +2099,7 @@ mod tests {
 
         let line_program = LineProgram::parse(data).expect("parse line program");
         let line = line_program
-            .lines_for_symbol(offset)
+            .lines_for_symbol(data, offset)
             .next()
             .expect("get line");
 
@@ -1565,7 +2145,7 @@ mod tests {
 
         let line_program = LineProgram::parse(data).expect("parse line program");
         let line = line_program
-            .lines_for_symbol(offset)
+            .lines_for_symbol(data, offset)
             .next()
             .expect("get line");
 
@@ -1649,7 +2229,13 @@ mod tests {
             },
         ];
 
-        assert_eq!(lines, expected)
+        assert_eq!(lines, expected);
+
+        let files: Vec<_> = lines[0]
+            .extra_files()
+            .collect()
+            .expect("collect extra files");
+        assert_eq!(files, [FileIndex(0x1ad8)]);
     }
 
     #[test]
@@ -1802,13 +2388,103 @@ mod tests {
         assert_eq!(lines, expected);
     }
 
+    #[test]
+    fn test_inlinee_lines_options() {
+        let mut encoder = BinaryAnnotationsEncoder::new();
+        let annotations = [
+            BinaryAnnotation::ChangeCodeOffset(0x10),
+            BinaryAnnotation::ChangeLineOffset(1),
+            BinaryAnnotation::ChangeCodeOffset(0),
+            BinaryAnnotation::ChangeLineOffset(1),
+            BinaryAnnotation::ChangeCodeOffset(8),
+            BinaryAnnotation::ChangeCodeLength(0x20),
+        ];
+        for &annotation in &annotations {
+            encoder.push(annotation).expect("encode annotation");
+        }
+        let data = encoder.finish();
+
+        let inline_site = InlineSiteSymbol {
+            parent: None,
+            end: SymbolIndex(0),
+            inlinee: IdIndex(0x1000),
+            invocations: None,
+            annotations: BinaryAnnotations::new(&data),
+        };
+
+        let inlinee_line = InlineeSourceLine {
+            inlinee: IdIndex(0x1000),
+            file_id: FileIndex(0),
+            line: 10,
+            extra_files: &[],
+        };
+
+        let lines = |configure: fn(InlineeLineIterator<'_>) -> InlineeLineIterator<'_>| {
+            let parent_offset = PdbInternalSectionOffset::new(1, 0x100);
+            let iter = InlineeLineIterator::new(parent_offset, &inline_site, inlinee_line);
+            configure(iter)
+                .map(|line| Ok((line.offset.offset, line.length, line.line_start)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            lines(|iter| iter).expect("lines"),
+            [
+                (0x110, Some(0), 10),
+                (0x110, Some(8), 11),
+                (0x118, Some(0x20), 12)
+            ]
+        );
+
+        assert_eq!(
+            lines(|iter| iter.zero_length(DegenerateRecordPolicy::Skip)).expect("lines"),
+            [(0x110, Some(8), 11), (0x118, Some(0x20), 12)]
+        );
+
+        assert!(matches!(
+            lines(|iter| iter.zero_length(DegenerateRecordPolicy::Report)),
+            Err(Error::DegenerateRecord(
+                DegenerateRecordKind::ZeroLengthLine,
+                _
+            ))
+        ));
+
+        assert_eq!(
+            lines(|iter| iter.infer_lengths(false)).expect("lines"),
+            [
+                (0x110, None, 10),
+                (0x110, None, 11),
+                (0x118, Some(0x20), 12)
+            ]
+        );
+
+        assert_eq!(
+            lines(|iter| iter.clamp_to(CodeRange {
+                offset: PdbInternalSectionOffset::new(1, 0x110),
+                length: 0x10,
+            }))
+            .expect("lines"),
+            [
+                (0x110, Some(0), 10),
+                (0x110, Some(8), 11),
+                (0x118, Some(8), 12)
+            ]
+        );
+
+        assert_eq!(
+            lines(|iter| iter.clamp_to(CodeRange {
+                offset: PdbInternalSectionOffset::new(1, 0x114),
+                length: 0x100,
+            }))
+            .expect("lines"),
+            [(0x118, Some(0x20), 12)]
+        );
+    }
+
     #[repr(align(4))]
     struct Align4<T>(T);
 
     /// Aligned data for parsing cross module imports.
-    ///
-    /// When parsing them from the file, alignment is validated during ruintime using
-    /// `cast_aligned`. If alignment is validated, it throws an error.
     const CROSS_MODULE_IMPORT_DATA: Align4<[u8; 76]> = Align4([
         // module 0
         189, 44, 0, 0, // module name 2CBD
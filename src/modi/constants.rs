@@ -9,6 +9,8 @@ pub const CV_SIGNATURE_C11: u32 = 2;
 pub const CV_SIGNATURE_C13: u32 = 4;
 
 /// Debug subsection kind for empty subsections. Should be skipped.
+///
+/// This is also a flag: any subsection kind with this bit set is ignored.
 pub const DEBUG_S_IGNORE: u32 = 0x8000_0000;
 /// Flag indicating that column information is present.
 pub const CV_LINES_HAVE_COLUMNS: u16 = 0x1;
@@ -0,0 +1,535 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::common::*;
+use crate::modi::{ModuleInfo, SharedLineProgram};
+use crate::omap::AddressMap;
+use crate::symbol::{CPUType, ProcedureSymbol, SymbolData};
+use crate::FallibleIterator;
+
+/// A function of the executable, merged from the procedure symbols of all modules.
+///
+/// Returned by [`Functions::iter`] and [`Functions::lookup`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Function<'a> {
+    /// The code of this function in the executable.
+    ///
+    /// If the address map splits the function into multiple ranges, this is the range containing
+    /// its entry point.
+    pub range: Range<Rva>,
+    /// The name of the function.
+    pub name: RawString<'a>,
+    /// Index of the module in [`DebugInformation::modules`](crate::DebugInformation::modules)
+    /// declaring the function.
+    pub module: usize,
+    /// Index of the procedure symbol in the symbols of the module.
+    pub symbol: SymbolIndex,
+    /// The number of other procedure symbols at the same address.
+    ///
+    /// This is non-zero if the linker folded identical functions (COMDAT folding), or if multiple
+    /// modules declare the same function.
+    pub folded: usize,
+    /// The architecture the function was compiled for, as declared by its module.
+    ///
+    /// In hybrid images, such as ARM64X binaries, this distinguishes native from emulation
+    /// compatible code. This is `None` if the module does not declare compile flags.
+    pub cpu_type: Option<CPUType>,
+}
+
+#[derive(Clone, Debug)]
+struct FunctionEntry {
+    range: Range<Rva>,
+    /// Position of the module in `Functions::modules`.
+    slot: usize,
+    symbol: SymbolIndex,
+    folded: usize,
+}
+
+/// A thunk of a hybrid image forwarding to a native function.
+#[derive(Clone, Debug)]
+struct HybridThunk {
+    range: Range<Rva>,
+    /// The decorated name of the native function.
+    target: Vec<u8>,
+}
+
+/// Code that transfers control to or belongs to code at another address.
+///
+/// This is used for trampolines, which jump to their target, and for separated code blocks, which
+/// belong to the procedure at their parent offset.
+#[derive(Clone, Debug)]
+struct Redirect {
+    range: Range<Rva>,
+    target: Rva,
+}
+
+/// Returns the item whose range contains the given address, where `items` are sorted by start.
+///
+/// Items without code still match their start address.
+pub(crate) fn find_range<T>(
+    items: &[T],
+    rva: Rva,
+    range: impl Fn(&T) -> &Range<Rva>,
+) -> Option<&T> {
+    let index = items.partition_point(|item| range(item).start <= rva);
+    let item = items[..index].last()?;
+    let range = range(item);
+    if range.contains(&rva) || range.start == rva {
+        Some(item)
+    } else {
+        None
+    }
+}
+
+/// The deduplicated functions of all modules in a PDB, sorted by address.
+///
+/// Created by [`PDB::functions`](crate::PDB::functions).
+pub struct Functions<'s> {
+    modules: Vec<(usize, ModuleInfo<'s>)>,
+    /// The architecture of each module in `modules`.
+    cpu_types: Vec<Option<CPUType>>,
+    entries: Vec<FunctionEntry>,
+    /// Thunks forwarding to native functions, sorted by address.
+    thunks: Vec<HybridThunk>,
+    /// Start addresses of native functions of hybrid images by decorated name.
+    native: BTreeMap<Vec<u8>, Rva>,
+    /// Trampolines jumping to their targets, sorted by address.
+    trampolines: Vec<Redirect>,
+    /// Separated code blocks targeting the start of their parent procedure, sorted by address.
+    separated: Vec<Redirect>,
+}
+
+impl<'s> Functions<'s> {
+    pub(crate) fn new(
+        modules: Vec<(usize, ModuleInfo<'s>)>,
+        address_map: &AddressMap<'_>,
+        zero_length: DegenerateRecordPolicy,
+    ) -> Result<Self> {
+        let mut entries = Vec::new();
+        let mut cpu_types = Vec::with_capacity(modules.len());
+        let mut thunks = Vec::new();
+        let mut native = BTreeMap::new();
+        let mut trampolines = Vec::new();
+        let mut separated = Vec::new();
+
+        let code_range = |offset: PdbInternalSectionOffset, len: u32| {
+            let start = offset.to_internal_rva(address_map)?;
+            match start.checked_add(len) {
+                Some(end) if end > start => address_map.rva_ranges(start..end).next(),
+                _ => start.to_rva(address_map).map(|rva| rva..rva),
+            }
+        };
+
+        for (slot, (_, info)) in modules.iter().enumerate() {
+            cpu_types.push(info.compile_flags()?.map(|flags| flags.cpu_type));
+
+            let mut symbols = info.symbols()?;
+            while let Some(symbol) = symbols.next()? {
+                let procedure = match symbol.parse() {
+                    Ok(SymbolData::Procedure(procedure)) => procedure,
+                    Ok(SymbolData::Thunk(thunk)) => {
+                        let target = match native_mangling(thunk.name.as_bytes()) {
+                            Some(target) => target,
+                            None => continue,
+                        };
+
+                        if let Some(range) = code_range(thunk.offset, thunk.len.into()) {
+                            thunks.push(HybridThunk { range, target });
+                        }
+                        continue;
+                    }
+                    Ok(SymbolData::Trampoline(trampoline)) => {
+                        let range = code_range(trampoline.thunk, trampoline.size.into());
+                        if let (Some(range), Some(target)) =
+                            (range, trampoline.target.to_rva(address_map))
+                        {
+                            trampolines.push(Redirect { range, target });
+                        }
+                        continue;
+                    }
+                    Ok(SymbolData::SeparatedCode(block)) => {
+                        let range = code_range(block.offset, block.len);
+                        if let (Some(range), Some(target)) =
+                            (range, block.parent_offset.to_rva(address_map))
+                        {
+                            separated.push(Redirect { range, target });
+                        }
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                if procedure.len == 0
+                    && !zero_length
+                        .keep(DegenerateRecordKind::ZeroLengthFunction, procedure.offset)?
+                {
+                    continue;
+                }
+
+                if let Some(range) = code_range(procedure.offset, procedure.len) {
+                    if has_native_mangling(procedure.name.as_bytes()) {
+                        native
+                            .entry(procedure.name.as_bytes().to_vec())
+                            .or_insert(range.start);
+                    }
+
+                    entries.push(FunctionEntry {
+                        range,
+                        slot,
+                        symbol: symbol.index(),
+                        folded: 0,
+                    });
+                }
+            }
+        }
+
+        // Keep the first symbol at every address in module order, and count the others.
+        entries.sort_by_key(|entry| (entry.range.start, entry.slot, entry.symbol));
+        entries.dedup_by(|duplicate, first| {
+            let is_duplicate = duplicate.range.start == first.range.start;
+            if is_duplicate {
+                first.folded += 1;
+            }
+            is_duplicate
+        });
+
+        thunks.sort_by_key(|thunk: &HybridThunk| thunk.range.start);
+        trampolines.sort_by_key(|trampoline: &Redirect| trampoline.range.start);
+        separated.sort_by_key(|block: &Redirect| block.range.start);
+
+        Ok(Self {
+            modules,
+            cpu_types,
+            entries,
+            thunks,
+            native,
+            trampolines,
+            separated,
+        })
+    }
+
+    /// Returns the number of distinct functions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no functions were found.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the module information of all modules with their index, in module order.
+    pub(crate) fn modules(&self) -> &[(usize, ModuleInfo<'s>)] {
+        &self.modules
+    }
+
+    /// Returns an iterator over all functions, sorted by address.
+    pub fn iter(&self) -> FunctionIter<'_, 's> {
+        FunctionIter {
+            functions: self,
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns the function containing the given address.
+    ///
+    /// Functions split by hot/cold optimizations contain the code of their separated blocks
+    /// (`S_SEPCODE`), so an address in such a block returns the function the block was separated
+    /// from.
+    pub fn lookup(&self, rva: Rva) -> Result<Option<Function<'_>>> {
+        let entry = match find_range(&self.entries, rva, |entry| &entry.range) {
+            Some(entry) => entry,
+            None => match find_range(&self.separated, rva, |block| &block.range) {
+                Some(block) => match find_range(&self.entries, block.target, |e| &e.range) {
+                    Some(entry) => entry,
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            },
+        };
+
+        self.resolve(entry).map(Some)
+    }
+
+    /// Returns the function containing the given address, following trampolines to the function
+    /// they jump to.
+    ///
+    /// Incrementally linked executables call functions through a table of jump thunks, and
+    /// executables with large code sections may contain branch islands. Both are described by
+    /// `S_TRAMPOLINE` symbols. If the address lies in a trampoline, this returns the function at
+    /// its target. Otherwise, this is equivalent to [`lookup`](Self::lookup).
+    pub fn lookup_target(&self, rva: Rva) -> Result<Option<Function<'_>>> {
+        self.lookup(self.trampoline_target(rva).unwrap_or(rva))
+    }
+
+    /// Returns the target of the trampoline at the given address.
+    ///
+    /// Returns `None` if the address does not lie in a trampoline.
+    pub fn trampoline_target(&self, rva: Rva) -> Option<Rva> {
+        find_range(&self.trampolines, rva, |trampoline| &trampoline.range)
+            .map(|trampoline| trampoline.target)
+    }
+
+    /// Returns the function containing the given address, following thunks of hybrid images to
+    /// the native function they forward to.
+    ///
+    /// In ARM64EC and CHPE images, callers compiled for x64 or x86 enter native functions through
+    /// thunks, such as the fast-forward sequence of an exported function. These thunks are named
+    /// after the function, while the native function carries a `#` prefix (C) or a `$$h` tag (C++)
+    /// in its decorated name. If the address lies in such a thunk, this returns the native
+    /// function. Otherwise, this is equivalent to [`lookup`](Self::lookup).
+    ///
+    /// `S_THUNK32` records do not store the target of these thunks, so they are matched to native
+    /// functions by name mangling alone. Thunks whose native function has no procedure symbol, or
+    /// whose names follow other conventions, are not followed.
+    pub fn lookup_native(&self, rva: Rva) -> Result<Option<Function<'_>>> {
+        self.lookup(self.native_target(rva).unwrap_or(rva))
+    }
+
+    /// Returns the functions with code but without line information, sorted by address.
+    ///
+    /// These are usually written in assembly, or come from translation units compiled without
+    /// debug information, and cannot be stepped through at the source level. A function is listed
+    /// if none of the line records of its module start within its code.
+    pub fn without_lines(&self) -> Result<Vec<Function<'_>>> {
+        let mut programs: Vec<Option<SharedLineProgram<'s>>> =
+            self.modules.iter().map(|_| None).collect();
+        let mut functions = Vec::new();
+
+        for entry in &self.entries {
+            if entry.range.is_empty() {
+                continue;
+            }
+
+            let program = match programs[entry.slot] {
+                Some(ref program) => program.program(),
+                ref mut slot => slot
+                    .insert(self.modules[entry.slot].1.shared_line_program()?)
+                    .program(),
+            };
+
+            let procedure = self.procedure(entry)?;
+            let start = procedure.offset;
+            let end = u64::from(start.offset) + u64::from(procedure.len);
+
+            let mut lines = program.lines_in_range(start, Some(procedure.len));
+            let has_lines = lines.any(|line| {
+                Ok(line.offset.section == start.section
+                    && line.offset.offset >= start.offset
+                    && u64::from(line.offset.offset) < end)
+            })?;
+
+            if !has_lines {
+                functions.push(self.resolve(entry)?);
+            }
+        }
+
+        Ok(functions)
+    }
+
+    /// Returns the start of the native function that a thunk at the given address forwards to.
+    fn native_target(&self, rva: Rva) -> Option<Rva> {
+        let thunk = find_range(&self.thunks, rva, |thunk| &thunk.range)?;
+        self.native.get(&thunk.target).copied()
+    }
+
+    fn procedure(&self, entry: &FunctionEntry) -> Result<ProcedureSymbol<'_>> {
+        let symbol = self.modules[entry.slot].1.symbol_at(entry.symbol)?;
+
+        match symbol.parse()? {
+            SymbolData::Procedure(procedure) => Ok(procedure),
+            _ => unreachable!("function entries refer to procedure symbols"),
+        }
+    }
+
+    fn resolve(&self, entry: &FunctionEntry) -> Result<Function<'_>> {
+        let module = self.modules[entry.slot].0;
+        let name = self.procedure(entry)?.name;
+
+        Ok(Function {
+            range: entry.range.clone(),
+            name,
+            module,
+            symbol: entry.symbol,
+            folded: entry.folded,
+            cpu_type: self.cpu_types[entry.slot],
+        })
+    }
+}
+
+/// Returns whether a decorated name is mangled like native code of a hybrid image.
+fn has_native_mangling(name: &[u8]) -> bool {
+    name.starts_with(b"#") || name.windows(3).any(|window| window == b"$$h")
+}
+
+/// Returns the decorated name of the native function that a thunk of a hybrid image forwards to,
+/// by applying the ARM64EC name mangling to the name of the thunk.
+///
+/// C functions are prefixed with `#`, and C++ functions carry a `$$h` tag after their qualified
+/// name, following the rules of MSVC and LLVM. Returns `None` if the name already refers to native
+/// code, or belongs to an entry or exit thunk generated for calls between architectures.
+fn native_mangling(name: &[u8]) -> Option<Vec<u8>> {
+    if has_native_mangling(name) || name.starts_with(b"$i") || name.ends_with(b"_thunk") {
+        return None;
+    }
+
+    if !name.starts_with(b"?") {
+        let mut native = Vec::with_capacity(name.len() + 1);
+        native.push(b'#');
+        native.extend_from_slice(name);
+        return Some(native);
+    }
+
+    // Insert the tag after the qualified name, which is terminated by `@@` unless the name ends in
+    // a template argument list.
+    let find = |needle: &[u8]| name.windows(needle.len()).position(|w| w == needle);
+    let index = match (find(b"@@"), find(b"@@@")) {
+        (Some(index), three) if three != Some(index) => index + 2,
+        _ => find(b"@")? + 1,
+    };
+
+    let mut native = Vec::with_capacity(name.len() + 3);
+    native.extend_from_slice(&name[..index]);
+    native.extend_from_slice(b"$$h");
+    native.extend_from_slice(&name[index..]);
+    Some(native)
+}
+
+/// An iterator over the functions of a PDB.
+///
+/// Created by [`Functions::iter`].
+pub struct FunctionIter<'a, 's> {
+    functions: &'a Functions<'s>,
+    entries: core::slice::Iter<'a, FunctionEntry>,
+}
+
+impl<'a> FallibleIterator for FunctionIter<'a, '_> {
+    type Item = Function<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        match self.entries.next() {
+            Some(entry) => self.functions.resolve(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_mangling() {
+        let native =
+            |name: &str| native_mangling(name.as_bytes()).map(|n| String::from_utf8(n).unwrap());
+
+        assert_eq!(native("foo").as_deref(), Some("#foo"));
+        assert_eq!(native("?foo@@YAHXZ").as_deref(), Some("?foo@@$$hYAHXZ"));
+        assert_eq!(
+            native("?bar@Baz@@QEAAXXZ").as_deref(),
+            Some("?bar@Baz@@$$hQEAAXXZ")
+        );
+        assert_eq!(
+            native("??$f@H@@@YAXXZ").as_deref(),
+            Some("??$f@$$hH@@@YAXXZ")
+        );
+
+        assert_eq!(native("#foo"), None);
+        assert_eq!(native("?foo@@$$hYAHXZ"), None);
+        assert_eq!(native("#foo$exit_thunk"), None);
+        assert_eq!(native("$ientry_thunk$cdecl$v$v"), None);
+    }
+
+    #[test]
+    fn test_native_mangling_arm64ec() {
+        let native =
+            |name: &str| native_mangling(name.as_bytes()).map(|n| String::from_utf8(n).unwrap());
+
+        // Symbols of an ARM64EC build of a DLL with C and C++ exports.
+        assert_eq!(native("memcpy").as_deref(), Some("#memcpy"));
+        assert_eq!(native("DllMain").as_deref(), Some("#DllMain"));
+        assert_eq!(
+            native("?GetValue@CFoo@@QEBAHXZ").as_deref(),
+            Some("?GetValue@CFoo@@$$hQEBAHXZ")
+        );
+        assert_eq!(
+            native("??0CFoo@@QEAA@XZ").as_deref(),
+            Some("??0CFoo@@$$hQEAA@XZ")
+        );
+        assert_eq!(
+            native("??$max@H@std@@YAAEBHAEBH0@Z").as_deref(),
+            Some("??$max@H@std@@$$hYAAEBHAEBH0@Z")
+        );
+
+        // Native code and the thunks generated between architectures.
+        assert!(has_native_mangling(b"#memcpy"));
+        assert!(has_native_mangling(b"?GetValue@CFoo@@$$hQEBAHXZ"));
+        assert!(!has_native_mangling(b"?GetValue@CFoo@@QEBAHXZ"));
+        assert_eq!(native("#memcpy$exit_thunk"), None);
+        assert_eq!(native("$iexit_thunk$cdecl$i8$i8"), None);
+        assert_eq!(native("$ientry_thunk$cdecl$i8$i8i8i8"), None);
+    }
+
+    #[test]
+    fn test_native_target() {
+        let mut native = BTreeMap::new();
+        native.insert(b"#foo".to_vec(), Rva(0x2000));
+
+        let functions = Functions {
+            modules: Vec::new(),
+            cpu_types: Vec::new(),
+            entries: Vec::new(),
+            thunks: alloc::vec![
+                HybridThunk {
+                    range: Rva(0x1000)..Rva(0x1010),
+                    target: b"#foo".to_vec(),
+                },
+                HybridThunk {
+                    range: Rva(0x1010)..Rva(0x1020),
+                    target: b"#bar".to_vec(),
+                },
+            ],
+            native,
+            trampolines: Vec::new(),
+            separated: Vec::new(),
+        };
+
+        assert_eq!(functions.native_target(Rva(0xfff)), None);
+        assert_eq!(functions.native_target(Rva(0x1000)), Some(Rva(0x2000)));
+        assert_eq!(functions.native_target(Rva(0x100f)), Some(Rva(0x2000)));
+        assert_eq!(functions.native_target(Rva(0x1010)), None);
+        assert_eq!(functions.native_target(Rva(0x1020)), None);
+    }
+
+    #[test]
+    fn test_separated_code() {
+        let functions = Functions {
+            modules: Vec::new(),
+            cpu_types: Vec::new(),
+            entries: Vec::new(),
+            thunks: Vec::new(),
+            native: BTreeMap::new(),
+            trampolines: alloc::vec![Redirect {
+                range: Rva(0x1000)..Rva(0x1005),
+                target: Rva(0x2000),
+            }],
+            separated: alloc::vec![Redirect {
+                range: Rva(0x3000)..Rva(0x3040),
+                target: Rva(0x2000),
+            }],
+        };
+
+        assert_eq!(functions.trampoline_target(Rva(0x1004)), Some(Rva(0x2000)));
+        assert_eq!(functions.trampoline_target(Rva(0x1005)), None);
+
+        let block = |rva| find_range(&functions.separated, rva, |block| &block.range);
+        assert_eq!(block(Rva(0x2fff)).map(|block| block.target), None);
+        assert_eq!(
+            block(Rva(0x3020)).map(|block| block.target),
+            Some(Rva(0x2000))
+        );
+        assert_eq!(block(Rva(0x3040)).map(|block| block.target), None);
+    }
+}
@@ -0,0 +1,253 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::common::*;
+use crate::dbi::DBIModuleInfo;
+use crate::modi::{Inlinee, InlineeLineIterator, ModuleInfo};
+use crate::pdb::PDB;
+use crate::source::Source;
+use crate::symbol::{InlineSiteSymbol, ProcedureSymbol, SymbolData, SymbolIter};
+use crate::sync::{self, Once};
+use crate::FallibleIterator;
+
+/// An inline site together with the procedure it was inlined into.
+///
+/// Returned by [`ModuleInfo::inline_sites`] and [`InlineSites::iter`].
+#[derive(Clone, Debug)]
+pub struct InlineSite<'a> {
+    /// Index of the outermost procedure symbol containing this inline site.
+    pub procedure_index: SymbolIndex,
+    /// The outermost procedure containing this inline site.
+    ///
+    /// For inline sites nested in other inline sites, this is still the procedure the code was
+    /// ultimately inlined into.
+    pub procedure: ProcedureSymbol<'a>,
+    /// Index of the inline site symbol.
+    pub index: SymbolIndex,
+    /// The inline site symbol.
+    pub symbol: InlineSiteSymbol<'a>,
+    /// The inlinee record of the inlined function, if the module declares one.
+    pub inlinee: Option<Inlinee<'a>>,
+}
+
+impl<'a> InlineSite<'a> {
+    /// Returns an iterator over the line records of this inline site.
+    ///
+    /// Returns `None` if the module does not contain an inlinee record for the inlined function.
+    pub fn lines(&self) -> Option<InlineeLineIterator<'a>> {
+        let inlinee = self.inlinee.as_ref()?;
+        Some(inlinee.lines(self.procedure.offset, &self.symbol))
+    }
+}
+
+/// An iterator over the inline sites of a single module.
+///
+/// Created by [`ModuleInfo::inline_sites`]. The iterator only borrows the module's data, so with the
+/// `sync` feature, the iterators of different modules can be moved to separate threads and consumed
+/// in parallel.
+#[derive(Debug)]
+pub struct ModuleInlineSiteIter<'a> {
+    symbols: SymbolIter<'a>,
+    inlinees: BTreeMap<IdIndex, Inlinee<'a>>,
+    procedure: Option<(SymbolIndex, ProcedureSymbol<'a>)>,
+}
+
+impl<'a> ModuleInlineSiteIter<'a> {
+    pub(crate) fn new(
+        symbols: SymbolIter<'a>,
+        mut inlinees: impl FallibleIterator<Item = Inlinee<'a>, Error = Error>,
+    ) -> Result<Self> {
+        let mut map = BTreeMap::new();
+        while let Some(inlinee) = inlinees.next()? {
+            map.entry(inlinee.index()).or_insert(inlinee);
+        }
+
+        Ok(Self {
+            symbols,
+            inlinees: map,
+            procedure: None,
+        })
+    }
+}
+
+impl<'a> FallibleIterator for ModuleInlineSiteIter<'a> {
+    type Item = InlineSite<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(symbol) = self.symbols.next()? {
+            if let Some((_, procedure)) = self.procedure {
+                if symbol.index() == procedure.end {
+                    self.procedure = None;
+                    continue;
+                }
+            }
+
+            if !symbol.starts_scope() {
+                continue;
+            }
+
+            match symbol.parse()? {
+                SymbolData::Procedure(procedure) if self.procedure.is_none() => {
+                    self.procedure = Some((symbol.index(), procedure));
+                }
+                SymbolData::InlineSite(site) => {
+                    // inline sites are only meaningful relative to their procedure's code
+                    let (procedure_index, procedure) = match self.procedure {
+                        Some(procedure) => procedure,
+                        None => continue,
+                    };
+
+                    return Ok(Some(InlineSite {
+                        procedure_index,
+                        procedure,
+                        index: symbol.index(),
+                        symbol: site,
+                        inlinee: self.inlinees.get(&site.inlinee).cloned(),
+                    }));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The inline sites of all modules in a PDB.
+///
+/// Created by [`PDB::inline_sites`](crate::PDB::inline_sites). Use [`iter`](Self::iter) to visit
+/// all inline sites sequentially, [`module`](Self::module) to look up the inline sites of a single
+/// module, or [`modules`](Self::modules) to obtain an independent iterator per module, for example
+/// to process modules in parallel.
+///
+/// The module information of a module is loaded from the PDB when its inline sites are first
+/// requested, and kept for subsequent requests.
+pub struct InlineSites<'p, 's, S> {
+    pdb: &'p PDB<'s, S>,
+    /// The index and descriptor of each module that has module information, in stream order.
+    modules: Vec<(usize, DBIModuleInfo)>,
+    /// The module information of each entry in `modules`, loaded on first use.
+    infos: Vec<Once<ModuleInfo<'s>>>,
+}
+
+impl<'p, 's, S: Source<'s> + 's> InlineSites<'p, 's, S> {
+    pub(crate) fn new(pdb: &'p PDB<'s, S>, modules: Vec<(usize, DBIModuleInfo)>) -> Self {
+        let infos = modules.iter().map(|_| Once::new()).collect();
+        Self {
+            pdb,
+            modules,
+            infos,
+        }
+    }
+
+    /// Returns the module information of the entry at `position`, loading it on first use.
+    fn info(&self, position: usize) -> Result<&ModuleInfo<'s>> {
+        let (_, ref module) = self.modules[position];
+        sync::get_or_try_init(&self.infos[position], || {
+            let stream = self
+                .pdb
+                .raw_stream(module.stream)?
+                .ok_or(Error::StreamNotFound(module.stream.0.into()))?;
+            Ok(ModuleInfo::parse(stream, module))
+        })
+    }
+
+    /// Returns an iterator over the inline sites of the module at `index` in
+    /// [`DebugInformation::modules`](crate::DebugInformation::modules).
+    ///
+    /// Returns `None` if the module does not exist or has no module information.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * Errors reading the inlinee records of the module
+    pub fn module(&self, index: usize) -> Result<Option<ModuleInlineSiteIter<'_>>> {
+        match self
+            .modules
+            .binary_search_by_key(&index, |&(module, _)| module)
+        {
+            Ok(position) => self.info(position)?.inline_sites().map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the inline sites of each module that has module information.
+    ///
+    /// Each iterator is paired with the index of its module in
+    /// [`DebugInformation::modules`](crate::DebugInformation::modules). This loads the module
+    /// information of all modules.
+    pub fn modules(&self) -> Result<Vec<(usize, ModuleInlineSiteIter<'_>)>> {
+        (0..self.modules.len())
+            .map(|position| {
+                Ok((
+                    self.modules[position].0,
+                    self.info(position)?.inline_sites()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the inline sites of all modules.
+    ///
+    /// Each inline site is paired with the index of its module in
+    /// [`DebugInformation::modules`](crate::DebugInformation::modules). Modules are loaded as the
+    /// iterator reaches them.
+    pub fn iter(&self) -> InlineSiteIter<'_, 'p, 's, S> {
+        InlineSiteIter {
+            sites: self,
+            position: 0,
+            current: None,
+        }
+    }
+}
+
+impl<S> fmt::Debug for InlineSites<'_, '_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let loaded = self
+            .infos
+            .iter()
+            .filter(|info| info.get().is_some())
+            .count();
+        f.debug_struct("InlineSites")
+            .field("modules", &self.modules.len())
+            .field("loaded", &loaded)
+            .finish()
+    }
+}
+
+/// An iterator over the inline sites of all modules in a PDB.
+///
+/// Created by [`InlineSites::iter`].
+#[derive(Debug)]
+pub struct InlineSiteIter<'a, 'p, 's, S> {
+    sites: &'a InlineSites<'p, 's, S>,
+    /// The position of the next module in `sites`.
+    position: usize,
+    current: Option<(usize, ModuleInlineSiteIter<'a>)>,
+}
+
+impl<'a, 's, S: Source<'s> + 's> FallibleIterator for InlineSiteIter<'a, '_, 's, S> {
+    type Item = (usize, InlineSite<'a>);
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some((module, ref mut current)) = self.current {
+                if let Some(site) = current.next()? {
+                    return Ok(Some((module, site)));
+                }
+            }
+
+            if self.position >= self.sites.modules.len() {
+                return Ok(None);
+            }
+
+            let module = self.sites.modules[self.position].0;
+            let sites = self.sites.info(self.position)?.inline_sites()?;
+            self.current = Some((module, sites));
+            self.position += 1;
+        }
+    }
+}
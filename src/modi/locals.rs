@@ -0,0 +1,675 @@
+use crate::common::*;
+use crate::symbol::{
+    DefRangeLocation, DefRangeSymbol, LocalVariableFlags, Symbol, SymbolData, SymbolIter, Variable,
+};
+use crate::tpi::{TypeData, TypeFinder};
+use crate::FallibleIterator;
+use alloc::vec::Vec;
+
+/// Describes where the value of a [`LocalVariable`] is stored.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LocalVariableStorage {
+    /// The variable is declared by an `S_LOCAL` symbol.
+    ///
+    /// Its location is described by the def-range symbols immediately following the declaration.
+    /// Use [`ModuleInfo::variable_location`](crate::ModuleInfo::variable_location) to obtain them.
+    DefRanges,
+    /// The variable is stored at `offset` relative to the value of `register`.
+    RegisterRelative {
+        /// The register this variable address is relative to.
+        register: Register,
+        /// The variable offset.
+        offset: i32,
+    },
+    /// The variable is stored at the given offset relative to the frame pointer.
+    FramePointerRelative(i32),
+}
+
+/// A local variable or parameter of a procedure.
+///
+/// Returned by [`ModuleInfo::locals_of`](crate::ModuleInfo::locals_of).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LocalVariable<'t> {
+    /// Index of the symbol declaring this variable.
+    pub index: SymbolIndex,
+    /// Name of the variable.
+    pub name: RawString<'t>,
+    /// The type of the variable.
+    pub type_index: TypeIndex,
+    /// Flags of the variable, if it is declared by an `S_LOCAL` symbol.
+    pub flags: Option<LocalVariableFlags>,
+    /// Where the value of this variable is stored.
+    pub storage: LocalVariableStorage,
+}
+
+impl<'t> LocalVariable<'t> {
+    fn from_symbol(index: SymbolIndex, data: SymbolData<'t>) -> Option<Self> {
+        Some(match data {
+            SymbolData::Local(data) => LocalVariable {
+                index,
+                name: data.name,
+                type_index: data.type_index,
+                flags: Some(data.flags),
+                storage: LocalVariableStorage::DefRanges,
+            },
+            SymbolData::RegisterRelative(data) => LocalVariable {
+                index,
+                name: data.name,
+                type_index: data.type_index,
+                flags: None,
+                storage: LocalVariableStorage::RegisterRelative {
+                    register: data.register,
+                    offset: data.offset,
+                },
+            },
+            SymbolData::BasePointerRelative(data) => LocalVariable {
+                index,
+                name: data.name,
+                type_index: data.type_index,
+                flags: None,
+                storage: LocalVariableStorage::FramePointerRelative(data.offset),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// The location of a variable, normalized across the symbol kinds that declare variables.
+///
+/// Created by [`VariableLocation::from_symbol`] or
+/// [`ModuleInfo::variable_location`](crate::ModuleInfo::variable_location).
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VariableLocation {
+    /// The variable is stored in a register.
+    ///
+    /// Declared by `S_REGISTER`.
+    Register(Register),
+    /// The variable is stored at `offset` relative to the value of `register`.
+    ///
+    /// Declared by `S_REGREL32`.
+    RegisterRelative {
+        /// The base register.
+        register: Register,
+        /// Offset of the variable relative to the base register.
+        offset: i32,
+    },
+    /// The variable is stored at the given offset relative to the frame pointer.
+    ///
+    /// Declared by `S_BPREL32`.
+    FrameRelative(i32),
+    /// The variable is stored at a static address.
+    ///
+    /// Declared by `S_LDATA32`, `S_GDATA32` and their managed variants.
+    Static(PdbInternalSectionOffset),
+    /// The variable is stored at the given offset in the thread local storage of each thread.
+    ///
+    /// Declared by `S_LTHREAD32` or `S_GTHREAD32`.
+    ThreadLocal(PdbInternalSectionOffset),
+    /// The variable has a constant value and is not stored anywhere.
+    ///
+    /// Declared by `S_CONSTANT`.
+    Constant(Variant),
+    /// The location of the variable depends on the code address.
+    ///
+    /// Declared by an `S_LOCAL` followed by def-range symbols, which are listed here in order. The
+    /// list is empty if the variable has been optimized out entirely. Use [`at`](Self::at) to
+    /// obtain the location at a specific address.
+    Composite(Vec<DefRangeSymbol>),
+}
+
+impl From<Variable<'_>> for VariableLocation {
+    fn from(variable: Variable<'_>) -> Self {
+        Self::Composite(variable.ranges)
+    }
+}
+
+impl VariableLocation {
+    /// Returns the location of the variable declared by a symbol.
+    ///
+    /// Returns `None` for symbols that do not declare variables, and for `S_LOCAL`, whose location
+    /// is described by the following def-range symbols. Use
+    /// [`ModuleInfo::variable_location`](crate::ModuleInfo::variable_location) to resolve those, or
+    /// convert a [`Variable`] returned by [`SymbolIter::variables`].
+    pub fn from_symbol(data: &SymbolData<'_>) -> Option<Self> {
+        Some(match data {
+            SymbolData::RegisterVariable(data) => Self::Register(data.register),
+            SymbolData::RegisterRelative(data) => Self::RegisterRelative {
+                register: data.register,
+                offset: data.offset,
+            },
+            SymbolData::BasePointerRelative(data) => Self::FrameRelative(data.offset),
+            SymbolData::Data(data) => Self::Static(data.offset),
+            SymbolData::ThreadStorage(data) => Self::ThreadLocal(data.offset),
+            SymbolData::Constant(data) => Self::Constant(data.value),
+            _ => return None,
+        })
+    }
+
+    /// Returns the location of the whole variable at the given code address.
+    ///
+    /// For [`Composite`](Self::Composite) locations, this is the first def-range that contains
+    /// `offset` and describes the entire variable. Returns `None` if there is no such def-range,
+    /// for instance because the variable is split into fields stored in different places. All
+    /// other locations are valid at every address and are returned unchanged.
+    pub fn at(&self, offset: PdbInternalSectionOffset) -> Option<Self> {
+        let ranges = match self {
+            Self::Composite(ranges) => ranges,
+            _ => return Some(self.clone()),
+        };
+
+        ranges
+            .iter()
+            .filter(|range| range.contains(offset))
+            .find_map(|range| match range.location {
+                DefRangeLocation::Register { register, .. } => Some(Self::Register(register)),
+                DefRangeLocation::FramePointerRelative { offset } => {
+                    Some(Self::FrameRelative(offset))
+                }
+                DefRangeLocation::RegisterRelative {
+                    register,
+                    offset,
+                    spilled_udt_member: false,
+                    ..
+                } => Some(Self::RegisterRelative { register, offset }),
+                _ => None,
+            })
+    }
+}
+
+/// Resolves the location of the variable declared by the next symbol of `symbols`.
+pub(crate) fn variable_location(mut symbols: SymbolIter<'_>) -> Result<Option<VariableLocation>> {
+    let symbol = match symbols.next()? {
+        Some(symbol) => symbol,
+        None => return Ok(None),
+    };
+
+    let data = symbol.parse()?;
+    if !matches!(data, SymbolData::Local(_)) {
+        return Ok(VariableLocation::from_symbol(&data));
+    }
+
+    // Group the declaration with its def-ranges the same way `SymbolIter::variables` does.
+    symbols.seek(symbol.index());
+    Ok(symbols.variables().next()?.map(VariableLocation::from))
+}
+
+/// Collects the local variables of the scope started by the next symbol of `symbols`.
+///
+/// Variables of nested blocks are included, while variables of inlined functions are skipped.
+pub(crate) fn scope_locals(mut symbols: SymbolIter<'_>) -> Result<Vec<LocalVariable<'_>>> {
+    let mut locals = Vec::new();
+
+    match symbols.next()? {
+        Some(scope) if scope.starts_scope() => (),
+        _ => return Ok(locals),
+    }
+
+    let mut depth = 1;
+    let mut inline_depth = None;
+
+    while let Some(symbol) = symbols.next()? {
+        if symbol.is_def_range() {
+            continue;
+        }
+
+        if symbol.starts_scope() {
+            depth += 1;
+            if inline_depth.is_none() && matches!(symbol.parse(), Ok(SymbolData::InlineSite(_))) {
+                inline_depth = Some(depth);
+            }
+            continue;
+        }
+
+        if symbol.ends_scope() {
+            if inline_depth == Some(depth) {
+                inline_depth = None;
+            }
+
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+            continue;
+        }
+
+        if inline_depth.is_some() {
+            continue;
+        }
+
+        let data = symbol.parse()?;
+
+        if let Some(local) = LocalVariable::from_symbol(symbol.index(), data) {
+            locals.push(local);
+        }
+    }
+
+    Ok(locals)
+}
+
+/// A parameter of a procedure, as declared by its signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Parameter<'t> {
+    /// The type of the parameter according to the procedure's signature.
+    ///
+    /// A type index of `0` marks a variadic argument list (`...`).
+    pub type_index: TypeIndex,
+    /// The variable holding this parameter in the procedure, if it could be found.
+    pub variable: Option<LocalVariable<'t>>,
+}
+
+impl<'t> Parameter<'t> {
+    /// Returns the name of this parameter, if its variable could be found.
+    pub fn name(&self) -> Option<RawString<'t>> {
+        self.variable.map(|variable| variable.name)
+    }
+}
+
+/// The signature of a procedure with named parameters.
+///
+/// Returned by [`ModuleInfo::signature_of`](crate::ModuleInfo::signature_of).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcedureSignature<'t> {
+    /// The return type of the procedure, if any.
+    pub return_type: Option<TypeIndex>,
+    /// The implicit `this` parameter of non-static member functions.
+    pub this: Option<Parameter<'t>>,
+    /// The explicit parameters of the procedure in declaration order.
+    pub parameters: Vec<Parameter<'t>>,
+}
+
+/// Pairs the variables of a procedure with the argument types of its signature.
+///
+/// If any variable is declared by an `S_LOCAL` symbol, only variables flagged as parameters are
+/// considered. Otherwise, compilers declare parameters before all other variables, so variables
+/// are paired in declaration order.
+fn pair_parameters<'t>(
+    locals: &[LocalVariable<'t>],
+    return_type: Option<TypeIndex>,
+    this_type: Option<TypeIndex>,
+    arguments: &[TypeIndex],
+) -> ProcedureSignature<'t> {
+    let has_flags = locals.iter().any(|local| local.flags.is_some());
+    let mut candidates = locals
+        .iter()
+        .filter(|local| !has_flags || local.flags.is_some_and(|flags| flags.isparam))
+        .copied()
+        .peekable();
+
+    let this = this_type.map(|type_index| Parameter {
+        type_index,
+        // without flags, `this` is the first variable
+        variable: candidates.next_if(|local| !has_flags || local.name.as_bytes() == b"this"),
+    });
+
+    let parameters = arguments
+        .iter()
+        .map(|&type_index| Parameter {
+            type_index,
+            variable: match type_index {
+                TypeIndex(0) => None,
+                _ => candidates.next(),
+            },
+        })
+        .collect();
+
+    ProcedureSignature {
+        return_type,
+        this,
+        parameters,
+    }
+}
+
+/// Resolves the signature of the procedure started by the next symbol of `symbols`.
+pub(crate) fn procedure_signature<'t>(
+    mut symbols: SymbolIter<'t>,
+    type_finder: &TypeFinder<'_>,
+) -> Result<Option<ProcedureSignature<'t>>> {
+    let (index, procedure) = match symbols.next()? {
+        Some(symbol) => match symbol.parse()? {
+            SymbolData::Procedure(procedure) => (symbol.index(), procedure),
+            _ => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+
+    // T_NOTYPE, emitted for procedures without type information.
+    if procedure.type_index == TypeIndex(0) {
+        return Ok(None);
+    }
+
+    let item = type_finder.find(procedure.type_index)?;
+    let (return_type, this_type, argument_list) = match item.parse()? {
+        TypeData::Procedure(data) => (data.return_type, None, data.argument_list),
+        TypeData::MemberFunction(data) => (
+            Some(data.return_type),
+            data.this_pointer_type,
+            data.argument_list,
+        ),
+        _ => return Err(Error::UnimplementedTypeKind(item.raw_kind())),
+    };
+
+    let item = type_finder.find(argument_list)?;
+    let arguments = match item.parse()? {
+        TypeData::ArgumentList(list) => list.arguments,
+        _ => return Err(Error::UnimplementedTypeKind(item.raw_kind())),
+    };
+
+    symbols.seek(index);
+    let locals = scope_locals(symbols)?;
+    Ok(Some(pair_parameters(
+        &locals,
+        return_type,
+        this_type,
+        &arguments,
+    )))
+}
+
+/// Returns whether the code range starting at `offset` with length `len` contains `address`.
+fn contains(offset: PdbInternalSectionOffset, len: u32, address: PdbInternalSectionOffset) -> bool {
+    offset.section == address.section
+        && offset.offset <= address.offset
+        && u64::from(address.offset) < u64::from(offset.offset) + u64::from(len)
+}
+
+/// Resolves `name` to the innermost variable visible at `address` in the procedure started by the
+/// next symbol of `symbols`.
+pub(crate) fn resolve_scoped_name<'t>(
+    mut symbols: SymbolIter<'t>,
+    address: PdbInternalSectionOffset,
+    name: &[u8],
+) -> Result<Option<Symbol<'t>>> {
+    match symbols.next()? {
+        Some(symbol) => match symbol.parse() {
+            Ok(SymbolData::Procedure(proc)) if contains(proc.offset, proc.len, address) => (),
+            _ => return Ok(None),
+        },
+        None => return Ok(None),
+    }
+
+    let mut depth = 1;
+    let mut skip_depth = None;
+    let mut best: Option<(usize, Symbol<'t>)> = None;
+
+    while let Some(symbol) = symbols.next()? {
+        if symbol.starts_scope() {
+            depth += 1;
+
+            // only blocks containing the address contribute to the scope. inline sites declare
+            // variables of the inlined function, which are not visible in this procedure.
+            if skip_depth.is_none() {
+                let active = match symbol.parse() {
+                    Ok(SymbolData::Block(block)) => contains(block.offset, block.len, address),
+                    _ => false,
+                };
+
+                if !active {
+                    skip_depth = Some(depth);
+                }
+            }
+            continue;
+        }
+
+        if symbol.ends_scope() {
+            if skip_depth == Some(depth) {
+                skip_depth = None;
+            }
+
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+            continue;
+        }
+
+        if skip_depth.is_some() {
+            continue;
+        }
+
+        let symbol_name = match symbol.parse()? {
+            SymbolData::Local(data) => data.name,
+            SymbolData::RegisterRelative(data) => data.name,
+            SymbolData::BasePointerRelative(data) => data.name,
+            SymbolData::Data(data) => data.name,
+            SymbolData::ThreadStorage(data) => data.name,
+            _ => continue,
+        };
+
+        // declarations in deeper scopes shadow the ones of their parents
+        if symbol_name.as_bytes() == name && best.map_or(true, |(d, _)| depth > d) {
+            best = Some((depth, symbol));
+        }
+    }
+
+    Ok(best.map(|(_, symbol)| symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::modi::test_records::{block, procedure, record};
+
+    #[test]
+    fn test_scope_locals() {
+        let data = &[
+            0x06, 0x00, 0x03, 0x11, // S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x0a, 0x00, 0x3e, 0x11, // S_LOCAL
+            0x74, 0x00, 0x00, 0x00, 0x01, 0x00, b'a', 0x00, // int a, parameter
+            0x04, 0x00, 0x41, 0x11, 0x00, 0x00, // S_DEFRANGE_REGISTER
+            0x04, 0x00, 0x42, 0x11, 0x00, 0x00, // S_DEFRANGE_FRAMEPOINTER_REL
+            0x0e, 0x00, 0x11, 0x11, // S_REGREL32
+            0x08, 0x00, 0x00, 0x00, 0x74, 0x00, 0x00, 0x00, 0x16, 0x00, b'b', 0x00, // int b
+            0x0e, 0x00, 0x4d, 0x11, // S_INLINESITE
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // parent, end
+            0x00, 0x10, 0x00, 0x00, // inlinee
+            0x0c, 0x00, 0x0b, 0x11, // S_BPREL32
+            0xf8, 0xff, 0xff, 0xff, 0x74, 0x00, 0x00, 0x00, b'c', 0x00, // int c, inlined
+            0x02, 0x00, 0x4e, 0x11, // S_INLINESITE_END
+            0x0c, 0x00, 0x0b, 0x11, // S_BPREL32
+            0xf8, 0xff, 0xff, 0xff, 0x74, 0x00, 0x00, 0x00, b'd', 0x00, // int d
+            0x02, 0x00, 0x06, 0x00, // S_END
+            0x0c, 0x00, 0x0b, 0x11, // S_BPREL32
+            0xf8, 0xff, 0xff, 0xff, 0x74, 0x00, 0x00, 0x00, b'e', 0x00, // int e, out of scope
+        ];
+
+        let symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+        let locals = scope_locals(symbols).expect("locals");
+
+        let names: Vec<_> = locals.iter().map(|local| local.name).collect();
+        assert_eq!(names, vec!["a".into(), "b".into(), "d".into()]);
+
+        assert_eq!(locals[0].storage, LocalVariableStorage::DefRanges);
+        assert!(locals[0].flags.expect("flags").isparam);
+        assert_eq!(
+            locals[1].storage,
+            LocalVariableStorage::RegisterRelative {
+                register: Register(0x16),
+                offset: 8,
+            }
+        );
+        assert_eq!(
+            locals[2].storage,
+            LocalVariableStorage::FramePointerRelative(-8)
+        );
+        assert_eq!(locals[2].type_index, TypeIndex(0x74));
+    }
+
+    #[test]
+    fn test_variable_location() {
+        let data = &[
+            0x0a, 0x00, 0x3e, 0x11, // S_LOCAL
+            0x74, 0x00, 0x00, 0x00, 0x00, 0x00, b'a', 0x00, // int a
+            0x0e, 0x00, 0x41, 0x11, 0x11, 0x00, 0x00, 0x00, // S_DEFRANGE_REGISTER eax
+            0x00, 0x10, 0x00, 0x00, 0x01, 0x00, 0x10, 0x00, // 0001:1000, length 0x10
+            0x0e, 0x00, 0x42, 0x11, 0xf8, 0xff, 0xff, 0xff, // S_DEFRANGE_FRAMEPOINTER_REL -8
+            0x10, 0x10, 0x00, 0x00, 0x01, 0x00, 0x10, 0x00, // 0001:1010, length 0x10
+            0x0a, 0x00, 0x06, 0x11, // S_REGISTER
+            0x74, 0x00, 0x00, 0x00, 0x12, 0x00, b'b', 0x00, // int b in ecx
+            0x0e, 0x00, 0x0d, 0x11, // S_GDATA32
+            0x74, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x03, 0x00, b'c', 0x00, // int c
+        ];
+
+        let location = |index| {
+            let mut symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+            symbols.seek(SymbolIndex(index));
+            variable_location(symbols).expect("location")
+        };
+
+        let local = location(0x00).expect("local");
+        assert!(matches!(&local, VariableLocation::Composite(ranges) if ranges.len() == 2));
+
+        let at = |offset| local.at(PdbInternalSectionOffset::new(1, offset));
+        assert_eq!(at(0x0fff), None);
+        assert_eq!(at(0x1000), Some(VariableLocation::Register(Register(0x11))));
+        assert_eq!(at(0x1018), Some(VariableLocation::FrameRelative(-8)));
+        assert_eq!(at(0x1020), None);
+
+        let register = VariableLocation::Register(Register(0x12));
+        assert_eq!(location(0x2c), Some(register.clone()));
+        assert_eq!(
+            register.at(PdbInternalSectionOffset::new(1, 0)),
+            Some(register)
+        );
+
+        assert_eq!(
+            location(0x38),
+            Some(VariableLocation::Static(PdbInternalSectionOffset::new(
+                3, 0x20
+            )))
+        );
+    }
+
+    fn variable(name: &'static str, isparam: Option<bool>) -> LocalVariable<'static> {
+        let flags = isparam.map(|isparam| {
+            let mut data = [0u8; 2];
+            data[0] = isparam as u8;
+            ParseBuffer::from(&data[..])
+                .parse::<LocalVariableFlags>()
+                .expect("flags")
+        });
+
+        LocalVariable {
+            index: SymbolIndex(0),
+            name: name.into(),
+            type_index: TypeIndex(0x74),
+            flags,
+            storage: LocalVariableStorage::DefRanges,
+        }
+    }
+
+    #[test]
+    fn test_pair_flagged_parameters() {
+        let locals = [
+            variable("this", Some(true)),
+            variable("tmp", Some(false)),
+            variable("a", Some(true)),
+            variable("b", Some(true)),
+        ];
+
+        let signature = pair_parameters(
+            &locals,
+            Some(TypeIndex(0x3)),
+            Some(TypeIndex(0x1000)),
+            &[TypeIndex(0x74), TypeIndex(0x70)],
+        );
+
+        let this = signature.this.expect("this");
+        assert_eq!(this.type_index, TypeIndex(0x1000));
+        assert_eq!(this.name(), Some("this".into()));
+
+        let names: Vec<_> = signature.parameters.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec![Some("a".into()), Some("b".into())]);
+        assert_eq!(signature.parameters[1].type_index, TypeIndex(0x70));
+    }
+
+    #[test]
+    fn test_pair_unflagged_parameters() {
+        let locals = [
+            variable("this", None),
+            variable("a", None),
+            variable("local", None),
+        ];
+
+        let signature = pair_parameters(
+            &locals,
+            None,
+            Some(TypeIndex(0x1000)),
+            &[TypeIndex(0x74), TypeIndex(0)],
+        );
+
+        assert_eq!(signature.this.expect("this").name(), Some("this".into()));
+
+        let names: Vec<_> = signature.parameters.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec![Some("a".into()), None]);
+    }
+
+    #[test]
+    fn test_pair_missing_parameters() {
+        let locals = [variable("a", Some(true))];
+
+        let signature = pair_parameters(&locals, None, None, &[TypeIndex(0x74), TypeIndex(0x74)]);
+
+        assert!(signature.this.is_none());
+        let names: Vec<_> = signature.parameters.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec![Some("a".into()), None]);
+    }
+
+    fn bprel(offset: i32, name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&offset.to_le_bytes());
+        data.extend_from_slice(&[0x74, 0, 0, 0]);
+        data.extend_from_slice(name.as_bytes());
+        data.push(0);
+        record(0x110b, &data)
+    }
+
+    fn end() -> Vec<u8> {
+        record(0x0006, &[])
+    }
+
+    fn resolve(data: &[u8], address: u32, name: &str) -> Option<i32> {
+        let symbols = SymbolIter::new(ParseBuffer::from(data));
+        let address = PdbInternalSectionOffset::new(1, address);
+        let symbol = resolve_scoped_name(symbols, address, name.as_bytes()).expect("resolve")?;
+        match symbol.parse().expect("parse") {
+            SymbolData::BasePointerRelative(data) => Some(data.offset),
+            other => panic!("unexpected symbol {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_scoped_name() {
+        let data = [
+            procedure(0x100, 0x100),
+            bprel(-4, "x"),
+            bprel(-8, "y"),
+            block(0x120, 0x10),
+            bprel(-12, "x"),
+            end(),
+            block(0x140, 0x10),
+            bprel(-16, "z"),
+            end(),
+            end(),
+        ]
+        .concat();
+
+        // outside of nested blocks, the procedure's variables are visible
+        assert_eq!(resolve(&data, 0x100, "x"), Some(-4));
+        assert_eq!(resolve(&data, 0x100, "z"), None);
+
+        // the first block shadows `x`
+        assert_eq!(resolve(&data, 0x128, "x"), Some(-12));
+        assert_eq!(resolve(&data, 0x128, "y"), Some(-8));
+        assert_eq!(resolve(&data, 0x128, "z"), None);
+
+        // the second block declares `z`
+        assert_eq!(resolve(&data, 0x140, "x"), Some(-4));
+        assert_eq!(resolve(&data, 0x140, "z"), Some(-16));
+
+        // addresses outside of the procedure resolve nothing
+        assert_eq!(resolve(&data, 0x200, "x"), None);
+    }
+}
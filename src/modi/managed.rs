@@ -0,0 +1,274 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::modi::{ILLineInfo, LineInfo};
+use crate::omap::AddressMap;
+
+/// A managed procedure declared by a module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ManagedProcedure {
+    /// The index of the [`ManagedProcedureSymbol`](crate::ManagedProcedureSymbol).
+    pub index: SymbolIndex,
+    /// The metadata token of the method.
+    pub token: u32,
+    /// Code offset of the start of the native code.
+    pub offset: PdbInternalSectionOffset,
+    /// The length of the native code.
+    pub len: u32,
+}
+
+impl ManagedProcedure {
+    fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        offset.section == self.offset.section
+            && offset.offset >= self.offset.offset
+            && offset.offset - self.offset.offset < self.len
+    }
+}
+
+/// A native code offset mapped to the IL instruction it was compiled from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ILNativeMapping {
+    /// Start of the native code generated for the IL instruction.
+    pub offset: PdbInternalSectionOffset,
+    /// Offset of the IL instruction relative to the start of the method's IL code.
+    pub il_offset: u32,
+}
+
+/// The IL location of a native code offset.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ILLocation {
+    /// The index of the [`ManagedProcedureSymbol`](crate::ManagedProcedureSymbol) containing the
+    /// code.
+    pub procedure: SymbolIndex,
+    /// The metadata token of the method.
+    pub token: u32,
+    /// Offset of the IL instruction relative to the start of the method's IL code.
+    pub il_offset: u32,
+}
+
+/// Maps the native code of managed procedures back to offsets in their IL code.
+///
+/// Managed modules, such as those of NGEN or ReadyToRun images, describe their native code with
+/// regular line records and their IL code with IL line records. Both refer to the same source
+/// lines, which is how native offsets are correlated with IL offsets: every native line record of
+/// a procedure maps to the first IL instruction of the same method on the same source line.
+///
+/// The PDB does not record this mapping directly, so it is a heuristic join on source lines. It is
+/// exact at the granularity of statements, but cannot distinguish several IL instructions on the
+/// same line, and native code whose line has no IL line record is not mapped at all.
+///
+/// Created by [`ModuleInfo::il_native_map`](crate::ModuleInfo::il_native_map).
+#[derive(Clone, Debug, Default)]
+pub struct ILNativeMap {
+    procedures: Vec<ManagedProcedure>,
+    mappings: Vec<ILNativeMapping>,
+    /// Native line records of procedures without a matching IL line, sorted by offset.
+    unmapped: Vec<PdbInternalSectionOffset>,
+}
+
+impl ILNativeMap {
+    pub(crate) fn new(
+        mut procedures: Vec<ManagedProcedure>,
+        il_lines: &[ILLineInfo],
+        lines: &[LineInfo],
+    ) -> Self {
+        procedures.sort_by_key(|p| (p.offset.section, p.offset.offset));
+
+        // IL lines are keyed by the start of the method's native code.
+        let mut methods = BTreeMap::new();
+        for il_line in il_lines {
+            let key = (il_line.method.section, il_line.method.offset);
+            let entry = methods
+                .entry((key, il_line.file_index, il_line.line_start))
+                .or_insert(il_line.il_offset);
+            *entry = (*entry).min(il_line.il_offset);
+        }
+
+        let mut mappings = Vec::new();
+        let mut unmapped = Vec::new();
+        for line in lines {
+            let procedure = match find_procedure(&procedures, line.offset) {
+                Some(procedure) => procedure,
+                None => continue,
+            };
+
+            let key = (procedure.offset.section, procedure.offset.offset);
+            if let Some(&il_offset) = methods.get(&(key, line.file_index, line.line_start)) {
+                mappings.push(ILNativeMapping {
+                    offset: line.offset,
+                    il_offset,
+                });
+            } else {
+                unmapped.push(line.offset);
+            }
+        }
+
+        mappings.sort_by_key(|m| (m.offset.section, m.offset.offset));
+        mappings.dedup_by_key(|m| m.offset);
+        unmapped.sort_by_key(|offset| (offset.section, offset.offset));
+
+        Self {
+            procedures,
+            mappings,
+            unmapped,
+        }
+    }
+
+    /// Returns the managed procedures of the module, sorted by code offset.
+    pub fn procedures(&self) -> &[ManagedProcedure] {
+        &self.procedures
+    }
+
+    /// Returns the native offsets with their IL offsets, sorted by native offset.
+    pub fn mappings(&self) -> &[ILNativeMapping] {
+        &self.mappings
+    }
+
+    /// Returns whether the map does not contain any mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Returns the managed procedure containing the given native code offset.
+    pub fn procedure(&self, offset: PdbInternalSectionOffset) -> Option<&ManagedProcedure> {
+        find_procedure(&self.procedures, offset)
+    }
+
+    /// Translates a native code offset to the IL instruction it was compiled from.
+    ///
+    /// Returns `None` if the offset is not within a managed procedure, if the procedure has no
+    /// mapping at or before the offset, or if the native line record covering the offset has no
+    /// matching IL line.
+    pub fn lookup(&self, offset: PdbInternalSectionOffset) -> Option<ILLocation> {
+        let procedure = self.procedure(offset)?;
+
+        let index = self.mappings.partition_point(|m| {
+            (m.offset.section, m.offset.offset) <= (offset.section, offset.offset)
+        });
+        let mapping = self.mappings[..index].last()?;
+
+        if !procedure.contains(mapping.offset) {
+            return None;
+        }
+
+        // A later native line without IL line ends the range of the mapping.
+        let key = |offset: &PdbInternalSectionOffset| (offset.section, offset.offset);
+        let unmapped = self.unmapped.partition_point(|o| key(o) <= key(&offset));
+        if self.unmapped[..unmapped]
+            .last()
+            .is_some_and(|o| key(o) > key(&mapping.offset))
+        {
+            return None;
+        }
+
+        Some(ILLocation {
+            procedure: procedure.index,
+            token: procedure.token,
+            il_offset: mapping.il_offset,
+        })
+    }
+
+    /// Translates an [`Rva`] to the IL instruction it was compiled from.
+    pub fn lookup_rva(&self, rva: Rva, address_map: &AddressMap<'_>) -> Option<ILLocation> {
+        self.lookup(rva.to_internal_offset(address_map)?)
+    }
+}
+
+fn find_procedure(
+    procedures: &[ManagedProcedure],
+    offset: PdbInternalSectionOffset,
+) -> Option<&ManagedProcedure> {
+    let index = procedures.partition_point(|p| {
+        (p.offset.section, p.offset.offset) <= (offset.section, offset.offset)
+    });
+    procedures[..index]
+        .last()
+        .filter(|procedure| procedure.contains(offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::modi::LineInfoKind;
+
+    fn il_line(method: u32, il_offset: u32, line: u32) -> ILLineInfo {
+        ILLineInfo {
+            method: PdbInternalSectionOffset::new(1, method),
+            il_offset,
+            file_index: FileIndex(0),
+            line_start: line,
+            line_end: line,
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        }
+    }
+
+    fn line(offset: u32, line: u32) -> LineInfo {
+        LineInfo {
+            offset: PdbInternalSectionOffset::new(1, offset),
+            length: None,
+            file_index: FileIndex(0),
+            line_start: line,
+            line_end: line,
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let procedures = vec![
+            ManagedProcedure {
+                index: SymbolIndex(0x80),
+                token: 0x0600_0002,
+                offset: PdbInternalSectionOffset::new(1, 0x1040),
+                len: 0x20,
+            },
+            ManagedProcedure {
+                index: SymbolIndex(0x40),
+                token: 0x0600_0001,
+                offset: PdbInternalSectionOffset::new(1, 0x1000),
+                len: 0x30,
+            },
+        ];
+
+        let il_lines = [
+            il_line(0x1000, 0x0, 10),
+            il_line(0x1000, 0x7, 11),
+            il_line(0x1000, 0x3, 11),
+            il_line(0x1040, 0x0, 20),
+        ];
+
+        let lines = [
+            line(0x1000, 10),
+            line(0x1008, 11),
+            line(0x1010, 12), // no IL line
+            line(0x1040, 20),
+            line(0x1080, 20), // outside of procedures
+        ];
+
+        let map = ILNativeMap::new(procedures, &il_lines, &lines);
+        assert_eq!(map.procedures()[0].token, 0x0600_0001);
+        assert_eq!(map.mappings().len(), 3);
+
+        let lookup = |offset| map.lookup(PdbInternalSectionOffset::new(1, offset));
+        let location = |procedure, token, il_offset| ILLocation {
+            procedure: SymbolIndex(procedure),
+            token,
+            il_offset,
+        };
+
+        assert_eq!(lookup(0x0fff), None);
+        assert_eq!(lookup(0x1004), Some(location(0x40, 0x0600_0001, 0x0)));
+        assert_eq!(lookup(0x100c), Some(location(0x40, 0x0600_0001, 0x3)));
+        assert_eq!(lookup(0x1010), None);
+        assert_eq!(lookup(0x1020), None);
+        assert_eq!(lookup(0x1030), None);
+        assert_eq!(lookup(0x1041), Some(location(0x80, 0x0600_0002, 0x0)));
+        assert_eq!(lookup(0x1080), None);
+    }
+}
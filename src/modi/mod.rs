@@ -1,18 +1,52 @@
-use std::fmt;
+use alloc::collections::{BTreeMap, BinaryHeap};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+use core::ops::Range;
 
 use crate::common::*;
-use crate::dbi::Module;
+use crate::dbi::DBIModuleInfo;
 use crate::msf::Stream;
-use crate::symbol::SymbolIter;
+use crate::omap::AddressMap;
+use crate::strings::StringTable;
+use crate::symbol::{
+    AnnotationError, CompileFlagsSymbol, EnvBlockSymbol, ObjNameSymbol, Symbol, SymbolCursor,
+    SymbolData, SymbolIter,
+};
+use crate::tpi::TypeFinder;
 use crate::FallibleIterator;
 
+mod architecture;
+mod c11;
 mod c13;
 mod constants;
+mod functions;
+mod inline_sites;
+mod locals;
+mod managed;
+mod prologue;
+mod scopes;
+#[cfg(test)]
+mod test_records;
+mod tokens;
 
+pub use architecture::{ArchitectureMap, ArchitectureRange};
 pub use c13::{
-    CrossModuleExportIter, CrossModuleExports, CrossModuleImports, Inlinee, InlineeIterator,
-    InlineeLineIterator,
+    CoffSymbolRvaIter, CrossModuleExportIter, CrossModuleExports, CrossModuleImports,
+    ILLineIterator, IgnoredSubsectionIter, Inlinee, InlineeFileIterator, InlineeIterator,
+    InlineeLineIterator, ModuleFrameDataIter,
+};
+pub(crate) use functions::find_range;
+pub use functions::{Function, FunctionIter, Functions};
+pub use inline_sites::{InlineSite, InlineSiteIter, InlineSites, ModuleInlineSiteIter};
+pub use locals::{
+    LocalVariable, LocalVariableStorage, Parameter, ProcedureSignature, VariableLocation,
 };
+pub use managed::{ILLocation, ILNativeMap, ILNativeMapping, ManagedProcedure};
+pub use prologue::{LineRegion, ProcedureLine};
+pub use scopes::{Scope, ScopeKind, ScopeTree};
+pub use tokens::{TokenMap, TokenTarget};
 
 #[derive(Clone, Copy, Debug)]
 enum LinesSize {
@@ -23,17 +57,21 @@ enum LinesSize {
 /// This struct contains data about a single module from its module info stream.
 ///
 /// The module info stream is where private symbols and line info is stored.
+///
+/// A `ModuleInfo` does not borrow from the [`PDB`](crate::PDB) it was loaded from. Any number of
+/// module infos can be alive at the same time and cloned cheaply, since clones share the stream
+/// data. With the `sync` feature, they can also be shared between threads.
+#[derive(Clone, Debug)]
 pub struct ModuleInfo<'s> {
     stream: Stream<'s>,
+    stream_index: StreamIndex,
     symbols_size: usize,
     lines_size: LinesSize,
 }
 
 impl<'s> ModuleInfo<'s> {
     /// Parses a `ModuleInfo` from it's Module info stream data.
-    pub(crate) fn parse(stream: Stream<'s>, module: &Module<'_>) -> Self {
-        let info = module.info();
-
+    pub(crate) fn parse(stream: Stream<'s>, info: &DBIModuleInfo) -> Self {
         let lines_size = if info.lines_size > 0 {
             LinesSize::C11(info.lines_size as usize)
         } else {
@@ -43,14 +81,36 @@ impl<'s> ModuleInfo<'s> {
         let symbols_size = info.symbols_size as usize;
         ModuleInfo {
             stream,
+            stream_index: info.stream,
             symbols_size,
             lines_size,
         }
     }
 
+    /// Returns the index of the module info stream.
+    pub(crate) fn stream_index(&self) -> StreamIndex {
+        self.stream_index
+    }
+
+    /// Returns whether the line information of this module uses the C13 format.
+    pub(crate) fn has_c13_lines(&self) -> bool {
+        matches!(self.lines_size, LinesSize::C13(size) if size > 0)
+    }
+
+    /// Returns the size of the line information of this module in bytes.
+    pub(crate) fn lines_len(&self) -> usize {
+        match self.lines_size {
+            LinesSize::C11(size) | LinesSize::C13(size) => size,
+        }
+    }
+
+    fn lines_range(&self, size: usize) -> Range<usize> {
+        let start = self.symbols_size;
+        start..start + size
+    }
+
     fn lines_data(&self, size: usize) -> &[u8] {
-        let start = self.symbols_size as usize;
-        &self.stream[start..start + size]
+        &self.stream[self.lines_range(size)]
     }
 
     /// Get an iterator over the all symbols in this module.
@@ -75,16 +135,291 @@ impl<'s> ModuleInfo<'s> {
         Ok(iter)
     }
 
+    /// Returns an iterator over symbols resuming at the position saved in `cursor`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IndexMismatch` if the cursor was not created for the symbols of this module
+    pub fn symbols_at_cursor(&self, cursor: &SymbolCursor) -> Result<SymbolIter<'_>> {
+        if cursor.module.is_none() || cursor.stream != self.stream_index {
+            return Err(Error::IndexMismatch);
+        }
+
+        self.symbols_at(cursor.symbol)
+    }
+
+    /// Returns the symbol at the given index.
+    ///
+    /// Symbol indexes are byte offsets into the symbol stream of the module, so this parses a single
+    /// record instead of iterating all preceding symbols. Use it to resolve the `parent`, `end` and
+    /// `next` fields of scope symbols, or the target of a
+    /// [`ProcedureReferenceSymbol`](crate::ProcedureReferenceSymbol).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::SymbolNotFound` if the index is past the end of the symbols of this module
+    pub fn symbol_at(&self, index: SymbolIndex) -> Result<Symbol<'_>> {
+        self.symbols_at(index)?
+            .next()?
+            .ok_or(Error::SymbolNotFound(index.0))
+    }
+
+    /// Returns the object file name record of this module.
+    ///
+    /// The record is part of the symbols describing the module, which precede all procedures and
+    /// other scopes. Returns `None` if the module does not declare an object file name.
+    pub fn object_name(&self) -> Result<Option<ObjNameSymbol<'_>>> {
+        self.find_header_symbol(|data| match data {
+            SymbolData::ObjName(data) => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Returns the compile flags record of this module.
+    ///
+    /// The record describes the source language, target and compiler of the module. Returns `None`
+    /// if the module does not declare compile flags.
+    pub fn compile_flags(&self) -> Result<Option<CompileFlagsSymbol<'_>>> {
+        self.find_header_symbol(|data| match data {
+            SymbolData::CompileFlags(data) => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Returns the build environment record of this module.
+    ///
+    /// The linker records the environment of the link, such as its working directory and command
+    /// line, in the `* Linker *` module. Returns `None` if the module does not declare an
+    /// environment.
+    pub fn env_block(&self) -> Result<Option<EnvBlockSymbol<'_>>> {
+        self.find_header_symbol(|data| match data {
+            SymbolData::EnvBlock(data) => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Returns the first symbol matched by `f` among the symbols preceding the first scope.
+    fn find_header_symbol<'a, T, F>(&'a self, mut f: F) -> Result<Option<T>>
+    where
+        F: FnMut(SymbolData<'a>) -> Option<T>,
+    {
+        let mut symbols = self.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            if symbol.starts_scope() {
+                break;
+            }
+
+            if let Some(value) = symbol.parse().ok().and_then(&mut f) {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the namespaces named by using-namespace directives in the scope at `index`.
+    ///
+    /// The scope is usually a procedure, but can be any symbol that
+    /// [starts a scope](crate::Symbol::starts_scope). Only directives declared directly in this
+    /// scope are returned; nested blocks may declare additional directives, which are active in
+    /// addition to the ones of their parent. If the symbol at `index` does not start a scope, the
+    /// result is empty.
+    ///
+    /// This is the information needed to resolve unqualified names the same way the compiler did
+    /// when evaluating expressions inside a function.
+    pub fn using_namespaces(&self, index: SymbolIndex) -> Result<Vec<RawString<'_>>> {
+        scope_using_namespaces(self.symbols_at(index)?)
+    }
+
+    /// Returns the namespaces named by all using-namespace directives in effect at the symbol at
+    /// `index`.
+    ///
+    /// Unlike [`using_namespaces`](Self::using_namespaces), this includes the directives of all
+    /// scopes enclosing the symbol, from the outermost to the innermost scope. If the symbol starts
+    /// a scope, such as the innermost block containing an address, its own directives are included
+    /// as well. Expression evaluators consult these namespaces, in addition to the global
+    /// namespace, when resolving unqualified names. The result is empty if there is no symbol at
+    /// `index`.
+    pub fn active_using_namespaces(&self, index: SymbolIndex) -> Result<Vec<RawString<'_>>> {
+        active_using_namespaces(self.symbols()?, index)
+    }
+
+    /// Returns the local variables and parameters of the procedure at `index`.
+    ///
+    /// This collects `S_LOCAL`, `S_REGREL32` and `S_BPREL32` symbols of the procedure, including
+    /// those declared in nested blocks, in the order they are declared. Variables of functions
+    /// inlined into this procedure are skipped. If the symbol at `index` does not
+    /// [start a scope](crate::Symbol::starts_scope), the result is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<usize> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// # let mut count: usize = 0;
+    ///
+    /// let dbi = pdb.debug_information()?;
+    /// let mut modules = dbi.modules()?;
+    /// while let Some(module) = modules.next()? {
+    ///     let info = match pdb.module_info(&module)? {
+    ///         Some(info) => info,
+    ///         None => continue,
+    ///     };
+    ///
+    ///     let mut symbols = info.symbols()?;
+    ///     while let Some(symbol) = symbols.next()? {
+    ///         if let Ok(pdb::SymbolData::Procedure(procedure)) = symbol.parse() {
+    ///             for local in info.locals_of(symbol.index())? {
+    ///                 println!("{}: {} ({})", procedure.name, local.name, local.type_index);
+    ///                 # count += 1;
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(count)
+    /// # }
+    /// # assert!(test().expect("test") > 0);
+    /// ```
+    pub fn locals_of(&self, index: SymbolIndex) -> Result<Vec<LocalVariable<'_>>> {
+        locals::scope_locals(self.symbols_at(index)?)
+    }
+
+    /// Returns the signature of the procedure at `index`, pairing its parameter variables with the
+    /// argument types of its `LF_PROCEDURE` or `LF_MFUNCTION` type.
+    ///
+    /// Parameters are identified by the `isparam` flag of their `S_LOCAL` symbols. In unoptimized
+    /// code, where parameters are declared by `S_REGREL32` or `S_BPREL32` symbols without flags,
+    /// the leading variables are paired in declaration order. For non-static member functions, the
+    /// implicit `this` parameter is returned separately.
+    ///
+    /// Returns `None` if the symbol at `index` is not a procedure or the procedure has no type.
+    /// `type_finder` must already be populated up to the procedure's type.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound` or `Error::TypeNotIndexed` if the procedure type cannot be found
+    /// * `Error::UnimplementedTypeKind` if the procedure type is not a function type, or its
+    ///   argument list is not an `LF_ARGLIST`
+    /// * Errors parsing the symbol or its types, for instance if the module is corrupt
+    pub fn signature_of(
+        &self,
+        index: SymbolIndex,
+        type_finder: &TypeFinder<'_>,
+    ) -> Result<Option<ProcedureSignature<'_>>> {
+        locals::procedure_signature(self.symbols_at(index)?, type_finder)
+    }
+
+    /// Resolves `name` to the variable it refers to at `address` inside the procedure at `index`.
+    ///
+    /// This considers the procedure's parameters, locals and function-level statics, as well as
+    /// variables of nested blocks that contain `address`. Declarations of inner blocks shadow
+    /// declarations of their parents. Variables of functions inlined into this procedure are not
+    /// considered. Use [`Rva::to_internal_offset`](crate::Rva::to_internal_offset) to obtain the
+    /// address from an RVA.
+    ///
+    /// Returns the declaring symbol, or `None` if the name cannot be resolved or `address` is
+    /// outside of the procedure.
+    pub fn resolve_name(
+        &self,
+        index: SymbolIndex,
+        address: PdbInternalSectionOffset,
+        name: &[u8],
+    ) -> Result<Option<Symbol<'_>>> {
+        locals::resolve_scoped_name(self.symbols_at(index)?, address, name)
+    }
+
+    /// Returns the location of the variable declared by the symbol at `index`.
+    ///
+    /// For `S_LOCAL` symbols, this collects the def-range symbols that follow the declaration into
+    /// a [`VariableLocation::Composite`], grouped like a [`Variable`](crate::Variable) returned by
+    /// [`SymbolIter::variables`](crate::SymbolIter::variables). Returns `None` if the symbol does
+    /// not declare a variable.
+    pub fn variable_location(&self, index: SymbolIndex) -> Result<Option<VariableLocation>> {
+        locals::variable_location(self.symbols_at(index)?)
+    }
+
+    /// Builds a tree of the procedures, blocks, inline sites and other scopes of this module.
+    ///
+    /// Use [`ScopeTree::enclosing_scope`] to find the innermost scope containing a code offset.
+    pub fn scope_tree(&self) -> Result<ScopeTree> {
+        ScopeTree::new(self.symbols()?)
+    }
+
+    /// Parses the line information of this module, returning its range in the stream.
+    fn parse_lines(&self) -> Result<(Range<usize>, LineProgramInner)> {
+        Ok(match self.lines_size {
+            LinesSize::C11(size) => (
+                self.lines_range(size),
+                LineProgramInner::C11(c11::LineProgram::parse(self.lines_data(size))?),
+            ),
+            LinesSize::C13(size) => (
+                self.lines_range(size),
+                LineProgramInner::C13(c13::LineProgram::parse(self.lines_data(size))?),
+            ),
+        })
+    }
+
     /// Returns a line program that gives access to file and line information in this module.
     pub fn line_program(&self) -> Result<LineProgram<'_>> {
-        let inner = match self.lines_size {
-            LinesSize::C11(_size) => return Err(Error::UnimplementedFeature("C11 line programs")),
-            LinesSize::C13(size) => {
-                LineProgramInner::C13(c13::LineProgram::parse(self.lines_data(size))?)
-            }
-        };
+        let (lines, inner) = self.parse_lines()?;
+        Ok(LineProgram {
+            data: &self.stream[lines],
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Returns a line program that shares the stream data of this module.
+    ///
+    /// Unlike [`line_program`](Self::line_program), the result does not borrow this `ModuleInfo`,
+    /// so it can be parsed once and stored in a cache. Clones are cheap and do not parse the line
+    /// information again.
+    pub fn shared_line_program(&self) -> Result<SharedLineProgram<'s>> {
+        let (lines, inner) = self.parse_lines()?;
+        Ok(SharedLineProgram {
+            stream: self.stream.clone(),
+            lines,
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Returns the source files of this module that contributed code, each with the address
+    /// ranges of its code.
+    ///
+    /// The ranges are derived from the records of the [`line_program`](Self::line_program). They
+    /// are sorted by section and offset, and adjacent or overlapping ranges are merged. Files
+    /// without line records, such as headers that only declare types, are omitted. Code inlined
+    /// from other files is attributed to the file of the enclosing line record.
+    pub fn files_with_lines(&self) -> Result<Vec<FileLines<'_>>> {
+        let program = self.line_program()?;
+
+        let mut ranges = BTreeMap::new();
+        let mut lines = program.lines();
+        while let Some(line) = lines.next()? {
+            let range = CodeRange {
+                offset: line.offset,
+                length: line.length.unwrap_or(0),
+            };
 
-        Ok(LineProgram { inner })
+            ranges
+                .entry(line.file_index)
+                .or_insert_with(Vec::new)
+                .push(range);
+        }
+
+        let mut files = Vec::with_capacity(ranges.len());
+        for (index, mut ranges) in ranges {
+            merge_code_ranges(&mut ranges);
+            files.push(FileLines {
+                index,
+                file: program.get_file_info(index)?,
+                ranges,
+            });
+        }
+
+        Ok(files)
     }
 
     /// Returns an iterator over all inlinees in this module.
@@ -99,6 +434,182 @@ impl<'s> ModuleInfo<'s> {
         })
     }
 
+    /// Returns an iterator over all inline sites in this module, paired with the procedure they
+    /// were inlined into and their inlinee record.
+    ///
+    /// To enumerate the inline sites of all modules in a PDB, use
+    /// [`PDB::inline_sites`](crate::PDB::inline_sites).
+    pub fn inline_sites(&self) -> Result<ModuleInlineSiteIter<'_>> {
+        ModuleInlineSiteIter::new(self.symbols()?, self.inlinees()?)
+    }
+
+    /// Validates the binary annotations of all inline sites in this module.
+    ///
+    /// Returns the inline sites whose annotations are defective, in symbol order. Iterating the
+    /// line records of these inline sites fails or yields incomplete records.
+    pub fn invalid_annotations(&self) -> Result<Vec<InvalidAnnotations>> {
+        let mut invalid = Vec::new();
+
+        let mut symbols = self.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            if let SymbolData::InlineSite(site) = symbol.parse()? {
+                if let Some(error) = site.annotations.validate() {
+                    invalid.push(InvalidAnnotations {
+                        index: symbol.index(),
+                        inlinee: site.inlinee,
+                        error,
+                    });
+                }
+            }
+        }
+
+        Ok(invalid)
+    }
+
+    /// Returns the section offsets of all code generated for the given source line.
+    ///
+    /// In addition to the line records of the [`line_program`](Self::line_program), this includes
+    /// the code of every inline site expanding an inlinee at this line. The file index refers to
+    /// the file records of this module's line program. The offsets are sorted and free of
+    /// duplicates.
+    pub fn find_locations(
+        &self,
+        file: FileIndex,
+        line: u32,
+    ) -> Result<Vec<PdbInternalSectionOffset>> {
+        let mut offsets = self.line_program()?.find_locations(file, line)?;
+
+        let mut sites = self.inline_sites()?;
+        while let Some(site) = sites.next()? {
+            let mut lines = match site.lines() {
+                Some(lines) => lines,
+                None => continue,
+            };
+
+            while let Some(info) = lines.next()? {
+                if info.covers(file, line) {
+                    offsets.push(info.offset);
+                }
+            }
+        }
+
+        sort_locations(&mut offsets);
+        Ok(offsets)
+    }
+
+    /// Returns an iterator over the IL line records of this module.
+    ///
+    /// Managed modules map offsets in the IL code of their methods to source lines, rather than
+    /// native code offsets. Native modules do not contain IL line records. The file indexes refer
+    /// to the files of the [`line_program`](Self::line_program).
+    pub fn il_lines(&self) -> Result<ILLineIterator<'_>> {
+        Ok(match self.lines_size {
+            // C11 does not contain IL lines.
+            LinesSize::C11(_size) => Default::default(),
+            LinesSize::C13(size) => ILLineIterator::parse(self.lines_data(size)),
+        })
+    }
+
+    /// Returns a map from the native code of this module's managed procedures to their IL offsets.
+    ///
+    /// The map correlates the native line records of each `S_GMANPROC` or `S_LMANPROC` procedure
+    /// with the [`il_lines`](Self::il_lines) of the same method. For native modules, the map is
+    /// empty.
+    pub fn il_native_map(&self) -> Result<ILNativeMap> {
+        let mut procedures = Vec::new();
+        let mut symbols = self.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            if let Ok(SymbolData::ManagedProcedure(procedure)) = symbol.parse() {
+                procedures.push(ManagedProcedure {
+                    index: symbol.index(),
+                    token: procedure.token,
+                    offset: procedure.offset,
+                    len: procedure.len,
+                });
+            }
+        }
+
+        if procedures.is_empty() {
+            return Ok(ILNativeMap::default());
+        }
+
+        let mut il_lines = Vec::new();
+        let mut iter = self.il_lines()?;
+        while let Some(il_line) = iter.next()? {
+            il_lines.push(il_line);
+        }
+
+        let mut lines = Vec::new();
+        let line_program = self.line_program()?;
+        let mut iter = line_program.lines();
+        while let Some(line) = iter.next()? {
+            lines.push(line);
+        }
+
+        Ok(ILNativeMap::new(procedures, &il_lines, &lines))
+    }
+
+    /// Returns an iterator over the frame data declared by this module.
+    ///
+    /// Module frame data uses the same format as the global
+    /// [`FrameTable`](crate::FrameTable), but is not guaranteed to be sorted or to be copied into
+    /// the global table by the linker. Frame data is only emitted for 32-bit x86 code.
+    pub fn frame_data(&self) -> Result<ModuleFrameDataIter<'_>> {
+        Ok(match self.lines_size {
+            // C11 does not contain frame data.
+            LinesSize::C11(_size) => Default::default(),
+            LinesSize::C13(size) => ModuleFrameDataIter::parse(self.lines_data(size)),
+        })
+    }
+
+    /// Returns an iterator over the RVAs of the COFF symbols listed by this module.
+    ///
+    /// The entries are stored in `DEBUG_S_COFF_SYMBOL_RVA` subsections of the line data. Each RVA
+    /// is paired with its ordinal among these entries.
+    pub fn coff_symbol_rvas(&self) -> Result<CoffSymbolRvaIter<'_>> {
+        Ok(match self.lines_size {
+            // C11 does not contain COFF symbol RVAs.
+            LinesSize::C11(_size) => Default::default(),
+            LinesSize::C13(size) => CoffSymbolRvaIter::parse(self.lines_data(size)),
+        })
+    }
+
+    /// Returns an iterator over the subsections of the line data marked with `DEBUG_S_IGNORE`.
+    ///
+    /// Other accessors skip these subsections. Linkers set the ignore bit to disable subsections
+    /// in place, but some vendor toolchains also use it to store custom data that debuggers should
+    /// not interpret.
+    pub fn ignored_subsections(&self) -> Result<IgnoredSubsectionIter<'_>> {
+        Ok(match self.lines_size {
+            // C11 does not contain subsections.
+            LinesSize::C11(_size) => Default::default(),
+            LinesSize::C13(size) => IgnoredSubsectionIter::parse(self.lines_data(size)),
+        })
+    }
+
+    /// Returns a map between the .NET metadata tokens of this module and its symbols and types.
+    ///
+    /// Only managed modules, such as those compiled by C++/CLI, declare metadata tokens. For native
+    /// modules, the map is empty.
+    pub fn token_map(&self) -> Result<TokenMap> {
+        let mut map = TokenMap::default();
+
+        let mut symbols = self.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            if let Ok(SymbolData::ManagedProcedure(procedure)) = symbol.parse() {
+                map.insert_symbol(procedure.token, symbol.index());
+            }
+        }
+
+        match self.lines_size {
+            // C11 does not contain token maps.
+            LinesSize::C11(_size) => (),
+            LinesSize::C13(size) => c13::read_token_maps(self.lines_data(size), &mut map)?,
+        }
+
+        Ok(map)
+    }
+
     /// Returns a table of exports declared by this module.
     pub fn exports(&self) -> Result<CrossModuleExports> {
         Ok(match self.lines_size {
@@ -118,6 +629,138 @@ impl<'s> ModuleInfo<'s> {
     }
 }
 
+/// Sorts code ranges by section and offset and merges adjacent or overlapping ranges.
+fn merge_code_ranges(ranges: &mut Vec<CodeRange>) {
+    ranges.sort_by_key(|range| (range.offset.section, range.offset.offset));
+
+    let mut merged: Vec<CodeRange> = Vec::with_capacity(ranges.len());
+    for range in ranges.drain(..) {
+        if let Some(last) = merged.last_mut() {
+            if last.offset.section == range.offset.section
+                && last.end_offset() >= range.offset.offset
+            {
+                let end = last.end_offset().max(range.end_offset());
+                last.length = end - last.offset.offset;
+                continue;
+            }
+        }
+
+        merged.push(range);
+    }
+
+    *ranges = merged;
+}
+
+/// Sorts section offsets by section and offset and removes duplicates.
+fn sort_locations(offsets: &mut Vec<PdbInternalSectionOffset>) {
+    offsets.sort_by_key(|offset| (offset.section, offset.offset));
+    offsets.dedup();
+}
+
+/// Collects the using-namespace directives declared directly in the scope started by the next
+/// symbol of `symbols`.
+fn scope_using_namespaces(mut symbols: SymbolIter<'_>) -> Result<Vec<RawString<'_>>> {
+    let mut namespaces = Vec::new();
+
+    match symbols.next()? {
+        Some(scope) if scope.starts_scope() => (),
+        _ => return Ok(namespaces),
+    }
+
+    direct_using_namespaces(&mut symbols, &mut namespaces)?;
+    Ok(namespaces)
+}
+
+/// Collects the using-namespace directives active at the symbol at `index`.
+///
+/// Directives of all enclosing scopes, including the module level, are returned from the outermost
+/// to the innermost scope. If the symbol starts a scope, the directives declared directly in that
+/// scope are included. Returns an empty list if there is no symbol at `index`.
+fn active_using_namespaces(
+    mut symbols: SymbolIter<'_>,
+    index: SymbolIndex,
+) -> Result<Vec<RawString<'_>>> {
+    let mut namespaces = Vec::new();
+    // The starts of all scopes enclosing the symbol, from the outermost to the innermost scope.
+    let mut scopes = Vec::new();
+    let mut found = false;
+
+    while let Some(symbol) = symbols.next()? {
+        if symbol.index() > index {
+            break;
+        }
+
+        if symbol.index() == index {
+            if symbol.starts_scope() {
+                scopes.push(index);
+            }
+            found = true;
+            break;
+        }
+
+        if symbol.starts_scope() {
+            scopes.push(symbol.index());
+        } else if symbol.ends_scope() {
+            scopes.pop();
+        } else if scopes.is_empty() {
+            push_using_namespace(&symbol, &mut namespaces)?;
+        }
+    }
+
+    if !found {
+        return Ok(Vec::new());
+    }
+
+    for scope in scopes {
+        symbols.seek(scope);
+        symbols.next()?;
+        direct_using_namespaces(&mut symbols, &mut namespaces)?;
+    }
+
+    Ok(namespaces)
+}
+
+/// Collects the using-namespace directives declared directly in a scope, whose start has just been
+/// read from `symbols`.
+fn direct_using_namespaces<'t>(
+    symbols: &mut SymbolIter<'t>,
+    namespaces: &mut Vec<RawString<'t>>,
+) -> Result<()> {
+    let mut depth = 1;
+    while let Some(symbol) = symbols.next()? {
+        if symbol.starts_scope() {
+            depth += 1;
+        } else if symbol.ends_scope() {
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+        } else if depth == 1 {
+            push_using_namespace(&symbol, namespaces)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the namespace named by `symbol` to `namespaces` if it is a using-namespace directive.
+///
+/// Other symbols are skipped without being parsed.
+fn push_using_namespace<'t>(
+    symbol: &Symbol<'t>,
+    namespaces: &mut Vec<RawString<'t>>,
+) -> Result<()> {
+    if !symbol.is_using_namespace() {
+        return Ok(());
+    }
+
+    if let SymbolData::UsingNamespace(data) = symbol.parse()? {
+        namespaces.push(data.name);
+    }
+
+    Ok(())
+}
+
 /// Checksum of a source file's contents.
 #[derive(Clone, Debug)]
 #[allow(missing_docs)]
@@ -143,28 +786,109 @@ impl PartialEq for FileChecksum<'_> {
 /// Information record on a source file.
 #[derive(Clone, Debug, PartialEq)]
 pub struct FileInfo<'a> {
-    /// Reference to the file name in the [`StringTable`](crate::StringTable).
+    /// Reference to the file name in the [`StringTable`].
+    ///
+    /// Legacy C11 line programs store file names inline, in which case this is `StringRef(0)`. Use
+    /// [`resolve_name`](Self::resolve_name) to obtain the name in either case.
     pub name: StringRef,
 
     /// Checksum of the file contents.
     pub checksum: FileChecksum<'a>,
+
+    /// The file name stored inline in C11 line programs.
+    pub(crate) legacy_name: Option<RawString<'a>>,
 }
 
-/// The kind of source construct a line info is referring to.
+impl<'a> FileInfo<'a> {
+    /// Resolves the name of this file.
+    ///
+    /// The name is looked up in the given string table, unless it was stored inline by a legacy
+    /// C11 line program.
+    pub fn resolve_name<'s>(&self, strings: &'s StringTable<'_>) -> Result<RawString<'s>>
+    where
+        'a: 's,
+    {
+        match self.legacy_name {
+            Some(name) => Ok(name),
+            None => self.name.to_raw_string(strings),
+        }
+    }
+
+    /// Resolves the name of this file, if possible without a string table.
+    ///
+    /// Without a string table, only the names stored inline by legacy C11 line programs resolve,
+    /// and `None` is returned for all other files. See [`resolve_name`](Self::resolve_name).
+    pub fn name<'s>(&self, strings: Option<&'s StringTable<'_>>) -> Result<Option<RawString<'s>>>
+    where
+        'a: 's,
+    {
+        match (strings, self.legacy_name) {
+            (Some(strings), _) => self.resolve_name(strings).map(Some),
+            (None, name) => Ok(name),
+        }
+    }
+}
+
+/// An inline site with defective binary annotations.
+///
+/// Returned by [`ModuleInfo::invalid_annotations`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidAnnotations {
+    /// Index of the inline site symbol.
+    pub index: SymbolIndex,
+    /// The inlined function.
+    pub inlinee: IdIndex,
+    /// The first defect in the annotations.
+    pub error: AnnotationError,
+}
+
+/// A range of code in a section of the executable.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CodeRange {
+    /// The start of the range.
+    pub offset: PdbInternalSectionOffset,
+    /// The length of the range in bytes.
+    ///
+    /// This is zero if the length of a line record is unknown.
+    pub length: u32,
+}
+
+impl CodeRange {
+    pub(crate) fn end_offset(&self) -> u32 {
+        self.offset.offset.saturating_add(self.length)
+    }
+
+    /// Returns whether the given offset lies within this range.
+    pub fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        offset.section == self.offset.section
+            && offset.offset >= self.offset.offset
+            && offset.offset < self.end_offset()
+    }
+}
+
+/// A source file that contributed code to a module.
+///
+/// Returned by [`ModuleInfo::files_with_lines`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileLines<'a> {
+    /// Index of the file in the line program of the module.
+    pub index: FileIndex,
+    /// Information on the file.
+    pub file: FileInfo<'a>,
+    /// The code generated from this file, sorted by section and offset.
+    pub ranges: Vec<CodeRange>,
+}
+
+/// The kind of source construct a line info is referring to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum LineInfoKind {
     /// A source code expression.
     Expression,
     /// A source code statement.
+    #[default]
     Statement,
 }
 
-impl Default for LineInfoKind {
-    fn default() -> Self {
-        Self::Statement
-    }
-}
-
 /// Mapping of a source code offset to a source file location.
 ///
 /// A line entry is always valid up to the subsequent entry.
@@ -173,6 +897,10 @@ pub struct LineInfo {
     /// Source code offset.
     pub offset: PdbInternalSectionOffset,
     /// The optional length of the code.
+    ///
+    /// Line programs do not store lengths explicitly. [`LineProgram::lines`] infers them from the
+    /// offset of the following line record, and uses the code size of the enclosing lines
+    /// subsection for the last record.
     pub length: Option<u32>,
     /// Index of the source file in this module.
     pub file_index: FileIndex,
@@ -182,19 +910,74 @@ pub struct LineInfo {
     pub line_end: u32,
     /// Column number of the start of the covered range.
     ///
-    /// This value is only present if column information is provided by the PDB. Even then, it is
-    /// often zero.
+    /// This value is only present if column information is provided by the PDB, which is indicated
+    /// by the `CV_LINES_HAVE_COLUMNS` flag on a lines subsection. In that case, every line record
+    /// of the subsection has a column range. Even then, it is often zero.
     pub column_start: Option<u32>,
     /// Column number of the end of the covered range.
     ///
-    /// This value is only present if column information is provided by the PDB. Even then, it is
-    /// often zero.
+    /// This value is present exactly if [`column_start`](Self::column_start) is. An end column of
+    /// zero means that the end of the range is unknown.
+    pub column_end: Option<u32>,
+    /// Kind of this line information.
+    pub kind: LineInfoKind,
+}
+
+/// The RVA of a COFF symbol of a module.
+///
+/// Returned by [`ModuleInfo::coff_symbol_rvas`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoffSymbolRva {
+    /// The position of this entry among the COFF symbol RVAs of the module, starting at zero.
+    ///
+    /// This is not the index of the symbol in the COFF symbol table of the object file, which also
+    /// counts auxiliary records. The PDB does not record which symbols the entries belong to.
+    pub ordinal: u32,
+    /// The relative virtual address of the symbol in the linked image.
+    pub rva: PdbInternalRva,
+}
+
+/// A subsection of the line data of a module that is marked with `DEBUG_S_IGNORE`.
+///
+/// Returned by [`ModuleInfo::ignored_subsections`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IgnoredSubsection<'a> {
+    /// The kind of the subsection, without the `DEBUG_S_IGNORE` bit.
+    ///
+    /// Subsections that linkers disabled for padding have a kind of zero.
+    pub kind: u32,
+    /// The raw contents of the subsection.
+    pub data: &'a [u8],
+}
+
+/// Mapping of an offset in the IL code of a managed method to a source file location.
+///
+/// Returned by [`ModuleInfo::il_lines`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ILLineInfo {
+    /// Section offset of the method containing the IL code.
+    pub method: PdbInternalSectionOffset,
+    /// Offset of the IL instruction relative to the start of the method's IL code.
+    pub il_offset: u32,
+    /// Index of the source file in this module.
+    pub file_index: FileIndex,
+    /// Line number of the start of the covered range.
+    pub line_start: u32,
+    /// Line number of the end of the covered range.
+    pub line_end: u32,
+    /// Column number of the start of the covered range, if column information is present.
+    pub column_start: Option<u32>,
+    /// Column number of the end of the covered range, if column information is present.
     pub column_end: Option<u32>,
     /// Kind of this line information.
     pub kind: LineInfoKind,
 }
 
 impl LineInfo {
+    fn covers(&self, file: FileIndex, line: u32) -> bool {
+        self.file_index == file && self.line_start <= line && line <= self.line_end
+    }
+
     pub(crate) fn set_end(&mut self, end_offset: PdbInternalSectionOffset) {
         // This uses PartialOrd which only compares if the section is equal
         debug_assert!(self.offset <= end_offset);
@@ -208,34 +991,99 @@ impl LineInfo {
     }
 }
 
-enum LineProgramInner<'a> {
-    C13(c13::LineProgram<'a>),
+#[derive(Debug)]
+enum LineProgramInner {
+    C11(c11::LineProgram),
+    C13(c13::LineProgram),
+}
+
+/// A line program that shares the stream data of its module.
+///
+/// Returned by [`ModuleInfo::shared_line_program`]. It does not borrow the `ModuleInfo`, so it can
+/// be stored in a cache. Clones are cheap and do not parse the line information again. Use
+/// [`program`](Self::program) to access the line information.
+#[derive(Clone, Debug)]
+pub struct SharedLineProgram<'s> {
+    /// The module info stream.
+    stream: Stream<'s>,
+    /// The range of the line information in the stream.
+    lines: Range<usize>,
+    inner: Arc<LineProgramInner>,
+}
+
+impl SharedLineProgram<'_> {
+    /// Returns the line program borrowing from the shared stream data.
+    pub fn program(&self) -> LineProgram<'_> {
+        LineProgram {
+            data: &self.stream[self.lines.clone()],
+            inner: self.inner.clone(),
+        }
+    }
 }
 
 /// The `LineProgram` provides access to source line information for a module and its procedures.
+///
+/// Line programs are obtained via [`ModuleInfo::line_program`]. Clones are cheap and do not parse
+/// the line information again. To store line programs independently of the `ModuleInfo`, use
+/// [`ModuleInfo::shared_line_program`].
+#[derive(Clone, Debug)]
 pub struct LineProgram<'a> {
-    inner: LineProgramInner<'a>,
+    /// The line information of the module.
+    data: &'a [u8],
+    inner: Arc<LineProgramInner>,
 }
 
 impl<'a> LineProgram<'a> {
+    fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
     /// Returns an iterator over all line information records of this module.
     ///
     /// Note that line records are not guaranteed to be ordered by source code offset. If a
-    /// monotonic order by `PdbInternalSectionOffset` or `Rva` is required, the lines have to be
-    /// sorted manually.
+    /// monotonic order by `Rva` is required, use [`lines_sorted`](Self::lines_sorted).
     pub fn lines(&self) -> LineIterator<'_> {
-        match self.inner {
+        match *self.inner {
+            LineProgramInner::C11(ref inner) => LineIterator {
+                inner: LineIteratorInner::C11(inner.lines(self.data())),
+            },
             LineProgramInner::C13(ref inner) => LineIterator {
-                inner: LineIteratorInner::C13(inner.lines()),
+                inner: LineIteratorInner::C13(inner.lines(self.data())),
             },
         }
     }
 
+    /// Returns an iterator over all line information records of this module, ordered by [`Rva`].
+    ///
+    /// Line records whose offset cannot be translated to an `Rva`, for instance because the code was
+    /// eliminated during optimization, are omitted. Records with the same `Rva` retain the order of
+    /// [`lines`](Self::lines).
+    ///
+    /// The records of each block are usually ordered already. They are merged as the iterator
+    /// advances, which is cheaper than sorting all records up front.
+    pub fn lines_sorted(&self, address_map: &AddressMap<'_>) -> Result<LineIterator<'_>> {
+        let mut merged = MergedLines::default();
+        let mut iter = self.lines();
+        while let Some(line) = iter.next()? {
+            if let Some(rva) = line.offset.to_rva(address_map) {
+                merged.push(rva, line);
+            }
+        }
+        merged.end_run();
+
+        Ok(LineIterator {
+            inner: LineIteratorInner::Sorted(merged),
+        })
+    }
+
     /// Returns an iterator over all file records of this module.
     pub fn files(&self) -> FileIterator<'a> {
-        match self.inner {
+        match *self.inner {
+            LineProgramInner::C11(ref inner) => FileIterator {
+                inner: FileIteratorInner::C11(inner.files(self.data())),
+            },
             LineProgramInner::C13(ref inner) => FileIterator {
-                inner: FileIteratorInner::C13(inner.files()),
+                inner: FileIteratorInner::C13(inner.files(self.data())),
             },
         }
     }
@@ -247,28 +1095,155 @@ impl<'a> LineProgram<'a> {
     /// code generated for this function. `lines_for_symbol` returns all line records covering this
     /// function, potentially exceeding this range.
     ///
+    /// Legacy C11 line programs do not record the extent of functions, so for them, only line
+    /// records at or after the offset are returned, which may include the lines of functions
+    /// following this symbol. To restrict the records to the code of a procedure, use
+    /// [`procedure_lines`](Self::procedure_lines).
+    ///
     /// Note that line records are not guaranteed to be ordered by source code offset. If a
     /// monotonic order by `PdbInternalSectionOffset` or `Rva` is required, the lines have to be
     /// sorted manually.
     pub fn lines_for_symbol(&self, offset: PdbInternalSectionOffset) -> LineIterator<'_> {
-        match self.inner {
+        self.lines_in_range(offset, None)
+    }
+
+    /// Returns an iterator over line records for the code starting at `offset` with length `len`.
+    ///
+    /// For C13 line programs, this returns the same records as
+    /// [`lines_for_symbol`](Self::lines_for_symbol). For C11 line programs, the records are clamped
+    /// to the given range.
+    pub(crate) fn lines_in_range(
+        &self,
+        offset: PdbInternalSectionOffset,
+        len: Option<u32>,
+    ) -> LineIterator<'_> {
+        match *self.inner {
+            LineProgramInner::C11(ref inner) => LineIterator {
+                inner: LineIteratorInner::C11(inner.lines_for_symbol(self.data(), offset, len)),
+            },
             LineProgramInner::C13(ref inner) => LineIterator {
-                inner: LineIteratorInner::C13(inner.lines_for_symbol(offset)),
+                inner: LineIteratorInner::C13(inner.lines_for_symbol(self.data(), offset)),
             },
         }
     }
 
+    /// Returns the line record covering the given section offset.
+    ///
+    /// Unlike scanning [`lines`](Self::lines), this only visits the line records near the offset.
+    /// If multiple records cover the offset, the one starting closest to it is returned. Returns
+    /// `None` if there is no line information for this offset.
+    pub fn lookup(&self, offset: PdbInternalSectionOffset) -> Result<Option<LineInfo>> {
+        let mut best: Option<LineInfo> = None;
+        let mut lines = match *self.inner {
+            // The record covering the offset may start before it, at any offset in the section.
+            LineProgramInner::C11(_) => {
+                self.lines_in_range(PdbInternalSectionOffset::new(offset.section, 0), None)
+            }
+            LineProgramInner::C13(_) => self.lines_for_symbol(offset),
+        };
+
+        while let Some(line) = lines.next()? {
+            if line.offset.section != offset.section || line.offset.offset > offset.offset {
+                continue;
+            }
+
+            let distance = offset.offset - line.offset.offset;
+            if line.length.is_some_and(|length| distance >= length) {
+                continue;
+            }
+
+            if best
+                .as_ref()
+                .map_or(true, |best| best.offset.offset < line.offset.offset)
+            {
+                best = Some(line);
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Returns the section offsets of all line records covering the given source line.
+    ///
+    /// This only considers the line records of this line program, which describe code that was not
+    /// inlined. To also find the code of inlined functions, use
+    /// [`ModuleInfo::find_locations`]. The offsets are sorted and free of duplicates.
+    pub fn find_locations(
+        &self,
+        file: FileIndex,
+        line: u32,
+    ) -> Result<Vec<PdbInternalSectionOffset>> {
+        let mut offsets = Vec::new();
+        let mut lines = self.lines();
+
+        while let Some(info) = lines.next()? {
+            if info.covers(file, line) {
+                offsets.push(info.offset);
+            }
+        }
+
+        sort_locations(&mut offsets);
+        Ok(offsets)
+    }
+
     /// Looks up file information for the specified file.
     pub fn get_file_info(&self, offset: FileIndex) -> Result<FileInfo<'a>> {
-        match self.inner {
-            LineProgramInner::C13(ref inner) => inner.get_file_info(offset),
+        match *self.inner {
+            LineProgramInner::C11(ref inner) => inner.get_file_info(self.data(), offset),
+            LineProgramInner::C13(ref inner) => inner.get_file_info(self.data(), offset),
         }
     }
 }
 
 #[derive(Clone, Debug)]
 enum LineIteratorInner<'a> {
+    C11(c11::LineIterator<'a>),
     C13(c13::LineIterator<'a>),
+    Sorted(MergedLines),
+}
+
+/// Merges ascending runs of line records by `Rva`.
+#[derive(Clone, Debug, Default)]
+struct MergedLines {
+    /// All line records in their original order, taken as they are returned.
+    lines: Vec<(Rva, Option<LineInfo>)>,
+    /// The next record of each run that has not been exhausted, as `(rva, position, run_end)`.
+    ///
+    /// Comparing positions makes the merge stable, as runs are stored in their original order.
+    heap: BinaryHeap<Reverse<(Rva, usize, usize)>>,
+    /// The position of the first record of the current run.
+    run_start: usize,
+}
+
+impl MergedLines {
+    /// Appends a record, starting a new run unless it is ordered after the previous record.
+    fn push(&mut self, rva: Rva, line: LineInfo) {
+        // Lines within a block are usually ordered, so they form runs of ascending records.
+        if self.lines.last().is_some_and(|&(last, _)| rva < last) {
+            self.end_run();
+        }
+        self.lines.push((rva, Some(line)));
+    }
+
+    /// Ends the current run, which must be called after the last record has been pushed.
+    fn end_run(&mut self) {
+        let start = self.run_start;
+        if let Some(&(rva, _)) = self.lines.get(start) {
+            self.heap.push(Reverse((rva, start, self.lines.len())));
+        }
+        self.run_start = self.lines.len();
+    }
+
+    /// Returns the next record with the lowest `Rva` among all runs.
+    fn next(&mut self) -> Option<LineInfo> {
+        let Reverse((_, position, end)) = self.heap.pop()?;
+        if position + 1 < end {
+            let rva = self.lines[position + 1].0;
+            self.heap.push(Reverse((rva, position + 1, end)));
+        }
+
+        self.lines[position].1.take()
+    }
 }
 
 /// An iterator over line information records in a module.
@@ -291,13 +1266,16 @@ impl<'a> FallibleIterator for LineIterator<'a> {
 
     fn next(&mut self) -> Result<Option<Self::Item>> {
         match self.inner {
+            LineIteratorInner::C11(ref mut inner) => inner.next(),
             LineIteratorInner::C13(ref mut inner) => inner.next(),
+            LineIteratorInner::Sorted(ref mut inner) => Ok(inner.next()),
         }
     }
 }
 
 #[derive(Clone, Debug)]
 enum FileIteratorInner<'a> {
+    C11(c11::FileIterator<'a>),
     C13(c13::FileIterator<'a>),
 }
 
@@ -321,15 +1299,16 @@ impl<'a> FallibleIterator for FileIterator<'a> {
 
     fn next(&mut self) -> Result<Option<Self::Item>> {
         match self.inner {
+            FileIteratorInner::C11(ref mut inner) => inner.next(),
             FileIteratorInner::C13(ref mut inner) => inner.next(),
         }
     }
 }
 
-/// Named reference to a [`Module`].
+/// Named reference to a [`Module`](crate::Module).
 ///
-/// The name stored in the [`StringTable`](crate::StringTable) corresponds to the name of the module
-/// as returned by [`Module::module_name`].
+/// The name stored in the [`StringTable`] corresponds to the name of the module
+/// as returned by [`Module::module_name`](crate::Module::module_name).
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ModuleRef(pub StringRef);
 
@@ -356,3 +1335,136 @@ pub enum CrossModuleExport {
     /// A cross module export of an [`Id`](crate::Id).
     Id(Local<IdIndex>, IdIndex),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_using_namespaces() {
+        let data = &[
+            0x06, 0x00, 0x03, 0x11, // S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x06, 0x00, 0x24, 0x11, b's', b't', b'd', 0x00, // S_UNAMESPACE std
+            0x06, 0x00, 0x11, 0x11, // S_REGREL32
+            0x00, 0x00, 0x00, 0x00, // truncated, skipped without parsing
+            0x06, 0x00, 0x03, 0x11, // nested S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x06, 0x00, 0x24, 0x11, b'f', b'o', b'o', 0x00, // nested S_UNAMESPACE foo
+            0x02, 0x00, 0x06, 0x00, // S_END
+            0x06, 0x00, 0x24, 0x11, b'b', b'a', b'r', 0x00, // S_UNAMESPACE bar
+            0x02, 0x00, 0x06, 0x00, // S_END
+            0x06, 0x00, 0x24, 0x11, b'b', b'a', b'z', 0x00, // S_UNAMESPACE baz, out of scope
+        ];
+
+        let symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+        let namespaces = scope_using_namespaces(symbols).expect("namespaces");
+        assert_eq!(
+            namespaces,
+            vec![RawString::from("std"), RawString::from("bar")]
+        );
+    }
+
+    #[test]
+    fn test_active_using_namespaces() {
+        let data = &[
+            0x06, 0x00, 0x24, 0x11, b'o', b'u', b't',
+            0x00, // 0x00: module-level S_UNAMESPACE out
+            0x06, 0x00, 0x03, 0x11, // 0x08: S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x06, 0x00, 0x24, 0x11, b's', b't', b'd', 0x00, // 0x10: S_UNAMESPACE std
+            0x06, 0x00, 0x03, 0x11, // 0x18: nested S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x06, 0x00, 0x24, 0x11, b'f', b'o', b'o', 0x00, // 0x20: nested S_UNAMESPACE foo
+            0x02, 0x00, 0x06, 0x00, // 0x28: S_END
+            0x06, 0x00, 0x03, 0x11, // 0x2c: sibling S_BLOCK32
+            0x00, 0x00, 0x00, 0x00, // truncated block data
+            0x02, 0x00, 0x06, 0x00, // 0x34: S_END
+            0x02, 0x00, 0x06, 0x00, // 0x38: S_END
+        ];
+
+        let namespaces = |index| {
+            let symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+            active_using_namespaces(symbols, SymbolIndex(index)).expect("namespaces")
+        };
+
+        assert_eq!(namespaces(0x08), ["out", "std"].map(RawString::from));
+        assert_eq!(namespaces(0x18), ["out", "std", "foo"].map(RawString::from));
+        assert_eq!(namespaces(0x28), ["out", "std", "foo"].map(RawString::from));
+        assert_eq!(namespaces(0x2c), ["out", "std"].map(RawString::from));
+        assert_eq!(namespaces(0x3c), []);
+    }
+
+    #[test]
+    fn test_no_scope() {
+        let data = &[
+            0x06, 0x00, 0x24, 0x11, b's', b't', b'd', 0x00, // S_UNAMESPACE std
+        ];
+
+        let symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+        let namespaces = scope_using_namespaces(symbols).expect("namespaces");
+        assert!(namespaces.is_empty());
+    }
+    #[test]
+    fn test_merge_code_ranges() {
+        fn range(section: u16, offset: u32, length: u32) -> CodeRange {
+            CodeRange {
+                offset: PdbInternalSectionOffset { section, offset },
+                length,
+            }
+        }
+
+        let mut ranges = vec![
+            range(1, 0x20, 0x10),
+            range(2, 0x00, 0x08),
+            range(1, 0x00, 0x10),
+            range(1, 0x10, 0x08),
+            range(1, 0x14, 0x04),
+            range(1, 0x40, 0x00),
+        ];
+
+        merge_code_ranges(&mut ranges);
+        assert_eq!(
+            ranges,
+            vec![
+                range(1, 0x00, 0x18),
+                range(1, 0x20, 0x10),
+                range(1, 0x40, 0x00),
+                range(2, 0x00, 0x08),
+            ]
+        );
+
+        assert!(ranges[0].contains(PdbInternalSectionOffset::new(1, 0x17)));
+        assert!(!ranges[0].contains(PdbInternalSectionOffset::new(1, 0x18)));
+        assert!(!ranges[0].contains(PdbInternalSectionOffset::new(2, 0x00)));
+    }
+
+    #[test]
+    fn test_merged_lines() {
+        let line = |line_start| LineInfo {
+            offset: PdbInternalSectionOffset::default(),
+            length: None,
+            file_index: FileIndex(0),
+            line_start,
+            line_end: line_start,
+            column_start: None,
+            column_end: None,
+            kind: LineInfoKind::Statement,
+        };
+
+        let mut merged = MergedLines::default();
+        for &(rva, line_start) in &[(1, 1), (4, 2), (6, 3), (2, 4), (4, 5), (5, 6), (0, 7)] {
+            merged.push(Rva(rva), line(line_start));
+        }
+        merged.end_run();
+        assert_eq!(merged.heap.len(), 3);
+
+        let mut order = Vec::new();
+        while let Some(line) = merged.next() {
+            order.push(line.line_start);
+        }
+
+        // Records with the same address retain their original order.
+        assert_eq!(order, [7, 1, 4, 2, 5, 6, 3]);
+    }
+}
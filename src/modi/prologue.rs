@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::modi::{LineInfo, LineProgram};
+use crate::symbol::ProcedureSymbol;
+use crate::FallibleIterator;
+
+/// The part of a procedure's code that a [`ProcedureLine`] starts in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineRegion {
+    /// The prologue, which sets up the stack frame before the body.
+    Prologue,
+    /// The body of the procedure.
+    Body,
+    /// The epilogue, which tears down the stack frame and returns.
+    Epilogue,
+}
+
+/// A line record of a procedure, classified by the region of code it starts in.
+///
+/// Returned by [`LineProgram::procedure_lines`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcedureLine {
+    /// The line record.
+    pub line: LineInfo,
+    /// The region of the procedure the line record starts in.
+    pub region: LineRegion,
+}
+
+/// Classifies an offset relative to the start of a procedure.
+///
+/// Procedures without debug start and end markers, such as those written in assembly, consist of a
+/// body only.
+fn region(procedure: &ProcedureSymbol<'_>, offset: u32) -> LineRegion {
+    let start = procedure.dbg_start_offset;
+    let end = procedure.dbg_end_offset;
+
+    if start == 0 && end == 0 {
+        LineRegion::Body
+    } else if offset < start {
+        LineRegion::Prologue
+    } else if offset >= end {
+        LineRegion::Epilogue
+    } else {
+        LineRegion::Body
+    }
+}
+
+impl<'s> LineProgram<'s> {
+    /// Returns the line records within the code of `procedure`, sorted by offset.
+    ///
+    /// Each record is classified by the debug start and end offsets of the procedure, which mark
+    /// the end of the prologue and the start of the epilogue. Unlike
+    /// [`lines_for_symbol`](Self::lines_for_symbol), records outside the code of the procedure are
+    /// omitted.
+    pub fn procedure_lines(&self, procedure: &ProcedureSymbol<'_>) -> Result<Vec<ProcedureLine>> {
+        let start = procedure.offset;
+        let mut lines = Vec::new();
+
+        let mut iter = self.lines_in_range(start, Some(procedure.len));
+        while let Some(line) = iter.next()? {
+            if line.offset.section != start.section || line.offset.offset < start.offset {
+                continue;
+            }
+
+            let offset = line.offset.offset - start.offset;
+            if offset >= procedure.len {
+                continue;
+            }
+
+            lines.push(ProcedureLine {
+                region: region(procedure, offset),
+                line,
+            });
+        }
+
+        lines.sort_by_key(|line| line.line.offset.offset);
+        Ok(lines)
+    }
+
+    /// Returns the location of the first instruction after the prologue of `procedure`.
+    ///
+    /// This is where debuggers place breakpoints on a function, so that parameters are accessible
+    /// once the breakpoint is hit. The returned record is the line record covering the debug start
+    /// offset of the procedure, with its offset and length adjusted to start at that offset.
+    /// Returns `None` if no line record covers it, or if the debug start offset lies beyond the
+    /// addressable range of the section.
+    pub fn prologue_end(&self, procedure: &ProcedureSymbol<'_>) -> Result<Option<LineInfo>> {
+        let offset = match procedure
+            .offset
+            .offset
+            .checked_add(procedure.dbg_start_offset)
+        {
+            Some(offset) => PdbInternalSectionOffset {
+                section: procedure.offset.section,
+                offset,
+            },
+            None => return Ok(None),
+        };
+
+        let mut line = match self.lookup(offset)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+
+        let skipped = offset.offset - line.offset.offset;
+        line.offset = offset;
+        line.length = line.length.map(|length| length - skipped);
+        Ok(Some(line))
+    }
+}
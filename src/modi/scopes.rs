@@ -0,0 +1,264 @@
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::modi::c13::{InlineeLineIterator, InlineeSourceLine};
+use crate::modi::{merge_code_ranges, CodeRange};
+use crate::symbol::{Symbol, SymbolData, SymbolIter};
+use crate::FallibleIterator;
+
+/// The kind of symbol that opens a [`Scope`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScopeKind {
+    /// A native or managed procedure.
+    Procedure,
+    /// A lexical block within a procedure.
+    Block,
+    /// The callsite of an inlined function.
+    InlineSite,
+    /// A thunk.
+    Thunk,
+    /// A block of code separated from its parent procedure.
+    SeparatedCode,
+    /// Any other scope, such as a `with` block.
+    Other,
+}
+
+/// A scope in a [`ScopeTree`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scope {
+    /// Index of the symbol opening this scope.
+    pub index: SymbolIndex,
+    /// Index of the symbol closing this scope.
+    ///
+    /// If the module ends before the scope is closed, this is the index of the opening symbol.
+    pub end: SymbolIndex,
+    /// The kind of symbol opening this scope.
+    pub kind: ScopeKind,
+    /// Index of the symbol opening the enclosing scope, if any.
+    pub parent: Option<SymbolIndex>,
+    /// Indexes of the symbols opening the scopes directly nested in this one, in symbol order.
+    pub children: Vec<SymbolIndex>,
+    /// The code covered by this scope, sorted by section and offset.
+    ///
+    /// Inline sites derive their ranges from their binary annotations. Scopes of kind
+    /// [`ScopeKind::Other`] do not declare any code.
+    pub ranges: Vec<CodeRange>,
+    depth: usize,
+}
+
+impl Scope {
+    /// Returns whether the code of this scope contains the given offset.
+    pub fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        self.ranges.iter().any(|range| range.contains(offset))
+    }
+}
+
+/// The nesting of procedures, blocks and inline sites in the symbols of a module.
+///
+/// Created by [`ModuleInfo::scope_tree`](crate::ModuleInfo::scope_tree). The tree is built from
+/// the scope start and end symbols rather than the `parent` and `end` fields of the individual
+/// symbols, which some compilers leave empty.
+#[derive(Clone, Debug, Default)]
+pub struct ScopeTree {
+    /// All scopes sorted by symbol index.
+    scopes: Vec<Scope>,
+}
+
+impl ScopeTree {
+    pub(crate) fn new(mut symbols: SymbolIter<'_>) -> Result<Self> {
+        let mut scopes: Vec<Scope> = Vec::new();
+        // Positions of the open scopes in `scopes`, innermost last.
+        let mut stack: Vec<usize> = Vec::new();
+
+        while let Some(symbol) = symbols.next()? {
+            if symbol.ends_scope() {
+                if let Some(position) = stack.pop() {
+                    scopes[position].end = symbol.index();
+                }
+                continue;
+            }
+
+            if !symbol.starts_scope() {
+                continue;
+            }
+
+            // Inline sites encode their code relative to the enclosing procedure.
+            let procedure_offset = stack
+                .iter()
+                .rev()
+                .map(|&position| &scopes[position])
+                .find(|scope| scope.kind == ScopeKind::Procedure)
+                .and_then(|scope| scope.ranges.first())
+                .map(|range| range.offset);
+
+            let (kind, mut ranges) = scope_ranges(&symbol, procedure_offset)?;
+            merge_code_ranges(&mut ranges);
+
+            let parent = stack.last().copied();
+            if let Some(parent) = parent {
+                scopes[parent].children.push(symbol.index());
+            }
+
+            stack.push(scopes.len());
+            scopes.push(Scope {
+                index: symbol.index(),
+                end: symbol.index(),
+                kind,
+                parent: parent.map(|parent| scopes[parent].index),
+                children: Vec::new(),
+                ranges,
+                depth: stack.len(),
+            });
+        }
+
+        Ok(Self { scopes })
+    }
+
+    /// Returns all scopes in symbol order.
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// Returns an iterator over the outermost scopes, such as procedures, in symbol order.
+    pub fn roots(&self) -> impl Iterator<Item = &Scope> + '_ {
+        self.scopes.iter().filter(|scope| scope.parent.is_none())
+    }
+
+    /// Returns the scope opened by the symbol at `index`.
+    pub fn get(&self, index: SymbolIndex) -> Option<&Scope> {
+        let position = self
+            .scopes
+            .binary_search_by_key(&index, |scope| scope.index)
+            .ok()?;
+        Some(&self.scopes[position])
+    }
+
+    /// Returns the scope enclosing the given scope.
+    pub fn parent(&self, scope: &Scope) -> Option<&Scope> {
+        self.get(scope.parent?)
+    }
+
+    /// Returns an iterator over the scopes directly nested in the given scope.
+    pub fn children<'a>(&'a self, scope: &'a Scope) -> impl Iterator<Item = &'a Scope> + 'a {
+        scope
+            .children
+            .iter()
+            .filter_map(move |&index| self.get(index))
+    }
+
+    /// Returns the innermost scope whose code contains the given offset.
+    ///
+    /// Returns `None` if no scope contains the offset.
+    pub fn enclosing_scope(&self, offset: PdbInternalSectionOffset) -> Option<&Scope> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.contains(offset))
+            .max_by_key(|scope| scope.depth)
+    }
+}
+
+/// Determines the kind and code ranges of the scope opened by `symbol`.
+fn scope_ranges(
+    symbol: &Symbol<'_>,
+    procedure_offset: Option<PdbInternalSectionOffset>,
+) -> Result<(ScopeKind, Vec<CodeRange>)> {
+    let range = |offset, length| alloc::vec![CodeRange { offset, length }];
+
+    Ok(match symbol.parse()? {
+        SymbolData::Procedure(data) => (ScopeKind::Procedure, range(data.offset, data.len)),
+        SymbolData::ManagedProcedure(data) => (ScopeKind::Procedure, range(data.offset, data.len)),
+        SymbolData::Block(data) => (ScopeKind::Block, range(data.offset, data.len)),
+        SymbolData::Thunk(data) => (ScopeKind::Thunk, range(data.offset, data.len.into())),
+        SymbolData::SeparatedCode(data) => (ScopeKind::SeparatedCode, range(data.offset, data.len)),
+        SymbolData::InlineSite(data) => {
+            let mut ranges = Vec::new();
+            if let Some(offset) = procedure_offset {
+                let mut lines =
+                    InlineeLineIterator::new(offset, &data, InlineeSourceLine::default());
+                while let Some(line) = lines.next()? {
+                    ranges.push(CodeRange {
+                        offset: line.offset,
+                        length: line.length.unwrap_or(0),
+                    });
+                }
+            }
+            (ScopeKind::InlineSite, ranges)
+        }
+        _ => (ScopeKind::Other, Vec::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::modi::test_records::{block, procedure, record};
+
+    fn inline_site(annotations: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 8];
+        data.extend_from_slice(&0x1000u32.to_le_bytes());
+        data.extend_from_slice(annotations);
+        record(0x114d, &data)
+    }
+
+    fn end(kind: u16) -> Vec<u8> {
+        record(kind, &[])
+    }
+
+    #[test]
+    fn test_scope_tree() {
+        let data = [
+            procedure(0x100, 0x40),
+            block(0x110, 0x20),
+            inline_site(&[0x0c, 0x08, 0x18]), // length 8 at offset 0x18
+            end(0x114e),
+            end(0x0006),
+            end(0x0006),
+            procedure(0x200, 0x10),
+            end(0x0006),
+        ]
+        .concat();
+
+        let tree = ScopeTree::new(SymbolIter::new(ParseBuffer::from(&data[..]))).expect("tree");
+
+        let kinds: Vec<_> = tree.scopes().iter().map(|scope| scope.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ScopeKind::Procedure,
+                ScopeKind::Block,
+                ScopeKind::InlineSite,
+                ScopeKind::Procedure,
+            ]
+        );
+        assert_eq!(tree.roots().count(), 2);
+
+        let procedure = &tree.scopes()[0];
+        let block = &tree.scopes()[1];
+        let site = &tree.scopes()[2];
+        assert_eq!(procedure.children, [block.index]);
+        assert_eq!(tree.children(block).collect::<Vec<_>>(), [site]);
+        assert_eq!(tree.parent(site), Some(block));
+        assert_eq!(tree.parent(procedure), None);
+        assert_eq!(procedure.end, SymbolIndex(tree.scopes()[3].index.0 - 4));
+
+        assert_eq!(
+            site.ranges,
+            [CodeRange {
+                offset: PdbInternalSectionOffset::new(1, 0x118),
+                length: 8,
+            }]
+        );
+
+        let enclosing = |offset| {
+            tree.enclosing_scope(PdbInternalSectionOffset::new(1, offset))
+                .map(|scope| scope.kind)
+        };
+        assert_eq!(enclosing(0x100), Some(ScopeKind::Procedure));
+        assert_eq!(enclosing(0x110), Some(ScopeKind::Block));
+        assert_eq!(enclosing(0x11c), Some(ScopeKind::InlineSite));
+        assert_eq!(enclosing(0x208), Some(ScopeKind::Procedure));
+        assert_eq!(enclosing(0x300), None);
+    }
+}
@@ -0,0 +1,31 @@
+//! Builders for symbol records in the unit tests of module information.
+
+use alloc::vec::Vec;
+
+/// Builds a symbol record with the given kind and data, including its length prefix.
+pub fn record(kind: u16, data: &[u8]) -> Vec<u8> {
+    let mut record = Vec::new();
+    record.extend_from_slice(&(data.len() as u16 + 2).to_le_bytes());
+    record.extend_from_slice(&kind.to_le_bytes());
+    record.extend_from_slice(data);
+    record
+}
+
+/// Builds an `S_GPROC32` record named `f` in section 1.
+pub fn procedure(offset: u32, len: u32) -> Vec<u8> {
+    let mut data = alloc::vec![0; 12];
+    data.extend_from_slice(&len.to_le_bytes());
+    data.extend_from_slice(&[0; 12]);
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(&[1, 0, 0, b'f', 0]);
+    record(0x1110, &data)
+}
+
+/// Builds an unnamed `S_BLOCK32` record in section 1.
+pub fn block(offset: u32, len: u32) -> Vec<u8> {
+    let mut data = alloc::vec![0; 8];
+    data.extend_from_slice(&len.to_le_bytes());
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(&[1, 0, 0]);
+    record(0x1103, &data)
+}
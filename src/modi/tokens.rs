@@ -0,0 +1,124 @@
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+
+use crate::common::*;
+
+/// The entity referenced by a .NET metadata token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenTarget {
+    /// A symbol of the module, usually a [`ManagedProcedureSymbol`](crate::ManagedProcedureSymbol).
+    Symbol(SymbolIndex),
+    /// A type in the [`TypeInformation`](crate::TypeInformation) stream.
+    Type(TypeIndex),
+}
+
+/// Maps .NET metadata tokens of a managed module to symbols and types, and vice versa.
+///
+/// Created by [`ModuleInfo::token_map`](crate::ModuleInfo::token_map). The map is built from the
+/// tokens of managed procedure symbols and from the function and type token map subsections of
+/// the module. If a token is declared more than once, the first declaration wins, and symbols take
+/// precedence over the token map subsections.
+#[derive(Clone, Debug, Default)]
+pub struct TokenMap {
+    tokens: BTreeMap<u32, TokenTarget>,
+    symbols: BTreeMap<SymbolIndex, u32>,
+    types: BTreeMap<TypeIndex, u32>,
+}
+
+impl TokenMap {
+    pub(crate) fn insert_symbol(&mut self, token: u32, index: SymbolIndex) {
+        // Only map back to tokens that resolve to the target, so both directions agree.
+        if let Entry::Vacant(entry) = self.tokens.entry(token) {
+            entry.insert(TokenTarget::Symbol(index));
+            self.symbols.entry(index).or_insert(token);
+        }
+    }
+
+    pub(crate) fn insert_type(&mut self, token: u32, index: TypeIndex) {
+        if let Entry::Vacant(entry) = self.tokens.entry(token) {
+            entry.insert(TokenTarget::Type(index));
+            self.types.entry(index).or_insert(token);
+        }
+    }
+
+    /// Returns the number of distinct tokens in this map.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Returns whether this map contains no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Resolves a metadata token to the symbol or type it refers to.
+    pub fn resolve(&self, token: u32) -> Option<TokenTarget> {
+        self.tokens.get(&token).copied()
+    }
+
+    /// Returns the metadata token of the symbol at the given index.
+    pub fn symbol_token(&self, index: SymbolIndex) -> Option<u32> {
+        self.symbols.get(&index).copied()
+    }
+
+    /// Returns the metadata token of the given type.
+    pub fn type_token(&self, index: TypeIndex) -> Option<u32> {
+        self.types.get(&index).copied()
+    }
+
+    /// Returns an iterator over all tokens and their targets, ordered by token.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, TokenTarget)> + '_ {
+        self.tokens.iter().map(|(token, target)| (*token, *target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_map() {
+        let mut map = TokenMap::default();
+        map.insert_symbol(0x0600_0001, SymbolIndex(0x40));
+        map.insert_type(0x0200_0002, TypeIndex(0x1003));
+        map.insert_type(0x0600_0001, TypeIndex(0x1004));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.resolve(0x0600_0001),
+            Some(TokenTarget::Symbol(SymbolIndex(0x40)))
+        );
+        assert_eq!(
+            map.resolve(0x0200_0002),
+            Some(TokenTarget::Type(TypeIndex(0x1003)))
+        );
+        assert_eq!(map.resolve(0x0200_0003), None);
+
+        assert_eq!(map.symbol_token(SymbolIndex(0x40)), Some(0x0600_0001));
+        assert_eq!(map.type_token(TypeIndex(0x1003)), Some(0x0200_0002));
+    }
+
+    #[test]
+    fn test_token_map_reinsert() {
+        let mut map = TokenMap::default();
+        map.insert_symbol(0x0600_0001, SymbolIndex(0x40));
+        map.insert_symbol(0x0600_0001, SymbolIndex(0x80));
+        map.insert_type(0x0600_0001, TypeIndex(0x1004));
+        map.insert_symbol(0x0600_0002, SymbolIndex(0x40));
+
+        // The first declaration of a token wins, and later ones leave no reverse entries.
+        assert_eq!(
+            map.resolve(0x0600_0001),
+            Some(TokenTarget::Symbol(SymbolIndex(0x40)))
+        );
+        assert_eq!(map.symbol_token(SymbolIndex(0x80)), None);
+        assert_eq!(map.type_token(TypeIndex(0x1004)), None);
+
+        // Every reverse entry resolves back to its target.
+        assert_eq!(map.symbol_token(SymbolIndex(0x40)), Some(0x0600_0001));
+        assert_eq!(
+            map.resolve(0x0600_0002),
+            Some(TokenTarget::Symbol(SymbolIndex(0x40)))
+        );
+    }
+}
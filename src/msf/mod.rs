@@ -5,17 +5,23 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::fmt;
-use std::ops::Deref;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt;
+use core::ops::Deref;
 
 use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
 use crate::source::*;
+use crate::sync::MaybeSend;
 
 mod page_list;
+mod writer;
 use self::page_list::PageList;
 
+pub(crate) use self::writer::write_msf;
+
 type PageNumber = u32;
 
 #[derive(Debug, Copy, Clone)]
@@ -275,7 +281,7 @@ mod big {
                 let mut page_numbers_to_skip: usize = 0;
                 for _ in 0..stream_number {
                     let bytes = stream_table.parse_u32()?;
-                    if bytes == u32::max_value() {
+                    if bytes == u32::MAX {
                         // stream is not present, ergo nothing to skip
                     } else {
                         page_numbers_to_skip += header.pages_needed_to_store(bytes as usize);
@@ -284,7 +290,7 @@ mod big {
 
                 // read our stream's size
                 bytes_in_stream = stream_table.parse_u32()?;
-                if bytes_in_stream == u32::max_value() {
+                if bytes_in_stream == u32::MAX {
                     return Err(Error::StreamNotFound(stream_number));
                 }
                 let pages_in_stream = header.pages_needed_to_store(bytes_in_stream as usize);
@@ -293,7 +299,7 @@ mod big {
                 let _ = stream_table.take((stream_count - stream_number - 1) as usize * 4)?;
 
                 // skip the preceding streams' page numbers
-                let _ = stream_table.take((page_numbers_to_skip as usize) * 4)?;
+                let _ = stream_table.take(page_numbers_to_skip * 4)?;
 
                 // we're now at the list of pages for our stream
                 // accumulate them into a PageList
@@ -316,7 +322,7 @@ mod big {
         }
     }
 
-    impl<'s, S: Source<'s>> Msf<'s, S> for BigMSF<'s, S> {
+    impl<'s, S: Source<'s> + MaybeSend> Msf<'s, S> for BigMSF<'s, S> {
         fn get(&mut self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
             // look up the stream
             let mut page_list = self.look_up_stream(stream_number)?;
@@ -330,10 +336,64 @@ mod big {
             let view = view(&mut self.source, &page_list)?;
 
             // pack it into a Stream
-            let stream = Stream { source_view: view };
+            let stream = Stream {
+                source_view: view.into(),
+            };
 
             Ok(stream)
         }
+
+        fn page_size(&self) -> usize {
+            self.header.page_size
+        }
+
+        fn stream_count(&mut self) -> Result<u32> {
+            self.make_stream_table_available()?;
+
+            if let StreamTable::Available {
+                ref stream_table_view,
+            } = self.stream_table
+            {
+                ParseBuffer::from(stream_table_view.as_slice()).parse_u32()
+            } else {
+                unreachable!();
+            }
+        }
+
+        fn stream_size(&mut self, stream_number: u32) -> Result<Option<usize>> {
+            if stream_number >= self.stream_count()? {
+                return Err(Error::StreamNotFound(stream_number));
+            }
+
+            match self.look_up_stream(stream_number) {
+                Ok(page_list) => Ok(Some(page_list.len())),
+                Err(Error::StreamNotFound(_)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        fn copy_stream(
+            &mut self,
+            stream_number: u32,
+            sink: &mut dyn Sink,
+            pages: &[PageNumber],
+        ) -> Result<()> {
+            let page_list = self.look_up_stream(stream_number)?;
+            let page_size = self.header.page_size;
+
+            // view and write each run of sequential pages separately, so that only a small part
+            // of the stream is held in memory at any time
+            let mut first_page = 0;
+            for slice in page_list.source_slices() {
+                let view = self.source.view(core::slice::from_ref(slice))?;
+                let page_count = (slice.size + (page_size - 1)) / page_size;
+                let target = &pages[first_page..first_page + page_count];
+                writer::write_pages(sink, page_size, target, view.as_slice())?;
+                first_page += page_count;
+            }
+
+            Ok(())
+        }
     }
 }
 
@@ -343,9 +403,12 @@ mod small {
 }
 
 /// Represents a single Stream within the multi-stream file.
-#[derive(Debug)]
+///
+/// The data of a stream is reference counted. Cloning a stream is cheap and shares the data with
+/// the original, which is released once the last clone is dropped.
+#[derive(Clone, Debug)]
 pub struct Stream<'s> {
-    source_view: Box<dyn SourceView<'s>>,
+    source_view: Arc<dyn SourceView<'s>>,
 }
 
 impl<'s> Stream<'s> {
@@ -371,16 +434,38 @@ impl Deref for Stream<'_> {
 }
 
 /// Provides access to a "multi-stream file", which is the container format used by PDBs.
-pub trait Msf<'s, S>: fmt::Debug {
+pub trait Msf<'s, S>: fmt::Debug + MaybeSend {
     /// Accesses a stream by stream number, optionally restricted by a byte limit.
     fn get(&mut self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>>;
+
+    /// Returns the size of a page in bytes.
+    fn page_size(&self) -> usize;
+
+    /// Returns the number of entries in the stream table, including streams that are not present.
+    fn stream_count(&mut self) -> Result<u32>;
+
+    /// Returns the size of a stream in bytes, or `None` if the stream is not present.
+    fn stream_size(&mut self, stream_number: u32) -> Result<Option<usize>>;
+
+    /// Copies the raw pages of a stream to `sink`, placing them at the given page numbers.
+    ///
+    /// `pages` must contain enough pages to hold the entire stream.
+    fn copy_stream(
+        &mut self,
+        stream_number: u32,
+        sink: &mut dyn Sink,
+        pages: &[PageNumber],
+    ) -> Result<()>;
 }
 
 fn header_matches(actual: &[u8], expected: &[u8]) -> bool {
     actual.len() >= expected.len() && &actual[0..expected.len()] == expected
 }
 
-pub fn open_msf<'s, S: Source<'s> + 's>(mut source: S) -> Result<Box<dyn Msf<'s, S> + 's>> {
+pub fn open_msf<'s, S>(mut source: S) -> Result<Box<dyn Msf<'s, S> + 's>>
+where
+    S: Source<'s> + MaybeSend + 's,
+{
     // map the header
     let mut header_location = PageList::new(4096);
     header_location.push(0);
@@ -388,7 +473,7 @@ pub fn open_msf<'s, S: Source<'s> + 's>(mut source: S) -> Result<Box<dyn Msf<'s,
         Ok(view) => view,
         Err(e) => match e {
             Error::IoError(x) => {
-                if x.kind() == std::io::ErrorKind::UnexpectedEof {
+                if is_unexpected_eof(&x) {
                     return Err(Error::UnrecognizedFileFormat);
                 } else {
                     return Err(Error::IoError(x));
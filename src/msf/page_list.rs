@@ -7,6 +7,7 @@
 
 use crate::msf::PageNumber;
 use crate::source::SourceSlice;
+use alloc::vec::Vec;
 
 /// Represents a list of `PageNumbers`, which are likely (but not certainly) sequential, and which
 /// will be presented as a slice of `SourceSlice`s.
@@ -0,0 +1,273 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::common::*;
+use crate::msf::{big, Msf, PageNumber};
+use crate::source::Sink;
+
+/// Hands out page numbers for a BigMSF being written.
+///
+/// Page 0 holds the header. The two free page maps occupy the pages at offsets 1 and 2 of every
+/// interval of `page_size` pages, so these are never allocated to streams.
+#[derive(Debug)]
+pub(super) struct PageAllocator {
+    page_size: usize,
+    next_page: PageNumber,
+}
+
+impl PageAllocator {
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            page_size,
+            next_page: 3,
+        }
+    }
+
+    fn is_free_page_map(&self, page: PageNumber) -> bool {
+        let in_interval = page as usize % self.page_size;
+        in_interval == 1 || in_interval == 2
+    }
+
+    /// Allocates the next available page.
+    pub fn allocate(&mut self) -> PageNumber {
+        while self.is_free_page_map(self.next_page) {
+            self.next_page += 1;
+        }
+
+        let page = self.next_page;
+        self.next_page += 1;
+        page
+    }
+
+    /// Allocates enough pages to store `bytes` bytes.
+    pub fn allocate_bytes(&mut self, bytes: usize) -> Vec<PageNumber> {
+        let count = (bytes + (self.page_size - 1)) / self.page_size;
+        (0..count).map(|_| self.allocate()).collect()
+    }
+
+    /// Returns the total number of pages in the file, including the header and free page maps.
+    pub fn page_count(&self) -> PageNumber {
+        self.next_page
+    }
+}
+
+/// Writes `data` into consecutive entries of `pages`, combining runs of sequential pages into a
+/// single write.
+pub(super) fn write_pages(
+    sink: &mut dyn Sink,
+    page_size: usize,
+    pages: &[PageNumber],
+    data: &[u8],
+) -> Result<()> {
+    debug_assert!(data.len() <= pages.len() * page_size);
+
+    let mut written = 0;
+    let mut index = 0;
+    while written < data.len() {
+        let first = pages[index];
+        let mut run = 1;
+        while index + run < pages.len()
+            && pages[index + run] == first + run as PageNumber
+            && written + run * page_size < data.len()
+        {
+            run += 1;
+        }
+
+        let end = core::cmp::min(data.len(), written + run * page_size);
+        let offset = first as u64 * page_size as u64;
+        sink.write_at(offset, &data[written..end])?;
+
+        written = end;
+        index += run;
+    }
+
+    Ok(())
+}
+
+/// The contents of a stream in the file being written.
+#[derive(Debug)]
+enum StreamSource<'a> {
+    /// The stream does not exist.
+    Absent,
+    /// The stream is copied from the input file without being parsed.
+    Copy(usize),
+    /// The stream is replaced with new data.
+    Replace(&'a [u8]),
+}
+
+impl StreamSource<'_> {
+    fn size(&self) -> Option<usize> {
+        match *self {
+            Self::Absent => None,
+            Self::Copy(size) => Some(size),
+            Self::Replace(data) => Some(data.len()),
+        }
+    }
+}
+
+/// Writes a BigMSF to `sink`, using the page size and streams of `msf`.
+///
+/// Streams listed in `replacements` are written with the given contents, and may also add streams
+/// beyond the end of the input's stream table. All other streams are copied from the input page
+/// by page.
+pub(crate) fn write_msf<'s, S>(
+    msf: &mut dyn Msf<'s, S>,
+    sink: &mut dyn Sink,
+    replacements: &BTreeMap<u32, &[u8]>,
+) -> Result<()> {
+    let page_size = msf.page_size();
+
+    let input_count = msf.stream_count()?;
+    let output_count = match replacements.keys().next_back() {
+        Some(&last) => core::cmp::max(input_count, last + 1),
+        None => input_count,
+    };
+
+    let mut streams = Vec::with_capacity(output_count as usize);
+    for stream_number in 0..output_count {
+        let source = match replacements.get(&stream_number) {
+            Some(data) => StreamSource::Replace(data),
+            None if stream_number >= input_count => StreamSource::Absent,
+            None => match msf.stream_size(stream_number)? {
+                Some(size) => StreamSource::Copy(size),
+                None => StreamSource::Absent,
+            },
+        };
+        streams.push(source);
+    }
+
+    // lay out the streams and write their contents
+    let mut allocator = PageAllocator::new(page_size);
+    let mut directory = Vec::new();
+    directory.extend_from_slice(&output_count.to_le_bytes());
+
+    for stream in &streams {
+        let size = match stream.size() {
+            Some(size) => u32::try_from(size)
+                .ok()
+                .filter(|&size| size != u32::MAX)
+                .ok_or(Error::InvalidStreamLength("MSF stream"))?,
+            None => u32::MAX,
+        };
+        directory.extend_from_slice(&size.to_le_bytes());
+    }
+
+    for (stream_number, stream) in streams.iter().enumerate() {
+        let pages = allocator.allocate_bytes(stream.size().unwrap_or(0));
+        match *stream {
+            StreamSource::Absent => (),
+            StreamSource::Copy(_) => msf.copy_stream(stream_number as u32, sink, &pages)?,
+            StreamSource::Replace(data) => write_pages(sink, page_size, &pages, data)?,
+        }
+
+        for page in pages {
+            directory.extend_from_slice(&page.to_le_bytes());
+        }
+    }
+
+    // the directory is stored in pages listed by the block map, which is in turn listed by the
+    // header
+    let directory_pages = allocator.allocate_bytes(directory.len());
+    write_pages(sink, page_size, &directory_pages, &directory)?;
+
+    let mut block_map = Vec::with_capacity(directory_pages.len() * 4);
+    for page in &directory_pages {
+        block_map.extend_from_slice(&page.to_le_bytes());
+    }
+
+    let block_map_pages = allocator.allocate_bytes(block_map.len());
+    write_pages(sink, page_size, &block_map_pages, &block_map)?;
+
+    let page_count = allocator.page_count();
+
+    // pad the final page, so that the file is a multiple of the page size
+    let last_page = u64::from(page_count - 1) * page_size as u64;
+    let last_page_used = match block_map.len() % page_size {
+        0 => page_size,
+        used => used,
+    };
+    sink.write_at(
+        last_page + last_page_used as u64,
+        &vec![0; page_size - last_page_used],
+    )?;
+
+    // header
+    let mut header = Vec::with_capacity(page_size);
+    header.extend_from_slice(big::MAGIC);
+    header.extend_from_slice(&(page_size as u32).to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes());
+    header.extend_from_slice(&page_count.to_le_bytes());
+    header.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    for page in &block_map_pages {
+        header.extend_from_slice(&page.to_le_bytes());
+    }
+
+    if header.len() > page_size {
+        return Err(Error::UnimplementedFeature(
+            "MSF directory too large for page size",
+        ));
+    }
+
+    header.resize(page_size, 0);
+    sink.write_at(0, &header)?;
+
+    // free page maps: every page in the file is in use
+    //
+    // the free page map is a bitmap spread across the free page map pages of all intervals, with
+    // each page holding `page_size * 8` bits. a set bit marks a free page.
+    for (interval, interval_start) in (0..page_count).step_by(page_size).enumerate() {
+        let first_bit = interval * page_size * 8;
+        let mut free_page_map = vec![0xff; page_size];
+        for (index, byte) in free_page_map.iter_mut().enumerate() {
+            for b in 0..8 {
+                if first_bit + index * 8 + b < page_count as usize {
+                    *byte &= !(1 << b);
+                }
+            }
+        }
+
+        for fpm_page in &[interval_start + 1, interval_start + 2] {
+            if *fpm_page < page_count {
+                sink.write_at(u64::from(*fpm_page) * page_size as u64, &free_page_map)?;
+            }
+        }
+    }
+
+    sink.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::msf::writer::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_allocator_skips_free_page_maps() {
+        let mut allocator = PageAllocator::new(256);
+        assert_eq!(allocator.allocate(), 3);
+
+        let pages = allocator.allocate_bytes(253 * 256);
+        assert_eq!(pages.first(), Some(&4));
+        assert_eq!(pages.last(), Some(&256));
+        assert_eq!(allocator.allocate(), 259);
+        assert_eq!(allocator.page_count(), 260);
+    }
+
+    #[test]
+    fn test_write_pages() {
+        let mut sink = Cursor::new(Vec::new());
+        write_pages(&mut sink, 4, &[1, 2, 0], b"abcdefghij").expect("writing must succeed");
+        assert_eq!(sink.get_ref().as_slice(), b"ij\0\0abcdefgh");
+    }
+}
@@ -7,11 +7,14 @@
 
 //! Utilities for translating addresses between PDB offsets and _Relative Virtual Addresses_ (RVAs).
 
-use std::cmp::{self, Ordering};
-use std::fmt;
-use std::iter::FusedIterator;
-use std::mem;
-use std::ops::Range;
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::Range;
+
+use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
 use crate::msf::Stream;
@@ -22,7 +25,7 @@ use crate::pe::ImageSectionHeader;
 /// This record applies to the half-open interval [ `record.source_address`,
 /// `next_record.source_address` ).
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
 pub(crate) struct OMAPRecord {
     source_address: u32,
     target_address: u32,
@@ -32,21 +35,21 @@ impl OMAPRecord {
     /// Create a new OMAP record for the given mapping.
     pub fn new(source_address: u32, target_address: u32) -> Self {
         Self {
-            source_address: source_address.to_le(),
-            target_address: target_address.to_le(),
+            source_address,
+            target_address,
         }
     }
 
     /// Returns the address in the source space.
     #[inline]
     pub fn source_address(self) -> u32 {
-        u32::from_le(self.source_address)
+        self.source_address
     }
 
     /// Returns the start of the mapped portion in the target address space.
     #[inline]
     pub fn target_address(self) -> u32 {
-        u32::from_le(self.target_address)
+        self.target_address
     }
 
     /// Translate the given address into the target address space.
@@ -59,6 +62,19 @@ impl OMAPRecord {
     }
 }
 
+impl<'t> TryFromCtx<'t, Endian> for OMAPRecord {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let mut offset = 0;
+        let data = Self {
+            source_address: this.gread_with(&mut offset, le)?,
+            target_address: this.gread_with(&mut offset, le)?,
+        };
+        Ok((data, offset))
+    }
+}
+
 impl fmt::Debug for OMAPRecord {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("OMAPRecord")
@@ -77,7 +93,7 @@ impl fmt::Debug for OMAPRecord {
 impl PartialOrd for OMAPRecord {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.source_address().partial_cmp(&other.source_address())
+        Some(self.cmp(other))
     }
 }
 
@@ -124,7 +140,7 @@ pub(crate) struct OMAPTable<'s> {
 
 impl<'s> OMAPTable<'s> {
     pub(crate) fn parse(stream: Stream<'s>) -> Result<Self> {
-        match cast_aligned::<OMAPRecord>(stream.as_slice()) {
+        match RecordTable::<OMAPRecord>::new(stream.as_slice()) {
             Some(_) => Ok(OMAPTable { stream }),
             None => Err(Error::InvalidStreamLength("OMAP")),
         }
@@ -132,9 +148,9 @@ impl<'s> OMAPTable<'s> {
 
     /// Returns a direct view onto the records stored in this OMAP table.
     #[inline]
-    pub fn records(&self) -> &[OMAPRecord] {
-        // alignment is checked during parsing, unwrap is safe.
-        cast_aligned(self.stream.as_slice()).unwrap()
+    pub fn records(&self) -> RecordTable<'_, OMAPRecord> {
+        // the size is checked during parsing, unwrap is safe.
+        RecordTable::new(self.stream.as_slice()).unwrap()
     }
 
     /// Look up `source_address` to yield a target address.
@@ -147,7 +163,7 @@ impl<'s> OMAPTable<'s> {
             Err(i) => i - 1,
         };
 
-        let record = records[index];
+        let record = records.get(index)?;
 
         // As a special case, `target_address` can be zero, which indicates that the
         // `source_address` does not exist in the target address space.
@@ -167,12 +183,12 @@ impl<'s> OMAPTable<'s> {
 
         let records = self.records();
         let (record, next) = match records.binary_search_by_key(&start, |r| r.source_address()) {
-            Ok(i) => (records[i], &records[i + 1..]),
+            Ok(i) => (records.get(i).unwrap_or_default(), records.skip(i + 1)),
             // Insert a dummy record no indicate that the range before the first record is invalid.
             // The range might still overlap with the first record however, so attempt regular
             // iteration.
             Err(0) => (OMAPRecord::new(0, 0), records),
-            Err(i) => (records[i - 1], &records[i..]),
+            Err(i) => (records.get(i - 1).unwrap_or_default(), records.skip(i)),
         };
 
         RangeIter {
@@ -193,7 +209,7 @@ impl fmt::Debug for OMAPTable<'_> {
 /// An iterator over mapped target ranges in an OMAP.
 pub(crate) struct RangeIter<'t> {
     /// Iterator over subsequent OMAP records.
-    records: std::slice::Iter<'t, OMAPRecord>,
+    records: RecordIter<'t, OMAPRecord>,
     /// The record that spans the current start address.
     record: OMAPRecord,
     /// The start address of the current subrange.
@@ -206,7 +222,7 @@ impl<'t> RangeIter<'t> {
     /// Creates a `RangeIter` that does not yield any ranges.
     pub fn empty() -> Self {
         RangeIter {
-            records: [].iter(),
+            records: RecordIter::default(),
             record: OMAPRecord::new(0, 0),
             addr: 0,
             end: 0,
@@ -219,7 +235,7 @@ impl<'t> RangeIter<'t> {
         // since the target must be a non-zero value to be recognized as valid mapping. Since there
         // are no further records, a single subrange `start..end` will be considered.
         RangeIter {
-            records: [].iter(),
+            records: RecordIter::default(),
             record: OMAPRecord::new(range.start, range.start),
             addr: range.start,
             end: range.end,
@@ -242,7 +258,7 @@ impl Iterator for RangeIter<'_> {
             // next one, this will determine the end of the current sub slice. If there are no more
             // records, create an unmapped dummy record starting at the end of the source range.
             let next_record = match self.records.next() {
-                Some(record) => *record,
+                Some(record) => record,
                 None => OMAPRecord::new(self.end, 0),
             };
 
@@ -359,7 +375,7 @@ impl FusedIterator for PdbInternalRvaRangeIter<'_> {}
 /// #
 /// # fn test() -> pdb::Result<()> {
 /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(source)?;
+/// let pdb = pdb::PDB::open(source)?;
 ///
 /// // Compute the address map once and reuse it
 /// let address_map = pdb.address_map()?;
@@ -423,7 +439,7 @@ impl<'s> AddressMap<'s> {
     }
 }
 
-fn get_section_offset(sections: &[ImageSectionHeader], address: u32) -> Option<(u16, u32)> {
+fn get_section_offset(sections: &[ImageSectionHeader], address: u32) -> Result<(u16, u32)> {
     // Section headers are sorted by virtual_address, so we only need to iterate until we exceed
     // the desired address. Since the number of section headers is relatively low, a sequential
     // search is the fastest option here.
@@ -431,16 +447,40 @@ fn get_section_offset(sections: &[ImageSectionHeader], address: u32) -> Option<(
         .iter()
         .take_while(|s| s.virtual_address <= address)
         .enumerate()
-        .find(|(_, s)| address < s.virtual_address + s.size_of_raw_data)?;
+        .find(|(_, s)| {
+            u64::from(address) < u64::from(s.virtual_address) + u64::from(s.size_of_raw_data)
+        })
+        .ok_or(Error::AddressOutsideSections(address))?;
 
-    Some((index as u16 + 1, address - section.virtual_address))
+    Ok((index as u16 + 1, address - section.virtual_address))
 }
 
-fn get_virtual_address(sections: &[ImageSectionHeader], section: u16, offset: u32) -> Option<u32> {
-    (section as usize)
+fn get_virtual_address(sections: &[ImageSectionHeader], section: u16, offset: u32) -> Result<u32> {
+    let header = (section as usize)
         .checked_sub(1)
         .and_then(|i| sections.get(i))
-        .map(|section| section.virtual_address + offset)
+        .ok_or(Error::InvalidSection(section))?;
+
+    // The end of the section is a valid offset, so that the ends of ranges can be translated.
+    // Uninitialized data occupies no space in the file, so the virtual size may be larger.
+    let size = header.virtual_size.max(header.size_of_raw_data);
+    if offset > size {
+        return Err(Error::InvalidSectionOffset(section, offset));
+    }
+
+    header
+        .virtual_address
+        .checked_add(offset)
+        .ok_or(Error::InvalidSectionOffset(section, offset))
+}
+
+fn lookup_omap(omap: &Option<OMAPTable<'_>>, address: u32) -> Result<u32> {
+    match omap {
+        Some(ref omap) => omap
+            .lookup(address)
+            .ok_or(Error::AddressEliminated(address)),
+        None => Ok(address),
+    }
 }
 
 impl Rva {
@@ -449,10 +489,17 @@ impl Rva {
     /// This address is not necessarily compatible with the executable's address space and should
     /// therefore not be used for debugging purposes.
     pub fn to_internal_rva(self, translator: &AddressMap<'_>) -> Option<PdbInternalRva> {
-        match translator.transformed_to_original {
-            Some(ref omap) => omap.lookup(self.0).map(PdbInternalRva),
-            None => Some(PdbInternalRva(self.0)),
-        }
+        self.try_to_internal_rva(translator).ok()
+    }
+
+    /// Resolves a PDB-internal Relative Virtual Address, reporting why the address cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressEliminated` if the address has no counterpart in the PDB's address space
+    pub fn try_to_internal_rva(self, translator: &AddressMap<'_>) -> Result<PdbInternalRva> {
+        lookup_omap(&translator.transformed_to_original, self.0).map(PdbInternalRva)
     }
 
     /// Resolves the section offset in the PE headers.
@@ -460,12 +507,22 @@ impl Rva {
     /// This is an offset into PE section headers of the executable. To retrieve section offsets
     /// used in the PDB, use [`to_internal_offset`](Self::to_internal_offset) instead.
     pub fn to_section_offset(self, translator: &AddressMap<'_>) -> Option<SectionOffset> {
+        self.try_to_section_offset(translator).ok()
+    }
+
+    /// Resolves the section offset in the PE headers, reporting why the address cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressOutsideSections` if the address is not covered by any section
+    pub fn try_to_section_offset(self, translator: &AddressMap<'_>) -> Result<SectionOffset> {
         let (section, offset) = match translator.transformed_sections {
             Some(ref sections) => get_section_offset(sections, self.0)?,
             None => get_section_offset(&translator.original_sections, self.0)?,
         };
 
-        Some(SectionOffset { section, offset })
+        Ok(SectionOffset { section, offset })
     }
 
     /// Resolves the PDB internal section offset.
@@ -476,18 +533,39 @@ impl Rva {
         self,
         translator: &AddressMap<'_>,
     ) -> Option<PdbInternalSectionOffset> {
-        self.to_internal_rva(translator)?
-            .to_internal_offset(translator)
+        self.try_to_internal_offset(translator).ok()
+    }
+
+    /// Resolves the PDB internal section offset, reporting why the address cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressEliminated` if the address has no counterpart in the PDB's address space
+    /// * `Error::AddressOutsideSections` if the address is not covered by any original section
+    pub fn try_to_internal_offset(
+        self,
+        translator: &AddressMap<'_>,
+    ) -> Result<PdbInternalSectionOffset> {
+        self.try_to_internal_rva(translator)?
+            .try_to_internal_offset(translator)
     }
 }
 
 impl PdbInternalRva {
     /// Resolves an actual Relative Virtual Address in the executable's address space.
     pub fn to_rva(self, translator: &AddressMap<'_>) -> Option<Rva> {
-        match translator.original_to_transformed {
-            Some(ref omap) => omap.lookup(self.0).map(Rva),
-            None => Some(Rva(self.0)),
-        }
+        self.try_to_rva(translator).ok()
+    }
+
+    /// Resolves an actual Relative Virtual Address in the executable's address space, reporting
+    /// why the address cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressEliminated` if the code or data at this address was eliminated from the
+    ///   executable
+    pub fn try_to_rva(self, translator: &AddressMap<'_>) -> Result<Rva> {
+        lookup_omap(&translator.original_to_transformed, self.0).map(Rva)
     }
 
     /// Resolves the section offset in the PE headers.
@@ -495,7 +573,20 @@ impl PdbInternalRva {
     /// This is an offset into PE section headers of the executable. To retrieve section offsets
     /// used in the PDB, use [`to_internal_offset`](Self::to_internal_offset) instead.
     pub fn to_section_offset(self, translator: &AddressMap<'_>) -> Option<SectionOffset> {
-        self.to_rva(translator)?.to_section_offset(translator)
+        self.try_to_section_offset(translator).ok()
+    }
+
+    /// Resolves the section offset in the PE headers, reporting why the address cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressEliminated` if the code or data at this address was eliminated from the
+    ///   executable
+    /// * `Error::AddressOutsideSections` if the address is not covered by any section
+    pub fn try_to_section_offset(self, translator: &AddressMap<'_>) -> Result<SectionOffset> {
+        self.try_to_rva(translator)?
+            .try_to_section_offset(translator)
     }
 
     /// Resolves the PDB internal section offset.
@@ -506,20 +597,43 @@ impl PdbInternalRva {
         self,
         translator: &AddressMap<'_>,
     ) -> Option<PdbInternalSectionOffset> {
+        self.try_to_internal_offset(translator).ok()
+    }
+
+    /// Resolves the PDB internal section offset, reporting why the address cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::AddressOutsideSections` if the address is not covered by any original section
+    pub fn try_to_internal_offset(
+        self,
+        translator: &AddressMap<'_>,
+    ) -> Result<PdbInternalSectionOffset> {
         let (section, offset) = get_section_offset(&translator.original_sections, self.0)?;
-        Some(PdbInternalSectionOffset { section, offset })
+        Ok(PdbInternalSectionOffset { section, offset })
     }
 }
 
 impl SectionOffset {
     /// Resolves an actual Relative Virtual Address in the executable's address space.
     pub fn to_rva(self, translator: &AddressMap<'_>) -> Option<Rva> {
+        self.try_to_rva(translator).ok()
+    }
+
+    /// Resolves an actual Relative Virtual Address in the executable's address space, reporting
+    /// why the offset cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the executable
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    pub fn try_to_rva(self, translator: &AddressMap<'_>) -> Result<Rva> {
         let address = match translator.transformed_sections {
             Some(ref sections) => get_virtual_address(sections, self.section, self.offset)?,
             None => get_virtual_address(&translator.original_sections, self.section, self.offset)?,
         };
 
-        Some(Rva(address))
+        Ok(Rva(address))
     }
 
     /// Resolves a PDB-internal Relative Virtual Address.
@@ -527,7 +641,19 @@ impl SectionOffset {
     /// This address is not necessarily compatible with the executable's address space and should
     /// therefore not be used for debugging purposes.
     pub fn to_internal_rva(self, translator: &AddressMap<'_>) -> Option<PdbInternalRva> {
-        self.to_rva(translator)?.to_internal_rva(translator)
+        self.try_to_internal_rva(translator).ok()
+    }
+
+    /// Resolves a PDB-internal Relative Virtual Address, reporting why the offset cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the executable
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    /// * `Error::AddressEliminated` if the address has no counterpart in the PDB's address space
+    pub fn try_to_internal_rva(self, translator: &AddressMap<'_>) -> Result<PdbInternalRva> {
+        self.try_to_rva(translator)?.try_to_internal_rva(translator)
     }
 
     /// Resolves the PDB internal section offset.
@@ -535,21 +661,49 @@ impl SectionOffset {
         self,
         translator: &AddressMap<'_>,
     ) -> Option<PdbInternalSectionOffset> {
+        self.try_to_internal_offset(translator).ok()
+    }
+
+    /// Resolves the PDB internal section offset, reporting why the offset cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the executable
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    /// * `Error::AddressEliminated` if the address has no counterpart in the PDB's address space
+    /// * `Error::AddressOutsideSections` if the address is not covered by any original section
+    pub fn try_to_internal_offset(
+        self,
+        translator: &AddressMap<'_>,
+    ) -> Result<PdbInternalSectionOffset> {
         if translator.transformed_sections.is_none() {
             // Fast path to avoid section table lookups
             let Self { section, offset } = self;
-            return Some(PdbInternalSectionOffset { section, offset });
+            return Ok(PdbInternalSectionOffset { section, offset });
         }
 
-        self.to_internal_rva(translator)?
-            .to_internal_offset(translator)
+        self.try_to_internal_rva(translator)?
+            .try_to_internal_offset(translator)
     }
 }
 
 impl PdbInternalSectionOffset {
     /// Resolves an actual Relative Virtual Address in the executable's address space.
     pub fn to_rva(self, translator: &AddressMap<'_>) -> Option<Rva> {
-        self.to_internal_rva(translator)?.to_rva(translator)
+        self.try_to_rva(translator).ok()
+    }
+
+    /// Resolves an actual Relative Virtual Address in the executable's address space, reporting
+    /// why the offset cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the original section headers
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    /// * `Error::AddressEliminated` if the code or data at this offset was eliminated from the
+    ///   executable
+    pub fn try_to_rva(self, translator: &AddressMap<'_>) -> Result<Rva> {
+        self.try_to_internal_rva(translator)?.try_to_rva(translator)
     }
 
     /// Resolves a PDB-internal Relative Virtual Address.
@@ -557,19 +711,45 @@ impl PdbInternalSectionOffset {
     /// This address is not necessarily compatible with the executable's address space and should
     /// therefore not be used for debugging purposes.
     pub fn to_internal_rva(self, translator: &AddressMap<'_>) -> Option<PdbInternalRva> {
+        self.try_to_internal_rva(translator).ok()
+    }
+
+    /// Resolves a PDB-internal Relative Virtual Address, reporting why the offset cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the original section headers
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    pub fn try_to_internal_rva(self, translator: &AddressMap<'_>) -> Result<PdbInternalRva> {
         get_virtual_address(&translator.original_sections, self.section, self.offset)
             .map(PdbInternalRva)
     }
 
     /// Resolves the section offset in the PE headers.
     pub fn to_section_offset(self, translator: &AddressMap<'_>) -> Option<SectionOffset> {
+        self.try_to_section_offset(translator).ok()
+    }
+
+    /// Resolves the section offset in the PE headers, reporting why the offset cannot be
+    /// resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSection` if the section does not exist in the original section headers
+    /// * `Error::InvalidSectionOffset` if the offset lies past the end of the section
+    /// * `Error::AddressEliminated` if the code or data at this offset was eliminated from the
+    ///   executable
+    /// * `Error::AddressOutsideSections` if the address is not covered by any section
+    pub fn try_to_section_offset(self, translator: &AddressMap<'_>) -> Result<SectionOffset> {
         if translator.transformed_sections.is_none() {
             // Fast path to avoid section table lookups
             let Self { section, offset } = self;
-            return Some(SectionOffset { section, offset });
+            return Ok(SectionOffset { section, offset });
         }
 
-        self.to_rva(translator)?.to_section_offset(translator)
+        self.try_to_rva(translator)?
+            .try_to_section_offset(translator)
     }
 }
 
@@ -577,7 +757,7 @@ impl PdbInternalSectionOffset {
 mod tests {
     use super::*;
 
-    use std::mem;
+    use core::mem;
 
     #[test]
     fn test_omap_record() {
@@ -589,13 +769,83 @@ mod tests {
     fn test_get_virtual_address() {
         let sections = vec![ImageSectionHeader {
             virtual_address: 0x1000_0000,
+            virtual_size: 0x2000,
             ..Default::default()
         }];
 
-        assert_eq!(get_virtual_address(&sections, 1, 0x1234), Some(0x1000_1234));
-        assert_eq!(get_virtual_address(&sections, 2, 0x1234), None);
+        assert_eq!(
+            get_virtual_address(&sections, 1, 0x1234).ok(),
+            Some(0x1000_1234)
+        );
+        assert_eq!(get_virtual_address(&sections, 2, 0x1234).ok(), None);
 
         // https://github.com/willglynn/pdb/issues/87
-        assert_eq!(get_virtual_address(&sections, 0, 0x1234), None);
+        assert_eq!(get_virtual_address(&sections, 0, 0x1234).ok(), None);
+    }
+
+    #[test]
+    fn test_get_virtual_address_errors() {
+        let sections = vec![
+            ImageSectionHeader {
+                virtual_address: 0x1000,
+                virtual_size: 0x100,
+                size_of_raw_data: 0x200,
+                ..Default::default()
+            },
+            ImageSectionHeader {
+                virtual_address: 0xffff_f000,
+                virtual_size: 0x2000,
+                ..Default::default()
+            },
+        ];
+
+        assert!(matches!(
+            get_virtual_address(&sections, 0, 0x10),
+            Err(Error::InvalidSection(0))
+        ));
+        assert!(matches!(
+            get_virtual_address(&sections, 3, 0x10),
+            Err(Error::InvalidSection(3))
+        ));
+
+        // offsets up to the end of the section are valid
+        assert_eq!(get_virtual_address(&sections, 1, 0x200).ok(), Some(0x1200));
+        assert!(matches!(
+            get_virtual_address(&sections, 1, 0x201),
+            Err(Error::InvalidSectionOffset(1, 0x201))
+        ));
+
+        // overflow of the address space
+        assert!(matches!(
+            get_virtual_address(&sections, 2, 0x1000),
+            Err(Error::InvalidSectionOffset(2, 0x1000))
+        ));
+    }
+
+    #[test]
+    fn test_get_section_offset() {
+        let sections = vec![
+            ImageSectionHeader {
+                virtual_address: 0x1000,
+                size_of_raw_data: 0x800,
+                ..Default::default()
+            },
+            ImageSectionHeader {
+                virtual_address: 0x2000,
+                size_of_raw_data: 0x1000,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(get_section_offset(&sections, 0x1010).ok(), Some((1, 0x10)));
+        assert_eq!(get_section_offset(&sections, 0x2fff).ok(), Some((2, 0xfff)));
+
+        // between sections and past the last section
+        for &address in &[0x0fff, 0x1800, 0x3000] {
+            assert!(matches!(
+                get_section_offset(&sections, address),
+                Err(Error::AddressOutsideSections(a)) if a == address
+            ));
+        }
     }
 }
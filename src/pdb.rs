@@ -5,18 +5,27 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::breakpad::{stack_cfi_records, StackRecord, StackWinRecord};
+use crate::cache::IndexKind;
 use crate::common::*;
-use crate::dbi::{DBIExtraStreams, DBIHeader, DebugInformation, Module};
+use crate::dbi::{DBIHeader, DebugInformation, Module, OptionalDebugHeader};
 use crate::framedata::FrameTable;
-use crate::modi::ModuleInfo;
+use crate::modi::{ArchitectureMap, Functions, InlineSites, ModuleInfo};
 use crate::msf::{self, Msf, Stream};
 use crate::omap::{AddressMap, OMAPTable};
-use crate::pdbi::PDBInformation;
+use crate::pdbi::{PDBInformation, PdbIdentity};
 use crate::pe::ImageSectionHeader;
-use crate::source::Source;
+use crate::source::{Sink, Source};
 use crate::strings::StringTable;
 use crate::symbol::SymbolTable;
-use crate::tpi::{IdInformation, TypeInformation};
+use crate::sync::{self, Lock, MaybeSend, Once};
+use crate::tpi::{IdInformation, ItemInformation, TypeInformation};
+use crate::unwind::UnwindData;
+use crate::FallibleIterator;
 
 // Some streams have a fixed stream index.
 // http://llvm.org/docs/PDB/index.html
@@ -31,25 +40,34 @@ const IPI_STREAM: u32 = 4;
 /// A PDB file is internally a Multi-Stream File (MSF), composed of multiple independent
 /// (and usually discontiguous) data streams on-disk. `PDB` provides lazy access to these data
 /// structures, which means the `PDB` accessor methods usually cause disk accesses.
+///
+/// Accessors only require a shared reference. With the `sync` feature, a `PDB` can be shared
+/// between threads, which serializes reads from the `Source`.
 #[derive(Debug)]
 pub struct PDB<'s, S> {
     /// `msf` provides access to the underlying data streams
-    msf: Box<dyn Msf<'s, S> + 's>,
+    msf: Lock<Box<dyn Msf<'s, S> + 's>>,
 
     /// Memoize the `dbi::Header`, since it contains stream numbers we sometimes need
-    dbi_header: Option<DBIHeader>,
+    dbi_header: Once<DBIHeader>,
 
-    /// Memoize the `dbi::DBIExtraStreams`, since it too contains stream numbers we sometimes need
-    dbi_extra_streams: Option<DBIExtraStreams>,
+    /// Memoize the `dbi::OptionalDebugHeader`, since it too contains stream numbers we sometimes need
+    optional_debug_header: Once<OptionalDebugHeader>,
+
+    /// Memoize the index of the string table stream, which is looked up by name
+    string_table_stream: Once<StreamIndex>,
 }
 
-impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+impl<'s, S: Source<'s> + MaybeSend + 's> PDB<'s, S> {
     /// Create a new `PDB` for a `Source`.
     ///
     /// `open()` accesses enough of the source file to find the MSF stream table. This usually
     /// involves reading the header, a block near the end of the file, and finally the stream table
     /// itself. It does not access or validate any of the contents of the rest of the PDB.
     ///
+    /// With the `sync` feature, the source must be `Send`, so that the `PDB` can be shared between
+    /// threads.
+    ///
     /// # Errors
     ///
     /// * `Error::UnimplementedFeature` if the PDB file predates ~2002
@@ -58,12 +76,15 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::PageReferenceOutOfRange`, `Error::InvalidPageSize` if the PDB file seems corrupt
     pub fn open(source: S) -> Result<PDB<'s, S>> {
         Ok(PDB {
-            msf: msf::open_msf(source)?,
-            dbi_header: None,
-            dbi_extra_streams: None,
+            msf: Lock::new(msf::open_msf(source)?),
+            dbi_header: Once::new(),
+            optional_debug_header: Once::new(),
+            string_table_stream: Once::new(),
         })
     }
+}
 
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// Retrieve the `PDBInformation` for this PDB.
     ///
     /// The `PDBInformation` object contains the GUID and age fields that can be used to verify
@@ -74,11 +95,70 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn pdb_information(&mut self) -> Result<PDBInformation<'s>> {
-        let stream = self.msf.get(PDB_STREAM, None)?;
+    pub fn pdb_information(&self) -> Result<PDBInformation<'s>> {
+        let stream = self.stream(PDB_STREAM, None)?;
         PDBInformation::parse(stream)
     }
 
+    /// Returns the identity that images linked with this PDB declare in their CodeView record.
+    ///
+    /// The age is taken from the DBI stream, which is written by the linker. Old PDBs that do not
+    /// declare an age there fall back to the age of the PDB information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the information or DBI stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn image_identity(&self) -> Result<PdbIdentity> {
+        let info = self.pdb_information()?;
+        let age = match self.dbi_header()?.age {
+            0 => info.age,
+            age => age,
+        };
+
+        Ok(PdbIdentity {
+            guid: info.guid,
+            age,
+        })
+    }
+
+    /// Checks whether this PDB belongs to an image with the given identity.
+    ///
+    /// The GUIDs must be equal. If the DBI stream declares an age, it must equal the image's age.
+    /// Otherwise, the age of the PDB information stream must be at least the image's age, since it
+    /// is bumped whenever the PDB is modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`image_identity`](Self::image_identity).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// // Usually parsed from the CodeView record of a minidump module.
+    /// let identity = pdb.image_identity()?;
+    /// assert!(pdb.matches_image(&identity)?);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn matches_image(&self, identity: &PdbIdentity) -> Result<bool> {
+        let info = self.pdb_information()?;
+        if info.guid != identity.guid {
+            return Ok(false);
+        }
+
+        Ok(match self.dbi_header()?.age {
+            0 => info.age >= identity.age,
+            age => age == identity.age,
+        })
+    }
+
     /// Retrieve the `TypeInformation` for this PDB.
     ///
     /// The `TypeInformation` object owns a `SourceView` for the type information ("TPI") stream.
@@ -91,9 +171,8 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::InvalidTypeInformationHeader` if the type information stream header was not
     ///   understood
-    pub fn type_information(&mut self) -> Result<TypeInformation<'s>> {
-        let stream = self.msf.get(TPI_STREAM, None)?;
-        TypeInformation::parse(stream)
+    pub fn type_information(&self) -> Result<TypeInformation<'s>> {
+        self.item_information(TPI_STREAM, IndexKind::TypeFinder)
     }
 
     /// Retrieve the `IdInformation` for this PDB.
@@ -107,9 +186,8 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::InvalidTypeInformationHeader` if the id information stream header was not
     ///   understood
-    pub fn id_information(&mut self) -> Result<IdInformation<'s>> {
-        let stream = self.msf.get(IPI_STREAM, None)?;
-        IdInformation::parse(stream)
+    pub fn id_information(&self) -> Result<IdInformation<'s>> {
+        self.item_information(IPI_STREAM, IndexKind::IdFinder)
     }
 
     /// Retrieve the `DebugInformation` for this PDB.
@@ -122,27 +200,22 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn debug_information(&mut self) -> Result<DebugInformation<'s>> {
-        let stream = self.msf.get(DBI_STREAM, None)?;
+    pub fn debug_information(&self) -> Result<DebugInformation<'s>> {
+        let stream = self.stream(DBI_STREAM, None)?;
         let debug_info = DebugInformation::parse(stream)?;
 
         // Grab its header, since we need that for unrelated operations
-        self.dbi_header = Some(debug_info.header());
+        let _ = self.dbi_header.set(debug_info.header());
         Ok(debug_info)
     }
 
-    fn dbi_header(&mut self) -> Result<DBIHeader> {
-        // see if we've already got a header
-        if let Some(ref h) = self.dbi_header {
-            return Ok(*h);
-        }
-
-        // get just the first little bit of the DBI stream
-        let stream = self.msf.get(DBI_STREAM, Some(1024))?;
-        let header = DBIHeader::parse(stream)?;
-
-        self.dbi_header = Some(header);
-        Ok(header)
+    fn dbi_header(&self) -> Result<DBIHeader> {
+        sync::get_or_try_init(&self.dbi_header, || {
+            // get just the first little bit of the DBI stream
+            let stream = self.stream(DBI_STREAM, Some(1024))?;
+            DBIHeader::parse(stream)
+        })
+        .copied()
     }
 
     /// Retrieve the global symbol table for this PDB.
@@ -165,7 +238,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     ///
     /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn global_symbols(&mut self) -> Result<SymbolTable<'s>> {
+    pub fn global_symbols(&self) -> Result<SymbolTable<'s>> {
         // the global symbol table is stored in a stream number described by the DBI header
         // so, start by getting the DBI header
         let dbi_header = self.dbi_header()?;
@@ -175,7 +248,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
             .raw_stream(dbi_header.symbol_records_stream)?
             .ok_or(Error::GlobalSymbolsNotFound)?;
 
-        Ok(SymbolTable::new(stream))
+        Ok(SymbolTable::new(dbi_header.symbol_records_stream, stream))
     }
 
     /// Retrieve the module info stream for a specific `Module`.
@@ -199,7 +272,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// let dbi = pdb.debug_information()?;
     /// let mut modules = dbi.modules()?;
     /// if let Some(module) = modules.next()? {
@@ -214,10 +287,184 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn module_info<'m>(&mut self, module: &Module<'m>) -> Result<Option<ModuleInfo<'s>>> {
+    pub fn module_info<'m>(&self, module: &Module<'m>) -> Result<Option<ModuleInfo<'s>>> {
         Ok(self
             .raw_stream(module.info().stream)?
-            .map(|stream| ModuleInfo::parse(stream, module)))
+            .map(|stream| ModuleInfo::parse(stream, module.info())))
+    }
+
+    /// Enumerates the inline sites of all modules.
+    ///
+    /// Every inline site is reported together with its module, the procedure it was inlined into
+    /// and the inlinee record of the inlined function. Modules without module information are
+    /// skipped. The module information is only loaded when the inline sites of a module are
+    /// requested.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the debug information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let inline_sites = pdb.inline_sites()?;
+    /// let mut sites = inline_sites.iter();
+    /// while let Some((module, site)) = sites.next()? {
+    ///     println!("module {}: {} inlined into {}", module, site.symbol.inlinee, site.procedure.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn inline_sites(&self) -> Result<InlineSites<'_, 's, S>> {
+        let dbi = self.debug_information()?;
+        let mut modules = dbi.modules()?;
+
+        let mut infos = Vec::new();
+        let mut index = 0;
+        while let Some(module) = modules.next()? {
+            if !module.info().stream.is_none() {
+                infos.push((index, *module.info()));
+            }
+            index += 1;
+        }
+
+        Ok(InlineSites::new(self, infos))
+    }
+
+    /// Loads the procedure symbols of all modules into a deduplicated list of functions.
+    ///
+    /// Procedure addresses are translated into the executable's address space using the
+    /// [`address_map`](Self::address_map), and procedures of eliminated code are skipped. If
+    /// multiple procedure symbols start at the same address, for example due to COMDAT folding,
+    /// only the first one in module order is kept. Modules without module information are skipped.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the debug information stream
+    /// * `Error::AddressMapNotFound` if the PDB contains OMAP tables but not both of them
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let functions = pdb.functions()?;
+    /// let mut iter = functions.iter();
+    /// while let Some(function) = iter.next()? {
+    ///     println!("{}..{}: {}", function.range.start, function.range.end, function.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn functions(&self) -> Result<Functions<'s>> {
+        self.functions_with_policy(DegenerateRecordPolicy::Keep)
+    }
+
+    /// Collect the functions of all modules like [`functions`](Self::functions), handling
+    /// procedures without code according to `zero_length`.
+    ///
+    /// Zero-length procedures are kept by [`functions`](Self::functions) and match lookups of their
+    /// start address, which often hides the actual function at that address.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`functions`](Self::functions):
+    ///
+    /// * `Error::DegenerateRecord` if a procedure has no code and `zero_length` is
+    ///   [`DegenerateRecordPolicy::Report`]
+    pub fn functions_with_policy(
+        &self,
+        zero_length: DegenerateRecordPolicy,
+    ) -> Result<Functions<'s>> {
+        let address_map = self.address_map()?;
+        let dbi = self.debug_information()?;
+        let mut modules = dbi.modules()?;
+
+        let mut infos = Vec::new();
+        let mut index = 0;
+        while let Some(module) = modules.next()? {
+            if let Some(info) = self.module_info(&module)? {
+                infos.push((index, info));
+            }
+            index += 1;
+        }
+
+        Functions::new(infos, &address_map, zero_length)
+    }
+
+    /// Determines the architecture of every code range of the executable.
+    ///
+    /// Hybrid images, such as ARM64X binaries, contain code of multiple architectures. The
+    /// architecture of a range is taken from the compile flags of the module contributing it, and
+    /// ranges of modules without compile flags are omitted. Use
+    /// [`MachineType::is_hybrid`](crate::MachineType::is_hybrid) or
+    /// [`ArchitectureMap::is_hybrid`] to detect such images.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the debug information stream
+    /// * `Error::AddressMapNotFound` if the PDB contains OMAP tables but not both of them
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let architectures = pdb.architecture_map()?;
+    /// for range in architectures.ranges() {
+    ///     println!("{}..{}: {}", range.range.start, range.range.end, range.cpu_type);
+    /// }
+    /// # assert!(!architectures.is_hybrid());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn architecture_map(&self) -> Result<ArchitectureMap> {
+        let address_map = self.address_map()?;
+        let dbi = self.debug_information()?;
+
+        let mut cpu_types = Vec::new();
+        let mut modules = dbi.modules()?;
+        while let Some(module) = modules.next()? {
+            let cpu_type = match self.module_info(&module)? {
+                Some(info) => info.compile_flags()?.map(|flags| flags.cpu_type),
+                None => None,
+            };
+            cpu_types.push(cpu_type);
+        }
+
+        let mut contributions = Vec::new();
+        let mut iter = dbi.section_contributions()?;
+        while let Some(contribution) = iter.next()? {
+            contributions.push(contribution);
+        }
+
+        Ok(ArchitectureMap::new(
+            dbi.machine_type()?,
+            &cpu_types,
+            &contributions,
+            &address_map,
+        ))
     }
 
     /// Retrieve the executable's section headers, as stored inside this PDB.
@@ -238,9 +485,9 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     ///
     /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn sections(&mut self) -> Result<Option<Vec<ImageSectionHeader>>> {
-        let index = self.extra_streams()?.section_headers;
-        let stream = match self.raw_stream(index)? {
+    pub fn sections(&self) -> Result<Option<Vec<ImageSectionHeader>>> {
+        let index = self.optional_debug_header()?.section_headers;
+        let stream = match self.optional_stream(index)? {
             Some(stream) => stream,
             None => return Ok(None),
         };
@@ -275,7 +522,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = PDB::open(source)?;
+    /// let pdb = PDB::open(source)?;
     ///
     /// // Read the tables once and reuse them
     /// let address_map = pdb.address_map()?;
@@ -290,16 +537,91 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # }
     /// # test().unwrap()
     /// ```
-    pub fn frame_table(&mut self) -> Result<FrameTable<'s>> {
-        let extra = self.extra_streams()?;
-        let old_stream = self.raw_stream(extra.fpo)?;
-        let new_stream = self.raw_stream(extra.framedata)?;
+    pub fn frame_table(&self) -> Result<FrameTable<'s>> {
+        let header = self.optional_debug_header()?;
+        let old_stream = self.optional_stream(header.fpo)?;
+        let new_stream = self.optional_stream(header.framedata)?;
         FrameTable::parse(old_stream, new_stream)
     }
 
-    pub(crate) fn original_sections(&mut self) -> Result<Option<Vec<ImageSectionHeader>>> {
-        let index = self.extra_streams()?.original_section_headers;
-        let stream = match self.raw_stream(index)? {
+    /// Retrieve the copies of the `.pdata` and `.xdata` sections of an x64 image.
+    ///
+    /// The linker stores these sections in the PDB so that x64 stacks can be unwound without the
+    /// image. Returns `None` if the PDB does not contain them, which is the case for x86 images.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced streams
+    /// * `Error::InvalidStreamLength` if the stream headers are malformed
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn unwind_data(&self) -> Result<Option<UnwindData<'s>>> {
+        let header = self.optional_debug_header()?;
+        let pdata = self.optional_stream(header.pdata)?;
+        let xdata = self.optional_stream(header.xdata)?;
+
+        match (pdata, xdata) {
+            (Some(pdata), Some(xdata)) => UnwindData::parse(pdata, xdata).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Collect Breakpad `STACK` records for all functions with frame data or unwind information.
+    ///
+    /// `STACK WIN` records are created from the [`frame_table`](Self::frame_table), and `STACK CFI`
+    /// records from the [`unwind_data`](Self::unwind_data). The records are sorted by address.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`frame_table`](Self::frame_table) and
+    /// [`unwind_data`](Self::unwind_data):
+    ///
+    /// * `Error::InvalidUnwindInfo` if the unwind information of a function is malformed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn test() -> pdb::Result<()> {
+    /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(source)?;
+    ///
+    /// for record in pdb.stack_records()? {
+    ///     println!("{}", record);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn stack_records(&self) -> Result<Vec<StackRecord>> {
+        let address_map = self.address_map()?;
+        let strings = match self.string_table() {
+            Ok(strings) => Some(strings),
+            Err(Error::StreamNameNotFound) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut records = Vec::new();
+
+        let frame_table = self.frame_table()?;
+        let mut frames = frame_table.iter();
+        while let Some(frame) = frames.next()? {
+            if let Some(record) = StackWinRecord::new(&frame, &address_map, strings.as_ref())? {
+                records.push(StackRecord::Win(record));
+            }
+        }
+
+        if let Some(unwind_data) = self.unwind_data()? {
+            let table = unwind_data.table();
+            records.extend(stack_cfi_records(&table)?.into_iter().map(StackRecord::Cfi));
+        }
+
+        records.sort_by_key(StackRecord::rva);
+        Ok(records)
+    }
+
+    pub(crate) fn original_sections(&self) -> Result<Option<Vec<ImageSectionHeader>>> {
+        let index = self.optional_debug_header()?.original_section_headers;
+        let stream = match self.optional_stream(index)? {
             Some(stream) => stream,
             None => return Ok(None),
         };
@@ -313,17 +635,17 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
         Ok(Some(headers))
     }
 
-    pub(crate) fn omap_from_src(&mut self) -> Result<Option<OMAPTable<'s>>> {
-        let index = self.extra_streams()?.omap_from_src;
-        match self.raw_stream(index)? {
+    pub(crate) fn omap_from_src(&self) -> Result<Option<OMAPTable<'s>>> {
+        let index = self.optional_debug_header()?.omap_from_src;
+        match self.optional_stream(index)? {
             Some(stream) => OMAPTable::parse(stream).map(Some),
             None => Ok(None),
         }
     }
 
-    pub(crate) fn omap_to_src(&mut self) -> Result<Option<OMAPTable<'s>>> {
-        let index = self.extra_streams()?.omap_to_src;
-        match self.raw_stream(index)? {
+    pub(crate) fn omap_to_src(&self) -> Result<Option<OMAPTable<'s>>> {
+        let index = self.optional_debug_header()?.omap_to_src;
+        match self.optional_stream(index)? {
             Some(stream) => OMAPTable::parse(stream).map(Some),
             None => Ok(None),
         }
@@ -358,7 +680,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(source)?;
+    /// let pdb = pdb::PDB::open(source)?;
     ///
     /// // Compute the address map once and reuse it
     /// let address_map = pdb.address_map()?;
@@ -382,7 +704,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # }
     /// # test().unwrap()
     /// ```
-    pub fn address_map(&mut self) -> Result<AddressMap<'s>> {
+    pub fn address_map(&self) -> Result<AddressMap<'s>> {
         let sections = self.sections()?.unwrap_or_default();
         Ok(match self.original_sections()? {
             Some(original_sections) => {
@@ -422,7 +744,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = PDB::open(file)?;
+    /// let pdb = PDB::open(file)?;
     /// let strings = pdb.string_table()?;
     ///
     /// // obtain a string ref somehow
@@ -443,8 +765,12 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::UnexpectedEof` if the string table ends prematurely
-    pub fn string_table(&mut self) -> Result<StringTable<'s>> {
-        let stream = self.named_stream(b"/names")?;
+    pub fn string_table(&self) -> Result<StringTable<'s>> {
+        let index = sync::get_or_try_init(&self.string_table_stream, || {
+            self.named_stream_index(b"/names")
+        })?;
+
+        let stream = self.raw_stream(*index)?.ok_or(Error::StreamNameNotFound)?;
         StringTable::parse(stream)
     }
 
@@ -453,6 +779,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # Errors
     ///
     /// * `Error::StreamNotFound` if the PDB does not contain this stream
+    /// * `Error::StreamDeleted` if the index marks a deleted stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     ///
@@ -461,20 +788,40 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// ```
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// // This is the index of the "mystream" stream that was added using pdbstr.exe.
     /// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
     /// assert_eq!(s.as_slice(), b"hello world\n");
     /// # Ok(())
     /// # }
     /// ```
-    pub fn raw_stream(&mut self, index: StreamIndex) -> Result<Option<Stream<'s>>> {
-        match index.msf_number() {
-            Some(number) => self.msf.get(number, None).map(Some),
+    pub fn raw_stream(&self, index: StreamIndex) -> Result<Option<Stream<'s>>> {
+        match index.msf_number()? {
+            Some(number) => self.stream(number, None).map(Some),
             None => Ok(None),
         }
     }
 
+    /// Parses the type or id stream with the given number along with its hash stream.
+    ///
+    /// The hash stream is optional, so failing to read it leaves the stream without hashes.
+    fn item_information<I: ItemIndex>(
+        &self,
+        number: u32,
+        kind: IndexKind,
+    ) -> Result<ItemInformation<'s, I>> {
+        let info = ItemInformation::parse(self.stream(number, None)?, kind)?;
+        let hash_stream = info
+            .hash_stream()
+            .and_then(|index| self.raw_stream(index).ok().flatten());
+
+        Ok(info.with_hash_stream(hash_stream))
+    }
+
+    fn stream(&self, number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
+        self.msf.with(|msf| msf.get(number, limit))
+    }
+
     /// Retrieve a stream by its name, as declared in the PDB info stream.
     ///
     /// # Errors
@@ -483,36 +830,122 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::StreamNotFound` if the PDB does not contain the stream referred to
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn named_stream(&mut self, name: &[u8]) -> Result<Stream<'s>> {
+    pub fn named_stream(&self, name: &[u8]) -> Result<Stream<'s>> {
+        let index = self.named_stream_index(name)?;
+        self.raw_stream(index)?.ok_or(Error::StreamNameNotFound)
+    }
+
+    fn named_stream_index(&self, name: &[u8]) -> Result<StreamIndex> {
         let info = self.pdb_information()?;
         let names = info.stream_names()?;
         for named_stream in &names {
             if named_stream.name.as_bytes() == name {
-                return self
-                    .raw_stream(named_stream.stream_id)?
-                    .ok_or(Error::StreamNameNotFound);
+                return Ok(named_stream.stream_id);
             }
         }
         Err(Error::StreamNameNotFound)
     }
 
-    /// Loads the Optional Debug Header Stream, which contains offsets into extra streams.
+    /// Writes a copy of this PDB to `sink`, replacing the contents of some streams.
+    ///
+    /// Streams listed in `replacements` are written with the given contents. Indexes beyond the
+    /// end of the stream table add new streams, and an index of `StreamIndex::none()` is ignored.
+    /// Replacing a stream at `StreamIndex::deleted()` is an error.
+    /// All other streams are copied page by page from the `Source` without being parsed, so the
+    /// cost of rewriting is dominated by the size of the replaced streams.
     ///
-    /// this stream is always returned, but its members are all optional depending on the data
-    /// present in the PDB.
+    /// The copy uses the page size of the original file. Streams are laid out contiguously, which
+    /// means that the output is usually smaller than the input.
     ///
-    /// The optional header begins at offset 0 immediately after the EC Substream ends.
-    fn extra_streams(&mut self) -> Result<DBIExtraStreams> {
-        if let Some(extra) = self.dbi_extra_streams {
-            return Ok(extra);
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source` or `Sink`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidStreamLength` if a replacement stream is too large for the MSF format
+    /// * `Error::StreamDeleted` if a replacement refers to a deleted stream
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let mut pdb = pdb::PDB::open(file)?;
+    ///
+    /// let mut replacements = BTreeMap::new();
+    /// replacements.insert(pdb::StreamIndex(208), &b"goodbye world\n"[..]);
+    ///
+    /// let mut output = std::io::Cursor::new(Vec::new());
+    /// pdb.rewrite(&mut output, &replacements)?;
+    ///
+    /// output.set_position(0);
+    /// let rewritten = pdb::PDB::open(output)?;
+    /// let s = rewritten.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+    /// assert_eq!(s.as_slice(), b"goodbye world\n");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn rewrite<W>(
+        &mut self,
+        sink: &mut W,
+        replacements: &BTreeMap<StreamIndex, &[u8]>,
+    ) -> Result<()>
+    where
+        W: Sink,
+    {
+        let mut numbered = BTreeMap::new();
+        for (index, data) in replacements {
+            if let Some(number) = index.msf_number()? {
+                numbered.insert(number, *data);
+            }
         }
 
-        // Parse and grab information on extra streams, since we might also need that
-        let debug_info = self.debug_information()?;
-        let extra = DBIExtraStreams::new(&debug_info)?;
-        self.dbi_extra_streams = Some(extra);
+        msf::write_msf(self.msf.get_mut().as_mut(), sink, &numbered)
+    }
 
-        Ok(extra)
+    /// Returns the indexes of additional streams declared in the optional debug header of the DBI
+    /// stream.
+    ///
+    /// The header is always present, but its members are all optional depending on the data
+    /// present in the PDB. It is loaded once and memoized.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the DBI stream
+    /// * `Error::UnexpectedEof` if the optional debug header is truncated
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let header = pdb.optional_debug_header()?;
+    /// if let Some(index) = header.token_rid_map {
+    ///     let stream = pdb.raw_stream(index)?.expect("token map");
+    ///     println!("token map: {} bytes", stream.as_slice().len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn optional_debug_header(&self) -> Result<OptionalDebugHeader> {
+        sync::get_or_try_init(&self.optional_debug_header, || {
+            self.debug_information()?.optional_debug_header()
+        })
+        .copied()
+    }
+
+    /// Loads a stream referenced by the optional debug header, if present.
+    fn optional_stream(&self, index: Option<StreamIndex>) -> Result<Option<Stream<'s>>> {
+        match index {
+            Some(index) => self.raw_stream(index),
+            None => Ok(None),
+        }
     }
 }
 
@@ -525,9 +958,10 @@ impl StreamIndex {
     /// # Errors
     ///
     /// * `Error::StreamNotFound` if the PDB does not contain this stream
+    /// * `Error::StreamDeleted` if this index marks a deleted stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn get<'s, S>(self, pdb: &mut PDB<'s, S>) -> Result<Option<Stream<'s>>>
+    pub fn get<'s, S>(self, pdb: &PDB<'s, S>) -> Result<Option<Stream<'s>>>
     where
         S: Source<'s> + 's,
     {
@@ -5,15 +5,35 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::convert::TryInto;
-use std::mem;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
+use core::mem;
 
 use uuid::Uuid;
 
 use crate::common::*;
-use crate::dbi::HeaderVersion;
 use crate::msf::*;
 
+/// Identifies a PDB by its GUID and age.
+///
+/// Images declare the identity of the PDB they were linked with in the CodeView record of their
+/// debug directory. Minidumps carry a copy of this record for every module in their module list,
+/// which can be parsed with [`from_codeview`](Self::from_codeview). Use
+/// [`PDB::matches_image`](crate::PDB::matches_image) to check whether a PDB belongs to an image.
+///
+/// Serialized indexes record the [`index_identity`](PDBInformation::index_identity) of the PDB
+/// they were built from. Since the age is bumped every time the PDB is written, they are
+/// invalidated when the PDB changes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PdbIdentity {
+    /// The GUID of the PDB.
+    pub guid: Uuid,
+    /// The age of the PDB.
+    pub age: u32,
+}
+
 /// A PDB info stream header parsed from a stream.
 ///
 /// The [PDB information stream] contains the GUID and age fields that can be used to
@@ -24,7 +44,7 @@ use crate::msf::*;
 #[derive(Debug)]
 pub struct PDBInformation<'s> {
     /// The version of the PDB format in use.
-    pub version: HeaderVersion,
+    pub version: PdbStreamVersion,
     /// A 32-bit timestamp.
     pub signature: u32,
     /// The number of times this PDB file has been written.
@@ -75,10 +95,20 @@ impl<'s> PDBInformation<'s> {
         })
     }
 
+    /// Returns the identity of this PDB, which binds serialized indexes to it.
+    pub fn index_identity(&self) -> PdbIdentity {
+        PdbIdentity {
+            guid: self.guid,
+            age: self.age,
+        }
+    }
+
     /// Get a `StreamNames` object that can be used to iterate over named streams contained
     /// within the PDB file.
     ///
-    /// This can be used to look up certain PDB streams by name.
+    /// This can be used to look up certain PDB streams by name. Entries with a stream number that
+    /// does not fit into a [`StreamIndex`] or with a name outside of the name data are malformed and
+    /// skipped, so that the remaining streams can still be found.
     ///
     /// # Example
     ///
@@ -87,7 +117,7 @@ impl<'s> PDBInformation<'s> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// let info = pdb.pdb_information()?;
     /// let names = info.stream_names()?;
     /// let mut v: Vec<_> = names.iter().map(|n| n.name.to_string()).collect();
@@ -136,8 +166,21 @@ impl<'s> PDBInformation<'s> {
         let names_buf = names_reader.take(self.names_size)?;
         for _ in 0..count {
             let name_offset = buf.parse_u32()? as usize;
-            let stream_id = StreamIndex(buf.parse_u32()? as u16);
-            let name = ParseBuffer::from(&names_buf[name_offset..]).parse_cstring()?;
+            let stream_number = buf.parse_u32()?;
+
+            // Skip malformed entries, so that they do not hide the remaining streams.
+            let stream_id = match StreamIndex::try_from(stream_number) {
+                Ok(stream_id) => stream_id,
+                Err(_) => continue,
+            };
+            let name = match names_buf.get(name_offset..) {
+                Some(data) => match ParseBuffer::from(data).parse_cstring() {
+                    Ok(name) => name,
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+
             names.push(StreamName { name, stream_id });
         }
 
@@ -145,6 +188,93 @@ impl<'s> PDBInformation<'s> {
     }
 }
 
+/// The version of the PDB information stream format.
+///
+/// Each version corresponds to the Visual C++ release that introduced it. Use [`u32::from`] to
+/// compare versions numerically.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PdbStreamVersion {
+    /// Visual C++ 2.0.
+    VC2,
+    /// Visual C++ 4.0.
+    VC4,
+    /// Visual C++ 4.1.
+    VC41,
+    /// Visual C++ 5.0.
+    VC50,
+    /// Visual C++ 6.0.
+    VC98,
+    /// Pre-release versions of Visual C++ 7.0.
+    VC70Dep,
+    /// Visual C++ 7.0 (Visual Studio .NET).
+    VC70,
+    /// Visual C++ 8.0 (Visual Studio 2005).
+    VC80,
+    /// Visual C++ 11.0 (Visual Studio 2012).
+    VC110,
+    /// Visual C++ 14.0 (Visual Studio 2015).
+    VC140,
+    /// An unknown version.
+    OtherValue(u32),
+}
+
+impl From<u32> for PdbStreamVersion {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(v: u32) -> Self {
+        match v {
+            1994_16_10 => Self::VC2,
+            1995_06_23 => Self::VC4,
+            1995_08_14 => Self::VC41,
+            1996_03_07 => Self::VC50,
+            1997_06_04 => Self::VC98,
+            1999_06_04 => Self::VC70Dep,
+            2000_04_04 => Self::VC70,
+            2003_09_01 => Self::VC80,
+            2009_12_01 => Self::VC110,
+            2014_05_08 => Self::VC140,
+            _ => Self::OtherValue(v),
+        }
+    }
+}
+
+impl From<PdbStreamVersion> for u32 {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(version: PdbStreamVersion) -> Self {
+        match version {
+            PdbStreamVersion::VC2 => 1994_16_10,
+            PdbStreamVersion::VC4 => 1995_06_23,
+            PdbStreamVersion::VC41 => 1995_08_14,
+            PdbStreamVersion::VC50 => 1996_03_07,
+            PdbStreamVersion::VC98 => 1997_06_04,
+            PdbStreamVersion::VC70Dep => 1999_06_04,
+            PdbStreamVersion::VC70 => 2000_04_04,
+            PdbStreamVersion::VC80 => 2003_09_01,
+            PdbStreamVersion::VC110 => 2009_12_01,
+            PdbStreamVersion::VC140 => 2014_05_08,
+            PdbStreamVersion::OtherValue(v) => v,
+        }
+    }
+}
+
+impl fmt::Display for PdbStreamVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VC2 => f.write_str("VC2"),
+            Self::VC4 => f.write_str("VC4"),
+            Self::VC41 => f.write_str("VC41"),
+            Self::VC50 => f.write_str("VC50"),
+            Self::VC98 => f.write_str("VC98"),
+            Self::VC70Dep => f.write_str("VC70Dep"),
+            Self::VC70 => f.write_str("VC70"),
+            Self::VC80 => f.write_str("VC80"),
+            Self::VC110 => f.write_str("VC110"),
+            Self::VC140 => f.write_str("VC140"),
+            Self::OtherValue(v) => write!(f, "unknown ({})", v),
+        }
+    }
+}
+
 /// A named stream contained within the PDB file.
 #[derive(Debug)]
 pub struct StreamName<'n> {
@@ -165,7 +295,7 @@ pub struct StreamNames<'s> {
 }
 
 /// An iterator over [`StreamName`]s.
-pub type NameIter<'a, 'n> = std::slice::Iter<'a, StreamName<'n>>;
+pub type NameIter<'a, 'n> = core::slice::Iter<'a, StreamName<'n>>;
 
 impl<'s> StreamNames<'s> {
     /// Return an iterator over named streams and their stream indices.
@@ -9,7 +9,7 @@
 
 // PDBs contain PE section headers in one or two streams. `pdb::pe` is responsible for parsing them.
 
-use std::fmt;
+use core::fmt;
 
 use scroll::ctx::TryFromCtx;
 use scroll::Endian;
@@ -236,6 +236,60 @@ impl SectionCharacteristics {
     }
 }
 
+/// A coarse classification of section contents, derived from [`SectionCharacteristics`].
+///
+/// This mirrors the conventional `.text`, `.data`, `.rdata` and `.bss` sections, but is computed
+/// from the flags rather than the section name, since names are not reliable.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SectionKind {
+    /// Executable code, such as `.text`.
+    Code,
+    /// Initialized, writable data, such as `.data`.
+    Data,
+    /// Initialized, read-only data, such as `.rdata`.
+    ReadOnlyData,
+    /// Uninitialized data that is zero-filled when loaded, such as `.bss`.
+    UninitializedData,
+    /// Anything else, such as linker information or sections without content flags.
+    Other,
+}
+
+impl SectionCharacteristics {
+    /// The section contains code or is executable.
+    ///
+    /// This checks both the content flag and the memory protection flag, since linkers do not
+    /// always set both.
+    pub fn is_code(self) -> bool {
+        self.executable() || self.execute()
+    }
+
+    /// The section can be written to when loaded.
+    pub fn is_writable(self) -> bool {
+        self.write()
+    }
+
+    /// The section can be discarded after loading, such as relocations.
+    pub fn is_discardable(self) -> bool {
+        self.discardable()
+    }
+
+    /// Classifies the section contents into code, data, read-only data or uninitialized data.
+    pub fn kind(self) -> SectionKind {
+        if self.is_code() {
+            SectionKind::Code
+        } else if self.uninitialized_data() {
+            SectionKind::UninitializedData
+        } else if self.initialized_data() && self.is_writable() {
+            SectionKind::Data
+        } else if self.initialized_data() {
+            SectionKind::ReadOnlyData
+        } else {
+            SectionKind::Other
+        }
+    }
+}
+
 impl fmt::Debug for SectionCharacteristics {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
@@ -368,6 +422,13 @@ impl ImageSectionHeader {
         })
     }
 
+    /// Classifies the section contents into code, data, read-only data or uninitialized data.
+    ///
+    /// See [`SectionCharacteristics::kind`].
+    pub fn kind(&self) -> SectionKind {
+        self.characteristics.kind()
+    }
+
     /// Returns the name of the section.
     pub fn name(&self) -> &str {
         let end = self
@@ -378,7 +439,7 @@ impl ImageSectionHeader {
 
         // The spec guarantees that the name is a proper UTF-8 string.
         // TODO: Look up long names from the string table.
-        std::str::from_utf8(&self.name[0..end]).unwrap_or("")
+        core::str::from_utf8(&self.name[0..end]).unwrap_or("")
     }
 }
 
@@ -445,6 +506,31 @@ mod tests {
         assert_eq!(characteristics.alignment(), Some(64));
     }
 
+    #[test]
+    fn test_section_kind() {
+        let text = SectionCharacteristics(0x6000_0020);
+        assert!(text.is_code());
+        assert!(!text.is_writable());
+        assert_eq!(text.kind(), SectionKind::Code);
+
+        let data = SectionCharacteristics(0xc000_0040);
+        assert!(!data.is_code());
+        assert!(data.is_writable());
+        assert_eq!(data.kind(), SectionKind::Data);
+
+        let rdata = SectionCharacteristics(0x4000_0040);
+        assert_eq!(rdata.kind(), SectionKind::ReadOnlyData);
+
+        let bss = SectionCharacteristics(0xc000_0080);
+        assert_eq!(bss.kind(), SectionKind::UninitializedData);
+
+        let reloc = SectionCharacteristics(0x4200_0040);
+        assert!(reloc.is_discardable());
+        assert_eq!(reloc.kind(), SectionKind::ReadOnlyData);
+
+        assert_eq!(SectionCharacteristics(0).kind(), SectionKind::Other);
+    }
+
     #[test]
     fn test_image_section_header() {
         let bytes: Vec<u8> = vec![
@@ -0,0 +1,376 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Translation of absolute addresses in a process, such as those found in minidumps, to [`Rva`]s
+//! of the loaded images.
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use uuid::Uuid;
+
+use crate::common::*;
+use crate::pdbi::PdbIdentity;
+use crate::pe::ImageSectionHeader;
+
+/// The signature of a PDB 7.0 CodeView record.
+const RSDS_SIGNATURE: &[u8; 4] = b"RSDS";
+/// The signature of the PE header.
+const PE_SIGNATURE: &[u8; 4] = b"PE\0\0";
+/// The position of the PE header offset in the DOS header.
+const PE_OFFSET_POSITION: usize = 0x3c;
+/// The magic of a 32-bit optional header.
+const PE32_MAGIC: u16 = 0x10b;
+/// The magic of a 64-bit optional header.
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+/// The index of the debug directory in the data directories of the optional header.
+const DEBUG_DIRECTORY: u32 = 6;
+/// The size of an `IMAGE_DEBUG_DIRECTORY` entry.
+const DEBUG_DIRECTORY_ENTRY_SIZE: usize = 28;
+/// The debug directory type of CodeView records.
+const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+
+impl PdbIdentity {
+    /// Parses the identity from a PDB 7.0 CodeView record, which starts with the `RSDS` signature.
+    ///
+    /// The path of the PDB following the identity is ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidCodeViewRecord` if the record does not start with the `RSDS` signature
+    /// * `Error::UnexpectedEof` if the record is truncated
+    pub fn from_codeview(record: &[u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(record);
+        if buf.take(RSDS_SIGNATURE.len())? != RSDS_SIGNATURE {
+            return Err(Error::InvalidCodeViewRecord);
+        }
+
+        let guid = Uuid::from_fields(
+            buf.parse_u32()?,
+            buf.parse_u16()?,
+            buf.parse_u16()?,
+            buf.take(8)?.try_into().unwrap(),
+        );
+        let age = buf.parse_u32()?;
+
+        Ok(Self { guid, age })
+    }
+
+    /// Parses the identity from the CodeView record in the debug directory of a PE image.
+    ///
+    /// `image` is the contents of an executable or DLL file as stored on disk. Returns `None` if
+    /// the image does not have a debug directory with a PDB 7.0 CodeView record.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidImage` if the data is not a PE image, or its headers point outside of the
+    ///   address space
+    /// * `Error::UnexpectedEof` if the headers or the debug directory are truncated
+    pub fn from_image(image: &[u8]) -> Result<Option<Self>> {
+        let mut buf = ParseBuffer::from(image);
+        if buf.take(2)? != b"MZ" {
+            return Err(Error::InvalidImage);
+        }
+
+        buf.seek(PE_OFFSET_POSITION);
+        let pe_offset = buf.parse_u32()? as usize;
+        buf.seek(pe_offset);
+        if buf.take(4)? != PE_SIGNATURE {
+            return Err(Error::InvalidImage);
+        }
+
+        // COFF file header
+        let _machine = buf.parse_u16()?;
+        let section_count = buf.parse_u16()?;
+        buf.take(12)?;
+        let optional_header_size = buf.parse_u16()? as usize;
+        let _characteristics = buf.parse_u16()?;
+
+        let mut optional = ParseBuffer::from(buf.take(optional_header_size)?);
+        let directories = match optional.parse_u16()? {
+            PE32_MAGIC => 96,
+            PE32_PLUS_MAGIC => 112,
+            _ => return Err(Error::InvalidImage),
+        };
+
+        optional.seek(directories - 4);
+        if optional.parse_u32()? <= DEBUG_DIRECTORY {
+            return Ok(None);
+        }
+
+        optional.seek(directories + 8 * DEBUG_DIRECTORY as usize);
+        let debug_rva = optional.parse_u32()?;
+        let debug_size = optional.parse_u32()?;
+
+        let mut sections = Vec::with_capacity(section_count.into());
+        for _ in 0..section_count {
+            sections.push(ImageSectionHeader::parse(&mut buf)?);
+        }
+
+        // The debug directory is stored in a section, so its RVA has to be mapped to a file offset.
+        let debug_offset = match sections.iter().find(|section| {
+            debug_rva >= section.virtual_address
+                && debug_rva - section.virtual_address < section.size_of_raw_data
+        }) {
+            Some(section) => (debug_rva - section.virtual_address)
+                .checked_add(section.pointer_to_raw_data)
+                .ok_or(Error::InvalidImage)?,
+            None => return Ok(None),
+        };
+
+        buf.seek(debug_offset as usize);
+        let mut entries = ParseBuffer::from(buf.take(debug_size as usize)?);
+        while entries.len() >= DEBUG_DIRECTORY_ENTRY_SIZE {
+            let mut entry = ParseBuffer::from(entries.take(DEBUG_DIRECTORY_ENTRY_SIZE)?);
+            entry.take(12)?;
+            let kind = entry.parse_u32()?;
+            let size = entry.parse_u32()? as usize;
+            let _address = entry.parse_u32()?;
+            let offset = entry.parse_u32()? as usize;
+
+            if kind != IMAGE_DEBUG_TYPE_CODEVIEW {
+                continue;
+            }
+
+            let record = offset
+                .checked_add(size)
+                .and_then(|end| image.get(offset..end))
+                .ok_or(Error::UnexpectedEof)?;
+
+            match Self::from_codeview(record) {
+                Ok(identity) => return Ok(Some(identity)),
+                Err(Error::InvalidCodeViewRecord) => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// An image loaded into the address space of a process.
+///
+/// `T` is arbitrary data associated with the image, such as the PDB to symbolicate it with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadedImage<T> {
+    /// The virtual address the image was loaded at.
+    pub base: u64,
+    /// The size of the image in memory in bytes.
+    pub size: u32,
+    /// The identity of the image's PDB, if known.
+    pub identity: Option<PdbIdentity>,
+    /// Data associated with the image.
+    pub data: T,
+}
+
+impl<T> LoadedImage<T> {
+    /// Creates a loaded image without identity.
+    pub fn new(base: u64, size: u32, data: T) -> Self {
+        Self {
+            base,
+            size,
+            identity: None,
+            data,
+        }
+    }
+
+    /// Translates an absolute virtual address to an [`Rva`] of this image.
+    ///
+    /// Returns `None` if the address is outside of the image.
+    pub fn rva(&self, address: u64) -> Option<Rva> {
+        let offset = address.checked_sub(self.base)?;
+        if offset < u64::from(self.size) {
+            Some(Rva(offset as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Translates an [`Rva`] of this image to an absolute virtual address.
+    pub fn address(&self, rva: Rva) -> u64 {
+        self.base.wrapping_add(u64::from(rva.0))
+    }
+}
+
+/// The images loaded into the address space of a process, such as the module list of a minidump.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::{ImageMap, LoadedImage, Rva};
+/// let mut images = ImageMap::new();
+/// images.insert(LoadedImage::new(0x7ff6_0000_0000, 0x10000, "foo.exe"));
+/// images.insert(LoadedImage::new(0x7ffc_0000_0000, 0x20000, "bar.dll"));
+///
+/// let (image, rva) = images.lookup(0x7ffc_0000_1234).expect("address in bar.dll");
+/// assert_eq!(image.data, "bar.dll");
+/// assert_eq!(rva, Rva(0x1234));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ImageMap<T> {
+    images: Vec<LoadedImage<T>>,
+}
+
+// Implemented manually, since deriving would require `T: Default`.
+impl<T> Default for ImageMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ImageMap<T> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self { images: Vec::new() }
+    }
+
+    /// Adds an image to the map.
+    ///
+    /// If images overlap, lookups resolve to the image with the highest base address below the
+    /// looked up address.
+    pub fn insert(&mut self, image: LoadedImage<T>) {
+        let index = self.images.partition_point(|i| i.base <= image.base);
+        self.images.insert(index, image);
+    }
+
+    /// Returns the number of images in the map.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns `true` if the map contains no images.
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Returns an iterator over the images in ascending order of base address.
+    pub fn iter(&self) -> core::slice::Iter<'_, LoadedImage<T>> {
+        self.images.iter()
+    }
+
+    /// Finds the image containing an absolute virtual address and translates the address to an
+    /// [`Rva`] of that image.
+    ///
+    /// Returns `None` if no image contains the address.
+    pub fn lookup(&self, address: u64) -> Option<(&LoadedImage<T>, Rva)> {
+        let index = self.images.partition_point(|i| i.base <= address);
+        let image = self.images[..index].last()?;
+        Some((image, image.rva(address)?))
+    }
+}
+
+impl<T> core::iter::FromIterator<LoadedImage<T>> for ImageMap<T> {
+    fn from_iter<I: IntoIterator<Item = LoadedImage<T>>>(iter: I) -> Self {
+        let mut images: Vec<_> = iter.into_iter().collect();
+        images.sort_by_key(|image| image.base);
+        Self { images }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_image_overflow() {
+        let mut image = vec![0; 0x40];
+        image[..2].copy_from_slice(b"MZ");
+        image[PE_OFFSET_POSITION..PE_OFFSET_POSITION + 4].copy_from_slice(&0x40u32.to_le_bytes());
+
+        // COFF file header with one section and an optional header with seven data directories
+        image.extend_from_slice(PE_SIGNATURE);
+        image.extend_from_slice(&[0, 0, 1, 0]);
+        image.extend_from_slice(&[0; 12]);
+        image.extend_from_slice(&(96u16 + 7 * 8).to_le_bytes());
+        image.extend_from_slice(&[0; 2]);
+
+        let mut optional = vec![0; 96 + 7 * 8];
+        optional[..2].copy_from_slice(&PE32_MAGIC.to_le_bytes());
+        optional[92..96].copy_from_slice(&7u32.to_le_bytes());
+        optional[96 + 48..96 + 52].copy_from_slice(&0x1010u32.to_le_bytes());
+        optional[96 + 52..96 + 56].copy_from_slice(&28u32.to_le_bytes());
+        image.extend_from_slice(&optional);
+
+        // The section maps the debug directory past the end of the address space.
+        let mut section = vec![0; 40];
+        section[12..16].copy_from_slice(&0x1000u32.to_le_bytes());
+        section[16..20].copy_from_slice(&0x100u32.to_le_bytes());
+        section[20..24].copy_from_slice(&0xffff_fff8u32.to_le_bytes());
+        image.extend_from_slice(&section);
+
+        assert!(matches!(
+            PdbIdentity::from_image(&image),
+            Err(Error::InvalidImage)
+        ));
+    }
+
+    #[test]
+    fn test_from_codeview() {
+        let record = &[
+            0x52, 0x53, 0x44, 0x53, // RSDS
+            0xa5, 0x3f, 0x3c, 0x2b, 0x2e, 0x5a, 0xb8, 0x44, // guid
+            0x8b, 0xba, 0xc3, 0x30, 0x0f, 0xf6, 0x9f, 0x62, // guid
+            0x02, 0x00, 0x00, 0x00, // age
+            0x66, 0x6f, 0x6f, 0x2e, 0x70, 0x64, 0x62, 0x00, // foo.pdb
+        ];
+
+        let identity = PdbIdentity::from_codeview(record).expect("parse");
+        assert_eq!(
+            identity,
+            PdbIdentity {
+                guid: Uuid::from_u128(0x2b3c3fa5_5a2e_44b8_8bba_c3300ff69f62),
+                age: 2,
+            }
+        );
+
+        assert!(matches!(
+            PdbIdentity::from_codeview(b"NB10\0\0\0\0"),
+            Err(Error::InvalidCodeViewRecord)
+        ));
+        assert!(matches!(
+            PdbIdentity::from_codeview(&record[..20]),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let images: ImageMap<_> = vec![
+            LoadedImage::new(0x20000, 0x1000, 2),
+            LoadedImage::new(0x10000, 0x2000, 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(images.lookup(0xffff), None);
+        assert_eq!(
+            images.lookup(0x10000).map(|(i, r)| (i.data, r)),
+            Some((1, Rva(0)))
+        );
+        assert_eq!(
+            images.lookup(0x11fff).map(|(i, r)| (i.data, r)),
+            Some((1, Rva(0x1fff)))
+        );
+        assert_eq!(images.lookup(0x12000), None);
+        assert_eq!(
+            images.lookup(0x20010).map(|(i, r)| (i.data, r)),
+            Some((2, Rva(0x10)))
+        );
+        assert_eq!(images.lookup(0x21000), None);
+
+        let image = &images.iter().next().expect("image");
+        assert_eq!(image.address(Rva(0x10)), 0x10010);
+    }
+
+    #[test]
+    fn test_default_without_default_data() {
+        struct Data;
+
+        let images = ImageMap::<Data>::default();
+        assert!(images.is_empty());
+    }
+}
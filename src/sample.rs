@@ -0,0 +1,336 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Random sampling of records for corpus triage and fuzzing.
+
+use alloc::vec::Vec;
+use core::result;
+
+use crate::common::*;
+use crate::pdb::PDB;
+use crate::source::Source;
+use crate::symbol::{Symbol, SymbolData};
+use crate::tpi::{Id, IdData, Type, TypeData};
+use crate::FallibleIterator;
+
+/// A uniform random sample of fixed size over a sequence of unknown length.
+///
+/// Items are offered one at a time with [`push`](Self::push). The reservoir keeps at most
+/// `capacity` of them, such that every item offered so far is retained with equal probability.
+/// Memory is proportional to the capacity, not to the number of items offered.
+///
+/// Sampling is deterministic: the same seed and the same sequence of items always produce the same
+/// sample.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// #
+/// # fn test() -> pdb::Result<()> {
+/// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// # let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+///
+/// let mut reservoir = pdb::Reservoir::new(10, 42);
+/// reservoir.fill(type_information.iter())?;
+/// for typ in reservoir.samples() {
+///     println!("{} {:?}", typ.index(), typ.parse());
+/// }
+/// # assert_eq!(reservoir.samples().len(), 10);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    samples: Vec<T>,
+    state: u64,
+}
+
+impl<T> Reservoir<T> {
+    /// Creates an empty reservoir retaining up to `capacity` items, seeded with `seed`.
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            samples: Vec::new(),
+            state: seed,
+        }
+    }
+
+    /// Returns a pseudo-random number using SplitMix64.
+    fn next_random(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Offers an item to the reservoir.
+    pub fn push(&mut self, item: T) {
+        self.seen += 1;
+        if self.samples.len() < self.capacity {
+            self.samples.push(item);
+            return;
+        }
+
+        // Replace a retained item with probability `capacity / seen`, reducing the random number
+        // to `0..seen` without modulo bias.
+        let slot = ((u128::from(self.next_random()) * u128::from(self.seen)) >> 64) as usize;
+        if let Some(sample) = self.samples.get_mut(slot) {
+            *sample = item;
+        }
+    }
+
+    /// Offers all items of an iterator to the reservoir.
+    pub fn fill<I>(&mut self, mut iter: I) -> result::Result<(), I::Error>
+    where
+        I: FallibleIterator<Item = T>,
+    {
+        while let Some(item) = iter.next()? {
+            self.push(item);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of items offered so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Returns the retained items in no particular order.
+    pub fn samples(&self) -> &[T] {
+        &self.samples
+    }
+
+    /// Returns the retained items in no particular order, consuming the reservoir.
+    pub fn into_samples(self) -> Vec<T> {
+        self.samples
+    }
+}
+
+/// A record sampled by [`PDB::sample_records`], along with its decoded contents.
+///
+/// The raw record is kept alongside the decoded data, so that records can be written out as fuzz
+/// seeds or compared against the decoded result.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum SampledRecord<'a> {
+    /// A symbol in the global symbol table.
+    GlobalSymbol {
+        /// The raw symbol.
+        symbol: Symbol<'a>,
+        /// The result of parsing the symbol.
+        data: Result<SymbolData<'a>>,
+    },
+    /// A symbol in the symbol stream of a module.
+    ModuleSymbol {
+        /// Index of the module in [`DebugInformation::modules`](crate::DebugInformation::modules).
+        module: usize,
+        /// The raw symbol.
+        symbol: Symbol<'a>,
+        /// The result of parsing the symbol.
+        data: Result<SymbolData<'a>>,
+    },
+    /// A record of the type stream.
+    Type {
+        /// The raw type.
+        item: Type<'a>,
+        /// The result of parsing the type.
+        data: Result<TypeData<'a>>,
+    },
+    /// A record of the id stream.
+    Id {
+        /// The raw id.
+        item: Id<'a>,
+        /// The result of parsing the id.
+        data: Result<IdData<'a>>,
+    },
+}
+
+/// Derives the seed of the reservoir for one stream, so that streams are sampled independently.
+fn stream_seed(seed: u64, stream: u64) -> u64 {
+    seed ^ stream.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+    /// Passes up to `count` random records of each major stream to `visitor`, fully decoded.
+    ///
+    /// Records are sampled independently from the global symbol table, the symbols of all modules
+    /// combined, the type stream and the id stream, using a [`Reservoir`] seeded with `seed`. The
+    /// streams are iterated once without collecting their records, so memory is proportional to
+    /// `count`. The same seed always selects the same records of a PDB.
+    ///
+    /// Samples are reported stream by stream in the order listed above, and in stream order within
+    /// each stream. Records that fail to decode are reported with their error, which makes this
+    /// suitable for finding unsupported or malformed records in a corpus. The id stream is skipped
+    /// if the PDB does not contain it.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * Errors reading the streams, for instance if the PDB is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// pdb.sample_records(5, 0, |record| {
+    ///     if let pdb::SampledRecord::Type { item, data: Err(error) } = record {
+    ///         println!("type {} does not parse: {}", item.index(), error);
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn sample_records<F>(&self, count: usize, seed: u64, mut visitor: F) -> Result<()>
+    where
+        F: FnMut(SampledRecord<'_>),
+    {
+        let symbols = self.global_symbols()?;
+        let mut reservoir = Reservoir::new(count, stream_seed(seed, 0));
+        reservoir.fill(symbols.iter())?;
+        let mut samples = reservoir.into_samples();
+        samples.sort_by_key(|symbol| symbol.index());
+        for symbol in samples {
+            let data = symbol.parse();
+            visitor(SampledRecord::GlobalSymbol { symbol, data });
+        }
+
+        self.sample_module_symbols(count, stream_seed(seed, 1), &mut visitor)?;
+
+        let types = self.type_information()?;
+        let mut reservoir = Reservoir::new(count, stream_seed(seed, 2));
+        reservoir.fill(types.iter())?;
+        let mut samples = reservoir.into_samples();
+        samples.sort_by_key(|item| item.index());
+        for item in samples {
+            let data = item.parse();
+            visitor(SampledRecord::Type { item, data });
+        }
+
+        let ids = match self.id_information() {
+            Ok(ids) => ids,
+            Err(Error::StreamNotFound(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let mut reservoir = Reservoir::new(count, stream_seed(seed, 3));
+        reservoir.fill(ids.iter())?;
+        let mut samples = reservoir.into_samples();
+        samples.sort_by_key(|item| item.index());
+        for item in samples {
+            let data = item.parse();
+            visitor(SampledRecord::Id { item, data });
+        }
+
+        Ok(())
+    }
+
+    /// Samples the symbols of all modules.
+    ///
+    /// Symbols borrow from their module, so the first pass samples their positions and the second
+    /// pass loads the sampled symbols module by module.
+    fn sample_module_symbols<F>(&self, count: usize, seed: u64, visitor: &mut F) -> Result<()>
+    where
+        F: FnMut(SampledRecord<'_>),
+    {
+        let dbi = self.debug_information()?;
+        let mut reservoir = Reservoir::new(count, seed);
+        let mut modules = dbi.modules()?;
+        let mut index = 0;
+        while let Some(module) = modules.next()? {
+            if let Some(info) = self.module_info(&module)? {
+                let mut symbols = info.symbols()?;
+                while let Some(symbol) = symbols.next()? {
+                    reservoir.push((index, symbol.index()));
+                }
+            }
+            index += 1;
+        }
+
+        let mut samples = reservoir.into_samples();
+        samples.sort_unstable();
+
+        let mut samples = samples.into_iter().peekable();
+        let mut modules = dbi.modules()?.enumerate();
+        while let Some(&(module_index, _)) = samples.peek() {
+            let module = match modules.find(|&(index, _)| Ok(index == module_index))? {
+                Some((_, module)) => module,
+                None => break,
+            };
+
+            let info = match self.module_info(&module)? {
+                Some(info) => info,
+                None => continue,
+            };
+
+            while let Some((_, index)) = samples.next_if(|&(module, _)| module == module_index) {
+                let symbol = info.symbol_at(index)?;
+                let data = symbol.parse();
+                visitor(SampledRecord::ModuleSymbol {
+                    module: module_index,
+                    symbol,
+                    data,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservoir() {
+        let mut reservoir = Reservoir::new(4, 7);
+        for item in 0..3 {
+            reservoir.push(item);
+        }
+        assert_eq!(reservoir.samples(), &[0, 1, 2]);
+
+        for item in 3..1000 {
+            reservoir.push(item);
+        }
+        assert_eq!(reservoir.seen(), 1000);
+        assert_eq!(reservoir.samples().len(), 4);
+
+        let mut again = Reservoir::new(4, 7);
+        for item in 0..1000 {
+            again.push(item);
+        }
+        assert_eq!(reservoir.samples(), again.samples());
+    }
+
+    #[test]
+    fn test_reservoir_uniform() {
+        // Every item should be retained in roughly a tenth of the samples.
+        let mut counts = [0u32; 10];
+        for seed in 0..2000 {
+            let mut reservoir = Reservoir::new(1, seed);
+            for item in 0..10 {
+                reservoir.push(item);
+            }
+            counts[reservoir.samples()[0]] += 1;
+        }
+
+        for &count in &counts {
+            assert!((140..260).contains(&count), "{:?}", counts);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::common::*;
+
+/// Limits for a search over a symbol table or type stream.
+///
+/// Searches stop as soon as any of the limits is reached, and return the results found so far
+/// together with a [`SearchContinuation`] to resume the search later. Apart from a limit of zero
+/// results, which completes the search immediately, every search scans at least one record so that
+/// resuming always makes progress. The default budget is unlimited, and limits are added with the
+/// `with_*` methods.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::{Duration, Instant};
+/// let budget = pdb::SearchBudget::default()
+///     .with_max_results(50)
+///     .with_deadline(Instant::now() + Duration::from_millis(16));
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchBudget {
+    /// The maximum number of results to return.
+    pub max_results: Option<usize>,
+    /// The maximum number of bytes of record data to scan.
+    pub max_bytes: Option<usize>,
+    /// The point in time after which the search stops.
+    ///
+    /// The deadline is checked periodically rather than before every record, so searches may
+    /// slightly overrun it. This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub deadline: Option<Instant>,
+}
+
+impl SearchBudget {
+    /// Limits the search to `max_results` results.
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Limits the search to `max_bytes` bytes of record data.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Stops the search after `deadline`.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns whether a search with this budget can return any results.
+    pub(crate) fn permits_results(&self) -> bool {
+        self.max_results != Some(0)
+    }
+}
+
+/// A position at which an interrupted search can be resumed.
+///
+/// Continuations are only valid for the table or stream that produced them. Resuming a search in a
+/// different table or stream fails with `Error::IndexMismatch`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SearchContinuation {
+    stream: u64,
+    pub(crate) offset: usize,
+    pub(crate) index: u32,
+}
+
+impl SearchContinuation {
+    /// The number of leading bytes of a stream that contribute to its identity.
+    const IDENTITY_PREFIX: usize = 64;
+
+    /// Creates a continuation at `offset` into `stream`.
+    pub(crate) fn new(stream: &[u8], offset: usize, index: u32) -> Self {
+        Self {
+            stream: stream_identity(stream),
+            offset,
+            index,
+        }
+    }
+
+    /// Checks that this continuation was created for `stream`.
+    pub(crate) fn validate(&self, stream: &[u8]) -> Result<()> {
+        if self.stream != stream_identity(stream) || self.offset > stream.len() {
+            return Err(Error::IndexMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes an FNV-1a hash over the length and the leading bytes of a stream.
+///
+/// This is cheap enough to run on every search, and tells apart the symbol tables and type streams
+/// of a PDB as well as those of different PDBs.
+fn stream_identity(stream: &[u8]) -> u64 {
+    let prefix = &stream[..stream.len().min(SearchContinuation::IDENTITY_PREFIX)];
+    let length = (stream.len() as u64).to_le_bytes();
+
+    length
+        .iter()
+        .chain(prefix)
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// The results of a budgeted search.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchResults<T> {
+    /// Records matching the search, in the order they occur in the stream.
+    pub items: Vec<T>,
+    /// Position to resume the search if the budget was exhausted before reaching the end.
+    ///
+    /// This is `None` if the entire remainder of the stream was searched.
+    pub continuation: Option<SearchContinuation>,
+}
+
+impl<T> SearchResults<T> {
+    /// Returns whether the search covered the entire stream.
+    pub fn is_complete(&self) -> bool {
+        self.continuation.is_none()
+    }
+}
+
+/// Tracks consumption of a [`SearchBudget`] while scanning records.
+pub(crate) struct BudgetTracker {
+    budget: SearchBudget,
+    start: usize,
+    records: usize,
+}
+
+impl BudgetTracker {
+    /// The number of records between two checks of the deadline.
+    #[cfg(feature = "std")]
+    const DEADLINE_INTERVAL: usize = 64;
+
+    /// Starts tracking a search that begins at byte offset `start`.
+    pub fn new(budget: SearchBudget, start: usize) -> Self {
+        Self {
+            budget,
+            start,
+            records: 0,
+        }
+    }
+
+    /// Returns whether the search must stop before the record at byte offset `position`.
+    pub fn exhausted(&mut self, position: usize, results: usize) -> bool {
+        if self.budget.max_results.is_some_and(|max| results >= max) {
+            return true;
+        }
+
+        // Always scan the first record, so that resumed searches make progress.
+        if position > self.start
+            && self
+                .budget
+                .max_bytes
+                .is_some_and(|max| position - self.start >= max)
+        {
+            return true;
+        }
+
+        self.records += 1;
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.budget.deadline {
+            if self.records % Self::DEADLINE_INTERVAL == 0 && Instant::now() >= deadline {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_unlimited() {
+        let mut tracker = BudgetTracker::new(SearchBudget::default(), 0);
+        assert!(!tracker.exhausted(usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = SearchBudget::default()
+            .with_max_results(2)
+            .with_max_bytes(100);
+
+        let mut tracker = BudgetTracker::new(budget, 50);
+        assert!(!tracker.exhausted(50, 0));
+        assert!(
+            !BudgetTracker::new(SearchBudget::default().with_max_bytes(0), 50).exhausted(50, 0)
+        );
+        assert!(!tracker.exhausted(149, 1));
+        assert!(tracker.exhausted(150, 1));
+        assert!(tracker.exhausted(60, 2));
+    }
+
+    #[test]
+    fn test_continuation_identity() {
+        let stream = [1u8; 100];
+        let continuation = SearchContinuation::new(&stream, 40, 0);
+        assert!(continuation.validate(&stream).is_ok());
+        assert!(matches!(
+            continuation.validate(&stream[..80]),
+            Err(Error::IndexMismatch)
+        ));
+        assert!(matches!(
+            continuation.validate(&[2u8; 100]),
+            Err(Error::IndexMismatch)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_budget_deadline() {
+        let budget = SearchBudget::default().with_deadline(Instant::now());
+
+        let mut tracker = BudgetTracker::new(budget, 0);
+        let checks = (0..BudgetTracker::DEADLINE_INTERVAL)
+            .take_while(|_| !tracker.exhausted(0, 0))
+            .count();
+        assert_eq!(checks, BudgetTracker::DEADLINE_INTERVAL - 1);
+    }
+}
@@ -5,9 +5,68 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::fmt;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::io;
 
+use crate::sync::MaybeSendSync;
+
+/// The error type of [`Source`] and [`Sink`] operations.
+///
+/// With the `std` feature, this is [`std::io::Error`].
+#[cfg(feature = "std")]
+pub type SourceError = io::Error;
+
+/// The error type of [`Source`] and [`Sink`] operations.
+///
+/// With the `std` feature, this is `std::io::Error`.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SourceError {
+    /// The requested data extends beyond the end of the source.
+    UnexpectedEof,
+    /// The source failed for another reason.
+    Other(&'static str),
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Creates the error returned when reading past the end of a source.
+#[cfg(feature = "std")]
+fn unexpected_eof() -> SourceError {
+    io::ErrorKind::UnexpectedEof.into()
+}
+
+/// Creates the error returned when reading past the end of a source.
+#[cfg(not(feature = "std"))]
+fn unexpected_eof() -> SourceError {
+    SourceError::UnexpectedEof
+}
+
+/// Returns whether a source failed because the requested data extends beyond its end.
+#[cfg(feature = "std")]
+pub(crate) fn is_unexpected_eof(error: &SourceError) -> bool {
+    error.kind() == io::ErrorKind::UnexpectedEof
+}
+
+/// Returns whether a source failed because the requested data extends beyond its end.
+#[cfg(not(feature = "std"))]
+pub(crate) fn is_unexpected_eof(error: &SourceError) -> bool {
+    *error == SourceError::UnexpectedEof
+}
+
 /// Represents an offset + size of the source file.
 ///
 /// The multi-stream file implementation (used by `pdb::PDB`) determines which byte ranges it needs
@@ -35,7 +94,10 @@ pub struct SourceSlice {
 /// There is a default `Source` implementation for `std::io::Read` + `std::io::Seek` +
 /// `std::fmt::Debug`, allowing a `std::fs::File` to be treated as `pdb::Source`. This
 /// implementation provides views by allocating a buffer, seeking, and reading the contents into
-/// that buffer.
+/// that buffer. It requires the `std` feature.
+///
+/// PDB files that are already in memory can be read with [`MemorySource`], which is also
+/// available without the `std` feature.
 ///
 /// # Alignment
 ///
@@ -51,11 +113,14 @@ pub trait Source<'s>: fmt::Debug {
     ///
     /// Note that the SourceView's as_slice() method cannot fail, so `view()` is the time to raise
     /// IO errors.
-    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s>>, io::Error>;
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s>>, SourceError>;
 }
 
 /// An owned, droppable, read-only view of the source file which can be referenced as a byte slice.
-pub trait SourceView<'s>: fmt::Debug {
+///
+/// With the `sync` feature, views are shared between threads together with the objects parsed from
+/// them, and must therefore be `Send` and `Sync`.
+pub trait SourceView<'s>: fmt::Debug + MaybeSendSync {
     /// Returns a view to the raw data.
     fn as_slice(&self) -> &[u8];
 }
@@ -77,6 +142,7 @@ impl SourceView<'_> for ReadView {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'s, T> Source<'s> for T
 where
     T: io::Read + io::Seek + fmt::Debug + 's,
@@ -103,6 +169,88 @@ where
     }
 }
 
+/// A [`Source`] for a PDB file that is entirely in memory.
+///
+/// Unlike `std::io::Cursor`, this does not require the `std` feature. Views copy the requested
+/// ranges into a new buffer.
+#[derive(Clone, Copy)]
+pub struct MemorySource<'s> {
+    data: &'s [u8],
+}
+
+impl<'s> MemorySource<'s> {
+    /// Creates a source reading from the given bytes.
+    pub fn new(data: &'s [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl fmt::Debug for MemorySource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MemorySource({} bytes)", self.data.len())
+    }
+}
+
+impl<'s> Source<'s> for MemorySource<'s> {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s>>, SourceError> {
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+
+        for slice in slices {
+            let data = usize::try_from(slice.offset)
+                .ok()
+                .and_then(|start| self.data.get(start..)?.get(..slice.size))
+                .ok_or_else(unexpected_eof)?;
+            bytes.extend_from_slice(data);
+        }
+
+        Ok(Box::new(ReadView { bytes }))
+    }
+}
+
+/// The write-side counterpart of [`Source`].
+///
+/// Writers in this crate never assume `std::fs`; they emit PDB data through a `Sink`, which only
+/// needs to support writing a byte slice at a given absolute offset. This allows PDBs to be
+/// written to files, memory buffers, or any other storage that supports positioned writes.
+///
+/// # Default
+///
+/// There is a default `Sink` implementation for `std::io::Write` + `std::io::Seek` +
+/// `std::fmt::Debug`. This covers `std::fs::File` as well as `std::io::Cursor<Vec<u8>>`, which is
+/// the simplest way to write a PDB into memory. It requires the `std` feature.
+///
+/// # Alignment
+///
+/// Writes are not required to be aligned or ordered. Writing past the current end of the sink must
+/// extend it; any gap left between the previous end and the written data must read back as zeroes.
+pub trait Sink: fmt::Debug {
+    /// Writes all of `data` at the absolute position `offset`.
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), SourceError>;
+
+    /// Flushes any buffered data to the underlying storage.
+    ///
+    /// The default implementation does nothing.
+    fn flush(&mut self) -> Result<(), SourceError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Sink for T
+where
+    T: io::Write + io::Seek + fmt::Debug,
+{
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), io::Error> {
+        self.seek(io::SeekFrom::Start(offset))?;
+        self.write_all(data)
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        io::Write::flush(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod read_view {
@@ -199,4 +347,89 @@ mod tests {
             }
         }
     }
+
+    mod memory_source {
+        use crate::source::*;
+
+        #[test]
+        fn test_discontinuous_reading() {
+            let mut data = vec![0; 4096];
+            data[42] = 42;
+            data[88] = 88;
+
+            let mut source = MemorySource::new(&data);
+
+            let source_slices = vec![
+                SourceSlice {
+                    offset: 88,
+                    size: 1,
+                },
+                SourceSlice {
+                    offset: 40,
+                    size: 4,
+                },
+            ];
+            let view = source
+                .view(source_slices.as_slice())
+                .expect("viewing must succeed");
+            assert_eq!(&[88u8, 0, 0, 42, 0], view.as_slice());
+        }
+
+        #[test]
+        fn test_eof_reading() {
+            let data = vec![0; 4096];
+
+            let mut source = MemorySource::new(&data);
+
+            // one byte is readable, but we asked for two
+            let source_slices = vec![SourceSlice {
+                offset: 4095,
+                size: 2,
+            }];
+            let r = source.view(source_slices.as_slice());
+            match r {
+                Ok(_) => panic!("should have failed"),
+                Err(e) => assert!(is_unexpected_eof(&e)),
+            }
+        }
+    }
+
+    mod sink {
+        use crate::source::*;
+        use std::io::Cursor;
+
+        #[test]
+        fn test_basic_writing() {
+            let mut sink = Cursor::new(Vec::new());
+            sink.write_at(0, &[1, 2, 3, 4])
+                .expect("writing must succeed");
+            assert_eq!(&[1u8, 2, 3, 4], sink.get_ref().as_slice());
+        }
+
+        #[test]
+        fn test_discontinuous_writing() {
+            let mut sink = Cursor::new(Vec::new());
+            sink.write_at(4, &[5, 6]).expect("writing must succeed");
+            sink.write_at(1, &[2]).expect("writing must succeed");
+            assert_eq!(&[0u8, 2, 0, 0, 5, 6], sink.get_ref().as_slice());
+        }
+
+        #[test]
+        fn test_overwriting() {
+            let mut sink = Cursor::new(vec![0; 4]);
+            sink.write_at(2, &[3, 4, 5]).expect("writing must succeed");
+            assert_eq!(&[0u8, 0, 3, 4, 5], sink.get_ref().as_slice());
+        }
+
+        #[test]
+        fn test_dyn_sink() {
+            let mut buffer = Cursor::new(Vec::new());
+            {
+                let sink: &mut dyn Sink = &mut buffer;
+                sink.write_at(0, b"MSF").expect("writing must succeed");
+                sink.flush().expect("flushing must succeed");
+            }
+            assert_eq!(b"MSF", buffer.get_ref().as_slice());
+        }
+    }
 }
@@ -1,9 +1,10 @@
-use std::borrow::Cow;
+use alloc::borrow::Cow;
 
 use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
 use crate::msf::Stream;
+use crate::FallibleIterator;
 
 /// Magic bytes identifying the string name table.
 ///
@@ -64,7 +65,7 @@ impl<'t> TryFromCtx<'t, Endian> for StringTableHeader {
 impl StringTableHeader {
     /// Start index of the names buffer in the string table stream.
     fn names_start(self) -> usize {
-        std::mem::size_of::<Self>()
+        core::mem::size_of::<Self>()
     }
 
     /// End index of the names buffer in the string table stream.
@@ -136,6 +137,38 @@ impl<'s> StringTable<'s> {
         let data = &self.stream.as_slice()[string_offset..self.header.names_end()];
         ParseBuffer::from(data).parse_cstring()
     }
+
+    /// Returns an iterator over all strings in this table and their offsets, in the order they
+    /// are stored.
+    pub fn iter(&self) -> StringTableIter<'_> {
+        let names = &self.stream.as_slice()[self.header.names_start()..self.header.names_end()];
+        StringTableIter {
+            buf: ParseBuffer::from(names),
+        }
+    }
+}
+
+/// An iterator over the strings of a [`StringTable`].
+///
+/// Created by [`StringTable::iter`].
+#[derive(Debug)]
+pub struct StringTableIter<'t> {
+    buf: ParseBuffer<'t>,
+}
+
+impl<'t> FallibleIterator for StringTableIter<'t> {
+    type Item = (StringRef, RawString<'t>);
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let offset = StringRef(self.buf.pos() as u32);
+        let string = self.buf.parse_cstring()?;
+        Ok(Some((offset, string)))
+    }
 }
 
 impl StringRef {
@@ -160,7 +193,7 @@ impl StringRef {
 mod tests {
     use super::*;
 
-    use std::mem;
+    use core::mem;
 
     #[test]
     fn test_string_table_header() {
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::common::*;
 use crate::FallibleIterator;
 
@@ -117,15 +119,174 @@ impl BinaryAnnotation {
                 | BinaryAnnotation::ChangeCodeLengthAndCodeOffset(..)
         )
     }
+
+    /// Returns the opcode this annotation is encoded with.
+    pub fn opcode(self) -> u32 {
+        let opcode = match self {
+            Self::CodeOffset(_) => BinaryAnnotationOpcode::CodeOffset,
+            Self::ChangeCodeOffsetBase(_) => BinaryAnnotationOpcode::ChangeCodeOffsetBase,
+            Self::ChangeCodeOffset(_) => BinaryAnnotationOpcode::ChangeCodeOffset,
+            Self::ChangeCodeLength(_) => BinaryAnnotationOpcode::ChangeCodeLength,
+            Self::ChangeFile(_) => BinaryAnnotationOpcode::ChangeFile,
+            Self::ChangeLineOffset(_) => BinaryAnnotationOpcode::ChangeLineOffset,
+            Self::ChangeLineEndDelta(_) => BinaryAnnotationOpcode::ChangeLineEndDelta,
+            Self::ChangeRangeKind(_) => BinaryAnnotationOpcode::ChangeRangeKind,
+            Self::ChangeColumnStart(_) => BinaryAnnotationOpcode::ChangeColumnStart,
+            Self::ChangeColumnEndDelta(_) => BinaryAnnotationOpcode::ChangeColumnEndDelta,
+            Self::ChangeCodeOffsetAndLineOffset(..) => {
+                BinaryAnnotationOpcode::ChangeCodeOffsetAndLineOffset
+            }
+            Self::ChangeCodeLengthAndCodeOffset(..) => {
+                BinaryAnnotationOpcode::ChangeCodeLengthAndCodeOffset
+            }
+            Self::ChangeColumnEnd(_) => BinaryAnnotationOpcode::ChangeColumnEnd,
+        };
+
+        opcode as u32
+    }
 }
 
-/// An iterator over binary annotations used by `S_INLINESITE`.
+/// Resembles `DecodeSignedInt32`.
+fn decode_signed_operand(value: u32) -> i32 {
+    if value & 1 != 0 {
+        -((value >> 1) as i32)
+    } else {
+        (value >> 1) as i32
+    }
+}
+
+/// Resembles `EncodeSignedInt32`.
+///
+/// Returns an error if the encoded value cannot be compressed.
+fn encode_signed_operand(value: i32) -> Result<u32> {
+    let magnitude = value.unsigned_abs();
+    if magnitude > MAX_COMPRESSED >> 1 {
+        return Err(Error::InvalidCompressedAnnotation);
+    }
+
+    Ok(if value < 0 {
+        (magnitude << 1) | 1
+    } else {
+        magnitude << 1
+    })
+}
+
+/// The largest value that can be compressed with `CVCompressData`.
+const MAX_COMPRESSED: u32 = 0x1fff_ffff;
+
+/// Appends a compact version of an unsigned integer to `data`.
+///
+/// This implements `CVCompressData`, the inverse of
+/// [`RawBinaryAnnotationsIter::uncompress_next`].
+fn compress(data: &mut Vec<u8>, value: u32) -> Result<()> {
+    if value <= 0x7f {
+        data.push(value as u8);
+    } else if value <= 0x3fff {
+        data.extend_from_slice(&[(value >> 8) as u8 | 0x80, value as u8]);
+    } else if value <= MAX_COMPRESSED {
+        data.extend_from_slice(&[
+            (value >> 24) as u8 | 0xc0,
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ]);
+    } else {
+        return Err(Error::InvalidCompressedAnnotation);
+    }
+
+    Ok(())
+}
+
+/// A binary annotation as it is encoded, with its raw operands.
+///
+/// Returned by [`RawBinaryAnnotationsIter`]. Use [`decode`](Self::decode) to interpret the
+/// operands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RawBinaryAnnotation<'t> {
+    /// Offset of the annotation from the start of the binary annotations, in bytes.
+    pub offset: usize,
+    /// The encoded annotation, including its opcode.
+    pub bytes: &'t [u8],
+    opcode: u32,
+    operands: [u32; 2],
+    operand_count: usize,
+}
+
+impl<'t> RawBinaryAnnotation<'t> {
+    /// Returns the opcode of the annotation.
+    ///
+    /// This is always a valid opcode other than the end marker.
+    pub fn opcode(&self) -> u32 {
+        self.opcode
+    }
+
+    /// Returns the uncompressed operands of the annotation.
+    ///
+    /// Signed operands are returned in their encoded form, and the combined operand of
+    /// `ChangeCodeOffsetAndLineOffset` is not split.
+    pub fn operands(&self) -> &[u32] {
+        &self.operands[..self.operand_count]
+    }
+
+    /// Interprets the operands of this annotation.
+    pub fn decode(&self) -> BinaryAnnotation {
+        let [first, second] = self.operands;
+
+        // The opcode has been validated when parsing the annotation and cannot be modified.
+        match BinaryAnnotationOpcode::parse(self.opcode) {
+            Ok(BinaryAnnotationOpcode::CodeOffset) => BinaryAnnotation::CodeOffset(first),
+            Ok(BinaryAnnotationOpcode::ChangeCodeOffsetBase) => {
+                BinaryAnnotation::ChangeCodeOffsetBase(first)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeCodeOffset) => {
+                BinaryAnnotation::ChangeCodeOffset(first)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeCodeLength) => {
+                BinaryAnnotation::ChangeCodeLength(first)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeFile) => {
+                BinaryAnnotation::ChangeFile(FileIndex(first))
+            }
+            Ok(BinaryAnnotationOpcode::ChangeLineOffset) => {
+                BinaryAnnotation::ChangeLineOffset(decode_signed_operand(first))
+            }
+            Ok(BinaryAnnotationOpcode::ChangeLineEndDelta) => {
+                BinaryAnnotation::ChangeLineEndDelta(first)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeRangeKind) => BinaryAnnotation::ChangeRangeKind(first),
+            Ok(BinaryAnnotationOpcode::ChangeColumnStart) => {
+                BinaryAnnotation::ChangeColumnStart(first)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeColumnEndDelta) => {
+                BinaryAnnotation::ChangeColumnEndDelta(decode_signed_operand(first))
+            }
+            Ok(BinaryAnnotationOpcode::ChangeCodeOffsetAndLineOffset) => {
+                BinaryAnnotation::ChangeCodeOffsetAndLineOffset(
+                    first & 0xf,
+                    decode_signed_operand(first >> 4),
+                )
+            }
+            Ok(BinaryAnnotationOpcode::ChangeCodeLengthAndCodeOffset) => {
+                BinaryAnnotation::ChangeCodeLengthAndCodeOffset(first, second)
+            }
+            Ok(BinaryAnnotationOpcode::ChangeColumnEnd) => BinaryAnnotation::ChangeColumnEnd(first),
+            Ok(BinaryAnnotationOpcode::Eof) | Err(_) => {
+                unreachable!("raw annotations have valid opcodes")
+            }
+        }
+    }
+}
+
+/// An iterator over the raw encoding of binary annotations.
+///
+/// Created by [`BinaryAnnotations::raw_iter`].
 #[derive(Clone, Debug, Default)]
-pub struct BinaryAnnotationsIter<'t> {
+pub struct RawBinaryAnnotationsIter<'t> {
+    data: &'t [u8],
     buffer: ParseBuffer<'t>,
 }
 
-impl<'t> BinaryAnnotationsIter<'t> {
+impl<'t> RawBinaryAnnotationsIter<'t> {
     /// Parse a compact version of an unsigned integer.
     ///
     /// This implements `CVUncompressData`, which can decode numbers no larger than 0x1FFFFFFF. It
@@ -154,17 +315,8 @@ impl<'t> BinaryAnnotationsIter<'t> {
     }
 }
 
-/// Resembles `DecodeSignedInt32`.
-fn decode_signed_operand(value: u32) -> i32 {
-    if value & 1 != 0 {
-        -((value >> 1) as i32)
-    } else {
-        (value >> 1) as i32
-    }
-}
-
-impl<'t> FallibleIterator for BinaryAnnotationsIter<'t> {
-    type Item = BinaryAnnotation;
+impl<'t> FallibleIterator for RawBinaryAnnotationsIter<'t> {
+    type Item = RawBinaryAnnotation<'t>;
     type Error = Error;
 
     fn next(&mut self) -> Result<Option<Self::Item>> {
@@ -172,65 +324,73 @@ impl<'t> FallibleIterator for BinaryAnnotationsIter<'t> {
             return Ok(None);
         }
 
-        let op = self.uncompress_next()?;
-        let annotation = match BinaryAnnotationOpcode::parse(op)? {
+        let offset = self.buffer.pos();
+        let opcode = self.uncompress_next()?;
+        let operand_count = match BinaryAnnotationOpcode::parse(opcode)? {
             BinaryAnnotationOpcode::Eof => {
                 // This makes the end of the stream
                 self.buffer = ParseBuffer::default();
                 return Ok(None);
             }
-            BinaryAnnotationOpcode::CodeOffset => {
-                BinaryAnnotation::CodeOffset(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeCodeOffsetBase => {
-                BinaryAnnotation::ChangeCodeOffsetBase(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeCodeOffset => {
-                BinaryAnnotation::ChangeCodeOffset(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeCodeLength => {
-                BinaryAnnotation::ChangeCodeLength(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeFile => {
-                BinaryAnnotation::ChangeFile(FileIndex(self.uncompress_next()?))
-            }
-            BinaryAnnotationOpcode::ChangeLineOffset => {
-                BinaryAnnotation::ChangeLineOffset(decode_signed_operand(self.uncompress_next()?))
-            }
-            BinaryAnnotationOpcode::ChangeLineEndDelta => {
-                BinaryAnnotation::ChangeLineEndDelta(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeRangeKind => {
-                BinaryAnnotation::ChangeRangeKind(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeColumnStart => {
-                BinaryAnnotation::ChangeColumnStart(self.uncompress_next()?)
-            }
-            BinaryAnnotationOpcode::ChangeColumnEndDelta => BinaryAnnotation::ChangeColumnEndDelta(
-                decode_signed_operand(self.uncompress_next()?),
-            ),
-            BinaryAnnotationOpcode::ChangeCodeOffsetAndLineOffset => {
-                let operand = self.uncompress_next()?;
-                BinaryAnnotation::ChangeCodeOffsetAndLineOffset(
-                    operand & 0xf,
-                    decode_signed_operand(operand >> 4),
-                )
-            }
-            BinaryAnnotationOpcode::ChangeCodeLengthAndCodeOffset => {
-                BinaryAnnotation::ChangeCodeLengthAndCodeOffset(
-                    self.uncompress_next()?,
-                    self.uncompress_next()?,
-                )
-            }
-            BinaryAnnotationOpcode::ChangeColumnEnd => {
-                BinaryAnnotation::ChangeColumnEnd(self.uncompress_next()?)
-            }
+            BinaryAnnotationOpcode::ChangeCodeLengthAndCodeOffset => 2,
+            _ => 1,
         };
 
-        Ok(Some(annotation))
+        let mut operands = [0; 2];
+        for operand in &mut operands[..operand_count] {
+            *operand = self.uncompress_next()?;
+        }
+
+        Ok(Some(RawBinaryAnnotation {
+            offset,
+            opcode,
+            bytes: &self.data[offset..self.buffer.pos()],
+            operands,
+            operand_count,
+        }))
     }
 }
 
+/// An iterator over binary annotations used by `S_INLINESITE`.
+#[derive(Clone, Debug, Default)]
+pub struct BinaryAnnotationsIter<'t> {
+    raw: RawBinaryAnnotationsIter<'t>,
+}
+
+impl<'t> FallibleIterator for BinaryAnnotationsIter<'t> {
+    type Item = BinaryAnnotation;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        Ok(self.raw.next()?.map(|raw| raw.decode()))
+    }
+}
+
+/// The kind of an [`AnnotationError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnnotationErrorKind {
+    /// The data ends in the middle of an annotation.
+    Truncated,
+    /// An opcode or operand is not a valid compressed integer.
+    InvalidCompression,
+    /// The opcode does not denote a known annotation.
+    UnknownOpcode(u32),
+    /// Non-zero bytes follow the end of the annotations.
+    TrailingData,
+}
+
+/// A defect in binary annotations, found by [`BinaryAnnotations::validate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AnnotationError {
+    /// Offset of the defective annotation from the start of the binary annotations, in bytes.
+    ///
+    /// For [`AnnotationErrorKind::TrailingData`], this is the offset of the first non-zero byte.
+    pub offset: usize,
+    /// The kind of defect.
+    pub kind: AnnotationErrorKind,
+}
+
 /// Binary annotations of a symbol.
 ///
 /// The binary annotation mechanism supports recording a list of annotations in an instruction
@@ -243,19 +403,170 @@ pub struct BinaryAnnotations<'t> {
 }
 
 impl<'t> BinaryAnnotations<'t> {
-    /// Creates a new instance of binary annotations.
-    pub(crate) fn new(data: &'t [u8]) -> Self {
+    /// Creates binary annotations from their encoded data.
+    ///
+    /// Use [`BinaryAnnotationsEncoder`] to encode annotations.
+    pub fn new(data: &'t [u8]) -> Self {
         BinaryAnnotations { data }
     }
 
+    /// Returns the encoded data of the annotations.
+    pub fn as_bytes(&self) -> &'t [u8] {
+        self.data
+    }
+
     /// Iterates through binary annotations.
     pub fn iter(&self) -> BinaryAnnotationsIter<'t> {
         BinaryAnnotationsIter {
+            raw: self.raw_iter(),
+        }
+    }
+
+    /// Checks that the data consists of valid annotations, optionally followed by zero padding.
+    ///
+    /// [`iter`](Self::iter) fails on the first invalid annotation and stops at the end marker,
+    /// ignoring any data after it. This instead reports where and how the data is defective, so
+    /// that broken annotations can be quantified. Returns `None` if the annotations are valid.
+    pub fn validate(&self) -> Option<AnnotationError> {
+        let mut iter = self.raw_iter();
+        let mut end = 0;
+
+        loop {
+            match iter.next() {
+                Ok(Some(annotation)) => end = annotation.offset + annotation.bytes.len(),
+                Ok(None) => break,
+                Err(error) => {
+                    let kind = match error {
+                        Error::UnknownBinaryAnnotation(opcode) => {
+                            AnnotationErrorKind::UnknownOpcode(opcode)
+                        }
+                        Error::InvalidCompressedAnnotation => {
+                            AnnotationErrorKind::InvalidCompression
+                        }
+                        _ => AnnotationErrorKind::Truncated,
+                    };
+
+                    return Some(AnnotationError { offset: end, kind });
+                }
+            }
+        }
+
+        // The end marker and the padding after it are zero.
+        let trailing = self.data[end..].iter().position(|&byte| byte != 0)?;
+        Some(AnnotationError {
+            offset: end + trailing,
+            kind: AnnotationErrorKind::TrailingData,
+        })
+    }
+
+    /// Iterates through binary annotations with their opcodes, raw operands and offsets.
+    pub fn raw_iter(&self) -> RawBinaryAnnotationsIter<'t> {
+        RawBinaryAnnotationsIter {
+            data: self.data,
             buffer: ParseBuffer::from(self.data),
         }
     }
 }
 
+/// Encodes binary annotations, for instance to synthesize the line program of an `S_INLINESITE`
+/// symbol.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::{BinaryAnnotation, BinaryAnnotations, BinaryAnnotationsEncoder, FallibleIterator};
+/// # fn test() -> pdb::Result<()> {
+/// let mut encoder = BinaryAnnotationsEncoder::new();
+/// encoder.push(BinaryAnnotation::ChangeLineOffset(3))?;
+/// encoder.push(BinaryAnnotation::ChangeCodeLengthAndCodeOffset(8, 0x10))?;
+/// let data = encoder.finish();
+///
+/// let annotations = BinaryAnnotations::new(&data);
+/// assert_eq!(
+///     annotations.iter().collect::<Vec<_>>()?,
+///     [
+///         BinaryAnnotation::ChangeLineOffset(3),
+///         BinaryAnnotation::ChangeCodeLengthAndCodeOffset(8, 0x10),
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BinaryAnnotationsEncoder {
+    data: Vec<u8>,
+}
+
+impl BinaryAnnotationsEncoder {
+    /// Creates an encoder without annotations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an annotation.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidCompressedAnnotation` if an operand is too large to be encoded. Operands
+    ///   can be at most `0x1FFFFFFF`, the code offset of `ChangeCodeOffsetAndLineOffset` at most
+    ///   `0xF`.
+    pub fn push(&mut self, annotation: BinaryAnnotation) -> Result<()> {
+        let (first, second) = match annotation {
+            BinaryAnnotation::CodeOffset(value)
+            | BinaryAnnotation::ChangeCodeOffsetBase(value)
+            | BinaryAnnotation::ChangeCodeOffset(value)
+            | BinaryAnnotation::ChangeCodeLength(value)
+            | BinaryAnnotation::ChangeFile(FileIndex(value))
+            | BinaryAnnotation::ChangeLineEndDelta(value)
+            | BinaryAnnotation::ChangeRangeKind(value)
+            | BinaryAnnotation::ChangeColumnStart(value)
+            | BinaryAnnotation::ChangeColumnEnd(value) => (value, None),
+            BinaryAnnotation::ChangeLineOffset(value)
+            | BinaryAnnotation::ChangeColumnEndDelta(value) => {
+                (encode_signed_operand(value)?, None)
+            }
+            BinaryAnnotation::ChangeCodeOffsetAndLineOffset(code, line) => {
+                let line = encode_signed_operand(line)?;
+                if code > 0xf || line > MAX_COMPRESSED >> 4 {
+                    return Err(Error::InvalidCompressedAnnotation);
+                }
+                ((line << 4) | code, None)
+            }
+            BinaryAnnotation::ChangeCodeLengthAndCodeOffset(length, offset) => {
+                (length, Some(offset))
+            }
+        };
+
+        // Validate all operands before modifying the data.
+        if first > MAX_COMPRESSED || second.is_some_and(|second| second > MAX_COMPRESSED) {
+            return Err(Error::InvalidCompressedAnnotation);
+        }
+
+        compress(&mut self.data, annotation.opcode())?;
+        compress(&mut self.data, first)?;
+        if let Some(second) = second {
+            compress(&mut self.data, second)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the annotations encoded so far, without padding.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the encoded annotations.
+    ///
+    /// The data is padded with zeros to a multiple of four bytes, as required for symbol records.
+    pub fn finish(mut self) -> Vec<u8> {
+        let padded = (self.data.len() + 3) & !3;
+        self.data.resize(padded, 0);
+        self.data
+    }
+}
+
 #[test]
 fn test_binary_annotation_iter() {
     let inp = b"\x0b\x03\x06\n\x03\x08\x06\x06\x03-\x06\x08\x03\x07\x0br\x06\x06\x0c\x03\x07\x06\x0f\x0c\x06\x05\x00\x00";
@@ -310,3 +621,117 @@ fn test_binary_annotation_iter() {
         ]
     );
 }
+
+#[test]
+fn test_binary_annotation_roundtrip() {
+    let inp = b"\x0b\x03\x06\n\x03\x08\x06\x06\x03-\x06\x08\x03\x07\x0br\x06\x06\x0c\x03\x07\x06\x0f\x0c\x06\x05\x00\x00";
+    let annotations = BinaryAnnotations::new(inp)
+        .iter()
+        .collect::<Vec<_>>()
+        .unwrap();
+
+    let mut encoder = BinaryAnnotationsEncoder::new();
+    for &annotation in &annotations {
+        encoder.push(annotation).unwrap();
+    }
+    assert_eq!(encoder.finish(), &inp[..]);
+
+    let mut encoder = BinaryAnnotationsEncoder::new();
+    let large = [
+        BinaryAnnotation::CodeOffset(0x1fff_ffff),
+        BinaryAnnotation::ChangeCodeLength(0x3fff),
+        BinaryAnnotation::ChangeLineOffset(-0x0fff_ffff),
+        BinaryAnnotation::ChangeCodeOffsetAndLineOffset(0xf, -0x00ff_ffff),
+    ];
+    for &annotation in &large {
+        encoder.push(annotation).unwrap();
+    }
+    let data = encoder.finish();
+    assert_eq!(data.len() % 4, 0);
+    assert_eq!(
+        BinaryAnnotations::new(&data)
+            .iter()
+            .collect::<Vec<_>>()
+            .unwrap(),
+        large
+    );
+
+    let mut encoder = BinaryAnnotationsEncoder::new();
+    assert!(encoder
+        .push(BinaryAnnotation::CodeOffset(0x2000_0000))
+        .is_err());
+    assert!(encoder
+        .push(BinaryAnnotation::ChangeLineOffset(0x1000_0000))
+        .is_err());
+    assert!(encoder
+        .push(BinaryAnnotation::ChangeCodeOffsetAndLineOffset(0x10, 0))
+        .is_err());
+    assert!(encoder
+        .push(BinaryAnnotation::ChangeCodeLengthAndCodeOffset(
+            1,
+            0x2000_0000
+        ))
+        .is_err());
+    assert!(encoder.as_bytes().is_empty());
+}
+
+#[test]
+fn test_raw_binary_annotation_iter() {
+    let inp = b"\x0b\x03\x06\x8a\x03\x0c\x03\x07\x00";
+    let raw = BinaryAnnotations::new(inp)
+        .raw_iter()
+        .collect::<Vec<_>>()
+        .unwrap();
+
+    let summary: Vec<_> = raw
+        .iter()
+        .map(|raw| (raw.offset, raw.opcode(), raw.operands(), raw.bytes))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (0, 11, &[0x03][..], &inp[0..2]),
+            (2, 6, &[0x0a03][..], &inp[2..5]),
+            (5, 12, &[0x03, 0x07][..], &inp[5..8]),
+        ]
+    );
+
+    assert_eq!(
+        raw[1].decode(),
+        BinaryAnnotation::ChangeLineOffset(decode_signed_operand(0x0a03))
+    );
+}
+
+#[test]
+fn test_binary_annotation_validate() {
+    let validate = |data: &[u8]| BinaryAnnotations::new(data).validate();
+    let error = |offset, kind| Some(AnnotationError { offset, kind });
+
+    assert_eq!(
+        validate(b"\x0b\x03\x06\x8a\x03\x0c\x03\x07\x00\x00\x00\x00"),
+        None
+    );
+    assert_eq!(validate(b"\x0b\x03"), None);
+    assert_eq!(validate(b""), None);
+
+    assert_eq!(
+        validate(b"\x0b\x03\x0c\x03"),
+        error(2, AnnotationErrorKind::Truncated)
+    );
+    assert_eq!(
+        validate(b"\x0b\x03\x06\xc0\x00"),
+        error(2, AnnotationErrorKind::Truncated)
+    );
+    assert_eq!(
+        validate(b"\x0b\x03\x0e\x00"),
+        error(2, AnnotationErrorKind::UnknownOpcode(14))
+    );
+    assert_eq!(
+        validate(b"\x0b\x03\x06\xe0\x00\x00\x00"),
+        error(2, AnnotationErrorKind::InvalidCompression)
+    );
+    assert_eq!(
+        validate(b"\x0b\x03\x00\x00\x06\x02"),
+        error(4, AnnotationErrorKind::TrailingData)
+    );
+}
@@ -11,7 +11,7 @@
 
 #![allow(unused, non_upper_case_globals, non_camel_case_types)]
 
-use std::fmt;
+use core::fmt;
 
 use scroll::{ctx::TryFromCtx, Endian};
 
@@ -336,6 +336,9 @@ pub enum CPUType {
     EBC = 0xe0,
     Thumb = 0xf0,
     ARMNT = 0xf4,
+    HybridX86ARM64 = 0xf7,
+    ARM64EC = 0xf8,
+    ARM64X = 0xf9,
     D3D11_Shader = 0x100,
 }
 
@@ -401,6 +404,9 @@ impl fmt::Display for CPUType {
             Self::EBC => write!(f, "EBC"),
             Self::Thumb => write!(f, "Thumb"),
             Self::ARMNT => write!(f, "ARMNT"),
+            Self::HybridX86ARM64 => write!(f, "HybridX86ARM64"),
+            Self::ARM64EC => write!(f, "ARM64EC"),
+            Self::ARM64X => write!(f, "ARM64X"),
             Self::D3D11_Shader => write!(f, "D3D11_Shader"),
         }
     }
@@ -468,6 +474,11 @@ impl From<u16> for CPUType {
             0xe0 => Self::EBC,
             0xf0 => Self::Thumb,
             0xf4 => Self::ARMNT,
+            // Current toolchains emit 0xf6 for ARM64, older ones 0x69.
+            0xf6 => Self::ARM64,
+            0xf7 => Self::HybridX86ARM64,
+            0xf8 => Self::ARM64EC,
+            0xf9 => Self::ARM64X,
             0x100 => Self::D3D11_Shader,
             _ => Self::Intel8080, // This enum doesn't have an unknown value, so we just force it to Intel8080 since it's 0x0.
         }
@@ -485,6 +496,7 @@ impl<'a> TryFromCtx<'a, Endian> for CPUType {
 /// These values correspond to the CV_CFL_LANG enumeration, and are documented
 /// [on MSDN](https://msdn.microsoft.com/en-us/library/bw3aekw6.aspx).
 #[non_exhaustive]
+#[repr(u8)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SourceLanguage {
     /// Application language is C.
@@ -525,10 +537,15 @@ pub enum SourceLanguage {
     MSIL = 0x0f,
     /// Application language is High Level Shader Language.
     HLSL = 0x10,
+    /// Application language is Rust.
+    Rust = 0x15,
 
     /// The DMD compiler emits 'D' for the CV source language. Microsoft doesn't
     /// have an enumerator for it yet.
     D = 0x44,
+
+    /// A language this crate does not know, with its raw value.
+    Unknown(u8),
 }
 
 impl fmt::Display for SourceLanguage {
@@ -551,7 +568,9 @@ impl fmt::Display for SourceLanguage {
             Self::JScript => write!(f, "JScript"),
             Self::MSIL => write!(f, "MSIL"),
             Self::HLSL => write!(f, "HLSL"),
+            Self::Rust => write!(f, "Rust"),
             Self::D => write!(f, "D"),
+            Self::Unknown(value) => write!(f, "Unknown({:#04x})", value),
         }
     }
 }
@@ -576,8 +595,9 @@ impl From<u8> for SourceLanguage {
             0x0e => Self::JScript,
             0x0f => Self::MSIL,
             0x10 => Self::HLSL,
+            0x15 => Self::Rust,
             0x44 => Self::D,
-            _ => Self::Masm, // There is no unknown, so we just force to Masm as the default.
+            _ => Self::Unknown(value),
         }
     }
 }
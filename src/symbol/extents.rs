@@ -0,0 +1,330 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::common::*;
+use crate::modi::find_range;
+use crate::pdb::PDB;
+use crate::source::Source;
+use crate::symbol::constants::{S_PUB32, S_PUB32_ST};
+use crate::symbol::{SymbolData, SymbolTable};
+use crate::FallibleIterator;
+
+/// The boundary that ends the estimated extent of a function.
+///
+/// See [`PDB::estimate_functions`] for how the boundaries are determined.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExtentBound {
+    /// The function ends at the next public symbol in the same section.
+    ///
+    /// This is the most common bound. It overestimates the size if the function is followed by
+    /// padding or by code without public symbol, such as static functions.
+    NextSymbol,
+    /// The function ends with the section contribution of its object file.
+    Contribution,
+    /// The function ends with its section.
+    Section,
+    /// No boundary is known, so the function is assumed to be empty.
+    Unbounded,
+}
+
+/// A function whose extent was estimated from public symbols.
+///
+/// Returned by [`EstimatedFunctions::iter`] and [`EstimatedFunctions::lookup`]. Unlike
+/// [`Function`](crate::Function), the size of this function is a heuristic and may include code of
+/// other functions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EstimatedFunction<'a> {
+    /// The estimated code of this function in the executable.
+    pub range: Range<Rva>,
+    /// The decorated name of the public symbol.
+    pub name: RawString<'a>,
+    /// Index of the public symbol in the global symbol table.
+    pub symbol: SymbolIndex,
+    /// The boundary that ends the estimated range.
+    pub bound: ExtentBound,
+    /// The number of other public symbols at the same address.
+    pub aliases: usize,
+}
+
+#[derive(Clone, Debug)]
+struct EstimateEntry {
+    range: Range<Rva>,
+    symbol: SymbolIndex,
+    bound: ExtentBound,
+    aliases: usize,
+}
+
+/// Functions estimated from the public symbols of a PDB, sorted by address.
+///
+/// Created by [`PDB::estimate_functions`].
+#[derive(Debug)]
+pub struct EstimatedFunctions<'s> {
+    symbols: SymbolTable<'s>,
+    entries: Vec<EstimateEntry>,
+}
+
+impl<'s> EstimatedFunctions<'s> {
+    /// Returns the number of distinct functions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no functions were found.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over all functions, sorted by address.
+    pub fn iter(&self) -> EstimatedFunctionIter<'_, 's> {
+        EstimatedFunctionIter {
+            functions: self,
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns the function whose estimated range contains the given address.
+    pub fn lookup(&self, rva: Rva) -> Result<Option<EstimatedFunction<'_>>> {
+        match find_range(&self.entries, rva, |entry| &entry.range) {
+            Some(entry) => self.resolve(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn resolve(&self, entry: &EstimateEntry) -> Result<EstimatedFunction<'_>> {
+        let symbol = self
+            .symbols
+            .iter_at(entry.symbol)
+            .next()?
+            .ok_or(Error::SymbolNotFound(entry.symbol.0))?;
+
+        let name = match symbol.parse()? {
+            SymbolData::Public(public) => public.name,
+            _ => unreachable!("estimated functions refer to public symbols"),
+        };
+
+        Ok(EstimatedFunction {
+            range: entry.range.clone(),
+            name,
+            symbol: entry.symbol,
+            bound: entry.bound,
+            aliases: entry.aliases,
+        })
+    }
+}
+
+/// An iterator over the estimated functions of a PDB.
+///
+/// Created by [`EstimatedFunctions::iter`].
+#[derive(Debug)]
+pub struct EstimatedFunctionIter<'a, 's> {
+    functions: &'a EstimatedFunctions<'s>,
+    entries: core::slice::Iter<'a, EstimateEntry>,
+}
+
+impl<'a> FallibleIterator for EstimatedFunctionIter<'a, '_> {
+    type Item = EstimatedFunction<'a>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        match self.entries.next() {
+            Some(entry) => self.functions.resolve(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the end of the extent starting at `start`, and the boundary that determined it.
+///
+/// Offsets are given as pairs of section and offset. `publics` and `contributions` are sorted,
+/// and the contributions are paired with their size. `sections` holds the size of each section,
+/// starting with section 1.
+fn extent_end(
+    start: (u16, u32),
+    publics: &[(u16, u32)],
+    contributions: &[((u16, u32), u32)],
+    sections: &[u32],
+) -> (u32, ExtentBound) {
+    let (section, start) = start;
+    let mut end = (u32::MAX, ExtentBound::Unbounded);
+    let mut clamp = |offset: u32, bound| {
+        if offset > start && offset <= end.0 {
+            end = (offset, bound);
+        }
+    };
+
+    let index = publics.partition_point(|&public| public <= (section, start));
+    if let Some(&(next_section, next)) = publics.get(index) {
+        if next_section == section {
+            clamp(next, ExtentBound::NextSymbol);
+        }
+    }
+
+    if let Some(&size) = sections.get(usize::from(section).wrapping_sub(1)) {
+        clamp(size, ExtentBound::Section);
+    }
+
+    let index = contributions.partition_point(|&(offset, _)| offset <= (section, start));
+    if let Some(&((contribution_section, offset), size)) =
+        index.checked_sub(1).map(|i| &contributions[i])
+    {
+        let contribution_end = offset.saturating_add(size);
+        if contribution_section == section && start < contribution_end {
+            clamp(contribution_end, ExtentBound::Contribution);
+        }
+    }
+
+    match end {
+        (_, ExtentBound::Unbounded) => (start, ExtentBound::Unbounded),
+        end => end,
+    }
+}
+
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+    /// Estimates the extents of functions from the public symbols of this PDB.
+    ///
+    /// Stripped PDBs, such as those published to public symbol servers, contain no procedure
+    /// symbols, so [`functions`](Self::functions) finds nothing. Their public symbols only declare
+    /// the start of each function. This pass sorts the public symbols of code by address and
+    /// assumes that every function extends until the nearest of:
+    ///
+    /// * the next public symbol in the same section,
+    /// * the end of the section contribution containing the function, and
+    /// * the end of its section.
+    ///
+    /// The [`bound`](EstimatedFunction::bound) of each function tells which of these applied. The
+    /// results are heuristic: static functions and other code without public symbol are attributed
+    /// to the preceding function, and padding between functions is included. Use this only to
+    /// report a probable function when no procedure symbols are available.
+    ///
+    /// Public symbols at the same address are merged, keeping the first in the table. Symbols that
+    /// do not point into any section are skipped.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::GlobalSymbolsNotFound` if the PDB does not contain a global symbol table
+    /// * Errors reading the debug information, section headers or address map
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let functions = pdb.estimate_functions()?;
+    /// if let Some(function) = functions.lookup(pdb::Rva(0x1010))? {
+    ///     println!("probably in {} ({:?})", function.name, function.bound);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn estimate_functions(&self) -> Result<EstimatedFunctions<'s>> {
+        let address_map = self.address_map()?;
+        let symbols = self.global_symbols()?;
+
+        // The start of all public symbols, and the index of those referring to code.
+        let mut publics = Vec::new();
+        let mut starts = Vec::new();
+        let mut iter = symbols.iter();
+        while let Some(symbol) = iter.next()? {
+            if !matches!(symbol.raw_kind(), S_PUB32 | S_PUB32_ST) {
+                continue;
+            }
+
+            if let SymbolData::Public(public) = symbol.parse()? {
+                if !public.offset.is_valid() {
+                    continue;
+                }
+
+                publics.push((public.offset.section, public.offset.offset));
+                if public.code || public.function {
+                    starts.push((public.offset, symbol.index()));
+                }
+            }
+        }
+        publics.sort_unstable();
+
+        let dbi = self.debug_information()?;
+        let mut contributions = Vec::new();
+        let mut iter = dbi.section_contributions()?;
+        while let Some(contribution) = iter.next()? {
+            let offset = contribution.offset;
+            contributions.push(((offset.section, offset.offset), contribution.size));
+        }
+        contributions.sort_unstable();
+
+        let sections: Vec<u32> = self
+            .sections()?
+            .unwrap_or_default()
+            .iter()
+            .map(|section| section.virtual_size)
+            .collect();
+
+        let mut entries = Vec::with_capacity(starts.len());
+        for (start, symbol) in starts {
+            let key = (start.section, start.offset);
+            let (end, bound) = extent_end(key, &publics, &contributions, &sections);
+
+            let internal = match start.to_internal_rva(&address_map) {
+                Some(internal) => internal,
+                None => continue,
+            };
+            let range = match internal.checked_add(end - start.offset) {
+                Some(end) if end > internal => address_map.rva_ranges(internal..end).next(),
+                _ => start.to_rva(&address_map).map(|rva| rva..rva),
+            };
+
+            if let Some(range) = range {
+                entries.push(EstimateEntry {
+                    range,
+                    symbol,
+                    bound,
+                    aliases: 0,
+                });
+            }
+        }
+
+        // Keep the first symbol at every address in table order, and count the others.
+        entries.sort_by_key(|entry| (entry.range.start, entry.symbol));
+        entries.dedup_by(|duplicate, first| {
+            let is_duplicate = duplicate.range.start == first.range.start;
+            if is_duplicate {
+                first.aliases += 1;
+            }
+            is_duplicate
+        });
+
+        Ok(EstimatedFunctions { symbols, entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extent_end() {
+        let publics = [(1, 0x10), (1, 0x40), (2, 0x10)];
+        let contributions = [((1, 0), 0x30), ((1, 0x30), 0x50)];
+        let sections = [0x100, 0x20];
+
+        let end = |start| extent_end(start, &publics, &contributions, &sections);
+        assert_eq!(end((1, 0x10)), (0x30, ExtentBound::Contribution));
+        assert_eq!(end((1, 0x40)), (0x80, ExtentBound::Contribution));
+        assert_eq!(end((1, 0x30)), (0x40, ExtentBound::NextSymbol));
+        assert_eq!(end((1, 0x90)), (0x100, ExtentBound::Section));
+        assert_eq!(end((2, 0x10)), (0x20, ExtentBound::Section));
+        assert_eq!(end((3, 0x10)), (0x10, ExtentBound::Unbounded));
+    }
+}
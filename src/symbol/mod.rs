@@ -5,21 +5,36 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::fmt;
+#[cfg(feature = "demangle")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
 use scroll::{ctx::TryFromCtx, Endian, Pread, LE};
+use uuid::Uuid;
 
+use crate::cache::{read_index, write_index, IndexKind};
 use crate::common::*;
+use crate::modi::ModuleInfo;
 use crate::msf::*;
+use crate::pdbi::PdbIdentity;
+use crate::search::{BudgetTracker, SearchBudget, SearchContinuation, SearchResults};
+use crate::tpi::{TypeData, TypeFinder};
 use crate::FallibleIterator;
 
 mod annotations;
 mod constants;
+mod extents;
+mod udt;
 
 use self::constants::*;
 pub use self::constants::{CPUType, SourceLanguage};
 
 pub use self::annotations::*;
+pub use self::extents::{
+    EstimatedFunction, EstimatedFunctionIter, EstimatedFunctions, ExtentBound,
+};
+pub use self::udt::UserDefinedType;
 
 /// The raw type discriminator for `Symbols`.
 pub type SymbolKind = u16;
@@ -59,11 +74,39 @@ impl<'t> Symbol<'t> {
     }
 
     /// Parse the symbol into the `SymbolData` it contains.
+    ///
+    /// Symbols of kinds that are not supported by this crate are returned as
+    /// [`SymbolData::Unknown`] with their raw data.
     #[inline]
     pub fn parse(&self) -> Result<SymbolData<'t>> {
         self.raw_bytes().pread_with(0, ())
     }
 
+    /// Returns an iterator over the key/value pairs following the version string of a
+    /// [`CompileFlagsSymbol`].
+    ///
+    /// Only `S_COMPILE2` records carry these pairs, which describe the build environment in the
+    /// same way as an [`EnvBlockSymbol`]. Newer compilers emit a separate `S_ENVBLOCK` record
+    /// instead, so the iterator is empty for `S_COMPILE3`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedSymbolKind` if this is not a compile flags symbol
+    /// * Errors parsing the compile flags, if the symbol is corrupt
+    pub fn compile_command_block(&self) -> Result<EnvBlockIter<'t>> {
+        let kind = self.raw_kind();
+        let data = match kind {
+            S_COMPILE2 | S_COMPILE2_ST => &self.data[2..],
+            S_COMPILE3 => return Ok(EnvBlockIter::default()),
+            _ => return Err(Error::UnimplementedSymbolKind(kind)),
+        };
+
+        let (_, size) = CompileFlagsSymbol::try_from_ctx(data, kind)?;
+        Ok(EnvBlockIter {
+            buf: ParseBuffer::from(&data[size..]),
+        })
+    }
+
     /// Returns whether this symbol starts a scope.
     ///
     /// If `true`, this symbol has a `parent` and an `end` field, which contains the offset of the
@@ -113,6 +156,31 @@ impl<'t> Symbol<'t> {
     pub fn ends_scope(&self) -> bool {
         matches!(self.raw_kind(), S_END | S_PROC_ID_END | S_INLINESITE_END)
     }
+
+    /// Returns whether this symbol declares an address range in which the preceding
+    /// [`LocalSymbol`] can be evaluated.
+    pub fn is_def_range(&self) -> bool {
+        matches!(
+            self.raw_kind(),
+            S_DEFRANGE
+                | S_DEFRANGE_SUBFIELD
+                | S_DEFRANGE_REGISTER
+                | S_DEFRANGE_FRAMEPOINTER_REL
+                | S_DEFRANGE_SUBFIELD_REGISTER
+                | S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE
+                | S_DEFRANGE_REGISTER_REL
+                | S_DEFRANGE_HLSL
+                | S_DEFRANGE_DPC_PTR_TAG
+                | S_DEFRANGE_2005
+                | S_DEFRANGE2_2005
+        )
+    }
+
+    /// Returns whether this symbol is a using-namespace directive, which parses into
+    /// [`SymbolData::UsingNamespace`].
+    pub(crate) fn is_using_namespace(&self) -> bool {
+        matches!(self.raw_kind(), S_UNAMESPACE | S_UNAMESPACE_ST)
+    }
 }
 
 impl<'t> fmt::Debug for Symbol<'t> {
@@ -189,6 +257,8 @@ pub enum SymbolData<'t> {
     ThreadStorage(ThreadStorageSymbol<'t>),
     /// Flags used to compile a module.
     CompileFlags(CompileFlagsSymbol<'t>),
+    /// Flags used to compile a module, in the format of early toolchains.
+    LegacyCompileFlags(LegacyCompileFlagsSymbol<'t>),
     /// A using namespace directive.
     UsingNamespace(UsingNamespaceSymbol<'t>),
     /// Reference to a [`ProcedureSymbol`].
@@ -217,10 +287,47 @@ pub enum SymbolData<'t> {
     Block(BlockSymbol<'t>),
     /// Data allocated relative to a register.
     RegisterRelative(RegisterRelativeSymbol<'t>),
+    /// Data allocated relative to the frame pointer.
+    BasePointerRelative(BasePointerRelativeSymbol<'t>),
     /// A thunk.
     Thunk(ThunkSymbol<'t>),
     /// A block of separated code.
     SeparatedCode(SeparatedCodeSymbol),
+    /// A managed procedure, such as a method compiled by C++/CLI.
+    ManagedProcedure(ManagedProcedureSymbol<'t>),
+    /// A managed local variable stored in a slot.
+    ManagedSlot(ManagedSlotSymbol<'t>),
+    /// Reference to a [`ManagedProcedureSymbol`].
+    TokenReference(ProcedureReferenceSymbol<'t>),
+    /// A symbol defined by a tool vendor.
+    Oem(OemSymbol<'t>),
+    /// An address range in which the preceding [`LocalSymbol`] can be evaluated.
+    DefRange(DefRangeSymbol),
+    /// Extra frame information of a procedure.
+    FrameProc(FrameProcSymbol),
+    /// The type of the target of an indirect call.
+    CallSiteInfo(CallSiteInfoSymbol),
+    /// A call to a heap allocation function.
+    HeapAllocationSite(HeapAllocationSiteSymbol),
+    /// A list of callers, callees or inlinees of a procedure.
+    FunctionList(FunctionListSymbol<'t>),
+    /// A static variable scoped to a source file.
+    FileStatic(FileStaticSymbol<'t>),
+    /// A jump table of a switch statement on ARM or ARM64.
+    ArmSwitchTable(ArmSwitchTableSymbol),
+    /// Strings attached to a code location with `__annotation`.
+    Annotation(AnnotationSymbol<'t>),
+    /// The build environment of a module.
+    EnvBlock(EnvBlockSymbol<'t>),
+    /// A symbol of a kind that is not supported by this crate.
+    ///
+    /// The record can be decoded by the caller from its raw data.
+    Unknown {
+        /// The kind of the symbol.
+        kind: SymbolKind,
+        /// The data of the record following the kind.
+        data: &'t [u8],
+    },
 }
 
 impl<'t> SymbolData<'t> {
@@ -238,6 +345,7 @@ impl<'t> SymbolData<'t> {
             Self::Procedure(data) => Some(data.name),
             Self::ThreadStorage(data) => Some(data.name),
             Self::CompileFlags(_) => None,
+            Self::LegacyCompileFlags(_) => None,
             Self::UsingNamespace(data) => Some(data.name),
             Self::ProcedureReference(data) => data.name,
             Self::DataReference(data) => data.name,
@@ -252,8 +360,23 @@ impl<'t> SymbolData<'t> {
             Self::Label(data) => Some(data.name),
             Self::Block(data) => Some(data.name),
             Self::RegisterRelative(data) => Some(data.name),
+            Self::BasePointerRelative(data) => Some(data.name),
             Self::Thunk(data) => Some(data.name),
             Self::SeparatedCode(_) => None,
+            Self::ManagedProcedure(data) => Some(data.name),
+            Self::ManagedSlot(data) => Some(data.name),
+            Self::TokenReference(data) => data.name,
+            Self::Oem(_) => None,
+            Self::DefRange(_) => None,
+            Self::FrameProc(_) => None,
+            Self::CallSiteInfo(_) => None,
+            Self::HeapAllocationSite(_) => None,
+            Self::FunctionList(_) => None,
+            Self::FileStatic(data) => Some(data.name),
+            Self::ArmSwitchTable(_) => None,
+            Self::Annotation(_) => None,
+            Self::EnvBlock(_) => None,
+            Self::Unknown { .. } => None,
         }
     }
 }
@@ -289,6 +412,7 @@ impl<'t> TryFromCtx<'t> for SymbolData<'t> {
             S_COMPILE2 | S_COMPILE2_ST | S_COMPILE3 => {
                 SymbolData::CompileFlags(buf.parse_with(kind)?)
             }
+            S_COMPILE => SymbolData::LegacyCompileFlags(buf.parse_with(kind)?),
             S_UNAMESPACE | S_UNAMESPACE_ST => SymbolData::UsingNamespace(buf.parse_with(kind)?),
             S_PROCREF | S_PROCREF_ST | S_LPROCREF | S_LPROCREF_ST => {
                 SymbolData::ProcedureReference(buf.parse_with(kind)?)
@@ -305,9 +429,34 @@ impl<'t> TryFromCtx<'t> for SymbolData<'t> {
             S_LABEL32 | S_LABEL32_ST => SymbolData::Label(buf.parse_with(kind)?),
             S_BLOCK32 | S_BLOCK32_ST => SymbolData::Block(buf.parse_with(kind)?),
             S_REGREL32 => SymbolData::RegisterRelative(buf.parse_with(kind)?),
+            S_BPREL32 | S_BPREL32_ST => SymbolData::BasePointerRelative(buf.parse_with(kind)?),
             S_THUNK32 | S_THUNK32_ST => SymbolData::Thunk(buf.parse_with(kind)?),
             S_SEPCODE => SymbolData::SeparatedCode(buf.parse_with(kind)?),
-            other => return Err(Error::UnimplementedSymbolKind(other)),
+            S_GMANPROC | S_GMANPROC_ST | S_LMANPROC | S_LMANPROC_ST => {
+                SymbolData::ManagedProcedure(buf.parse_with(kind)?)
+            }
+            S_MANSLOT | S_MANSLOT_ST => SymbolData::ManagedSlot(buf.parse_with(kind)?),
+            S_TOKENREF => SymbolData::TokenReference(buf.parse_with(kind)?),
+            S_OEM => SymbolData::Oem(buf.parse_with(kind)?),
+            S_DEFRANGE
+            | S_DEFRANGE_SUBFIELD
+            | S_DEFRANGE_REGISTER
+            | S_DEFRANGE_FRAMEPOINTER_REL
+            | S_DEFRANGE_SUBFIELD_REGISTER
+            | S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE
+            | S_DEFRANGE_REGISTER_REL => SymbolData::DefRange(buf.parse_with(kind)?),
+            S_FRAMEPROC => SymbolData::FrameProc(buf.parse_with(kind)?),
+            S_CALLSITEINFO => SymbolData::CallSiteInfo(buf.parse_with(kind)?),
+            S_HEAPALLOCSITE => SymbolData::HeapAllocationSite(buf.parse_with(kind)?),
+            S_CALLEES | S_CALLERS | S_INLINEES => SymbolData::FunctionList(buf.parse_with(kind)?),
+            S_FILESTATIC => SymbolData::FileStatic(buf.parse_with(kind)?),
+            S_ARMSWITCHTABLE => SymbolData::ArmSwitchTable(buf.parse_with(kind)?),
+            S_ANNOTATION => SymbolData::Annotation(buf.parse_with(kind)?),
+            S_ENVBLOCK => SymbolData::EnvBlock(buf.parse_with(kind)?),
+            other => SymbolData::Unknown {
+                kind: other,
+                data: buf.take(buf.len())?,
+            },
         };
 
         Ok((symbol, buf.pos()))
@@ -405,6 +554,17 @@ pub struct PublicSymbol<'t> {
     pub name: RawString<'t>,
 }
 
+impl PublicSymbol<'_> {
+    /// Demangles the name of this symbol if it is a mangled Rust name.
+    ///
+    /// Both the legacy and the v0 mangling scheme are supported. The hash suffix of legacy names is
+    /// omitted. Returns `None` if the name is not a Rust symbol name.
+    #[cfg(feature = "demangle")]
+    pub fn demangle_rust(&self) -> Option<String> {
+        demangle_rust(self.name)
+    }
+}
+
 impl<'t> TryFromCtx<'t, SymbolKind> for PublicSymbol<'t> {
     type Error = Error;
 
@@ -425,6 +585,75 @@ impl<'t> TryFromCtx<'t, SymbolKind> for PublicSymbol<'t> {
     }
 }
 
+/// Restricts the [`PublicSymbol`]s returned by [`SymbolTable::publics`].
+///
+/// Each field is matched against the corresponding flag of the public symbol. A field set to
+/// `None` matches any value. The default filter matches all public symbols.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// #
+/// # fn test() -> pdb::Result<usize> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let symbol_table = pdb.global_symbols()?;
+///
+/// let mut functions = symbol_table.publics(pdb::PublicSymbolFilter::functions());
+/// # let mut count: usize = 0;
+/// while let Some(public) = functions.next()? {
+///     assert!(public.function);
+///     # count += 1;
+/// }
+/// # Ok(count)
+/// # }
+/// # assert!(test().expect("test") > 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PublicSymbolFilter {
+    /// Required value of [`PublicSymbol::code`].
+    pub code: Option<bool>,
+    /// Required value of [`PublicSymbol::function`].
+    pub function: Option<bool>,
+    /// Required value of [`PublicSymbol::managed`].
+    pub managed: Option<bool>,
+    /// Required value of [`PublicSymbol::msil`].
+    pub msil: Option<bool>,
+}
+
+impl PublicSymbolFilter {
+    /// Returns a filter that matches only public symbols of functions.
+    pub fn functions() -> Self {
+        Self {
+            function: Some(true),
+            ..Self::default()
+        }
+    }
+
+    /// Returns a filter that matches only public symbols of data, that is, neither code nor
+    /// functions.
+    pub fn data() -> Self {
+        Self {
+            code: Some(false),
+            function: Some(false),
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether the given public symbol satisfies this filter.
+    pub fn matches(&self, symbol: &PublicSymbol<'_>) -> bool {
+        fn check(required: Option<bool>, actual: bool) -> bool {
+            required.map_or(true, |required| required == actual)
+        }
+
+        check(self.code, symbol.code)
+            && check(self.function, symbol.function)
+            && check(self.managed, symbol.managed)
+            && check(self.msil, symbol.msil)
+    }
+}
+
 /// Static data, such as a global variable.
 ///
 /// Symbol kinds:
@@ -469,7 +698,8 @@ impl<'t> TryFromCtx<'t, SymbolKind> for DataSymbol<'t> {
 
 /// Reference to an imported procedure.
 ///
-/// Symbol kind `S_PROCREF`, `S_PROCREF_ST`, `S_LPROCREF`, or `S_LPROCREF_ST`.
+/// Symbol kind `S_PROCREF`, `S_PROCREF_ST`, `S_LPROCREF`, or `S_LPROCREF_ST`. References to managed
+/// procedures use symbol kind `S_TOKENREF`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ProcedureReferenceSymbol<'t> {
     /// Whether the referenced procedure is global or local.
@@ -633,7 +863,8 @@ impl TryFromCtx<'_, SymbolKind> for TrampolineSymbol {
 
 /// A constant value.
 ///
-/// Symbol kind `S_CONSTANT`, or `S_CONSTANT_ST`.
+/// Symbol kind `S_CONSTANT`, `S_CONSTANT_ST`, or `S_MANCONSTANT` for constants with metadata type
+/// information.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ConstantSymbol<'t> {
     /// Whether this constant has metadata type information.
@@ -663,6 +894,26 @@ impl<'t> TryFromCtx<'t, SymbolKind> for ConstantSymbol<'t> {
     }
 }
 
+impl ConstantSymbol<'_> {
+    /// Resolves the type of this constant.
+    ///
+    /// Constants of enumeration types usually declare enumerators that are exported as constants.
+    /// Returns `None` for managed constants, since their `type_index` is a metadata token rather
+    /// than an index into the type stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound` or `Error::TypeNotIndexed` if the type cannot be found by `finder`
+    /// * `Error::UnimplementedTypeKind` if the type record isn't currently understood
+    pub fn type_data<'f>(&self, finder: &TypeFinder<'f>) -> Result<Option<TypeData<'f>>> {
+        if self.managed {
+            return Ok(None);
+        }
+
+        finder.find(self.type_index)?.parse().map(Some)
+    }
+}
+
 /// A user defined type.
 ///
 /// Symbol kind `S_UDT`, or `S_UDT_ST`.
@@ -815,6 +1066,17 @@ pub struct ProcedureSymbol<'t> {
     pub name: RawString<'t>,
 }
 
+impl ProcedureSymbol<'_> {
+    /// Demangles the name of this procedure if it is a mangled Rust name.
+    ///
+    /// Procedure names are usually stored demangled. Some toolchains emit linkage names instead,
+    /// which this function demangles. Returns `None` if the name is not a Rust symbol name.
+    #[cfg(feature = "demangle")]
+    pub fn demangle_rust(&self) -> Option<String> {
+        demangle_rust(self.name)
+    }
+}
+
 impl<'t> TryFromCtx<'t, SymbolKind> for ProcedureSymbol<'t> {
     type Error = Error;
 
@@ -840,6 +1102,99 @@ impl<'t> TryFromCtx<'t, SymbolKind> for ProcedureSymbol<'t> {
     }
 }
 
+/// A managed procedure, such as a method compiled by C++/CLI.
+///
+/// Symbol kinds:
+///  - `S_GMANPROC`, `S_GMANPROC_ST` for global procedures
+///  - `S_LMANPROC`, `S_LMANPROC_ST` for local procedures
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ManagedProcedureSymbol<'t> {
+    /// Whether this is a global or local procedure.
+    pub global: bool,
+    /// The parent scope that this procedure is nested in.
+    pub parent: Option<SymbolIndex>,
+    /// The end symbol of this procedure.
+    pub end: SymbolIndex,
+    /// The next procedure symbol.
+    pub next: Option<SymbolIndex>,
+    /// The length of the code block covered by this procedure.
+    pub len: u32,
+    /// Start offset of the procedure's body code, which marks the end of the prologue.
+    pub dbg_start_offset: u32,
+    /// End offset of the procedure's body code, which marks the start of the epilogue.
+    pub dbg_end_offset: u32,
+    /// The metadata token of the method.
+    pub token: u32,
+    /// Code offset of the start of this procedure.
+    pub offset: PdbInternalSectionOffset,
+    /// Detailed flags of this procedure.
+    pub flags: ProcedureFlags,
+    /// The register holding the return value.
+    pub return_register: Register,
+    /// The name of the procedure.
+    pub name: RawString<'t>,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for ManagedProcedureSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let symbol = ManagedProcedureSymbol {
+            global: matches!(kind, S_GMANPROC | S_GMANPROC_ST),
+            parent: parse_optional_index(&mut buf)?,
+            end: buf.parse()?,
+            next: parse_optional_index(&mut buf)?,
+            len: buf.parse()?,
+            dbg_start_offset: buf.parse()?,
+            dbg_end_offset: buf.parse()?,
+            token: buf.parse()?,
+            offset: buf.parse()?,
+            flags: buf.parse()?,
+            return_register: buf.parse()?,
+            name: parse_symbol_name(&mut buf, kind)?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// A managed local variable stored in a slot.
+///
+/// Symbol kind `S_MANSLOT`, or `S_MANSLOT_ST`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ManagedSlotSymbol<'t> {
+    /// Index of the slot holding the variable.
+    pub slot: u32,
+    /// The type of the variable, or its metadata token.
+    pub type_index: TypeIndex,
+    /// Code offset of the start of the variable's lifetime.
+    pub offset: PdbInternalSectionOffset,
+    /// Flags for this variable.
+    pub flags: LocalVariableFlags,
+    /// Name of the variable.
+    pub name: RawString<'t>,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for ManagedSlotSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let symbol = ManagedSlotSymbol {
+            slot: buf.parse()?,
+            type_index: buf.parse()?,
+            offset: buf.parse()?,
+            flags: buf.parse()?,
+            name: parse_symbol_name(&mut buf, kind)?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
 /// The callsite of an inlined function.
 ///
 /// Symbol kind `S_INLINESITE`, or `S_INLINESITE2`.
@@ -901,12 +1256,126 @@ impl<'t> TryFromCtx<'t, SymbolKind> for BuildInfoSymbol {
     }
 }
 
+/// The kind of a [`FunctionListSymbol`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FunctionListKind {
+    /// Functions called by the enclosing procedure.
+    Callees,
+    /// Functions calling the enclosing procedure.
+    Callers,
+    /// Functions inlined into the enclosing procedure.
+    Inlinees,
+}
+
+/// A list of functions related to the enclosing procedure.
+///
+/// Symbol kind `S_CALLEES`, `S_CALLERS`, or `S_INLINEES`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FunctionListSymbol<'t> {
+    /// The relation of the listed functions to the enclosing procedure.
+    pub kind: FunctionListKind,
+    /// The number of functions in the list.
+    pub count: u32,
+    functions: &'t [u8],
+    invocations: Option<&'t [u8]>,
+}
+
+impl<'t> FunctionListSymbol<'t> {
+    /// Returns an iterator over the functions in this list.
+    pub fn functions(&self) -> FunctionListIter<'t> {
+        FunctionListIter {
+            functions: ParseBuffer::from(self.functions),
+            invocations: self.invocations.map(ParseBuffer::from),
+        }
+    }
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for FunctionListSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let kind = match kind {
+            S_CALLEES => FunctionListKind::Callees,
+            S_CALLERS => FunctionListKind::Callers,
+            S_INLINEES => FunctionListKind::Inlinees,
+            _ => return Err(Error::UnimplementedSymbolKind(kind)),
+        };
+
+        let count = buf.parse_u32()?;
+        let functions = buf.take(count as usize * 4)?;
+
+        // Caller and callee lists are followed by a parallel array of invocation counts, which may
+        // be truncated at the end of the record.
+        let invocations = match kind {
+            FunctionListKind::Inlinees => None,
+            _ => Some(buf.take(buf.len().min(count as usize * 4))?),
+        };
+
+        let symbol = FunctionListSymbol {
+            kind,
+            count,
+            functions,
+            invocations,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// A function in a [`FunctionListSymbol`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FunctionListEntry {
+    /// The id of the function.
+    pub function: IdIndex,
+    /// The number of invocations, if the list records them.
+    ///
+    /// Caller and callee lists record invocation counts, inlinee lists do not. Counts omitted from
+    /// the end of the record are zero.
+    pub invocations: Option<u32>,
+}
+
+/// An iterator over the functions of a [`FunctionListSymbol`].
+#[derive(Clone, Debug, Default)]
+pub struct FunctionListIter<'t> {
+    functions: ParseBuffer<'t>,
+    invocations: Option<ParseBuffer<'t>>,
+}
+
+impl FallibleIterator for FunctionListIter<'_> {
+    type Item = FunctionListEntry;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.functions.is_empty() {
+            return Ok(None);
+        }
+
+        let function = self.functions.parse()?;
+        let invocations = match self.invocations {
+            Some(ref mut buf) if !buf.is_empty() => Some(buf.parse_u32()?),
+            Some(_) => Some(0),
+            None => None,
+        };
+
+        Ok(Some(FunctionListEntry {
+            function,
+            invocations,
+        }))
+    }
+}
+
 /// Name of the object file of this module.
 ///
 /// Symbol kind `S_OBJNAME`, or `S_OBJNAME_ST`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ObjNameSymbol<'t> {
-    /// Signature.
+    /// Signature of the precompiled types in this object file.
+    ///
+    /// Objects that create a precompiled header (`/Yc`) store the signature of its types here.
+    /// Objects using the precompiled header reference it with the same signature in an
+    /// `LF_PRECOMP` record. The signature is zero for all other objects.
     pub signature: u32,
     /// Path to the object file.
     pub name: RawString<'t>,
@@ -1032,7 +1501,21 @@ pub struct CompileFlagsSymbol<'t> {
     pub backend_version: CompilerVersion,
     /// Display name of the compiler.
     pub version_string: RawString<'t>,
-    // TODO: Command block for S_COMPILE2?
+}
+
+impl CompileFlagsSymbol<'_> {
+    /// Returns whether the module was compiled by the Rust compiler.
+    ///
+    /// Recent versions of rustc declare [`SourceLanguage::Rust`]. Older versions may declare a
+    /// different language, but identify themselves in the compiler's display name.
+    pub fn is_rust(&self) -> bool {
+        self.language == SourceLanguage::Rust
+            || self
+                .version_string
+                .as_bytes()
+                .windows(5)
+                .any(|window| window == b"rustc")
+    }
 }
 
 impl<'t> TryFromCtx<'t, SymbolKind> for CompileFlagsSymbol<'t> {
@@ -1055,6 +1538,65 @@ impl<'t> TryFromCtx<'t, SymbolKind> for CompileFlagsSymbol<'t> {
     }
 }
 
+/// Flags used to compile a module, in the format of 16-bit and early 32-bit toolchains.
+///
+/// Symbol kind `S_COMPILE`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LegacyCompileFlagsSymbol<'t> {
+    /// Machine type of the compilation target.
+    pub cpu_type: CPUType,
+    /// The source code language.
+    pub language: SourceLanguage,
+    /// Compiled for p-code.
+    pub pcode: bool,
+    /// Floating point precision. A value of `1` indicates ANSI C floating point rules.
+    pub float_precision: u8,
+    /// Floating point package: `0` for hardware, `1` for the emulator and `2` for altmath.
+    pub float_package: u8,
+    /// Ambient memory model for data: `0` for near, `1` for far and `2` for huge.
+    pub ambient_data: u8,
+    /// Ambient memory model for code: `0` for near, `1` for far and `2` for huge.
+    pub ambient_code: u8,
+    /// Compiled for 32-bit addresses.
+    pub mode32: bool,
+    /// Display name of the compiler.
+    pub version_string: RawString<'t>,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for LegacyCompileFlagsSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let cpu_type = CPUType::from(u16::from(buf.parse_u8()?));
+        let language = buf.parse()?;
+        let flags = buf.parse_u16()?;
+
+        let symbol = LegacyCompileFlagsSymbol {
+            cpu_type,
+            language,
+            pcode: flags & 1 != 0,
+            float_precision: ((flags >> 1) & 0x3) as u8,
+            float_package: ((flags >> 3) & 0x3) as u8,
+            ambient_data: ((flags >> 5) & 0x7) as u8,
+            ambient_code: ((flags >> 8) & 0x7) as u8,
+            mode32: (flags >> 11) & 1 != 0,
+            version_string: parse_symbol_name(&mut buf, kind)?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// Demangles a Rust symbol name in the legacy or v0 mangling scheme.
+#[cfg(feature = "demangle")]
+fn demangle_rust(name: RawString<'_>) -> Option<String> {
+    let name = core::str::from_utf8(name.as_bytes()).ok()?;
+    let demangled = rustc_demangle::try_demangle(name).ok()?;
+    Some(alloc::format!("{:#}", demangled))
+}
+
 /// A using namespace directive.
 ///
 /// Symbol kind `S_UNAMESPACE`, or `S_UNAMESPACE_ST`.
@@ -1090,7 +1632,7 @@ const CV_LVARFLAG_ISOPTIMIZEDOUT: u16 = 0x80;
 const CV_LVARFLAG_ISENREG_GLOB: u16 = 0x100;
 const CV_LVARFLAG_ISENREG_STAT: u16 = 0x200;
 
-/// Flags for a [`LocalSymbol`].
+/// Flags for a [`LocalSymbol`] or [`FileStaticSymbol`].
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct LocalVariableFlags {
@@ -1169,53 +1711,340 @@ impl<'t> TryFromCtx<'t, SymbolKind> for LocalSymbol<'t> {
     }
 }
 
-// https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L4456
-/// Flags of an [`ExportSymbol`].
-#[non_exhaustive]
+/// A static variable scoped to a source file, such as a `static` global in C.
+///
+/// The location of the variable is described by the def-range symbols that follow, like for a
+/// [`LocalSymbol`].
+///
+/// Symbol kind `S_FILESTATIC`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct ExportSymbolFlags {
-    /// An exported constant.
-    pub constant: bool,
-    /// Exported data (e.g. a static variable).
-    pub data: bool,
-    /// A private symbol.
-    pub private: bool,
-    /// A symbol with no name.
-    pub no_name: bool,
-    /// Ordinal was explicitly assigned.
-    pub ordinal: bool,
-    /// This is a forwarder.
-    pub forwarder: bool,
+pub struct FileStaticSymbol<'t> {
+    /// The type of the variable.
+    pub type_index: TypeIndex,
+    /// Reference to the name of the source file declaring the variable.
+    ///
+    /// Resolve the name via the [`StringTable`](crate::StringTable) of the PDB.
+    pub file: StringRef,
+    /// Flags for this variable.
+    pub flags: LocalVariableFlags,
+    /// Name of the variable.
+    pub name: RawString<'t>,
 }
 
-impl<'t> TryFromCtx<'t, Endian> for ExportSymbolFlags {
-    type Error = scroll::Error;
+impl<'t> TryFromCtx<'t, SymbolKind> for FileStaticSymbol<'t> {
+    type Error = Error;
 
-    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
-        let (value, size) = u16::try_from_ctx(this, le)?;
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
 
-        let flags = Self {
-            constant: value & 0x01 != 0,
-            data: value & 0x02 != 0,
-            private: value & 0x04 != 0,
-            no_name: value & 0x08 != 0,
-            ordinal: value & 0x10 != 0,
-            forwarder: value & 0x20 != 0,
+        let symbol = FileStaticSymbol {
+            type_index: buf.parse()?,
+            file: buf.parse()?,
+            flags: buf.parse()?,
+            name: parse_symbol_name(&mut buf, kind)?,
         };
 
-        Ok((flags, size))
+        Ok((symbol, buf.pos()))
     }
 }
 
-/// An exported symbol.
-///
-/// Symbol kind `S_EXPORT`.
+/// A gap in a [`LiveRange`], in which the variable is not available.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct ExportSymbol<'t> {
-    /// Ordinal of the symbol.
-    pub ordinal: u16,
-    /// Flags declaring the type of the exported symbol.
-    pub flags: ExportSymbolFlags,
+pub struct LiveRangeGap {
+    /// Offset of the start of the gap, relative to the start of the range.
+    pub offset: u16,
+    /// Length of the gap in bytes.
+    pub length: u16,
+}
+
+/// An address range in which a local variable can be evaluated, with optional gaps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiveRange {
+    /// Start of the range.
+    pub start: PdbInternalSectionOffset,
+    /// Length of the range in bytes.
+    pub length: u16,
+    /// Sub-ranges in which the variable is not available, in ascending order.
+    pub gaps: Vec<LiveRangeGap>,
+}
+
+impl LiveRange {
+    /// Returns whether the variable is available at the given offset.
+    ///
+    /// This is `false` for offsets outside the range or inside one of its gaps.
+    pub fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        if offset.section != self.start.section || offset.offset < self.start.offset {
+            return false;
+        }
+
+        let relative = offset.offset - self.start.offset;
+        if relative >= u32::from(self.length) {
+            return false;
+        }
+
+        !self.gaps.iter().any(|gap| {
+            let gap_start = u32::from(gap.offset);
+            relative >= gap_start && relative < gap_start + u32::from(gap.length)
+        })
+    }
+}
+
+impl<'t> TryFromCtx<'t, Endian> for LiveRange {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: Endian) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let start = buf.parse()?;
+        let length = buf.parse()?;
+
+        // The gaps fill the remainder of the record.
+        let mut gaps = Vec::with_capacity(buf.len() / 4);
+        while buf.len() >= 4 {
+            gaps.push(LiveRangeGap {
+                offset: buf.parse()?,
+                length: buf.parse()?,
+            });
+        }
+
+        let range = LiveRange {
+            start,
+            length,
+            gaps,
+        };
+
+        Ok((range, buf.pos()))
+    }
+}
+
+// CV_RANGEATTR:
+const CV_RANGEATTR_MAYBE: u16 = 0x01;
+
+/// The location of a local variable within a [`DefRangeSymbol`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DefRangeLocation {
+    /// The variable is evaluated by a program in an external evaluator DLL.
+    ///
+    /// Symbol kind `S_DEFRANGE`.
+    Program {
+        /// Offset of the evaluation program in the DIA program string table.
+        program: u32,
+    },
+    /// A field of the variable is evaluated by a program in an external evaluator DLL.
+    ///
+    /// Symbol kind `S_DEFRANGE_SUBFIELD`.
+    SubField {
+        /// Offset of the evaluation program in the DIA program string table.
+        program: u32,
+        /// Offset of the field within the parent variable.
+        parent_offset: u32,
+    },
+    /// The variable is stored in a register.
+    ///
+    /// Symbol kind `S_DEFRANGE_REGISTER`.
+    Register {
+        /// The register holding the variable.
+        register: Register,
+        /// The variable may not be held in the register on all control flow paths.
+        may_have_no_name: bool,
+    },
+    /// A field of the variable is stored in a register.
+    ///
+    /// Symbol kind `S_DEFRANGE_SUBFIELD_REGISTER`.
+    SubFieldRegister {
+        /// The register holding the field.
+        register: Register,
+        /// The field may not be held in the register on all control flow paths.
+        may_have_no_name: bool,
+        /// Offset of the field within the parent variable.
+        parent_offset: u32,
+    },
+    /// The variable is stored on the stack, relative to the frame pointer.
+    ///
+    /// Symbol kind `S_DEFRANGE_FRAMEPOINTER_REL` or `S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE`.
+    FramePointerRelative {
+        /// Offset of the variable relative to the frame pointer.
+        offset: i32,
+    },
+    /// The variable is stored relative to a base register.
+    ///
+    /// Symbol kind `S_DEFRANGE_REGISTER_REL`.
+    RegisterRelative {
+        /// The base register.
+        register: Register,
+        /// Offset of the variable relative to the base register.
+        offset: i32,
+        /// The variable is a spilled member of a user defined type.
+        spilled_udt_member: bool,
+        /// Offset of the spilled member within the parent variable.
+        parent_offset: u16,
+    },
+}
+
+/// An address range and location in which the preceding [`LocalSymbol`] can be evaluated.
+///
+/// Symbol kind `S_DEFRANGE`, `S_DEFRANGE_SUBFIELD`, `S_DEFRANGE_REGISTER`,
+/// `S_DEFRANGE_FRAMEPOINTER_REL`, `S_DEFRANGE_SUBFIELD_REGISTER`,
+/// `S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE`, or `S_DEFRANGE_REGISTER_REL`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DefRangeSymbol {
+    /// Where the variable is stored.
+    pub location: DefRangeLocation,
+    /// The address range in which the location is valid.
+    ///
+    /// This is `None` if the location is valid for the full scope of the enclosing procedure.
+    pub range: Option<LiveRange>,
+}
+
+impl DefRangeSymbol {
+    /// Returns whether the location is valid at the given offset.
+    ///
+    /// Locations without a range are valid throughout the enclosing procedure, and always return
+    /// `true`.
+    pub fn contains(&self, offset: PdbInternalSectionOffset) -> bool {
+        self.range
+            .as_ref()
+            .map_or(true, |range| range.contains(offset))
+    }
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for DefRangeSymbol {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let location = match kind {
+            S_DEFRANGE => DefRangeLocation::Program {
+                program: buf.parse()?,
+            },
+            S_DEFRANGE_SUBFIELD => DefRangeLocation::SubField {
+                program: buf.parse()?,
+                parent_offset: buf.parse()?,
+            },
+            S_DEFRANGE_REGISTER => DefRangeLocation::Register {
+                register: buf.parse()?,
+                may_have_no_name: buf.parse::<u16>()? & CV_RANGEATTR_MAYBE != 0,
+            },
+            S_DEFRANGE_SUBFIELD_REGISTER => DefRangeLocation::SubFieldRegister {
+                register: buf.parse()?,
+                may_have_no_name: buf.parse::<u16>()? & CV_RANGEATTR_MAYBE != 0,
+                // The parent offset is stored in the lower 12 bits.
+                parent_offset: buf.parse::<u32>()? & 0xfff,
+            },
+            S_DEFRANGE_FRAMEPOINTER_REL | S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE => {
+                DefRangeLocation::FramePointerRelative {
+                    offset: buf.parse()?,
+                }
+            }
+            S_DEFRANGE_REGISTER_REL => {
+                let register = buf.parse()?;
+                // spilledUdtMember:1, padding:3, offsetParent:12
+                let flags = buf.parse::<u16>()?;
+                DefRangeLocation::RegisterRelative {
+                    register,
+                    offset: buf.parse()?,
+                    spilled_udt_member: flags & 0x1 != 0,
+                    parent_offset: flags >> 4,
+                }
+            }
+            _ => return Err(Error::UnimplementedSymbolKind(kind)),
+        };
+
+        let range = match kind {
+            S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE => None,
+            _ => Some(buf.parse()?),
+        };
+
+        Ok((DefRangeSymbol { location, range }, buf.pos()))
+    }
+}
+
+// https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L4456
+/// Flags of an [`ExportSymbol`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExportSymbolFlags {
+    /// An exported constant.
+    pub constant: bool,
+    /// Exported data (e.g. a static variable).
+    pub data: bool,
+    /// A private symbol.
+    pub private: bool,
+    /// A symbol with no name.
+    pub no_name: bool,
+    /// Ordinal was explicitly assigned.
+    pub ordinal: bool,
+    /// This is a forwarder.
+    pub forwarder: bool,
+}
+
+impl<'t> TryFromCtx<'t, Endian> for ExportSymbolFlags {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let (value, size) = u16::try_from_ctx(this, le)?;
+
+        let flags = Self {
+            constant: value & 0x01 != 0,
+            data: value & 0x02 != 0,
+            private: value & 0x04 != 0,
+            no_name: value & 0x08 != 0,
+            ordinal: value & 0x10 != 0,
+            forwarder: value & 0x20 != 0,
+        };
+
+        Ok((flags, size))
+    }
+}
+
+/// An exported symbol.
+///
+/// The linker records one of these for every entry of the export table in the symbols of the
+/// `* Linker *` module. Unlike the export directory of the PE file, they also retain private
+/// exports and exports without a name.
+///
+/// Symbol kind `S_EXPORT`.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// #
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+///
+/// let dbi = pdb.debug_information()?;
+/// let mut modules = dbi.modules()?;
+/// while let Some(module) = modules.next()? {
+///     if !matches!(module.kind(), pdb::ModuleKind::Linker(_)) {
+///         continue;
+///     }
+///
+///     let info = match pdb.module_info(&module)? {
+///         Some(info) => info,
+///         None => continue,
+///     };
+///
+///     let mut symbols = info.symbols()?;
+///     while let Some(symbol) = symbols.next()? {
+///         if let Ok(pdb::SymbolData::Export(export)) = symbol.parse() {
+///             println!("@{} {}", export.ordinal, export.name);
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExportSymbol<'t> {
+    /// Ordinal of the symbol.
+    pub ordinal: u16,
+    /// Flags declaring the type of the exported symbol.
+    pub flags: ExportSymbolFlags,
     /// The name of the exported symbol.
     pub name: RawString<'t>,
 }
@@ -1334,11 +2163,48 @@ impl<'t> TryFromCtx<'t, SymbolKind> for RegisterRelativeSymbol<'t> {
     }
 }
 
-/// Thunk adjustor
+/// A frame pointer relative symbol.
+///
+/// The address of the variable is the value of the frame pointer (e.g. %EBP) + offset.
+///
+/// Symbol kind `S_BPREL32`, or `S_BPREL32_ST`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BasePointerRelativeSymbol<'t> {
+    /// The variable offset.
+    pub offset: i32,
+    /// The type of the variable.
+    pub type_index: TypeIndex,
+    /// The variable name.
+    pub name: RawString<'t>,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for BasePointerRelativeSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], kind: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let symbol = BasePointerRelativeSymbol {
+            offset: buf.parse()?,
+            type_index: buf.parse()?,
+            name: parse_symbol_name(&mut buf, kind)?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// The payload of a `this` adjustor thunk.
+///
+/// Adjustor thunks are emitted for virtual functions of classes with multiple inheritance. They
+/// adjust the `this` pointer from a base class subobject to the derived class, and then jump to
+/// the target function.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ThunkAdjustor<'t> {
-    delta: u16,
-    target: RawString<'t>,
+    /// The number of bytes by which the `this` pointer is adjusted.
+    pub delta: i16,
+    /// The name of the function that the thunk jumps to after adjusting `this`.
+    pub target: RawString<'t>,
 }
 
 /// A thunk kind
@@ -1349,10 +2215,11 @@ pub enum ThunkKind<'t> {
     NoType,
     /// "this" adjustor thunk with delta and target
     Adjustor(ThunkAdjustor<'t>),
-    /// Virtual call thunk with table entry
+    /// Virtual call thunk, which calls the function at the given offset in the virtual function
+    /// table of the object.
     VCall(u16),
-    /// pcode thunk
-    PCode,
+    /// pcode thunk, with the address of the pcode function if it is recorded.
+    PCode(Option<PdbInternalSectionOffset>),
     /// thunk which loads the address to jump to via unknown means...
     Load,
     /// Unknown with ordinal value
@@ -1397,11 +2264,14 @@ impl<'t> TryFromCtx<'t, SymbolKind> for ThunkSymbol<'t> {
         let kind = match ord {
             0 => ThunkKind::NoType,
             1 => ThunkKind::Adjustor(ThunkAdjustor {
-                delta: buf.parse::<u16>()?,
-                target: buf.parse_cstring()?,
+                delta: buf.parse()?,
+                target: parse_symbol_name(&mut buf, kind)?,
             }),
-            2 => ThunkKind::VCall(buf.parse::<u16>()?),
-            3 => ThunkKind::PCode,
+            2 => ThunkKind::VCall(buf.parse()?),
+            // MSVC emits this ordinal for some native thunks, without the address of a pcode
+            // function and only followed by padding.
+            3 if buf.len() >= 6 => ThunkKind::PCode(Some(buf.parse()?)),
+            3 => ThunkKind::PCode(None),
             4 => ThunkKind::Load,
             ord => ThunkKind::Unknown(ord),
         };
@@ -1449,6 +2319,41 @@ impl<'t> TryFromCtx<'t, Endian> for SeparatedCodeFlags {
     }
 }
 
+/// A symbol defined by a tool vendor.
+///
+/// The vendor is identified by a GUID, and the layout of the payload is specific to the vendor.
+///
+/// Symbol kind `S_OEM`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OemSymbol<'t> {
+    /// The GUID identifying the vendor.
+    pub id: Uuid,
+    /// The type of the symbol.
+    pub type_index: TypeIndex,
+    /// The vendor-specific payload, including any padding at the end of the record.
+    pub data: &'t [u8],
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for OemSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let id = Uuid::from_slice_le(buf.take(16)?).map_err(|_| Error::UnexpectedEof)?;
+        let type_index = buf.parse()?;
+        let data = buf.take(buf.len())?;
+
+        let symbol = Self {
+            id,
+            type_index,
+            data,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
 /// A separated code symbol.
 ///
 /// Symbol kind `S_SEPCODE`.
@@ -1499,804 +2404,2834 @@ impl<'t> TryFromCtx<'t, SymbolKind> for SeparatedCodeSymbol {
     }
 }
 
-/// PDB symbol tables contain names, locations, and metadata about functions, global/static data,
-/// constants, data types, and more.
-///
-/// The `SymbolTable` holds a `SourceView` referencing the symbol table inside the PDB file. All the
-/// data structures returned by a `SymbolTable` refer to that buffer.
-///
-/// # Example
-///
-/// ```
-/// # use pdb::FallibleIterator;
-/// #
-/// # fn test() -> pdb::Result<usize> {
-/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(file)?;
-///
-/// let symbol_table = pdb.global_symbols()?;
-/// let address_map = pdb.address_map()?;
-///
-/// # let mut count: usize = 0;
-/// let mut symbols = symbol_table.iter();
-/// while let Some(symbol) = symbols.next()? {
-///     match symbol.parse() {
-///         Ok(pdb::SymbolData::Public(data)) if data.function => {
-///             // we found the location of a function!
-///             let rva = data.offset.to_rva(&address_map).unwrap_or_default();
-///             println!("{} is {}", rva, data.name);
-///             # count += 1;
-///         }
-///         _ => {}
-///     }
-/// }
+// FRAMEPROCSYM flags:
+const CV_FPFLAG_HAS_ALLOCA: u32 = 0x0000_0001;
+const CV_FPFLAG_HAS_SETJMP: u32 = 0x0000_0002;
+const CV_FPFLAG_HAS_LONGJMP: u32 = 0x0000_0004;
+const CV_FPFLAG_HAS_INLASM: u32 = 0x0000_0008;
+const CV_FPFLAG_HAS_EH: u32 = 0x0000_0010;
+const CV_FPFLAG_INL_SPEC: u32 = 0x0000_0020;
+const CV_FPFLAG_HAS_SEH: u32 = 0x0000_0040;
+const CV_FPFLAG_NAKED: u32 = 0x0000_0080;
+const CV_FPFLAG_SECURITY_CHECKS: u32 = 0x0000_0100;
+const CV_FPFLAG_ASYNC_EH: u32 = 0x0000_0200;
+const CV_FPFLAG_GS_NO_STACK_ORDERING: u32 = 0x0000_0400;
+const CV_FPFLAG_WAS_INLINED: u32 = 0x0000_0800;
+const CV_FPFLAG_GS_CHECK: u32 = 0x0000_1000;
+const CV_FPFLAG_SAFE_BUFFERS: u32 = 0x0000_2000;
+const CV_FPFLAG_LOCAL_BASE_POINTER_SHIFT: u32 = 14;
+const CV_FPFLAG_PARAM_BASE_POINTER_SHIFT: u32 = 16;
+const CV_FPFLAG_POGO_ON: u32 = 0x0004_0000;
+const CV_FPFLAG_VALID_COUNTS: u32 = 0x0008_0000;
+const CV_FPFLAG_OPT_SPEED: u32 = 0x0010_0000;
+const CV_FPFLAG_GUARD_CF: u32 = 0x0020_0000;
+const CV_FPFLAG_GUARD_CFW: u32 = 0x0040_0000;
+
+/// The register used to address locals or parameters, as encoded in [`FrameProcFlags`].
 ///
-/// # Ok(count)
-/// # }
-/// # assert!(test().expect("test") > 2000);
-/// ```
-#[derive(Debug)]
-pub struct SymbolTable<'s> {
-    stream: Stream<'s>,
+/// The actual register depends on the CPU type of the module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EncodedFramePointer {
+    /// No register is used.
+    None,
+    /// The stack pointer, `ESP` on x86 or `RSP` on x64.
+    StackPointer,
+    /// The frame pointer, `EBP` on x86 or `RBP` on x64.
+    FramePointer,
+    /// An alternate base register, `EBX` on x86 or `R13` on x64.
+    BaseRegister,
 }
 
-impl<'s> SymbolTable<'s> {
-    /// Parses a symbol table from raw stream data.
-    pub(crate) fn new(stream: Stream<'s>) -> Self {
-        SymbolTable { stream }
+impl From<u32> for EncodedFramePointer {
+    fn from(value: u32) -> Self {
+        match value & 0x3 {
+            0 => Self::None,
+            1 => Self::StackPointer,
+            2 => Self::FramePointer,
+            _ => Self::BaseRegister,
+        }
     }
+}
 
-    /// Returns an iterator that can traverse the symbol table in sequential order.
-    pub fn iter(&self) -> SymbolIter<'_> {
-        SymbolIter::new(self.stream.parse_buffer())
-    }
+/// Flags of a [`FrameProcSymbol`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameProcFlags {
+    /// Function uses `_alloca()`.
+    pub has_alloca: bool,
+    /// Function uses `setjmp()`.
+    pub has_setjmp: bool,
+    /// Function uses `longjmp()`.
+    pub has_longjmp: bool,
+    /// Function uses inline assembly.
+    pub has_inline_asm: bool,
+    /// Function has C++ exception handling states.
+    pub has_eh: bool,
+    /// Function was declared as inline.
+    pub inline_spec: bool,
+    /// Function has structured exception handling.
+    pub has_seh: bool,
+    /// Function is `__declspec(naked)`.
+    pub naked: bool,
+    /// Function has buffer security checks (`/GS`).
+    pub security_checks: bool,
+    /// Function was compiled with asynchronous exception handling (`/EHa`).
+    pub async_eh: bool,
+    /// Function has `/GS` buffer checks, but stack ordering could not be done.
+    pub gs_no_stack_ordering: bool,
+    /// Function was inlined within another function.
+    pub was_inlined: bool,
+    /// Function is `__declspec(strict_gs_check)`.
+    pub gs_check: bool,
+    /// Function is `__declspec(safebuffers)`.
+    pub safe_buffers: bool,
+    /// Register used to address local variables.
+    pub local_base_pointer: EncodedFramePointer,
+    /// Register used to address parameters.
+    pub param_base_pointer: EncodedFramePointer,
+    /// Function was compiled with profile guided optimization.
+    pub pogo_on: bool,
+    /// The profile guided optimization counts are valid.
+    pub valid_counts: bool,
+    /// Function was optimized for speed.
+    pub opt_speed: bool,
+    /// Function contains control flow guard checks.
+    pub guard_cf: bool,
+    /// Function contains control flow guard write checks.
+    pub guard_cfw: bool,
+}
 
-    /// Returns an iterator over symbols starting at the given index.
-    pub fn iter_at(&self, index: SymbolIndex) -> SymbolIter<'_> {
-        let mut iter = self.iter();
-        iter.seek(index);
-        iter
+impl<'t> TryFromCtx<'t, Endian> for FrameProcFlags {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'t [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let (value, size) = u32::try_from_ctx(this, le)?;
+
+        let flags = Self {
+            has_alloca: value & CV_FPFLAG_HAS_ALLOCA != 0,
+            has_setjmp: value & CV_FPFLAG_HAS_SETJMP != 0,
+            has_longjmp: value & CV_FPFLAG_HAS_LONGJMP != 0,
+            has_inline_asm: value & CV_FPFLAG_HAS_INLASM != 0,
+            has_eh: value & CV_FPFLAG_HAS_EH != 0,
+            inline_spec: value & CV_FPFLAG_INL_SPEC != 0,
+            has_seh: value & CV_FPFLAG_HAS_SEH != 0,
+            naked: value & CV_FPFLAG_NAKED != 0,
+            security_checks: value & CV_FPFLAG_SECURITY_CHECKS != 0,
+            async_eh: value & CV_FPFLAG_ASYNC_EH != 0,
+            gs_no_stack_ordering: value & CV_FPFLAG_GS_NO_STACK_ORDERING != 0,
+            was_inlined: value & CV_FPFLAG_WAS_INLINED != 0,
+            gs_check: value & CV_FPFLAG_GS_CHECK != 0,
+            safe_buffers: value & CV_FPFLAG_SAFE_BUFFERS != 0,
+            local_base_pointer: (value >> CV_FPFLAG_LOCAL_BASE_POINTER_SHIFT).into(),
+            param_base_pointer: (value >> CV_FPFLAG_PARAM_BASE_POINTER_SHIFT).into(),
+            pogo_on: value & CV_FPFLAG_POGO_ON != 0,
+            valid_counts: value & CV_FPFLAG_VALID_COUNTS != 0,
+            opt_speed: value & CV_FPFLAG_OPT_SPEED != 0,
+            guard_cf: value & CV_FPFLAG_GUARD_CF != 0,
+            guard_cfw: value & CV_FPFLAG_GUARD_CFW != 0,
+        };
+
+        Ok((flags, size))
     }
 }
 
-/// A `SymbolIter` iterates over a `SymbolTable`, producing `Symbol`s.
+/// Extra frame information of the enclosing procedure.
 ///
-/// Symbol tables are represented internally as a series of records, each of which have a length, a
-/// type, and a type-specific field layout. Iteration performance is therefore similar to a linked
-/// list.
-#[derive(Debug)]
-pub struct SymbolIter<'t> {
-    buf: ParseBuffer<'t>,
+/// Symbol kind `S_FRAMEPROC`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameProcSymbol {
+    /// Size of the total stack frame of the procedure in bytes.
+    pub frame_size: u32,
+    /// Size of the padding in the stack frame in bytes.
+    pub padding_size: u32,
+    /// Offset of the padding, relative to the frame pointer.
+    pub padding_offset: u32,
+    /// Size of the callee-saved registers in bytes.
+    pub saved_registers_size: u32,
+    /// Location of the exception handler.
+    pub exception_handler: PdbInternalSectionOffset,
+    /// Flags for this procedure.
+    pub flags: FrameProcFlags,
 }
 
-impl<'t> SymbolIter<'t> {
-    pub(crate) fn new(buf: ParseBuffer<'t>) -> SymbolIter<'t> {
-        SymbolIter { buf }
-    }
+impl<'t> TryFromCtx<'t, SymbolKind> for FrameProcSymbol {
+    type Error = Error;
 
-    /// Move the iterator to the symbol referred to by `index`.
-    ///
-    /// This can be used to jump to the sibiling or parent of a symbol record.
-    pub fn seek(&mut self, index: SymbolIndex) {
-        self.buf.seek(index.0 as usize);
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let symbol = FrameProcSymbol {
+            frame_size: buf.parse()?,
+            padding_size: buf.parse()?,
+            padding_offset: buf.parse()?,
+            saved_registers_size: buf.parse()?,
+            exception_handler: buf.parse()?,
+            flags: buf.parse()?,
+        };
+
+        Ok((symbol, buf.pos()))
     }
+}
 
-    /// Skip to the symbol referred to by `index`, returning the symbol.
+/// The type of the target of an indirect call.
+///
+/// Symbol kind `S_CALLSITEINFO`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CallSiteInfoSymbol {
+    /// Code offset of the call instruction.
+    pub offset: PdbInternalSectionOffset,
+    /// The function signature of the call target.
+    pub type_index: TypeIndex,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for CallSiteInfoSymbol {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let offset = buf.parse()?;
+        buf.parse_u16()?; // padding
+        let type_index = buf.parse()?;
+
+        let symbol = CallSiteInfoSymbol { offset, type_index };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// A call to a heap allocation function, such as `operator new`.
+///
+/// Symbol kind `S_HEAPALLOCSITE`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HeapAllocationSiteSymbol {
+    /// Code offset of the call instruction.
+    pub offset: PdbInternalSectionOffset,
+    /// Length of the call instruction in bytes.
+    pub instruction_length: u16,
+    /// The type of the allocation.
     ///
-    /// This can be used to jump to the sibiling or parent of a symbol record. Iteration continues
-    /// after that symbol.
+    /// For calls of `operator new`, this is the allocated type. Otherwise, it is the function
+    /// signature of the allocation function.
+    pub type_index: TypeIndex,
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for HeapAllocationSiteSymbol {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let symbol = HeapAllocationSiteSymbol {
+            offset: buf.parse()?,
+            instruction_length: buf.parse()?,
+            type_index: buf.parse()?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// The encoding of the entries in an [`ArmSwitchTableSymbol`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArmSwitchEntryKind {
+    /// Signed 8-bit offsets from the base.
+    Int8,
+    /// Unsigned 8-bit offsets from the base.
+    UInt8,
+    /// Signed 16-bit offsets from the base.
+    Int16,
+    /// Unsigned 16-bit offsets from the base.
+    UInt16,
+    /// Signed 32-bit offsets from the base.
+    Int32,
+    /// Unsigned 32-bit offsets from the base.
+    UInt32,
+    /// Absolute pointers to the targets.
+    Pointer,
+    /// Unsigned 8-bit offsets from the base, shifted left by one, as used by the Thumb `TBB`
+    /// instruction.
+    UInt8ShiftLeft,
+    /// Unsigned 16-bit offsets from the base, shifted left by one, as used by the Thumb `TBH`
+    /// instruction.
+    UInt16ShiftLeft,
+    /// Signed 8-bit offsets from the base, shifted left by one.
+    Int8ShiftLeft,
+    /// Signed 16-bit offsets from the base, shifted left by one.
+    Int16ShiftLeft,
+    /// An unknown entry kind with its raw value.
+    Unknown(u16),
+}
+
+impl ArmSwitchEntryKind {
+    /// Returns the size of a single table entry in bytes.
     ///
-    /// Note that the symbol may be located **before** the originating symbol, for instance when
-    /// jumping to the parent symbol. Take care not to enter an endless loop in this case.
-    pub fn skip_to(&mut self, index: SymbolIndex) -> Result<Option<Symbol<'t>>> {
-        self.seek(index);
-        self.next()
+    /// Returns `None` for pointers, whose size depends on the architecture, and for unknown kinds.
+    pub fn size(self) -> Option<u32> {
+        match self {
+            Self::Int8 | Self::UInt8 | Self::UInt8ShiftLeft | Self::Int8ShiftLeft => Some(1),
+            Self::Int16 | Self::UInt16 | Self::UInt16ShiftLeft | Self::Int16ShiftLeft => Some(2),
+            Self::Int32 | Self::UInt32 => Some(4),
+            Self::Pointer | Self::Unknown(_) => None,
+        }
     }
 }
 
-impl<'t> FallibleIterator for SymbolIter<'t> {
-    type Item = Symbol<'t>;
+impl From<u16> for ArmSwitchEntryKind {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::Int8,
+            1 => Self::UInt8,
+            2 => Self::Int16,
+            3 => Self::UInt16,
+            4 => Self::Int32,
+            5 => Self::UInt32,
+            6 => Self::Pointer,
+            7 => Self::UInt8ShiftLeft,
+            8 => Self::UInt16ShiftLeft,
+            9 => Self::Int8ShiftLeft,
+            10 => Self::Int16ShiftLeft,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// A jump table of a switch statement on ARM or ARM64.
+///
+/// The target of each entry is computed from the entry value and `base`, according to
+/// `entry_kind`.
+///
+/// Symbol kind `S_ARMSWITCHTABLE`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArmSwitchTableSymbol {
+    /// The base address that the table entries are relative to.
+    pub base: PdbInternalSectionOffset,
+    /// The encoding of the table entries.
+    pub entry_kind: ArmSwitchEntryKind,
+    /// Code offset of the branch instruction that uses the table.
+    pub branch: PdbInternalSectionOffset,
+    /// Offset of the start of the table.
+    pub table: PdbInternalSectionOffset,
+    /// The number of entries in the table.
+    pub entry_count: u32,
+}
+
+impl ArmSwitchTableSymbol {
+    /// Returns the size of the table in bytes, if the size of its entries is known.
+    pub fn table_size(&self) -> Option<u32> {
+        self.entry_kind
+            .size()
+            .and_then(|size| size.checked_mul(self.entry_count))
+    }
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for ArmSwitchTableSymbol {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let base = buf.parse()?;
+        let entry_kind = buf.parse::<u16>()?.into();
+        let branch_offset = buf.parse()?;
+        let table_offset = buf.parse()?;
+        let branch_section = buf.parse()?;
+        let table_section = buf.parse()?;
+
+        let symbol = ArmSwitchTableSymbol {
+            base,
+            entry_kind,
+            branch: PdbInternalSectionOffset::new(branch_section, branch_offset),
+            table: PdbInternalSectionOffset::new(table_section, table_offset),
+            entry_count: buf.parse()?,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// Strings attached to a code location.
+///
+/// The compiler emits this symbol for calls to the `__annotation` intrinsic, which embeds a list of
+/// string literals at the call site. Crash analysis tools use them to recover messages of failed
+/// assertions.
+///
+/// Symbol kind `S_ANNOTATION`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AnnotationSymbol<'t> {
+    /// Code offset of the annotated instruction.
+    pub offset: PdbInternalSectionOffset,
+    /// The number of strings in the annotation.
+    pub count: u16,
+    strings: &'t [u8],
+}
+
+impl<'t> AnnotationSymbol<'t> {
+    /// Returns an iterator over the strings of this annotation.
+    pub fn strings(&self) -> AnnotationStringIter<'t> {
+        AnnotationStringIter {
+            buf: ParseBuffer::from(self.strings),
+        }
+    }
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for AnnotationSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let offset = buf.parse()?;
+        let count = buf.parse_u16()?;
+
+        let start = buf.pos();
+        for _ in 0..count {
+            buf.parse_cstring()?;
+        }
+
+        let symbol = AnnotationSymbol {
+            offset,
+            count,
+            strings: &this[start..buf.pos()],
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// An iterator over the strings of an [`AnnotationSymbol`].
+#[derive(Clone, Debug, Default)]
+pub struct AnnotationStringIter<'t> {
+    buf: ParseBuffer<'t>,
+}
+
+impl<'t> FallibleIterator for AnnotationStringIter<'t> {
+    type Item = RawString<'t>;
     type Error = Error;
 
     fn next(&mut self) -> Result<Option<Self::Item>> {
-        while !self.buf.is_empty() {
-            let index = SymbolIndex(self.buf.pos() as u32);
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
 
-            // read the length of the next symbol
-            let symbol_length = self.buf.parse::<u16>()? as usize;
-            if symbol_length < 2 {
-                // this can't be correct
-                return Err(Error::SymbolTooShort);
-            }
+        self.buf.parse_cstring().map(Some)
+    }
+}
 
-            // grab the symbol itself
-            let data = self.buf.take(symbol_length)?;
-            let symbol = Symbol { index, data };
+/// The build environment of a module, as a list of key/value pairs.
+///
+/// Compilers record the environment of each object file, and the linker records its own in the
+/// `* Linker *` module. Common keys are `cwd` (working directory), `exe` (path of the compiler or
+/// linker), `pdb` (path of the PDB), `src` (source file) and `cmd` (command line arguments).
+///
+/// Symbol kind `S_ENVBLOCK`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EnvBlockSymbol<'t> {
+    /// Indicates that the module was compiled for edit and continue.
+    pub edit_and_continue: bool,
+    entries: &'t [u8],
+}
 
-            // skip over padding in the symbol table
-            match symbol.raw_kind() {
-                S_ALIGN | S_SKIP => continue,
-                _ => return Ok(Some(symbol)),
-            }
+impl<'t> EnvBlockSymbol<'t> {
+    /// Returns an iterator over the key/value pairs of the environment.
+    pub fn entries(&self) -> EnvBlockIter<'t> {
+        EnvBlockIter {
+            buf: ParseBuffer::from(self.entries),
+        }
+    }
+
+    /// Returns the value of the first entry with the given key.
+    pub fn get(&self, key: &str) -> Result<Option<RawString<'t>>> {
+        self.entries()
+            .find(|entry| Ok(entry.key.as_bytes() == key.as_bytes()))
+            .map(|entry| entry.map(|entry| entry.value))
+    }
+
+    /// Returns the working directory of the build.
+    pub fn cwd(&self) -> Result<Option<RawString<'t>>> {
+        self.get("cwd")
+    }
+
+    /// Returns the path of the compiler or linker executable.
+    pub fn exe(&self) -> Result<Option<RawString<'t>>> {
+        self.get("exe")
+    }
+
+    /// Returns the path of the PDB file.
+    pub fn pdb(&self) -> Result<Option<RawString<'t>>> {
+        self.get("pdb")
+    }
+
+    /// Returns the command line arguments, without the executable.
+    pub fn command_line(&self) -> Result<Option<RawString<'t>>> {
+        self.get("cmd")
+    }
+}
+
+impl<'t> TryFromCtx<'t, SymbolKind> for EnvBlockSymbol<'t> {
+    type Error = Error;
+
+    fn try_from_ctx(this: &'t [u8], _: SymbolKind) -> Result<(Self, usize)> {
+        let mut buf = ParseBuffer::from(this);
+
+        let flags = buf.parse_u8()?;
+        let entries = buf.take(buf.len())?;
+
+        let symbol = EnvBlockSymbol {
+            edit_and_continue: flags & 1 != 0,
+            entries,
+        };
+
+        Ok((symbol, buf.pos()))
+    }
+}
+
+/// A key/value pair of an [`EnvBlockSymbol`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EnvBlockEntry<'t> {
+    /// The name of the entry, such as `cwd`.
+    pub key: RawString<'t>,
+    /// The value of the entry.
+    pub value: RawString<'t>,
+}
+
+/// An iterator over the entries of an [`EnvBlockSymbol`] or the command block of a
+/// [`CompileFlagsSymbol`], see [`Symbol::compile_command_block`].
+#[derive(Clone, Debug, Default)]
+pub struct EnvBlockIter<'t> {
+    buf: ParseBuffer<'t>,
+}
+
+impl<'t> FallibleIterator for EnvBlockIter<'t> {
+    type Item = EnvBlockEntry<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        // The list is terminated by an empty key, and records may be padded after it.
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let key = self.buf.parse_cstring()?;
+        if key.is_empty() {
+            self.buf = ParseBuffer::default();
+            return Ok(None);
+        }
+
+        let value = self.buf.parse_cstring()?;
+        Ok(Some(EnvBlockEntry { key, value }))
+    }
+}
+
+/// PDB symbol tables contain names, locations, and metadata about functions, global/static data,
+/// constants, data types, and more.
+///
+/// The `SymbolTable` holds a `SourceView` referencing the symbol table inside the PDB file. All the
+/// data structures returned by a `SymbolTable` refer to that buffer.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// #
+/// # fn test() -> pdb::Result<usize> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+///
+/// let symbol_table = pdb.global_symbols()?;
+/// let address_map = pdb.address_map()?;
+///
+/// # let mut count: usize = 0;
+/// let mut symbols = symbol_table.iter();
+/// while let Some(symbol) = symbols.next()? {
+///     match symbol.parse() {
+///         Ok(pdb::SymbolData::Public(data)) if data.function => {
+///             // we found the location of a function!
+///             let rva = data.offset.to_rva(&address_map).unwrap_or_default();
+///             println!("{} is {}", rva, data.name);
+///             # count += 1;
+///         }
+///         _ => {}
+///     }
+/// }
+///
+/// # Ok(count)
+/// # }
+/// # assert!(test().expect("test") > 2000);
+/// ```
+#[derive(Debug)]
+pub struct SymbolTable<'s> {
+    index: StreamIndex,
+    stream: Stream<'s>,
+}
+
+impl<'s> SymbolTable<'s> {
+    /// Parses a symbol table from raw stream data.
+    pub(crate) fn new(index: StreamIndex, stream: Stream<'s>) -> Self {
+        SymbolTable { index, stream }
+    }
+
+    /// Returns the size of the symbol table in bytes.
+    pub(crate) fn stream_len(&self) -> usize {
+        self.stream.len()
+    }
+
+    /// Returns an iterator that can traverse the symbol table in sequential order.
+    pub fn iter(&self) -> SymbolIter<'_> {
+        SymbolIter::new(self.stream.parse_buffer())
+    }
+
+    /// Returns an iterator over symbols starting at the given index.
+    pub fn iter_at(&self, index: SymbolIndex) -> SymbolIter<'_> {
+        let mut iter = self.iter();
+        iter.seek(index);
+        iter
+    }
+
+    /// Returns an iterator over symbols resuming at the position saved in `cursor`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IndexMismatch` if the cursor was not created for this symbol table
+    pub fn iter_at_cursor(&self, cursor: &SymbolCursor) -> Result<SymbolIter<'_>> {
+        if cursor.module.is_some() || cursor.stream != self.index {
+            return Err(Error::IndexMismatch);
+        }
+
+        Ok(self.iter_at(cursor.symbol))
+    }
+
+    /// Searches the table for symbols matching `predicate`, within the limits of `budget`.
+    ///
+    /// The search starts at the beginning of the table, or at `start` to resume a previous search.
+    /// If the budget is exhausted before reaching the end of the table, the results contain a
+    /// continuation to resume from.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IndexMismatch` if `start` was returned by a search of a different table
+    /// * Errors reading symbols, for instance if the table is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let symbol_table = pdb.global_symbols()?;
+    ///
+    /// let budget = pdb::SearchBudget::default().with_max_results(10);
+    ///
+    /// let is_match = |symbol: &pdb::Symbol<'_>| match symbol.parse() {
+    ///     Ok(data) => data.name().is_some_and(|name| name.to_string().contains("main")),
+    ///     Err(_) => false,
+    /// };
+    ///
+    /// let mut continuation = None;
+    /// loop {
+    ///     let results = symbol_table.search(continuation, &budget, is_match)?;
+    ///     for symbol in &results.items {
+    ///         println!("{:?}", symbol.parse()?.name());
+    ///     }
+    ///
+    ///     continuation = results.continuation;
+    ///     if continuation.is_none() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn search<F>(
+        &self,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        predicate: F,
+    ) -> Result<SearchResults<Symbol<'_>>>
+    where
+        F: FnMut(&Symbol<'_>) -> bool,
+    {
+        self.search_symbols(None, start, budget, predicate)
+    }
+
+    /// Searches the table for symbols matching `predicate`, within the limits of `budget`, unless
+    /// cancelled.
+    ///
+    /// This behaves like [`search`](Self::search), but checks `token` before each symbol.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Cancelled` if `token` was cancelled before the search completed
+    /// * The errors of [`search`](Self::search)
+    pub fn search_cancellable<F>(
+        &self,
+        token: &CancellationToken,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        predicate: F,
+    ) -> Result<SearchResults<Symbol<'_>>>
+    where
+        F: FnMut(&Symbol<'_>) -> bool,
+    {
+        self.search_symbols(Some(token), start, budget, predicate)
+    }
+
+    fn search_symbols<F>(
+        &self,
+        token: Option<&CancellationToken>,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        mut predicate: F,
+    ) -> Result<SearchResults<Symbol<'_>>>
+    where
+        F: FnMut(&Symbol<'_>) -> bool,
+    {
+        if !budget.permits_results() {
+            return Ok(SearchResults {
+                items: Vec::new(),
+                continuation: None,
+            });
+        }
+
+        let mut iter = self.iter();
+        if let Some(start) = start {
+            start.validate(&self.stream)?;
+            iter.buf.seek(start.offset);
+        }
+
+        let mut tracker = BudgetTracker::new(*budget, iter.buf.pos());
+        let mut items = Vec::new();
+
+        loop {
+            let offset = iter.buf.pos();
+            if tracker.exhausted(offset, items.len()) {
+                let continuation = SearchContinuation::new(&self.stream, offset, 0);
+                return Ok(SearchResults {
+                    items,
+                    continuation: Some(continuation).filter(|_| !iter.buf.is_empty()),
+                });
+            }
+
+            if let Some(token) = token {
+                token.check()?;
+            }
+
+            match iter.next()? {
+                Some(symbol) if predicate(&symbol) => items.push(symbol),
+                Some(_) => (),
+                None => break,
+            }
+        }
+
+        Ok(SearchResults {
+            items,
+            continuation: None,
+        })
+    }
+
+    /// Returns an iterator over the constants in this table.
+    ///
+    /// Constants are emitted for `const` globals and `#define`-like values that the compiler kept,
+    /// as well as for enumerators. Symbols of other kinds are skipped without being parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<usize> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let symbol_table = pdb.global_symbols()?;
+    ///
+    /// let mut constants = symbol_table.constants();
+    /// # let mut count: usize = 0;
+    /// while let Some(constant) = constants.next()? {
+    ///     println!("{} = {}", constant.name, constant.value);
+    ///     # count += 1;
+    /// }
+    /// # Ok(count)
+    /// # }
+    /// # assert!(test().expect("test") > 0);
+    /// ```
+    pub fn constants(&self) -> ConstantSymbolIter<'_> {
+        ConstantSymbolIter {
+            symbols: self.iter(),
+        }
+    }
+
+    /// Returns an iterator over the public symbols in this table that match `filter`.
+    ///
+    /// Symbols of other kinds are skipped without being parsed.
+    pub fn publics(&self, filter: PublicSymbolFilter) -> PublicSymbolIter<'_> {
+        PublicSymbolIter {
+            symbols: self.iter(),
+            filter,
+            zero_address: DegenerateRecordPolicy::Keep,
+        }
+    }
+
+    /// Returns an iterator over the global variables, constants, user defined types and thread
+    /// storage in this table.
+    ///
+    /// This is meant for reconstructing the global variables of a program. Procedure references and
+    /// symbols of other kinds are skipped without being parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<usize> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let symbol_table = pdb.global_symbols()?;
+    ///
+    /// let mut globals = symbol_table.globals();
+    /// # let mut count: usize = 0;
+    /// while let Some(global) = globals.next()? {
+    ///     if let pdb::GlobalSymbol::Data(data) = global {
+    ///         println!("{} at {:?}", data.name, data.offset);
+    ///         # count += 1;
+    ///     }
+    /// }
+    /// # Ok(count)
+    /// # }
+    /// # assert!(test().expect("test") > 0);
+    /// ```
+    pub fn globals(&self) -> GlobalSymbols<'_> {
+        GlobalSymbols {
+            symbols: self.iter(),
+        }
+    }
+}
+
+/// An iterator over the [`ConstantSymbol`]s of a [`SymbolTable`], returned by
+/// [`SymbolTable::constants`].
+#[derive(Debug)]
+pub struct ConstantSymbolIter<'t> {
+    symbols: SymbolIter<'t>,
+}
+
+impl<'t> FallibleIterator for ConstantSymbolIter<'t> {
+    type Item = ConstantSymbol<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(symbol) = self.symbols.next()? {
+            if !matches!(
+                symbol.raw_kind(),
+                S_CONSTANT | S_CONSTANT_ST | S_MANCONSTANT
+            ) {
+                continue;
+            }
+
+            if let SymbolData::Constant(constant) = symbol.parse()? {
+                return Ok(Some(constant));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A data symbol of a [`SymbolTable`], returned by [`GlobalSymbols`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GlobalSymbol<'t> {
+    /// A global or static variable.
+    Data(DataSymbol<'t>),
+    /// A constant value.
+    Constant(ConstantSymbol<'t>),
+    /// A user defined type.
+    UserDefinedType(UserDefinedTypeSymbol<'t>),
+    /// A thread local variable.
+    ThreadStorage(ThreadStorageSymbol<'t>),
+}
+
+impl<'t> GlobalSymbol<'t> {
+    /// Returns the name of the symbol.
+    pub fn name(&self) -> RawString<'t> {
+        match self {
+            Self::Data(data) => data.name,
+            Self::Constant(data) => data.name,
+            Self::UserDefinedType(data) => data.name,
+            Self::ThreadStorage(data) => data.name,
+        }
+    }
+}
+
+/// An iterator over the data symbols of a [`SymbolTable`], returned by
+/// [`SymbolTable::globals`].
+#[derive(Debug)]
+pub struct GlobalSymbols<'t> {
+    symbols: SymbolIter<'t>,
+}
+
+impl<'t> FallibleIterator for GlobalSymbols<'t> {
+    type Item = GlobalSymbol<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(symbol) = self.symbols.next()? {
+            if !matches!(
+                symbol.raw_kind(),
+                S_LDATA32
+                    | S_LDATA32_ST
+                    | S_GDATA32
+                    | S_GDATA32_ST
+                    | S_LMANDATA
+                    | S_LMANDATA_ST
+                    | S_GMANDATA
+                    | S_GMANDATA_ST
+                    | S_CONSTANT
+                    | S_CONSTANT_ST
+                    | S_MANCONSTANT
+                    | S_UDT
+                    | S_UDT_ST
+                    | S_COBOLUDT
+                    | S_COBOLUDT_ST
+                    | S_LTHREAD32
+                    | S_LTHREAD32_ST
+                    | S_GTHREAD32
+                    | S_GTHREAD32_ST
+            ) {
+                continue;
+            }
+
+            let global = match symbol.parse()? {
+                SymbolData::Data(data) => GlobalSymbol::Data(data),
+                SymbolData::Constant(data) => GlobalSymbol::Constant(data),
+                SymbolData::UserDefinedType(data) => GlobalSymbol::UserDefinedType(data),
+                SymbolData::ThreadStorage(data) => GlobalSymbol::ThreadStorage(data),
+                _ => continue,
+            };
+
+            return Ok(Some(global));
+        }
+
+        Ok(None)
+    }
+}
+
+/// An iterator over the [`PublicSymbol`]s of a [`SymbolTable`], returned by
+/// [`SymbolTable::publics`].
+#[derive(Debug)]
+pub struct PublicSymbolIter<'t> {
+    symbols: SymbolIter<'t>,
+    filter: PublicSymbolFilter,
+    zero_address: DegenerateRecordPolicy,
+}
+
+impl PublicSymbolIter<'_> {
+    /// Sets how public symbols that do not point into any section are handled.
+    ///
+    /// Such symbols resolve to no address, or RVA 0 when translated naively. By default, they are
+    /// returned like any other public symbol.
+    pub fn zero_address(mut self, policy: DegenerateRecordPolicy) -> Self {
+        self.zero_address = policy;
+        self
+    }
+}
+
+impl<'t> FallibleIterator for PublicSymbolIter<'t> {
+    type Item = PublicSymbol<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(symbol) = self.symbols.next()? {
+            if !matches!(symbol.raw_kind(), S_PUB32 | S_PUB32_ST) {
+                continue;
+            }
+
+            if let SymbolData::Public(public) = symbol.parse()? {
+                if !self.filter.matches(&public) {
+                    continue;
+                }
+
+                if !public.offset.is_valid()
+                    && !self
+                        .zero_address
+                        .keep(DegenerateRecordKind::ZeroAddressPublic, public.offset)?
+                {
+                    continue;
+                }
+
+                return Ok(Some(public));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A position in the symbols of a PDB, at which processing can be resumed.
+///
+/// A cursor identifies either the global [`SymbolTable`] or the symbols of a module, and the index
+/// of the next symbol to process. Obtain the index via [`SymbolIter::position`] while iterating.
+/// Cursors can be serialized with [`to_bytes`](Self::to_bytes) to resume processing after a
+/// restart of the process.
+///
+/// Cursors are only valid for the stream and PDB they were created from. The serialized form
+/// records the [`index_identity`](crate::PDBInformation::index_identity) of the PDB, which is
+/// checked when loading the cursor, and resuming checks that the cursor refers to the stream being
+/// iterated.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+///
+/// // Process the first ten symbols of the first module and save a checkpoint.
+/// let dbi = pdb.debug_information()?;
+/// let module = dbi.modules()?.next()?.expect("module");
+/// let info = pdb.module_info(&module)?.expect("module info");
+/// let mut symbols = info.symbols()?;
+/// for _ in 0..10 {
+///     symbols.next()?;
+/// }
+/// let identity = pdb.pdb_information()?.index_identity();
+/// let checkpoint = pdb::SymbolCursor::module(0, &info, symbols.position()).to_bytes(identity);
+///
+/// // Later, resume where processing stopped.
+/// let cursor = pdb::SymbolCursor::from_bytes(&checkpoint, identity)?;
+/// let module = dbi.modules()?.nth(cursor.module.expect("module cursor"))?.expect("module");
+/// let info = pdb.module_info(&module)?.expect("module info");
+/// let mut symbols = info.symbols_at_cursor(&cursor)?;
+/// # assert!(symbols.next()?.is_some());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SymbolCursor {
+    /// Index of the module in [`DebugInformation::modules`](crate::DebugInformation::modules), or
+    /// `None` for the global symbol table.
+    pub module: Option<usize>,
+    /// Index of the stream containing the symbols.
+    pub stream: StreamIndex,
+    /// Index of the next symbol to process.
+    pub symbol: SymbolIndex,
+}
+
+impl SymbolCursor {
+    /// Creates a cursor into the global symbol table.
+    pub fn global(symbols: &SymbolTable<'_>, symbol: SymbolIndex) -> Self {
+        Self {
+            module: None,
+            stream: symbols.index,
+            symbol,
+        }
+    }
+
+    /// Creates a cursor into the symbols of the module at the given index, whose information is
+    /// `info`.
+    pub fn module(module: usize, info: &ModuleInfo<'_>, symbol: SymbolIndex) -> Self {
+        Self {
+            module: Some(module),
+            stream: info.stream_index(),
+            symbol,
+        }
+    }
+
+    /// Serializes this cursor for the PDB with the given identity.
+    ///
+    /// The representation is platform-independent and can be loaded again with
+    /// [`from_bytes`](Self::from_bytes).
+    pub fn to_bytes(&self, identity: PdbIdentity) -> Vec<u8> {
+        // Module indexes are stored as u16 in the DBI stream, so they always fit into a u32.
+        let module = self.module.map_or(u32::MAX, |module| module as u32);
+
+        let mut payload = Vec::with_capacity(10);
+        payload.extend_from_slice(&module.to_le_bytes());
+        payload.extend_from_slice(&self.stream.0.to_le_bytes());
+        payload.extend_from_slice(&self.symbol.0.to_le_bytes());
+        write_index(IndexKind::SymbolCursor, identity, &payload)
+    }
+
+    /// Deserializes a cursor created by [`to_bytes`](Self::to_bytes) for the PDB with the given
+    /// identity.
+    ///
+    /// The stream of the cursor is validated when resuming iteration with
+    /// [`SymbolTable::iter_at_cursor`] or
+    /// [`ModuleInfo::symbols_at_cursor`](crate::ModuleInfo::symbols_at_cursor).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidIndex` if the data is not a serialized cursor
+    /// * `Error::IndexMismatch` if the cursor was created for a different PDB
+    pub fn from_bytes(bytes: &[u8], identity: PdbIdentity) -> Result<Self> {
+        let mut buf = read_index(bytes, IndexKind::SymbolCursor, identity)?;
+        let module = match buf.parse_u32()? {
+            u32::MAX => None,
+            module => Some(module as usize),
+        };
+        let stream = StreamIndex(buf.parse_u16()?);
+        let symbol = buf.parse()?;
+
+        if !buf.is_empty() {
+            return Err(Error::InvalidIndex);
+        }
+
+        Ok(Self {
+            module,
+            stream,
+            symbol,
+        })
+    }
+}
+
+/// A `SymbolIter` iterates over a `SymbolTable`, producing `Symbol`s.
+///
+/// Symbol tables are represented internally as a series of records, each of which have a length, a
+/// type, and a type-specific field layout. Iteration performance is therefore similar to a linked
+/// list.
+#[derive(Debug)]
+pub struct SymbolIter<'t> {
+    buf: ParseBuffer<'t>,
+}
+
+impl<'t> SymbolIter<'t> {
+    pub(crate) fn new(buf: ParseBuffer<'t>) -> SymbolIter<'t> {
+        SymbolIter { buf }
+    }
+
+    /// Move the iterator to the symbol referred to by `index`.
+    ///
+    /// This can be used to jump to the sibiling or parent of a symbol record.
+    pub fn seek(&mut self, index: SymbolIndex) {
+        self.buf.seek(index.0 as usize);
+    }
+
+    /// Returns the index of the symbol that will be returned next.
+    ///
+    /// Together with the module being iterated, this can be stored in a [`SymbolCursor`] to resume
+    /// iteration later via [`seek`](Self::seek).
+    pub fn position(&self) -> SymbolIndex {
+        SymbolIndex(self.buf.pos() as u32)
+    }
+
+    /// Skip to the symbol referred to by `index`, returning the symbol.
+    ///
+    /// This can be used to jump to the sibiling or parent of a symbol record. Iteration continues
+    /// after that symbol.
+    ///
+    /// Note that the symbol may be located **before** the originating symbol, for instance when
+    /// jumping to the parent symbol. Take care not to enter an endless loop in this case.
+    pub fn skip_to(&mut self, index: SymbolIndex) -> Result<Option<Symbol<'t>>> {
+        self.seek(index);
+        self.next()
+    }
+
+    /// Groups local variables with the address ranges in which they can be evaluated.
+    ///
+    /// Iteration starts at the current position of this iterator. To list the variables of a
+    /// single procedure, [`seek`](Self::seek) to the procedure first and stop once the returned
+    /// variables are past its `end`.
+    pub fn variables(self) -> VariableIter<'t> {
+        VariableIter { symbols: self }
+    }
+}
+
+impl<'t> FallibleIterator for SymbolIter<'t> {
+    type Item = Symbol<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while !self.buf.is_empty() {
+            let index = SymbolIndex(self.buf.pos() as u32);
+
+            // read the length of the next symbol
+            let symbol_length = self.buf.parse::<u16>()? as usize;
+            if symbol_length < 2 {
+                // this can't be correct
+                return Err(Error::SymbolTooShort);
+            }
+
+            // grab the symbol itself
+            let data = self.buf.take(symbol_length)?;
+            let symbol = Symbol { index, data };
+
+            // skip over padding in the symbol table
+            match symbol.raw_kind() {
+                S_ALIGN | S_SKIP => continue,
+                _ => return Ok(Some(symbol)),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A local variable in optimized code, together with the locations in which it can be evaluated.
+///
+/// Returned by [`VariableIter`], which groups an [`S_LOCAL`](LocalSymbol) record with the
+/// `S_DEFRANGE_*` records following it. This is the only place def-ranges are grouped; convert into
+/// a [`VariableLocation`](crate::VariableLocation) to treat the variable like any other.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Variable<'t> {
+    /// The index of the `S_LOCAL` symbol declaring the variable.
+    pub index: SymbolIndex,
+    /// The declaration of the variable.
+    pub local: LocalSymbol<'t>,
+    /// Locations of the variable and the address ranges in which they are valid.
+    ///
+    /// If ranges overlap, each of the locations holds (a field of) the variable.
+    pub ranges: Vec<DefRangeSymbol>,
+}
+
+impl Variable<'_> {
+    /// Returns the locations of the variable that are valid at the given offset.
+    ///
+    /// If the returned iterator is empty, the variable is not available at this offset.
+    pub fn locations_at(
+        &self,
+        offset: PdbInternalSectionOffset,
+    ) -> impl Iterator<Item = &DefRangeLocation> + '_ {
+        self.ranges
+            .iter()
+            .filter(move |range| range.contains(offset))
+            .map(|range| &range.location)
+    }
+}
+
+/// An iterator over the [`Variable`]s in a symbol stream, returned by [`SymbolIter::variables`].
+///
+/// Symbols other than `S_LOCAL` and `S_DEFRANGE_*` are skipped without being parsed. Range records
+/// of unsupported kinds, such as `S_DEFRANGE_HLSL`, are skipped as well.
+#[derive(Debug)]
+pub struct VariableIter<'t> {
+    symbols: SymbolIter<'t>,
+}
+
+impl<'t> FallibleIterator for VariableIter<'t> {
+    type Item = Variable<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let (index, local) = loop {
+            let symbol = match self.symbols.next()? {
+                Some(symbol) => symbol,
+                None => return Ok(None),
+            };
+
+            if symbol.raw_kind() != S_LOCAL {
+                continue;
+            }
+
+            if let SymbolData::Local(local) = symbol.parse()? {
+                break (symbol.index(), local);
+            }
+        };
+
+        let mut ranges = Vec::new();
+        while let Some(symbol) = self.symbols.next()? {
+            if !symbol.is_def_range() {
+                // This symbol may start the next variable, so revisit it on the next call.
+                self.symbols.seek(symbol.index());
+                break;
+            }
+
+            if let SymbolData::DefRange(range) = symbol.parse()? {
+                ranges.push(range);
+            }
+        }
+
+        Ok(Some(Variable {
+            index,
+            local,
+            ranges,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod parsing {
+        use crate::symbol::*;
+
+        #[test]
+        fn kind_0001() {
+            let data = &[
+                1, 0, 3, 0, 2, 8, 11, 77, 105, 99, 114, 111, 115, 111, 102, 116, 32, 67,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x0001);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::LegacyCompileFlags(LegacyCompileFlagsSymbol {
+                    cpu_type: CPUType::Intel80386,
+                    language: SourceLanguage::C,
+                    pcode: false,
+                    float_precision: 1,
+                    float_package: 0,
+                    ambient_data: 0,
+                    ambient_code: 0,
+                    mode32: true,
+                    version_string: "Microsoft C".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_0006() {
+            let data = &[6, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x0006);
+            assert_eq!(symbol.parse().expect("parse"), SymbolData::ScopeEnd);
+        }
+
+        #[test]
+        fn kind_0404() {
+            let data = &[
+                4, 4, 0xa5, 0x3f, 0x3c, 0x2b, 0x2e, 0x5a, 0xb8, 0x44, 0x8b, 0xba, 0xc3, 0x30, 0x0f,
+                0xf6, 0x9f, 0x62, 0x74, 0, 0, 0, 1, 2, 3, 4,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x0404);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Oem(OemSymbol {
+                    id: "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap(),
+                    type_index: TypeIndex(0x74),
+                    data: &[1, 2, 3, 4],
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1101() {
+            let data = &[1, 17, 0, 0, 0, 0, 42, 32, 67, 73, 76, 32, 42, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1101);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::ObjName(ObjNameSymbol {
+                    signature: 0,
+                    name: "* CIL *".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1102() {
+            let data = &[
+                2, 17, 0, 0, 0, 0, 108, 22, 0, 0, 0, 0, 0, 0, 140, 11, 0, 0, 1, 0, 9, 0, 3, 91,
+                116, 104, 117, 110, 107, 93, 58, 68, 101, 114, 105, 118, 101, 100, 58, 58, 70, 117,
+                110, 99, 49, 96, 97, 100, 106, 117, 115, 116, 111, 114, 123, 56, 125, 39, 0, 0, 0,
+                0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1102);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Thunk(ThunkSymbol {
+                    parent: None,
+                    end: SymbolIndex(0x166c),
+                    next: None,
+                    offset: PdbInternalSectionOffset {
+                        section: 0x1,
+                        offset: 0xb8c
+                    },
+                    len: 9,
+                    kind: ThunkKind::PCode(None),
+                    name: "[thunk]:Derived::Func1`adjustor{8}'".into()
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1102_adjustor() {
+            let data = &[
+                2, 17, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 1, 0, 5, 0, 1, b'f', 0,
+                0xf8, 0xff, b'g', 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Thunk(ThunkSymbol {
+                    parent: None,
+                    end: SymbolIndex(0x100),
+                    next: None,
+                    offset: PdbInternalSectionOffset {
+                        section: 0x1,
+                        offset: 0x10
+                    },
+                    len: 5,
+                    kind: ThunkKind::Adjustor(ThunkAdjustor {
+                        delta: -8,
+                        target: "g".into(),
+                    }),
+                    name: "f".into()
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1102_vcall() {
+            let data = &[
+                2, 17, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 1, 0, 6, 0, 2, b'f', 0,
+                0x18, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            match symbol.parse().expect("parse") {
+                SymbolData::Thunk(thunk) => assert_eq!(thunk.kind, ThunkKind::VCall(0x18)),
+                data => panic!("unexpected symbol {:?}", data),
+            }
+        }
+
+        #[test]
+        fn kind_1102_pcode() {
+            let data = &[
+                2, 17, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 1, 0, 6, 0, 3, b'f', 0,
+                0x40, 0x12, 0, 0, 2, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            match symbol.parse().expect("parse") {
+                SymbolData::Thunk(thunk) => assert_eq!(
+                    thunk.kind,
+                    ThunkKind::PCode(Some(PdbInternalSectionOffset {
+                        section: 0x2,
+                        offset: 0x1240
+                    }))
+                ),
+                data => panic!("unexpected symbol {:?}", data),
+            }
+        }
+
+        #[test]
+        fn kind_1105() {
+            let data = &[
+                5, 17, 224, 95, 151, 0, 1, 0, 0, 100, 97, 118, 49, 100, 95, 119, 95, 97, 118, 103,
+                95, 115, 115, 115, 101, 51, 0, 0, 0, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1105);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Label(LabelSymbol {
+                    offset: PdbInternalSectionOffset {
+                        offset: 0x0097_5fe0,
+                        section: 1
+                    },
+                    flags: ProcedureFlags {
+                        nofpo: false,
+                        int: false,
+                        far: false,
+                        never: false,
+                        notreached: false,
+                        cust_call: false,
+                        noinline: false,
+                        optdbginfo: false
+                    },
+                    name: "dav1d_w_avg_ssse3".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1106() {
+            let data = &[6, 17, 120, 34, 0, 0, 18, 0, 116, 104, 105, 115, 0, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1106);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::RegisterVariable(RegisterVariableSymbol {
+                    type_index: TypeIndex(8824),
+                    register: Register(18),
+                    name: "this".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_110e() {
+            let data = &[
+                14, 17, 2, 0, 0, 0, 192, 85, 0, 0, 1, 0, 95, 95, 108, 111, 99, 97, 108, 95, 115,
+                116, 100, 105, 111, 95, 112, 114, 105, 110, 116, 102, 95, 111, 112, 116, 105, 111,
+                110, 115, 0, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x110e);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Public(PublicSymbol {
+                    code: false,
+                    function: true,
+                    managed: false,
+                    msil: false,
+                    offset: PdbInternalSectionOffset {
+                        offset: 21952,
+                        section: 1
+                    },
+                    name: "__local_stdio_printf_options".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1111() {
+            let data = &[
+                17, 17, 12, 0, 0, 0, 48, 16, 0, 0, 22, 0, 109, 97, 120, 105, 109, 117, 109, 95, 99,
+                111, 117, 110, 116, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1111);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::RegisterRelative(RegisterRelativeSymbol {
+                    offset: 12,
+                    type_index: TypeIndex(0x1030),
+                    register: Register(22),
+                    name: "maximum_count".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_110b() {
+            let data = &[
+                11, 17, 248, 255, 255, 255, 116, 0, 0, 0, 97, 114, 103, 99, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x110b);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::BasePointerRelative(BasePointerRelativeSymbol {
+                    offset: -8,
+                    type_index: TypeIndex(0x74),
+                    name: "argc".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1124() {
+            let data = &[36, 17, 115, 116, 100, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1124);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::UsingNamespace(UsingNamespaceSymbol { name: "std".into() })
+            );
+        }
+
+        #[test]
+        fn kind_1125() {
+            let data = &[
+                37, 17, 0, 0, 0, 0, 108, 0, 0, 0, 1, 0, 66, 97, 122, 58, 58, 102, 95, 112, 117, 98,
+                108, 105, 99, 0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1125);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::ProcedureReference(ProcedureReferenceSymbol {
+                    global: true,
+                    sum_name: 0,
+                    symbol_index: SymbolIndex(108),
+                    module: Some(0),
+                    name: Some("Baz::f_public".into()),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1108() {
+            let data = &[8, 17, 112, 6, 0, 0, 118, 97, 95, 108, 105, 115, 116, 0];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1108);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::UserDefinedType(UserDefinedTypeSymbol {
+                    type_index: TypeIndex(1648),
+                    name: "va_list".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1107() {
+            let data = &[
+                7, 17, 201, 18, 0, 0, 1, 0, 95, 95, 73, 83, 65, 95, 65, 86, 65, 73, 76, 65, 66, 76,
+                69, 95, 83, 83, 69, 50, 0, 0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1107);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Constant(ConstantSymbol {
+                    managed: false,
+                    type_index: TypeIndex(4809),
+                    value: Variant::U16(1),
+                    name: "__ISA_AVAILABLE_SSE2".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_110d() {
+            let data = &[
+                13, 17, 116, 0, 0, 0, 16, 0, 0, 0, 3, 0, 95, 95, 105, 115, 97, 95, 97, 118, 97,
+                105, 108, 97, 98, 108, 101, 0, 0, 0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x110d);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Data(DataSymbol {
+                    global: true,
+                    managed: false,
+                    type_index: TypeIndex(116),
+                    offset: PdbInternalSectionOffset {
+                        offset: 16,
+                        section: 3
+                    },
+                    name: "__isa_available".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_110c() {
+            let data = &[
+                12, 17, 32, 0, 0, 0, 240, 36, 1, 0, 2, 0, 36, 120, 100, 97, 116, 97, 115, 121, 109,
+                0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x110c);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Data(DataSymbol {
+                    global: false,
+                    managed: false,
+                    type_index: TypeIndex(32),
+                    offset: PdbInternalSectionOffset {
+                        offset: 74992,
+                        section: 2
+                    },
+                    name: "$xdatasym".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1127() {
+            let data = &[
+                39, 17, 0, 0, 0, 0, 128, 4, 0, 0, 182, 0, 99, 97, 112, 116, 117, 114, 101, 95, 99,
+                117, 114, 114, 101, 110, 116, 95, 99, 111, 110, 116, 101, 120, 116, 0, 0, 0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1127);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::ProcedureReference(ProcedureReferenceSymbol {
+                    global: false,
+                    sum_name: 0,
+                    symbol_index: SymbolIndex(1152),
+                    module: Some(181),
+                    name: Some("capture_current_context".into()),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_112c() {
+            let data = &[44, 17, 0, 0, 5, 0, 5, 0, 0, 0, 32, 124, 0, 0, 2, 0, 2, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+
+            assert_eq!(symbol.raw_kind(), 0x112c);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Trampoline(TrampolineSymbol {
+                    tramp_type: TrampolineType::Incremental,
+                    size: 0x5,
+                    thunk: PdbInternalSectionOffset {
+                        offset: 0x5,
+                        section: 0x2
+                    },
+                    target: PdbInternalSectionOffset {
+                        offset: 0x7c20,
+                        section: 0x2
+                    },
+                })
+            );
+        }
+
+        #[test]
+        fn kind_1110() {
+            let data = &[
+                16, 17, 0, 0, 0, 0, 48, 2, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 5, 0, 0, 0, 5, 0, 0, 0, 7,
+                16, 0, 0, 64, 85, 0, 0, 1, 0, 0, 66, 97, 122, 58, 58, 102, 95, 112, 114, 111, 116,
+                101, 99, 116, 101, 100, 0,
+            ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1110);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Procedure(ProcedureSymbol {
+                    global: true,
+                    dpc: false,
+                    parent: None,
+                    end: SymbolIndex(560),
+                    next: None,
+                    len: 6,
+                    dbg_start_offset: 5,
+                    dbg_end_offset: 5,
+                    type_index: TypeIndex(4103),
+                    offset: PdbInternalSectionOffset {
+                        offset: 21824,
+                        section: 1
+                    },
+                    flags: ProcedureFlags {
+                        nofpo: false,
+                        int: false,
+                        far: false,
+                        never: false,
+                        notreached: false,
+                        cust_call: false,
+                        noinline: false,
+                        optdbginfo: false
+                    },
+                    name: "Baz::f_protected".into(),
+                })
+            );
         }
 
-        Ok(None)
-    }
-}
+        #[test]
+        fn kind_1103() {
+            let data = &[
+                3, 17, 244, 149, 9, 0, 40, 151, 9, 0, 135, 1, 0, 0, 108, 191, 184, 2, 1, 0, 0, 0,
+            ];
 
-#[cfg(test)]
-mod tests {
-    mod parsing {
-        use crate::symbol::*;
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1103);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Block(BlockSymbol {
+                    parent: SymbolIndex(0x0009_95f4),
+                    end: SymbolIndex(0x0009_9728),
+                    len: 391,
+                    offset: PdbInternalSectionOffset {
+                        section: 0x1,
+                        offset: 0x02b8_bf6c
+                    },
+                    name: "".into(),
+                })
+            );
+        }
 
         #[test]
-        fn kind_0006() {
-            let data = &[6, 0];
-
+        fn kind_110f() {
+            let data = &[
+                15, 17, 0, 0, 0, 0, 156, 1, 0, 0, 0, 0, 0, 0, 18, 0, 0, 0, 4, 0, 0, 0, 9, 0, 0, 0,
+                128, 16, 0, 0, 196, 87, 0, 0, 1, 0, 128, 95, 95, 115, 99, 114, 116, 95, 99, 111,
+                109, 109, 111, 110, 95, 109, 97, 105, 110, 0, 0, 0,
+            ];
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x0006);
-            assert_eq!(symbol.parse().expect("parse"), SymbolData::ScopeEnd);
+            assert_eq!(symbol.raw_kind(), 0x110f);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Procedure(ProcedureSymbol {
+                    global: false,
+                    dpc: false,
+                    parent: None,
+                    end: SymbolIndex(412),
+                    next: None,
+                    len: 18,
+                    dbg_start_offset: 4,
+                    dbg_end_offset: 9,
+                    type_index: TypeIndex(4224),
+                    offset: PdbInternalSectionOffset {
+                        offset: 22468,
+                        section: 1
+                    },
+                    flags: ProcedureFlags {
+                        nofpo: false,
+                        int: false,
+                        far: false,
+                        never: false,
+                        notreached: false,
+                        cust_call: false,
+                        noinline: false,
+                        optdbginfo: true
+                    },
+                    name: "__scrt_common_main".into(),
+                })
+            );
         }
 
         #[test]
-        fn kind_1101() {
-            let data = &[1, 17, 0, 0, 0, 0, 42, 32, 67, 73, 76, 32, 42, 0];
+        fn kind_1116() {
+            let data = &[
+                22, 17, 7, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 14, 0, 10, 0, 115, 98, 77, 105, 99,
+                114, 111, 115, 111, 102, 116, 32, 40, 82, 41, 32, 76, 73, 78, 75, 0, 0, 0, 0,
+            ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1101);
+            assert_eq!(symbol.raw_kind(), 0x1116);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::ObjName(ObjNameSymbol {
-                    signature: 0,
-                    name: "* CIL *".into(),
+                SymbolData::CompileFlags(CompileFlagsSymbol {
+                    language: SourceLanguage::Link,
+                    flags: CompileFlags {
+                        edit_and_continue: false,
+                        no_debug_info: false,
+                        link_time_codegen: false,
+                        no_data_align: false,
+                        managed: false,
+                        security_checks: false,
+                        hot_patch: false,
+                        cvtcil: false,
+                        msil_module: false,
+                        sdl: false,
+                        pgo: false,
+                        exp_module: false,
+                    },
+                    cpu_type: CPUType::Intel80386,
+                    frontend_version: CompilerVersion {
+                        major: 0,
+                        minor: 0,
+                        build: 0,
+                        qfe: None,
+                    },
+                    backend_version: CompilerVersion {
+                        major: 14,
+                        minor: 10,
+                        build: 25203,
+                        qfe: None,
+                    },
+                    version_string: "Microsoft (R) LINK".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_1102() {
+        fn kind_1116_command_block() {
             let data = &[
-                2, 17, 0, 0, 0, 0, 108, 22, 0, 0, 0, 0, 0, 0, 140, 11, 0, 0, 1, 0, 9, 0, 3, 91,
-                116, 104, 117, 110, 107, 93, 58, 68, 101, 114, 105, 118, 101, 100, 58, 58, 70, 117,
-                110, 99, 49, 96, 97, 100, 106, 117, 115, 116, 111, 114, 123, 56, 125, 39, 0, 0, 0,
-                0,
+                22, 17, 1, 0, 0, 0, 3, 0, 13, 0, 0, 0, 0, 0, 13, 0, 0, 0, 0, 0, 99, 108, 0, 99,
+                119, 100, 0, 99, 58, 92, 0, 99, 109, 100, 0, 45, 90, 105, 0, 0, 0,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1102);
+            assert_eq!(symbol.raw_kind(), 0x1116);
+
+            let flags = match symbol.parse().expect("parse") {
+                SymbolData::CompileFlags(flags) => flags,
+                _ => panic!("expected compile flags"),
+            };
+            assert_eq!(flags.language, SourceLanguage::Cpp);
+            assert_eq!(flags.version_string, "cl".into());
+
+            let entries: Vec<_> = symbol
+                .compile_command_block()
+                .expect("command block")
+                .collect()
+                .expect("collect");
+            assert_eq!(
+                entries,
+                [
+                    EnvBlockEntry {
+                        key: "cwd".into(),
+                        value: "c:\\".into(),
+                    },
+                    EnvBlockEntry {
+                        key: "cmd".into(),
+                        value: "-Zi".into(),
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn compile_command_block_without_pairs() {
+            let compile3 = Symbol {
+                data: &[
+                    60, 17, 1, 36, 2, 0, 7, 0, 19, 0, 13, 0, 6, 102, 0, 0, 19, 0, 13, 0, 6, 102, 0,
+                    0, 0,
+                ],
+                index: SymbolIndex(0),
+            };
+            let mut entries = compile3.compile_command_block().expect("command block");
+            assert_eq!(entries.next().expect("next"), None);
+
+            let local = Symbol {
+                data: &[62, 17, 193, 19, 0, 0, 1, 0, 116, 104, 105, 115, 0, 0],
+                index: SymbolIndex(0),
+            };
+            assert!(matches!(
+                local.compile_command_block(),
+                Err(Error::UnimplementedSymbolKind(0x113e))
+            ));
+        }
+
+        #[test]
+        fn kind_1132() {
+            let data = &[
+                50, 17, 0, 0, 0, 0, 108, 0, 0, 0, 88, 0, 0, 0, 0, 0, 0, 0, 196, 252, 10, 0, 56, 67,
+                0, 0, 1, 0, 1, 0,
+            ];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1132);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Thunk(ThunkSymbol {
-                    parent: None,
-                    end: SymbolIndex(0x166c),
-                    next: None,
+                SymbolData::SeparatedCode(SeparatedCodeSymbol {
+                    parent: SymbolIndex(0x0),
+                    end: SymbolIndex(0x6c),
+                    len: 88,
+                    flags: SeparatedCodeFlags {
+                        islexicalscope: false,
+                        returnstoparent: false
+                    },
                     offset: PdbInternalSectionOffset {
                         section: 0x1,
-                        offset: 0xb8c
+                        offset: 0xafcc4
                     },
-                    len: 9,
-                    kind: ThunkKind::PCode,
-                    name: "[thunk]:Derived::Func1`adjustor{8}'".into()
+                    parent_offset: PdbInternalSectionOffset {
+                        section: 0x1,
+                        offset: 0x4338
+                    }
                 })
             );
         }
 
         #[test]
-        fn kind_1105() {
+        fn kind_113c() {
             let data = &[
-                5, 17, 224, 95, 151, 0, 1, 0, 0, 100, 97, 118, 49, 100, 95, 119, 95, 97, 118, 103,
-                95, 115, 115, 115, 101, 51, 0, 0, 0, 0,
+                60, 17, 1, 36, 2, 0, 7, 0, 19, 0, 13, 0, 6, 102, 0, 0, 19, 0, 13, 0, 6, 102, 0, 0,
+                77, 105, 99, 114, 111, 115, 111, 102, 116, 32, 40, 82, 41, 32, 79, 112, 116, 105,
+                109, 105, 122, 105, 110, 103, 32, 67, 111, 109, 112, 105, 108, 101, 114, 0,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1105);
+            assert_eq!(symbol.raw_kind(), 0x113c);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Label(LabelSymbol {
-                    offset: PdbInternalSectionOffset {
-                        offset: 0x0097_5fe0,
-                        section: 1
+                SymbolData::CompileFlags(CompileFlagsSymbol {
+                    language: SourceLanguage::Cpp,
+                    flags: CompileFlags {
+                        edit_and_continue: false,
+                        no_debug_info: false,
+                        link_time_codegen: true,
+                        no_data_align: false,
+                        managed: false,
+                        security_checks: true,
+                        hot_patch: false,
+                        cvtcil: false,
+                        msil_module: false,
+                        sdl: true,
+                        pgo: false,
+                        exp_module: false,
                     },
-                    flags: ProcedureFlags {
-                        nofpo: false,
-                        int: false,
-                        far: false,
-                        never: false,
-                        notreached: false,
-                        cust_call: false,
-                        noinline: false,
-                        optdbginfo: false
+                    cpu_type: CPUType::Pentium3,
+                    frontend_version: CompilerVersion {
+                        major: 19,
+                        minor: 13,
+                        build: 26118,
+                        qfe: Some(0),
+                    },
+                    backend_version: CompilerVersion {
+                        major: 19,
+                        minor: 13,
+                        build: 26118,
+                        qfe: Some(0),
+                    },
+                    version_string: "Microsoft (R) Optimizing Compiler".into(),
+                })
+            );
+        }
+
+        #[test]
+        fn kind_113e() {
+            let data = &[62, 17, 193, 19, 0, 0, 1, 0, 116, 104, 105, 115, 0, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x113e);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Local(LocalSymbol {
+                    type_index: TypeIndex(5057),
+                    flags: LocalVariableFlags {
+                        isparam: true,
+                        addrtaken: false,
+                        compgenx: false,
+                        isaggregate: false,
+                        isaliased: false,
+                        isalias: false,
+                        isretvalue: false,
+                        isoptimizedout: false,
+                        isenreg_glob: false,
+                        isenreg_stat: false,
                     },
-                    name: "dav1d_w_avg_ssse3".into(),
+                    name: "this".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_1106() {
-            let data = &[6, 17, 120, 34, 0, 0, 18, 0, 116, 104, 105, 115, 0, 0];
+        fn kind_114c() {
+            let data = &[76, 17, 95, 17, 0, 0];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1106);
+            assert_eq!(symbol.raw_kind(), 0x114c);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::RegisterVariable(RegisterVariableSymbol {
-                    type_index: TypeIndex(8824),
-                    register: Register(18),
-                    name: "this".into(),
+                SymbolData::BuildInfo(BuildInfoSymbol {
+                    id: IdIndex(0x115F)
                 })
             );
         }
 
         #[test]
-        fn kind_110e() {
+        fn kind_114d() {
             let data = &[
-                14, 17, 2, 0, 0, 0, 192, 85, 0, 0, 1, 0, 95, 95, 108, 111, 99, 97, 108, 95, 115,
-                116, 100, 105, 111, 95, 112, 114, 105, 110, 116, 102, 95, 111, 112, 116, 105, 111,
-                110, 115, 0, 0,
+                77, 17, 144, 1, 0, 0, 208, 1, 0, 0, 121, 17, 0, 0, 12, 6, 3, 0,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x110e);
+            assert_eq!(symbol.raw_kind(), 0x114d);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Public(PublicSymbol {
-                    code: false,
-                    function: true,
-                    managed: false,
-                    msil: false,
-                    offset: PdbInternalSectionOffset {
-                        offset: 21952,
-                        section: 1
-                    },
-                    name: "__local_stdio_printf_options".into(),
+                SymbolData::InlineSite(InlineSiteSymbol {
+                    parent: Some(SymbolIndex(0x0190)),
+                    end: SymbolIndex(0x01d0),
+                    inlinee: IdIndex(4473),
+                    invocations: None,
+                    annotations: BinaryAnnotations::new(&[12, 6, 3, 0]),
                 })
             );
         }
 
         #[test]
-        fn kind_1111() {
+        fn kind_114e() {
+            let data = &[78, 17];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x114e);
+            assert_eq!(symbol.parse().expect("parse"), SymbolData::InlineSiteEnd);
+        }
+
+        #[test]
+        fn kind_1159() {
             let data = &[
-                17, 17, 12, 0, 0, 0, 48, 16, 0, 0, 22, 0, 109, 97, 120, 105, 109, 117, 109, 95, 99,
-                111, 117, 110, 116, 0,
+                89, 17, 0, 16, 0, 0, 1, 0, 7, 0, 36, 16, 0, 0, 40, 16, 0, 0, 1, 0, 1, 0, 5, 0, 0, 0,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1111);
+            assert_eq!(symbol.raw_kind(), 0x1159);
+
+            let table = match symbol.parse().expect("parse") {
+                SymbolData::ArmSwitchTable(table) => table,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::RegisterRelative(RegisterRelativeSymbol {
-                    offset: 12,
-                    type_index: TypeIndex(0x1030),
-                    register: Register(22),
-                    name: "maximum_count".into(),
-                })
+                table,
+                ArmSwitchTableSymbol {
+                    base: PdbInternalSectionOffset {
+                        section: 1,
+                        offset: 0x1000
+                    },
+                    entry_kind: ArmSwitchEntryKind::UInt8ShiftLeft,
+                    branch: PdbInternalSectionOffset {
+                        section: 1,
+                        offset: 0x1024
+                    },
+                    table: PdbInternalSectionOffset {
+                        section: 1,
+                        offset: 0x1028
+                    },
+                    entry_count: 5,
+                }
             );
+            assert_eq!(table.table_size(), Some(5));
         }
 
         #[test]
-        fn kind_1124() {
-            let data = &[36, 17, 115, 116, 100, 0];
+        fn kind_1019() {
+            let data = &[
+                25, 16, 16, 0, 0, 0, 1, 0, 2, 0, 65, 115, 115, 101, 114, 116, 0, 120, 32, 62, 32,
+                48, 0,
+            ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1124);
+            assert_eq!(symbol.raw_kind(), 0x1019);
+
+            let annotation = match symbol.parse().expect("parse") {
+                SymbolData::Annotation(annotation) => annotation,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::UsingNamespace(UsingNamespaceSymbol { name: "std".into() })
+                annotation.offset,
+                PdbInternalSectionOffset {
+                    section: 1,
+                    offset: 0x10
+                }
+            );
+            assert_eq!(annotation.count, 2);
+
+            let strings: Vec<_> = annotation.strings().collect().expect("strings");
+            assert_eq!(
+                strings,
+                [RawString::from("Assert"), RawString::from("x > 0")]
             );
         }
 
         #[test]
-        fn kind_1125() {
+        fn kind_113d() {
             let data = &[
-                37, 17, 0, 0, 0, 0, 108, 0, 0, 0, 1, 0, 66, 97, 122, 58, 58, 102, 95, 112, 117, 98,
-                108, 105, 99, 0,
+                61, 17, 0, 99, 119, 100, 0, 67, 58, 92, 115, 114, 99, 0, 101, 120, 101, 0, 108,
+                105, 110, 107, 46, 101, 120, 101, 0, 99, 109, 100, 0, 32, 47, 68, 69, 66, 85, 71,
+                0, 0, 0,
             ];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1125);
+            assert_eq!(symbol.raw_kind(), 0x113d);
+
+            let env = match symbol.parse().expect("parse") {
+                SymbolData::EnvBlock(env) => env,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+
+            assert!(!env.edit_and_continue);
+
+            let entries: Vec<_> = env.entries().collect().expect("entries");
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::ProcedureReference(ProcedureReferenceSymbol {
-                    global: true,
-                    sum_name: 0,
-                    symbol_index: SymbolIndex(108),
-                    module: Some(0),
-                    name: Some("Baz::f_public".into()),
-                })
+                entries,
+                [
+                    EnvBlockEntry {
+                        key: "cwd".into(),
+                        value: "C:\\src".into(),
+                    },
+                    EnvBlockEntry {
+                        key: "exe".into(),
+                        value: "link.exe".into(),
+                    },
+                    EnvBlockEntry {
+                        key: "cmd".into(),
+                        value: " /DEBUG".into(),
+                    },
+                ]
             );
+
+            assert_eq!(env.cwd().expect("cwd"), Some("C:\\src".into()));
+            assert_eq!(env.command_line().expect("cmd"), Some(" /DEBUG".into()));
+            assert_eq!(env.pdb().expect("pdb"), None);
         }
 
         #[test]
-        fn kind_1108() {
-            let data = &[8, 17, 112, 6, 0, 0, 118, 97, 95, 108, 105, 115, 116, 0];
+        fn kind_1138() {
+            let data = &[56, 17, 3, 0, 22, 0, 102, 111, 111, 0];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1108);
+            assert_eq!(symbol.raw_kind(), 0x1138);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::UserDefinedType(UserDefinedTypeSymbol {
-                    type_index: TypeIndex(1648),
-                    name: "va_list".into(),
+                SymbolData::Export(ExportSymbol {
+                    ordinal: 3,
+                    flags: ExportSymbolFlags {
+                        constant: false,
+                        data: true,
+                        private: true,
+                        no_name: false,
+                        ordinal: true,
+                        forwarder: false,
+                    },
+                    name: "foo".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_1107() {
+        fn kind_1153() {
             let data = &[
-                7, 17, 201, 18, 0, 0, 1, 0, 95, 95, 73, 83, 65, 95, 65, 86, 65, 73, 76, 65, 66, 76,
-                69, 95, 83, 83, 69, 50, 0, 0,
+                83, 17, 116, 0, 0, 0, 42, 0, 0, 0, 0, 2, 99, 111, 117, 110, 116, 101, 114, 0,
             ];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1107);
+            assert_eq!(symbol.raw_kind(), 0x1153);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Constant(ConstantSymbol {
-                    managed: false,
-                    type_index: TypeIndex(4809),
-                    value: Variant::U16(1),
-                    name: "__ISA_AVAILABLE_SSE2".into(),
+                SymbolData::FileStatic(FileStaticSymbol {
+                    type_index: TypeIndex(0x74),
+                    file: StringRef(0x2a),
+                    flags: LocalVariableFlags {
+                        isparam: false,
+                        addrtaken: false,
+                        compgenx: false,
+                        isaggregate: false,
+                        isaliased: false,
+                        isalias: false,
+                        isretvalue: false,
+                        isoptimizedout: false,
+                        isenreg_glob: false,
+                        isenreg_stat: true,
+                    },
+                    name: "counter".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_110d() {
+        fn kind_115a() {
             let data = &[
-                13, 17, 116, 0, 0, 0, 16, 0, 0, 0, 3, 0, 95, 95, 105, 115, 97, 95, 97, 118, 97,
-                105, 108, 97, 98, 108, 101, 0, 0, 0,
+                90, 17, 2, 0, 0, 0, 3, 16, 0, 0, 4, 16, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0,
             ];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x110d);
+            assert_eq!(symbol.raw_kind(), 0x115a);
+
+            let list = match symbol.parse().expect("parse") {
+                SymbolData::FunctionList(list) => list,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+            assert_eq!(list.kind, FunctionListKind::Callees);
+            assert_eq!(list.count, 2);
+
+            let functions: Vec<_> = list.functions().collect().expect("collect");
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::Data(DataSymbol {
-                    global: true,
-                    managed: false,
-                    type_index: TypeIndex(116),
-                    offset: PdbInternalSectionOffset {
-                        offset: 16,
-                        section: 3
+                functions,
+                [
+                    FunctionListEntry {
+                        function: IdIndex(0x1003),
+                        invocations: Some(7),
                     },
-                    name: "__isa_available".into(),
-                })
+                    FunctionListEntry {
+                        function: IdIndex(0x1004),
+                        invocations: Some(0),
+                    },
+                ]
             );
         }
 
         #[test]
-        fn kind_110c() {
-            let data = &[
-                12, 17, 32, 0, 0, 0, 240, 36, 1, 0, 2, 0, 36, 120, 100, 97, 116, 97, 115, 121, 109,
-                0,
-            ];
+        fn kind_115b_truncated() {
+            let data = &[91, 17, 2, 0, 0, 0, 3, 16, 0, 0, 4, 16, 0, 0, 9, 0, 0, 0];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x110c);
+            assert_eq!(symbol.raw_kind(), 0x115b);
+
+            let list = match symbol.parse().expect("parse") {
+                SymbolData::FunctionList(list) => list,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+            assert_eq!(list.kind, FunctionListKind::Callers);
+
+            let invocations: Vec<_> = list
+                .functions()
+                .map(|entry| Ok(entry.invocations))
+                .collect()
+                .expect("collect");
+            assert_eq!(invocations, [Some(9), Some(0)]);
+        }
+
+        #[test]
+        fn kind_1168() {
+            let data = &[104, 17, 1, 0, 0, 0, 18, 16, 0, 0];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1168);
+
+            let list = match symbol.parse().expect("parse") {
+                SymbolData::FunctionList(list) => list,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+            assert_eq!(list.kind, FunctionListKind::Inlinees);
+
+            let functions: Vec<_> = list.functions().collect().expect("collect");
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::Data(DataSymbol {
-                    global: false,
-                    managed: false,
-                    type_index: TypeIndex(32),
-                    offset: PdbInternalSectionOffset {
-                        offset: 74992,
-                        section: 2
-                    },
-                    name: "$xdatasym".into(),
-                })
+                functions,
+                [FunctionListEntry {
+                    function: IdIndex(0x1012),
+                    invocations: None,
+                }]
             );
         }
 
         #[test]
-        fn kind_1127() {
-            let data = &[
-                39, 17, 0, 0, 0, 0, 128, 4, 0, 0, 182, 0, 99, 97, 112, 116, 117, 114, 101, 95, 99,
-                117, 114, 114, 101, 110, 116, 95, 99, 111, 110, 116, 101, 120, 116, 0, 0, 0,
-            ];
+        fn kind_1139() {
+            let data = &[57, 17, 32, 1, 0, 0, 1, 0, 0, 0, 3, 16, 0, 0];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1127);
+            assert_eq!(symbol.raw_kind(), 0x1139);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::ProcedureReference(ProcedureReferenceSymbol {
-                    global: false,
-                    sum_name: 0,
-                    symbol_index: SymbolIndex(1152),
-                    module: Some(181),
-                    name: Some("capture_current_context".into()),
+                SymbolData::CallSiteInfo(CallSiteInfoSymbol {
+                    offset: PdbInternalSectionOffset {
+                        offset: 0x120,
+                        section: 1
+                    },
+                    type_index: TypeIndex(0x1003),
                 })
             );
         }
 
         #[test]
-        fn kind_112c() {
-            let data = &[44, 17, 0, 0, 5, 0, 5, 0, 0, 0, 32, 124, 0, 0, 2, 0, 2, 0];
+        fn kind_115e() {
+            let data = &[94, 17, 64, 2, 0, 0, 1, 0, 5, 0, 17, 16, 0, 0];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-
-            assert_eq!(symbol.raw_kind(), 0x112c);
+            assert_eq!(symbol.raw_kind(), 0x115e);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Trampoline(TrampolineSymbol {
-                    tramp_type: TrampolineType::Incremental,
-                    size: 0x5,
-                    thunk: PdbInternalSectionOffset {
-                        offset: 0x5,
-                        section: 0x2
-                    },
-                    target: PdbInternalSectionOffset {
-                        offset: 0x7c20,
-                        section: 0x2
+                SymbolData::HeapAllocationSite(HeapAllocationSiteSymbol {
+                    offset: PdbInternalSectionOffset {
+                        offset: 0x240,
+                        section: 1
                     },
+                    instruction_length: 5,
+                    type_index: TypeIndex(0x1011),
                 })
             );
         }
 
         #[test]
-        fn kind_1110() {
+        fn kind_1012() {
             let data = &[
-                16, 17, 0, 0, 0, 0, 48, 2, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 5, 0, 0, 0, 5, 0, 0, 0, 7,
-                16, 0, 0, 64, 85, 0, 0, 1, 0, 0, 66, 97, 122, 58, 58, 102, 95, 112, 114, 111, 116,
-                101, 99, 116, 101, 100, 0,
+                18, 16, 40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 80,
+                2, 0,
             ];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1110);
+            assert_eq!(symbol.raw_kind(), 0x1012);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Procedure(ProcedureSymbol {
-                    global: true,
-                    dpc: false,
-                    parent: None,
-                    end: SymbolIndex(560),
-                    next: None,
-                    len: 6,
-                    dbg_start_offset: 5,
-                    dbg_end_offset: 5,
-                    type_index: TypeIndex(4103),
-                    offset: PdbInternalSectionOffset {
-                        offset: 21824,
-                        section: 1
+                SymbolData::FrameProc(FrameProcSymbol {
+                    frame_size: 40,
+                    padding_size: 0,
+                    padding_offset: 0,
+                    saved_registers_size: 8,
+                    exception_handler: PdbInternalSectionOffset {
+                        offset: 0,
+                        section: 0
                     },
-                    flags: ProcedureFlags {
-                        nofpo: false,
-                        int: false,
-                        far: false,
-                        never: false,
-                        notreached: false,
-                        cust_call: false,
-                        noinline: false,
-                        optdbginfo: false
+                    flags: FrameProcFlags {
+                        has_alloca: false,
+                        has_setjmp: false,
+                        has_longjmp: false,
+                        has_inline_asm: false,
+                        has_eh: false,
+                        inline_spec: false,
+                        has_seh: false,
+                        naked: false,
+                        security_checks: false,
+                        async_eh: false,
+                        gs_no_stack_ordering: false,
+                        was_inlined: false,
+                        gs_check: true,
+                        safe_buffers: false,
+                        local_base_pointer: EncodedFramePointer::StackPointer,
+                        param_base_pointer: EncodedFramePointer::FramePointer,
+                        pogo_on: false,
+                        valid_counts: false,
+                        opt_speed: false,
+                        guard_cf: false,
+                        guard_cfw: false,
                     },
-                    name: "Baz::f_protected".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_1103() {
+        fn unknown_kind() {
+            let data = &[0xff, 0x7f, 0x01, 0x02, 0x03];
+
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x7fff);
+            assert_eq!(
+                symbol.parse().expect("parse"),
+                SymbolData::Unknown {
+                    kind: 0x7fff,
+                    data: &[0x01, 0x02, 0x03],
+                }
+            );
+        }
+    }
+
+    mod def_range {
+        use crate::symbol::*;
+
+        fn range(gaps: Vec<LiveRangeGap>) -> LiveRange {
+            LiveRange {
+                start: PdbInternalSectionOffset::new(1, 0x10),
+                length: 0x20,
+                gaps,
+            }
+        }
+
+        #[test]
+        fn kind_1141() {
             let data = &[
-                3, 17, 244, 149, 9, 0, 40, 151, 9, 0, 135, 1, 0, 0, 108, 191, 184, 2, 1, 0, 0, 0,
+                0x41, 0x11, 0x11, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00,
+                0x04, 0x00, 0x02, 0x00,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1103);
+            assert_eq!(symbol.raw_kind(), 0x1141);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Block(BlockSymbol {
-                    parent: SymbolIndex(0x0009_95f4),
-                    end: SymbolIndex(0x0009_9728),
-                    len: 391,
-                    offset: PdbInternalSectionOffset {
-                        section: 0x1,
-                        offset: 0x02b8_bf6c
+                SymbolData::DefRange(DefRangeSymbol {
+                    location: DefRangeLocation::Register {
+                        register: Register(17),
+                        may_have_no_name: false,
                     },
-                    name: "".into(),
+                    range: Some(range(vec![LiveRangeGap {
+                        offset: 4,
+                        length: 2
+                    }])),
                 })
             );
         }
 
         #[test]
-        fn kind_110f() {
+        fn kind_1142() {
             let data = &[
-                15, 17, 0, 0, 0, 0, 156, 1, 0, 0, 0, 0, 0, 0, 18, 0, 0, 0, 4, 0, 0, 0, 9, 0, 0, 0,
-                128, 16, 0, 0, 196, 87, 0, 0, 1, 0, 128, 95, 95, 115, 99, 114, 116, 95, 99, 111,
-                109, 109, 111, 110, 95, 109, 97, 105, 110, 0, 0, 0,
+                0x42, 0x11, 0xf0, 0xff, 0xff, 0xff, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00,
             ];
+
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x110f);
+            assert_eq!(symbol.raw_kind(), 0x1142);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::Procedure(ProcedureSymbol {
-                    global: false,
-                    dpc: false,
-                    parent: None,
-                    end: SymbolIndex(412),
-                    next: None,
-                    len: 18,
-                    dbg_start_offset: 4,
-                    dbg_end_offset: 9,
-                    type_index: TypeIndex(4224),
-                    offset: PdbInternalSectionOffset {
-                        offset: 22468,
-                        section: 1
-                    },
-                    flags: ProcedureFlags {
-                        nofpo: false,
-                        int: false,
-                        far: false,
-                        never: false,
-                        notreached: false,
-                        cust_call: false,
-                        noinline: false,
-                        optdbginfo: true
-                    },
-                    name: "__scrt_common_main".into(),
+                SymbolData::DefRange(DefRangeSymbol {
+                    location: DefRangeLocation::FramePointerRelative { offset: -16 },
+                    range: Some(range(vec![])),
                 })
             );
         }
 
         #[test]
-        fn kind_1116() {
+        fn kind_1143() {
             let data = &[
-                22, 17, 7, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 14, 0, 10, 0, 115, 98, 77, 105, 99,
-                114, 111, 115, 111, 102, 116, 32, 40, 82, 41, 32, 76, 73, 78, 75, 0, 0, 0, 0,
+                0x43, 0x11, 0x12, 0x00, 0x01, 0x00, 0x08, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+                0x01, 0x00, 0x20, 0x00,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1116);
+            assert_eq!(symbol.raw_kind(), 0x1143);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::CompileFlags(CompileFlagsSymbol {
-                    language: SourceLanguage::Link,
-                    flags: CompileFlags {
-                        edit_and_continue: false,
-                        no_debug_info: false,
-                        link_time_codegen: false,
-                        no_data_align: false,
-                        managed: false,
-                        security_checks: false,
-                        hot_patch: false,
-                        cvtcil: false,
-                        msil_module: false,
-                        sdl: false,
-                        pgo: false,
-                        exp_module: false,
+                SymbolData::DefRange(DefRangeSymbol {
+                    location: DefRangeLocation::SubFieldRegister {
+                        register: Register(18),
+                        may_have_no_name: true,
+                        parent_offset: 8,
                     },
-                    cpu_type: CPUType::Intel80386,
-                    frontend_version: CompilerVersion {
-                        major: 0,
-                        minor: 0,
-                        build: 0,
-                        qfe: None,
-                    },
-                    backend_version: CompilerVersion {
-                        major: 14,
-                        minor: 10,
-                        build: 25203,
-                        qfe: None,
-                    },
-                    version_string: "Microsoft (R) LINK".into(),
+                    range: Some(range(vec![])),
                 })
             );
         }
 
         #[test]
-        fn kind_1132() {
-            let data = &[
-                50, 17, 0, 0, 0, 0, 108, 0, 0, 0, 88, 0, 0, 0, 0, 0, 0, 0, 196, 252, 10, 0, 56, 67,
-                0, 0, 1, 0, 1, 0,
-            ];
+        fn kind_1144() {
+            let data = &[0x44, 0x11, 0x08, 0x00, 0x00, 0x00];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x1132);
+            assert_eq!(symbol.raw_kind(), 0x1144);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::SeparatedCode(SeparatedCodeSymbol {
-                    parent: SymbolIndex(0x0),
-                    end: SymbolIndex(0x6c),
-                    len: 88,
-                    flags: SeparatedCodeFlags {
-                        islexicalscope: false,
-                        returnstoparent: false
-                    },
-                    offset: PdbInternalSectionOffset {
-                        section: 0x1,
-                        offset: 0xafcc4
-                    },
-                    parent_offset: PdbInternalSectionOffset {
-                        section: 0x1,
-                        offset: 0x4338
-                    }
+                SymbolData::DefRange(DefRangeSymbol {
+                    location: DefRangeLocation::FramePointerRelative { offset: 8 },
+                    range: None,
                 })
             );
         }
 
         #[test]
-        fn kind_113c() {
+        fn kind_1145() {
             let data = &[
-                60, 17, 1, 36, 2, 0, 7, 0, 19, 0, 13, 0, 6, 102, 0, 0, 19, 0, 13, 0, 6, 102, 0, 0,
-                77, 105, 99, 114, 111, 115, 111, 102, 116, 32, 40, 82, 41, 32, 79, 112, 116, 105,
-                109, 105, 122, 105, 110, 103, 32, 67, 111, 109, 112, 105, 108, 101, 114, 0,
+                0x45, 0x11, 0x4f, 0x01, 0x81, 0x00, 0x28, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+                0x01, 0x00, 0x20, 0x00,
             ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x113c);
+            assert_eq!(symbol.raw_kind(), 0x1145);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::CompileFlags(CompileFlagsSymbol {
-                    language: SourceLanguage::Cpp,
-                    flags: CompileFlags {
-                        edit_and_continue: false,
-                        no_debug_info: false,
-                        link_time_codegen: true,
-                        no_data_align: false,
-                        managed: false,
-                        security_checks: true,
-                        hot_patch: false,
-                        cvtcil: false,
-                        msil_module: false,
-                        sdl: true,
-                        pgo: false,
-                        exp_module: false,
-                    },
-                    cpu_type: CPUType::Pentium3,
-                    frontend_version: CompilerVersion {
-                        major: 19,
-                        minor: 13,
-                        build: 26118,
-                        qfe: Some(0),
-                    },
-                    backend_version: CompilerVersion {
-                        major: 19,
-                        minor: 13,
-                        build: 26118,
-                        qfe: Some(0),
+                SymbolData::DefRange(DefRangeSymbol {
+                    location: DefRangeLocation::RegisterRelative {
+                        register: Register(335),
+                        offset: 0x28,
+                        spilled_udt_member: true,
+                        parent_offset: 8,
                     },
-                    version_string: "Microsoft (R) Optimizing Compiler".into(),
+                    range: Some(range(vec![])),
                 })
             );
         }
 
         #[test]
-        fn kind_113e() {
-            let data = &[62, 17, 193, 19, 0, 0, 1, 0, 116, 104, 105, 115, 0, 0];
+        fn test_live_range_contains() {
+            let range = range(vec![LiveRangeGap {
+                offset: 4,
+                length: 2,
+            }]);
+
+            assert!(!range.contains(PdbInternalSectionOffset::new(1, 0x0f)));
+            assert!(range.contains(PdbInternalSectionOffset::new(1, 0x10)));
+            assert!(range.contains(PdbInternalSectionOffset::new(1, 0x13)));
+            assert!(!range.contains(PdbInternalSectionOffset::new(1, 0x14)));
+            assert!(!range.contains(PdbInternalSectionOffset::new(1, 0x15)));
+            assert!(range.contains(PdbInternalSectionOffset::new(1, 0x16)));
+            assert!(range.contains(PdbInternalSectionOffset::new(1, 0x2f)));
+            assert!(!range.contains(PdbInternalSectionOffset::new(1, 0x30)));
+            assert!(!range.contains(PdbInternalSectionOffset::new(2, 0x10)));
+        }
 
-            let symbol = Symbol {
-                data,
-                index: SymbolIndex(0),
-            };
-            assert_eq!(symbol.raw_kind(), 0x113e);
+        #[test]
+        fn test_variables() {
+            let data = &[
+                0x0c, 0x00, 0x3e, 0x11, // S_LOCAL
+                0x74, 0x00, 0x00, 0x00, 0x01, 0x00, // type, flags
+                b'a', 0x00, 0x00, 0x00, // name, padding
+                0x06, 0x00, 0x44, 0x11, // S_DEFRANGE_FRAMEPOINTER_REL_FULL_SCOPE
+                0x08, 0x00, 0x00, 0x00, // offset
+                0x0e, 0x00, 0x41, 0x11, // S_DEFRANGE_REGISTER
+                0x11, 0x00, 0x00, 0x00, // register, attributes
+                0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, // range
+                0x0c, 0x00, 0x3e, 0x11, // S_LOCAL
+                0x74, 0x00, 0x00, 0x00, 0x80, 0x00, // type, flags
+                b'b', 0x00, 0x00, 0x00, // name, padding
+                0x02, 0x00, 0x06, 0x00, // S_END
+            ];
+
+            let symbols = SymbolIter::new(ParseBuffer::from(&data[..]));
+            let variables: Vec<_> = symbols.variables().collect().expect("collect");
+
+            assert_eq!(variables.len(), 2);
+            assert_eq!(variables[0].index, SymbolIndex(0));
+            assert_eq!(variables[0].local.name, RawString::from("a"));
+            assert_eq!(variables[0].ranges.len(), 2);
+            assert_eq!(variables[1].index, SymbolIndex(0x26));
+            assert_eq!(variables[1].local.name, RawString::from("b"));
+            assert!(variables[1].local.flags.isoptimizedout);
+            assert!(variables[1].ranges.is_empty());
+
+            let locations: Vec<_> = variables[0]
+                .locations_at(PdbInternalSectionOffset::new(1, 0x18))
+                .collect();
             assert_eq!(
-                symbol.parse().expect("parse"),
-                SymbolData::Local(LocalSymbol {
-                    type_index: TypeIndex(5057),
-                    flags: LocalVariableFlags {
-                        isparam: true,
-                        addrtaken: false,
-                        compgenx: false,
-                        isaggregate: false,
-                        isaliased: false,
-                        isalias: false,
-                        isretvalue: false,
-                        isoptimizedout: false,
-                        isenreg_glob: false,
-                        isenreg_stat: false,
+                locations,
+                [
+                    &DefRangeLocation::FramePointerRelative { offset: 8 },
+                    &DefRangeLocation::Register {
+                        register: Register(17),
+                        may_have_no_name: false,
                     },
-                    name: "this".into(),
-                })
+                ]
             );
         }
+    }
 
-        #[test]
-        fn kind_114c() {
-            let data = &[76, 17, 95, 17, 0, 0];
+    mod managed {
+        use crate::symbol::*;
 
+        #[test]
+        fn kind_112a() {
+            let data = &[
+                42, 17, 0, 0, 0, 0, 64, 0, 0, 0, 0, 0, 0, 0, 24, 0, 0, 0, 2, 0, 0, 0, 22, 0, 0, 0,
+                1, 0, 0, 6, 0, 16, 0, 0, 1, 0, 0, 0, 0, 77, 97, 105, 110, 0,
+            ];
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x114c);
+            assert_eq!(symbol.raw_kind(), 0x112a);
+            assert!(symbol.starts_scope());
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::BuildInfo(BuildInfoSymbol {
-                    id: IdIndex(0x115F)
+                SymbolData::ManagedProcedure(ManagedProcedureSymbol {
+                    global: true,
+                    parent: None,
+                    end: SymbolIndex(64),
+                    next: None,
+                    len: 24,
+                    dbg_start_offset: 2,
+                    dbg_end_offset: 22,
+                    token: 0x0600_0001,
+                    offset: PdbInternalSectionOffset {
+                        offset: 0x1000,
+                        section: 1
+                    },
+                    flags: ProcedureFlags {
+                        nofpo: false,
+                        int: false,
+                        far: false,
+                        never: false,
+                        notreached: false,
+                        cust_call: false,
+                        noinline: false,
+                        optdbginfo: false
+                    },
+                    return_register: Register(0),
+                    name: "Main".into(),
                 })
             );
         }
 
         #[test]
-        fn kind_114d() {
+        fn kind_1120() {
             let data = &[
-                77, 17, 144, 1, 0, 0, 208, 1, 0, 0, 121, 17, 0, 0, 12, 6, 3, 0,
+                32, 17, 1, 0, 0, 0, 8, 0, 0, 17, 0, 0, 0, 0, 0, 0, 0, 0, 105, 0,
             ];
+            let symbol = Symbol {
+                data,
+                index: SymbolIndex(0),
+            };
+            assert_eq!(symbol.raw_kind(), 0x1120);
+            let slot = match symbol.parse().expect("parse") {
+                SymbolData::ManagedSlot(slot) => slot,
+                other => panic!("unexpected symbol {:?}", other),
+            };
+            assert_eq!(slot.slot, 1);
+            assert_eq!(slot.type_index, TypeIndex(0x1100_0008));
+            assert_eq!(slot.offset, PdbInternalSectionOffset::new(0, 0));
+            assert!(!slot.flags.isparam);
+            assert_eq!(slot.name, "i".into());
+        }
 
+        #[test]
+        fn kind_1129() {
+            let data = &[41, 17, 0, 0, 0, 0, 4, 0, 0, 0, 3, 0, 77, 97, 105, 110, 0, 0];
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x114d);
+            assert_eq!(symbol.raw_kind(), 0x1129);
             assert_eq!(
                 symbol.parse().expect("parse"),
-                SymbolData::InlineSite(InlineSiteSymbol {
-                    parent: Some(SymbolIndex(0x0190)),
-                    end: SymbolIndex(0x01d0),
-                    inlinee: IdIndex(4473),
-                    invocations: None,
-                    annotations: BinaryAnnotations::new(&[12, 6, 3, 0]),
+                SymbolData::TokenReference(ProcedureReferenceSymbol {
+                    global: false,
+                    sum_name: 0,
+                    symbol_index: SymbolIndex(4),
+                    module: Some(2),
+                    name: Some("Main".into()),
                 })
             );
         }
+    }
 
-        #[test]
-        fn kind_114e() {
-            let data = &[78, 17];
+    mod rust {
+        use crate::symbol::*;
+
+        fn compile_flags(language: SourceLanguage, version: &str) -> CompileFlagsSymbol<'_> {
+            let data = &[
+                22, 17, 7, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 14, 0, 10, 0, 115, 98, 77, 105, 99,
+                114, 111, 115, 111, 102, 116, 32, 40, 82, 41, 32, 76, 73, 78, 75, 0, 0, 0, 0,
+            ];
 
             let symbol = Symbol {
                 data,
                 index: SymbolIndex(0),
             };
-            assert_eq!(symbol.raw_kind(), 0x114e);
-            assert_eq!(symbol.parse().expect("parse"), SymbolData::InlineSiteEnd);
+            match symbol.parse().expect("parse") {
+                SymbolData::CompileFlags(flags) => CompileFlagsSymbol {
+                    language,
+                    version_string: version.into(),
+                    ..flags
+                },
+                _ => panic!("expected compile flags"),
+            }
+        }
+
+        #[test]
+        fn test_is_rust() {
+            assert!(compile_flags(SourceLanguage::Rust, "rustc version 1.70.0").is_rust());
+            assert!(
+                compile_flags(SourceLanguage::Cpp, "clang LLVM (rustc version 1.40.0)").is_rust()
+            );
+            assert!(
+                !compile_flags(SourceLanguage::Cpp, "Microsoft (R) Optimizing Compiler").is_rust()
+            );
+        }
+
+        #[test]
+        #[cfg(feature = "demangle")]
+        fn test_demangle_public() {
+            let mut symbol = PublicSymbol {
+                code: true,
+                function: true,
+                managed: false,
+                msil: false,
+                offset: PdbInternalSectionOffset::new(1, 0),
+                name: "_ZN4core3fmt5write17h0123456789abcdefE".into(),
+            };
+            assert_eq!(symbol.demangle_rust().as_deref(), Some("core::fmt::write"));
+
+            symbol.name = "_RNvCs1234_7mycrate3foo".into();
+            assert_eq!(symbol.demangle_rust().as_deref(), Some("mycrate::foo"));
+
+            symbol.name = "?foo@@YAXXZ".into();
+            assert_eq!(symbol.demangle_rust(), None);
         }
     }
 
@@ -2360,4 +5295,78 @@ mod tests {
             assert_eq!(symbol, Some(expected));
         }
     }
+
+    mod public_filter {
+        use crate::symbol::*;
+
+        fn public(code: bool, function: bool) -> PublicSymbol<'static> {
+            PublicSymbol {
+                code,
+                function,
+                managed: false,
+                msil: false,
+                offset: PdbInternalSectionOffset::new(1, 0),
+                name: "public".into(),
+            }
+        }
+
+        #[test]
+        fn test_default_matches_all() {
+            let filter = PublicSymbolFilter::default();
+            assert!(filter.matches(&public(false, false)));
+            assert!(filter.matches(&public(true, true)));
+        }
+
+        #[test]
+        fn test_functions() {
+            let filter = PublicSymbolFilter::functions();
+            assert!(filter.matches(&public(true, true)));
+            assert!(!filter.matches(&public(true, false)));
+            assert!(!filter.matches(&public(false, false)));
+        }
+
+        #[test]
+        fn test_data() {
+            let filter = PublicSymbolFilter::data();
+            assert!(filter.matches(&public(false, false)));
+            assert!(!filter.matches(&public(true, false)));
+            assert!(!filter.matches(&public(false, true)));
+        }
+
+        #[test]
+        fn test_zero_address() {
+            let data = &[
+                14, 0, 14, 17, 2, 0, 0, 0, 16, 0, 0, 0, 1, 0, 97, 0, // S_PUB32 a at 0001:0010
+                14, 0, 14, 17, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 98, 0, // S_PUB32 b at 0000:0000
+            ];
+
+            let publics = |policy| PublicSymbolIter {
+                symbols: SymbolIter::new(ParseBuffer::from(&data[..])),
+                filter: PublicSymbolFilter::default(),
+                zero_address: policy,
+            };
+
+            let names: Vec<_> = publics(DegenerateRecordPolicy::Keep)
+                .map(|public| Ok(public.name))
+                .collect()
+                .expect("keep");
+            assert_eq!(names, [RawString::from("a"), RawString::from("b")]);
+
+            let names: Vec<_> = publics(DegenerateRecordPolicy::Skip)
+                .map(|public| Ok(public.name))
+                .collect()
+                .expect("skip");
+            assert_eq!(names, [RawString::from("a")]);
+
+            let mut iter = publics(DegenerateRecordPolicy::Report);
+            assert!(iter.next().expect("first").is_some());
+            assert!(matches!(
+                iter.next(),
+                Err(Error::DegenerateRecord(
+                    DegenerateRecordKind::ZeroAddressPublic,
+                    _
+                ))
+            ));
+        }
+    }
 }
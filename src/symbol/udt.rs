@@ -0,0 +1,90 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::symbol::constants::{S_UDT, S_UDT_ST};
+use crate::symbol::{SymbolData, SymbolTable};
+use crate::tpi::TypeInformation;
+use crate::FallibleIterator;
+
+/// A user defined type or typedef of a [`SymbolTable`], joined with its type definition.
+///
+/// Returned by [`SymbolTable::user_defined_types`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UserDefinedType<'t> {
+    /// The index of the first `S_UDT` symbol declaring this type.
+    pub index: SymbolIndex,
+    /// The name declared by the symbol, such as the name of a typedef.
+    pub name: RawString<'t>,
+    /// The type referenced by the symbol.
+    pub type_index: TypeIndex,
+    /// The type defining the class, struct, union or enum referenced by the symbol.
+    ///
+    /// If the symbol references a forward reference, this is the complete definition of the same
+    /// type, or `None` if the type stream does not contain one. For all other types, this is the
+    /// same as `type_index`.
+    pub definition: Option<TypeIndex>,
+}
+
+impl<'s> SymbolTable<'s> {
+    /// Lists the user defined types and typedefs declared by `S_UDT` symbols in this table.
+    ///
+    /// References to incomplete types are resolved to their definitions in `types`. Symbols
+    /// declaring the same name for the same definition are only listed once, in order of their
+    /// first occurrence.
+    ///
+    /// Definitions are found with [`TypeInformation::resolve_definition`], which looks them up in
+    /// the hash stream or scans the type stream once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<usize> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let symbol_table = pdb.global_symbols()?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let types = symbol_table.user_defined_types(&type_information)?;
+    /// for udt in &types {
+    ///     println!("{} -> {:?}", udt.name, udt.definition);
+    /// }
+    /// # Ok(types.len())
+    /// # }
+    /// # assert!(test().expect("test") > 0);
+    /// ```
+    pub fn user_defined_types(
+        &self,
+        types: &TypeInformation<'_>,
+    ) -> Result<Vec<UserDefinedType<'_>>> {
+        let mut seen = BTreeSet::new();
+        let mut udts = Vec::new();
+
+        let mut symbols = self.iter();
+        while let Some(symbol) = symbols.next()? {
+            if !matches!(symbol.raw_kind(), S_UDT | S_UDT_ST) {
+                continue;
+            }
+
+            let data = match symbol.parse()? {
+                SymbolData::UserDefinedType(data) => data,
+                _ => continue,
+            };
+
+            let definition = types.resolve_definition(data.type_index)?;
+            let key = (data.name, definition.unwrap_or(data.type_index));
+            if !seen.insert(key) {
+                continue;
+            }
+
+            udts.push(UserDefinedType {
+                index: symbol.index(),
+                name: data.name,
+                type_index: data.type_index,
+                definition,
+            });
+        }
+
+        Ok(udts)
+    }
+}
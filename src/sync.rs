@@ -0,0 +1,139 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Interior mutability for state shared by the accessors of a `PDB`.
+//!
+//! With the `std` feature, these types are thread-safe. Without it, they fall back to their
+//! single-threaded counterparts from `core`. Whether a `PDB` can actually be shared between threads
+//! depends on its source, which the `sync` feature requires to be `Send` and `Sync`.
+
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+/// A bound that requires `Send` if the `sync` feature is enabled, and nothing otherwise.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// A bound that requires `Send` if the `sync` feature is enabled, and nothing otherwise.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// A bound that requires `Send` and `Sync` if the `sync` feature is enabled, and nothing otherwise.
+#[cfg(feature = "sync")]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+/// A bound that requires `Send` and `Sync` if the `sync` feature is enabled, and nothing otherwise.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSendSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+/// A cell that is initialized at most once.
+#[cfg(feature = "std")]
+pub(crate) type Once<T> = std::sync::OnceLock<T>;
+
+/// A cell that is initialized at most once.
+#[cfg(not(feature = "std"))]
+pub(crate) type Once<T> = core::cell::OnceCell<T>;
+
+/// Returns the value of `cell`, initializing it with `f` if it is empty.
+///
+/// If `f` fails, the cell remains empty and the error is returned. Concurrent callers may run `f`
+/// more than once, in which case the first value to be stored wins.
+pub(crate) fn get_or_try_init<T, E, F>(cell: &Once<T>, f: F) -> Result<&T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+
+    let value = f()?;
+    Ok(cell.get_or_init(|| value))
+}
+
+/// A lock granting exclusive access to a value.
+#[derive(Debug)]
+pub(crate) struct Lock<T> {
+    #[cfg(feature = "std")]
+    inner: Mutex<T>,
+    #[cfg(not(feature = "std"))]
+    inner: RefCell<T>,
+}
+
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            inner: Mutex::new(value),
+            #[cfg(not(feature = "std"))]
+            inner: RefCell::new(value),
+        }
+    }
+
+    /// Calls `f` with exclusive access to the value.
+    #[cfg(feature = "std")]
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        // A panic while holding the lock cannot leave the value in an inconsistent state, since
+        // all state is read-only after initialization.
+        let mut guard = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut guard)
+    }
+
+    /// Calls `f` with exclusive access to the value.
+    #[cfg(not(feature = "std"))]
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.inner.borrow_mut())
+    }
+
+    /// Returns a mutable reference to the value without locking.
+    #[cfg(feature = "std")]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns a mutable reference to the value without locking.
+    #[cfg(not(feature = "std"))]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_or_try_init() {
+        let cell = Once::new();
+        assert_eq!(
+            get_or_try_init(&cell, || Err::<u32, _>("error")),
+            Err("error")
+        );
+        assert_eq!(get_or_try_init(&cell, || Ok::<_, ()>(42)), Ok(&42));
+        assert_eq!(get_or_try_init(&cell, || Ok::<_, ()>(7)), Ok(&42));
+    }
+
+    #[test]
+    fn test_lock() {
+        let mut lock = Lock::new(1);
+        lock.with(|value| *value += 1);
+        assert_eq!(*lock.get_mut(), 2);
+    }
+}
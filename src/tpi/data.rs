@@ -10,6 +10,10 @@
 use crate::common::*;
 use crate::tpi::constants::*;
 use crate::tpi::primitive::*;
+use crate::tpi::{Type, TypeIter};
+use crate::FallibleIterator;
+use alloc::vec::Vec;
+use core::mem;
 
 /// Encapsulates parsed data about a `Type`.
 #[non_exhaustive]
@@ -58,8 +62,145 @@ impl<'t> TypeData<'t> {
     }
 }
 
+/// Reusable buffers for parsing list types.
+///
+/// Parsing a field list, argument list, method list or array allocates a vector for its elements.
+/// When scanning many types, pass the same scratch to
+/// [`Type::parse_with`](crate::Item::parse_with) and hand each parsed type back via
+/// [`recycle`](Self::recycle) once it is no longer needed. Subsequent types then reuse the
+/// allocations of previous ones instead of allocating anew.
+///
+/// To parse every type of a stream, [`TypeIter::with_scratch`](crate::ItemIter::with_scratch)
+/// recycles each parsed type automatically before parsing the next one.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// # let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+/// let mut scratch = pdb::TypeScratch::new();
+///
+/// let mut fields = 0;
+/// let mut iter = type_information.iter();
+/// while let Some(typ) = iter.next()? {
+///     if let Ok(data) = typ.parse_with(&mut scratch) {
+///         if let pdb::TypeData::FieldList(ref list) = data {
+///             fields += list.fields.len();
+///         }
+///         scratch.recycle(data);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug, Default)]
+pub struct TypeScratch<'t> {
+    fields: Vec<TypeData<'t>>,
+    arguments: Vec<TypeIndex>,
+    methods: Vec<MethodListEntry>,
+    dimensions: Vec<u32>,
+}
+
+impl<'t> TypeScratch<'t> {
+    /// Creates an empty scratch, which does not allocate until types are recycled into it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the buffers of a parsed type to the scratch for reuse.
+    ///
+    /// Types without buffers are dropped. A buffer only replaces the one currently held if it has a
+    /// larger capacity.
+    pub fn recycle(&mut self, data: TypeData<'t>) {
+        match data {
+            TypeData::FieldList(list) => keep(&mut self.fields, list.fields),
+            TypeData::ArgumentList(list) => keep(&mut self.arguments, list.arguments),
+            TypeData::MethodList(list) => keep(&mut self.methods, list.methods),
+            TypeData::Array(array) => keep(&mut self.dimensions, array.dimensions),
+            _ => (),
+        }
+    }
+}
+
+fn keep<T>(slot: &mut Vec<T>, mut buffer: Vec<T>) {
+    if buffer.capacity() > slot.capacity() {
+        buffer.clear();
+        *slot = buffer;
+    }
+}
+
+/// An iterator that parses types using a [`TypeScratch`].
+///
+/// Created by [`TypeIter::with_scratch`](crate::ItemIter::with_scratch). Each call to
+/// [`next`](Self::next) returns the previously parsed type to the scratch before parsing the next
+/// one, so that a scan over an entire stream only allocates for the largest lists it encounters.
+/// The last parsed type is recycled when the iterator is dropped.
+#[derive(Debug)]
+pub struct ParsedTypes<'t, 'a> {
+    iter: TypeIter<'t>,
+    scratch: &'a mut TypeScratch<'t>,
+    current: Option<TypeData<'t>>,
+}
+
+impl<'t, 'a> ParsedTypes<'t, 'a> {
+    pub(crate) fn new(iter: TypeIter<'t>, scratch: &'a mut TypeScratch<'t>) -> Self {
+        Self {
+            iter,
+            scratch,
+            current: None,
+        }
+    }
+
+    /// Advances to the next type and parses it.
+    ///
+    /// Returns the type along with the result of parsing it, which borrows from this iterator
+    /// until the next call. Types that fail to parse do not end the iteration.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeTooShort` or `Error::UnexpectedEof` if the type stream is corrupt
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<(Type<'t>, Result<&TypeData<'t>>)>> {
+        if let Some(data) = self.current.take() {
+            self.scratch.recycle(data);
+        }
+
+        let typ = match self.iter.next()? {
+            Some(typ) => typ,
+            None => return Ok(None),
+        };
+
+        let data = match typ.parse_with(self.scratch) {
+            Ok(data) => Ok(&*self.current.insert(data)),
+            Err(error) => Err(error),
+        };
+
+        Ok(Some((typ, data)))
+    }
+}
+
+impl Drop for ParsedTypes<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(data) = self.current.take() {
+            self.scratch.recycle(data);
+        }
+    }
+}
+
 /// Parse a type out of a `ParseBuffer`.
 pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<'t>> {
+    parse_type_data_with(buf, &mut TypeScratch::new())
+}
+
+/// Parse a type out of a `ParseBuffer`, taking list buffers from `scratch`.
+pub(crate) fn parse_type_data_with<'t>(
+    buf: &mut ParseBuffer<'t>,
+    scratch: &mut TypeScratch<'t>,
+) -> Result<TypeData<'t>> {
     let leaf = buf.parse_u16()?;
 
     match leaf {
@@ -166,7 +307,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                 attributes: attr,
                 method_type: buf.parse()?,
                 vtable_offset: if attr.is_intro_virtual() {
-                    Some(buf.parse_u32()? as u32)
+                    Some(buf.parse_u32()?)
                 } else {
                     // yes, this is variable length
                     None
@@ -281,11 +422,11 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                 None
             };
 
-            let mut dimensions: Vec<u32> = Vec::new();
+            let mut dimensions = mem::take(&mut scratch.dimensions);
 
             loop {
                 let dim = parse_unsigned(buf)?;
-                if dim > u64::from(u32::max_value()) {
+                if dim > u64::from(u32::MAX) {
                     return Err(Error::UnimplementedFeature("u64 array sizes"));
                 }
                 dimensions.push(dim as u32);
@@ -370,7 +511,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
 
         // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2112-L2115
         LF_FIELDLIST => {
-            let mut fields: Vec<TypeData<'t>> = Vec::new();
+            let mut fields = mem::take(&mut scratch.fields);
             let mut continuation: Option<TypeIndex> = None;
 
             while !buf.is_empty() {
@@ -383,10 +524,14 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                         // parse the TypeIndex where we continue
                         continuation = Some(buf.parse()?);
                     }
+                    LF_FIELDLIST => {
+                        // field lists do not nest; parsing them recursively could overflow the
+                        // stack on corrupt records
+                        return Err(Error::UnimplementedTypeKind(LF_FIELDLIST));
+                    }
                     _ => {
                         // other type
-                        // recurse because recursion is endless fun because [STACK OVERFLOW]
-                        fields.push(parse_type_data(buf)?);
+                        fields.push(parse_type_data_with(buf, scratch)?);
                     }
                 }
 
@@ -402,7 +547,8 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
 
         LF_ARGLIST => {
             let count = buf.parse_u32()?;
-            let mut arglist: Vec<TypeIndex> = Vec::with_capacity(count as usize);
+            let mut arglist = mem::take(&mut scratch.arguments);
+            arglist.reserve(count as usize);
             for _ in 0..count {
                 arglist.push(buf.parse()?);
             }
@@ -410,7 +556,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
         }
 
         LF_METHODLIST => {
-            let mut methods: Vec<MethodListEntry> = Vec::new();
+            let mut methods = mem::take(&mut scratch.methods);
 
             while !buf.is_empty() {
                 // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2131-L2136
@@ -1077,6 +1223,43 @@ ParseBuf::from("\x03\x15\xb7\x16\x00\x00\x23\x00\x00\x00\x28\x00\x00\xf1").as_by
 ParseBuf::from("\x03\x15\x14\x10\x00\x00\x23\x00\x00\x00\x55\x00\x00\xf1").as_bytes(),
 */
 
+#[test]
+fn test_nested_field_list() {
+    // LF_FIELDLIST containing another LF_FIELDLIST
+    let data = &[0x03, 0x12, 0x03, 0x12, 0x03, 0x12][..];
+    assert!(matches!(
+        parse_type_data(&mut ParseBuffer::from(data)),
+        Err(Error::UnimplementedTypeKind(LF_FIELDLIST))
+    ));
+}
+
+#[test]
+fn test_scratch_reuses_buffers() {
+    // LF_ARGLIST with two arguments
+    let data = &[0x01, 0x12, 2, 0, 0, 0, 0x74, 0, 0, 0, 0x75, 0, 0, 0][..];
+    let mut scratch = TypeScratch::new();
+
+    let first = parse_type_data_with(&mut ParseBuffer::from(data), &mut scratch).expect("parse");
+    let pointer = match first {
+        TypeData::ArgumentList(ref list) => list.arguments.as_ptr(),
+        _ => panic!("expected an argument list"),
+    };
+    scratch.recycle(first);
+
+    let second = parse_type_data_with(&mut ParseBuffer::from(data), &mut scratch).expect("parse");
+    assert_eq!(
+        second,
+        parse_type_data(&mut ParseBuffer::from(data)).expect("parse")
+    );
+    match second {
+        TypeData::ArgumentList(ref list) => {
+            assert_eq!(list.arguments, vec![TypeIndex(0x74), TypeIndex(0x75)]);
+            assert_eq!(list.arguments.as_ptr(), pointer);
+        }
+        _ => panic!("expected an argument list"),
+    }
+}
+
 #[test]
 fn kind_1609() {
     let data = &[
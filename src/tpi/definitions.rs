@@ -0,0 +1,474 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::sync::{self, Once};
+use crate::tpi::constants::*;
+use crate::tpi::{ItemHashes, Type, TypeData, TypeFinder, TypeInformation, TypeProperties};
+use crate::FallibleIterator;
+
+/// The forward references and definitions of classes, structs, unions and enums in a type stream.
+#[derive(Debug, Default)]
+struct Definitions<'t> {
+    /// Forward references with their unique names, or their names if they have no unique name.
+    forward_references: BTreeMap<TypeIndex, (bool, &'t [u8])>,
+    /// Definitions by unique name.
+    by_unique_name: BTreeMap<&'t [u8], TypeIndex>,
+    /// Definitions by name.
+    by_name: BTreeMap<&'t [u8], TypeIndex>,
+}
+
+impl<'t> Definitions<'t> {
+    /// Records the type if it is a forward reference or definition of a user defined type.
+    ///
+    /// Returns whether the type is a definition.
+    fn record(&mut self, item: &Type<'t>) -> Result<bool> {
+        let (properties, name, unique_name) = match parse_user_defined(item)? {
+            Some(parsed) => parsed,
+            None => return Ok(false),
+        };
+
+        if properties.forward_reference() {
+            let key = match unique_name {
+                Some(unique_name) => (true, unique_name.as_bytes()),
+                None => (false, name.as_bytes()),
+            };
+            self.forward_references.insert(item.index(), key);
+            return Ok(false);
+        }
+
+        if let Some(unique_name) = unique_name {
+            self.by_unique_name
+                .entry(unique_name.as_bytes())
+                .or_insert_with(|| item.index());
+        }
+
+        self.by_name
+            .entry(name.as_bytes())
+            .or_insert_with(|| item.index());
+
+        Ok(true)
+    }
+
+    /// Resolves a forward reference to the index of its definition.
+    ///
+    /// Returns `None` if `index` is a forward reference without definition. All other indexes are
+    /// returned unchanged.
+    fn resolve(&self, index: TypeIndex) -> Option<TypeIndex> {
+        match self.forward_references.get(&index) {
+            Some(&(true, unique_name)) => self.by_unique_name.get(unique_name).copied(),
+            Some(&(false, name)) => self.by_name.get(name).copied(),
+            None => Some(index),
+        }
+    }
+}
+
+/// Parses the properties, name and unique name of a class, struct, union or enum.
+///
+/// Returns `None` for all other kinds of types.
+pub(crate) fn parse_user_defined<'t>(
+    item: &Type<'t>,
+) -> Result<Option<(TypeProperties, RawString<'t>, Option<RawString<'t>>)>> {
+    if !matches!(
+        item.raw_kind(),
+        LF_CLASS
+            | LF_CLASS_ST
+            | LF_STRUCTURE
+            | LF_STRUCTURE_ST
+            | LF_STRUCTURE19
+            | LF_INTERFACE
+            | LF_UNION
+            | LF_UNION_ST
+            | LF_ENUM
+            | LF_ENUM_ST
+    ) {
+        return Ok(None);
+    }
+
+    Ok(match item.parse()? {
+        TypeData::Class(data) => Some((data.properties, data.name, data.unique_name)),
+        TypeData::Union(data) => Some((data.properties, data.name, data.unique_name)),
+        TypeData::Enumeration(data) => Some((data.properties, data.name, data.unique_name)),
+        _ => None,
+    })
+}
+
+/// Returns the first definition hashed by `key` whose name and unique name satisfy `matches`.
+///
+/// Hashes are case-insensitive and collide, so `matches` must compare the names of all candidates.
+fn probe_definitions<F>(
+    hashes: &ItemHashes<TypeIndex>,
+    finder: &TypeFinder<'_>,
+    key: &[u8],
+    matches: F,
+) -> Result<Option<TypeIndex>>
+where
+    F: Fn(RawString<'_>, Option<RawString<'_>>) -> bool,
+{
+    for index in hashes.items_with_hash(hashes.hash_name(key)) {
+        let item = finder.find(index)?;
+        if let Some((properties, name, unique_name)) = parse_user_defined(&item)? {
+            if !properties.forward_reference() && matches(name, unique_name) {
+                return Ok(Some(index));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// The definitions and positions of all types in a type stream, owning their names.
+///
+/// Built by a single scan of the stream and shared by all lookups that need to see every type.
+#[derive(Debug)]
+pub(crate) struct DefinitionIndex {
+    /// The definitions of all forward references.
+    resolved: BTreeMap<TypeIndex, Option<TypeIndex>>,
+    /// Definitions by name and by unique name.
+    names: BTreeMap<Vec<u8>, TypeIndex>,
+    /// Definitions of all classes, structs and interfaces, in stream order.
+    classes: Vec<TypeIndex>,
+    /// The positions recorded by a [`TypeFinder`] that has seen every type.
+    positions: Vec<u32>,
+    /// The pointer size of the target machine, inferred from the most common size of pointers.
+    pointer_size: u64,
+}
+
+impl DefinitionIndex {
+    /// Scans the entire type stream to build the index.
+    fn scan(types: &TypeInformation<'_>, token: Option<&CancellationToken>) -> Result<Self> {
+        let mut finder = types.finder();
+        let mut definitions = Definitions::default();
+        let mut classes = Vec::new();
+        // Counts of 32-bit and 64-bit pointers.
+        let mut pointers = [0usize; 2];
+
+        let mut iter = types.iter();
+        loop {
+            if let Some(token) = token {
+                token.check()?;
+            }
+
+            let item = match iter.next()? {
+                Some(item) => item,
+                None => break,
+            };
+
+            finder.update(&iter);
+
+            let is_class = matches!(
+                item.raw_kind(),
+                LF_CLASS
+                    | LF_CLASS_ST
+                    | LF_STRUCTURE
+                    | LF_STRUCTURE_ST
+                    | LF_STRUCTURE19
+                    | LF_INTERFACE
+            );
+            if definitions.record(&item)? && is_class {
+                classes.push(item.index());
+            }
+
+            if item.raw_kind() == LF_POINTER {
+                if let TypeData::Pointer(data) = item.parse()? {
+                    match data.attributes.size() {
+                        4 => pointers[0] += 1,
+                        8 => pointers[1] += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        // Copy the names of all definitions, so that they no longer borrow from the type stream.
+        let resolved = definitions
+            .forward_references
+            .keys()
+            .map(|&index| (index, definitions.resolve(index)))
+            .collect();
+
+        let mut names = BTreeMap::new();
+        for (&name, &index) in definitions
+            .by_name
+            .iter()
+            .chain(&definitions.by_unique_name)
+        {
+            names.entry(name.to_vec()).or_insert(index);
+        }
+
+        Ok(Self {
+            resolved,
+            names,
+            classes,
+            positions: finder.positions,
+            pointer_size: if pointers[0] > pointers[1] { 4 } else { 8 },
+        })
+    }
+
+    /// Resolves a forward reference to the index of its definition.
+    ///
+    /// Returns `None` if `index` is a forward reference without definition. All other indexes are
+    /// returned unchanged.
+    pub(crate) fn resolve(&self, index: TypeIndex) -> Option<TypeIndex> {
+        match self.resolved.get(&index) {
+            Some(&definition) => definition,
+            None => Some(index),
+        }
+    }
+
+    /// Returns the definitions of all classes, structs and interfaces, in stream order.
+    pub(crate) fn classes(&self) -> &[TypeIndex] {
+        &self.classes
+    }
+
+    /// Returns the inferred pointer size of the target machine in bytes.
+    pub(crate) fn pointer_size(&self) -> u64 {
+        self.pointer_size
+    }
+}
+
+/// Memoized definitions of a type stream, see [`TypeInformation::resolve_definition`] and
+/// [`TypeInformation::find_by_name`].
+#[derive(Debug)]
+pub(crate) struct DefinitionCache {
+    /// The index of all definitions in the stream, built on first use.
+    index: Once<DefinitionIndex>,
+}
+
+impl DefinitionCache {
+    pub fn new() -> Self {
+        Self { index: Once::new() }
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Resolves a forward reference to the index of the class, struct, union or enum defining it.
+    ///
+    /// Forward references declare a type without its fields and with a size of zero. Their
+    /// definitions are located by unique name, or by name if the forward reference has no unique
+    /// name. Returns `None` if `index` is a forward reference without definition in this stream.
+    /// All other indexes, including primitive types, are returned unchanged.
+    ///
+    /// If the PDB contains a [hash stream](Self::hashes), the definition is looked up among the types
+    /// hashed by the name and unique name of the forward reference. Otherwise, the first call scans
+    /// the entire stream to index all forward references and definitions.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` or other parsing errors if the type stream is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let mut finder = type_information.finder();
+    ///
+    /// let mut forward_references = Vec::new();
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     finder.update(&iter);
+    ///     if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+    ///         if class.properties.forward_reference() {
+    ///             forward_references.push(typ.index());
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// for index in forward_references {
+    ///     if let Some(definition) = type_information.resolve_definition(index)? {
+    ///         if let pdb::TypeData::Class(class) = finder.find(definition)?.parse()? {
+    ///             println!("{} has {} bytes", class.name, class.size);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn resolve_definition(&self, index: TypeIndex) -> Result<Option<TypeIndex>> {
+        if let (Some(hashes), Some(finder)) = (self.hashes(), self.finder_from_hashes()) {
+            return self.resolve_hashed_definition(hashes, &finder, index);
+        }
+
+        Ok(self.definition_index(None)?.resolve(index))
+    }
+
+    /// Resolves a forward reference by probing the types hashed by its name and unique name.
+    fn resolve_hashed_definition(
+        &self,
+        hashes: &ItemHashes<TypeIndex>,
+        finder: &TypeFinder<'_>,
+        index: TypeIndex,
+    ) -> Result<Option<TypeIndex>> {
+        if index.0 < self.header.minimum_index || index.0 >= self.header.maximum_index {
+            return Ok(Some(index));
+        }
+
+        let (name, unique_name) = match parse_user_defined(&finder.find(index)?)? {
+            Some((properties, name, unique_name)) if properties.forward_reference() => {
+                (name, unique_name)
+            }
+            _ => return Ok(Some(index)),
+        };
+
+        // Definitions are hashed by unique name if they are scoped, and by name otherwise.
+        let matches =
+            |candidate: RawString<'_>, candidate_unique: Option<RawString<'_>>| match unique_name {
+                Some(unique_name) => candidate_unique == Some(unique_name),
+                None => candidate == name,
+            };
+
+        let by_name = probe_definitions(hashes, finder, name.as_bytes(), matches)?;
+        let by_unique_name = match unique_name {
+            Some(unique_name) => {
+                probe_definitions(hashes, finder, unique_name.as_bytes(), matches)?
+            }
+            None => None,
+        };
+
+        Ok(by_name.into_iter().chain(by_unique_name).min())
+    }
+
+    /// Finds the class, struct, union or enum with the given name or unique name.
+    ///
+    /// Names are compared exactly, including namespaces and template arguments, such as
+    /// `std::basic_string<char,std::char_traits<char>,std::allocator<char> >`. Unique names are the
+    /// decorated names emitted by MSVC, such as `.?AVexception@std@@`. Only definitions are
+    /// considered; if a name is defined more than once, the first definition is returned. Returns
+    /// `None` if the stream does not define a type with this name.
+    ///
+    /// If the PDB contains a [hash stream](Self::hashes), the definitions hashed by this name are
+    /// probed first. Since scoped definitions are hashed by their unique name only, names not found
+    /// this way are looked up in an index of all definitions. The first call that needs this index
+    /// scans the entire stream to build it, subsequent lookups do not scan the stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` or other parsing errors if the type stream is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let layouts = type_information.layouts()?;
+    ///
+    /// if let Some(index) = type_information.find_by_name("std::exception")? {
+    ///     println!("std::exception has {} bytes", layouts.size_of(index)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn find_by_name(&self, name: &str) -> Result<Option<TypeIndex>> {
+        self.find_name(name, None)
+    }
+
+    /// Finds the class, struct, union or enum with the given name or unique name, unless cancelled.
+    ///
+    /// This behaves like [`find_by_name`](Self::find_by_name), but checks `token` before each type
+    /// while building the index of all definitions. If the scan is cancelled, the index is not
+    /// retained and the next lookup scans the stream again.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Cancelled` if `token` was cancelled before the index of definitions was built
+    /// * The errors of [`find_by_name`](Self::find_by_name)
+    pub fn find_by_name_cancellable(
+        &self,
+        name: &str,
+        token: &CancellationToken,
+    ) -> Result<Option<TypeIndex>> {
+        self.find_name(name, Some(token))
+    }
+
+    fn find_name(
+        &self,
+        name: &str,
+        token: Option<&CancellationToken>,
+    ) -> Result<Option<TypeIndex>> {
+        if let Some(index) = self.find_hashed_name(name.as_bytes())? {
+            return Ok(Some(index));
+        }
+
+        let index = self.definition_index(token)?;
+        Ok(index.names.get(name.as_bytes()).copied())
+    }
+
+    /// Finds a definition with the given name or unique name among the types hashed by this name.
+    fn find_hashed_name(&self, name: &[u8]) -> Result<Option<TypeIndex>> {
+        let (hashes, finder) = match (self.hashes(), self.finder_from_hashes()) {
+            (Some(hashes), Some(finder)) => (hashes, finder),
+            _ => return Ok(None),
+        };
+
+        probe_definitions(hashes, &finder, name, |item_name, unique_name| {
+            item_name.as_bytes() == name
+                || unique_name.is_some_and(|unique_name| unique_name.as_bytes() == name)
+        })
+    }
+
+    /// Returns the index of all definitions, building it on first use unless `token` is cancelled.
+    fn definition_index(&self, token: Option<&CancellationToken>) -> Result<&DefinitionIndex> {
+        sync::get_or_try_init(&self.definitions.index, || {
+            DefinitionIndex::scan(self, token)
+        })
+    }
+
+    /// Returns a finder that knows all types and the index of all definitions, scanning the stream
+    /// on first use.
+    pub(crate) fn indexed_finder(&self) -> Result<(TypeFinder<'_>, &DefinitionIndex)> {
+        let index = self.definition_index(None)?;
+        let mut finder = self.finder();
+        finder.positions.clone_from(&index.positions);
+        Ok((finder, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let mut definitions = Definitions::default();
+        let forward_references = [
+            (0x1000, true, &b".?AUFoo@@"[..]),
+            (0x1001, false, &b"Bar"[..]),
+            (0x1002, false, &b"Baz"[..]),
+            (0x1006, false, &b"Qux"[..]),
+        ];
+        for &(index, unique, name) in &forward_references {
+            definitions
+                .forward_references
+                .insert(TypeIndex(index), (unique, name));
+        }
+        definitions
+            .by_unique_name
+            .insert(&b".?AUFoo@@"[..], TypeIndex(0x1003));
+        definitions.by_name.insert(&b"Bar"[..], TypeIndex(0x1004));
+        definitions.by_name.insert(&b"Baz"[..], TypeIndex(0x1005));
+
+        let resolve = |index| definitions.resolve(TypeIndex(index));
+        assert_eq!(resolve(0x1000), Some(TypeIndex(0x1003)));
+        assert_eq!(resolve(0x1001), Some(TypeIndex(0x1004)));
+        assert_eq!(resolve(0x1002), Some(TypeIndex(0x1005)));
+        assert_eq!(resolve(0x1005), Some(TypeIndex(0x1005)));
+        assert_eq!(resolve(0x0074), Some(TypeIndex(0x0074)));
+        assert_eq!(resolve(0x1006), None);
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use core::mem;
+
+use crate::common::*;
+use crate::msf::Stream;
+use crate::sync::Once;
+use crate::tpi::header::{Header, Slice};
+
+/// Computes the hash of a name as stored in the hash stream of a type or id stream.
+///
+/// This function has originally been defined as `LHashPbCb` (`hashStringV1` in LLVM).
+fn hash_name(name: &[u8]) -> u32 {
+    let mut hash = 0u32;
+
+    let mut words = name.chunks_exact(4);
+    for word in &mut words {
+        hash ^= u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+    }
+
+    let mut remainder = words.remainder();
+    if remainder.len() >= 2 {
+        hash ^= u32::from(u16::from_le_bytes([remainder[0], remainder[1]]));
+        remainder = &remainder[2..];
+    }
+    if let Some(&byte) = remainder.first() {
+        hash ^= u32::from(byte);
+    }
+
+    // Make the hash case-insensitive for ASCII letters.
+    hash |= 0x2020_2020;
+    hash ^= hash >> 11;
+    hash ^ (hash >> 16)
+}
+
+/// Returns the part of the hash stream referenced by `slice`.
+fn hash_slice(data: &[u8], slice: Slice) -> Result<&[u8]> {
+    if slice.size == 0 {
+        return Ok(&[]);
+    }
+
+    let start = usize::try_from(slice.offset)
+        .map_err(|_| Error::InvalidTypeInformationHeader("negative hash stream offset"))?;
+    data.get(start..)
+        .and_then(|data| data.get(..slice.size as usize))
+        .ok_or(Error::UnexpectedEof)
+}
+
+/// The hash stream of a [`TypeInformation`](crate::TypeInformation) or
+/// [`IdInformation`](crate::IdInformation) stream.
+///
+/// The hash stream accompanies the item stream with three tables:
+///
+///  - The hash of every item, reduced to the number of buckets of the hash table that the compiler
+///    and linker use to deduplicate items. Classes, unions and enums with a name are hashed by
+///    their name (or unique name if they are scoped), all other items by their contents.
+///  - The byte offset of every few items, which allows locating items without scanning the stream.
+///  - Hash adjusters, which select the item to prefer among several items with the same name.
+///
+/// Obtain an instance via [`ItemInformation::hashes`](crate::ItemInformation::hashes).
+#[derive(Debug)]
+pub struct ItemHashes<I> {
+    bucket_count: u32,
+    minimum_index: u32,
+    /// The hash value of every item, in index order.
+    hashes: Vec<u32>,
+    /// Pairs of item index and offset relative to the first item, in ascending order.
+    offsets: Vec<(u32, u32)>,
+    /// Pairs of name and preferred item.
+    adjusters: Vec<(StringRef, u32)>,
+    /// Pairs of hash and item index, sorted by hash and built on first use.
+    buckets: Once<Vec<(u32, u32)>>,
+    _ph: PhantomData<I>,
+}
+
+impl<I> ItemHashes<I>
+where
+    I: ItemIndex,
+{
+    /// Parses the hash stream described by `header`, whose item records are `records_size` bytes
+    /// long.
+    pub(crate) fn parse(stream: &Stream<'_>, header: &Header, records_size: usize) -> Result<Self> {
+        let data = stream.as_slice();
+        let count = (header.maximum_index - header.minimum_index) as usize;
+
+        let hash_values = hash_slice(data, header.hash_values)?;
+        let mut buf = ParseBuffer::from(hash_values);
+        let mut hashes = Vec::with_capacity(count);
+        if !buf.is_empty() {
+            let key_size = header.hash_key_size as usize;
+            if key_size * count != buf.len() {
+                return Err(Error::InvalidTypeInformationHeader(
+                    "hash values do not match the number of items",
+                ));
+            }
+
+            while !buf.is_empty() {
+                hashes.push(match key_size {
+                    2 => u32::from(buf.parse_u16()?),
+                    4 => buf.parse_u32()?,
+                    _ => {
+                        return Err(Error::InvalidTypeInformationHeader(
+                            "unsupported hash key size",
+                        ))
+                    }
+                });
+            }
+        }
+
+        let mut buf = ParseBuffer::from(hash_slice(data, header.ti_off)?);
+        let mut offsets: Vec<(u32, u32)> = Vec::with_capacity(buf.len() / 8);
+        while !buf.is_empty() {
+            let index = buf.parse_u32()?;
+            let offset = buf.parse_u32()?;
+
+            let ascending = offsets.last().map_or(true, |&(last_index, last_offset)| {
+                last_index < index && last_offset < offset
+            });
+            let in_range = index >= header.minimum_index
+                && index < header.maximum_index
+                && (offset as usize) < records_size;
+            if !ascending || !in_range {
+                return Err(Error::InvalidTypeInformationHeader(
+                    "invalid item index offsets",
+                ));
+            }
+
+            offsets.push((index, offset));
+        }
+
+        // The adjusters are a serialized hash table of name offsets and item indexes, see
+        // `PDBInformation::stream_names` for the format.
+        let mut adjusters = Vec::new();
+        let mut buf = ParseBuffer::from(hash_slice(data, header.hash_adj)?);
+        if !buf.is_empty() {
+            let size = buf.parse_u32()?;
+            let _capacity = buf.parse_u32()?;
+            let present_words = buf.parse_u32()?;
+            buf.take(present_words as usize * mem::size_of::<u32>())?;
+            let deleted_words = buf.parse_u32()?;
+            buf.take(deleted_words as usize * mem::size_of::<u32>())?;
+
+            for _ in 0..size {
+                let name = StringRef(buf.parse_u32()?);
+                adjusters.push((name, buf.parse_u32()?));
+            }
+        }
+
+        Ok(Self {
+            bucket_count: header.hash_bucket_size,
+            minimum_index: header.minimum_index,
+            hashes,
+            offsets,
+            adjusters,
+            buckets: Once::new(),
+            _ph: PhantomData,
+        })
+    }
+
+    /// Returns the number of buckets that hash values are reduced to.
+    pub fn bucket_count(&self) -> u32 {
+        self.bucket_count
+    }
+
+    /// Returns the hash value of an item, or `None` if the item does not exist or the hash stream
+    /// does not contain hash values.
+    pub fn hash(&self, index: I) -> Option<u32> {
+        let raw: u32 = index.into();
+        let position = raw.checked_sub(self.minimum_index)?;
+        self.hashes.get(position as usize).copied()
+    }
+
+    /// Returns the hash value of a name, reduced to the number of buckets.
+    ///
+    /// Classes, unions and enums are hashed by their name, or by their unique name if they are
+    /// scoped definitions. Use [`items_with_hash`](Self::items_with_hash) to find candidates for a
+    /// name.
+    pub fn hash_name(&self, name: &[u8]) -> u32 {
+        match self.bucket_count {
+            0 => 0,
+            count => hash_name(name) % count,
+        }
+    }
+
+    /// Returns the items with the given hash value, in ascending order.
+    ///
+    /// The first call sorts the hash values of all items. Subsequent calls perform a binary search.
+    pub fn items_with_hash(&self, hash: u32) -> impl Iterator<Item = I> + '_ {
+        let buckets = self.buckets.get_or_init(|| {
+            let mut buckets: Vec<(u32, u32)> = (self.minimum_index..)
+                .zip(&self.hashes)
+                .map(|(index, &hash)| (hash, index))
+                .collect();
+            buckets.sort_unstable();
+            buckets
+        });
+
+        let start = buckets.partition_point(|&(h, _)| h < hash);
+        buckets[start..]
+            .iter()
+            .take_while(move |&&(h, _)| h == hash)
+            .map(|&(_, index)| I::from(index))
+    }
+
+    /// Returns the hash adjusters, which map names in the [`StringTable`](crate::StringTable) to
+    /// the item preferred among several items with this name.
+    pub fn adjusters(&self) -> impl Iterator<Item = (StringRef, I)> + '_ {
+        self.adjusters
+            .iter()
+            .map(|&(name, index)| (name, I::from(index)))
+    }
+
+    /// Returns pairs of item index and byte offset relative to the first item, in ascending order.
+    pub(crate) fn offsets(&self) -> &[(u32, u32)] {
+        &self.offsets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_name() {
+        assert_eq!(hash_name(b""), 0x2024_0400);
+        // The hash of `std::exception` in `fixtures/self/foo.pdb`, reduced to 0x3ffff buckets.
+        assert_eq!(hash_name(b"std::exception") % 0x3ffff, 138_787);
+    }
+}
@@ -5,8 +5,78 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use core::fmt;
+
 use crate::common::*;
 
+/// The version of the type or id stream format.
+///
+/// Returned by [`ItemInformation::version`](crate::ItemInformation::version). Each version
+/// corresponds to the Visual C++ release that introduced it. Use [`u32::from`] to compare versions
+/// numerically.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TypeStreamVersion {
+    /// Visual C++ 4.0.
+    V40,
+    /// Visual C++ 4.1.
+    V41,
+    /// Pre-release versions of Visual C++ 5.0.
+    V50Interim,
+    /// Visual C++ 5.0.
+    V50,
+    /// Visual C++ 7.0 (Visual Studio .NET).
+    V70,
+    /// Visual C++ 8.0 (Visual Studio 2005) and later.
+    V80,
+    /// An unknown version.
+    OtherValue(u32),
+}
+
+impl From<u32> for TypeStreamVersion {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(v: u32) -> Self {
+        match v {
+            1995_04_10 => Self::V40,
+            1995_11_22 => Self::V41,
+            1996_03_07 => Self::V50Interim,
+            1996_10_31 => Self::V50,
+            1999_09_03 => Self::V70,
+            2004_02_03 => Self::V80,
+            _ => Self::OtherValue(v),
+        }
+    }
+}
+
+impl From<TypeStreamVersion> for u32 {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(version: TypeStreamVersion) -> Self {
+        match version {
+            TypeStreamVersion::V40 => 1995_04_10,
+            TypeStreamVersion::V41 => 1995_11_22,
+            TypeStreamVersion::V50Interim => 1996_03_07,
+            TypeStreamVersion::V50 => 1996_10_31,
+            TypeStreamVersion::V70 => 1999_09_03,
+            TypeStreamVersion::V80 => 2004_02_03,
+            TypeStreamVersion::OtherValue(v) => v,
+        }
+    }
+}
+
+impl fmt::Display for TypeStreamVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V40 => f.write_str("V40"),
+            Self::V41 => f.write_str("V41"),
+            Self::V50Interim => f.write_str("V50Interim"),
+            Self::V50 => f.write_str("V50"),
+            Self::V70 => f.write_str("V70"),
+            Self::V80 => f.write_str("V80"),
+            Self::OtherValue(v) => write!(f, "unknown ({})", v),
+        }
+    }
+}
+
 // OFFCB:
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Slice {
@@ -0,0 +1,207 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::sync::Once;
+use crate::tpi::{DefinitionIndex, FieldAttributes, TypeData, TypeFinder, TypeInformation};
+
+/// A direct base class of a class, struct or interface.
+///
+/// Returned by [`ClassHierarchy::bases_of`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ClassBase {
+    /// The base class.
+    ///
+    /// If the field list references a forward reference, this is the index of its definition. If
+    /// the type stream does not contain one, this is the forward reference.
+    pub index: TypeIndex,
+    /// Whether the base class is virtual.
+    pub is_virtual: bool,
+    /// The offset of a non-virtual base class within the derived class.
+    ///
+    /// Virtual base classes are located through the virtual base table at runtime, so this is
+    /// `None` for them.
+    pub offset: Option<u32>,
+    /// The access and other attributes of the base class.
+    pub attributes: FieldAttributes,
+}
+
+/// An index of the inheritance relations between the classes of a type stream.
+///
+/// Created by [`TypeInformation::class_hierarchy`]. Forward references passed to its methods are
+/// resolved to their definitions.
+#[derive(Debug)]
+pub struct ClassHierarchy<'t> {
+    finder: TypeFinder<'t>,
+    definitions: &'t DefinitionIndex,
+    /// Directly derived classes by base class, built on first use.
+    derived: Once<BTreeMap<TypeIndex, Vec<TypeIndex>>>,
+}
+
+impl<'t> ClassHierarchy<'t> {
+    /// Returns the direct base classes of the class at `index`, in declaration order.
+    ///
+    /// Indirect virtual base classes, which the field list of a class repeats for every virtual
+    /// base of its bases, are omitted.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotAClassType` if the type is not a class, struct or interface
+    /// * `Error::IncompleteType` if the type is a forward reference without definition
+    /// * `Error::TypeNotFound` if the type or its field list does not exist
+    /// * `Error::CyclicTypeReference` if the continuations of the field list form a cycle
+    pub fn bases_of(&self, index: TypeIndex) -> Result<Vec<ClassBase>> {
+        let index = self
+            .definitions
+            .resolve(index)
+            .ok_or(Error::IncompleteType(index.0))?;
+
+        let mut next = match self.finder.find(index)?.parse()? {
+            TypeData::Class(data) => data.fields,
+            _ => return Err(Error::NotAClassType(index.0)),
+        };
+
+        let mut bases = Vec::new();
+        let mut lists = Vec::new();
+        while let Some(field_list) = next {
+            if lists.contains(&field_list) {
+                return Err(Error::CyclicTypeReference(field_list.0));
+            }
+            lists.push(field_list);
+
+            let list = match self.finder.find(field_list)?.parse()? {
+                TypeData::FieldList(list) => list,
+                _ => break,
+            };
+
+            for field in list.fields {
+                let base = match field {
+                    TypeData::BaseClass(data) => ClassBase {
+                        index: data.base_class,
+                        is_virtual: false,
+                        offset: Some(data.offset),
+                        attributes: data.attributes,
+                    },
+                    TypeData::VirtualBaseClass(data) if data.direct => ClassBase {
+                        index: data.base_class,
+                        is_virtual: true,
+                        offset: None,
+                        attributes: data.attributes,
+                    },
+                    _ => continue,
+                };
+
+                bases.push(ClassBase {
+                    index: self.definitions.resolve(base.index).unwrap_or(base.index),
+                    ..base
+                });
+            }
+
+            next = list.continuation;
+        }
+
+        Ok(bases)
+    }
+
+    /// Returns the classes directly deriving from the class at `index`, in stream order.
+    ///
+    /// The first call scans the field lists of all classes in the stream to build a reverse index.
+    /// Classes whose field lists cannot be read are skipped.
+    pub fn derived_from(&self, index: TypeIndex) -> &[TypeIndex] {
+        let index = self.definitions.resolve(index).unwrap_or(index);
+        let derived = self.derived();
+        derived.get(&index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns all classes deriving from the class at `index` directly or indirectly.
+    ///
+    /// The classes are listed breadth first, so that every class is listed after one of its bases.
+    /// Classes inheriting from the same base on multiple paths are listed once.
+    pub fn all_derived_from(&self, index: TypeIndex) -> Vec<TypeIndex> {
+        let index = self.definitions.resolve(index).unwrap_or(index);
+        let mut seen = BTreeSet::new();
+        seen.insert(index);
+
+        let mut all = Vec::new();
+        let mut position = 0;
+        let mut current = index;
+        loop {
+            for &derived in self.derived_from(current) {
+                if seen.insert(derived) {
+                    all.push(derived);
+                }
+            }
+
+            match all.get(position) {
+                Some(&next) => current = next,
+                None => break,
+            }
+            position += 1;
+        }
+
+        all
+    }
+
+    /// Returns the reverse index of base classes, building it on first use.
+    fn derived(&self) -> &BTreeMap<TypeIndex, Vec<TypeIndex>> {
+        self.derived.get_or_init(|| {
+            let mut derived: BTreeMap<TypeIndex, Vec<TypeIndex>> = BTreeMap::new();
+            for &class in self.definitions.classes() {
+                for base in self.bases_of(class).unwrap_or_default() {
+                    derived.entry(base.index).or_default().push(class);
+                }
+            }
+            derived
+        })
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Indexes the inheritance relations between the classes in this type stream.
+    ///
+    /// The first call iterates the entire stream once to index all types and to find the
+    /// definitions of forward references. This index is shared with [`layouts`](Self::layouts) and
+    /// [`find_by_name`](Self::find_by_name), so the stream is scanned at most once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let hierarchy = type_information.class_hierarchy()?;
+    ///
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+    ///         if class.name == "std::exception".into() && !class.properties.forward_reference() {
+    ///             for derived in hierarchy.all_derived_from(typ.index()) {
+    ///                 println!("{:?} derives from std::exception", derived);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn class_hierarchy(&self) -> Result<ClassHierarchy<'_>> {
+        let (finder, definitions) = self.indexed_finder()?;
+
+        Ok(ClassHierarchy {
+            finder,
+            definitions,
+            derived: Once::new(),
+        })
+    }
+}
@@ -1,7 +1,15 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 use scroll::ctx::TryFromCtx;
 
 use crate::common::*;
+use crate::strings::StringTable;
+use crate::sync::{self, Lock, Once};
 use crate::tpi::constants::*;
+use crate::tpi::IdInformation;
+use crate::FallibleIterator;
 
 #[inline]
 fn parse_optional_id_index(buf: &mut ParseBuffer<'_>) -> Result<Option<IdIndex>> {
@@ -164,11 +172,11 @@ pub enum UserDefinedTypeSourceFileRef {
     ///
     /// The index should resolve to a [`IdData::String`].
     Local(IdIndex),
-    /// Reference into the [`StringTable`](crate::StringTable) of another module that contributes
-    /// this UDT definition.
+    /// The one-based index of the module that contributes this UDT definition, and the offset of
+    /// the source file name in the [`StringTable`](crate::StringTable).
     ///
-    /// Use [`DebugInformation::modules`](crate::DebugInformation::modules) to resolve the
-    /// corresponding module.
+    /// Subtract one from the module index to look up the module in
+    /// [`DebugInformation::modules`](crate::DebugInformation::modules).
     Remote(u16, StringRef),
 }
 
@@ -182,3 +190,350 @@ pub struct UserDefinedTypeSourceId {
     /// Line number in the source file.
     pub line: u32,
 }
+
+/// The location of the definition of a User Defined Type (UDT) in the source code.
+///
+/// Returned by [`IdInformation::type_source`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeSourceLocation {
+    /// The path of the source file, as recorded by the compiler.
+    pub file: Arc<str>,
+    /// The line number of the definition in the source file.
+    pub line: u32,
+    /// Index of the module in [`DebugInformation::modules`](crate::DebugInformation::modules) that
+    /// contributes the definition, if recorded.
+    pub module: Option<usize>,
+}
+
+/// Memoized source locations of UDTs, see [`IdInformation::udt_source`].
+#[derive(Debug)]
+pub(crate) struct UdtSourceCache {
+    /// All `LF_UDT_SRC_LINE` and `LF_UDT_MOD_SRC_LINE` records by the UDT they describe.
+    sources: Once<BTreeMap<TypeIndex, UserDefinedTypeSourceId>>,
+}
+
+impl UdtSourceCache {
+    pub fn new() -> Self {
+        Self {
+            sources: Once::new(),
+        }
+    }
+}
+
+/// The build information of a module, with all strings resolved.
+///
+/// Returned by [`IdInformation::build_info`]. Each field is `None` if the compiler did not record
+/// it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The working directory of the compiler.
+    pub current_directory: Option<Arc<str>>,
+    /// The path of the compiler executable.
+    pub build_tool: Option<Arc<str>>,
+    /// The path of the compiled source file, relative to the working directory.
+    pub source_file: Option<Arc<str>>,
+    /// The path of the PDB written by the compiler.
+    pub program_database: Option<Arc<str>>,
+    /// The command line arguments of the compiler, excluding the source file and executable.
+    pub command_line: Option<Arc<str>>,
+}
+
+/// Memoized full strings of [`StringId`] records, see [`IdInformation::string`].
+#[derive(Debug)]
+pub(crate) struct StringCache {
+    /// Positions of all `LF_STRING_ID`, `LF_SUBSTR_LIST` and `LF_BUILDINFO` records by their
+    /// index.
+    positions: Once<BTreeMap<u32, usize>>,
+    /// Full strings that have been resolved so far.
+    strings: Lock<BTreeMap<u32, Arc<str>>>,
+}
+
+impl StringCache {
+    pub fn new() -> Self {
+        Self {
+            positions: Once::new(),
+            strings: Lock::new(BTreeMap::new()),
+        }
+    }
+}
+
+/// A pending step while resolving a string id and its substrings.
+enum ResolveStep<'t> {
+    /// Load the string id and schedule its substrings.
+    Enter(u32),
+    /// Concatenate the resolved substrings and the name of the string id.
+    Finish(u32, Vec<u32>, RawString<'t>),
+}
+
+impl<'s> IdInformation<'s> {
+    /// Returns the full string of the [`StringId`] at the given index.
+    ///
+    /// Long strings, such as command lines in build information, are split into a list of
+    /// substrings which are themselves string ids. This resolves the entire chain and returns the
+    /// concatenation of all substrings followed by the string's own name. Invalid UTF-8 sequences
+    /// are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// Resolved strings are cached, so substrings shared by several strings are only resolved once.
+    /// The first call scans the entire stream to locate all string records.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the index, or one of its substrings, does not refer to a
+    ///   string id.
+    /// * `Error::CyclicStringId(index)` if a string id contains itself as a substring.
+    /// * `Error::UnexpectedEof` if a record is malformed.
+    pub fn string(&self, index: IdIndex) -> Result<Arc<str>> {
+        let positions = self.record_positions()?;
+
+        self.strings.strings.with(|strings| {
+            if let Some(string) = strings.get(&index.0) {
+                return Ok(string.clone());
+            }
+
+            let mut active = BTreeSet::new();
+            let mut pending = Vec::new();
+            pending.push(ResolveStep::Enter(index.0));
+
+            while let Some(step) = pending.pop() {
+                match step {
+                    ResolveStep::Enter(index) => {
+                        if strings.contains_key(&index) {
+                            continue;
+                        }
+
+                        if !active.insert(index) {
+                            return Err(Error::CyclicStringId(index));
+                        }
+
+                        let string = match self.record_at(positions, index)? {
+                            IdData::String(string) => string,
+                            _ => return Err(Error::TypeNotFound(index)),
+                        };
+
+                        let substrings = match string.substrings {
+                            Some(list) => match self.record_at(positions, list.0)? {
+                                IdData::StringList(list) => {
+                                    list.substrings.iter().map(|index| index.0).collect()
+                                }
+                                _ => return Err(Error::TypeNotFound(list.0)),
+                            },
+                            None => Vec::new(),
+                        };
+
+                        // Substrings are resolved first, as they are pushed on top of this step.
+                        let children = substrings.iter().rev().map(|&s| ResolveStep::Enter(s));
+                        pending.push(ResolveStep::Finish(index, substrings.clone(), string.name));
+                        pending.extend(children);
+                    }
+                    ResolveStep::Finish(index, substrings, name) => {
+                        let mut string = String::new();
+                        for substring in &substrings {
+                            string.push_str(&strings[substring]);
+                        }
+                        string.push_str(&name.to_string());
+
+                        strings.insert(index, string.into());
+                        active.remove(&index);
+                    }
+                }
+            }
+
+            Ok(strings[&index.0].clone())
+        })
+    }
+
+    /// Returns the record declaring the source file and line of a UDT definition.
+    ///
+    /// The compiler emits such a record for every class, struct, union and enum defined in the
+    /// source code, keyed by the index of the definition. Forward references have no source
+    /// record; resolve them with
+    /// [`TypeInformation::resolve_definition`](crate::TypeInformation::resolve_definition) first.
+    /// Returns `None` if the stream contains no record for this type.
+    ///
+    /// The first call scans the entire stream to index all records.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if a record is malformed.
+    pub fn udt_source(&self, udt: TypeIndex) -> Result<Option<UserDefinedTypeSourceId>> {
+        let sources =
+            sync::get_or_try_init(&self.udt_sources.sources, || self.collect_udt_sources())?;
+        Ok(sources.get(&udt).cloned())
+    }
+
+    /// Collects all UDT source records, keeping the first record of every UDT.
+    fn collect_udt_sources(&self) -> Result<BTreeMap<TypeIndex, UserDefinedTypeSourceId>> {
+        let mut sources = BTreeMap::new();
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            if !matches!(item.raw_kind(), LF_UDT_SRC_LINE | LF_UDT_MOD_SRC_LINE) {
+                continue;
+            }
+
+            if let IdData::UserDefinedTypeSource(source) = item.parse()? {
+                sources.entry(source.udt).or_insert(source);
+            }
+        }
+
+        Ok(sources)
+    }
+
+    /// Returns the source file, line and module of the definition of a UDT.
+    ///
+    /// This looks up the record with [`udt_source`](Self::udt_source) and resolves the name of the
+    /// source file, either from a [`StringId`] of this stream or from the global string table.
+    /// Returns `None` if the stream contains no record for this type.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the file name refers to a missing string id.
+    /// * `Error::UnexpectedEof` if a record or the string table is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let id_information = pdb.id_information()?;
+    /// let strings = pdb.string_table()?;
+    ///
+    /// if let Some(index) = type_information.find_by_name("std::exception")? {
+    ///     if let Some(location) = id_information.type_source(index, &strings)? {
+    ///         println!("{}:{}", location.file, location.line);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn type_source(
+        &self,
+        udt: TypeIndex,
+        strings: &StringTable<'_>,
+    ) -> Result<Option<TypeSourceLocation>> {
+        let source = match self.udt_source(udt)? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let (file, module) = match source.source_file {
+            UserDefinedTypeSourceFileRef::Local(id) => (self.string(id)?, None),
+            UserDefinedTypeSourceFileRef::Remote(module, name) => {
+                let file = strings.get(name)?.to_string().into();
+                (file, usize::from(module).checked_sub(1))
+            }
+        };
+
+        Ok(Some(TypeSourceLocation {
+            file,
+            line: source.line,
+            module,
+        }))
+    }
+
+    /// Returns the build information at the given index, resolving all of its strings.
+    ///
+    /// `LF_BUILDINFO` records list string ids for the working directory, compiler, source file, PDB
+    /// and command line of a module. This resolves each of them with [`string`](Self::string),
+    /// which concatenates the substrings of long values such as command lines. Modules reference
+    /// their build information with a [`BuildInfoSymbol`](crate::BuildInfoSymbol).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the index does not refer to build information, or one of
+    ///   its arguments does not refer to a string id.
+    /// * `Error::CyclicStringId(index)` if a string id contains itself as a substring.
+    /// * `Error::UnexpectedEof` if a record is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let id_information = pdb.id_information()?;
+    /// let dbi = pdb.debug_information()?;
+    /// let mut modules = dbi.modules()?;
+    /// while let Some(module) = modules.next()? {
+    ///     let info = match pdb.module_info(&module)? {
+    ///         Some(info) => info,
+    ///         None => continue,
+    ///     };
+    ///
+    ///     let mut symbols = info.symbols()?;
+    ///     while let Some(symbol) = symbols.next()? {
+    ///         if let Ok(pdb::SymbolData::BuildInfo(symbol)) = symbol.parse() {
+    ///             let build_info = id_information.build_info(symbol.id)?;
+    ///             println!("{:?} {:?}", build_info.build_tool, build_info.command_line);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn build_info(&self, index: IdIndex) -> Result<BuildInfo> {
+        let positions = self.record_positions()?;
+        let arguments = match self.record_at(positions, index.0)? {
+            IdData::BuildInfo(build_info) => build_info.arguments,
+            _ => return Err(Error::TypeNotFound(index.0)),
+        };
+
+        // Arguments are ordered as in `CV_BuildInfo_e`, and a zero index marks a missing string.
+        let argument = |position: usize| match arguments.get(position) {
+            Some(&IdIndex(0)) | None => Ok(None),
+            Some(&index) => self.string(index).map(Some),
+        };
+
+        Ok(BuildInfo {
+            current_directory: argument(0)?,
+            build_tool: argument(1)?,
+            source_file: argument(2)?,
+            program_database: argument(3)?,
+            command_line: argument(4)?,
+        })
+    }
+
+    /// Returns the positions of all records that can be looked up by index, locating them on first
+    /// use.
+    fn record_positions(&self) -> Result<&BTreeMap<u32, usize>> {
+        sync::get_or_try_init(&self.strings.positions, || self.locate_records())
+    }
+
+    /// Locates all records that participate in string ids or build information.
+    fn locate_records(&self) -> Result<BTreeMap<u32, usize>> {
+        let mut positions = BTreeMap::new();
+        let mut iter = self.iter();
+
+        loop {
+            let position = iter.buf.pos();
+            let item = match iter.next()? {
+                Some(item) => item,
+                None => break,
+            };
+
+            if matches!(
+                item.raw_kind(),
+                LF_STRING_ID | LF_SUBSTR_LIST | LF_BUILDINFO
+            ) {
+                positions.insert(item.index.0, position);
+            }
+        }
+
+        Ok(positions)
+    }
+
+    /// Parses the record at the given index.
+    fn record_at(&self, positions: &BTreeMap<u32, usize>, index: u32) -> Result<IdData<'_>> {
+        let position = *positions.get(&index).ok_or(Error::TypeNotFound(index))?;
+
+        let mut buf = self.stream.parse_buffer();
+        buf.take(position)?;
+        let length = buf.parse_u16()?;
+        ParseBuffer::from(buf.take(length as usize)?).parse()
+    }
+}
@@ -0,0 +1,715 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::tpi::primitive::type_data_for_primitive;
+use crate::tpi::{DefinitionIndex, TypeData, TypeFinder, TypeInformation};
+
+/// The kind of a [`FieldLayout`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FieldLayoutKind {
+    /// A non-static data member.
+    Member,
+    /// A direct, non-virtual base class.
+    BaseClass,
+    /// The pointer to the virtual function table.
+    VirtualFunctionTablePointer,
+    /// The pointer to the virtual base table.
+    VirtualBasePointer,
+}
+
+/// The bits of a storage unit occupied by a bitfield member.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BitfieldLayout {
+    /// The index of the first bit, counted from the least significant bit of the storage unit.
+    pub position: u8,
+    /// The number of bits.
+    pub length: u8,
+}
+
+/// A bitfield member within a [`BitfieldUnit`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BitfieldMember<'t> {
+    /// The name of the member.
+    pub name: RawString<'t>,
+    /// The `LF_BITFIELD` record describing the member.
+    pub type_index: TypeIndex,
+    /// The offset of the storage unit in bytes from the start of the enclosing type.
+    pub byte_offset: u64,
+    /// The index of the first bit, counted from the least significant bit of the storage unit.
+    pub bit_offset: u8,
+    /// The number of bits.
+    pub bit_width: u8,
+}
+
+impl BitfieldMember<'_> {
+    /// Returns the offset of the first bit from the start of the enclosing type.
+    pub fn absolute_bit_offset(&self) -> u64 {
+        self.byte_offset * 8 + u64::from(self.bit_offset)
+    }
+
+    /// Returns the bits of the storage unit occupied by the member.
+    ///
+    /// Bits beyond the first 64 bits of the storage unit are not represented.
+    pub fn mask(&self) -> u64 {
+        let bits = match self.bit_width {
+            0 => 0,
+            width if width >= 64 => u64::MAX,
+            width => (1 << width) - 1,
+        };
+        bits.checked_shl(self.bit_offset.into()).unwrap_or(0)
+    }
+}
+
+/// A storage unit shared by consecutive bitfield members.
+///
+/// Returned by [`TypeLayout::bitfield_units`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitfieldUnit<'t> {
+    /// The offset of the storage unit in bytes from the start of the enclosing type.
+    pub offset: u64,
+    /// The size of the storage unit in bytes, which is the size of the members' declared type.
+    pub size: u64,
+    /// The members stored in the unit, sorted by bit offset.
+    pub members: Vec<BitfieldMember<'t>>,
+}
+
+impl BitfieldUnit<'_> {
+    /// Returns the number of bits in the storage unit not occupied by any member.
+    pub fn unused_bits(&self) -> u64 {
+        let used = self
+            .members
+            .iter()
+            .fold(0u64, |mask, member| mask | member.mask());
+        (self.size * 8).saturating_sub(used.count_ones().into())
+    }
+}
+
+/// The placement of a field within a [`TypeLayout`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FieldLayout<'t> {
+    /// The kind of field.
+    pub kind: FieldLayoutKind,
+    /// The name of the member, or `None` for base classes and compiler generated pointers.
+    pub name: Option<RawString<'t>>,
+    /// The type of the field.
+    ///
+    /// For bitfields, this is the `LF_BITFIELD` record rather than the type of the storage unit.
+    pub type_index: TypeIndex,
+    /// The offset of the field in bytes from the start of the enclosing type.
+    ///
+    /// For bitfields, this is the offset of the storage unit.
+    pub offset: u64,
+    /// The size of the field in bytes.
+    ///
+    /// For bitfields, this is the size of the storage unit.
+    pub size: u64,
+    /// The natural alignment of the field's type in bytes.
+    pub alignment: u64,
+    /// The bits occupied by a bitfield member, or `None` for all other fields.
+    pub bitfield: Option<BitfieldLayout>,
+}
+
+/// A range of bytes within a [`TypeLayout`] not covered by any field.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PaddingHole {
+    /// The offset of the first padding byte from the start of the type.
+    pub offset: u64,
+    /// The number of padding bytes.
+    pub size: u64,
+}
+
+/// The memory layout of a class, struct or union.
+///
+/// Returned by [`TypeLayouts::layout`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeLayout<'t> {
+    /// The index of the type's definition.
+    ///
+    /// If the layout was requested for a forward reference, this is the index of the definition
+    /// it resolved to.
+    pub index: TypeIndex,
+    /// The name of the type.
+    pub name: RawString<'t>,
+    /// The size of the type in bytes.
+    pub size: u64,
+    /// The alignment of the type in bytes.
+    ///
+    /// This is the largest natural alignment of its fields, reduced to a divisor of the size for
+    /// packed types.
+    pub alignment: u64,
+    /// The fields of the type, sorted by offset.
+    ///
+    /// Fields of unions and bitfields sharing a storage unit have the same offset.
+    pub fields: Vec<FieldLayout<'t>>,
+    /// The bytes not covered by any field, including padding at the end of the type.
+    pub holes: Vec<PaddingHole>,
+}
+
+impl<'t> TypeLayout<'t> {
+    /// Returns the total number of padding bytes.
+    pub fn padding(&self) -> u64 {
+        self.holes.iter().map(|hole| hole.size).sum()
+    }
+
+    /// Groups the bitfield members of this type into their storage units, sorted by offset.
+    ///
+    /// MSVC allocates consecutive bitfields in a storage unit of their declared type, starting
+    /// from the least significant bit. A new unit starts when the size of the declared type changes,
+    /// or when the remaining bits of the unit cannot hold the next member. Since the PDB records
+    /// the offset of every unit, members are grouped by the offset and size of their unit.
+    ///
+    /// In unions, all bitfields are placed at the start of the same unit and overlap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let layouts = type_information.layouts()?;
+    ///
+    /// # let index = pdb::TypeIndex(0x11be);
+    /// for unit in layouts.layout(index)?.bitfield_units() {
+    ///     for member in &unit.members {
+    ///         println!(
+    ///             "{}: byte {} bits {}..{}",
+    ///             member.name,
+    ///             member.byte_offset,
+    ///             member.bit_offset,
+    ///             member.bit_offset + member.bit_width,
+    ///         );
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn bitfield_units(&self) -> Vec<BitfieldUnit<'t>> {
+        let mut units: Vec<BitfieldUnit<'t>> = Vec::new();
+
+        for field in &self.fields {
+            let (bitfield, name) = match (field.bitfield, field.name) {
+                (Some(bitfield), Some(name)) => (bitfield, name),
+                _ => continue,
+            };
+
+            let member = BitfieldMember {
+                name,
+                type_index: field.type_index,
+                byte_offset: field.offset,
+                bit_offset: bitfield.position,
+                bit_width: bitfield.length,
+            };
+
+            match units
+                .iter_mut()
+                .find(|unit| unit.offset == field.offset && unit.size == field.size)
+            {
+                Some(unit) => unit.members.push(member),
+                None => units.push(BitfieldUnit {
+                    offset: field.offset,
+                    size: field.size,
+                    members: alloc::vec![member],
+                }),
+            }
+        }
+
+        for unit in &mut units {
+            unit.members.sort_by_key(|member| member.bit_offset);
+        }
+
+        units
+    }
+}
+
+/// The default limit of nested types resolved by [`TypeLayouts`].
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Computes memory layouts of classes, structs and unions in a type stream.
+///
+/// Member types are resolved recursively to determine their sizes and alignments, and forward
+/// references are resolved to their definitions. Virtual base classes are not part of the layout,
+/// since their placement is only known to the virtual base table. Their storage at the end of a
+/// class is reported as padding.
+///
+/// Types that contain themselves, which only occur in corrupt type streams, are detected and
+/// reported as errors. The nesting of types is limited to protect against stack overflows, see
+/// [`max_depth`](Self::max_depth).
+///
+/// Created by [`TypeInformation::layouts`].
+#[derive(Debug)]
+pub struct TypeLayouts<'t> {
+    finder: TypeFinder<'t>,
+    definitions: &'t DefinitionIndex,
+    /// The size of pointers that do not declare their size.
+    pointer_size: u64,
+    /// The maximum number of nested types resolved for a single request.
+    max_depth: usize,
+}
+
+impl<'t> TypeLayouts<'t> {
+    /// Sets the size of pointers that do not declare their size, such as 16-bit or based
+    /// pointers.
+    ///
+    /// By default, this is the pointer size of the target machine, inferred from the most common
+    /// size of pointer types in the stream.
+    pub fn pointer_size(mut self, size: u64) -> Self {
+        self.pointer_size = size;
+        self
+    }
+
+    /// Sets the maximum number of nested types resolved to compute a size or layout.
+    ///
+    /// Each class, union, array, modifier, enum and bitfield between the requested type and a
+    /// primitive or pointer type counts as one level. Requests exceeding the limit fail with
+    /// `Error::TypeDepthExceeded`. The default is 128, which is far deeper than types in practice.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Returns the size of a value of the type at `index` in bytes.
+    ///
+    /// Forward references are resolved to their definitions, and modifiers, enums and bitfields
+    /// to their underlying types. Procedure types have no size.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IncompleteType` if the type is a forward reference without definition
+    /// * `Error::TypeNotFound` if a type referenced by the type does not exist
+    /// * `Error::CyclicTypeReference` if the type contains itself
+    /// * `Error::TypeDepthExceeded` if the type is nested deeper than [`max_depth`](Self::max_depth)
+    pub fn size_of(&self, index: TypeIndex) -> Result<u64> {
+        self.size_and_alignment(index, &mut Vec::new())
+            .map(|(size, _)| size)
+    }
+
+    /// Computes the layout of the class, struct or union at `index`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotAClassType` if the type is not a class, struct or union
+    /// * `Error::IncompleteType` if the type or the type of a field is a forward reference without
+    ///   definition
+    /// * `Error::TypeNotFound` if a type referenced by the layout does not exist
+    /// * `Error::CyclicTypeReference` if the type contains itself
+    /// * `Error::TypeDepthExceeded` if the type is nested deeper than [`max_depth`](Self::max_depth)
+    pub fn layout(&self, index: TypeIndex) -> Result<TypeLayout<'t>> {
+        let index = self.definition(index)?;
+
+        let (name, size, field_list) = match self.finder.find(index)?.parse()? {
+            TypeData::Class(data) => (data.name, data.size, data.fields),
+            TypeData::Union(data) => (data.name, data.size, Some(data.fields)),
+            _ => return Err(Error::NotAClassType(index.0)),
+        };
+
+        let mut path = alloc::vec![index];
+        let fields = self.fields(field_list, &mut path)?;
+
+        Ok(TypeLayout {
+            index,
+            name,
+            size,
+            alignment: alignment(&fields, size),
+            holes: holes(&fields, size),
+            fields,
+        })
+    }
+
+    /// Resolves forward references to their definitions.
+    fn definition(&self, index: TypeIndex) -> Result<TypeIndex> {
+        self.definitions
+            .resolve(index)
+            .ok_or(Error::IncompleteType(index.0))
+    }
+
+    /// Returns the size and alignment of a type in bytes.
+    ///
+    /// `path` holds the types being resolved by the callers, to detect cycles and limit the depth.
+    fn size_and_alignment(
+        &self,
+        index: TypeIndex,
+        path: &mut Vec<TypeIndex>,
+    ) -> Result<(u64, u64)> {
+        let index = self.definition(index)?;
+
+        if path.contains(&index) {
+            return Err(Error::CyclicTypeReference(index.0));
+        }
+        if path.len() >= self.max_depth {
+            return Err(Error::TypeDepthExceeded(index.0));
+        }
+
+        path.push(index);
+        let result = self.resolve_size_and_alignment(index, path);
+        path.pop();
+        result
+    }
+
+    /// Returns the size and alignment of a type, which has been added to `path`.
+    fn resolve_size_and_alignment(
+        &self,
+        index: TypeIndex,
+        path: &mut Vec<TypeIndex>,
+    ) -> Result<(u64, u64)> {
+        Ok(match self.finder.find(index)?.parse()? {
+            TypeData::Primitive(data) => {
+                let size = data.size();
+                (size, natural_alignment(size, 16))
+            }
+            TypeData::Pointer(data) => {
+                let size = match data.attributes.size() {
+                    0 => self.pointer_size,
+                    size => size.into(),
+                };
+                (size, natural_alignment(size, 8))
+            }
+            TypeData::Modifier(data) => self.size_and_alignment(data.underlying_type, path)?,
+            TypeData::Bitfield(data) => self.size_and_alignment(data.underlying_type, path)?,
+            TypeData::Enumeration(data) => self.size_and_alignment(data.underlying_type, path)?,
+            TypeData::Array(data) => {
+                // Arrays declare their size, even if their element type is incomplete.
+                let alignment = match self.size_and_alignment(data.element_type, path) {
+                    Ok((_, alignment)) => alignment,
+                    Err(Error::IncompleteType(_)) => 1,
+                    Err(error) => return Err(error),
+                };
+                let size = data.dimensions.last().map_or(0, |&size| size.into());
+                (size, alignment)
+            }
+            TypeData::Class(data) => {
+                let fields = self.fields(data.fields, path)?;
+                (data.size, alignment(&fields, data.size))
+            }
+            TypeData::Union(data) => {
+                let fields = self.fields(Some(data.fields), path)?;
+                (data.size, alignment(&fields, data.size))
+            }
+            _ => (0, 1),
+        })
+    }
+
+    /// Collects the fields of a field list and its continuations, sorted by offset.
+    fn fields(
+        &self,
+        field_list: Option<TypeIndex>,
+        path: &mut Vec<TypeIndex>,
+    ) -> Result<Vec<FieldLayout<'t>>> {
+        let mut fields = Vec::new();
+        let mut lists = Vec::new();
+        let mut next = field_list;
+
+        while let Some(index) = next {
+            if lists.contains(&index) {
+                return Err(Error::CyclicTypeReference(index.0));
+            }
+            lists.push(index);
+
+            let list = match self.finder.find(index)?.parse()? {
+                TypeData::FieldList(list) => list,
+                _ => break,
+            };
+
+            for field in list.fields {
+                if let Some(field) = self.field(field, &fields, path)? {
+                    fields.push(field);
+                }
+            }
+
+            next = list.continuation;
+        }
+
+        fields.sort_by_key(|field| field.offset);
+        Ok(fields)
+    }
+
+    /// Places a single entry of a field list, skipping entries that do not occupy storage.
+    fn field(
+        &self,
+        field: TypeData<'t>,
+        fields: &[FieldLayout<'t>],
+        path: &mut Vec<TypeIndex>,
+    ) -> Result<Option<FieldLayout<'t>>> {
+        let (kind, name, type_index, offset, storage, bitfield) = match field {
+            TypeData::Member(data) => {
+                let (storage, bitfield) = match self.finder.find(data.field_type)?.parse()? {
+                    TypeData::Bitfield(bitfield) => (
+                        bitfield.underlying_type,
+                        Some(BitfieldLayout {
+                            position: bitfield.position,
+                            length: bitfield.length,
+                        }),
+                    ),
+                    _ => (data.field_type, None),
+                };
+
+                let kind = FieldLayoutKind::Member;
+                (
+                    kind,
+                    Some(data.name),
+                    data.field_type,
+                    data.offset,
+                    storage,
+                    bitfield,
+                )
+            }
+            TypeData::BaseClass(data) => {
+                let kind = FieldLayoutKind::BaseClass;
+                let offset = data.offset.into();
+                (kind, None, data.base_class, offset, data.base_class, None)
+            }
+            TypeData::VirtualBaseClass(data) => {
+                // All virtual bases share a single pointer to the virtual base table.
+                let offset = data.base_pointer_offset.into();
+                if fields.iter().any(|field| {
+                    field.kind == FieldLayoutKind::VirtualBasePointer && field.offset == offset
+                }) {
+                    return Ok(None);
+                }
+
+                let kind = FieldLayoutKind::VirtualBasePointer;
+                (
+                    kind,
+                    None,
+                    data.base_pointer,
+                    offset,
+                    data.base_pointer,
+                    None,
+                )
+            }
+            TypeData::VirtualFunctionTablePointer(data) => {
+                let kind = FieldLayoutKind::VirtualFunctionTablePointer;
+                (kind, None, data.table, 0, data.table, None)
+            }
+            _ => return Ok(None),
+        };
+
+        let (size, alignment) = self.size_and_alignment(storage, path)?;
+
+        Ok(Some(FieldLayout {
+            kind,
+            name,
+            type_index,
+            offset,
+            size,
+            alignment,
+            bitfield,
+        }))
+    }
+}
+
+/// Returns the largest power of two dividing `size`, limited to `max`.
+fn natural_alignment(size: u64, max: u64) -> u64 {
+    if size == 0 {
+        return 1;
+    }
+
+    (1 << size.trailing_zeros()).min(max)
+}
+
+/// Returns the alignment of a type with the given fields.
+///
+/// The size of a type is always a multiple of its alignment. If it is not a multiple of the
+/// largest field alignment, the type is packed and its alignment is reduced accordingly.
+fn alignment(fields: &[FieldLayout<'_>], size: u64) -> u64 {
+    let alignment = fields
+        .iter()
+        .map(|field| field.alignment)
+        .max()
+        .unwrap_or(1);
+    alignment.min(natural_alignment(size, alignment))
+}
+
+/// Returns the ranges of a type with the given fields that are not covered by any field.
+fn holes(fields: &[FieldLayout<'_>], size: u64) -> Vec<PaddingHole> {
+    let mut holes = Vec::new();
+    let mut end = 0;
+
+    for field in fields {
+        if field.offset > end {
+            holes.push(PaddingHole {
+                offset: end,
+                size: field.offset - end,
+            });
+        }
+        end = end.max(field.offset.saturating_add(field.size));
+    }
+
+    if size > end {
+        holes.push(PaddingHole {
+            offset: end,
+            size: size - end,
+        });
+    }
+
+    holes
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Prepares the computation of memory layouts of the classes, structs and unions in this type
+    /// stream.
+    ///
+    /// The first call iterates the entire stream once to index all types, to find the definitions
+    /// of forward references, and to infer the pointer size of the target machine. The index is
+    /// kept by this `TypeInformation`, so subsequent calls do not scan the stream again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<usize> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let layouts = type_information.layouts()?;
+    ///
+    /// # let mut count = 0;
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+    ///         if class.properties.forward_reference() {
+    ///             continue;
+    ///         }
+    ///
+    ///         let layout = layouts.layout(typ.index())?;
+    ///         if layout.padding() > 0 {
+    ///             println!("{} wastes {} bytes", layout.name, layout.padding());
+    ///         }
+    ///         # count += 1;
+    ///     }
+    /// }
+    /// # Ok(count)
+    /// # }
+    /// # assert!(test().expect("test") > 0);
+    /// ```
+    pub fn layouts(&self) -> Result<TypeLayouts<'_>> {
+        let (finder, definitions) = self.indexed_finder()?;
+
+        Ok(TypeLayouts {
+            finder,
+            definitions,
+            pointer_size: definitions.pointer_size(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        })
+    }
+
+    /// Returns the size of a value of the type at `index` in bytes.
+    ///
+    /// This is a shorthand for [`TypeLayouts::size_of`] with the default settings. The first call
+    /// for a type that is not primitive iterates the entire stream, see [`layouts`](Self::layouts).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// // unsigned __int64
+    /// assert_eq!(type_information.size_of(pdb::TypeIndex(0x23))?, 8);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IncompleteType` if the type is a forward reference without definition
+    /// * `Error::TypeNotFound` if a type referenced by the type does not exist
+    pub fn size_of(&self, index: TypeIndex) -> Result<u64> {
+        if index < TypeIndex(0x1000) {
+            if let TypeData::Primitive(data) = type_data_for_primitive(index)? {
+                return Ok(data.size());
+            }
+        }
+
+        self.layouts()?.size_of(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(offset: u64, size: u64, alignment: u64) -> FieldLayout<'static> {
+        FieldLayout {
+            kind: FieldLayoutKind::Member,
+            name: None,
+            type_index: TypeIndex(0x74),
+            offset,
+            size,
+            alignment,
+            bitfield: None,
+        }
+    }
+
+    #[test]
+    fn test_holes() {
+        let fields = [
+            field(0, 4, 4),
+            field(8, 8, 8),
+            field(8, 4, 4),
+            field(16, 1, 1),
+        ];
+        assert_eq!(
+            holes(&fields, 24),
+            [
+                PaddingHole { offset: 4, size: 4 },
+                PaddingHole {
+                    offset: 17,
+                    size: 7
+                },
+            ]
+        );
+        assert_eq!(holes(&[], 4), [PaddingHole { offset: 0, size: 4 }]);
+        assert_eq!(holes(&[field(0, 8, 8)], 8), []);
+    }
+
+    #[test]
+    fn test_bitfield_mask() {
+        let member = |bit_offset, bit_width| BitfieldMember {
+            name: "".into(),
+            type_index: TypeIndex(0x1000),
+            byte_offset: 0,
+            bit_offset,
+            bit_width,
+        };
+
+        assert_eq!(member(0, 1).mask(), 0x1);
+        assert_eq!(member(4, 4).mask(), 0xf0);
+        assert_eq!(member(0, 64).mask(), u64::MAX);
+        assert_eq!(member(63, 1).mask(), 1 << 63);
+        assert_eq!(member(0, 0).mask(), 0);
+
+        let unit = BitfieldUnit {
+            offset: 0,
+            size: 1,
+            members: alloc::vec![member(0, 3), member(4, 2)],
+        };
+        assert_eq!(unit.unused_bits(), 3);
+    }
+
+    #[test]
+    fn test_alignment() {
+        assert_eq!(alignment(&[field(0, 4, 4), field(8, 8, 8)], 16), 8);
+        // #pragma pack(1)
+        assert_eq!(alignment(&[field(0, 1, 1), field(1, 4, 4)], 5), 1);
+        // #pragma pack(2)
+        assert_eq!(alignment(&[field(0, 2, 2), field(2, 8, 8)], 10), 2);
+        assert_eq!(alignment(&[], 1), 1);
+        assert_eq!(natural_alignment(10, 16), 2);
+        assert_eq!(natural_alignment(32, 16), 16);
+        assert_eq!(natural_alignment(0, 16), 1);
+    }
+}
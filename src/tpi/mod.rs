@@ -5,26 +5,45 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use std::fmt;
-use std::marker::PhantomData;
-use std::result;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::result;
 
+use crate::cache::{read_index, write_index, IndexKind};
 use crate::common::*;
 use crate::msf::Stream;
+use crate::pdbi::PdbIdentity;
+use crate::search::{BudgetTracker, SearchBudget, SearchContinuation, SearchResults};
+use crate::sync::Once;
 use crate::FallibleIterator;
 
 pub(crate) mod constants;
 mod data;
+mod definitions;
+mod hash;
 mod header;
+mod hierarchy;
 mod id;
+mod layout;
+mod names;
 mod primitive;
+mod stats;
 
 use self::header::*;
 use self::primitive::type_data_for_primitive;
 
+pub(crate) use self::definitions::{DefinitionCache, DefinitionIndex};
+
 pub use self::data::*;
+pub use self::hash::ItemHashes;
+pub use self::header::TypeStreamVersion;
+pub use self::hierarchy::*;
 pub use self::id::*;
+pub use self::layout::*;
+pub use self::names::TypeNameMatches;
 pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
+pub use self::stats::*;
 
 /// Zero-copy access to a PDB type or id stream.
 ///
@@ -63,7 +82,7 @@ pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
 /// #
 /// # fn test() -> pdb::Result<usize> {
 /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// # let mut pdb = pdb::PDB::open(file)?;
+/// # let pdb = pdb::PDB::open(file)?;
 ///
 /// let type_information = pdb.type_information()?;
 /// let mut type_finder = type_information.finder();
@@ -127,7 +146,15 @@ pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
 #[derive(Debug)]
 pub struct ItemInformation<'s, I> {
     stream: Stream<'s>,
+    /// Whether this is the type or id stream, recorded in serialized finders.
+    kind: IndexKind,
     header: Header,
+    strings: StringCache,
+    definitions: DefinitionCache,
+    udt_sources: UdtSourceCache,
+    hash_stream: Option<Stream<'s>>,
+    /// The parsed hash stream, or `None` if it is missing or cannot be parsed.
+    hashes: Once<Option<ItemHashes<I>>>,
     _ph: PhantomData<&'s I>,
 }
 
@@ -136,17 +163,160 @@ where
     I: ItemIndex,
 {
     /// Parses `TypeInformation` from raw stream data.
-    pub(crate) fn parse(stream: Stream<'s>) -> Result<Self> {
+    ///
+    /// `kind` is the kind of [`ItemFinder`] serialized for this stream, which tells the type and id
+    /// streams apart.
+    pub(crate) fn parse(stream: Stream<'s>, kind: IndexKind) -> Result<Self> {
         let mut buf = stream.parse_buffer();
         let header = Header::parse(&mut buf)?;
         let _ph = PhantomData;
         Ok(Self {
             stream,
+            kind,
             header,
+            strings: StringCache::new(),
+            definitions: DefinitionCache::new(),
+            udt_sources: UdtSourceCache::new(),
+            hash_stream: None,
+            hashes: Once::new(),
             _ph,
         })
     }
 
+    /// Returns the index of the hash stream accompanying this stream, if any.
+    pub(crate) fn hash_stream(&self) -> Option<StreamIndex> {
+        match self.header.header_size {
+            // An empty header indicates a missing stream.
+            0 => None,
+            _ => Some(StreamIndex(self.header.tpi_hash_stream)),
+        }
+    }
+
+    /// Attaches the hash stream referenced by [`hash_stream`](Self::hash_stream), which is parsed
+    /// on first use.
+    pub(crate) fn with_hash_stream(mut self, stream: Option<Stream<'s>>) -> Self {
+        self.hash_stream = stream;
+        self
+    }
+
+    /// Returns the hash stream of this stream, or `None` if the PDB does not contain one.
+    ///
+    /// The hash stream allows finding items by name and by index without iterating the stream
+    /// first. See [`finder_from_hashes`](Self::finder_from_hashes).
+    ///
+    /// The hash stream is optional and only accelerates lookups, so it is parsed on first use and
+    /// treated as missing if it is malformed or uses an unsupported format.
+    pub fn hashes(&self) -> Option<&ItemHashes<I>> {
+        self.hashes
+            .get_or_init(|| {
+                let stream = self.hash_stream.as_ref()?;
+                let header_size = self.header.header_size as usize;
+                let records_size = self.stream.len().saturating_sub(header_size);
+                ItemHashes::parse(stream, &self.header, records_size).ok()
+            })
+            .as_ref()
+    }
+
+    /// Returns an `ItemFinder` that can find every item without being populated first.
+    ///
+    /// Instead of iterating the stream, this finder starts at the nearest item whose offset is
+    /// recorded in the hash stream, and skips the records in between. Compilers record an offset
+    /// every few kilobytes, so lookups are slower than with a populated [`finder`](Self::finder).
+    /// The finder can still be populated with [`ItemFinder::update`] to speed up lookups.
+    ///
+    /// Returns `None` if the PDB does not contain a hash stream for this stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// if let Some(finder) = type_information.finder_from_hashes() {
+    ///     let typ = finder.find(pdb::TypeIndex(0x1212))?;
+    ///     println!("{:?}", typ.parse()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn finder_from_hashes(&self) -> Option<ItemFinder<'_, I>> {
+        let hashes = self.hashes()?;
+        let mut finder = self.finder();
+        if self.is_empty() {
+            return Some(finder);
+        }
+
+        // The first item always starts right after the header, even if no offset is recorded.
+        let header_size = self.header.header_size;
+        if hashes.offsets().first().map(|&(index, _)| index) != Some(self.header.minimum_index) {
+            finder
+                .checkpoints
+                .push((self.header.minimum_index, header_size));
+        }
+
+        finder.checkpoints.extend(
+            hashes
+                .offsets()
+                .iter()
+                .map(|&(index, offset)| (index, header_size + offset)),
+        );
+
+        Some(finder)
+    }
+
+    /// Loads an `ItemFinder` that was serialized with [`ItemFinder::to_bytes`].
+    ///
+    /// This avoids iterating the stream to populate a finder. The serialized finder must have been
+    /// built from the same stream of a PDB with the given identity.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidIndex` if the data is not a serialized finder for this stream.
+    /// * `Error::IndexMismatch` if the finder was built from a different PDB.
+    pub fn finder_from_bytes(
+        &self,
+        data: &[u8],
+        identity: PdbIdentity,
+    ) -> Result<ItemFinder<'_, I>> {
+        let mut buf = read_index(data, self.kind, identity)?;
+
+        let shift = buf.parse_u8()?;
+        let stream_size = buf.parse_u32()?;
+        let minimum_index = buf.parse_u32()?;
+        let maximum_index = buf.parse_u32()?;
+        if shift > 31
+            || stream_size as usize != self.stream.len()
+            || minimum_index != self.header.minimum_index
+            || maximum_index != self.header.maximum_index
+        {
+            return Err(Error::InvalidIndex);
+        }
+
+        let mut finder = ItemFinder::new(self, shift);
+
+        let count = buf.parse_u32()?;
+        if count > ItemFinder::slots(self, shift) || buf.len() != count as usize * 4 {
+            return Err(Error::InvalidIndex);
+        }
+
+        finder.positions.clear();
+        for _ in 0..count {
+            let position = buf.parse_u32()?;
+            let ascending = finder
+                .positions
+                .last()
+                .map_or(true, |&last| last < position);
+            if !ascending || position >= stream_size {
+                return Err(Error::InvalidIndex);
+            }
+            finder.positions.push(position);
+        }
+
+        Ok(finder)
+    }
+
     /// Returns an iterator that can traverse the type table in sequential order.
     pub fn iter(&self) -> ItemIter<'_, I> {
         // get a parse buffer
@@ -173,11 +343,23 @@ where
         (self.header.maximum_index - self.header.minimum_index) as usize
     }
 
+    /// Returns the size of the stream in bytes.
+    pub(crate) fn stream_len(&self) -> usize {
+        self.stream.len()
+    }
+
     /// Returns whether this `ItemInformation` contains any data.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns the version of the stream format.
+    ///
+    /// If the stream is missing from the PDB, this is `TypeStreamVersion::OtherValue(0)`.
+    pub fn version(&self) -> TypeStreamVersion {
+        self.header.version.into()
+    }
+
     /// Returns an `ItemFinder` with a default time-space tradeoff useful for access by
     /// [`ItemIndex`].
     ///
@@ -186,6 +368,204 @@ where
     pub fn finder(&self) -> ItemFinder<'_, I> {
         ItemFinder::new(self, 3)
     }
+
+    /// Searches this stream for items matching `predicate`, within the limits of `budget`.
+    ///
+    /// The search starts at the first item, or at `start` to resume a previous search. If the
+    /// budget is exhausted before reaching the end of the stream, the results contain a
+    /// continuation to resume from.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IndexMismatch` if `start` was returned by a search of a different stream
+    /// * Errors reading items, for instance if the stream is corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let budget = pdb::SearchBudget::default().with_max_bytes(64 * 1024);
+    ///
+    /// let results = type_information.search(None, &budget, |item| {
+    ///     matches!(item.parse(), Ok(pdb::TypeData::Class(ref class)) if class.name == "Baz".into())
+    /// })?;
+    ///
+    /// if let Some(continuation) = results.continuation {
+    ///     // search the next chunk later, for instance after rendering the partial results
+    ///     let more = type_information.search(Some(continuation), &budget, |_| true)?;
+    ///     # assert!(!more.items.is_empty());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn search<F>(
+        &self,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        predicate: F,
+    ) -> Result<SearchResults<Item<'_, I>>>
+    where
+        F: FnMut(&Item<'_, I>) -> bool,
+    {
+        self.search_items(None, start, budget, predicate)
+    }
+
+    /// Searches this stream for items matching `predicate`, within the limits of `budget`, unless
+    /// cancelled.
+    ///
+    /// This behaves like [`search`](Self::search), but checks `token` before each item.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Cancelled` if `token` was cancelled before the search completed
+    /// * The errors of [`search`](Self::search)
+    pub fn search_cancellable<F>(
+        &self,
+        token: &CancellationToken,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        predicate: F,
+    ) -> Result<SearchResults<Item<'_, I>>>
+    where
+        F: FnMut(&Item<'_, I>) -> bool,
+    {
+        self.search_items(Some(token), start, budget, predicate)
+    }
+
+    fn search_items<F>(
+        &self,
+        token: Option<&CancellationToken>,
+        start: Option<SearchContinuation>,
+        budget: &SearchBudget,
+        mut predicate: F,
+    ) -> Result<SearchResults<Item<'_, I>>>
+    where
+        F: FnMut(&Item<'_, I>) -> bool,
+    {
+        if !budget.permits_results() {
+            return Ok(SearchResults {
+                items: Vec::new(),
+                continuation: None,
+            });
+        }
+
+        let mut iter = self.iter();
+        if let Some(start) = start {
+            start.validate(&self.stream)?;
+            iter.buf.seek(start.offset);
+            iter.index = start.index;
+        }
+
+        let mut tracker = BudgetTracker::new(*budget, iter.buf.pos());
+        let mut items = Vec::new();
+
+        loop {
+            let offset = iter.buf.pos();
+            if tracker.exhausted(offset, items.len()) {
+                let continuation = SearchContinuation::new(&self.stream, offset, iter.index);
+
+                return Ok(SearchResults {
+                    items,
+                    continuation: Some(continuation).filter(|_| !iter.buf.is_empty()),
+                });
+            }
+
+            if let Some(token) = token {
+                token.check()?;
+            }
+
+            match iter.next()? {
+                Some(item) if predicate(&item) => items.push(item),
+                Some(_) => (),
+                None => break,
+            }
+        }
+
+        Ok(SearchResults {
+            items,
+            continuation: None,
+        })
+    }
+
+    /// Returns an `ItemFinder` that has been populated with all items in this stream.
+    ///
+    /// This iterates the entire stream once, invoking `progress` periodically with the number of
+    /// items and bytes processed, as well as once on completion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let finder = type_information.finder_with_progress(|progress| {
+    ///     println!("indexed {:.0}% of types", progress.fraction() * 100.0);
+    /// })?;
+    /// # assert!(finder.find(pdb::TypeIndex(0x1000)).is_ok());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn finder_with_progress<F>(&self, progress: F) -> Result<ItemFinder<'_, I>>
+    where
+        F: FnMut(Progress),
+    {
+        self.build_finder(None, progress)
+    }
+
+    /// Returns an `ItemFinder` that has been populated with all items in this stream, unless
+    /// cancelled.
+    ///
+    /// This behaves like [`finder_with_progress`](Self::finder_with_progress), but checks `token`
+    /// before each item.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Cancelled` if `token` was cancelled before indexing completed
+    pub fn finder_cancellable<F>(
+        &self,
+        token: &CancellationToken,
+        progress: F,
+    ) -> Result<ItemFinder<'_, I>>
+    where
+        F: FnMut(Progress),
+    {
+        self.build_finder(Some(token), progress)
+    }
+
+    fn build_finder<F>(
+        &self,
+        token: Option<&CancellationToken>,
+        progress: F,
+    ) -> Result<ItemFinder<'_, I>>
+    where
+        F: FnMut(Progress),
+    {
+        let mut finder = self.finder();
+        let mut iter = self.iter();
+        let mut reporter = ProgressReporter::new(progress, self.stream.len());
+
+        loop {
+            if let Some(token) = token {
+                token.check()?;
+            }
+
+            if iter.next()?.is_none() {
+                break;
+            }
+
+            finder.update(&iter);
+            reporter.record(iter.buf.pos());
+        }
+
+        reporter.finish();
+        Ok(finder)
+    }
 }
 
 /// This buffer is used when a `Type` refers to a primitive type. It doesn't contain anything
@@ -236,6 +616,13 @@ where
         self.data.is_empty()
     }
 
+    /// Returns the raw bytes of this item, including the leaf kind but not the preceding length.
+    ///
+    /// Primitive types are not stored in the PDB and return a placeholder of two bytes.
+    pub fn raw_bytes(&self) -> &'t [u8] {
+        self.data
+    }
+
     /// Returns the identifier of the kind of data stored by this this `Item`.
     ///
     /// As a special case, if this is a primitive [`Type`], this function will return `0xffff`.
@@ -312,10 +699,14 @@ where
 #[derive(Debug)]
 pub struct ItemFinder<'t, I> {
     buffer: ParseBuffer<'t>,
+    /// Whether this finder indexes the type or id stream.
+    kind: IndexKind,
     minimum_index: u32,
     maximum_index: u32,
     positions: Vec<u32>,
     shift: u8,
+    /// Pairs of item index and position from the hash stream, in ascending order.
+    checkpoints: Vec<(u32, u32)>,
     _ph: PhantomData<&'t I>,
 }
 
@@ -324,11 +715,7 @@ where
     I: ItemIndex,
 {
     fn new(info: &'t ItemInformation<'_, I>, shift: u8) -> Self {
-        // maximum index is the highest index + 1.
-        let count = info.header.maximum_index - info.header.minimum_index;
-
-        let round_base = (1 << shift) - 1;
-        let shifted_count = ((count + round_base) & !round_base) >> shift;
+        let shifted_count = Self::slots(info, shift);
         let mut positions = Vec::with_capacity(shifted_count as usize);
 
         if shifted_count > 0 {
@@ -338,14 +725,25 @@ where
 
         Self {
             buffer: info.stream.parse_buffer(),
+            kind: info.kind,
             minimum_index: info.header.minimum_index,
             maximum_index: info.header.maximum_index,
             positions,
             shift,
+            checkpoints: Vec::new(),
             _ph: PhantomData,
         }
     }
 
+    /// Returns the number of positions needed to index all items of the stream.
+    fn slots(info: &ItemInformation<'_, I>, shift: u8) -> u32 {
+        // maximum index is the highest index + 1.
+        let count = info.header.maximum_index - info.header.minimum_index;
+
+        let round_base = (1 << shift) - 1;
+        ((count + round_base) & !round_base) >> shift
+    }
+
     /// Given an index, find which position in the Vec we should jump to and how many times we
     /// need to iterate to find the requested type.
     ///
@@ -366,12 +764,35 @@ where
     /// can be useful to check whether iteration is required.
     #[inline]
     pub fn max_index(&self) -> I {
+        if !self.checkpoints.is_empty() {
+            // finders created from the hash stream serve all items
+            return I::from(self.maximum_index - 1);
+        }
+
         I::from(match self.positions.len() {
             0 => 0, // special case for an empty type index
             len => (len << self.shift) as u32 + self.minimum_index - 1,
         })
     }
 
+    /// Serializes this `ItemFinder` for the PDB with the given identity.
+    ///
+    /// The finder can be loaded again with [`ItemInformation::finder_from_bytes`], even from a
+    /// different process. Serializing the same finder always produces the same bytes.
+    pub fn to_bytes(&self, identity: PdbIdentity) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(17 + self.positions.len() * 4);
+        payload.push(self.shift);
+        payload.extend_from_slice(&(self.buffer.len() as u32).to_le_bytes());
+        payload.extend_from_slice(&self.minimum_index.to_le_bytes());
+        payload.extend_from_slice(&self.maximum_index.to_le_bytes());
+        payload.extend_from_slice(&(self.positions.len() as u32).to_le_bytes());
+        for position in &self.positions {
+            payload.extend_from_slice(&position.to_le_bytes());
+        }
+
+        write_index(self.kind, identity, &payload)
+    }
+
     /// Update this `ItemFinder` based on the current position of a [`ItemIter`].
     ///
     /// Do this each time you call `.next()`. See documentation of [`ItemInformation`] for an
@@ -381,7 +802,7 @@ where
         let (vec_index, iteration_count) = self.resolve(iterator.index);
         if iteration_count == 0 && vec_index == self.positions.len() {
             let pos = iterator.buf.pos();
-            assert!(pos < u32::max_value() as usize);
+            assert!(pos < u32::MAX as usize);
             self.positions.push(pos as u32);
         }
     }
@@ -407,30 +828,42 @@ where
         // figure out where we'd find this
         let (vec_index, iteration_count) = self.resolve(index);
 
-        if let Some(pos) = self.positions.get(vec_index) {
+        let (pos, iteration_count) = match self.positions.get(vec_index) {
             // hit
-            let mut buf = self.buffer.clone();
+            Some(&pos) => (pos, iteration_count),
+            // miss, unless the hash stream recorded a nearby position
+            None => match self.checkpoint(index) {
+                Some((start, pos)) => (pos, (index - start) as usize),
+                None => return Err(Error::TypeNotIndexed(index, self.max_index().into())),
+            },
+        };
 
-            // jump forwards
-            buf.take(*pos as usize)?;
+        let mut buf = self.buffer.clone();
 
-            // skip some records
-            for _ in 0..iteration_count {
-                let length = buf.parse_u16()?;
-                buf.take(length as usize)?;
-            }
+        // jump forwards
+        buf.take(pos as usize)?;
 
-            // read the type
+        // skip some records
+        for _ in 0..iteration_count {
             let length = buf.parse_u16()?;
-
-            Ok(Item {
-                index: I::from(index),
-                data: buf.take(length as usize)?,
-            })
-        } else {
-            // miss
-            Err(Error::TypeNotIndexed(index, self.max_index().into()))
+            buf.take(length as usize)?;
         }
+
+        // read the type
+        let length = buf.parse_u16()?;
+
+        Ok(Item {
+            index: I::from(index),
+            data: buf.take(length as usize)?,
+        })
+    }
+
+    /// Returns the last checkpoint at or before the given index.
+    fn checkpoint(&self, index: u32) -> Option<(u32, u32)> {
+        let next = self
+            .checkpoints
+            .partition_point(|&(start, _)| start <= index);
+        next.checked_sub(1).map(|i| self.checkpoints[i])
     }
 }
 
@@ -447,6 +880,13 @@ pub struct ItemIter<'t, I> {
     _ph: PhantomData<&'t I>,
 }
 
+impl<I> ItemIter<'_, I> {
+    /// Returns the offset of the next item in the stream.
+    pub(crate) fn position(&self) -> usize {
+        self.buf.pos()
+    }
+}
+
 impl<'t, I> FallibleIterator for ItemIter<'t, I>
 where
     I: ItemIndex,
@@ -518,6 +958,58 @@ impl<'t> Item<'t, TypeIndex> {
             parse_type_data(&mut buf)
         }
     }
+
+    /// Parse this `Type` into `TypeData`, reusing the buffers held by `scratch`.
+    ///
+    /// This is equivalent to [`parse`](Self::parse), but avoids allocating for list types when
+    /// parsed types are returned to the scratch via [`TypeScratch::recycle`].
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedTypeKind(kind)` if the type record isn't currently understood by this
+    ///   library
+    /// * `Error::UnexpectedEof` if the type record is malformed
+    pub fn parse_with(&self, scratch: &mut TypeScratch<'t>) -> Result<TypeData<'t>> {
+        if self.index < TypeIndex(0x1000) {
+            // Primitive type
+            type_data_for_primitive(self.index)
+        } else {
+            let mut buf = ParseBuffer::from(self.data);
+            parse_type_data_with(&mut buf, scratch)
+        }
+    }
+}
+
+impl<'t> ItemIter<'t, TypeIndex> {
+    /// Returns an iterator that parses the remaining types, reusing the buffers of `scratch`.
+    ///
+    /// Unlike calling [`Type::parse_with`](Item::parse_with) in a loop, parsed types do not have
+    /// to be recycled manually. See [`ParsedTypes`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let mut scratch = pdb::TypeScratch::new();
+    ///
+    /// let mut fields = 0;
+    /// let mut types = type_information.iter().with_scratch(&mut scratch);
+    /// while let Some((_, data)) = types.next()? {
+    ///     if let Ok(pdb::TypeData::FieldList(list)) = data {
+    ///         fields += list.fields.len();
+    ///     }
+    /// }
+    /// # assert!(fields > 0);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn with_scratch<'a>(self, scratch: &'a mut TypeScratch<'t>) -> ParsedTypes<'t, 'a> {
+        ParsedTypes::new(self, scratch)
+    }
 }
 
 /// Zero-copy access to the PDB type stream (TPI).
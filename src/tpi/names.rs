@@ -0,0 +1,209 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::tpi::definitions::parse_user_defined;
+use crate::tpi::{Type, TypeInformation, TypeIter};
+use crate::FallibleIterator;
+
+/// Returns whether `name` matches a wildcard pattern.
+///
+/// `*` matches any sequence of characters, including none, and `?` matches exactly one character.
+/// All other characters match themselves.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    // Returns the length of the UTF-8 sequence starting at `name[index]`, treating invalid bytes as
+    // single characters.
+    let char_len = |index: usize| {
+        let continuation = name[index + 1..]
+            .iter()
+            .take(3)
+            .take_while(|&&byte| byte & 0xc0 == 0x80)
+            .count();
+        match name[index] {
+            0xc0..=0xdf => 1 + continuation.min(1),
+            0xe0..=0xef => 1 + continuation.min(2),
+            0xf0..=0xf7 => 1 + continuation.min(3),
+            _ => 1,
+        }
+    };
+
+    let (mut p, mut n) = (0, 0);
+    // The position after the last `*` in the pattern, and the position in the name it resumes at.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                backtrack = Some((p, n));
+                continue;
+            }
+            Some(b'?') => {
+                p += 1;
+                n += char_len(n);
+                continue;
+            }
+            Some(&byte) if byte == name[n] => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => (),
+        }
+
+        // Let the last `*` consume one more character and retry.
+        match backtrack {
+            Some((star, start)) => {
+                let start = start + char_len(start);
+                backtrack = Some((star, start));
+                p = star;
+                n = start;
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+/// An iterator over the classes, unions and enums whose name matches a wildcard pattern.
+///
+/// Created by [`TypeInformation::search_names`].
+#[derive(Debug)]
+pub struct TypeNameMatches<'t> {
+    iter: TypeIter<'t>,
+    pattern: Vec<u8>,
+    token: Option<CancellationToken>,
+}
+
+impl<'t> FallibleIterator for TypeNameMatches<'t> {
+    type Item = Type<'t>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some(ref token) = self.token {
+                token.check()?;
+            }
+
+            let item = match self.iter.next()? {
+                Some(item) => item,
+                None => return Ok(None),
+            };
+
+            if let Some((properties, name, _)) = parse_user_defined(&item)? {
+                if !properties.forward_reference() && wildcard_match(&self.pattern, name.as_bytes())
+                {
+                    return Ok(Some(item));
+                }
+            }
+        }
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Returns an iterator over the classes, structs, unions and enums whose name matches
+    /// `pattern`.
+    ///
+    /// In the pattern, `*` matches any sequence of characters, including none, and `?` matches a
+    /// single character. All other characters must match exactly, so the pattern `std::*` matches
+    /// all types in namespace `std`, including nested namespaces. Names are compared including
+    /// namespaces and template arguments.
+    ///
+    /// Matching types are returned in stream order as the stream is iterated, so the first matches
+    /// are available without scanning the entire stream. Forward references are skipped; use
+    /// [`resolve_definition`](Self::resolve_definition) to map references to a matching type.
+    ///
+    /// # Errors
+    ///
+    /// The iterator returns `Error::UnexpectedEof` or other parsing errors if the type stream is
+    /// corrupt.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut matches = type_information.search_names("std::*exception");
+    /// while let Some(typ) = matches.next()? {
+    ///     if let Some(name) = typ.parse()?.name() {
+    ///         println!("{} {}", typ.index(), name);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn search_names(&self, pattern: &str) -> TypeNameMatches<'_> {
+        TypeNameMatches {
+            iter: self.iter(),
+            pattern: pattern.as_bytes().to_vec(),
+            token: None,
+        }
+    }
+
+    /// Returns an iterator over the classes, structs, unions and enums whose name matches
+    /// `pattern`, unless cancelled.
+    ///
+    /// This behaves like [`search_names`](Self::search_names), but the iterator checks `token`
+    /// before each type it scans, including types that do not match.
+    ///
+    /// # Errors
+    ///
+    /// The iterator returns `Error::Cancelled` once `token` has been cancelled, in addition to the
+    /// errors of [`search_names`](Self::search_names).
+    pub fn search_names_cancellable(
+        &self,
+        pattern: &str,
+        token: &CancellationToken,
+    ) -> TypeNameMatches<'_> {
+        TypeNameMatches {
+            iter: self.iter(),
+            pattern: pattern.as_bytes().to_vec(),
+            token: Some(token.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        let matches =
+            |pattern: &str, name: &str| wildcard_match(pattern.as_bytes(), name.as_bytes());
+
+        assert!(matches("Foo", "Foo"));
+        assert!(!matches("Foo", "Foobar"));
+        assert!(!matches("Foo", "foo"));
+        assert!(matches("*", ""));
+        assert!(matches("*", "std::vector<int>"));
+        assert!(matches("std::*", "std::vector<int>"));
+        assert!(!matches("std::*", "stdext::hash"));
+        assert!(matches("*<int>", "std::vector<int>"));
+        assert!(matches("*::*::*", "a::b::c::d"));
+        assert!(matches(
+            "std::vector<*>",
+            "std::vector<std::pair<int,int> >"
+        ));
+        assert!(matches("Foo?", "Foo1"));
+        assert!(!matches("Foo?", "Foo"));
+        assert!(matches("?*?", "ab"));
+        assert!(!matches("?*?", "a"));
+        assert!(matches("caf?", "café"));
+        assert!(matches("*a*b*", "xxaxxbxx"));
+        assert!(!matches("*a*b", "xxaxxbxx"));
+    }
+}
@@ -188,6 +188,70 @@ pub enum Indirection {
     Near128,
 }
 
+impl PrimitiveType {
+    /// Returns the size of a value of this type in bytes.
+    ///
+    /// This is the size of the pointer for indirections, and zero for `void`.
+    pub fn size(self) -> u64 {
+        match self.indirection {
+            Some(indirection) => indirection.size(),
+            None => self.kind.size(),
+        }
+    }
+}
+
+impl PrimitiveKind {
+    /// Returns the size of a value of this kind in bytes.
+    fn size(self) -> u64 {
+        match self {
+            Self::NoType | Self::Void => 0,
+            Self::Char | Self::UChar | Self::RChar | Self::I8 | Self::U8 | Self::Bool8 => 1,
+            Self::WChar
+            | Self::RChar16
+            | Self::Short
+            | Self::UShort
+            | Self::I16
+            | Self::U16
+            | Self::F16
+            | Self::Bool16 => 2,
+            Self::RChar32
+            | Self::Long
+            | Self::ULong
+            | Self::I32
+            | Self::U32
+            | Self::F32
+            | Self::F32PP
+            | Self::Bool32
+            | Self::HRESULT => 4,
+            Self::F48 => 6,
+            Self::Quad
+            | Self::UQuad
+            | Self::I64
+            | Self::U64
+            | Self::F64
+            | Self::Bool64
+            | Self::Complex32 => 8,
+            Self::F80 => 10,
+            Self::Octa | Self::UOcta | Self::I128 | Self::U128 | Self::F128 | Self::Complex64 => 16,
+            Self::Complex80 => 20,
+            Self::Complex128 => 32,
+        }
+    }
+}
+
+impl Indirection {
+    /// Returns the size of the pointer in bytes.
+    fn size(self) -> u64 {
+        match self {
+            Self::Near16 => 2,
+            Self::Far16 | Self::Huge16 | Self::Near32 => 4,
+            Self::Far32 => 6,
+            Self::Near64 => 8,
+            Self::Near128 => 16,
+        }
+    }
+}
+
 pub fn type_data_for_primitive(index: TypeIndex) -> Result<TypeData<'static>> {
     // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L326-L750
 
@@ -0,0 +1,221 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::tpi::constants::*;
+use crate::tpi::{Type, TypeInformation};
+use crate::FallibleIterator;
+
+/// The number of records and bytes of a single leaf kind in [`TypeStatistics`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KindStatistics {
+    /// The number of records of this kind.
+    pub count: usize,
+    /// The total size of these records in bytes, including their length prefixes.
+    pub bytes: usize,
+}
+
+/// A field list record in [`TypeStatistics::largest_field_lists`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FieldListSize {
+    /// The index of the field list.
+    pub index: TypeIndex,
+    /// The size of the record in bytes, including its length prefix.
+    ///
+    /// Field lists larger than a single record are split into several records linked by
+    /// continuation indexes. Each of these records is counted separately.
+    pub bytes: usize,
+}
+
+/// A user defined type in [`TypeStatistics::deepest_templates`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TemplateNesting<'t> {
+    /// The index of the class, struct, union or enum.
+    pub index: TypeIndex,
+    /// The maximum nesting depth of template arguments in the type's name.
+    pub depth: usize,
+    /// The name of the type.
+    pub name: RawString<'t>,
+}
+
+/// The composition of a type stream, returned by [`TypeInformation::statistics`].
+///
+/// This helps to find out which kinds of types contribute most to the size of a PDB.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TypeStatistics<'t> {
+    /// Record counts and sizes by leaf kind, such as `0x1203` for `LF_FIELDLIST`.
+    pub kinds: BTreeMap<u16, KindStatistics>,
+    /// The largest field list records, in descending order of size.
+    pub largest_field_lists: Vec<FieldListSize>,
+    /// The user defined types with the most deeply nested template arguments, in descending
+    /// order of depth.
+    pub deepest_templates: Vec<TemplateNesting<'t>>,
+}
+
+impl TypeStatistics<'_> {
+    /// Returns the total number of records in the stream.
+    pub fn count(&self) -> usize {
+        self.kinds.values().map(|kind| kind.count).sum()
+    }
+
+    /// Returns the total size of all records in bytes, including their length prefixes.
+    pub fn bytes(&self) -> usize {
+        self.kinds.values().map(|kind| kind.bytes).sum()
+    }
+
+    fn record(&mut self, item: &Type<'_>) {
+        let kind = self.kinds.entry(item.raw_kind()).or_default();
+        kind.count += 1;
+        kind.bytes += item.len() + 2;
+    }
+}
+
+/// Inserts `value` into `list`, which is sorted in descending order of `key`, keeping at most
+/// `limit` elements.
+///
+/// Elements with equal keys retain their insertion order.
+fn insert_top<T, K, F>(list: &mut Vec<T>, limit: usize, value: T, key: F)
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let value_key = key(&value);
+    let position = list.partition_point(|element| key(element) >= value_key);
+    if position < limit {
+        list.insert(position, value);
+        list.truncate(limit);
+    }
+}
+
+/// Returns the maximum nesting depth of angle brackets in a type name.
+fn template_depth(name: RawString<'_>) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+
+    for &byte in name.as_bytes() {
+        match byte {
+            b'<' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b'>' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    max_depth
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Computes statistics about the composition of this type stream.
+    ///
+    /// This iterates the entire stream once. The lists of largest field lists and deepest template
+    /// types are limited to `limit` entries each. The template depth is derived from the names of
+    /// classes, structs, unions and enums, so `Foo<Bar<int>>` has a depth of 2.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// # let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let statistics = type_information.statistics(10)?;
+    ///
+    /// for (kind, kind_statistics) in &statistics.kinds {
+    ///     println!(
+    ///         "{:#06x}: {} records, {} bytes",
+    ///         kind, kind_statistics.count, kind_statistics.bytes
+    ///     );
+    /// }
+    /// # assert_eq!(statistics.count(), type_information.len());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn statistics(&self, limit: usize) -> Result<TypeStatistics<'_>> {
+        let mut statistics = TypeStatistics::default();
+        let mut iter = self.iter();
+
+        while let Some(item) = iter.next()? {
+            statistics.record(&item);
+
+            match item.raw_kind() {
+                LF_FIELDLIST => {
+                    let field_list = FieldListSize {
+                        index: item.index(),
+                        bytes: item.len() + 2,
+                    };
+
+                    insert_top(
+                        &mut statistics.largest_field_lists,
+                        limit,
+                        field_list,
+                        |list| list.bytes,
+                    );
+                }
+                LF_CLASS | LF_CLASS_ST | LF_STRUCTURE | LF_STRUCTURE_ST | LF_STRUCTURE19
+                | LF_INTERFACE | LF_UNION | LF_UNION_ST | LF_ENUM | LF_ENUM_ST => {
+                    let name = match item.parse()?.name() {
+                        Some(name) => name,
+                        None => continue,
+                    };
+
+                    let depth = template_depth(name);
+                    if depth == 0 {
+                        continue;
+                    }
+
+                    let nesting = TemplateNesting {
+                        index: item.index(),
+                        depth,
+                        name,
+                    };
+
+                    insert_top(
+                        &mut statistics.deepest_templates,
+                        limit,
+                        nesting,
+                        |nesting| nesting.depth,
+                    );
+                }
+                _ => (),
+            }
+        }
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_depth() {
+        assert_eq!(template_depth("Foo".into()), 0);
+        assert_eq!(template_depth("Foo<int>".into()), 1);
+        assert_eq!(template_depth("Foo<Bar<int>,Baz<char> >".into()), 2);
+        assert_eq!(template_depth("std::pair<A<B<C> >,D>".into()), 3);
+    }
+
+    #[test]
+    fn test_insert_top() {
+        let mut list = Vec::new();
+        for value in [3, 1, 4, 1, 5, 9, 2, 6] {
+            insert_top(&mut list, 3, value, |&value| value);
+        }
+        assert_eq!(list, [9, 6, 5]);
+
+        let mut list = Vec::new();
+        insert_top(&mut list, 0, 1, |&value| value);
+        assert!(list.is_empty());
+    }
+}
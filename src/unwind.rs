@@ -0,0 +1,461 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Parsing of x64 unwind information from the `.pdata` and `.xdata` sections of an image.
+
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::msf::Stream;
+use crate::FallibleIterator;
+
+/// The unwind information contains a [`RuntimeFunction`] of the parent function.
+const UNW_FLAG_CHAININFO: u8 = 0x04;
+
+const UWOP_PUSH_NONVOL: u8 = 0;
+const UWOP_ALLOC_LARGE: u8 = 1;
+const UWOP_ALLOC_SMALL: u8 = 2;
+const UWOP_SET_FPREG: u8 = 3;
+const UWOP_SAVE_NONVOL: u8 = 4;
+const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+const UWOP_EPILOG: u8 = 6;
+const UWOP_SPARE_CODE: u8 = 7;
+const UWOP_SAVE_XMM128: u8 = 8;
+const UWOP_SAVE_XMM128_FAR: u8 = 9;
+const UWOP_PUSH_MACHFRAME: u8 = 10;
+
+/// An entry of the function table in the `.pdata` section of an x64 image.
+///
+/// Each entry covers a function, or a fragment of a function, and refers to its unwind
+/// information.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RuntimeFunction {
+    /// The address of the first instruction.
+    pub begin: Rva,
+    /// The address after the last instruction.
+    pub end: Rva,
+    /// The address of the [`UnwindInfo`] of the function.
+    pub unwind_info: Rva,
+}
+
+impl RuntimeFunction {
+    fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
+        Ok(Self {
+            begin: Rva(buf.parse_u32()?),
+            end: Rva(buf.parse_u32()?),
+            unwind_info: Rva(buf.parse_u32()?),
+        })
+    }
+}
+
+/// An operation performed by the prologue of an x64 function, as described by its unwind codes.
+///
+/// Registers are numbered in the order of their encoding in x64 instructions: `rax`, `rcx`, `rdx`,
+/// `rbx`, `rsp`, `rbp`, `rsi`, `rdi` and `r8` through `r15`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnwindOperation {
+    /// Pushes a nonvolatile integer register, decrementing `rsp` by 8.
+    PushNonVolatile(u8),
+    /// Allocates the given number of bytes on the stack.
+    Alloc(u32),
+    /// Establishes the frame register of the [`UnwindInfo`], at the given offset from `rsp`.
+    SetFramePointer,
+    /// Saves a nonvolatile integer register at the given offset from `rsp`, without a push.
+    SaveNonVolatile {
+        /// The saved register.
+        register: u8,
+        /// The offset from `rsp` in bytes.
+        offset: u32,
+    },
+    /// Saves all 128 bits of a nonvolatile XMM register at the given offset from `rsp`.
+    SaveXmm128 {
+        /// The number of the XMM register.
+        register: u8,
+        /// The offset from `rsp` in bytes.
+        offset: u32,
+    },
+    /// Pushes a machine frame, as done by hardware interrupts and exceptions.
+    PushMachineFrame {
+        /// Whether the frame includes an error code.
+        error_code: bool,
+    },
+    /// Describes the location of an epilogue, in version 2 of the unwind information.
+    Epilog,
+}
+
+/// An unwind code of an [`UnwindInfo`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnwindCode {
+    /// Offset from the beginning of the prologue to the end of the instruction performing the
+    /// operation.
+    pub code_offset: u8,
+    /// The operation performed by the instruction.
+    pub operation: UnwindOperation,
+}
+
+/// Unwind information of an x64 function, as stored in the `.xdata` section of the image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnwindInfo<'d> {
+    /// The address of this unwind information.
+    pub rva: Rva,
+    /// The version of the unwind information, either 1 or 2.
+    pub version: u8,
+    /// The `UNW_FLAG_*` flags of the unwind information.
+    pub flags: u8,
+    /// Size of the prologue in bytes.
+    pub prolog_size: u8,
+    /// The number of the frame register, or zero if the function does not use a frame pointer.
+    pub frame_register: u8,
+    /// The offset of the frame register from `rsp` when it is established, in bytes.
+    pub frame_offset: u32,
+    /// The parent function, if this function is a fragment of it.
+    ///
+    /// The prologue of the parent has been executed when entering a fragment, so its unwind
+    /// operations apply to the fragment as well.
+    pub chained: Option<RuntimeFunction>,
+    codes: &'d [u8],
+}
+
+impl<'d> UnwindInfo<'d> {
+    fn parse(rva: Rva, data: &'d [u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+
+        let version_flags = buf.parse_u8()?;
+        let prolog_size = buf.parse_u8()?;
+        let code_count = buf.parse_u8()?;
+        let frame = buf.parse_u8()?;
+
+        let version = version_flags & 0x7;
+        if version != 1 && version != 2 {
+            return Err(Error::InvalidUnwindInfo(rva.0));
+        }
+
+        let flags = version_flags >> 3;
+        let codes = buf.take(usize::from(code_count) * 2)?;
+
+        let chained = if flags & UNW_FLAG_CHAININFO != 0 {
+            // The code array is padded to an even number of entries.
+            if code_count % 2 != 0 {
+                buf.take(2)?;
+            }
+            Some(RuntimeFunction::parse(&mut buf)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rva,
+            version,
+            flags,
+            prolog_size,
+            frame_register: frame & 0xf,
+            frame_offset: u32::from(frame >> 4) * 16,
+            chained,
+            codes,
+        })
+    }
+
+    /// Returns an iterator over the unwind codes of this function.
+    ///
+    /// The codes are stored in reverse order of the prologue, so the first code describes the last
+    /// instruction of the prologue.
+    pub fn codes(&self) -> UnwindCodeIter<'d> {
+        UnwindCodeIter {
+            buf: ParseBuffer::from(self.codes),
+            version: self.version,
+            rva: self.rva,
+        }
+    }
+}
+
+/// An iterator over the unwind codes of an [`UnwindInfo`].
+#[derive(Clone, Debug)]
+pub struct UnwindCodeIter<'d> {
+    buf: ParseBuffer<'d>,
+    version: u8,
+    rva: Rva,
+}
+
+impl FallibleIterator for UnwindCodeIter<'_> {
+    type Item = UnwindCode;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let code_offset = self.buf.parse_u8()?;
+        let op_info = self.buf.parse_u8()?;
+        let info = op_info >> 4;
+
+        let operation = match op_info & 0xf {
+            UWOP_PUSH_NONVOL => UnwindOperation::PushNonVolatile(info),
+            UWOP_ALLOC_LARGE if info == 0 => {
+                UnwindOperation::Alloc(u32::from(self.buf.parse_u16()?) * 8)
+            }
+            UWOP_ALLOC_LARGE if info == 1 => UnwindOperation::Alloc(self.buf.parse_u32()?),
+            UWOP_ALLOC_SMALL => UnwindOperation::Alloc(u32::from(info) * 8 + 8),
+            UWOP_SET_FPREG => UnwindOperation::SetFramePointer,
+            UWOP_SAVE_NONVOL => UnwindOperation::SaveNonVolatile {
+                register: info,
+                offset: u32::from(self.buf.parse_u16()?) * 8,
+            },
+            UWOP_SAVE_NONVOL_FAR => UnwindOperation::SaveNonVolatile {
+                register: info,
+                offset: self.buf.parse_u32()?,
+            },
+            UWOP_EPILOG if self.version >= 2 => UnwindOperation::Epilog,
+            // Version 1 used these codes to save the lower 64 bits of XMM registers.
+            UWOP_EPILOG => UnwindOperation::SaveXmm128 {
+                register: info,
+                offset: u32::from(self.buf.parse_u16()?) * 8,
+            },
+            UWOP_SPARE_CODE if self.version < 2 => UnwindOperation::SaveXmm128 {
+                register: info,
+                offset: self.buf.parse_u32()?,
+            },
+            UWOP_SAVE_XMM128 => UnwindOperation::SaveXmm128 {
+                register: info,
+                offset: u32::from(self.buf.parse_u16()?) * 16,
+            },
+            UWOP_SAVE_XMM128_FAR => UnwindOperation::SaveXmm128 {
+                register: info,
+                offset: self.buf.parse_u32()?,
+            },
+            UWOP_PUSH_MACHFRAME => UnwindOperation::PushMachineFrame {
+                error_code: info != 0,
+            },
+            _ => return Err(Error::InvalidUnwindInfo(self.rva.0)),
+        };
+
+        Ok(Some(UnwindCode {
+            code_offset,
+            operation,
+        }))
+    }
+}
+
+/// The function table and unwind information of an x64 image.
+///
+/// The function table is the contents of the `.pdata` section. Unwind information is located by
+/// its RVA in blocks of image data registered with [`add_image_data`](Self::add_image_data),
+/// usually the `.rdata` or `.xdata` section. PDBs may contain copies of both, see
+/// [`PDB::unwind_data`](crate::PDB::unwind_data).
+#[derive(Clone, Debug)]
+pub struct UnwindTable<'d> {
+    functions: &'d [u8],
+    blocks: Vec<(Rva, &'d [u8])>,
+}
+
+impl<'d> UnwindTable<'d> {
+    /// Creates an unwind table from the contents of the `.pdata` section.
+    pub fn new(pdata: &'d [u8]) -> Self {
+        Self {
+            functions: pdata,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Registers image data at the given address, which contains unwind information.
+    pub fn add_image_data(&mut self, rva: Rva, data: &'d [u8]) {
+        self.blocks.push((rva, data));
+    }
+
+    /// Returns an iterator over the entries of the function table.
+    pub fn functions(&self) -> RuntimeFunctionIter<'d> {
+        RuntimeFunctionIter {
+            buf: ParseBuffer::from(self.functions),
+        }
+    }
+
+    /// Parses the unwind information at the given address.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidUnwindInfo` if no image data covers the address, or the unwind information
+    ///   has an unknown version or unwind code
+    /// * `Error::UnexpectedEof` if the unwind information is truncated
+    pub fn unwind_info(&self, rva: Rva) -> Result<UnwindInfo<'d>> {
+        for &(start, data) in &self.blocks {
+            if rva < start {
+                continue;
+            }
+
+            let offset = (rva.0 - start.0) as usize;
+            if offset < data.len() {
+                return UnwindInfo::parse(rva, &data[offset..]);
+            }
+        }
+
+        Err(Error::InvalidUnwindInfo(rva.0))
+    }
+}
+
+/// An iterator over the entries of an [`UnwindTable`].
+#[derive(Clone, Debug)]
+pub struct RuntimeFunctionIter<'d> {
+    buf: ParseBuffer<'d>,
+}
+
+impl FallibleIterator for RuntimeFunctionIter<'_> {
+    type Item = RuntimeFunction;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        RuntimeFunction::parse(&mut self.buf).map(Some)
+    }
+}
+
+/// Copies of the `.pdata` and `.xdata` sections of an x64 image stored in a PDB.
+///
+/// Obtained via [`PDB::unwind_data`](crate::PDB::unwind_data).
+#[derive(Debug)]
+pub struct UnwindData<'s> {
+    pdata: Stream<'s>,
+    pdata_range: (usize, Rva),
+    xdata: Stream<'s>,
+    xdata_range: (usize, Rva),
+}
+
+impl<'s> UnwindData<'s> {
+    pub(crate) fn parse(pdata: Stream<'s>, xdata: Stream<'s>) -> Result<Self> {
+        let pdata_range = Self::parse_header(&pdata)?;
+        let xdata_range = Self::parse_header(&xdata)?;
+
+        Ok(Self {
+            pdata,
+            pdata_range,
+            xdata,
+            xdata_range,
+        })
+    }
+
+    /// Parses the `DbgRvaVaBlob` header of a stream and returns the offset and address of its data.
+    fn parse_header(stream: &Stream<'_>) -> Result<(usize, Rva)> {
+        let mut buf = stream.parse_buffer();
+        let _version = buf.parse_u32()?;
+        let header_size = buf.parse_u32()? as usize;
+        let _data_size = buf.parse_u32()?;
+        let rva = buf.parse_u64()?;
+
+        if header_size > stream.len() || rva > u64::from(u32::MAX) {
+            return Err(Error::InvalidStreamLength("DbgRvaVaBlob"));
+        }
+
+        Ok((header_size, Rva(rva as u32)))
+    }
+
+    /// Returns the address of the `.pdata` section copied into the PDB.
+    pub fn pdata_rva(&self) -> Rva {
+        self.pdata_range.1
+    }
+
+    /// Returns an unwind table over the copied sections.
+    pub fn table(&self) -> UnwindTable<'_> {
+        let mut table = UnwindTable::new(&self.pdata[self.pdata_range.0..]);
+        table.add_image_data(self.xdata_range.1, &self.xdata[self.xdata_range.0..]);
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwind_codes() {
+        let data = &[
+            0x19, 0x0a, 0x06, 0x35, // version 1, UNW_FLAG_UHANDLER, 6 codes, rbp at rsp+48
+            0x0a, 0x53, // 0a: UWOP_SET_FPREG
+            0x06, 0x01, // 06: UWOP_ALLOC_LARGE
+            0x10, 0x00, // 0x10 * 8 bytes
+            0x02, 0x50, // 02: UWOP_PUSH_NONVOL rbp
+            0x01, 0x34, // 01: UWOP_SAVE_NONVOL rbx
+            0x03, 0x00, // at rsp+24
+        ];
+
+        let info = UnwindInfo::parse(Rva(0x2000), data).expect("parse");
+        assert_eq!(info.version, 1);
+        assert_eq!(info.flags, 0x3);
+        assert_eq!(info.prolog_size, 0x0a);
+        assert_eq!(info.frame_register, 5);
+        assert_eq!(info.frame_offset, 48);
+        assert_eq!(info.chained, None);
+
+        let codes: Vec<_> = info.codes().collect().expect("codes");
+        assert_eq!(
+            codes,
+            [
+                UnwindCode {
+                    code_offset: 0x0a,
+                    operation: UnwindOperation::SetFramePointer,
+                },
+                UnwindCode {
+                    code_offset: 0x06,
+                    operation: UnwindOperation::Alloc(0x80),
+                },
+                UnwindCode {
+                    code_offset: 0x02,
+                    operation: UnwindOperation::PushNonVolatile(5),
+                },
+                UnwindCode {
+                    code_offset: 0x01,
+                    operation: UnwindOperation::SaveNonVolatile {
+                        register: 3,
+                        offset: 24,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chained_unwind_info() {
+        let data = &[
+            0x21, 0x00, 0x01, 0x00, // version 1, UNW_FLAG_CHAININFO, 1 code
+            0x00, 0x32, // 00: UWOP_ALLOC_SMALL 32 bytes
+            0x00, 0x00, // padding
+            0x00, 0x10, 0x00, 0x00, // begin
+            0x40, 0x10, 0x00, 0x00, // end
+            0x00, 0x30, 0x00, 0x00, // unwind info
+        ];
+
+        let info = UnwindInfo::parse(Rva(0x2000), data).expect("parse");
+        assert_eq!(
+            info.chained,
+            Some(RuntimeFunction {
+                begin: Rva(0x1000),
+                end: Rva(0x1040),
+                unwind_info: Rva(0x3000),
+            })
+        );
+
+        let codes: Vec<_> = info.codes().collect().expect("codes");
+        assert_eq!(codes[0].operation, UnwindOperation::Alloc(32));
+    }
+
+    #[test]
+    fn test_invalid_unwind_info() {
+        let table = UnwindTable::new(&[]);
+        assert!(matches!(
+            table.unwind_info(Rva(0x1000)),
+            Err(Error::InvalidUnwindInfo(0x1000))
+        ));
+
+        let data = &[0x03, 0x00, 0x00, 0x00];
+        assert!(matches!(
+            UnwindInfo::parse(Rva(0x10), data),
+            Err(Error::InvalidUnwindInfo(0x10))
+        ));
+    }
+}
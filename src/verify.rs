@@ -0,0 +1,476 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Verification of a PDB against the image it was built for and the source files it was built
+//! from.
+
+use alloc::collections::btree_map::{BTreeMap, Entry};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::common::*;
+use crate::dbi::{Module, ModuleHealth};
+use crate::modi::FileChecksum;
+use crate::pdb::PDB;
+use crate::pdbi::PdbIdentity;
+use crate::source::Source;
+use crate::symbol::SymbolTable;
+use crate::tpi::{IdInformation, TypeInformation};
+use crate::FallibleIterator;
+
+/// The result of comparing the CodeView record of an image with a PDB.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageStatus {
+    /// The image was linked with this PDB.
+    Matches,
+    /// The image was linked with a different PDB, or with a different age of this PDB.
+    Mismatch(PdbIdentity),
+    /// The image does not contain a PDB 7.0 CodeView record.
+    MissingCodeView,
+}
+
+/// A part of the PDB checked by [`PDB::verify`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PdbComponent {
+    /// The global symbol table.
+    GlobalSymbols,
+    /// The type information stream.
+    TypeInformation,
+    /// The id information stream.
+    IdInformation,
+    /// The module at the given index in [`DebugInformation::modules`](crate::DebugInformation::modules).
+    Module(usize),
+}
+
+/// An error found while reading a part of the PDB.
+///
+/// Only the first error of each component is reported, since records following a corrupt record
+/// cannot be located reliably.
+#[derive(Debug)]
+pub struct ConsistencyError {
+    /// The part of the PDB containing the error.
+    pub component: PdbComponent,
+    /// The error returned while reading it.
+    pub error: Error,
+}
+
+/// The result of comparing a source file with its checksum in the PDB.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SourceStatus {
+    /// The checksum of the file matches the PDB.
+    Matches,
+    /// The file differs from the one the PDB was built from.
+    Mismatch,
+    /// The source resolver could not locate the file.
+    Missing,
+    /// The PDB does not record a checksum for the file, so it cannot be verified.
+    NoChecksum,
+}
+
+/// A source file referenced by the line information of a PDB.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceVerification {
+    /// The path of the file as recorded in the PDB.
+    pub path: String,
+    /// The result of comparing the file with its checksum.
+    pub status: SourceStatus,
+}
+
+/// The result of [`PDB::verify`].
+#[derive(Debug)]
+pub struct VerificationReport {
+    /// The identity that images linked with the PDB declare.
+    pub identity: PdbIdentity,
+    /// The result of comparing the image with the PDB, or `None` if no image was given.
+    pub image: Option<ImageStatus>,
+    /// Errors found while reading the symbols, types and line information of the PDB.
+    pub errors: Vec<ConsistencyError>,
+    /// Modules for which one of the checks of [`ModuleHealth`] found a problem.
+    ///
+    /// This is empty if reading the modules resulted in an error, which is listed in `errors`.
+    pub unhealthy_modules: Vec<ModuleHealth>,
+    /// The source files of all modules, sorted by path.
+    pub sources: Vec<SourceVerification>,
+}
+
+impl VerificationReport {
+    /// Returns whether the image matches, the PDB could be read without errors, and no source file
+    /// differs from its checksum.
+    ///
+    /// Unhealthy modules, missing source files and files without checksums are not considered
+    /// failures, since they are common in release builds and third party code.
+    pub fn is_valid(&self) -> bool {
+        matches!(self.image, None | Some(ImageStatus::Matches))
+            && self.errors.is_empty()
+            && self
+                .sources
+                .iter()
+                .all(|source| source.status != SourceStatus::Mismatch)
+    }
+
+    /// Returns an iterator over the source files with the given status.
+    pub fn sources_with_status(
+        &self,
+        status: SourceStatus,
+    ) -> impl Iterator<Item = &SourceVerification> + '_ {
+        self.sources
+            .iter()
+            .filter(move |source| source.status == status)
+    }
+}
+
+/// Records the error of a component, if any.
+///
+/// Cancellation is not an inconsistency of the PDB, and is returned instead.
+fn check(
+    errors: &mut Vec<ConsistencyError>,
+    component: PdbComponent,
+    result: Result<()>,
+) -> Result<()> {
+    match result {
+        Err(Error::Cancelled) => return Err(Error::Cancelled),
+        Err(error) => errors.push(ConsistencyError { component, error }),
+        Ok(()) => (),
+    }
+
+    Ok(())
+}
+
+/// Fails with the error of a parsed record, unless the record is merely of a kind this crate does
+/// not support.
+fn supported<T>(result: Result<T>) -> Result<()> {
+    match result {
+        Err(error) if error.kind() == ErrorKind::Unsupported => Ok(()),
+        Err(error) => Err(error),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Compares a source file with its checksum using the resolver passed to [`PDB::verify`].
+fn verify_source<F>(path: &str, checksum: &FileChecksum<'_>, resolve_source: &mut F) -> SourceStatus
+where
+    F: FnMut(&str, &FileChecksum<'_>) -> Option<Vec<u8>>,
+{
+    let expected = match *checksum {
+        FileChecksum::None => return SourceStatus::NoChecksum,
+        FileChecksum::Md5(digest) | FileChecksum::Sha1(digest) | FileChecksum::Sha256(digest) => {
+            digest
+        }
+    };
+
+    match resolve_source(path, checksum) {
+        Some(digest) if digest == expected => SourceStatus::Matches,
+        Some(_) => SourceStatus::Mismatch,
+        None => SourceStatus::Missing,
+    }
+}
+
+impl<'s, S: Source<'s> + 's> PDB<'s, S> {
+    /// Verifies this PDB against an image and the source files it was built from.
+    ///
+    /// This is a single entry point for the checks a symbol pipeline performs before publishing a
+    /// PDB:
+    ///
+    /// * If `image` is given, the CodeView record in its debug directory must reference this PDB,
+    ///   as checked by [`matches_image`](Self::matches_image).
+    /// * All symbols, types, ids and line programs must parse. Records of kinds this crate does not
+    ///   support are ignored.
+    /// * The [`module_health`](crate::DebugInformation::module_health) of all modules is reported.
+    /// * Every source file with a checksum is passed to `resolve_source` along with its checksum.
+    ///   The resolver returns the digest of the file computed with the same algorithm, or `None` if
+    ///   the file cannot be found. Each path is resolved once, even if several modules refer to it.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidImage` or `Error::UnexpectedEof` if `image` is not a valid PE image
+    /// * Errors reading the information, DBI and names streams, without which none of the other
+    ///   checks can be performed. All other errors are listed in the report.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let image = std::fs::read("fixtures/self/foo.exe")?;
+    ///
+    /// // The source files are not available here.
+    /// let report = pdb.verify(Some(&image), |_path, _checksum| None)?;
+    /// assert_eq!(report.image, Some(pdb::ImageStatus::Matches));
+    /// for source in report.sources_with_status(pdb::SourceStatus::Missing) {
+    ///     println!("missing {}", source.path);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn verify<F>(&self, image: Option<&[u8]>, resolve_source: F) -> Result<VerificationReport>
+    where
+        F: FnMut(&str, &FileChecksum<'_>) -> Option<Vec<u8>>,
+    {
+        self.verify_with_progress(image, resolve_source, |_| ())
+    }
+
+    /// Verifies this PDB like [`verify`](Self::verify), reporting progress along the way.
+    ///
+    /// `progress` is invoked periodically with the number of records read and the number of bytes
+    /// of the symbol, type, id and module streams processed, as well as once on completion.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`verify`](Self::verify).
+    pub fn verify_with_progress<F, P>(
+        &self,
+        image: Option<&[u8]>,
+        resolve_source: F,
+        progress: P,
+    ) -> Result<VerificationReport>
+    where
+        F: FnMut(&str, &FileChecksum<'_>) -> Option<Vec<u8>>,
+        P: FnMut(Progress),
+    {
+        self.verify_records(None, image, resolve_source, progress)
+    }
+
+    /// Verifies this PDB like [`verify_with_progress`](Self::verify_with_progress), unless
+    /// cancelled.
+    ///
+    /// `token` is checked before each symbol, type, id and line record.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Cancelled` if `token` was cancelled before verification completed
+    /// * The errors of [`verify`](Self::verify)
+    pub fn verify_cancellable<F, P>(
+        &self,
+        token: &CancellationToken,
+        image: Option<&[u8]>,
+        resolve_source: F,
+        progress: P,
+    ) -> Result<VerificationReport>
+    where
+        F: FnMut(&str, &FileChecksum<'_>) -> Option<Vec<u8>>,
+        P: FnMut(Progress),
+    {
+        self.verify_records(Some(token), image, resolve_source, progress)
+    }
+
+    fn verify_records<F, P>(
+        &self,
+        token: Option<&CancellationToken>,
+        image: Option<&[u8]>,
+        mut resolve_source: F,
+        progress: P,
+    ) -> Result<VerificationReport>
+    where
+        F: FnMut(&str, &FileChecksum<'_>) -> Option<Vec<u8>>,
+        P: FnMut(Progress),
+    {
+        let identity = self.image_identity()?;
+
+        let image = match image.map(PdbIdentity::from_image).transpose()? {
+            None => None,
+            Some(None) => Some(ImageStatus::MissingCodeView),
+            Some(Some(image)) if self.matches_image(&image)? => Some(ImageStatus::Matches),
+            Some(Some(image)) => Some(ImageStatus::Mismatch(image)),
+        };
+
+        let dbi = self.debug_information()?;
+        let global_symbols = self.global_symbols();
+        let types = self.type_information();
+        let ids = self.id_information();
+
+        // Streams that fail to load are reported as errors below and do not contribute progress.
+        let mut total = global_symbols
+            .as_ref()
+            .map_or(0, |table| table.stream_len())
+            + types.as_ref().map_or(0, |types| types.stream_len())
+            + ids.as_ref().map_or(0, |ids| ids.stream_len());
+        let mut modules = dbi.modules()?;
+        while let Some(module) = modules.next()? {
+            total += module_size(&module);
+        }
+
+        let mut reporter = ProgressReporter::new(progress, total);
+        let mut offset = 0;
+
+        let mut errors = Vec::new();
+        let result = global_symbols.and_then(|table| {
+            let result = verify_global_symbols(&table, token, &mut reporter, offset);
+            offset += table.stream_len();
+            result
+        });
+        check(&mut errors, PdbComponent::GlobalSymbols, result)?;
+
+        let result = types.and_then(|types| {
+            let result = verify_types(&types, token, &mut reporter, offset);
+            offset += types.stream_len();
+            result
+        });
+        check(&mut errors, PdbComponent::TypeInformation, result)?;
+
+        let result = ids.and_then(|ids| {
+            let result = verify_ids(&ids, token, &mut reporter, offset);
+            offset += ids.stream_len();
+            result
+        });
+        check(&mut errors, PdbComponent::IdInformation, result)?;
+
+        let strings = match self.string_table() {
+            Ok(strings) => Some(strings),
+            Err(Error::StreamNameNotFound) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut sources = BTreeMap::new();
+        let mut module_errors = false;
+
+        let mut modules = dbi.modules()?;
+        let mut index = 0;
+        while let Some(module) = modules.next()? {
+            let result = (|| {
+                let info = match self.module_info(&module)? {
+                    Some(info) => info,
+                    None => return Ok(()),
+                };
+
+                let mut symbols = info.symbols()?;
+                while let Some(symbol) = next_record(&mut symbols, token)? {
+                    supported(symbol.parse())?;
+                    reporter.record(offset + symbols.position().0 as usize);
+                }
+
+                let lines_offset = offset + module.info().symbols_size as usize;
+                let program = info.line_program()?;
+                let mut lines = program.lines();
+                while next_record(&mut lines, token)?.is_some() {
+                    reporter.record(lines_offset);
+                }
+
+                let mut files = program.files();
+                while let Some(file) = files.next()? {
+                    let name = match file.name(strings.as_ref())? {
+                        Some(name) => name,
+                        None => continue,
+                    };
+
+                    let path = name.to_string().into_owned();
+                    if let Entry::Vacant(entry) = sources.entry(path) {
+                        let status =
+                            verify_source(entry.key(), &file.checksum, &mut resolve_source);
+                        entry.insert(status);
+                    }
+                }
+
+                Ok(())
+            })();
+
+            offset += module_size(&module);
+            module_errors |= result.is_err();
+            check(&mut errors, PdbComponent::Module(index), result)?;
+            index += 1;
+        }
+
+        reporter.finish();
+
+        // Module health reads the same streams, and would fail on the first error listed above.
+        let unhealthy_modules = if module_errors {
+            Vec::new()
+        } else {
+            let health = dbi.module_health(self)?;
+            health.into_iter().filter(|h| !h.is_healthy()).collect()
+        };
+
+        Ok(VerificationReport {
+            identity,
+            image,
+            errors,
+            unhealthy_modules,
+            sources: sources
+                .into_iter()
+                .map(|(path, status)| SourceVerification { path, status })
+                .collect(),
+        })
+    }
+}
+
+/// Returns the next record of `iter`, unless `token` has been cancelled.
+fn next_record<I>(iter: &mut I, token: Option<&CancellationToken>) -> Result<Option<I::Item>>
+where
+    I: FallibleIterator<Error = Error>,
+{
+    if let Some(token) = token {
+        token.check()?;
+    }
+
+    iter.next()
+}
+
+/// Returns the size of the symbols and line information of a module in bytes.
+fn module_size(module: &Module<'_>) -> usize {
+    let info = module.info();
+    info.symbols_size as usize + info.lines_size as usize + info.c13_lines_size as usize
+}
+
+/// Parses all records of the global symbol table, which starts at `offset` of the verified data.
+fn verify_global_symbols<P>(
+    table: &SymbolTable<'_>,
+    token: Option<&CancellationToken>,
+    reporter: &mut ProgressReporter<P>,
+    offset: usize,
+) -> Result<()>
+where
+    P: FnMut(Progress),
+{
+    let mut symbols = table.iter();
+    while let Some(symbol) = next_record(&mut symbols, token)? {
+        supported(symbol.parse())?;
+        reporter.record(offset + symbols.position().0 as usize);
+    }
+    Ok(())
+}
+
+/// Parses all records of the type information stream, which starts at `offset` of the verified
+/// data.
+fn verify_types<P>(
+    types: &TypeInformation<'_>,
+    token: Option<&CancellationToken>,
+    reporter: &mut ProgressReporter<P>,
+    offset: usize,
+) -> Result<()>
+where
+    P: FnMut(Progress),
+{
+    let mut iter = types.iter();
+    while let Some(item) = next_record(&mut iter, token)? {
+        supported(item.parse())?;
+        reporter.record(offset + iter.position());
+    }
+    Ok(())
+}
+
+/// Parses all records of the id information stream, which starts at `offset` of the verified
+/// data.
+fn verify_ids<P>(
+    ids: &IdInformation<'_>,
+    token: Option<&CancellationToken>,
+    reporter: &mut ProgressReporter<P>,
+    offset: usize,
+) -> Result<()>
+where
+    P: FnMut(Progress),
+{
+    let mut iter = ids.iter();
+    while let Some(item) = next_record(&mut iter, token)? {
+        supported(item.parse())?;
+        reporter.record(offset + iter.position());
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use pdb::FallibleIterator;
+
+/// Returns the address and raw data of a section of `fixtures/self/foo.exe`.
+fn section<'d>(
+    sections: &[pdb::ImageSectionHeader],
+    image: &'d [u8],
+    name: &str,
+) -> (pdb::Rva, &'d [u8]) {
+    let header = sections
+        .iter()
+        .find(|header| header.name() == name)
+        .expect("section");
+
+    let start = header.pointer_to_raw_data as usize;
+    let end = start + header.size_of_raw_data as usize;
+    (pdb::Rva(header.virtual_address), &image[start..end])
+}
+
+#[test]
+fn test_stack_cfi_records() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let sections = pdb.sections().expect("sections").expect("section headers");
+    let image = std::fs::read("fixtures/self/foo.exe").expect("reading image");
+
+    let (_, pdata) = section(&sections, &image, ".pdata");
+    let (rdata_rva, rdata) = section(&sections, &image, ".rdata");
+
+    let mut table = pdb::UnwindTable::new(pdata);
+    table.add_image_data(rdata_rva, rdata);
+
+    let functions: Vec<_> = table.functions().collect().expect("functions");
+    let count = functions.iter().filter(|f| f.begin != f.end).count();
+
+    let records = pdb::stack_cfi_records(&table).expect("stack cfi records");
+    assert_eq!(records.len(), count);
+    assert!(records.windows(2).all(|pair| pair[0].rva < pair[1].rva));
+
+    assert_eq!(
+        records[0].to_string(),
+        "STACK CFI INIT 6560 20 .cfa: $rsp 8 + .ra: .cfa 8 - ^\n\
+         STACK CFI 656d .cfa: $rsp 48 +"
+    );
+
+    // Registers saved with `mov` into the home space of the caller are at or above the CFA.
+    let record = records
+        .iter()
+        .find(|record| record.rva == pdb::Rva(0x69dc))
+        .expect("record");
+    assert_eq!(record.rows[1].rules, ".cfa: $rsp 48 + $rbx: .cfa 0 - ^");
+}
+
+#[test]
+fn test_stack_records_without_frame_data() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    // The fixture contains neither x86 frame data nor copies of the x64 unwind sections.
+    assert!(pdb.unwind_data().expect("unwind data").is_none());
+    assert_eq!(pdb.stack_records().expect("stack records"), []);
+}
@@ -0,0 +1,170 @@
+#![cfg(any(feature = "zstd", feature = "gzip"))]
+
+use std::io::Cursor;
+#[cfg(feature = "gzip")]
+use std::io::Write;
+
+use pdb::{FallibleIterator, Source, SourceSlice};
+
+const FRAME_SIZE: usize = 0x10000;
+
+fn fixture() -> Vec<u8> {
+    std::fs::read("fixtures/self/foo.pdb").expect("reading fixture")
+}
+
+/// Asserts that the compressed PDB reads the same as the original.
+fn assert_same_pdb(original: &[u8], compressed: Vec<u8>) {
+    let mut source = pdb::CompressedSource::new(Cursor::new(compressed))
+        .expect("compressed source")
+        .cache_frames(2);
+    assert_eq!(source.decompressed_len(), original.len() as u64);
+
+    // Slices spanning frame boundaries, out of order.
+    let slices = [
+        SourceSlice {
+            offset: FRAME_SIZE as u64 * 3 - 0x800,
+            size: 0x1000,
+        },
+        SourceSlice {
+            offset: 0,
+            size: 0x1000,
+        },
+        SourceSlice {
+            offset: FRAME_SIZE as u64 - 0x1000,
+            size: FRAME_SIZE + 0x2000,
+        },
+    ];
+    let view = source.view(&slices).expect("view");
+    let expected: Vec<u8> = slices
+        .iter()
+        .flat_map(|slice| {
+            let offset = slice.offset as usize;
+            original[offset..offset + slice.size].iter().copied()
+        })
+        .collect();
+    assert_eq!(view.as_slice(), expected.as_slice());
+
+    let beyond = SourceSlice {
+        offset: original.len() as u64 - 0x10,
+        size: 0x20,
+    };
+    assert!(source.view(&[beyond]).is_err());
+
+    let pdb = pdb::PDB::open(source).expect("opening pdb");
+    let symbols = pdb.global_symbols().expect("global symbols");
+    let count = symbols.iter().count().expect("count symbols");
+
+    let expected = pdb::PDB::open(Cursor::new(original))
+        .expect("opening pdb")
+        .global_symbols()
+        .expect("global symbols")
+        .iter()
+        .count()
+        .expect("count symbols");
+    assert_eq!(count, expected);
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_frames(data: &[u8]) -> (Vec<u8>, Vec<(u32, u32)>) {
+    let mut compressed = Vec::new();
+    let mut entries = Vec::new();
+
+    for chunk in data.chunks(FRAME_SIZE) {
+        let frame =
+            ruzstd::encoding::compress_to_vec(chunk, ruzstd::encoding::CompressionLevel::Fastest);
+        entries.push((frame.len() as u32, chunk.len() as u32));
+        compressed.extend_from_slice(&frame);
+    }
+
+    (compressed, entries)
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_seekable() {
+    let original = fixture();
+    let (mut compressed, entries) = zstd_frames(&original);
+
+    // Seek table with checksums, which are ignored.
+    let table_size = entries.len() as u32 * 12 + 9;
+    compressed.extend_from_slice(&0x184d_2a5eu32.to_le_bytes());
+    compressed.extend_from_slice(&table_size.to_le_bytes());
+    for (compressed_size, size) in &entries {
+        compressed.extend_from_slice(&compressed_size.to_le_bytes());
+        compressed.extend_from_slice(&size.to_le_bytes());
+        compressed.extend_from_slice(&0u32.to_le_bytes());
+    }
+    compressed.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    compressed.push(0x80);
+    compressed.extend_from_slice(&0x8f92_eab1u32.to_le_bytes());
+
+    let source = pdb::CompressedSource::new(Cursor::new(compressed.clone())).expect("source");
+    assert_eq!(source.format(), pdb::CompressionFormat::Zstd);
+    assert_eq!(source.frame_count(), entries.len());
+
+    assert_same_pdb(&original, compressed);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_frames_without_seek_table() {
+    let original = fixture();
+    let (compressed, entries) = zstd_frames(&original);
+
+    let source = pdb::CompressedSource::new(Cursor::new(compressed.clone())).expect("source");
+    assert_eq!(source.frame_count(), entries.len());
+
+    assert_same_pdb(&original, compressed);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_bgzf() {
+    let original = fixture();
+    let mut compressed = Vec::new();
+
+    for chunk in original.chunks(FRAME_SIZE) {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(chunk).expect("compress");
+        let deflated = encoder.finish().expect("compress");
+
+        let mut crc = flate2::Crc::new();
+        crc.update(chunk);
+
+        let block_size = 18 + deflated.len() + 8;
+        compressed.extend_from_slice(&[
+            0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0,
+        ]);
+        compressed.extend_from_slice(&(block_size as u16 - 1).to_le_bytes());
+        compressed.extend_from_slice(&deflated);
+        compressed.extend_from_slice(&crc.sum().to_le_bytes());
+        compressed.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+
+    let source = pdb::CompressedSource::new(Cursor::new(compressed.clone())).expect("source");
+    assert_eq!(source.format(), pdb::CompressionFormat::Gzip);
+    assert_eq!(source.frame_count(), original.len().div_ceil(FRAME_SIZE));
+
+    assert_same_pdb(&original, compressed);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_single_member() {
+    let original = fixture();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(&original).expect("compress");
+    let compressed = encoder.finish().expect("compress");
+
+    let source = pdb::CompressedSource::new(Cursor::new(compressed.clone())).expect("source");
+    assert_eq!(source.frame_count(), 1);
+
+    assert_same_pdb(&original, compressed);
+}
+
+#[test]
+fn unsupported_format() {
+    let error = pdb::CompressedSource::new(Cursor::new(fixture())).expect_err("not compressed");
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -2,7 +2,7 @@
 fn pdb_info() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let pdb_info = pdb.debug_information().expect("pdb information");
 
     assert_eq!(
@@ -10,3 +10,308 @@ fn pdb_info() {
         pdb::MachineType::Amd64
     );
 }
+
+#[test]
+fn section_contribution_kinds() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let sections = pdb.sections().expect("sections").expect("section headers");
+    let text = sections
+        .iter()
+        .find(|section| section.name() == ".text")
+        .expect(".text section");
+    assert_eq!(text.kind(), pdb::SectionKind::Code);
+
+    let dbi = pdb.debug_information().expect("debug information");
+    let mut contributions = dbi.section_contributions().expect("section contributions");
+
+    let mut kinds = std::collections::HashSet::new();
+    while let Some(contribution) = contributions.next().expect("contribution") {
+        kinds.insert(contribution.kind());
+    }
+
+    assert!(kinds.contains(&pdb::SectionKind::Code));
+    assert!(kinds.contains(&pdb::SectionKind::Data));
+    assert!(kinds.contains(&pdb::SectionKind::ReadOnlyData));
+}
+
+#[test]
+fn module_contributions() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("debug information");
+
+    let modules: Vec<_> = dbi
+        .modules()
+        .expect("modules")
+        .collect()
+        .expect("collect modules");
+
+    let mut contributions = dbi.section_contributions().expect("section contributions");
+    let mut joined = dbi.module_contributions().expect("module contributions");
+    let mut count = 0;
+
+    while let Some(contribution) = contributions.next().expect("contribution") {
+        let module = &modules[contribution.module];
+
+        let joined = joined.next().expect("joined").expect("joined contribution");
+        assert_eq!(joined.range.start, contribution.offset);
+        assert_eq!(joined.range.end.section, contribution.offset.section);
+        assert_eq!(
+            joined.range.end.offset - joined.range.start.offset,
+            contribution.size
+        );
+        assert_eq!(joined.characteristics, contribution.characteristics);
+        assert_eq!(
+            joined.module_name.map(|name| name.to_string()),
+            Some(module.module_name())
+        );
+
+        count += 1;
+    }
+
+    assert!(count > 0);
+    assert!(joined.next().expect("end").is_none());
+}
+
+#[test]
+fn module_kinds() {
+    use pdb::{FallibleIterator, ModuleKind};
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("debug information");
+    let modules: Vec<_> = dbi
+        .modules()
+        .expect("modules")
+        .collect()
+        .expect("collect modules");
+
+    assert_eq!(
+        modules[0].kind(),
+        ModuleKind::Object("c:\\Users\\User\\Desktop\\self\\foo.obj".into())
+    );
+    assert_eq!(modules[0].kind().file_name(), "foo.obj".into());
+
+    match modules[1].kind() {
+        ModuleKind::ArchiveMember { archive, member } => {
+            assert!(archive.to_string().ends_with("LIBCMT.lib"));
+            assert!(member.to_string().ends_with("fltused.obj"));
+        }
+        other => panic!("unexpected module kind {:?}", other),
+    }
+
+    let imports = modules
+        .iter()
+        .filter(|module| match module.kind() {
+            ModuleKind::Import { dll, .. } => dll == "KERNEL32.dll".into(),
+            _ => false,
+        })
+        .count();
+    assert_eq!(imports, 2);
+
+    assert_eq!(
+        modules.last().expect("last module").kind(),
+        ModuleKind::Linker("* Linker *".into())
+    );
+}
+
+#[test]
+fn language_summary() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("debug information");
+    let summary = dbi.language_summary(&pdb).expect("language summary");
+
+    assert_eq!(summary.total(), 194);
+    assert_eq!(summary.count(pdb::SourceLanguage::Cpp), 149);
+    assert_eq!(summary.count(pdb::SourceLanguage::C), 31);
+    assert_eq!(summary.count(pdb::SourceLanguage::Masm), 11);
+    assert_eq!(summary.count(pdb::SourceLanguage::Link), 3);
+    assert_eq!(summary.count(pdb::SourceLanguage::Rust), 0);
+    assert_eq!(summary.unknown, 0);
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn module_infos_in_parallel() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    // Load all module infos up front and keep them alive together.
+    let dbi = pdb.debug_information().expect("debug information");
+    let mut modules = dbi.modules().expect("modules");
+    let mut infos = Vec::new();
+    while let Some(module) = modules.next().expect("next module") {
+        if let Some(info) = pdb.module_info(&module).expect("module info") {
+            infos.push(info);
+        }
+    }
+    assert!(infos.len() > 1);
+
+    let expected: Vec<usize> = infos
+        .iter()
+        .map(|info| info.symbols().expect("symbols").count().expect("count"))
+        .collect();
+
+    // Clones share the stream data and can be consumed on other threads.
+    let counts: Vec<usize> = std::thread::scope(|scope| {
+        let handles: Vec<_> = infos
+            .iter()
+            .cloned()
+            .map(|info| {
+                scope.spawn(move || info.symbols().expect("symbols").count().expect("count"))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("join"))
+            .collect()
+    });
+
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn linker_env_block() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("debug information");
+    let modules: Vec<_> = dbi
+        .modules()
+        .expect("modules")
+        .collect()
+        .expect("collect modules");
+
+    let linker = modules.last().expect("linker module");
+    let info = pdb
+        .module_info(linker)
+        .expect("module info")
+        .expect("linker module info");
+    let env = info.env_block().expect("env block").expect("linker env");
+
+    let keys: Vec<_> = env
+        .entries()
+        .map(|entry| Ok(entry.key))
+        .collect()
+        .expect("entries");
+    assert_eq!(keys, ["cwd", "exe", "pdb", "cmd"].map(pdb::RawString::from));
+
+    assert_eq!(
+        env.cwd().expect("cwd"),
+        Some("c:\\Users\\User\\Desktop\\self".into())
+    );
+    assert_eq!(
+        env.pdb().expect("pdb"),
+        Some("c:\\Users\\User\\Desktop\\self\\foo.pdb".into())
+    );
+    assert!(env
+        .exe()
+        .expect("exe")
+        .expect("linker path")
+        .to_string()
+        .ends_with("link.exe"));
+    assert_eq!(
+        env.command_line().expect("cmd"),
+        Some(" /errorreport:queue /out:foo.exe /debug /debug:full /out:foo.exe".into())
+    );
+}
+
+#[test]
+fn module_health() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let reports = dbi.module_health(&pdb).expect("module health");
+    assert_eq!(reports.len(), 194);
+    assert!(reports.iter().enumerate().all(|(i, h)| h.module == i));
+
+    // foo.obj has C13 lines with checksums for every file.
+    assert!(reports[0].is_healthy());
+
+    // The assembly source of log10.obj is recorded with a relative path for edit and continue.
+    assert!(!reports[156].ec_only_source);
+
+    // The linker module has no lines, and declares sections and COFF groups.
+    let linker = &reports[193];
+    assert!(linker.has_module_info);
+    assert!(!linker.has_c13_lines);
+    assert!(!linker.is_healthy());
+    assert_eq!(linker.unknown_symbol_kinds, [0x1136, 0x1137]);
+}
+
+#[test]
+fn substreams() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("debug information");
+    let stream = pdb
+        .raw_stream(pdb::StreamIndex(3))
+        .expect("raw stream")
+        .expect("dbi stream");
+
+    // The substreams follow the 64 byte header without gaps and fill the stream.
+    let mut end = 64;
+    for &substream in pdb::DBISubstream::ALL {
+        let range = dbi.substream_range(substream);
+        assert_eq!(range.start, end, "{:?}", substream);
+        assert_eq!(
+            dbi.substream(substream).expect("substream"),
+            &stream.as_slice()[range.clone()]
+        );
+        end = range.end;
+    }
+    assert_eq!(end, stream.as_slice().len());
+
+    // Section contributions start with their version, which is V60 in this file.
+    let contributions = dbi
+        .substream(pdb::DBISubstream::SectionContributions)
+        .expect("section contributions");
+    assert_eq!(
+        contributions[..4],
+        0xeffe_0000u32.wrapping_add(19_970_605).to_le_bytes()
+    );
+
+    // The optional debug header holds eleven stream indexes.
+    let header = dbi
+        .substream(pdb::DBISubstream::OptionalDebugHeader)
+        .expect("optional debug header");
+    assert_eq!(header.len(), 22);
+}
+
+#[test]
+fn optional_debug_header() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let header = pdb.optional_debug_header().expect("optional debug header");
+
+    // This x64 image was linked without FPO data and OMAP tables.
+    assert_eq!(header.section_headers, Some(pdb::StreamIndex(11)));
+    assert_eq!(header.fpo, None);
+    assert_eq!(header.framedata, None);
+    assert_eq!(header.omap_from_src, None);
+    assert_eq!(header.original_section_headers, None);
+
+    let dbi = pdb.debug_information().expect("debug information");
+    assert_eq!(dbi.optional_debug_header().expect("header"), header);
+
+    // The section headers stream holds one 40 byte header per section.
+    let index = header.section_headers.expect("section headers");
+    let stream = pdb.raw_stream(index).expect("raw stream").expect("stream");
+    let sections = pdb.sections().expect("sections").expect("section headers");
+    assert_eq!(stream.as_slice().len(), sections.len() * 40);
+}
@@ -0,0 +1,83 @@
+use pdb::{ExtractedString, FallibleIterator, StringOrigin};
+
+fn open() -> pdb::PDB<'static, std::fs::File> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    pdb::PDB::open(file).expect("opening pdb")
+}
+
+#[test]
+fn string_table_iter() {
+    let pdb = open();
+    let strings = pdb.string_table().expect("string table");
+
+    let mut count = 0;
+    let mut iter = strings.iter();
+    while let Some((offset, value)) = iter.next().expect("next") {
+        assert_eq!(strings.get(offset).expect("get"), value);
+        count += 1;
+    }
+
+    assert!(count > 1);
+}
+
+#[test]
+fn extract_strings() {
+    let pdb = open();
+
+    let mut strings = Vec::new();
+    pdb.extract_strings(|string| {
+        assert!(!string.value.is_empty());
+        strings.push((string.value.to_string().into_owned(), string.origin));
+    })
+    .expect("extract strings");
+
+    let has = |predicate: &dyn Fn(&str, StringOrigin) -> bool| {
+        strings
+            .iter()
+            .any(|(value, origin)| predicate(value, *origin))
+    };
+
+    assert!(has(&|_, origin| matches!(
+        origin,
+        StringOrigin::StringTable(_)
+    )));
+    assert!(has(
+        &|value, origin| value == "/names" && matches!(origin, StringOrigin::StreamName(_))
+    ));
+    assert!(has(
+        &|value, origin| value.ends_with(".obj") && matches!(origin, StringOrigin::ModuleName(_))
+    ));
+    assert!(has(
+        &|value, origin| value.ends_with(".h") && matches!(origin, StringOrigin::SourceFile(_))
+    ));
+    assert!(has(
+        &|value, origin| value == "main" && matches!(origin, StringOrigin::ModuleSymbol { .. })
+    ));
+    assert!(has(&|value, origin| value == "std::exception"
+        && origin == StringOrigin::Type(pdb::TypeIndex(0x1212))));
+    assert!(has(&|value, origin| value.starts_with(".?AV")
+        && origin == StringOrigin::Type(pdb::TypeIndex(0x1212))));
+    assert!(has(&|_, origin| matches!(
+        origin,
+        StringOrigin::GlobalSymbol(_)
+    )));
+    assert!(has(&|_, origin| matches!(origin, StringOrigin::Id(_))));
+}
+
+#[test]
+fn content_hash() {
+    let string = |value: &'static str, origin| ExtractedString {
+        value: value.into(),
+        origin,
+    };
+
+    let a = string("a", StringOrigin::ModuleName(0));
+    assert_eq!(a.content_hash(), 0xaf63_dc4c_8601_ec8c);
+
+    let b = string("a", StringOrigin::Type(pdb::TypeIndex(0x1000)));
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_ne!(
+        a.content_hash(),
+        string("b", StringOrigin::ModuleName(0)).content_hash()
+    );
+}
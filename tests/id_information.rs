@@ -1,6 +1,9 @@
-//! Tests that IdInformation works on files where the IPI is missing (empty stream).
+//! Tests for IdInformation, including files where the IPI is missing (empty stream).
 
-use pdb::{FallibleIterator, IdIndex, PDB};
+use pdb::{
+    Error, FallibleIterator, IdData, IdIndex, StringRef, SymbolData, TypeIndex,
+    UserDefinedTypeSourceFileRef, PDB,
+};
 
 fn open_file() -> std::fs::File {
     let path = "fixtures/symbol_server/0ea7c70545374958ad3307514bdfc8642-wntdll.pdb";
@@ -9,7 +12,7 @@ fn open_file() -> std::fs::File {
 
 #[test]
 fn test_missing_ipi() {
-    let mut pdb = PDB::open(open_file()).expect("opening pdb");
+    let pdb = PDB::open(open_file()).expect("opening pdb");
 
     let id_information = pdb.id_information().expect("get id information");
 
@@ -27,3 +30,141 @@ fn test_missing_ipi() {
     finder.find(IdIndex(0)).expect_err("find index");
     finder.find(IdIndex(4097)).expect_err("find index");
 }
+
+#[test]
+fn test_resolve_strings() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+
+    let id_information = pdb.id_information().expect("get id information");
+    let mut finder = id_information.finder();
+    let mut iter = id_information.iter();
+
+    let mut split_strings = 0;
+    while let Some(id) = iter.next().expect("next id") {
+        finder.update(&iter);
+
+        let string = match id.parse() {
+            Ok(IdData::String(string)) => string,
+            _ => continue,
+        };
+
+        let mut expected = String::new();
+        if let Some(list) = string.substrings {
+            split_strings += 1;
+            let list = match finder.find(list).expect("find list").parse() {
+                Ok(IdData::StringList(list)) => list,
+                other => panic!("expected a substring list, got {:?}", other),
+            };
+
+            for substring in list.substrings {
+                let index = IdIndex(substring.0);
+                match finder.find(index).expect("find substring").parse() {
+                    Ok(IdData::String(substring)) => expected.push_str(&substring.name.to_string()),
+                    other => panic!("expected a string, got {:?}", other),
+                }
+            }
+        }
+        expected.push_str(&string.name.to_string());
+
+        let resolved = id_information.string(id.index()).expect("resolve string");
+        assert_eq!(&*resolved, expected);
+        assert_eq!(id_information.string(id.index()).expect("cached"), resolved);
+    }
+
+    assert!(split_strings > 0);
+    assert!(matches!(
+        id_information.string(IdIndex(0x1000)),
+        Err(Error::TypeNotFound(0x1000))
+    ));
+}
+
+#[test]
+fn test_type_source() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+
+    let id_information = pdb.id_information().expect("get id information");
+    let strings = pdb.string_table().expect("string table");
+
+    let source = id_information
+        .udt_source(TypeIndex(0x100e))
+        .expect("udt source")
+        .expect("source record");
+    assert_eq!(source.line, 145);
+    assert_eq!(
+        source.source_file,
+        UserDefinedTypeSourceFileRef::Remote(1, StringRef(0x1))
+    );
+
+    let location = id_information
+        .type_source(TypeIndex(0x100e), &strings)
+        .expect("type source")
+        .expect("location");
+    assert!(location.file.ends_with("vadefs.h"), "{}", location.file);
+    assert_eq!(location.line, 145);
+    assert_eq!(location.module, Some(0));
+
+    let type_information = pdb.type_information().expect("type information");
+    let exception = type_information
+        .find_by_name("std::exception")
+        .expect("find")
+        .expect("std::exception");
+    let location = id_information
+        .type_source(exception, &strings)
+        .expect("type source")
+        .expect("location");
+    assert!(location.file.ends_with(".h"), "{}", location.file);
+
+    assert_eq!(
+        id_information
+            .udt_source(TypeIndex(0x74))
+            .expect("primitive"),
+        None
+    );
+}
+
+#[test]
+fn test_build_info() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+
+    let id_information = pdb.id_information().expect("get id information");
+    let dbi = pdb.debug_information().expect("debug information");
+    let module = dbi
+        .modules()
+        .expect("modules")
+        .next()
+        .expect("next module")
+        .expect("first module");
+    let info = pdb
+        .module_info(&module)
+        .expect("module info")
+        .expect("module stream");
+
+    let mut symbols = info.symbols().expect("symbols");
+    let id = loop {
+        match symbols
+            .next()
+            .expect("next symbol")
+            .expect("build info symbol")
+            .parse()
+        {
+            Ok(SymbolData::BuildInfo(symbol)) => break symbol.id,
+            _ => continue,
+        }
+    };
+
+    let build_info = id_information.build_info(id).expect("build info");
+    let source_file = build_info.source_file.expect("source file");
+    assert!(source_file.ends_with("foo.cpp"), "{}", source_file);
+    let build_tool = build_info.build_tool.expect("build tool");
+    assert!(build_tool.ends_with("cl.EXE"), "{}", build_tool);
+    assert!(build_info.current_directory.is_some());
+    assert!(build_info.command_line.expect("command line").contains('-'));
+
+    assert!(matches!(
+        id_information.build_info(IdIndex(0x1000)),
+        Err(Error::TypeNotFound(0x1000))
+    ));
+}
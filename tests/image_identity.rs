@@ -0,0 +1,76 @@
+use pdb::{ImageMap, LoadedImage, PdbIdentity, Rva};
+
+/// Returns the CodeView record from the debug directory of `fixtures/self/foo.exe`.
+fn codeview_record(image: &[u8]) -> &[u8] {
+    let offset = image
+        .windows(4)
+        .position(|window| window == b"RSDS")
+        .expect("CodeView record");
+    &image[offset..]
+}
+
+#[test]
+fn test_matches_image() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let image = std::fs::read("fixtures/self/foo.exe").expect("reading image");
+
+    let identity = PdbIdentity::from_codeview(codeview_record(&image)).expect("identity");
+    assert_eq!(pdb.image_identity().expect("image identity"), identity);
+    assert!(pdb.matches_image(&identity).expect("matches"));
+
+    let newer = PdbIdentity {
+        age: identity.age + 1,
+        ..identity
+    };
+    assert!(!pdb.matches_image(&newer).expect("matches"));
+
+    let other = PdbIdentity {
+        guid: uuid::Uuid::nil(),
+        ..identity
+    };
+    assert!(!pdb.matches_image(&other).expect("matches"));
+}
+
+#[test]
+fn test_symbolicate_address() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let identity = pdb.image_identity().expect("image identity");
+
+    let mut images = ImageMap::new();
+    images.insert(LoadedImage {
+        base: 0x1_4000_0000,
+        size: 0x7b000,
+        identity: Some(identity),
+        data: &pdb,
+    });
+
+    let functions = pdb.functions().expect("functions");
+    let (image, rva) = images.lookup(0x1_4000_6561).expect("image");
+    assert_eq!(rva, Rva(0x6561));
+    assert!(image.data.matches_image(&identity).expect("matches"));
+
+    let function = functions.lookup(rva).expect("lookup").expect("function");
+    assert!(function.range.start <= rva && rva < function.range.end);
+
+    assert!(images.lookup(0x1_4007_b000).is_none());
+}
+
+#[test]
+fn test_from_image() {
+    let image = std::fs::read("fixtures/self/foo.exe").expect("reading image");
+
+    let identity = PdbIdentity::from_image(&image).expect("parse");
+    let expected = PdbIdentity::from_codeview(codeview_record(&image)).expect("identity");
+    assert_eq!(identity, Some(expected));
+
+    assert!(matches!(
+        PdbIdentity::from_image(b"not an image"),
+        Err(pdb::Error::InvalidImage)
+    ));
+    assert!(matches!(
+        PdbIdentity::from_image(&image[..0x100]),
+        Err(pdb::Error::UnexpectedEof)
+    ));
+}
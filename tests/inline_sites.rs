@@ -0,0 +1,90 @@
+use pdb::{FallibleIterator, PDB};
+
+#[test]
+fn test_inline_sites() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+    let inline_sites = pdb.inline_sites()?;
+
+    let mut count = 0;
+    let mut sites = inline_sites.iter();
+    while let Some((_module, site)) = sites.next()? {
+        // every inline site lies within the procedure it was inlined into
+        assert!(site.index > site.procedure_index);
+        assert!(site.symbol.end < site.procedure.end);
+
+        if let Some(inlinee) = &site.inlinee {
+            assert_eq!(inlinee.index(), site.symbol.inlinee);
+        }
+
+        count += 1;
+    }
+
+    assert!(count > 0);
+
+    // modules can be processed independently, and in parallel if sources can be shared between
+    // threads
+    let modules = inline_sites.modules()?;
+    #[cfg(feature = "sync")]
+    let independent: usize = std::thread::scope(|scope| {
+        let handles: Vec<_> = modules
+            .into_iter()
+            .map(|(_module, sites)| scope.spawn(move || sites.count()))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap().expect("inline sites"))
+            .sum()
+    });
+    #[cfg(not(feature = "sync"))]
+    let independent: usize = modules
+        .into_iter()
+        .map(|(_module, sites)| sites.count().expect("inline sites"))
+        .sum();
+
+    assert_eq!(independent, count);
+    Ok(())
+}
+
+#[test]
+fn test_inline_sites_by_module() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+    let inline_sites = pdb.inline_sites()?;
+
+    // nothing is loaded before the first lookup
+    assert!(format!("{:?}", inline_sites).contains("loaded: 0"));
+
+    let mut per_module = std::collections::BTreeMap::new();
+    let mut sites = inline_sites.iter();
+    while let Some((module, _site)) = sites.next()? {
+        *per_module.entry(module).or_insert(0) += 1;
+    }
+
+    for (&module, &count) in &per_module {
+        let sites = inline_sites.module(module)?.expect("module");
+        assert_eq!(sites.count()?, count);
+    }
+
+    assert!(inline_sites.module(usize::MAX)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_invalid_annotations() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+    let dbi = pdb.debug_information()?;
+
+    let mut invalid = Vec::new();
+    let mut modules = dbi.modules()?;
+    while let Some(module) = modules.next()? {
+        if let Some(info) = pdb.module_info(&module)? {
+            invalid.extend(info.invalid_annotations()?);
+        }
+    }
+
+    assert_eq!(invalid, []);
+    Ok(())
+}
@@ -5,7 +5,7 @@ use pdb::{FallibleIterator, Result, PDB};
 #[test]
 fn test_symbol_depth() -> Result<()> {
     let file = File::open("fixtures/self/foo.pdb")?;
-    let mut pdb = PDB::open(file)?;
+    let pdb = PDB::open(file)?;
 
     let dbi = pdb.debug_information()?;
     let mut modules = dbi.modules()?;
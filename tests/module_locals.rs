@@ -0,0 +1,198 @@
+use pdb::{FallibleIterator, PDB};
+
+#[test]
+fn test_signatures() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+
+    let type_information = pdb.type_information()?;
+    let mut type_finder = type_information.finder();
+    let mut types = type_information.iter();
+    while types.next()?.is_some() {
+        type_finder.update(&types);
+    }
+
+    let dbi = pdb.debug_information()?;
+    let mut modules = dbi.modules()?;
+    let mut found = 0;
+
+    while let Some(module) = modules.next()? {
+        if !module.module_name().ends_with("foo.obj") {
+            continue;
+        }
+
+        let info = pdb.module_info(&module)?.expect("module info");
+        let mut symbols = info.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            let procedure = match symbol.parse() {
+                Ok(pdb::SymbolData::Procedure(procedure)) => procedure,
+                _ => continue,
+            };
+
+            let signature = info
+                .signature_of(symbol.index(), &type_finder)?
+                .expect("signature");
+            let names: Vec<_> = signature
+                .parameters
+                .iter()
+                .map(|parameter| parameter.name().map(|name| name.to_string().into_owned()))
+                .collect();
+
+            match procedure.name.as_bytes() {
+                b"Baz::Baz" => {
+                    let this = signature.this.expect("this");
+                    assert_eq!(this.name(), Some("this".into()));
+                    assert_eq!(names, vec![Some("constructor_arg".to_owned())]);
+                    assert_eq!(signature.parameters[0].type_index, pdb::TypeIndex(0x74));
+                    found += 1;
+                }
+                b"Baz::static_f_public" => {
+                    assert!(signature.this.is_none());
+                    assert!(names.is_empty());
+                    found += 1;
+                }
+                b"main" => {
+                    assert!(signature.this.is_none());
+                    assert_eq!(
+                        names,
+                        vec![Some("argc".to_owned()), Some("argv".to_owned())]
+                    );
+
+                    let locals = info.locals_of(symbol.index())?;
+                    assert!(locals.len() >= 2);
+                    assert_eq!(locals[0].name, "argc".into());
+                    assert_eq!(locals[1].name, "argv".into());
+                    found += 1;
+                }
+                b"printf" => {
+                    // variadic arguments are not paired with a variable
+                    let last = signature.parameters.last().expect("parameters");
+                    assert_eq!(last.type_index, pdb::TypeIndex(0));
+                    assert!(last.variable.is_none());
+                    found += 1;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    assert_eq!(found, 4);
+    Ok(())
+}
+
+#[test]
+fn test_signature_errors() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+
+    // a finder that has not seen any types, which can only find the first few types by
+    // scanning from the start of the stream
+    let type_information = pdb.type_information()?;
+    let type_finder = type_information.finder();
+
+    let dbi = pdb.debug_information()?;
+    let mut modules = dbi.modules()?;
+    let mut procedures = 0;
+
+    while let Some(module) = modules.next()? {
+        if !module.module_name().ends_with("foo.obj") {
+            continue;
+        }
+
+        let info = pdb.module_info(&module)?.expect("module info");
+        let mut symbols = info.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            let signature = info.signature_of(symbol.index(), &type_finder);
+            match symbol.parse()? {
+                pdb::SymbolData::Procedure(procedure) if procedure.type_index.0 >= 0x1008 => {
+                    assert!(matches!(signature, Err(pdb::Error::TypeNotIndexed(..))));
+                    procedures += 1;
+                }
+                pdb::SymbolData::Procedure(_) => (),
+                _ => assert!(signature?.is_none()),
+            }
+        }
+    }
+
+    assert!(procedures > 0);
+    Ok(())
+}
+
+#[test]
+fn test_resolve_name() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+
+    let dbi = pdb.debug_information()?;
+    let mut modules = dbi.modules()?;
+    let mut found = false;
+
+    while let Some(module) = modules.next()? {
+        if !module.module_name().ends_with("foo.obj") {
+            continue;
+        }
+
+        let info = pdb.module_info(&module)?.expect("module info");
+        let mut symbols = info.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            let procedure = match symbol.parse() {
+                Ok(pdb::SymbolData::Procedure(procedure)) if procedure.name == "main".into() => {
+                    procedure
+                }
+                _ => continue,
+            };
+
+            let argc = info
+                .resolve_name(symbol.index(), procedure.offset, b"argc")?
+                .expect("argc");
+            match argc.parse()? {
+                pdb::SymbolData::RegisterRelative(data) => assert_eq!(data.name, "argc".into()),
+                other => panic!("unexpected symbol {:?}", other),
+            }
+
+            assert!(info
+                .resolve_name(symbol.index(), procedure.offset, b"missing")?
+                .is_none());
+
+            // addresses outside of the procedure do not resolve
+            let mut outside = procedure.offset;
+            outside.offset += procedure.len;
+            assert!(info
+                .resolve_name(symbol.index(), outside, b"argc")?
+                .is_none());
+            found = true;
+        }
+    }
+
+    assert!(found);
+    Ok(())
+}
+
+#[test]
+fn test_object_name() -> pdb::Result<()> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    let pdb = PDB::open(file)?;
+
+    let dbi = pdb.debug_information()?;
+    let mut modules = dbi.modules()?;
+    let mut found = 0;
+
+    while let Some(module) = modules.next()? {
+        let info = match pdb.module_info(&module)? {
+            Some(info) => info,
+            None => continue,
+        };
+
+        let object = info.object_name()?;
+        if module.module_name().ends_with("foo.obj") {
+            let object = object.expect("object name");
+            assert_eq!(object.name.to_string(), module.module_name());
+            assert_eq!(object.signature, 0);
+        }
+
+        found += object.is_some() as usize;
+    }
+
+    assert!(found > 0);
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use pdb::FallibleIterator;
+
+#[test]
+fn functions_sorted_and_deduplicated() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let functions = pdb.functions().expect("functions");
+    let list: Vec<_> = functions.iter().collect().expect("collect functions");
+
+    assert_eq!(list.len(), functions.len());
+    assert!(!list.is_empty());
+    assert!(list
+        .windows(2)
+        .all(|pair| pair[0].range.start < pair[1].range.start));
+
+    for function in list.iter().step_by(50) {
+        let found = functions
+            .lookup(function.range.start)
+            .expect("lookup")
+            .expect("function at start");
+        assert_eq!(&found, function);
+
+        // Without hybrid thunks, native lookups are plain lookups.
+        let native = functions
+            .lookup_native(function.range.start)
+            .expect("lookup native");
+        assert_eq!(native, Some(found));
+    }
+}
+
+#[test]
+fn functions_match_public_symbols() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let address_map = pdb.address_map().expect("address map");
+
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let main = global_symbols
+        .iter()
+        .filter_map(|symbol| match symbol.parse() {
+            Ok(pdb::SymbolData::Public(public)) if public.name == "main".into() => Ok(Some(public)),
+            _ => Ok(None),
+        })
+        .next()
+        .expect("next symbol")
+        .expect("public symbol of main");
+    let rva = main.offset.to_rva(&address_map).expect("main rva");
+
+    let functions = pdb.functions().expect("functions");
+    let function = functions
+        .lookup(rva + 1)
+        .expect("lookup")
+        .expect("function containing main");
+    assert_eq!(function.range.start, rva);
+    assert_eq!(function.name, "main".into());
+}
+
+#[test]
+fn functions_skip_zero_length() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let all = pdb.functions().expect("functions");
+    let functions = pdb
+        .functions_with_policy(pdb::DegenerateRecordPolicy::Skip)
+        .expect("functions");
+
+    assert!(functions.len() <= all.len());
+    let mut iter = functions.iter();
+    while let Some(function) = iter.next().expect("function") {
+        assert!(function.range.start < function.range.end);
+    }
+}
+
+#[test]
+fn functions_tagged_with_architecture() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let architectures = pdb.architecture_map().expect("architecture map");
+    assert_eq!(architectures.machine_type(), pdb::MachineType::Amd64);
+    assert!(!architectures.is_hybrid());
+    assert!(!architectures.ranges().is_empty());
+    assert!(architectures
+        .ranges()
+        .windows(2)
+        .all(|pair| pair[0].range.end <= pair[1].range.start));
+
+    let functions = pdb.functions().expect("functions");
+    let mut iter = functions.iter();
+    let mut tagged = 0;
+    while let Some(function) = iter.next().expect("next function") {
+        if let Some(cpu_type) = function.cpu_type {
+            assert_eq!(cpu_type, pdb::CPUType::X64);
+            if function.range.start < function.range.end {
+                assert_eq!(architectures.lookup(function.range.start), Some(cpu_type));
+            }
+            tagged += 1;
+        }
+    }
+
+    assert!(tagged > 0);
+}
+
+#[test]
+fn functions_without_lines() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let functions = pdb.functions().expect("functions");
+    let missing = functions.without_lines().expect("functions without lines");
+
+    assert!(!missing.is_empty());
+    assert!(missing.len() < functions.len());
+    assert!(missing
+        .windows(2)
+        .all(|pair| pair[0].range.start < pair[1].range.start));
+
+    // _local_unwind is written in assembly, while main has line information.
+    let names: Vec<_> = missing.iter().map(|function| function.name).collect();
+    assert!(names.contains(&"_local_unwind".into()));
+    assert!(!names.contains(&"main".into()));
+}
+
+#[test]
+fn functions_follow_trampolines() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let address_map = pdb.address_map().expect("address map");
+
+    // The fixture is linked incrementally, so the linker module declares a jump thunk for every
+    // function.
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let mut trampoline = None;
+    while let Some(module) = modules.next().expect("next module") {
+        let info = match pdb.module_info(&module).expect("module info") {
+            Some(info) => info,
+            None => continue,
+        };
+
+        let mut symbols = info.symbols().expect("symbols");
+        while let Some(symbol) = symbols.next().expect("next symbol") {
+            if let Ok(pdb::SymbolData::Trampoline(data)) = symbol.parse() {
+                trampoline = Some(data);
+                break;
+            }
+        }
+    }
+
+    let trampoline = trampoline.expect("trampoline");
+    assert_eq!(trampoline.tramp_type, pdb::TrampolineType::Incremental);
+    let thunk = trampoline.thunk.to_rva(&address_map).expect("thunk rva");
+    let target = trampoline.target.to_rva(&address_map).expect("target rva");
+
+    let functions = pdb.functions().expect("functions");
+    assert_eq!(functions.trampoline_target(thunk), Some(target));
+    assert_eq!(functions.trampoline_target(target), None);
+    assert_eq!(functions.lookup(thunk).expect("lookup"), None);
+
+    let function = functions
+        .lookup_target(thunk)
+        .expect("lookup target")
+        .expect("function at target");
+    assert_eq!(function.range.start, target);
+    assert_eq!(
+        functions.lookup_target(target).expect("lookup target"),
+        Some(function)
+    );
+}
+
+#[test]
+fn estimated_functions_cover_procedures() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let estimated = pdb.estimate_functions().expect("estimate functions");
+    let functions = pdb.functions().expect("functions");
+    assert!(!estimated.is_empty());
+
+    let mut matched = 0;
+    let mut iter = estimated.iter();
+    while let Some(function) = iter.next().expect("next function") {
+        assert_ne!(function.bound, pdb::ExtentBound::Unbounded);
+        assert_eq!(
+            estimated
+                .lookup(function.range.start)
+                .expect("lookup")
+                .as_ref(),
+            Some(&function)
+        );
+
+        // Estimates never cut off the code of a procedure starting at the same address.
+        if let Some(exact) = functions.lookup(function.range.start).expect("lookup") {
+            if exact.range.start == function.range.start {
+                assert!(exact.range.end <= function.range.end, "{}", exact.name);
+                matched += 1;
+            }
+        }
+    }
+
+    assert!(matched > estimated.len() / 2);
+}
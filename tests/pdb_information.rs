@@ -2,7 +2,7 @@
 fn pdb_info() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let pdb_info = pdb.pdb_information().expect("pdb information");
 
     assert_eq!(pdb_info.age, 2);
@@ -12,3 +12,98 @@ fn pdb_info() {
     );
     assert_eq!(pdb_info.signature, 0x587B_A621);
 }
+
+#[test]
+fn stream_versions() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb_info = pdb.pdb_information().expect("pdb information");
+    assert_eq!(pdb_info.version, pdb::PdbStreamVersion::VC70);
+    assert_eq!(pdb_info.version.to_string(), "VC70");
+    assert_eq!(u32::from(pdb_info.version), 20000404);
+
+    let dbi = pdb.debug_information().expect("debug information");
+    assert_eq!(dbi.version(), pdb::HeaderVersion::V70);
+    assert_eq!(dbi.version().to_string(), "V70");
+
+    let type_information = pdb.type_information().expect("type information");
+    assert_eq!(type_information.version(), pdb::TypeStreamVersion::V80);
+    assert_eq!(type_information.version().to_string(), "V80");
+}
+
+#[test]
+fn stream_names_skip_malformed_entries() {
+    use std::convert::TryInto;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let info = pdb.pdb_information().expect("pdb information");
+    let (names_offset, names_size) = (info.names_offset, info.names_size);
+    let mut stream = pdb
+        .raw_stream(pdb::StreamIndex(1))
+        .expect("raw stream")
+        .expect("pdb information stream")
+        .as_slice()
+        .to_vec();
+
+    // Skip the name data, the entry count and size, and the present and deleted bit sets.
+    let read = |stream: &[u8], offset: usize| {
+        u32::from_le_bytes(stream[offset..offset + 4].try_into().unwrap()) as usize
+    };
+    let mut offset = names_offset + names_size + 8;
+    offset += 4 + read(&stream, offset) * 4;
+    offset += 4 + read(&stream, offset) * 4;
+
+    // The first entry refers to a stream number beyond the range of stream indexes, and the second
+    // entry to a name past the end of the name data.
+    stream[offset + 4..offset + 8].copy_from_slice(&0x1_0000u32.to_le_bytes());
+    stream[offset + 8..offset + 12].copy_from_slice(&0xffffu32.to_le_bytes());
+
+    let mut replacements = std::collections::BTreeMap::new();
+    replacements.insert(pdb::StreamIndex(1), &stream[..]);
+    let mut output = std::io::Cursor::new(Vec::new());
+    pdb.rewrite(&mut output, &replacements).expect("rewrite");
+
+    let pdb = pdb::PDB::open(output).expect("opening rewritten pdb");
+    let info = pdb.pdb_information().expect("pdb information");
+    let names = info.stream_names().expect("stream names");
+    assert_eq!(names.iter().count(), 2);
+}
+
+#[test]
+fn pdb_info_from_memory() {
+    let data = std::fs::read("fixtures/self/foo.pdb").expect("reading file");
+
+    let pdb = pdb::PDB::open(pdb::MemorySource::new(&data)).expect("opening pdb");
+    let pdb_info = pdb.pdb_information().expect("pdb information");
+
+    assert_eq!(pdb_info.age, 2);
+    assert_eq!(pdb_info.signature, 0x587B_A621);
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn pdb_shared_between_threads() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    pdb.string_table().expect("string table");
+                    let dbi = pdb.debug_information().expect("debug information");
+                    dbi.modules().expect("modules").count().expect("count")
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            assert_eq!(thread.join().expect("join"), 194);
+        }
+    });
+}
@@ -1,9 +1,9 @@
-use pdb::{FallibleIterator, Rva, PDB};
+use pdb::{FallibleIterator, LineRegion, PdbInternalSectionOffset, Rva, PDB};
 
 #[test]
 fn test_module_lines() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
-    let mut pdb = PDB::open(file).expect("parse pdb");
+    let pdb = PDB::open(file).expect("parse pdb");
 
     let address_map = pdb.address_map().expect("address map");
     let string_table = pdb.string_table().expect("string table");
@@ -21,7 +21,10 @@ fn test_module_lines() {
     let line_info = lines.next().expect("parse line info").expect("no lines");
 
     let rva = line_info.offset.to_rva(&address_map).expect("line rva");
-    let file_info = line_program
+    // File information borrows from the module and outlives the line program.
+    let file_info = module_info
+        .line_program()
+        .expect("line program")
         .get_file_info(line_info.file_index)
         .expect("file info");
     let file_name = file_info
@@ -34,3 +37,459 @@ fn test_module_lines() {
     assert_eq!(rva, Rva(0x64f0));
     assert_eq!(file_name, "c:\\users\\user\\desktop\\self\\foo.cpp");
 }
+
+#[test]
+fn test_line_lengths() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let mut count = 0;
+
+    while let Some(module) = modules.next().expect("parse module") {
+        let module_info = match pdb.module_info(&module).expect("parse module info") {
+            Some(module_info) => module_info,
+            None => continue,
+        };
+
+        let line_program = module_info.line_program().expect("line program");
+        let lines: Vec<_> = line_program.lines().collect().expect("collect lines");
+
+        // every line record has a length, which never overlaps the following record
+        for pair in lines.windows(2) {
+            let length = pair[0].length.expect("line length");
+            if pair[0].offset.section == pair[1].offset.section
+                && pair[0].offset.offset < pair[1].offset.offset
+            {
+                assert!(pair[0].offset.offset + length <= pair[1].offset.offset);
+            }
+        }
+
+        if let Some(last) = lines.last() {
+            assert!(last.length.is_some());
+        }
+
+        count += lines.len();
+    }
+
+    assert!(count > 0);
+}
+
+#[test]
+fn test_lines_sorted() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let address_map = pdb.address_map().expect("address map");
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let mut count = 0;
+
+    while let Some(module) = modules.next().expect("parse module") {
+        let module_info = match pdb.module_info(&module).expect("parse module info") {
+            Some(module_info) => module_info,
+            None => continue,
+        };
+
+        let line_program = module_info.line_program().expect("line program");
+        let mut expected: Vec<_> = line_program.lines().collect().expect("collect lines");
+        let sorted: Vec<_> = line_program
+            .lines_sorted(&address_map)
+            .expect("sort lines")
+            .collect()
+            .expect("collect sorted lines");
+
+        let rvas: Vec<_> = sorted
+            .iter()
+            .map(|line| line.offset.to_rva(&address_map).expect("line rva"))
+            .collect();
+        assert!(rvas.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // sorting must not drop or duplicate any records
+        expected.sort_by_key(|line| line.offset.to_rva(&address_map));
+        assert_eq!(expected, sorted);
+
+        count += sorted.len();
+    }
+
+    assert!(count > 0);
+}
+
+#[test]
+fn test_line_lookup() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let mut count = 0;
+
+    while let Some(module) = modules.next().expect("parse module") {
+        let module_info = match pdb.module_info(&module).expect("parse module info") {
+            Some(module_info) => module_info,
+            None => continue,
+        };
+
+        let line_program = module_info.line_program().expect("line program");
+        let mut lines = line_program.lines();
+
+        while let Some(line) = lines.next().expect("parse line") {
+            let length = line.length.expect("line length");
+            if length == 0 {
+                continue;
+            }
+
+            let last =
+                PdbInternalSectionOffset::new(line.offset.section, line.offset.offset + length - 1);
+
+            for offset in [line.offset, last] {
+                let found = line_program
+                    .lookup(offset)
+                    .expect("lookup line")
+                    .expect("line covering offset");
+                assert_eq!(found.offset, line.offset);
+            }
+
+            count += 1;
+        }
+    }
+
+    assert!(count > 0);
+}
+
+#[test]
+fn test_find_locations() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let module = modules.next().expect("parse module").expect("no module");
+    let module_info = pdb
+        .module_info(&module)
+        .expect("parse module info")
+        .expect("module info");
+
+    let line_program = module_info.line_program().expect("line program");
+    let line = line_program
+        .lines()
+        .next()
+        .expect("parse line info")
+        .expect("no lines");
+
+    let locations = line_program
+        .find_locations(line.file_index, line.line_start)
+        .expect("find locations");
+    assert!(locations.contains(&line.offset));
+    assert!(locations
+        .windows(2)
+        .all(|pair| (pair[0].section, pair[0].offset) < (pair[1].section, pair[1].offset)));
+
+    let module_locations = module_info
+        .find_locations(line.file_index, line.line_start)
+        .expect("find module locations");
+    assert!(locations.iter().all(|l| module_locations.contains(l)));
+
+    let missing = line_program
+        .find_locations(line.file_index, u32::MAX)
+        .expect("find locations");
+    assert!(missing.is_empty());
+}
+
+#[test]
+fn test_line_lookup_missing() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let module = modules.next().expect("parse module").expect("no module");
+    let module_info = pdb
+        .module_info(&module)
+        .expect("parse module info")
+        .expect("module info");
+
+    let line_program = module_info.line_program().expect("line program");
+    let offset = PdbInternalSectionOffset::new(0xffff, 0);
+    assert_eq!(line_program.lookup(offset).expect("lookup line"), None);
+}
+
+#[test]
+fn test_line_columns() {
+    let file = std::fs::File::open("fixtures/columns/columns.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let module = modules.next().expect("parse module").expect("no module");
+    let module_info = pdb
+        .module_info(&module)
+        .expect("parse module info")
+        .expect("module info");
+
+    let line_program = module_info.line_program().expect("line program");
+    let lines: Vec<_> = line_program.lines().collect().expect("collect lines");
+
+    let columns: Vec<_> = lines
+        .iter()
+        .map(|line| {
+            (
+                line.offset.offset,
+                line.line_start,
+                line.column_start,
+                line.column_end,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        columns,
+        [
+            (0x10, 3, Some(5), Some(12)),
+            (0x14, 4, Some(5), Some(9)),
+            (0x1a, 4, Some(13), Some(0)),
+        ]
+    );
+}
+
+#[test]
+fn test_cached_line_programs() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+
+    // Line programs outlive their module info, so they can be parsed once and stored.
+    let mut cache = Vec::new();
+    while let Some(module) = modules.next().expect("parse module") {
+        if let Some(module_info) = pdb.module_info(&module).expect("parse module info") {
+            cache.push(module_info.shared_line_program().expect("line program"));
+        }
+    }
+
+    let shared = cache
+        .iter()
+        .find(|p| p.program().lines().count().unwrap_or(0) > 0);
+    let shared = shared.expect("line program with lines");
+    let clone = shared.clone();
+
+    let program = shared.program();
+    let lines: Vec<_> = program.lines().collect().expect("collect lines");
+    let cloned_lines: Vec<_> = clone.program().lines().collect().expect("collect lines");
+    assert_eq!(lines, cloned_lines);
+
+    let files: Vec<_> = clone.program().files().collect().expect("collect files");
+    assert!(!files.is_empty());
+}
+
+#[test]
+fn test_files_with_lines() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+    let string_table = pdb.string_table().expect("string table");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+
+    let mut files_with_code = 0;
+    while let Some(module) = modules.next().expect("parse module") {
+        let module_info = match pdb.module_info(&module).expect("parse module info") {
+            Some(module_info) => module_info,
+            None => continue,
+        };
+
+        let program = module_info.line_program().expect("line program");
+        let files = module_info.files_with_lines().expect("files with lines");
+
+        for file in &files {
+            assert!(!file.ranges.is_empty());
+            assert_eq!(file.file, program.get_file_info(file.index).expect("file"));
+            file.file.resolve_name(&string_table).expect("file name");
+
+            for pair in file.ranges.windows(2) {
+                let (first, second) = (pair[0], pair[1]);
+                assert!(
+                    first.offset.section < second.offset.section
+                        || first.offset.offset + first.length < second.offset.offset
+                );
+            }
+        }
+
+        // Every line record is covered by the ranges of its file.
+        let mut lines = program.lines();
+        while let Some(line) = lines.next().expect("line") {
+            let file = files.iter().find(|f| f.index == line.file_index);
+            let file = file.expect("file of line record");
+            assert!(file
+                .ranges
+                .iter()
+                .any(|range| range.offset.section == line.offset.section
+                    && range.offset.offset <= line.offset.offset
+                    && line.offset.offset <= range.offset.offset + range.length));
+        }
+
+        files_with_code += files.len();
+    }
+
+    assert!(files_with_code > 0);
+}
+
+#[test]
+fn test_procedure_lines() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let dbi = pdb.debug_information().expect("dbi");
+    let mut modules = dbi.modules().expect("modules");
+    let module = modules.next().expect("parse module").expect("no module");
+    let module_info = pdb
+        .module_info(&module)
+        .expect("parse module info")
+        .expect("module info");
+    let line_program = module_info.line_program().expect("line program");
+
+    let procedure = |name: &str| {
+        module_info
+            .symbols()
+            .expect("symbols")
+            .find_map(|symbol| match symbol.parse() {
+                Ok(pdb::SymbolData::Procedure(data)) if data.name.to_string() == name => {
+                    Ok(Some(data))
+                }
+                _ => Ok(None),
+            })
+            .expect("find procedure")
+            .expect("procedure")
+    };
+
+    let regions = |name| {
+        line_program
+            .procedure_lines(&procedure(name))
+            .expect("procedure lines")
+            .into_iter()
+            .map(|line| (line.line.line_start, line.region))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        regions("main"),
+        [
+            (45, LineRegion::Prologue),
+            (46, LineRegion::Body),
+            (47, LineRegion::Body),
+        ]
+    );
+    assert_eq!(
+        regions("printf"),
+        [
+            (949, LineRegion::Prologue),
+            (952, LineRegion::Body),
+            (953, LineRegion::Body),
+            (954, LineRegion::Body),
+            (955, LineRegion::Body),
+            (956, LineRegion::Epilogue),
+        ]
+    );
+
+    let body = line_program
+        .prologue_end(&procedure("main"))
+        .expect("prologue end")
+        .expect("body line");
+    assert_eq!(body.offset, PdbInternalSectionOffset::new(1, 0x556d));
+    assert_eq!(body.length, Some(12));
+    assert_eq!(body.line_start, 46);
+
+    // A corrupt debug start offset past the end of the section does not overflow.
+    let mut corrupt = procedure("main");
+    corrupt.dbg_start_offset = u32::MAX - 1;
+    assert_eq!(
+        line_program.prologue_end(&corrupt).expect("prologue end"),
+        None
+    );
+}
+
+/// Collects exported rows as owned values.
+#[derive(Debug, Default)]
+struct RowCollector {
+    rows: Vec<(Rva, u32, String, u32, Option<String>, usize)>,
+    finished: bool,
+}
+
+impl pdb::LineSink for RowCollector {
+    fn write_row(&mut self, row: &pdb::LineRow<'_>) -> std::io::Result<()> {
+        self.rows.push((
+            row.rva,
+            row.length,
+            row.file.to_string().into_owned(),
+            row.line,
+            row.function.map(|name| name.to_string().into_owned()),
+            row.module,
+        ));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_export_lines() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let mut collector = RowCollector::default();
+    pdb.export_lines(&mut collector).expect("export lines");
+    assert!(collector.finished);
+
+    let rows = collector.rows;
+    assert!(!rows.is_empty());
+    assert!(rows
+        .windows(2)
+        .all(|pair| pair[0].5 < pair[1].5 || pair[0].0 <= pair[1].0));
+    assert!(rows.iter().all(|row| !row.2.is_empty() && row.3 > 0));
+
+    let main = rows
+        .iter()
+        .find(|row| row.4.as_deref() == Some("main"))
+        .expect("lines of main");
+    assert!(main.2.ends_with("foo.cpp"), "{}", main.2);
+
+    let mut sink = pdb::CsvLineSink::new(Vec::new());
+    pdb.export_lines(&mut sink).expect("export csv");
+    let csv = String::from_utf8(sink.into_inner()).expect("utf-8");
+    assert_eq!(csv.lines().count(), rows.len() + 1);
+    assert_eq!(
+        csv.lines().nth(1).expect("first row"),
+        format!(
+            "{},{},{},{},,{},{}",
+            rows[0].0 .0,
+            rows[0].1,
+            rows[0].2,
+            rows[0].3,
+            rows[0].4.as_deref().unwrap_or(""),
+            rows[0].5
+        )
+    );
+}
+
+#[test]
+fn test_export_lines_with_progress() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("parse pdb");
+
+    let mut reports = Vec::new();
+    let mut collector = RowCollector::default();
+    pdb.export_lines_with_progress(&mut collector, |progress| reports.push(progress))
+        .expect("export lines");
+    assert!(collector.finished);
+
+    assert!(reports.windows(2).all(|w| w[0].bytes <= w[1].bytes));
+    let last = reports.last().expect("final progress");
+    assert!(last.records > 0 && last.records <= collector.rows.len());
+    assert!(last.total_bytes > 0);
+    assert_eq!(last.bytes, last.total_bytes);
+}
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+fn open_fixture() -> pdb::PDB<'static, std::fs::File> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    pdb::PDB::open(file).expect("opening pdb")
+}
+
+fn stream_bytes<'s, S>(pdb: &pdb::PDB<'s, S>, index: u16) -> Option<Vec<u8>>
+where
+    S: pdb::Source<'s> + 's,
+{
+    match pdb.raw_stream(pdb::StreamIndex(index)) {
+        Ok(stream) => stream.map(|s| s.as_slice().to_vec()),
+        Err(pdb::Error::StreamNotFound(_)) => None,
+        Err(e) => panic!("reading stream {}: {}", index, e),
+    }
+}
+
+#[test]
+fn test_copy_through() {
+    let mut original = open_fixture();
+
+    let mut output = Cursor::new(Vec::new());
+    original
+        .rewrite(&mut output, &BTreeMap::new())
+        .expect("rewriting pdb");
+
+    assert_eq!(output.get_ref().len() % 4096, 0);
+
+    let rewritten = pdb::PDB::open(output).expect("opening rewritten pdb");
+    for index in 0..300 {
+        assert_eq!(
+            stream_bytes(&original, index),
+            stream_bytes(&rewritten, index),
+            "stream {}",
+            index
+        );
+    }
+
+    let pdb_info = rewritten.pdb_information().expect("pdb information");
+    assert_eq!(pdb_info.age, 2);
+}
+
+#[test]
+fn test_replace_and_add_streams() {
+    let mut original = open_fixture();
+
+    let large = vec![0xab; 3 * 4096 + 17];
+    let mut replacements = BTreeMap::new();
+    replacements.insert(pdb::StreamIndex(208), &b"replaced"[..]);
+    replacements.insert(pdb::StreamIndex(400), large.as_slice());
+
+    let mut output = Cursor::new(Vec::new());
+    original
+        .rewrite(&mut output, &replacements)
+        .expect("rewriting pdb");
+
+    let rewritten = pdb::PDB::open(output).expect("opening rewritten pdb");
+    assert_eq!(
+        stream_bytes(&rewritten, 208).as_deref(),
+        Some(&b"replaced"[..])
+    );
+    assert_eq!(stream_bytes(&rewritten, 400), Some(large));
+    assert_eq!(stream_bytes(&rewritten, 399), None);
+
+    // untouched streams are still readable
+    let type_information = rewritten.type_information().expect("type information");
+    assert!(type_information.len() > 8000);
+}
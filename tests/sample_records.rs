@@ -0,0 +1,80 @@
+use pdb::SampledRecord;
+
+fn open() -> pdb::PDB<'static, std::fs::File> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    pdb::PDB::open(file).expect("opening pdb")
+}
+
+/// Summarizes a sampled record by its stream, module and index.
+fn key(record: &SampledRecord<'_>) -> (u8, usize, u32) {
+    match record {
+        SampledRecord::GlobalSymbol { symbol, .. } => (0, 0, symbol.index().0),
+        SampledRecord::ModuleSymbol { module, symbol, .. } => (1, *module, symbol.index().0),
+        SampledRecord::Type { item, .. } => (2, 0, item.index().0),
+        SampledRecord::Id { item, .. } => (3, 0, item.index().0),
+        _ => panic!("unexpected record {:?}", record),
+    }
+}
+
+#[test]
+fn sample_records() {
+    let pdb = open();
+
+    let mut keys = Vec::new();
+    pdb.sample_records(8, 1, |record| {
+        match &record {
+            SampledRecord::GlobalSymbol { symbol, data } => {
+                assert_eq!(data.as_ref().ok(), symbol.parse().ok().as_ref());
+            }
+            SampledRecord::ModuleSymbol { symbol, data, .. } => {
+                assert_eq!(data.as_ref().ok(), symbol.parse().ok().as_ref());
+            }
+            SampledRecord::Type { item, .. } => {
+                assert_eq!(item.raw_bytes().len(), item.len());
+            }
+            _ => (),
+        }
+        keys.push(key(&record));
+    })
+    .expect("sample records");
+
+    for stream in 0..4 {
+        let count = keys.iter().filter(|&&(s, _, _)| s == stream).count();
+        assert_eq!(count, 8, "stream {}", stream);
+    }
+
+    // Samples are reported in stream order.
+    let mut sorted = keys.clone();
+    sorted.sort_unstable();
+    assert_eq!(keys, sorted);
+
+    let mut again = Vec::new();
+    pdb.sample_records(8, 1, |record| again.push(key(&record)))
+        .expect("sample records");
+    assert_eq!(keys, again);
+
+    let mut other = Vec::new();
+    pdb.sample_records(8, 2, |record| other.push(key(&record)))
+        .expect("sample records");
+    assert_ne!(keys, other);
+}
+
+#[test]
+fn sample_everything() {
+    let pdb = open();
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut types = 0;
+    pdb.sample_records(usize::MAX >> 8, 0, |record| {
+        if let SampledRecord::Type { .. } = record {
+            types += 1;
+        }
+    })
+    .expect("sample records");
+    assert_eq!(types, type_information.len());
+
+    let mut reservoir = pdb::Reservoir::new(0, 0);
+    reservoir.fill(type_information.iter()).expect("fill");
+    assert!(reservoir.samples().is_empty());
+    assert_eq!(reservoir.seen(), type_information.len() as u64);
+}
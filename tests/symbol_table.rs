@@ -15,7 +15,7 @@ where
         )
     };
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let symbol_table = pdb.global_symbols().expect("global symbols");
 
     func(&symbol_table, is_fixture);
@@ -121,3 +121,326 @@ fn find_symbols() {
         }
     })
 }
+
+#[test]
+fn filter_publics() {
+    setup(|global_symbols, _is_fixture| {
+        let all: Vec<_> = global_symbols
+            .publics(pdb::PublicSymbolFilter::default())
+            .collect()
+            .expect("all publics");
+        let functions: Vec<_> = global_symbols
+            .publics(pdb::PublicSymbolFilter::functions())
+            .collect()
+            .expect("function publics");
+        let data: Vec<_> = global_symbols
+            .publics(pdb::PublicSymbolFilter::data())
+            .collect()
+            .expect("data publics");
+
+        assert!(!functions.is_empty());
+        assert!(functions.iter().all(|public| public.function));
+        assert!(data.iter().all(|public| !public.code && !public.function));
+        assert!(functions.len() + data.len() <= all.len());
+        assert_eq!(
+            functions.len(),
+            all.iter().filter(|public| public.function).count()
+        );
+    })
+}
+
+#[test]
+fn resolve_constants() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    let type_information = pdb.type_information().expect("type information");
+    let mut type_finder = type_information.finder();
+    let mut type_iter = type_information.iter();
+    while type_iter.next().expect("next type").is_some() {
+        type_finder.update(&type_iter);
+    }
+
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let mut constants = global_symbols.constants();
+    let mut found = false;
+    while let Some(constant) = constants.next().expect("next constant") {
+        if constant.name.as_bytes() == b"__ISA_AVAILABLE_SSE2" {
+            found = true;
+            assert_eq!(constant.value.to_i64(), Some(1));
+            match constant.type_data(&type_finder).expect("resolve type") {
+                Some(pdb::TypeData::Enumeration(_)) => (),
+                other => panic!("unexpected type {:?}", other),
+            }
+        }
+    }
+
+    assert!(found);
+}
+
+#[test]
+fn search_in_chunks() {
+    setup(|global_symbols, _is_fixture| {
+        let is_procedure = |symbol: &pdb::Symbol<'_>| {
+            matches!(symbol.parse(), Ok(pdb::SymbolData::ProcedureReference(_)))
+        };
+
+        let complete = global_symbols
+            .search(None, &pdb::SearchBudget::default(), is_procedure)
+            .expect("search");
+        assert!(complete.is_complete());
+        assert!(complete.items.len() > 10);
+
+        let budget = pdb::SearchBudget::default().with_max_results(10);
+
+        let mut chunked = Vec::new();
+        let mut continuation = None;
+        loop {
+            let results = global_symbols
+                .search(continuation, &budget, is_procedure)
+                .expect("search");
+            assert!(results.items.len() <= 10);
+            chunked.extend(results.items);
+
+            continuation = results.continuation;
+            if continuation.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(chunked, complete.items);
+
+        let nothing = pdb::SearchBudget::default().with_max_results(0);
+        let results = global_symbols
+            .search(None, &nothing, is_procedure)
+            .expect("search");
+        assert!(results.items.is_empty());
+        assert!(results.is_complete());
+    })
+}
+
+#[test]
+fn search_continuation_mismatch() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let type_information = pdb.type_information().expect("type information");
+
+    let budget = pdb::SearchBudget::default().with_max_bytes(64);
+    let continuation = global_symbols
+        .search(None, &budget, |_| true)
+        .expect("search")
+        .continuation
+        .expect("continuation");
+
+    assert!(matches!(
+        type_information.search(Some(continuation), &budget, |_| true),
+        Err(pdb::Error::IndexMismatch)
+    ));
+    assert!(global_symbols
+        .search(Some(continuation), &budget, |_| true)
+        .is_ok());
+}
+
+#[test]
+fn resume_from_cursor() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let identity = pdb.pdb_information().expect("pdbi").index_identity();
+
+    let mut iter = global_symbols.iter();
+    let mut remaining = Vec::new();
+    let mut checkpoint = None;
+    let mut count = 0;
+    while let Some(symbol) = iter.next().expect("next symbol") {
+        count += 1;
+        if count == 100 {
+            let cursor = pdb::SymbolCursor::global(&global_symbols, iter.position());
+            checkpoint = Some(cursor.to_bytes(identity));
+        } else if count > 100 {
+            remaining.push(symbol.index());
+        }
+    }
+
+    let checkpoint = checkpoint.expect("checkpoint");
+    let cursor = pdb::SymbolCursor::from_bytes(&checkpoint, identity).expect("cursor");
+    assert_eq!(cursor.module, None);
+
+    let resumed: Vec<_> = global_symbols
+        .iter_at_cursor(&cursor)
+        .expect("resume")
+        .map(|symbol| Ok(symbol.index()))
+        .collect()
+        .expect("collect symbols");
+    assert!(!resumed.is_empty());
+    assert_eq!(resumed, remaining);
+}
+
+#[test]
+fn resolve_procedure_references() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let debug_info = pdb.debug_information().expect("dbi");
+    let modules: Vec<_> = debug_info
+        .modules()
+        .expect("modules")
+        .collect()
+        .expect("collect modules");
+
+    let mut infos = HashMap::new();
+    let mut resolved = 0;
+
+    let mut iter = global_symbols.iter();
+    while let Some(symbol) = iter.next().expect("next symbol") {
+        let reference = match symbol.parse() {
+            Ok(pdb::SymbolData::ProcedureReference(reference)) => reference,
+            _ => continue,
+        };
+        let module = reference.module.expect("module index");
+
+        let info = match infos.entry(module) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(
+                pdb.module_info(&modules[module])
+                    .expect("module info")
+                    .expect("module info present"),
+            ),
+        };
+
+        let target = info.symbol_at(reference.symbol_index).expect("symbol");
+        assert_eq!(target.index(), reference.symbol_index);
+        match target.parse().expect("parse target") {
+            pdb::SymbolData::Procedure(procedure) => {
+                assert_eq!(Some(procedure.name), reference.name)
+            }
+            data => panic!("procedure reference resolved to {:?}", data),
+        }
+        resolved += 1;
+    }
+    assert!(resolved > 10);
+
+    let info = infos.values().next().expect("module info");
+    assert!(matches!(
+        info.symbol_at(pdb::SymbolIndex(u32::MAX)),
+        Err(pdb::Error::SymbolNotFound(u32::MAX))
+    ));
+}
+
+#[test]
+fn iterate_globals() {
+    setup(|global_symbols, _is_fixture| {
+        let expected: Vec<_> = global_symbols
+            .iter()
+            .filter_map(|symbol| {
+                Ok(match symbol.parse() {
+                    Ok(pdb::SymbolData::Data(data)) => Some(pdb::GlobalSymbol::Data(data)),
+                    Ok(pdb::SymbolData::Constant(data)) => Some(pdb::GlobalSymbol::Constant(data)),
+                    Ok(pdb::SymbolData::UserDefinedType(data)) => {
+                        Some(pdb::GlobalSymbol::UserDefinedType(data))
+                    }
+                    Ok(pdb::SymbolData::ThreadStorage(data)) => {
+                        Some(pdb::GlobalSymbol::ThreadStorage(data))
+                    }
+                    _ => None,
+                })
+            })
+            .collect()
+            .expect("collect symbols");
+
+        let globals: Vec<_> = global_symbols.globals().collect().expect("collect globals");
+
+        assert!(!globals.is_empty());
+        assert_eq!(globals, expected);
+    })
+}
+
+#[test]
+fn user_defined_types() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let type_information = pdb.type_information().expect("type information");
+    let type_finder = {
+        let mut finder = type_information.finder();
+        let mut iter = type_information.iter();
+        while iter.next().expect("next type").is_some() {
+            finder.update(&iter);
+        }
+        finder
+    };
+
+    let udts = global_symbols
+        .user_defined_types(&type_information)
+        .expect("user defined types");
+    assert!(!udts.is_empty());
+
+    let mut seen = std::collections::HashSet::new();
+    for udt in &udts {
+        let definition = udt.definition.expect("definition");
+        assert!(seen.insert((udt.name, definition)), "duplicate {:?}", udt);
+
+        let data = type_finder.find(definition).expect("find").parse();
+        if let Ok(pdb::TypeData::Class(class)) = data {
+            assert!(!class.properties.forward_reference());
+        }
+    }
+}
+
+#[test]
+fn cursor_identity() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let global_symbols = pdb.global_symbols().expect("global symbols");
+    let identity = pdb.pdb_information().expect("pdbi").index_identity();
+
+    let cursor = pdb::SymbolCursor::global(&global_symbols, pdb::SymbolIndex(0));
+    let bytes = cursor.to_bytes(identity);
+    assert_eq!(
+        pdb::SymbolCursor::from_bytes(&bytes, identity).expect("cursor"),
+        cursor
+    );
+
+    // The cursor is rejected for a different PDB.
+    let other = pdb::PdbIdentity {
+        age: identity.age + 1,
+        ..identity
+    };
+    assert!(matches!(
+        pdb::SymbolCursor::from_bytes(&bytes, other),
+        Err(pdb::Error::IndexMismatch)
+    ));
+
+    // The cursor is rejected for the symbols of a module.
+    let dbi = pdb.debug_information().expect("dbi");
+    let module = dbi.modules().expect("modules").next().expect("module");
+    let info = pdb
+        .module_info(&module.expect("module"))
+        .expect("module info")
+        .expect("module info");
+    assert!(matches!(
+        info.symbols_at_cursor(&cursor),
+        Err(pdb::Error::IndexMismatch)
+    ));
+
+    let cursor = pdb::SymbolCursor::module(0, &info, pdb::SymbolIndex(4));
+    assert!(info.symbols_at_cursor(&cursor).is_ok());
+    assert!(matches!(
+        global_symbols.iter_at_cursor(&cursor),
+        Err(pdb::Error::IndexMismatch)
+    ));
+}
+
+#[test]
+fn search_cancelled() {
+    setup(|global_symbols, _| {
+        let token = pdb::CancellationToken::new();
+        let budget = pdb::SearchBudget::default();
+        let result = global_symbols.search_cancellable(&token, None, &budget, |_| {
+            token.cancel();
+            false
+        });
+        assert!(matches!(result, Err(pdb::Error::Cancelled)));
+    })
+}
@@ -13,7 +13,7 @@ where
     }
     .expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let type_information = pdb.type_information().expect("type information");
 
     func(&type_information);
@@ -37,6 +37,41 @@ fn iteration() {
     });
 }
 
+#[test]
+fn parse_with_scratch() {
+    setup(|type_information| {
+        let mut scratch = pdb::TypeScratch::new();
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            let data = typ.parse_with(&mut scratch);
+            assert_eq!(data.as_ref().ok(), typ.parse().as_ref().ok());
+
+            if let Ok(data) = data {
+                scratch.recycle(data);
+            }
+        }
+    });
+}
+
+#[test]
+fn iterate_with_scratch() {
+    setup(|type_information| {
+        let mut scratch = pdb::TypeScratch::new();
+        let mut types = type_information.iter().with_scratch(&mut scratch);
+        let mut iter = type_information.iter();
+
+        let mut count = 0;
+        while let Some((typ, data)) = types.next().expect("next parsed type") {
+            let expected = iter.next().expect("next type").expect("type");
+            assert_eq!(typ.index(), expected.index());
+            assert_eq!(data.ok(), expected.parse().as_ref().ok());
+            count += 1;
+        }
+
+        assert_eq!(count, type_information.len());
+    });
+}
+
 #[test]
 fn type_finder() {
     setup(|type_information| {
@@ -65,6 +100,66 @@ fn type_finder() {
     })
 }
 
+#[test]
+fn type_finder_with_progress() {
+    setup(|type_information| {
+        let mut reports = Vec::new();
+        let type_finder = type_information
+            .finder_with_progress(|progress| reports.push(progress))
+            .expect("index types");
+
+        let last = reports.last().expect("final progress");
+        assert_eq!(last.records, type_information.len());
+        assert_eq!(last.bytes, last.total_bytes);
+        assert!(reports.windows(2).all(|w| w[0].bytes <= w[1].bytes));
+
+        let max_index = pdb::TypeIndex(0x1000 + type_information.len() as u32 - 1);
+        assert!(type_finder.find(max_index).is_ok());
+    })
+}
+
+#[test]
+fn type_finder_cancelled() {
+    setup(|type_information| {
+        let token = pdb::CancellationToken::new();
+        let mut reports = 0;
+        let result = type_information.finder_cancellable(&token, |_| {
+            reports += 1;
+            token.cancel();
+        });
+
+        // cancellation is observed after the first progress report
+        assert!(matches!(result, Err(pdb::Error::Cancelled)));
+        assert_eq!(reports, 1);
+    })
+}
+
+#[test]
+fn type_search_cancelled() {
+    setup(|type_information| {
+        let token = pdb::CancellationToken::new();
+        let budget = pdb::SearchBudget::default();
+        let result = type_information.search_cancellable(&token, None, &budget, |_| {
+            token.cancel();
+            true
+        });
+        assert!(matches!(result, Err(pdb::Error::Cancelled)));
+
+        let token = pdb::CancellationToken::new();
+        let mut matches = type_information.search_names_cancellable("*", &token);
+        assert!(matches.next().expect("first match").is_some());
+        token.cancel();
+        assert!(matches!(matches.next(), Err(pdb::Error::Cancelled)));
+
+        // a cancelled scan does not leave a partial index of definitions behind
+        let result = type_information.find_by_name_cancellable("NoSuchType", &token);
+        assert!(matches!(result, Err(pdb::Error::Cancelled)));
+        let token = pdb::CancellationToken::new();
+        let result = type_information.find_by_name_cancellable("NoSuchType", &token);
+        assert_eq!(result.expect("find by name"), None);
+    })
+}
+
 #[test]
 fn find_classes() {
     setup(|type_information| {
@@ -210,3 +305,684 @@ fn type_length_histogram() {
     })
 }
 */
+
+#[test]
+fn search_in_chunks() {
+    setup(|type_information| {
+        let budget = pdb::SearchBudget::default().with_max_bytes(4096);
+
+        let mut chunks = 0;
+        let mut indexes = Vec::new();
+        let mut continuation = None;
+        loop {
+            let results = type_information
+                .search(continuation, &budget, |_| true)
+                .expect("search");
+            indexes.extend(results.items.iter().map(|item| item.index()));
+            chunks += 1;
+
+            continuation = results.continuation;
+            if continuation.is_none() {
+                break;
+            }
+        }
+
+        assert!(chunks > 1);
+        assert_eq!(indexes.len(), type_information.len());
+        assert!(indexes.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+    })
+}
+
+#[test]
+fn type_finder_serialization() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let identity = pdb
+        .pdb_information()
+        .expect("pdb information")
+        .index_identity();
+    let type_information = pdb.type_information().expect("type information");
+
+    let type_finder = type_information
+        .finder_with_progress(|_| ())
+        .expect("index types");
+    let data = type_finder.to_bytes(identity);
+    assert_eq!(data, type_finder.to_bytes(identity));
+
+    let loaded = type_information
+        .finder_from_bytes(&data, identity)
+        .expect("load type finder");
+    assert_eq!(loaded.max_index(), type_finder.max_index());
+
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        let found = loaded.find(typ.index()).expect("find type");
+        assert_eq!(found, typ);
+    }
+
+    let stale = pdb::PdbIdentity {
+        age: identity.age + 1,
+        ..identity
+    };
+    assert!(matches!(
+        type_information.finder_from_bytes(&data, stale),
+        Err(pdb::Error::IndexMismatch)
+    ));
+
+    // a finder of the type stream cannot be loaded for the id stream
+    let id_information = pdb.id_information().expect("id information");
+    assert!(matches!(
+        id_information.finder_from_bytes(&data, identity),
+        Err(pdb::Error::InvalidIndex)
+    ));
+}
+
+#[test]
+fn type_statistics() {
+    setup(|type_information| {
+        let statistics = type_information.statistics(5).expect("statistics");
+        assert_eq!(statistics.count(), type_information.len());
+
+        let field_lists = statistics.kinds[&0x1203];
+        assert!(field_lists.count > 0);
+        assert_eq!(statistics.largest_field_lists.len(), 5);
+        assert!(statistics
+            .largest_field_lists
+            .windows(2)
+            .all(|pair| pair[0].bytes >= pair[1].bytes));
+
+        let largest = statistics.largest_field_lists[0];
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if typ.raw_kind() == 0x1203 {
+                assert!(typ.len() + 2 <= largest.bytes);
+            }
+        }
+
+        assert!(!statistics.deepest_templates.is_empty());
+        assert!(statistics
+            .deepest_templates
+            .windows(2)
+            .all(|pair| pair[0].depth >= pair[1].depth));
+    })
+}
+
+#[test]
+fn type_layouts() {
+    setup(|type_information| {
+        let layouts = type_information.layouts().expect("layouts");
+
+        let mut forward_reference = None;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+                if class.name == "_UNICODE_STRING".into() && class.properties.forward_reference() {
+                    forward_reference = Some(typ.index());
+                    break;
+                }
+            }
+        }
+
+        // The forward reference resolves to the definition.
+        let forward_reference = forward_reference.expect("_UNICODE_STRING");
+        let layout = layouts.layout(forward_reference).expect("layout");
+        assert_ne!(layout.index, forward_reference);
+        assert_eq!(layout.name, "_UNICODE_STRING".into());
+        assert_eq!(layout.size, 16);
+        assert_eq!(layout.alignment, 8);
+
+        let fields: Vec<_> = layout
+            .fields
+            .iter()
+            .map(|field| (field.name.expect("name"), field.offset, field.size))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("Length".into(), 0, 2),
+                ("MaximumLength".into(), 2, 2),
+                ("Buffer".into(), 8, 8),
+            ]
+        );
+
+        assert_eq!(layout.holes, [pdb::PaddingHole { offset: 4, size: 4 }]);
+        assert_eq!(layout.padding(), 4);
+
+        assert!(matches!(
+            layouts.layout(pdb::TypeIndex(0x74)),
+            Err(pdb::Error::NotAClassType(0x74))
+        ));
+    });
+}
+
+#[test]
+fn type_sizes() {
+    setup(|type_information| {
+        let layouts = type_information.layouts().expect("layouts");
+
+        let mut unicode_string = None;
+        let mut arrays = 0;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            match typ.parse() {
+                Ok(pdb::TypeData::Class(class)) if class.name == "_UNICODE_STRING".into() => {
+                    unicode_string.get_or_insert(typ.index());
+                }
+                Ok(pdb::TypeData::Pointer(pointer)) => {
+                    let size = layouts.size_of(typ.index()).expect("pointer size");
+                    assert_eq!(size, pointer.attributes.size().into());
+                }
+                Ok(pdb::TypeData::Modifier(modifier)) => {
+                    // Modifiers of incomplete types are incomplete as well.
+                    let size = layouts.size_of(typ.index()).ok();
+                    assert_eq!(size, layouts.size_of(modifier.underlying_type).ok());
+                }
+                Ok(pdb::TypeData::Array(array)) => {
+                    let size = layouts.size_of(typ.index()).expect("array size");
+                    assert_eq!(
+                        size,
+                        u64::from(*array.dimensions.last().expect("dimension"))
+                    );
+                    arrays += 1;
+                }
+                _ => (),
+            }
+        }
+
+        assert!(arrays > 0);
+
+        // The forward reference resolves to the definition.
+        let unicode_string = unicode_string.expect("_UNICODE_STRING");
+        assert_eq!(layouts.size_of(unicode_string).expect("size"), 16);
+        assert_eq!(type_information.size_of(unicode_string).expect("size"), 16);
+
+        // int, wchar_t *, void
+        assert_eq!(
+            type_information.size_of(pdb::TypeIndex(0x74)).expect("int"),
+            4
+        );
+        assert_eq!(
+            type_information
+                .size_of(pdb::TypeIndex(0x671))
+                .expect("ptr"),
+            8
+        );
+        assert_eq!(
+            type_information
+                .size_of(pdb::TypeIndex(0x03))
+                .expect("void"),
+            0
+        );
+    });
+}
+
+#[test]
+fn bitfield_units() {
+    setup(|type_information| {
+        let layouts = type_information.layouts().expect("layouts");
+
+        let mut slist_header = None;
+        let mut dname = None;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+                if class.properties.forward_reference() {
+                    continue;
+                }
+                if class.name == "_SLIST_HEADER::<unnamed-type-HeaderX64>".into() {
+                    slist_header = Some(typ.index());
+                } else if class.name == "DName".into() {
+                    dname = Some(typ.index());
+                }
+            }
+        }
+
+        // Two 64-bit storage units, each filled completely.
+        let layout = layouts
+            .layout(slist_header.expect("_SLIST_HEADER"))
+            .expect("layout");
+        let units = layout.bitfield_units();
+        let summary: Vec<_> = units
+            .iter()
+            .map(|unit| {
+                (
+                    unit.offset,
+                    unit.size,
+                    unit.members.len(),
+                    unit.unused_bits(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, [(0, 8, 2, 0), (8, 8, 2, 0)]);
+
+        let members: Vec<_> = units
+            .iter()
+            .flat_map(|unit| &unit.members)
+            .map(|member| (member.name, member.bit_offset, member.bit_width))
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("Depth".into(), 0, 16),
+                ("Sequence".into(), 16, 48),
+                ("Reserved".into(), 0, 4),
+                ("NextEntry".into(), 4, 60),
+            ]
+        );
+
+        let next_entry = &units[1].members[1];
+        assert_eq!(next_entry.byte_offset, 8);
+        assert_eq!(next_entry.absolute_bit_offset(), 68);
+        assert_eq!(next_entry.mask(), !0xf);
+
+        // Flags following a pointer share a single 32-bit unit, leaving the upper bits unused.
+        let layout = layouts.layout(dname.expect("DName")).expect("layout");
+        let units = layout.bitfield_units();
+        assert_eq!(units.len(), 1);
+        assert_eq!((units[0].offset, units[0].size), (8, 4));
+        assert_eq!(units[0].members.len(), 9);
+        assert_eq!(units[0].unused_bits(), 16);
+    });
+}
+
+#[test]
+fn class_hierarchy() {
+    setup(|type_information| {
+        let hierarchy = type_information.class_hierarchy().expect("class hierarchy");
+
+        let mut definitions = HashMap::new();
+        let mut forward_reference = None;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+                let name = class.name.to_string().into_owned();
+                if !class.properties.forward_reference() {
+                    definitions.insert(typ.index(), name);
+                } else if name == "std::exception" {
+                    forward_reference = Some(typ.index());
+                }
+            }
+        }
+
+        let find = |name: &str| {
+            *definitions
+                .iter()
+                .find(|(_, n)| n.as_str() == name)
+                .expect(name)
+                .0
+        };
+        let names = |indexes: &[pdb::TypeIndex]| -> Vec<&str> {
+            indexes.iter().map(|i| definitions[i].as_str()).collect()
+        };
+
+        let exception = find("std::exception");
+        let bad_alloc = find("std::bad_alloc");
+        assert!(hierarchy.bases_of(exception).expect("bases").is_empty());
+
+        let bases = hierarchy
+            .bases_of(find("std::bad_array_new_length"))
+            .expect("bases");
+        assert_eq!(bases.len(), 1);
+        assert_eq!(bases[0].index, bad_alloc);
+        assert!(!bases[0].is_virtual);
+        assert_eq!(bases[0].offset, Some(0));
+
+        // Forward references resolve to the same class.
+        let forward_reference = forward_reference.expect("forward reference");
+        assert_eq!(
+            hierarchy.derived_from(forward_reference),
+            hierarchy.derived_from(exception)
+        );
+
+        assert_eq!(
+            names(hierarchy.derived_from(exception)),
+            [
+                "std::bad_cast",
+                "std::bad_exception",
+                "std::bad_alloc",
+                "std::bad_typeid",
+            ]
+        );
+        assert_eq!(
+            names(&hierarchy.all_derived_from(exception)),
+            [
+                "std::bad_cast",
+                "std::bad_exception",
+                "std::bad_alloc",
+                "std::bad_typeid",
+                "std::bad_array_new_length",
+                "std::__non_rtti_object",
+            ]
+        );
+        assert!(hierarchy
+            .all_derived_from(find("std::bad_array_new_length"))
+            .is_empty());
+
+        assert!(matches!(
+            hierarchy.bases_of(pdb::TypeIndex(0x74)),
+            Err(pdb::Error::NotAClassType(0x74))
+        ));
+    });
+}
+
+#[test]
+fn resolve_definition() {
+    setup(|type_information| {
+        let mut finder = type_information.finder();
+        let mut classes = Vec::new();
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            finder.update(&iter);
+            if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+                classes.push((typ.index(), class));
+            }
+        }
+
+        let mut forward_references = 0;
+        for (index, class) in classes {
+            let definition = type_information
+                .resolve_definition(index)
+                .expect("resolve definition");
+            if !class.properties.forward_reference() {
+                assert_eq!(definition, Some(index));
+                continue;
+            }
+
+            forward_references += 1;
+            let definition = match definition {
+                Some(definition) => definition,
+                None => continue,
+            };
+            match finder.find(definition).expect("find").parse() {
+                Ok(pdb::TypeData::Class(defined)) => {
+                    assert_eq!(defined.name, class.name);
+                    assert!(!defined.properties.forward_reference());
+                }
+                other => panic!("unexpected definition {:?}", other),
+            }
+
+            if class.name == "std::exception".into() {
+                assert_eq!(definition, pdb::TypeIndex(0x1212));
+            }
+        }
+
+        assert!(forward_references > 0);
+        assert_eq!(
+            type_information
+                .resolve_definition(pdb::TypeIndex(0x74))
+                .expect("primitive"),
+            Some(pdb::TypeIndex(0x74))
+        );
+    });
+}
+
+#[test]
+fn resolve_definition_without_hashes() {
+    setup(|type_information| {
+        assert!(type_information.hashes().is_some());
+
+        let mut records = Vec::new();
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            let mut record = (typ.raw_bytes().len() as u16).to_le_bytes().to_vec();
+            record.extend_from_slice(typ.raw_bytes());
+            records.push(record);
+        }
+
+        // Resolving through the hash stream must agree with scanning the whole stream.
+        with_types(&records, |scanned| {
+            assert!(scanned.hashes().is_none());
+            for index in 0x1000..0x1000 + records.len() as u32 {
+                let index = pdb::TypeIndex(index);
+                assert_eq!(
+                    type_information.resolve_definition(index).expect("hashed"),
+                    scanned.resolve_definition(index).expect("scanned"),
+                    "{}",
+                    index
+                );
+            }
+        });
+    });
+}
+
+#[test]
+fn find_by_name() {
+    setup(|type_information| {
+        let exception = pdb::TypeIndex(0x1212);
+        assert_eq!(
+            type_information
+                .find_by_name("std::exception")
+                .expect("find"),
+            Some(exception)
+        );
+
+        let finder = {
+            let mut finder = type_information.finder();
+            let mut iter = type_information.iter();
+            while iter.next().expect("next type").is_some() {
+                finder.update(&iter);
+            }
+            finder
+        };
+        let unique_name = match finder.find(exception).expect("find").parse() {
+            Ok(pdb::TypeData::Class(class)) => class.unique_name.expect("unique name"),
+            other => panic!("unexpected type {:?}", other),
+        };
+        assert_eq!(
+            type_information
+                .find_by_name(&unique_name.to_string())
+                .expect("find"),
+            Some(exception)
+        );
+
+        assert_eq!(
+            type_information
+                .find_by_name("std::nonexistent")
+                .expect("find"),
+            None
+        );
+    });
+}
+
+#[test]
+fn search_names() {
+    setup(|type_information| {
+        let mut names = Vec::new();
+        let mut matches = type_information.search_names("std::*exception");
+        while let Some(typ) = matches.next().expect("next match") {
+            let name = typ.parse().expect("parse").name().expect("name");
+            names.push((typ.index(), name.to_string().into_owned()));
+        }
+
+        assert!(names.contains(&(pdb::TypeIndex(0x1212), "std::exception".to_owned())));
+        assert!(names.iter().all(|(_, name)| name.ends_with("exception")));
+        assert!(names.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let count = type_information
+            .search_names("*")
+            .count()
+            .expect("count all");
+        let definitions = type_information
+            .search_names("std::exception")
+            .count()
+            .expect("count exact");
+        assert!(count > names.len());
+        assert_eq!(definitions, 1);
+    });
+}
+
+#[test]
+fn hash_stream() {
+    setup(|type_information| {
+        let hashes = type_information.hashes().expect("hash stream");
+        assert_eq!(hashes.bucket_count(), 0x3ffff);
+
+        let exception = pdb::TypeIndex(0x1212);
+        let hash = hashes.hash_name(b"std::exception");
+        assert_eq!(hashes.hash(exception), Some(hash));
+        assert!(hashes.items_with_hash(hash).any(|index| index == exception));
+
+        let hashed_finder = type_information
+            .finder_from_hashes()
+            .expect("hashed finder");
+        assert_eq!(
+            hashed_finder.max_index(),
+            pdb::TypeIndex(0x1000 + type_information.len() as u32 - 1)
+        );
+
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            assert_eq!(hashed_finder.find(typ.index()).expect("find"), typ);
+        }
+    });
+}
+
+#[test]
+fn missing_hash_stream() {
+    let record = type_record(0x1201, &[0; 4]); // empty argument list
+    with_types(&[record], |type_information| {
+        assert!(type_information.hashes().is_none());
+        assert!(type_information.finder_from_hashes().is_none());
+    });
+}
+
+#[test]
+fn damaged_hash_stream() {
+    let record = type_record(0x1201, &[0; 4]); // empty argument list
+
+    // Refer to the hash stream of the fixture, which holds more hash values than items.
+    let mut hash_header = Vec::new();
+    hash_header.extend_from_slice(&206u16.to_le_bytes());
+    hash_header.extend_from_slice(&0xffffu16.to_le_bytes());
+    for value in [4u32, 0x3ffff, 0, 33624, 0, 0, 0, 0] {
+        hash_header.extend_from_slice(&value.to_le_bytes());
+    }
+
+    with_hashed_types(&[record], &hash_header, |type_information| {
+        assert!(type_information.hashes().is_none());
+        assert!(type_information.finder_from_hashes().is_none());
+        assert_eq!(type_information.iter().count().expect("count"), 1);
+    });
+}
+
+/// Builds a type record with the given leaf kind, padded to four bytes.
+fn type_record(kind: u16, data: &[u8]) -> Vec<u8> {
+    let mut record = kind.to_le_bytes().to_vec();
+    record.extend_from_slice(data);
+    let padding = (4 - (record.len() + 2) % 4) % 4;
+    for remaining in (1..=padding).rev() {
+        record.push(0xf0 | remaining as u8);
+    }
+
+    let mut bytes = (record.len() as u16).to_le_bytes().to_vec();
+    bytes.extend(record);
+    bytes
+}
+
+/// Replaces the type stream of the fixture with the given records, starting at index `0x1000`.
+fn with_types<F>(records: &[Vec<u8>], func: F)
+where
+    F: FnOnce(&pdb::TypeInformation<'_>),
+{
+    let mut hash_header = vec![0xff; 4]; // no hash streams
+    hash_header.extend_from_slice(&[0; 32]);
+    with_hashed_types(records, &hash_header, func);
+}
+
+/// Like `with_types`, but with the given hash stream fields of the header.
+fn with_hashed_types<F>(records: &[Vec<u8>], hash_header: &[u8], func: F)
+where
+    F: FnOnce(&pdb::TypeInformation<'_>),
+{
+    let count = records.len() as u32;
+    let records = records.concat();
+    let mut stream = Vec::new();
+    for value in [
+        20_040_203u32,
+        56,
+        0x1000,
+        0x1000 + count,
+        records.len() as u32,
+    ] {
+        stream.extend_from_slice(&value.to_le_bytes());
+    }
+    stream.extend_from_slice(hash_header);
+    stream.extend(records);
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let mut replacements = std::collections::BTreeMap::new();
+    replacements.insert(pdb::StreamIndex(2), &stream[..]);
+
+    let mut output = std::io::Cursor::new(Vec::new());
+    pdb.rewrite(&mut output, &replacements).expect("rewrite");
+    let pdb = pdb::PDB::open(output).expect("opening rewritten pdb");
+    func(&pdb.type_information().expect("type information"));
+}
+
+#[test]
+fn cyclic_types() {
+    let modifier = |underlying: u32| {
+        let mut data = underlying.to_le_bytes().to_vec();
+        data.extend_from_slice(&[1, 0]); // const
+        type_record(0x1001, &data)
+    };
+    let structure = |fields: u32, name: &str| {
+        let mut data = vec![1, 0, 0, 0]; // one member, no properties
+        data.extend_from_slice(&fields.to_le_bytes());
+        data.extend_from_slice(&[0; 8]); // derived, vshape
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.push(0);
+        type_record(0x1505, &data)
+    };
+
+    let mut member = 0x150du16.to_le_bytes().to_vec();
+    member.extend_from_slice(&[3, 0]); // public
+    member.extend_from_slice(&0x1002u32.to_le_bytes());
+    member.extend_from_slice(&[0, 0, b'x', 0]);
+    let mut continuation = 0x1404u16.to_le_bytes().to_vec();
+    continuation.extend_from_slice(&0x1006u32.to_le_bytes());
+
+    let records = [
+        modifier(0x1000),                   // 0x1000: refers to itself
+        type_record(0x1203, &member),       // 0x1001: member of type 0x1002
+        structure(0x1001, "Recursive"),     // 0x1002: contains itself
+        modifier(0x1004),                   // 0x1003
+        modifier(0x1005),                   // 0x1004
+        modifier(0x74),                     // 0x1005: const int
+        type_record(0x1203, &continuation), // 0x1006: continues with itself
+        structure(0x1006, "Continued"),     // 0x1007
+    ];
+
+    with_types(&records, |type_information| {
+        let layouts = type_information.layouts().expect("layouts");
+
+        assert!(matches!(
+            layouts.size_of(pdb::TypeIndex(0x1000)),
+            Err(pdb::Error::CyclicTypeReference(0x1000))
+        ));
+        assert!(matches!(
+            layouts.layout(pdb::TypeIndex(0x1002)),
+            Err(pdb::Error::CyclicTypeReference(0x1002))
+        ));
+        assert!(matches!(
+            layouts.layout(pdb::TypeIndex(0x1007)),
+            Err(pdb::Error::CyclicTypeReference(0x1006))
+        ));
+
+        assert_eq!(layouts.size_of(pdb::TypeIndex(0x1003)).expect("size"), 4);
+        let layouts = layouts.max_depth(2);
+        assert!(matches!(
+            layouts.size_of(pdb::TypeIndex(0x1003)),
+            Err(pdb::Error::TypeDepthExceeded(0x1005))
+        ));
+
+        let hierarchy = type_information.class_hierarchy().expect("hierarchy");
+        assert!(matches!(
+            hierarchy.bases_of(pdb::TypeIndex(0x1007)),
+            Err(pdb::Error::CyclicTypeReference(0x1006))
+        ));
+        assert!(hierarchy.derived_from(pdb::TypeIndex(0x1002)).is_empty());
+    });
+}
@@ -0,0 +1,147 @@
+use pdb::{FileChecksum, ImageStatus, SourceStatus};
+
+fn open() -> pdb::PDB<'static, std::fs::File> {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    pdb::PDB::open(file).expect("opening pdb")
+}
+
+/// Returns the digest stored in a checksum.
+fn digest(checksum: &FileChecksum<'_>) -> Option<Vec<u8>> {
+    match *checksum {
+        FileChecksum::None => None,
+        FileChecksum::Md5(digest) | FileChecksum::Sha1(digest) | FileChecksum::Sha256(digest) => {
+            Some(digest.to_vec())
+        }
+    }
+}
+
+#[test]
+fn verify_image_and_sources() {
+    let pdb = open();
+    let image = std::fs::read("fixtures/self/foo.exe").expect("reading image");
+
+    let mut resolved = Vec::new();
+    let report = pdb
+        .verify(Some(&image), |path, checksum| {
+            resolved.push(path.to_owned());
+            if path.ends_with("foo.cpp") {
+                digest(checksum)
+            } else if path.ends_with("stdio.h") {
+                Some(vec![0; 16])
+            } else {
+                None
+            }
+        })
+        .expect("verify");
+
+    assert_eq!(report.identity, pdb.image_identity().expect("identity"));
+    assert_eq!(report.image, Some(ImageStatus::Matches));
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    // Every path is resolved exactly once.
+    let mut unique = resolved.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(resolved.len(), unique.len());
+    assert_eq!(resolved.len(), report.sources.len());
+
+    let status = |suffix: &str| {
+        report
+            .sources
+            .iter()
+            .find(|source| source.path.ends_with(suffix))
+            .map(|source| source.status)
+    };
+    assert_eq!(status("\\foo.cpp"), Some(SourceStatus::Matches));
+    assert_eq!(status("\\stdio.h"), Some(SourceStatus::Mismatch));
+    assert_eq!(status("\\vadefs.h"), Some(SourceStatus::Missing));
+    assert!(!report.is_valid());
+
+    let missing = report.sources_with_status(SourceStatus::Missing).count();
+    let found = report
+        .sources
+        .iter()
+        .filter(|source| source.path.ends_with("foo.cpp") || source.path.ends_with("stdio.h"))
+        .count();
+    assert_eq!(missing, report.sources.len() - found);
+}
+
+#[test]
+fn verify_without_image() {
+    let pdb = open();
+    let report = pdb
+        .verify(None, |_, checksum| digest(checksum))
+        .expect("verify");
+
+    assert_eq!(report.image, None);
+    assert!(report.is_valid());
+    assert!(report
+        .sources
+        .iter()
+        .all(|source| source.status == SourceStatus::Matches));
+}
+
+#[test]
+fn verify_mismatched_image() {
+    let pdb = open();
+    let mut image = std::fs::read("fixtures/self/foo.exe").expect("reading image");
+
+    // Corrupt the GUID of the CodeView record.
+    let offset = image
+        .windows(4)
+        .position(|window| window == b"RSDS")
+        .expect("CodeView record");
+    image[offset + 4] ^= 0xff;
+
+    let report = pdb.verify(Some(&image), |_, _| None).expect("verify");
+    assert!(matches!(report.image, Some(ImageStatus::Mismatch(_))));
+    assert!(!report.is_valid());
+
+    // Without a CodeView record, the image does not reference any PDB.
+    image[offset] = b'X';
+    let report = pdb.verify(Some(&image), |_, _| None).expect("verify");
+    assert_eq!(report.image, Some(ImageStatus::MissingCodeView));
+}
+
+#[test]
+fn verify_with_progress() {
+    let pdb = open();
+    let mut reports = Vec::new();
+    let report = pdb
+        .verify_with_progress(
+            None,
+            |_, checksum| digest(checksum),
+            |progress| reports.push(progress),
+        )
+        .expect("verify");
+    assert!(report.is_valid());
+
+    // The type stream of the fixture alone has more records than the reporting interval.
+    assert!(reports.len() > 1);
+    assert!(reports.windows(2).all(|w| w[0].bytes <= w[1].bytes));
+    assert!(reports.windows(2).all(|w| w[0].records <= w[1].records));
+
+    let last = reports.last().expect("final progress");
+    assert!(last.total_bytes > 0);
+    assert_eq!(last.bytes, last.total_bytes);
+}
+
+#[test]
+fn verify_cancelled() {
+    let pdb = open();
+    let token = pdb::CancellationToken::new();
+    let mut reports = 0;
+    let result = pdb.verify_cancellable(
+        &token,
+        None,
+        |_, _| None,
+        |_| {
+            reports += 1;
+            token.cancel();
+        },
+    );
+
+    // cancellation is observed after the first progress report, not recorded as an error
+    assert!(matches!(result, Err(pdb::Error::Cancelled)));
+    assert_eq!(reports, 1);
+}